@@ -0,0 +1,184 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::linalg::{Matrix3, Vector3, Vector4};
+use nalgebra::{Quaternion, UnitQuaternion};
+use serde_derive::{Deserialize, Serialize};
+use std::fmt;
+
+/// The attitude of a spacecraft: its orientation with respect to the inertial frame of its orbit, and its angular velocity.
+///
+/// The quaternion rotates vectors from the body frame into the inertial frame, i.e. it is also the orientation of the body axes in the inertial frame.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(from = "AttitudeRepr", into = "AttitudeRepr")]
+pub struct Attitude {
+    /// Rotation from the body frame to the inertial frame
+    pub q: UnitQuaternion<f64>,
+    /// Angular velocity of the body with respect to the inertial frame, expressed in the body frame, in rad/s
+    pub omega_rad_s: Vector3<f64>,
+}
+
+impl Attitude {
+    pub fn new(q: UnitQuaternion<f64>, omega_rad_s: Vector3<f64>) -> Self {
+        Self { q, omega_rad_s }
+    }
+
+    /// Body axes aligned with the inertial axes, without any rotation
+    pub fn identity() -> Self {
+        Self::new(UnitQuaternion::identity(), Vector3::zeros())
+    }
+
+    /// Initializes an attitude from the DCM whose columns are the body axes expressed in the inertial frame
+    pub fn from_dcm(dcm_body_to_inertial: &Matrix3<f64>, omega_rad_s: Vector3<f64>) -> Self {
+        Self::new(
+            UnitQuaternion::from_matrix(dcm_body_to_inertial),
+            omega_rad_s,
+        )
+    }
+
+    /// Initializes an attitude from the modified Rodrigues parameters of the body with respect to the inertial frame
+    pub fn from_mrp(sigma: Vector3<f64>, omega_rad_s: Vector3<f64>) -> Self {
+        let sigma_sq = sigma.norm_squared();
+        let w = (1.0 - sigma_sq) / (1.0 + sigma_sq);
+        let v = 2.0 * sigma / (1.0 + sigma_sq);
+        Self::new(
+            UnitQuaternion::from_quaternion(Quaternion::new(w, v[0], v[1], v[2])),
+            omega_rad_s,
+        )
+    }
+
+    /// Returns the modified Rodrigues parameters of this attitude, switching to the shadow set to ensure that their norm is at most one
+    pub fn mrp(&self) -> Vector3<f64> {
+        let q = if self.q.w < 0.0 {
+            -self.q.into_inner()
+        } else {
+            self.q.into_inner()
+        };
+        q.imag() / (1.0 + q.w)
+    }
+
+    /// Returns the quaternion as [w, x, y, z], i.e. scalar first
+    pub fn quaternion(&self) -> Vector4<f64> {
+        Vector4::new(self.q.w, self.q.i, self.q.j, self.q.k)
+    }
+
+    /// Returns the DCM whose columns are the body axes expressed in the inertial frame
+    pub fn dcm_body_to_inertial(&self) -> Matrix3<f64> {
+        self.q.to_rotation_matrix().into_inner()
+    }
+
+    /// Rotates a vector expressed in the body frame into the inertial frame
+    pub fn body_to_inertial(&self, vector: &Vector3<f64>) -> Vector3<f64> {
+        self.q * vector
+    }
+
+    /// Rotates a vector expressed in the inertial frame into the body frame
+    pub fn inertial_to_body(&self, vector: &Vector3<f64>) -> Vector3<f64> {
+        self.q.inverse_transform_vector(vector)
+    }
+
+    /// Time derivative of the quaternion as [w, x, y, z], from the angular velocity: q_dot = 1/2 q * (0, omega)
+    pub fn quaternion_rate(&self) -> Vector4<f64> {
+        let omega = Quaternion::from_imag(self.omega_rad_s);
+        let q_dot = self.q.into_inner() * omega * 0.5;
+        Vector4::new(q_dot.w, q_dot.i, q_dot.j, q_dot.k)
+    }
+
+    /// Rebuilds an attitude from a scalar first quaternion, which is normalized, and the angular velocity
+    pub(crate) fn from_components(quaternion: &[f64], omega_rad_s: &[f64]) -> Self {
+        Self::new(
+            UnitQuaternion::from_quaternion(Quaternion::new(
+                quaternion[0],
+                quaternion[1],
+                quaternion[2],
+                quaternion[3],
+            )),
+            Vector3::from_column_slice(omega_rad_s),
+        )
+    }
+}
+
+impl Default for Attitude {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl fmt::Display for Attitude {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let q = self.quaternion();
+        write!(
+            f,
+            "q = [{:.6}, {:.6}, {:.6}, {:.6}]  omega = [{:.6e}, {:.6e}, {:.6e}] rad/s",
+            q[0], q[1], q[2], q[3], self.omega_rad_s[0], self.omega_rad_s[1], self.omega_rad_s[2]
+        )
+    }
+}
+
+/// Serialized representation of the attitude, with a scalar first quaternion
+#[derive(Serialize, Deserialize)]
+struct AttitudeRepr {
+    quaternion: [f64; 4],
+    omega_rad_s: [f64; 3],
+}
+
+impl From<AttitudeRepr> for Attitude {
+    fn from(repr: AttitudeRepr) -> Self {
+        Self::from_components(&repr.quaternion, &repr.omega_rad_s)
+    }
+}
+
+impl From<Attitude> for AttitudeRepr {
+    fn from(attitude: Attitude) -> Self {
+        let q = attitude.quaternion();
+        Self {
+            quaternion: [q[0], q[1], q[2], q[3]],
+            omega_rad_s: [
+                attitude.omega_rad_s[0],
+                attitude.omega_rad_s[1],
+                attitude.omega_rad_s[2],
+            ],
+        }
+    }
+}
+
+#[test]
+fn attitude_mrp_round_trip() {
+    use std::f64::consts::PI;
+
+    let axis = Vector3::new(1.0, -2.0, 0.5).normalize();
+    for angle_deg in [0.0, 45.0, 179.0, 250.0] {
+        let q = UnitQuaternion::from_scaled_axis(axis * (angle_deg * PI / 180.0));
+        let attitude = Attitude::new(q, Vector3::zeros());
+        let sigma = attitude.mrp();
+        assert!(sigma.norm() <= 1.0);
+        let back = Attitude::from_mrp(sigma, Vector3::zeros());
+        // The shadow set describes the same rotation
+        assert!(back.q.angle_to(&q) < 1e-12, "{angle_deg}");
+    }
+
+    // Rotating the X axis by 90 degrees about Z yields the Y axis
+    let attitude = Attitude::new(
+        UnitQuaternion::from_scaled_axis(Vector3::z() * PI / 2.0),
+        Vector3::zeros(),
+    );
+    let y = attitude.body_to_inertial(&Vector3::x());
+    assert!((y - Vector3::y()).norm() < 1e-15);
+    assert!((attitude.inertial_to_body(&y) - Vector3::x()).norm() < 1e-15);
+    assert!((attitude.dcm_body_to_inertial() * Vector3::x() - y).norm() < 1e-15);
+}
@@ -16,13 +16,16 @@
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
+use super::{Cosm, Frame};
 use crate::NyxError;
 #[cfg(feature = "python")]
 use pyo3::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::convert::TryFrom;
+use std::fmt;
 
 /// Defines the default celestial bodies in the provided de438 XB.
+/// Any other body whose ephemeris is loaded in a Cosm (e.g. the moons of an SPK, or a trajectory) is available with `Cosm::try_body`.
 #[derive(Copy, Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
 #[allow(clippy::upper_case_acronyms)]
 #[cfg_attr(feature = "python", pyclass)]
@@ -66,6 +69,27 @@ impl Bodies {
         }
     }
 
+    /// Returns the NAIF ID of this body
+    pub fn naif_id(&self) -> i32 {
+        match *self {
+            Self::SSB => 0,
+            Self::Sun => 10,
+            Self::MercuryBarycenter => 1,
+            Self::Mercury => 199,
+            Self::VenusBarycenter => 2,
+            Self::Venus => 299,
+            Self::EarthBarycenter => 3,
+            Self::Earth => 399,
+            Self::Luna => 301,
+            Self::MarsBarycenter => 4,
+            Self::JupiterBarycenter => 5,
+            Self::SaturnBarycenter => 6,
+            Self::UranusBarycenter => 7,
+            Self::NeptuneBarycenter => 8,
+            Self::PlutoBarycenter => 9,
+        }
+    }
+
     /// Returns the human name
     pub fn name(&self) -> String {
         match *self {
@@ -176,3 +200,66 @@ impl TryFrom<Vec<usize>> for Bodies {
         }
     }
 }
+
+/// Source of the ephemeris of a body in a Cosm
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum EphemerisSource {
+    /// Ephemeris of the XB file
+    Xb,
+    /// Ephemeris of the SPK kernel
+    Spk,
+    /// Ephemeris interpolated from a user provided trajectory, cf. `Cosm::append_body_ephemeris`
+    Trajectory,
+}
+
+/// A celestial body whose ephemeris is loaded in a Cosm, as returned by `Cosm::try_body` or `Cosm::bodies`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Body {
+    /// NAIF ID of this body, if known (e.g. from the SPK or the planetary constants)
+    pub naif_id: Option<i32>,
+    /// Name of the ephemeris of this body
+    pub name: String,
+    /// Path of this ephemeris in the Cosm
+    pub ephem_path: Vec<usize>,
+    /// Where this ephemeris comes from
+    pub source: EphemerisSource,
+}
+
+impl fmt::Display for Body {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.naif_id {
+            Some(naif_id) => write!(f, "{} (NAIF {naif_id}, {:?})", self.name, self.source),
+            None => write!(f, "{} ({:?})", self.name, self.source),
+        }
+    }
+}
+
+/// Any identifier of a celestial body loaded in a Cosm: one of the default `Bodies`, a registered `Body`, or the name of a body (e.g. "Phobos").
+pub trait CelestialBody {
+    /// Returns the J2000 frame of this body, or an error if its ephemeris isn't loaded in this Cosm
+    fn try_frame(&self, cosm: &Cosm) -> Result<Frame, NyxError>;
+}
+
+impl CelestialBody for Bodies {
+    fn try_frame(&self, cosm: &Cosm) -> Result<Frame, NyxError> {
+        cosm.try_frame_from_ephem_path(self.ephem_path())
+    }
+}
+
+impl CelestialBody for Body {
+    fn try_frame(&self, cosm: &Cosm) -> Result<Frame, NyxError> {
+        cosm.try_frame_from_ephem_path(&self.ephem_path)
+    }
+}
+
+impl CelestialBody for &str {
+    fn try_frame(&self, cosm: &Cosm) -> Result<Frame, NyxError> {
+        cosm.try_body(self)?.try_frame(cosm)
+    }
+}
+
+impl CelestialBody for String {
+    fn try_frame(&self, cosm: &Cosm) -> Result<Frame, NyxError> {
+        self.as_str().try_frame(cosm)
+    }
+}
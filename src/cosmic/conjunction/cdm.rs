@@ -0,0 +1,188 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::{Conjunction, PcMethod};
+use crate::cosmic::{Frame, OrbitCovariance};
+use crate::io::ExportCfg;
+use crate::time::Epoch;
+use crate::NyxError;
+use hifitime::efmt::{Format, Formatter};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Names of the RTN covariance components, in the (lower triangular) order of the CDM
+const COVAR_NAMES: [&str; 6] = ["R", "T", "N", "RDOT", "TDOT", "NDOT"];
+
+impl Conjunction {
+    /// Exports this conjunction to a CCSDS Conjunction Data Message (CDM) in the KVN format, with the probability of collision computed with the provided method.
+    ///
+    /// Only the relative metadata, the state vectors, and the RTN covariances are exported. The `originator` and `message_for` metadata of the export configuration are used if provided.
+    /// Units follow the CDM standard: meters for the relative geometry and covariances, kilometers for the state vectors.
+    pub fn to_cdm_file<P: AsRef<Path>>(
+        &self,
+        path: P,
+        method: PcMethod,
+        cfg: ExportCfg,
+    ) -> Result<PathBuf, NyxError> {
+        let path_buf = cfg.actual_path(path);
+        let metadata = cfg.metadata.unwrap_or_default();
+        let pc = self.probability(method)?;
+
+        let file = File::create(&path_buf)
+            .map_err(|e| NyxError::CCSDS(format!("File creation error: {e}")))?;
+        let mut writer = BufWriter::new(file);
+
+        let err_hdlr = |e| NyxError::CCSDS(format!("Could not write: {e}"));
+
+        let iso8601_no_ts = Format::from_str("%Y-%m-%dT%H:%M:%S.%f").unwrap();
+        let fmt_epoch = |epoch: Epoch| format!("{}", Formatter::new(epoch, iso8601_no_ts));
+
+        writeln!(writer, "CCSDS_CDM_VERS = 1.0").map_err(err_hdlr)?;
+        writeln!(
+            writer,
+            "CREATION_DATE = {}",
+            fmt_epoch(Epoch::now().unwrap())
+        )
+        .map_err(err_hdlr)?;
+        writeln!(
+            writer,
+            "ORIGINATOR = {}",
+            metadata
+                .get("originator")
+                .unwrap_or(&"Nyx Space".to_string())
+        )
+        .map_err(err_hdlr)?;
+        if let Some(message_for) = metadata.get("message_for") {
+            writeln!(writer, "MESSAGE_FOR = {message_for}").map_err(err_hdlr)?;
+        }
+        writeln!(
+            writer,
+            "MESSAGE_ID = {}_{}\n",
+            self.secondary_name.as_deref().unwrap_or("SECONDARY"),
+            fmt_epoch(self.tca())
+        )
+        .map_err(err_hdlr)?;
+
+        // Relative metadata
+        let ric = self.relative_state_ric()?;
+        writeln!(writer, "TCA = {}", fmt_epoch(self.tca())).map_err(err_hdlr)?;
+        writeln!(
+            writer,
+            "MISS_DISTANCE = {:.3} [m]",
+            self.miss_distance_km() * 1e3
+        )
+        .map_err(err_hdlr)?;
+        writeln!(
+            writer,
+            "RELATIVE_SPEED = {:.3} [m/s]",
+            self.relative_speed_km_s() * 1e3
+        )
+        .map_err(err_hdlr)?;
+        for (i, axis) in ["R", "T", "N"].iter().enumerate() {
+            writeln!(writer, "RELATIVE_POSITION_{axis} = {:.3} [m]", ric[i] * 1e3)
+                .map_err(err_hdlr)?;
+        }
+        for (i, axis) in ["R", "T", "N"].iter().enumerate() {
+            writeln!(
+                writer,
+                "RELATIVE_VELOCITY_{axis} = {:.3} [m/s]",
+                ric[i + 3] * 1e3
+            )
+            .map_err(err_hdlr)?;
+        }
+        writeln!(writer, "COLLISION_PROBABILITY = {pc:.6e}").map_err(err_hdlr)?;
+        writeln!(writer, "COLLISION_PROBABILITY_METHOD = {method}").map_err(err_hdlr)?;
+        writeln!(
+            writer,
+            "COMMENT HARD_BODY_RADIUS = {:.3} [m]",
+            self.hard_body_radius_km * 1e3
+        )
+        .map_err(err_hdlr)?;
+
+        for (object, name, covar) in [
+            ("OBJECT1", &self.primary_name, &self.primary),
+            ("OBJECT2", &self.secondary_name, &self.secondary),
+        ] {
+            writeln!(writer, "\nOBJECT = {object}").map_err(err_hdlr)?;
+            writeln!(
+                writer,
+                "OBJECT_NAME = {}",
+                name.as_deref().unwrap_or("UNKNOWN")
+            )
+            .map_err(err_hdlr)?;
+            write_object(&mut writer, covar).map_err(err_hdlr)?;
+        }
+
+        writer.flush().map_err(err_hdlr)?;
+        info!("Conjunction exported to CDM {}", path_buf.display());
+        Ok(path_buf)
+    }
+}
+
+fn write_object<W: Write>(writer: &mut W, covar: &OrbitCovariance) -> Result<(), std::io::Error> {
+    let orbit = covar.nominal;
+    let frame_str = orbit.frame.to_string();
+    let ref_frame = frame_str
+        .split(' ')
+        .skip(1)
+        .collect::<Vec<&str>>()
+        .join(" ");
+    writeln!(
+        writer,
+        "REF_FRAME = {}",
+        match ref_frame.as_str() {
+            "J2000" => "EME2000",
+            other => other,
+        }
+    )?;
+    writeln!(writer, "COVARIANCE_METHOD = CALCULATED")?;
+
+    for (name, value) in ["X", "Y", "Z"].iter().zip(orbit.radius().iter()) {
+        writeln!(writer, "{name} = {value:.6} [km]")?;
+    }
+    for (name, value) in ["X_DOT", "Y_DOT", "Z_DOT"]
+        .iter()
+        .zip(orbit.velocity().iter())
+    {
+        writeln!(writer, "{name} = {value:.9} [km/s]")?;
+    }
+
+    // The CDM covariance is in the RTN frame of each object, i.e. the RIC frame.
+    let rtn = covar
+        .covar_in_local_frame(Frame::RIC)
+        .unwrap_or(covar.covar);
+    for i in 0..6 {
+        for j in 0..=i {
+            let unit = match (i >= 3) as u8 + (j >= 3) as u8 {
+                0 => "m**2",
+                1 => "m**2/s",
+                _ => "m**2/s**2",
+            };
+            writeln!(
+                writer,
+                "C{}_{} = {:.6e} [{unit}]",
+                COVAR_NAMES[i],
+                COVAR_NAMES[j],
+                rtn[(i, j)] * 1e6
+            )?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,414 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::{Cosm, Frame, Orbit, OrbitCovariance};
+use crate::linalg::allocator::Allocator;
+use crate::linalg::{DefaultAllocator, Matrix2x3, Matrix3, Matrix6, Vector3, Vector6};
+use crate::md::trajectory::{Interpolatable, Traj};
+use crate::md::EventEvaluator;
+use crate::time::{Duration, Epoch, TimeSeries, Unit};
+use crate::NyxError;
+use rand::SeedableRng;
+use rand_distr::{Distribution, StandardNormal};
+use rand_pcg::Pcg64Mcg;
+use std::fmt;
+use std::sync::Arc;
+
+mod cdm;
+mod probability;
+pub use probability::{EncounterPlane, PcMethod};
+
+/// Locates the times of closest approach (TCA) between the states of a primary trajectory and a secondary trajectory.
+///
+/// The event is the time to the closest approach assuming a rectilinear relative motion, i.e. `(r . v) / |v|^2` where `r` and `v`
+/// are the position and velocity of the secondary relative to the primary. This function crosses zero with a slope close to one
+/// at each local minimum of the distance between both objects, regardless of the encounter geometry.
+#[derive(Clone)]
+pub struct ClosestApproachLocator<'a, S: Interpolatable>
+where
+    DefaultAllocator:
+        Allocator<f64, S::VecLength> + Allocator<f64, S::Size> + Allocator<f64, S::Size, S::Size>,
+{
+    /// The trajectory of the secondary object
+    pub secondary: &'a Traj<S>,
+    pub cosm: Arc<Cosm>,
+}
+
+impl<'a, S: Interpolatable> ClosestApproachLocator<'a, S>
+where
+    DefaultAllocator:
+        Allocator<f64, S::VecLength> + Allocator<f64, S::Size> + Allocator<f64, S::Size, S::Size>,
+{
+    pub fn new(secondary: &'a Traj<S>, cosm: Arc<Cosm>) -> Self {
+        Self { secondary, cosm }
+    }
+
+    /// Returns the state of the secondary relative to the primary, in the frame of the primary.
+    pub fn relative_state(&self, primary: &Orbit) -> Result<Vector6<f64>, NyxError> {
+        let secondary = self.secondary.at(primary.epoch)?;
+        let secondary = self.cosm.try_frame_chg(secondary.orbit(), primary.frame)?;
+        Ok(secondary.to_cartesian_vec() - primary.to_cartesian_vec())
+    }
+
+    /// Finds all of the times of closest approach between the primary and the secondary trajectories, i.e. every local minimum of their distance.
+    ///
+    /// The overlap of both trajectories is sampled with the provided step, which must be shorter than half of the relative motion period (e.g. a tenth of an orbit).
+    /// Each closest approach is then found with a Brent solver, and the state of the primary at that time is returned.
+    pub fn find_all<P: Interpolatable>(
+        &self,
+        primary: &Traj<P>,
+        step: Duration,
+    ) -> Result<Vec<P>, NyxError>
+    where
+        DefaultAllocator: Allocator<f64, P::VecLength>
+            + Allocator<f64, P::Size>
+            + Allocator<f64, P::Size, P::Size>,
+    {
+        let start = primary.first().epoch().max(self.secondary.first().epoch());
+        let end = primary.last().epoch().min(self.secondary.last().epoch());
+        if start >= end {
+            return Err(NyxError::NoStateData(
+                "primary and secondary trajectories do not overlap".to_string(),
+            ));
+        }
+
+        let mut epochs: Vec<Epoch> = TimeSeries::inclusive(start, end, step).collect();
+        if epochs.last() != Some(&end) {
+            epochs.push(end);
+        }
+
+        let mut tcas = Vec::new();
+        let mut prev_epoch = epochs[0];
+        let mut prev_eval = self.eval(&primary.at(prev_epoch)?);
+        for epoch in epochs.iter().skip(1) {
+            let this_eval = self.eval(&primary.at(*epoch)?);
+            // The distance reaches a minimum when the time to closest approach goes from negative to positive
+            if prev_eval < 0.0 && this_eval >= 0.0 {
+                tcas.push(primary.find_bracketed(prev_epoch, *epoch, self)?);
+            }
+            prev_epoch = *epoch;
+            prev_eval = this_eval;
+        }
+
+        Ok(tcas)
+    }
+
+    fn time_to_tca(&self, primary: &Orbit) -> Result<f64, NyxError> {
+        let rel = self.relative_state(primary)?;
+        let r = rel.fixed_rows::<3>(0);
+        let v = rel.fixed_rows::<3>(3);
+        Ok(r.dot(&v) / v.norm_squared())
+    }
+}
+
+impl<S: Interpolatable> fmt::Display for ClosestApproachLocator<'_, S>
+where
+    DefaultAllocator:
+        Allocator<f64, S::VecLength> + Allocator<f64, S::Size> + Allocator<f64, S::Size, S::Size>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "closest approach with {}",
+            self.secondary.name.as_deref().unwrap_or("secondary")
+        )
+    }
+}
+
+impl<P: Interpolatable, S: Interpolatable> EventEvaluator<P> for ClosestApproachLocator<'_, S>
+where
+    DefaultAllocator: Allocator<f64, P::VecLength>
+        + Allocator<f64, P::Size>
+        + Allocator<f64, P::Size, P::Size>
+        + Allocator<f64, S::VecLength>
+        + Allocator<f64, S::Size>
+        + Allocator<f64, S::Size, S::Size>,
+{
+    fn eval(&self, state: &P) -> f64 {
+        match self.time_to_tca(state.orbit()) {
+            Ok(dt) => dt,
+            Err(e) => {
+                warn!("{self} not evaluated: {e}");
+                f64::NAN
+            }
+        }
+    }
+
+    fn eval_string(&self, state: &P) -> String {
+        match self.relative_state(state.orbit()) {
+            Ok(rel) => format!(
+                "{self}: range = {:.6} km\trange rate = {:.6} km/s",
+                rel.fixed_rows::<3>(0).norm(),
+                rel.fixed_rows::<3>(0).dot(&rel.fixed_rows::<3>(3)) / rel.fixed_rows::<3>(0).norm()
+            ),
+            Err(e) => format!("{self}: {e}"),
+        }
+    }
+
+    /// Epoch precision of the closest approach
+    fn epoch_precision(&self) -> Duration {
+        100 * Unit::Nanosecond
+    }
+
+    /// Precision of the time to closest approach, in seconds
+    fn value_precision(&self) -> f64 {
+        1e-6
+    }
+}
+
+/// An object involved in a conjunction: its trajectory and its covariance at the start of that trajectory.
+///
+/// The trajectory must be propagated with its STM enabled so that the covariance can be mapped to the time of closest approach.
+pub struct ConjunctionObject<'a, S: Interpolatable>
+where
+    DefaultAllocator:
+        Allocator<f64, S::VecLength> + Allocator<f64, S::Size> + Allocator<f64, S::Size, S::Size>,
+{
+    pub traj: &'a Traj<S>,
+    pub covar: OrbitCovariance,
+}
+
+impl<'a, S: Interpolatable> ConjunctionObject<'a, S>
+where
+    DefaultAllocator:
+        Allocator<f64, S::VecLength> + Allocator<f64, S::Size> + Allocator<f64, S::Size, S::Size>,
+{
+    pub fn new(traj: &'a Traj<S>, covar: OrbitCovariance) -> Self {
+        Self { traj, covar }
+    }
+
+    /// Returns the orbit and its covariance at the provided epoch of the trajectory
+    pub fn covar_at(&self, epoch: Epoch) -> Result<OrbitCovariance, NyxError> {
+        self.covar.mapped_at(self.traj, epoch)
+    }
+}
+
+/// A conjunction between two objects at their time of closest approach (TCA).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Conjunction {
+    /// The primary object (usually the asset being protected) and its covariance at TCA
+    pub primary: OrbitCovariance,
+    /// The secondary object and its covariance at TCA, in the frame of the primary
+    pub secondary: OrbitCovariance,
+    /// Radius of the sphere enveloping both objects, in kilometers
+    pub hard_body_radius_km: f64,
+    pub primary_name: Option<String>,
+    pub secondary_name: Option<String>,
+}
+
+impl Conjunction {
+    /// Initializes a new conjunction from both objects and their covariance at TCA, which must be defined at the same epoch and in the same frame.
+    pub fn new(
+        primary: OrbitCovariance,
+        secondary: OrbitCovariance,
+        hard_body_radius_km: f64,
+    ) -> Result<Self, NyxError> {
+        if primary.nominal.epoch != secondary.nominal.epoch {
+            return Err(NyxError::CustomError(format!(
+                "primary at {} but secondary at {}",
+                primary.nominal.epoch, secondary.nominal.epoch
+            )));
+        }
+        if primary.nominal.frame != secondary.nominal.frame {
+            return Err(NyxError::CustomError(format!(
+                "primary in {} but secondary in {}",
+                primary.nominal.frame, secondary.nominal.frame
+            )));
+        }
+        if hard_body_radius_km <= 0.0 {
+            return Err(NyxError::CustomError(
+                "hard body radius must be strictly positive".to_string(),
+            ));
+        }
+        Ok(Self {
+            primary,
+            secondary,
+            hard_body_radius_km,
+            primary_name: None,
+            secondary_name: None,
+        })
+    }
+
+    /// Finds all of the conjunctions between both objects whose miss distance is less than the provided threshold.
+    ///
+    /// The trajectories are sampled with the provided step to find the closest approaches, cf. `ClosestApproachLocator::find_all`.
+    pub fn screen<P: Interpolatable, S: Interpolatable>(
+        primary: &ConjunctionObject<P>,
+        secondary: &ConjunctionObject<S>,
+        hard_body_radius_km: f64,
+        max_miss_km: f64,
+        step: Duration,
+        cosm: Arc<Cosm>,
+    ) -> Result<Vec<Self>, NyxError>
+    where
+        DefaultAllocator: Allocator<f64, P::VecLength>
+            + Allocator<f64, P::Size>
+            + Allocator<f64, P::Size, P::Size>
+            + Allocator<f64, S::VecLength>
+            + Allocator<f64, S::Size>
+            + Allocator<f64, S::Size, S::Size>,
+    {
+        let locator = ClosestApproachLocator::new(secondary.traj, cosm.clone());
+
+        let mut conjunctions = Vec::new();
+        for tca_state in locator.find_all(primary.traj, step)? {
+            let tca = tca_state.epoch();
+            let primary_covar = primary.covar_at(tca)?;
+            let secondary_covar = secondary
+                .covar_at(tca)?
+                .try_in_frame(primary_covar.nominal.frame, &cosm)?;
+
+            let mut conjunction = Self::new(primary_covar, secondary_covar, hard_body_radius_km)?;
+            if conjunction.miss_distance_km() <= max_miss_km {
+                conjunction.primary_name = primary.traj.name.clone();
+                conjunction.secondary_name = secondary.traj.name.clone();
+                info!("{conjunction}");
+                conjunctions.push(conjunction);
+            }
+        }
+
+        Ok(conjunctions)
+    }
+
+    /// Time of closest approach
+    pub fn tca(&self) -> Epoch {
+        self.primary.nominal.epoch
+    }
+
+    /// Returns the position and velocity of the secondary relative to the primary
+    pub fn relative_state(&self) -> Vector6<f64> {
+        self.secondary.nominal.to_cartesian_vec() - self.primary.nominal.to_cartesian_vec()
+    }
+
+    /// Returns the miss distance in kilometers
+    pub fn miss_distance_km(&self) -> f64 {
+        self.relative_state().fixed_rows::<3>(0).norm()
+    }
+
+    /// Returns the relative speed in kilometers per second
+    pub fn relative_speed_km_s(&self) -> f64 {
+        self.relative_state().fixed_rows::<3>(3).norm()
+    }
+
+    /// Returns the position and velocity of the secondary relative to the primary in the RIC frame of the primary
+    pub fn relative_state_ric(&self) -> Result<Vector6<f64>, NyxError> {
+        let dcm = self.primary.nominal.dcm6x6_from_traj_frame(Frame::RIC)?;
+        Ok(dcm.transpose() * self.relative_state())
+    }
+
+    /// Returns the combined covariance of the relative state, assuming that both objects are uncorrelated.
+    pub fn combined_covar(&self) -> Matrix6<f64> {
+        self.primary.covar + self.secondary.covar
+    }
+
+    /// Returns the DCM from the frame of the primary to the encounter frame.
+    ///
+    /// The first axis is along the miss vector, the second along the relative velocity, and the third completes the frame.
+    /// The encounter plane is spanned by the first and third axes.
+    pub fn encounter_dcm(&self) -> Matrix3<f64> {
+        let rel = self.relative_state();
+        let r = rel.fixed_rows::<3>(0).into_owned();
+        let y_hat = rel.fixed_rows::<3>(3).normalize();
+        let miss = r - r.dot(&y_hat) * y_hat;
+        let x_hat = if miss.norm() > f64::EPSILON {
+            miss.normalize()
+        } else {
+            // Head-on collision, so pick any direction orthogonal to the relative velocity
+            let mut other = Vector3::x();
+            if y_hat.x.abs() > 0.9 {
+                other = Vector3::y();
+            }
+            (other - other.dot(&y_hat) * y_hat).normalize()
+        };
+        let z_hat = x_hat.cross(&y_hat);
+        Matrix3::from_rows(&[x_hat.transpose(), y_hat.transpose(), z_hat.transpose()])
+    }
+
+    /// Returns the encounter plane, i.e. the miss vector and the combined position covariance projected onto the plane orthogonal to the relative velocity.
+    pub fn encounter_plane(&self) -> EncounterPlane {
+        let dcm = self.encounter_dcm();
+        let proj = Matrix2x3::from_rows(&[dcm.row(0), dcm.row(2)]);
+        let r = self.relative_state().fixed_rows::<3>(0).into_owned();
+        let pos_covar = self.combined_covar().fixed_view::<3, 3>(0, 0).into_owned();
+        EncounterPlane {
+            miss_km: proj * r,
+            covar: proj * pos_covar * proj.transpose(),
+            hard_body_radius_km: self.hard_body_radius_km,
+        }
+    }
+
+    /// Computes the probability of collision with the requested method
+    pub fn probability(&self, method: PcMethod) -> Result<f64, NyxError> {
+        match method {
+            PcMethod::Foster => self.encounter_plane().foster(),
+            PcMethod::Chan => self.encounter_plane().chan(),
+            PcMethod::Alfano => self.encounter_plane().alfano(),
+            PcMethod::MonteCarlo { samples, seed } => self.monte_carlo(samples, seed),
+        }
+    }
+
+    /// Monte Carlo estimate of the probability of collision: the relative state is sampled from the combined covariance
+    /// and the miss distance of each sample is computed assuming a rectilinear relative motion during the encounter.
+    /// The standard deviation of this estimate is `sqrt(pc * (1 - pc) / samples)`.
+    fn monte_carlo(&self, samples: usize, seed: u64) -> Result<f64, NyxError> {
+        if samples == 0 {
+            return Err(NyxError::MonteCarlo(
+                "requested zero Monte Carlo samples".to_string(),
+            ));
+        }
+        // Use the eigen decomposition because the covariance may only be semi definite
+        let eigen = self.combined_covar().symmetric_eigen();
+        let sqrt_covar = eigen.eigenvectors
+            * Matrix6::from_diagonal(&eigen.eigenvalues.map(|val| val.max(0.0).sqrt()));
+
+        let mean = self.relative_state();
+        let mut rng = Pcg64Mcg::seed_from_u64(seed);
+        let mut hits = 0;
+        for _ in 0..samples {
+            let noise = Vector6::from_fn(|_, _| StandardNormal.sample(&mut rng));
+            let rel = mean + sqrt_covar * noise;
+            let r = rel.fixed_rows::<3>(0);
+            let v = rel.fixed_rows::<3>(3);
+            let miss = r - (r.dot(&v) / v.norm_squared()) * v;
+            if miss.norm() < self.hard_body_radius_km {
+                hits += 1;
+            }
+        }
+        Ok(hits as f64 / samples as f64)
+    }
+}
+
+impl fmt::Display for Conjunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let ric = self
+            .relative_state_ric()
+            .unwrap_or_else(|_| Vector6::zeros());
+        write!(
+            f,
+            "{} and {} at {}: miss distance = {:.3} m\trelative speed = {:.3} m/s\tRIC miss = [{:.3}, {:.3}, {:.3}] m",
+            self.primary_name.as_deref().unwrap_or("primary"),
+            self.secondary_name.as_deref().unwrap_or("secondary"),
+            self.tca(),
+            self.miss_distance_km() * 1e3,
+            self.relative_speed_km_s() * 1e3,
+            ric[0] * 1e3,
+            ric[1] * 1e3,
+            ric[2] * 1e3
+        )
+    }
+}
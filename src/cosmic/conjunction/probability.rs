@@ -0,0 +1,242 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::linalg::{Matrix2, Vector2};
+use crate::NyxError;
+use std::f64::consts::{FRAC_PI_2, PI, TAU};
+use std::fmt;
+
+/// Minimum number of integration steps of the numerical methods
+const MIN_STEPS: usize = 200;
+/// Maximum number of integration steps of the numerical methods
+const MAX_STEPS: usize = 20_000;
+
+/// Methods available to compute the probability of collision
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PcMethod {
+    /// Numerical integration of the 2D Gaussian over the hard body circle (Foster, 1992)
+    Foster,
+    /// Series expansion using the equivalent area of the hard body circle (Chan, 1997), exact for isotropic covariances
+    Chan,
+    /// One dimensional integration of error functions in the principal axes of the covariance (Alfano, 2005)
+    Alfano,
+    /// Monte Carlo sampling of the full relative state, assuming a rectilinear relative motion during the encounter
+    MonteCarlo { samples: usize, seed: u64 },
+}
+
+impl fmt::Display for PcMethod {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Foster => write!(f, "FOSTER-1992"),
+            Self::Chan => write!(f, "CHAN-1997"),
+            Self::Alfano => write!(f, "ALFANO-2005"),
+            Self::MonteCarlo { samples, .. } => write!(f, "MONTE_CARLO-{samples}"),
+        }
+    }
+}
+
+/// The geometry of an encounter, projected onto the plane orthogonal to the relative velocity at the time of closest approach.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct EncounterPlane {
+    /// Miss vector in the encounter plane, in kilometers
+    pub miss_km: Vector2<f64>,
+    /// Combined position covariance in the encounter plane, in km^2
+    pub covar: Matrix2<f64>,
+    /// Radius of the sphere enveloping both objects, in kilometers
+    pub hard_body_radius_km: f64,
+}
+
+impl EncounterPlane {
+    /// Returns the standard deviations along the principal axes of the covariance (largest first), and the miss vector in those axes.
+    pub fn principal_axes(&self) -> Result<(f64, f64, Vector2<f64>), NyxError> {
+        let eigen = self.covar.symmetric_eigen();
+        let (major, minor) = if eigen.eigenvalues[0] >= eigen.eigenvalues[1] {
+            (0, 1)
+        } else {
+            (1, 0)
+        };
+        if eigen.eigenvalues[minor] <= 0.0 {
+            return Err(NyxError::SingularCovarianceMatrix);
+        }
+        let miss = Vector2::new(
+            eigen.eigenvectors.column(major).dot(&self.miss_km),
+            eigen.eigenvectors.column(minor).dot(&self.miss_km),
+        );
+        Ok((
+            eigen.eigenvalues[major].sqrt(),
+            eigen.eigenvalues[minor].sqrt(),
+            miss,
+        ))
+    }
+
+    /// Probability of collision computed by integrating the Gaussian over the hard body circle in polar coordinates (Foster, 1992).
+    pub fn foster(&self) -> Result<f64, NyxError> {
+        let covar_inv = self
+            .covar
+            .try_inverse()
+            .ok_or(NyxError::SingularCovarianceMatrix)?;
+        let norm = 1.0 / (TAU * self.covar.determinant().sqrt());
+        let radius = self.hard_body_radius_km;
+        let (_, sigma_minor, _) = self.principal_axes()?;
+
+        // The integrand is periodic in the angle, so a trapezoidal rule converges very quickly.
+        let n_angle = 4 * self.steps(sigma_minor);
+        let angles: Vec<(f64, f64)> = (0..n_angle)
+            .map(|i| (TAU * i as f64 / n_angle as f64).sin_cos())
+            .collect();
+
+        let ring = |rho: f64| -> f64 {
+            let sum: f64 = angles
+                .iter()
+                .map(|(sin, cos)| {
+                    let delta = Vector2::new(rho * cos, rho * sin) - self.miss_km;
+                    (-0.5 * delta.dot(&(covar_inv * delta))).exp()
+                })
+                .sum();
+            rho * sum * TAU / n_angle as f64
+        };
+
+        Ok(norm * simpson(ring, 0.0, radius, self.steps(sigma_minor)))
+    }
+
+    /// Probability of collision using Chan's series expansion (Chan, 1997).
+    ///
+    /// The hard body circle is replaced by an ellipse of the same area and of the same shape as the covariance, which makes this method
+    /// exact for isotropic covariances. In that case, the probability is that a Poisson variable of mean `u/2` exceeds
+    /// a Poisson variable of mean `v/2`, where `u = R^2 / (sx sy)` and `v = xm^2 / sx^2 + ym^2 / sy^2`.
+    pub fn chan(&self) -> Result<f64, NyxError> {
+        let (sigma_x, sigma_y, miss) = self.principal_axes()?;
+        let u = self.hard_body_radius_km.powi(2) / (sigma_x * sigma_y);
+        let v = (miss[0] / sigma_x).powi(2) + (miss[1] / sigma_y).powi(2);
+
+        let max_terms = |lambda: f64| (lambda + 20.0 * lambda.sqrt() + 50.0).ceil() as usize;
+        let n_terms = max_terms(u / 2.0).max(max_terms(v / 2.0));
+
+        let pmf_v = poisson_pmf(v / 2.0, n_terms);
+        let pmf_u = poisson_pmf(u / 2.0, n_terms);
+
+        // Tail of the Poisson distribution of the hard body term, accumulated from the top to avoid cancellations
+        let mut pc = 0.0;
+        let mut tail_u = 0.0;
+        for m in (0..n_terms).rev() {
+            tail_u += pmf_u[m + 1];
+            pc += pmf_v[m] * tail_u;
+        }
+        Ok(pc)
+    }
+
+    /// Probability of collision computed as a one dimensional integral of error functions in the principal axes of the covariance (Alfano, 2005).
+    pub fn alfano(&self) -> Result<f64, NyxError> {
+        let (sigma_x, sigma_y, miss) = self.principal_axes()?;
+        let radius = self.hard_body_radius_km;
+        let sqrt2_sigma_y = 2.0_f64.sqrt() * sigma_y;
+
+        // Substitute x = R sin(phi) to remove the square root singularity at the edges of the circle
+        let integrand = |phi: f64| -> f64 {
+            let (sin_phi, cos_phi) = phi.sin_cos();
+            let half_chord = radius * cos_phi;
+            let x = radius * sin_phi;
+            (erf((half_chord + miss[1]) / sqrt2_sigma_y)
+                + erf((half_chord - miss[1]) / sqrt2_sigma_y))
+                * (-(x - miss[0]).powi(2) / (2.0 * sigma_x.powi(2))).exp()
+                * half_chord
+        };
+
+        Ok(
+            simpson(integrand, -FRAC_PI_2, FRAC_PI_2, self.steps(sigma_y))
+                / ((8.0 * PI).sqrt() * sigma_x),
+        )
+    }
+
+    /// Number of integration steps such that the smallest standard deviation is sampled at least a few times across the hard body circle.
+    fn steps(&self, sigma_minor: f64) -> usize {
+        let steps = (20.0 * self.hard_body_radius_km / sigma_minor).ceil() as usize;
+        2 * (steps.clamp(MIN_STEPS, MAX_STEPS) / 2)
+    }
+}
+
+/// Composite Simpson integration with an even number of steps
+fn simpson<F: Fn(f64) -> f64>(func: F, start: f64, end: f64, steps: usize) -> f64 {
+    let h = (end - start) / steps as f64;
+    let mut sum = func(start) + func(end);
+    for i in 1..steps {
+        let weight = if i % 2 == 1 { 4.0 } else { 2.0 };
+        sum += weight * func(start + i as f64 * h);
+    }
+    sum * h / 3.0
+}
+
+/// Returns the probability mass function of a Poisson distribution up to and including `n`, computed in log space to avoid overflows.
+fn poisson_pmf(lambda: f64, n: usize) -> Vec<f64> {
+    let mut pmf = Vec::with_capacity(n + 1);
+    let mut log_factorial = 0.0;
+    for k in 0..=n {
+        if k > 0 {
+            log_factorial += (k as f64).ln();
+        }
+        let log_pmf = if lambda > 0.0 {
+            -lambda + k as f64 * lambda.ln() - log_factorial
+        } else if k == 0 {
+            0.0
+        } else {
+            f64::NEG_INFINITY
+        };
+        pmf.push(log_pmf.exp());
+    }
+    pmf
+}
+
+/// Error function, computed with its power series for small arguments and with a continued fraction of the complementary error function otherwise.
+fn erf(x: f64) -> f64 {
+    let abs_x = x.abs();
+    let value = if abs_x < 2.5 {
+        // erf(x) = 2/sqrt(pi) exp(-x^2) sum 2^n x^(2n+1) / (1 * 3 * ... * (2n+1)), where all terms are positive
+        let mut term = abs_x;
+        let mut sum = term;
+        let mut n = 0.0;
+        while term > 1e-17 * sum {
+            n += 1.0;
+            term *= 2.0 * abs_x * abs_x / (2.0 * n + 1.0);
+            sum += term;
+        }
+        2.0 / PI.sqrt() * (-abs_x * abs_x).exp() * sum
+    } else {
+        // erfc(x) = exp(-x^2) / sqrt(pi) / (x + (1/2) / (x + 1 / (x + (3/2) / (x + ...))))
+        let mut frac = abs_x;
+        for k in (1..=60).rev() {
+            frac = abs_x + (k as f64 / 2.0) / frac;
+        }
+        1.0 - (-abs_x * abs_x).exp() / (PI.sqrt() * frac)
+    };
+    value.copysign(x)
+}
+
+#[test]
+fn test_erf() {
+    for (x, expected) in [
+        (0.0, 0.0),
+        (0.1, 0.1124629160182849),
+        (1.0, 0.8427007929497149),
+        (-1.5, -0.9661051464753108),
+        (2.4, 0.9993114861033549),
+        (2.6, 0.9997639655834707),
+        (3.5, 0.9999992569016276),
+    ] {
+        assert!((erf(x) - expected).abs() < 1e-14, "erf({x}) = {}", erf(x));
+    }
+}
@@ -25,15 +25,22 @@ extern crate toml;
 use self::meval::Expr;
 use self::rust_embed::RustEmbed;
 use super::frames::*;
+use super::itrf::{EopRotation, TemeRotation};
 use super::orbit::Orbit;
 use super::rotations::*;
 use super::xb::ephem_interp::StateData::{EqualStates, VarwindowStates};
-use super::xb::{Ephemeris, Xb};
+use super::xb::{Constant, Ephemeris, Unit as XbUnit, Xb};
 use super::SPEED_OF_LIGHT_KMS;
+use super::{Bodies, Body, EphemerisSource};
 use crate::errors::NyxError;
 use crate::hifitime::{Epoch, Unit, SECONDS_PER_DAY};
+use crate::io::bpc::Bpc;
+use crate::io::eop::EarthOrientationParams;
 use crate::io::frame_serde;
-use crate::na::{Matrix3, Matrix6};
+use crate::io::pck::{BodyConstants, PlanetaryConstants};
+use crate::io::spk::{Spk, SPK_J2000};
+use crate::md::trajectory::Traj;
+use crate::na::{Matrix3, Matrix6, Vector3};
 use crate::utils::{capitalize, dcm_finite_differencing, rotv};
 #[cfg(feature = "python")]
 use pyo3::prelude::*;
@@ -51,8 +58,119 @@ struct EmbeddedAsset;
 pub const SS_MASS: f64 = 1.0014;
 /// GM of the Sun in km^3/s^2
 pub const SUN_GM: f64 = 132_712_440_041.939_38;
+/// Maximum number of iterations of the converged light time corrections
+const LIGHT_TIME_MAX_ITER: usize = 10;
+/// Convergence tolerance of the light time iterations, in seconds
+const LIGHT_TIME_TOLERANCE_S: f64 = 1e-12;
+
+/// Returns the name, GM (km^3/s^2), and optionally the flattening and equatorial radius (km) of the bodies of the JPL DE files.
+/// Names match those of the de438 XB. GM values are those of the DE438 header.
+/// Other bodies (e.g. the moons of Mars) use the embedded planetary constants, if they are defined there.
+fn naif_body_constants(naif_id: i32) -> (String, Option<f64>, Option<(f64, f64)>) {
+    match naif_id {
+        0 => ("Solar System Barycenter".to_string(), None, None),
+        // The GM of the Sun is set when building its frame
+        10 => ("Sun".to_string(), None, None),
+        1 => (
+            "Mercury Barycenter".to_string(),
+            Some(22_031.780_000),
+            Some((0.0, 2_440.53)),
+        ),
+        199 => (
+            "Mercury".to_string(),
+            Some(22_031.780_000),
+            Some((0.0, 2_440.53)),
+        ),
+        2 => (
+            "Venus Barycenter".to_string(),
+            Some(324_858.592_000),
+            Some((0.0, 6_051.8)),
+        ),
+        299 => (
+            "Venus".to_string(),
+            Some(324_858.592_000),
+            Some((0.0, 6_051.8)),
+        ),
+        3 => ("Earth Barycenter".to_string(), Some(403_503.235_502), None),
+        399 => (
+            "Earth".to_string(),
+            Some(398_600.435_436),
+            Some((1.0 / 298.256_42, 6_378.136_6)),
+        ),
+        301 => (
+            "Moon".to_string(),
+            Some(4_902.800_066),
+            Some((0.0012, 1_738.1)),
+        ),
+        4 => (
+            "Mars Barycenter".to_string(),
+            Some(42_828.375_214),
+            Some((0.005_886_007_555, 3_396.19)),
+        ),
+        5 => (
+            "Jupiter Barycenter".to_string(),
+            Some(126_712_764.8),
+            Some((0.064_873_0, 71_492.0)),
+        ),
+        6 => (
+            "Saturn Barycenter".to_string(),
+            Some(37_940_585.2),
+            Some((0.097_962_4, 60_268.0)),
+        ),
+        7 => (
+            "Uranus Barycenter".to_string(),
+            Some(5_794_548.6),
+            Some((0.022_927_3, 25_559.0)),
+        ),
+        8 => (
+            "Neptune Barycenter".to_string(),
+            Some(6_836_527.100_58),
+            Some((0.017_081_2, 24_764.0)),
+        ),
+        9 => (
+            "Pluto Barycenter".to_string(),
+            Some(977.000_000),
+            Some((0.0, 1_188.3)),
+        ),
+        _ => match PlanetaryConstants::embedded().get(naif_id) {
+            Some(constants) => (
+                constants
+                    .name
+                    .clone()
+                    .unwrap_or_else(|| format!("NAIF {naif_id}")),
+                Some(constants.gm_km3_s2.unwrap_or(0.0)),
+                constants
+                    .radii_km
+                    .map(|radii| (constants.flattening().unwrap(), radii[0])),
+            ),
+            None => (format!("NAIF {naif_id}"), Some(0.0), None),
+        },
+    }
+}
+
+/// Builds the rotation from J2000 to the body fixed frame of this body from its pole and prime meridian polynomials
+fn pck_rotation(constants: &BodyConstants) -> Result<Euler3AxisDt, NyxError> {
+    let (right_asc, declin, w) = constants
+        .rotation_exprs()
+        .ok_or_else(|| NyxError::LoadingError(format!("{constants} has no rotation model")))?;
+    let parse = |expr: &str| -> Result<Expr, NyxError> {
+        expr.parse().map_err(|e| {
+            NyxError::LoadingError(format!(
+                "{constants}: could not parse rotation `{expr}`: {e}"
+            ))
+        })
+    };
+    Ok(Euler3AxisDt::from_ra_dec_w(
+        parse(&right_asc)?,
+        parse(&declin)?,
+        parse(&w)?,
+        HashMap::new(),
+        AngleUnit::Degrees,
+    ))
+}
 
-/// Enable or not light time correction for the computation of the celestial states
+/// Enable or not light time correction for the computation of the celestial states.
+/// The naming follows the aberration corrections of SPICE, cf. https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/req/abcorr.html .
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 #[allow(clippy::upper_case_acronyms)]
 pub enum LightTimeCalc {
@@ -62,6 +180,72 @@ pub enum LightTimeCalc {
     LightTime,
     /// Accounts for light-time and stellar aberration where the solar system barycenter is the inertial frame. Corresponds to CN+S in SPICE.
     Aberration,
+    /// Reception case, single iteration of the light-time (LT in SPICE)
+    LT,
+    /// Reception case, single iteration of the light-time and stellar aberration (LT+S in SPICE)
+    LTS,
+    /// Reception case, converged light-time (CN in SPICE)
+    CN,
+    /// Reception case, converged light-time and stellar aberration (CN+S in SPICE)
+    CNS,
+    /// Transmission case, single iteration of the light-time (XLT in SPICE)
+    XLT,
+    /// Transmission case, single iteration of the light-time and stellar aberration (XLT+S in SPICE)
+    XLTS,
+    /// Transmission case, converged light-time (XCN in SPICE)
+    XCN,
+    /// Transmission case, converged light-time and stellar aberration (XCN+S in SPICE)
+    XCNS,
+}
+
+impl LightTimeCalc {
+    /// Returns whether this assumes instantaneous propagation of photons
+    pub fn is_none(&self) -> bool {
+        *self == Self::None
+    }
+
+    /// Returns whether this is a transmission case, i.e. photons leave the observer at the requested epoch and reach the target later
+    pub fn is_transmission(&self) -> bool {
+        matches!(self, Self::XLT | Self::XLTS | Self::XCN | Self::XCNS)
+    }
+
+    /// Returns whether the stellar aberration is corrected for
+    pub fn has_aberration(&self) -> bool {
+        matches!(
+            self,
+            Self::Aberration | Self::LTS | Self::CNS | Self::XLTS | Self::XCNS
+        )
+    }
+
+    /// Returns whether the light time is iterated until convergence instead of a single iteration
+    pub fn is_converged(&self) -> bool {
+        matches!(
+            self,
+            Self::LightTime | Self::Aberration | Self::CN | Self::CNS | Self::XCN | Self::XCNS
+        )
+    }
+}
+
+impl FromStr for LightTimeCalc {
+    type Err = NyxError;
+
+    /// Parses the SPICE name of the aberration correction, e.g. `CN+S`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().replace(' ', "").to_uppercase().as_str() {
+            "NONE" => Ok(Self::None),
+            "LT" => Ok(Self::LT),
+            "LT+S" => Ok(Self::LTS),
+            "CN" => Ok(Self::CN),
+            "CN+S" => Ok(Self::CNS),
+            "XLT" => Ok(Self::XLT),
+            "XLT+S" => Ok(Self::XLTS),
+            "XCN" => Ok(Self::XCN),
+            "XCN+S" => Ok(Self::XCNS),
+            _ => Err(NyxError::LoadingError(format!(
+                "unknown light time correction `{s}`"
+            ))),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -112,6 +296,14 @@ pub struct Cosm {
     pub frame_root: FrameTree,
     // Maps the ephemeris path to the frame root path (remove this with the upcoming xb file)
     ephem2frame_map: HashMap<Vec<usize>, Vec<usize>>,
+    /// SPK ephemerides, if this Cosm was loaded from SPICE kernels instead of an XB
+    spk: Option<Spk>,
+    // Maps the ephemeris path to the NAIF IDs of the SPK target and center
+    ephem2naif: HashMap<Vec<usize>, (i32, i32)>,
+    // Maps the ephemeris path to the trajectory of user defined ephemerides (e.g. spacecraft)
+    ephem2traj: HashMap<Vec<usize>, Traj<Orbit>>,
+    /// Physical constants of the bodies, initialized from the embedded planetary constants
+    pck: PlanetaryConstants,
 }
 
 impl fmt::Debug for Cosm {
@@ -175,19 +367,138 @@ impl Cosm {
                 name: "SSB J2000".to_string(),
                 frame: Frame::Celestial {
                     gm: SS_MASS * SUN_GM,
-                    ephem_path: [None, None, None],
-                    frame_path: [None, None, None],
+                    ephem_path: TreePath::default(),
+                    frame_path: TreePath::default(),
+                    kind: FrameKind::J2000,
                 },
                 parent_rotation: None,
                 children: Vec::new(),
             },
             ephem2frame_map: HashMap::new(),
+            spk: None,
+            ephem2naif: HashMap::new(),
+            ephem2traj: HashMap::new(),
+            pck: PlanetaryConstants::embedded().clone(),
         };
         cosm.append_xb();
         cosm.load_iau_frames()?;
+        cosm.load_pck_frames()?;
+        cosm.load_teme_frame()?;
         Ok(cosm)
     }
 
+    /// Builds a Cosm from a SPICE SPK file (e.g. `de438s.bsp`) and the embedded IAU frames.
+    pub fn from_spk(filename: &str) -> Result<Self, NyxError> {
+        Self::try_from_spk(Spk::from_file(filename)?)
+    }
+
+    /// Attempts to build a Cosm from an SPK and the embedded IAU frames.
+    ///
+    /// The ephemeris tree is built from the centers and targets of the J2000 segments, starting from the solar system barycenter.
+    /// The bodies of the JPL DE files are ordered as in the de438 XB, so the paths of `Bodies` remain valid.
+    /// Only the GM, flattening, and radii of the DE bodies are known: other bodies are point masses of zero GM.
+    pub fn try_from_spk(spk: Spk) -> Result<Self, NyxError> {
+        let mut pairs: Vec<(i32, i32)> = Vec::new();
+        for seg in &spk.segments {
+            if seg.frame != SPK_J2000 {
+                warn!(
+                    "ignoring SPK segment of {} w.r.t. {}: only J2000 segments are supported (frame is {})",
+                    seg.target, seg.center, seg.frame
+                );
+            } else if !pairs.iter().any(|(target, _)| *target == seg.target) {
+                pairs.push((seg.target, seg.center));
+            }
+        }
+
+        let mut ephem2naif = HashMap::new();
+        let root = Self::spk_ephemeris_node(0, &[], &pairs, &mut ephem2naif);
+
+        let reachable = ephem2naif.len();
+        if reachable < pairs.len() {
+            warn!(
+                "{} SPK targets are not connected to the solar system barycenter and were ignored",
+                pairs.len() - reachable
+            );
+        }
+
+        let mut cosm = Self::try_from_xb(Xb {
+            ephemeris_root: Some(root),
+            ..Default::default()
+        })?;
+        cosm.spk = Some(spk);
+        cosm.ephem2naif = ephem2naif;
+        // Now that the NAIF IDs are known, add the body fixed frames of the other bodies (e.g. moons)
+        cosm.load_pck_frames()?;
+        Ok(cosm)
+    }
+
+    /// Recursively builds the ephemeris node of this NAIF ID from the SPK (target, center) pairs.
+    fn spk_ephemeris_node(
+        naif_id: i32,
+        path: &[usize],
+        pairs: &[(i32, i32)],
+        ephem2naif: &mut HashMap<Vec<usize>, (i32, i32)>,
+    ) -> Ephemeris {
+        let (name, gm, shape) = naif_body_constants(naif_id);
+
+        let mut node = Ephemeris {
+            name,
+            orientation: "J2000".to_string(),
+            ..Default::default()
+        };
+
+        if let Some(gm) = gm {
+            node.constants.insert(
+                "GM".to_string(),
+                Constant {
+                    value: gm,
+                    unit: XbUnit::Km3S2 as i32,
+                },
+            );
+        }
+        if let Some((flattening, equatorial_radius)) = shape {
+            node.constants.insert(
+                "Flattening".to_string(),
+                Constant {
+                    value: flattening,
+                    unit: XbUnit::Dimensionless as i32,
+                },
+            );
+            node.constants.insert(
+                "Equatorial radius".to_string(),
+                Constant {
+                    value: equatorial_radius,
+                    unit: XbUnit::Km as i32,
+                },
+            );
+        }
+
+        // The Sun is the first child of the SSB, and the planet is the first child of its barycenter, then sort by ID.
+        let mut children = pairs
+            .iter()
+            .filter(|(_, center)| *center == naif_id)
+            .map(|(target, _)| *target)
+            .collect::<Vec<i32>>();
+        children.sort_by_key(|target| {
+            let preferred = (naif_id == 0 && *target == 10) || *target == 100 * naif_id + 99;
+            (!preferred, *target < 0, target.abs())
+        });
+
+        for target in children {
+            let mut child_path = path.to_vec();
+            child_path.push(node.children.len());
+            ephem2naif.insert(child_path.clone(), (target, naif_id));
+            node.children.push(Self::spk_ephemeris_node(
+                target,
+                &child_path,
+                pairs,
+                ephem2naif,
+            ));
+        }
+
+        node
+    }
+
     /// Switch the GM values to those from GMAT
     pub fn use_gmat_gm(&mut self) {
         // Set all of the GMs and their body fixed frames too
@@ -224,6 +535,225 @@ impl Cosm {
         )
     }
 
+    /// Returns the physical constants of the bodies of this Cosm
+    pub fn planetary_constants(&self) -> &PlanetaryConstants {
+        &self.pck
+    }
+
+    /// Returns the physical constants of the body of this frame, e.g. its J2 or its rotation model
+    pub fn try_body_constants(&self, frame: &Frame) -> Result<BodyConstants, NyxError> {
+        self.ephem_naif_id(&frame.try_ephem_path()?)
+            .and_then(|naif_id| self.pck.get(naif_id))
+            .ok_or_else(|| {
+                NyxError::ObjectNotFound(
+                    format!("planetary constants of {frame}"),
+                    self.pck
+                        .naif_ids()
+                        .iter()
+                        .map(|naif_id| format!("{naif_id}"))
+                        .collect(),
+                )
+            })
+    }
+
+    /// Loads the text planetary constants kernel and applies it to this Cosm, cf. `append_pck`.
+    pub fn append_pck_file(&mut self, filename: &str) -> Result<(), NyxError> {
+        self.append_pck(PlanetaryConstants::from_file(filename)?)
+    }
+
+    /// Overwrites the physical constants of the bodies defined in this text planetary constants kernel (e.g. `pck00010.tpc`).
+    ///
+    /// The GM and shape of all of the frames of these bodies are updated, the rotation model of their IAU frames is replaced,
+    /// and IAU frames are added for the bodies which didn't have one.
+    pub fn append_pck(&mut self, pck: PlanetaryConstants) -> Result<(), NyxError> {
+        self.pck.append(&pck);
+        let updated = pck.naif_ids();
+
+        for frame in self.frames_get() {
+            let (ephem_path, frame_path) = match (frame.try_ephem_path(), frame.try_frame_path()) {
+                (Ok(ephem_path), Ok(frame_path)) => (ephem_path, frame_path),
+                _ => continue,
+            };
+            let naif_id = match self.ephem_naif_id(&ephem_path) {
+                Some(naif_id) => naif_id,
+                None => continue,
+            };
+
+            let rotation = match self.pck.rotation_body(naif_id) {
+                Some(body) if updated.contains(&body.naif_id) => Some(pck_rotation(body)?),
+                _ => None,
+            };
+            let constants = pck.get(naif_id);
+
+            let node = self.try_frame_node_mut(&frame_path)?;
+            if let Some(constants) = constants {
+                node.frame = constants.update_frame(node.frame);
+            }
+            if let Some(rotation) = rotation {
+                if node.name.to_lowercase().starts_with("iau ") {
+                    debug!("replacing the rotation of frame `{}`", node.name);
+                    node.parent_rotation = Some(Box::new(rotation));
+                }
+            }
+        }
+
+        self.load_pck_frames()
+    }
+
+    /// Adds the IAU frame of the bodies with a rotation model in the planetary constants, unless that frame already exists.
+    fn load_pck_frames(&mut self) -> Result<(), NyxError> {
+        for j2000 in self
+            .frame_root
+            .children
+            .iter()
+            .map(|node| node.frame)
+            .collect::<Vec<Frame>>()
+        {
+            let ephem_path = match j2000.try_ephem_path() {
+                Ok(ephem_path) => ephem_path,
+                Err(_) => continue,
+            };
+            let body = match self
+                .ephem_naif_id(&ephem_path)
+                .and_then(|naif_id| self.pck.rotation_body(naif_id))
+            {
+                Some(body) => body,
+                None => continue,
+            };
+            let name = format!(
+                "iau {}",
+                body.name
+                    .clone()
+                    .unwrap_or_else(|| format!("naif {}", body.naif_id))
+                    .to_lowercase()
+            );
+            if self.try_frame(&name).is_ok() {
+                continue;
+            }
+            let rotation = pck_rotation(body)?;
+            debug!("Loaded frame {name} from the planetary constants");
+            self.insert_frame(name, j2000, j2000, FrameKind::Iau, Box::new(rotation))?;
+        }
+        Ok(())
+    }
+
+    /// Returns the NAIF ID of the body of this ephemeris, from the SPK or from the name of the ephemeris
+    fn ephem_naif_id(&self, ephem_path: &[usize]) -> Option<i32> {
+        if let Some((target, _)) = self.ephem2naif.get(ephem_path) {
+            return Some(*target);
+        }
+        if ephem_path.is_empty() {
+            return Some(0);
+        }
+        match self.xb.ephemeris_from_path(ephem_path) {
+            Ok(ephem) => self.pck.naif_id(&ephem.name),
+            Err(_) => None,
+        }
+        .or_else(|| {
+            Bodies::try_from(ephem_path.to_vec())
+                .ok()
+                .map(|body| body.naif_id())
+        })
+    }
+
+    /// Returns all of the bodies whose ephemeris is loaded in this Cosm, depth first from the solar system barycenter
+    pub fn bodies(&self) -> Vec<Body> {
+        let mut bodies = Vec::new();
+        let mut to_visit: Vec<Vec<usize>> = vec![Vec::new()];
+        while let Some(path) = to_visit.pop() {
+            let ephem = if path.is_empty() {
+                match &self.xb.ephemeris_root {
+                    Some(root) => root,
+                    None => break,
+                }
+            } else {
+                match self.xb.ephemeris_from_path(&path) {
+                    Ok(ephem) => ephem,
+                    Err(_) => continue,
+                }
+            };
+            for j in (0..ephem.children.len()).rev() {
+                let mut child_path = path.clone();
+                child_path.push(j);
+                to_visit.push(child_path);
+            }
+
+            let source = if self.ephem2traj.contains_key(&path) {
+                EphemerisSource::Trajectory
+            } else if self.spk.is_some() {
+                EphemerisSource::Spk
+            } else {
+                EphemerisSource::Xb
+            };
+            bodies.push(Body {
+                naif_id: self.ephem_naif_id(&path),
+                name: ephem.name.clone(),
+                ephem_path: path,
+                source,
+            });
+        }
+        bodies
+    }
+
+    /// Returns the body of this name (case insensitive): the name of its ephemeris (e.g. "Mars Barycenter"),
+    /// the name of its NAIF ID in the planetary constants (e.g. "Phobos"), or the name of one of the default `Bodies` (e.g. "Luna").
+    pub fn try_body(&self, name: &str) -> Result<Body, NyxError> {
+        let bodies = self.bodies();
+        let name = name.trim().to_lowercase();
+        if let Some(body) = bodies.iter().find(|body| body.name.to_lowercase() == name) {
+            return Ok(body.clone());
+        }
+        if let Some(naif_id) = self.pck.naif_id(&name) {
+            if let Some(body) = bodies.iter().find(|body| body.naif_id == Some(naif_id)) {
+                return Ok(body.clone());
+            }
+        }
+        if let Ok(default) = Bodies::try_from(name.clone()) {
+            if let Some(body) = bodies
+                .iter()
+                .find(|body| body.ephem_path == default.ephem_path())
+            {
+                return Ok(body.clone());
+            }
+        }
+        Err(NyxError::ObjectNotFound(
+            name,
+            bodies.into_iter().map(|body| body.name).collect(),
+        ))
+    }
+
+    /// Returns the body of this NAIF ID
+    pub fn try_body_from_naif(&self, naif_id: i32) -> Result<Body, NyxError> {
+        let bodies = self.bodies();
+        match bodies.iter().find(|body| body.naif_id == Some(naif_id)) {
+            Some(body) => Ok(body.clone()),
+            None => Err(NyxError::ObjectNotFound(
+                format!("NAIF {naif_id}"),
+                bodies.into_iter().map(|body| body.name).collect(),
+            )),
+        }
+    }
+
+    /// Registers this trajectory as the ephemeris of the body of this NAIF ID and name, e.g. a small body target, cf. `append_traj_ephemeris`.
+    /// The body may then be used as any other body, e.g. in `PointMasses` or `EclipseLocator`, once its GM and radii are set (e.g. with `append_pck`).
+    pub fn append_body_ephemeris(
+        &mut self,
+        naif_id: i32,
+        name: &str,
+        traj: Traj<Orbit>,
+    ) -> Result<Body, NyxError> {
+        if let Ok(body) = self.try_body_from_naif(naif_id) {
+            return Err(NyxError::LoadingError(format!(
+                "NAIF {naif_id} is already loaded as {body}"
+            )));
+        }
+        let mut constants = BodyConstants::new(naif_id);
+        constants.name = Some(name.to_string());
+        self.pck.insert(constants);
+        self.append_traj_ephemeris(name, traj)?;
+        self.try_body_from_naif(naif_id)
+    }
+
     /// Returns the machine path of the ephemeris whose orientation is requested
     pub fn frame_find_path_for_orientation(&self, name: &str) -> Result<Vec<usize>, NyxError> {
         if self.frame_root.name == name {
@@ -267,8 +797,8 @@ impl Cosm {
     /// Returns the correct frame for this ephemeris
     fn default_frame_value(
         e: &Ephemeris,
-        ephem_path: [Option<usize>; 3],
-        pos: usize,
+        ephem_path: TreePath,
+        frame_path: TreePath,
     ) -> Option<FrameTree> {
         match e.constants.get("GM") {
             Some(gm) => {
@@ -313,7 +843,8 @@ impl Cosm {
                         equatorial_radius,
                         semi_major_radius,
                         ephem_path,
-                        frame_path: [Some(pos), None, None],
+                        frame_path,
+                        kind: FrameKind::J2000,
                     },
                     parent_rotation: None,
                     children: Vec::new(),
@@ -331,7 +862,8 @@ impl Cosm {
                             equatorial_radius: 696_342.0,
                             semi_major_radius: 696_342.0,
                             ephem_path,
-                            frame_path: [Some(pos), None, None],
+                            frame_path,
+                            kind: FrameKind::J2000,
                         },
                         parent_rotation: None,
                         children: Vec::new(),
@@ -348,39 +880,262 @@ impl Cosm {
         // Insert the links between the SSB ephem and the J2000 frame (data stored in self.frame_root!)
         self.ephem2frame_map.insert(Vec::new(), Vec::new());
 
-        // Build the frames
-        for i in 0..self.xb.ephemeris_root.as_ref().unwrap().children.len() {
-            if let Ok(child) = self.xb.ephemeris_from_path(&[i]) {
-                // Add base J2000 frame for all barycenters
-                if let Some(frame) = Self::default_frame_value(
-                    child,
-                    [Some(i), None, None],
-                    self.frame_root.children.len(),
-                ) {
-                    self.frame_root.children.push(frame);
-                    let frame_path = vec![self.frame_root.children.len() - 1];
-                    self.ephem2frame_map.insert(vec![i], frame_path);
+        // Build the J2000 frame of every ephemeris, depth first, as children of the root frame
+        // Bug: This should eventually use the orientation of the XB or it'll fail if it isn't J2000 based
+        let mut to_visit: Vec<Vec<usize>> =
+            (0..self.xb.ephemeris_root.as_ref().unwrap().children.len())
+                .rev()
+                .map(|i| vec![i])
+                .collect();
+        while let Some(path) = to_visit.pop() {
+            let ephem = match self.xb.ephemeris_from_path(&path) {
+                Ok(ephem) => ephem,
+                Err(_) => continue,
+            };
+            for j in (0..ephem.children.len()).rev() {
+                let mut child_path = path.clone();
+                child_path.push(j);
+                to_visit.push(child_path);
+            }
+
+            let (ephem_path, frame_path) = match (
+                TreePath::try_from_slice(&path),
+                TreePath::default().child(self.frame_root.children.len()),
+            ) {
+                (Ok(ephem_path), Ok(frame_path)) => (ephem_path, frame_path),
+                (Err(e), _) | (_, Err(e)) => {
+                    warn!("skipping XB {}: {}", ephem.name, e);
+                    continue;
                 }
+            };
 
-                // Try to go one level deeper
-                for j in 0..child.children.len() {
-                    if let Ok(next_child) = self.xb.ephemeris_from_path(&[i, j]) {
-                        // Create the frame
-                        if let Some(frame) = Self::default_frame_value(
-                            next_child,
-                            [Some(i), Some(j), None],
-                            self.frame_root.children.len(),
-                        ) {
-                            // At this stage, they are all children of the J2000 frame
-                            // Bug: This should eventually use the orientation of the XB or it'll fail if it isn't J2000 based
-                            self.frame_root.children.push(frame);
-                            let frame_path = vec![self.frame_root.children.len() - 1];
-                            self.ephem2frame_map.insert(vec![i, j], frame_path);
-                        }
-                    }
+            if let Some(frame) = Self::default_frame_value(ephem, ephem_path, frame_path) {
+                self.frame_root.children.push(frame);
+                self.ephem2frame_map
+                    .insert(path, vec![self.frame_root.children.len() - 1]);
+            }
+        }
+    }
+
+    /// Inserts a new frame in the frame tree as a child of `src_frame`, which it also shares the ephemeris of.
+    /// Returns the new frame with its frame and ephemeris paths set.
+    fn insert_frame(
+        &mut self,
+        name: String,
+        mut new_frame: Frame,
+        src_frame: Frame,
+        new_kind: FrameKind,
+        parent_rotation: Box<dyn ParentRotation>,
+    ) -> Result<Frame, NyxError> {
+        let src_frame_path = src_frame.try_frame_path()?;
+        let parent = self.try_frame_node_mut(&src_frame_path)?;
+        let new_frame_path =
+            TreePath::try_from_slice(&src_frame_path)?.child(parent.children.len())?;
+        // Set the frame path and ephem path for this new frame
+        match new_frame {
+            Frame::Celestial {
+                ref mut ephem_path,
+                ref mut frame_path,
+                ref mut kind,
+                ..
+            }
+            | Frame::Geoid {
+                ref mut ephem_path,
+                ref mut frame_path,
+                ref mut kind,
+                ..
+            } => {
+                *frame_path = new_frame_path;
+                *ephem_path = TreePath::try_from_slice(&src_frame.try_ephem_path()?)?;
+                *kind = new_kind;
+            }
+            _ => {
+                return Err(NyxError::LoadingError(format!(
+                    "`{name}` must be a celestial or geoid frame"
+                )))
+            }
+        }
+
+        // Create the new FrameTree node, and insert it as a child of the current path
+        parent.children.push(FrameTree {
+            name,
+            frame: new_frame,
+            parent_rotation: Some(parent_rotation),
+            children: Vec::new(),
+        });
+
+        Ok(new_frame)
+    }
+
+    /// Loads the binary PCK file and adds the body fixed frames it defines to this Cosm, cf. `append_bpc`.
+    pub fn append_bpc_file(&mut self, filename: &str) -> Result<(), NyxError> {
+        self.append_bpc(Bpc::from_file(filename)?)
+    }
+
+    /// Adds the body fixed frames of this binary PCK whose NAIF frame class ID is known (i.e. ITRF93 and the Moon PA frames).
+    /// Each of these frames has the same GM and shape as the J2000 frame of its body.
+    /// Use `append_bpc_frame` to add any other frame of the binary PCK.
+    pub fn append_bpc(&mut self, bpc: Bpc) -> Result<(), NyxError> {
+        let bpc = Arc::new(bpc);
+        for frame_id in bpc.frame_ids() {
+            let (name, inherit) = match frame_id {
+                3000 => ("ITRF93", "EME2000"),
+                31006 | 31008 => ("Moon PA", "Luna"),
+                _ => {
+                    warn!(
+                        "binary PCK frame {frame_id} is unknown: use `append_bpc_frame` to add it"
+                    );
+                    continue;
                 }
+            };
+            if self.try_frame(name).is_ok() {
+                warn!("frame `{name}` already defined, ignoring binary PCK frame {frame_id}");
+                continue;
             }
+            self.append_bpc_frame(bpc.clone(), frame_id, name, inherit)?;
+        }
+        Ok(())
+    }
+
+    /// Adds the frame `frame_id` of the binary PCK with the provided name.
+    /// The new frame has the same GM and shape as the `inherit` frame, whose ephemeris it also uses.
+    pub fn append_bpc_frame(
+        &mut self,
+        bpc: Arc<Bpc>,
+        frame_id: i32,
+        name: &str,
+        inherit: &str,
+    ) -> Result<Frame, NyxError> {
+        if !bpc.frame_ids().contains(&frame_id) {
+            return Err(NyxError::ObjectNotFound(
+                format!("binary PCK frame {frame_id}"),
+                bpc.frame_ids().iter().map(|id| id.to_string()).collect(),
+            ));
+        }
+        // The orientation of the binary PCK is relative to J2000, so the frame must be attached to a J2000 frame
+        let src_frame = self.try_frame(inherit)?;
+        if src_frame.try_frame_path()?.len() != 1 {
+            return Err(NyxError::LoadingError(format!(
+                "binary PCK frame `{name}` must inherit from a J2000 frame, not `{inherit}`"
+            )));
+        }
+        debug!("Loaded binary PCK frame {frame_id} as {name}");
+        let kind = match Self::fix_frame_name(name).as_str() {
+            "ITRF93" => FrameKind::Itrf93,
+            "Moon PA" => FrameKind::MoonPa,
+            _ => FrameKind::custom(name),
+        };
+        self.insert_frame(
+            name.to_string(),
+            src_frame,
+            src_frame,
+            kind,
+            Box::new(BpcRotation { bpc, frame_id }),
+        )
+    }
+
+    /// Adds the True Equator Mean Equinox `TEME` frame of the SGP4 propagator, if the Earth is in the ephemeris.
+    /// This frame has the same GM and shape as EME2000. Cf. `teme_dcm_from_j2000` for the models used.
+    fn load_teme_frame(&mut self) -> Result<(), NyxError> {
+        if let Ok(eme2k) = self.try_frame("EME2000") {
+            self.insert_frame(
+                "TEME".to_string(),
+                eme2k,
+                eme2k,
+                FrameKind::Teme,
+                Box::new(TemeRotation),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Adds the `ITRF` frame, whose orientation with respect to EME2000 is computed from these IERS Earth orientation parameters.
+    /// This frame has the same GM and shape as EME2000. Cf. `itrf_dcm_from_j2000` for the models used.
+    pub fn append_eop(&mut self, eop: EarthOrientationParams) -> Result<Frame, NyxError> {
+        if self.try_frame("ITRF").is_ok() {
+            return Err(NyxError::LoadingError(
+                "the ITRF frame is already defined".to_string(),
+            ));
+        }
+        let eme2k = self.try_frame("EME2000")?;
+        self.insert_frame(
+            "ITRF".to_string(),
+            eme2k,
+            eme2k,
+            FrameKind::Itrf,
+            Box::new(EopRotation { eop: Arc::new(eop) }),
+        )
+    }
+
+    /// Registers this trajectory (e.g. a relay satellite or a landmark) as a new ephemeris, whose parent is the center of the trajectory's frame.
+    ///
+    /// Returns the J2000 frame of this ephemeris, named `{name} J2000` and of zero GM, which may then be used as the center or the target
+    /// of frame changes, point masses, eclipse computations, etc. Requesting a state outside of the trajectory will return an error.
+    /// If `name` is that of a body of the planetary constants (e.g. `Phobos`), the frame has the GM and shape of that body and its IAU frame is added.
+    pub fn append_traj_ephemeris(
+        &mut self,
+        name: &str,
+        traj: Traj<Orbit>,
+    ) -> Result<Frame, NyxError> {
+        if traj.states.is_empty() {
+            return Err(NyxError::NoInterpolationData(format!(
+                "trajectory of `{name}` is empty"
+            )));
+        }
+        if self.xb.ephemeris_find_path(name.to_string()).is_ok() {
+            return Err(NyxError::LoadingError(format!(
+                "ephemeris `{name}` is already defined"
+            )));
+        }
+
+        let center_path = traj.first().frame.try_ephem_path()?;
+        let center_tree_path = TreePath::try_from_slice(&center_path)?;
+
+        let mut parent = self
+            .xb
+            .ephemeris_root
+            .as_mut()
+            .ok_or_else(|| NyxError::LoadingError("no ephemeris root".to_string()))?;
+        for idx in &center_path {
+            parent = &mut parent.children[*idx];
+        }
+
+        let mut ephem_path = center_path.clone();
+        ephem_path.push(parent.children.len());
+        // All ephemerides have a J2000 frame as a child of the root frame
+        let frame = Frame::Celestial {
+            gm: 0.0,
+            ephem_path: center_tree_path.child(parent.children.len())?,
+            frame_path: TreePath::default().child(self.frame_root.children.len())?,
+            kind: FrameKind::J2000,
+        };
+        parent.children.push(Ephemeris {
+            name: name.to_string(),
+            orientation: "J2000".to_string(),
+            ..Default::default()
+        });
+
+        self.frame_root.children.push(FrameTree {
+            name: format!("{name} J2000"),
+            frame,
+            parent_rotation: None,
+            children: Vec::new(),
+        });
+        self.ephem2frame_map
+            .insert(ephem_path.clone(), vec![self.frame_root.children.len() - 1]);
+        self.ephem2traj.insert(ephem_path, traj);
+
+        debug!("Loaded trajectory of {name} as an ephemeris");
+
+        // Use the physical constants of this body, if known
+        if let Some(constants) = self.pck.naif_id(name).and_then(|id| self.pck.get(id)) {
+            let frame_path = frame.try_frame_path()?;
+            let node = self.try_frame_node_mut(&frame_path)?;
+            node.frame = constants.update_frame(node.frame);
+            self.load_pck_frames()?;
+            return self.try_frame_from_frame_path(&frame_path);
         }
+        Ok(frame)
     }
 
     /// Append Cosm with the contents of this TOML (must _not_ be the filename)
@@ -397,10 +1152,13 @@ impl Cosm {
                             Ok(src_frame) => {
                                 definition.update_from(&src_frame);
                             }
-                            Err(_) => error!(
-                                "frame `{}` is derived from unknown frame `{}`, skipping!",
-                                name, src_frame_name
-                            ),
+                            Err(_) => {
+                                error!(
+                                    "frame `{}` is derived from unknown frame `{}`, skipping!",
+                                    name, src_frame_name
+                                );
+                                continue;
+                            }
                         }
                     }
                     let rot = &definition.rotation;
@@ -447,70 +1205,25 @@ impl Cosm {
                     );
 
                     // Let's now create the Frame, we'll add the ephem path and frame path just after
-                    let mut new_frame = definition.as_frame();
+                    let new_frame = definition.as_frame();
                     let frame_name = name.replace('_', " ").trim().to_string();
 
                     // Grab the inherited frame again so we know how to place it in the frame tree
                     if let Some(src_frame_name) = &definition.inherit {
                         debug!("Loaded frame {}", frame_name);
                         let src_frame = self.try_frame(src_frame_name.as_str())?;
-                        let mut fpath = src_frame.frame_path();
-                        // And find the correct children
-                        let children = match fpath.len() {
-                            2 => {
-                                &mut self.frame_root.children[fpath[0]].children[fpath[1]].children
-                            }
-                            1 => &mut self.frame_root.children[fpath[0]].children,
-                            0 => &mut self.frame_root.children,
-                            _ => unimplemented!("Too many children for now"),
-                        };
-                        fpath.push(children.len());
-                        // Set the frame path and ephem path for this new frame
-                        match new_frame {
-                            Frame::Celestial {
-                                ref mut ephem_path,
-                                ref mut frame_path,
-                                ..
-                            }
-                            | Frame::Geoid {
-                                ref mut ephem_path,
-                                ref mut frame_path,
-                                ..
-                            } => {
-                                match fpath.len() {
-                                    3 => {
-                                        *frame_path =
-                                            [Some(fpath[0]), Some(fpath[1]), Some(fpath[2])]
-                                    }
-                                    2 => *frame_path = [Some(fpath[0]), Some(fpath[1]), None],
-                                    1 => *frame_path = [Some(fpath[0]), None, None],
-                                    _ => unimplemented!(),
-                                };
-
-                                let epath = src_frame.ephem_path();
-                                match epath.len() {
-                                    3 => {
-                                        *ephem_path =
-                                            [Some(epath[0]), Some(epath[1]), Some(epath[2])]
-                                    }
-                                    2 => *ephem_path = [Some(epath[0]), Some(epath[1]), None],
-                                    1 => *ephem_path = [Some(epath[0]), None, None],
-                                    _ => unimplemented!(),
-                                };
-                            }
-                            _ => unimplemented!(),
-                        }
-
-                        // And create and insert
-                        // Create the new FrameTree node, and insert it as a child of the current path
-                        let fnode = FrameTree {
-                            name: frame_name,
-                            frame: new_frame,
-                            parent_rotation: Some(Box::new(frame_rot)),
-                            children: Vec::new(),
+                        let kind = if frame_name.to_lowercase().starts_with("iau ") {
+                            FrameKind::Iau
+                        } else {
+                            FrameKind::custom(&frame_name)
                         };
-
-                        children.push(fnode);
+                        self.insert_frame(
+                            frame_name,
+                            new_frame,
+                            src_frame,
+                            kind,
+                            Box::new(frame_rot),
+                        )?;
                     } else {
                         warn!(
                             "Frame `{frame_name}` does not inherit from anyone, cannot organize tree",
@@ -538,6 +1251,14 @@ impl Cosm {
             String::from("Earth Barycenter J2000")
         } else if name == "ssb" || name == "ssb j2000" {
             String::from("SSB J2000")
+        } else if name == "itrf" || name == "earth itrf" {
+            String::from("ITRF")
+        } else if name == "itrf93" || name == "earth itrf93" {
+            String::from("ITRF93")
+        } else if name == "moon pa" {
+            String::from("Moon PA")
+        } else if name == "teme" || name == "earth teme" {
+            String::from("TEME")
         } else {
             let splt: Vec<_> = name.split(' ').collect();
             if splt[0] == "iau" {
@@ -560,17 +1281,24 @@ impl Cosm {
     /// Fetch the frame associated with this ephemeris name
     /// This is slow, so avoid using it.
     pub fn try_frame(&self, name: &str) -> Result<Frame, NyxError> {
+        // User defined frames may not follow the naming convention, so try the exact name first
+        if let Ok(path) = FrameTree::frame_seek_by_name(name, &mut Vec::new(), &self.frame_root) {
+            return Ok(self.frame_from_frame_path(&path));
+        }
         let name = Self::fix_frame_name(name);
         if self.frame_root.name == name {
             // Return an empty vector (but OK because we're asking for the root)
             Ok(self.frame_root.frame)
         } else {
             let mut path = Vec::new();
-            Ok(self.frame_from_frame_path(&FrameTree::frame_seek_by_name(
-                &name,
-                &mut path,
-                &self.frame_root,
-            )?))
+            match FrameTree::frame_seek_by_name(&name, &mut path, &self.frame_root) {
+                Ok(path) => Ok(self.frame_from_frame_path(&path)),
+                // Otherwise, this may be the name of a body, e.g. "Phobos"
+                Err(e) => match self.try_body(&name) {
+                    Ok(body) => self.try_frame_from_ephem_path(&body.ephem_path),
+                    Err(_) => Err(e),
+                },
+            }
         }
     }
 
@@ -581,14 +1309,47 @@ impl Cosm {
         self.frame_from_frame_path(self.ephem2frame_map.get(&ephem_path.to_vec()).unwrap())
     }
 
-    /// Provided a frame path returns the Frame.
+    /// Returns the J2000 frame of the ephemeris at this path, or an error if there is no such ephemeris.
+    pub fn try_frame_from_ephem_path(&self, ephem_path: &[usize]) -> Result<Frame, NyxError> {
+        match self.ephem2frame_map.get(ephem_path) {
+            Some(frame_path) => self.try_frame_from_frame_path(frame_path),
+            None => Err(NyxError::ObjectNotFound(
+                format!("ephemeris {ephem_path:?}"),
+                self.bodies().iter().map(|body| body.name.clone()).collect(),
+            )),
+        }
+    }
+
+    /// Provided a frame path returns the Frame. Panics if the path is invalid.
     pub fn frame_from_frame_path(&self, frame_path: &[usize]) -> Frame {
-        match frame_path.len() {
-            2 => self.frame_root.children[frame_path[0]].children[frame_path[1]].frame,
-            1 => self.frame_root.children[frame_path[0]].frame,
-            0 => self.frame_root.frame,
-            _ => unimplemented!("Not expecting three layers of attitude frames"),
+        self.try_frame_from_frame_path(frame_path).unwrap()
+    }
+
+    /// Provided a frame path returns the Frame, or an error if the path is invalid.
+    pub fn try_frame_from_frame_path(&self, frame_path: &[usize]) -> Result<Frame, NyxError> {
+        Ok(self.try_frame_node(frame_path)?.frame)
+    }
+
+    /// Returns the node of the frame tree at this path
+    fn try_frame_node(&self, frame_path: &[usize]) -> Result<&FrameTree, NyxError> {
+        let mut node = &self.frame_root;
+        for pos in frame_path {
+            node = node.children.get(*pos).ok_or_else(|| {
+                NyxError::ObjectNotFound(format!("frame {frame_path:?}"), self.frames_get_names())
+            })?;
         }
+        Ok(node)
+    }
+
+    /// Returns the mutable node of the frame tree at this path
+    fn try_frame_node_mut(&mut self, frame_path: &[usize]) -> Result<&mut FrameTree, NyxError> {
+        // Check the path first so the error can list the frame names
+        self.try_frame_node(frame_path)?;
+        let mut node = &mut self.frame_root;
+        for pos in frame_path {
+            node = &mut node.children[*pos];
+        }
+        Ok(node)
     }
 
     fn frame_names(names: &mut Vec<String>, f: &FrameTree) {
@@ -627,15 +1388,10 @@ impl Cosm {
     pub fn frame_mut_gm(&mut self, name: &str, new_gm: f64) {
         // Grab the frame -- this may panic!
         let frame_path = self.frame(name).frame_path();
-
-        match frame_path.len() {
-            2 => self.frame_root.children[frame_path[0]].children[frame_path[1]]
-                .frame
-                .gm_mut(new_gm),
-            1 => self.frame_root.children[frame_path[0]].frame.gm_mut(new_gm),
-            0 => self.frame_root.frame.gm_mut(new_gm),
-            _ => unimplemented!("Not expecting three layers of attitude frames"),
-        }
+        self.try_frame_node_mut(&frame_path)
+            .unwrap()
+            .frame
+            .gm_mut(new_gm);
     }
 
     /// Returns the celestial state as computed from a de4xx.{FXB,XB} file in the original frame
@@ -654,6 +1410,38 @@ impl Cosm {
                 self.frame_root.frame,
             ));
         }
+        if let Some(traj) = self.ephem2traj.get(path) {
+            // Trajectories may be stored in any frame centered on the parent ephemeris
+            let center_frame = self.frame_from_ephem_path(&path[..path.len() - 1]);
+            let state = self.try_frame_chg(&traj.at(epoch)?, center_frame)?;
+            return Ok(Orbit::cartesian(
+                state.x_km,
+                state.y_km,
+                state.z_km,
+                state.vx_km_s,
+                state.vy_km_s,
+                state.vz_km_s,
+                epoch,
+                self.frame_from_ephem_path(path),
+            ));
+        }
+        if let Some(spk) = &self.spk {
+            let (target, center) = self.ephem2naif.get(path).ok_or_else(|| {
+                NyxError::ObjectNotFound(format!("{path:?}"), self.xb.ephemeris_get_names())
+            })?;
+            let state = spk.try_state(*target, *center, epoch)?;
+            return Ok(Orbit::cartesian(
+                state[0],
+                state[1],
+                state[2],
+                state[3],
+                state[4],
+                state[5],
+                epoch,
+                self.frame_from_ephem_path(path),
+            ));
+        }
+
         let ephem = self.xb.ephemeris_from_path(path)?;
 
         // Compute the position as per the algorithm from jplephem
@@ -752,11 +1540,8 @@ impl Cosm {
         ))
     }
 
-    /// Attempts to return the state of the celestial object at the provided time
-    ///
-    /// The light time correction is based on SPICE's implementation: https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/spkezr_c.html .
-    /// Aberration computation is a conversion of the stelab function in SPICE, available here
-    /// https://github.com/ChristopherRabotin/cspice/blob/26c72936fb7ff6f366803a1419b7cc3c61e0b6e5/src/cspice/stelab.c#L255
+    /// Attempts to return the state of the celestial object at the provided time as seen from the center of the requested frame,
+    /// with the requested light time and stellar aberration corrections.
     pub fn try_celestial_state(
         &self,
         target_ephem: &[usize],
@@ -768,77 +1553,176 @@ impl Cosm {
         match correction {
             LightTimeCalc::None => {
                 let state = Orbit::cartesian(0.0, 0.0, 0.0, 0.0, 0.0, 0.0, datetime, target_frame);
-                Ok(-self.try_frame_chg(&state, frame)?)
+                self.try_frame_chg(&state, frame)
             }
-            LightTimeCalc::LightTime | LightTimeCalc::Aberration => {
+            _ => {
                 // Get the geometric states as seen from SSB
                 let ssb2k = self.frame_root.frame;
 
                 let obs = self.try_celestial_state(
-                    &frame.ephem_path(),
+                    &frame.try_ephem_path()?,
                     datetime,
                     ssb2k,
                     LightTimeCalc::None,
                 )?;
-                let mut tgt =
-                    self.try_celestial_state(target_ephem, datetime, ssb2k, LightTimeCalc::None)?;
-                // It will take less than three iterations to converge
-                for _ in 0..3 {
-                    // Compute the light time
-                    let lt = (tgt - obs).rmag_km() / SPEED_OF_LIGHT_KMS;
-                    // Compute the new target state
-                    let lt_dt = datetime - lt * Unit::Second;
-                    tgt =
-                        self.try_celestial_state(target_ephem, lt_dt, ssb2k, LightTimeCalc::None)?;
-                }
-                // Compute the correct state
-                let mut state = Orbit::cartesian(
-                    (tgt - obs).x_km,
-                    (tgt - obs).y_km,
-                    (tgt - obs).z_km,
-                    (tgt - obs).vx_km_s,
-                    (tgt - obs).vy_km_s,
-                    (tgt - obs).vz_km_s,
-                    datetime,
-                    frame,
-                );
 
-                // Include the range-rate term in the velocity computation as explained in
-                // https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/req/abcorr.html#Reception%20case
-                let state_acc = state.velocity() / state.rmag_km();
-                let dltdt = state.radius().dot(&state_acc) / SPEED_OF_LIGHT_KMS;
-
-                state.vx_km_s = tgt.vx_km_s * (1.0 - dltdt) - obs.vx_km_s;
-                state.vy_km_s = tgt.vy_km_s * (1.0 - dltdt) - obs.vy_km_s;
-                state.vz_km_s = tgt.vz_km_s * (1.0 - dltdt) - obs.vz_km_s;
-
-                if correction == LightTimeCalc::Aberration {
-                    // Get a unit vector that points in the direction of the object
-                    let r_hat = state.r_hat();
-                    // Get the velocity vector (of the observer) scaled with respect to the speed of light
-                    let vbyc = obs.velocity() / SPEED_OF_LIGHT_KMS;
-                    /* If the square of the length of the velocity vector is greater than or equal
-                    to one, the speed of the observer is greater than or equal to the speed of light.
-                    The observer speed is definitely out of range. */
-                    if vbyc.dot(&vbyc) >= 1.0 {
-                        warn!("observer is traveling faster than the speed of light");
-                    } else {
-                        let h_hat = r_hat.cross(&vbyc);
-                        /* If the magnitude of the vector H is zero, the observer is moving along the line
-                        of sight to the object, and no correction is required. Otherwise, rotate the
-                        position of the object by phi radians about H to obtain the apparent position. */
-                        if h_hat.norm() > std::f64::EPSILON {
-                            let phi = h_hat.norm().asin();
-                            let ab_pos = rotv(&state.radius(), &h_hat, phi);
-                            state.x_km = ab_pos[0];
-                            state.y_km = ab_pos[1];
-                            state.z_km = ab_pos[2];
-                        }
-                    }
+                let rel = self.try_light_time_state(
+                    &obs,
+                    |epoch| {
+                        self.try_celestial_state(target_ephem, epoch, ssb2k, LightTimeCalc::None)
+                    },
+                    correction,
+                )?;
+
+                self.try_from_ssb_axes(rel, frame)
+            }
+        }
+    }
+
+    /// Attempts to return the state of the celestial object as seen from the observer, which need not be at the center of its frame (e.g. a spacecraft).
+    /// The returned state is relative to the observer but expressed in the axes of the frame of the observer.
+    pub fn try_celestial_state_from(
+        &self,
+        target_ephem: &[usize],
+        observer: &Orbit,
+        correction: LightTimeCalc,
+    ) -> Result<Orbit, NyxError> {
+        let ssb2k = self.frame_root.frame;
+        let obs = self.try_frame_chg(observer, ssb2k)?;
+
+        let rel = self.try_light_time_state(
+            &obs,
+            |epoch| self.try_celestial_state(target_ephem, epoch, ssb2k, LightTimeCalc::None),
+            correction,
+        )?;
+
+        self.try_from_ssb_axes(rel, observer.frame)
+    }
+
+    /// Returns the state of the celestial object as seen from the observer (cf. `try_celestial_state_from`), or panics
+    pub fn celestial_state_from(
+        &self,
+        target_ephem: &[usize],
+        observer: &Orbit,
+        correction: LightTimeCalc,
+    ) -> Orbit {
+        self.try_celestial_state_from(target_ephem, observer, correction)
+            .unwrap()
+    }
+
+    /// Solves for the light time between the observer and the target, both as seen from the solar system barycenter.
+    /// Returns the state of the target relative to the observer at the epoch of the observer, in the axes of the SSB frame.
+    ///
+    /// The light time correction is based on SPICE's implementation: https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/spkezr_c.html .
+    /// Aberration computation is a conversion of the stelab and stlabx functions in SPICE, available here
+    /// https://github.com/ChristopherRabotin/cspice/blob/26c72936fb7ff6f366803a1419b7cc3c61e0b6e5/src/cspice/stelab.c#L255
+    fn try_light_time_state<F>(
+        &self,
+        obs: &Orbit,
+        target_at: F,
+        correction: LightTimeCalc,
+    ) -> Result<Orbit, NyxError>
+    where
+        F: Fn(Epoch) -> Result<Orbit, NyxError>,
+    {
+        let datetime = obs.epoch;
+        let mut tgt = target_at(datetime)?;
+        if correction.is_none() {
+            let mut state = tgt - *obs;
+            state.epoch = datetime;
+            return Ok(state);
+        }
+
+        // Photons are received at the observer (negative) or transmitted by the observer (positive)
+        let direction = if correction.is_transmission() {
+            1.0
+        } else {
+            -1.0
+        };
+        let max_iter = if correction.is_converged() {
+            LIGHT_TIME_MAX_ITER
+        } else {
+            1
+        };
+
+        let mut lt = (tgt - *obs).rmag_km() / SPEED_OF_LIGHT_KMS;
+        for _ in 0..max_iter {
+            // Compute the new target state
+            let lt_dt = datetime + direction * lt * Unit::Second;
+            tgt = target_at(lt_dt)?;
+            let prev_lt = lt;
+            lt = (tgt - *obs).rmag_km() / SPEED_OF_LIGHT_KMS;
+            if (lt - prev_lt).abs() < LIGHT_TIME_TOLERANCE_S {
+                break;
+            }
+        }
+        // Compute the correct state
+        let mut state = Orbit::cartesian(
+            (tgt - *obs).x_km,
+            (tgt - *obs).y_km,
+            (tgt - *obs).z_km,
+            (tgt - *obs).vx_km_s,
+            (tgt - *obs).vy_km_s,
+            (tgt - *obs).vz_km_s,
+            datetime,
+            obs.frame,
+        );
+
+        // Include the range-rate term in the velocity computation as explained in
+        // https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/req/abcorr.html#Reception%20case
+        let state_acc = state.velocity() / state.rmag_km();
+        let dltdt = state.radius().dot(&state_acc) / SPEED_OF_LIGHT_KMS;
+
+        state.vx_km_s = tgt.vx_km_s * (1.0 + direction * dltdt) - obs.vx_km_s;
+        state.vy_km_s = tgt.vy_km_s * (1.0 + direction * dltdt) - obs.vy_km_s;
+        state.vz_km_s = tgt.vz_km_s * (1.0 + direction * dltdt) - obs.vz_km_s;
+
+        if correction.has_aberration() {
+            // Get a unit vector that points in the direction of the object
+            let r_hat = state.r_hat();
+            // Get the velocity vector (of the observer) scaled with respect to the speed of light,
+            // in the transmission case the correction is computed with the opposite velocity.
+            let vbyc = -direction * obs.velocity() / SPEED_OF_LIGHT_KMS;
+            /* If the square of the length of the velocity vector is greater than or equal
+            to one, the speed of the observer is greater than or equal to the speed of light.
+            The observer speed is definitely out of range. */
+            if vbyc.dot(&vbyc) >= 1.0 {
+                warn!("observer is traveling faster than the speed of light");
+            } else {
+                let h_hat = r_hat.cross(&vbyc);
+                /* If the magnitude of the vector H is zero, the observer is moving along the line
+                of sight to the object, and no correction is required. Otherwise, rotate the
+                position of the object by phi radians about H to obtain the apparent position. */
+                if h_hat.norm() > std::f64::EPSILON {
+                    let phi = h_hat.norm().asin();
+                    let ab_pos = rotv(&state.radius(), &h_hat, phi);
+                    state.x_km = ab_pos[0];
+                    state.y_km = ab_pos[1];
+                    state.z_km = ab_pos[2];
                 }
-                Ok(state)
             }
         }
+        Ok(state)
+    }
+
+    /// Rotates a state computed in the axes of the SSB J2000 frame into the axes of the provided frame, without any translation.
+    fn try_from_ssb_axes(&self, state: Orbit, frame: Frame) -> Result<Orbit, NyxError> {
+        // All J2000 frames share the axes of the SSB J2000 frame
+        let frame_path = frame.try_frame_path()?;
+        let j2k = if frame_path.len() > 1 {
+            self.try_frame_from_frame_path(&frame_path[..1])?
+        } else {
+            frame
+        };
+        let mut state = state;
+        state.frame = j2k;
+        if j2k == frame {
+            Ok(state)
+        } else {
+            state.rotate_by(self.try_dcm_from_to(&j2k, &frame, state.epoch)?);
+            state.frame = frame;
+            Ok(state)
+        }
     }
 
     /// Returns the state of the celestial object (target ephem) as seen in the requested frame at the provided time
@@ -863,41 +1747,45 @@ impl Cosm {
         // And now let's compute the rotation path
         let mut dcm = Matrix3::<f64>::identity();
 
-        if to.frame_path() == from.frame_path() {
+        let state_frame_path = from.try_frame_path()?;
+        let new_frame_path = to.try_frame_path()?;
+
+        if new_frame_path == state_frame_path {
             // No need to go any further
             return Ok(dcm);
         }
 
-        let state_frame_path = from.frame_path();
-        let new_frame_path = to.frame_path();
-
         // Let's get the translation path between both both states.
         let f_common_path = self.find_common_root(&new_frame_path, &state_frame_path);
 
-        let get_dcm = |path: &[usize]| -> &FrameTree {
-            // This is absolutely terrible, and there must be a better way to do it, but it's late.
-            match path.len() {
-                1 => &self.frame_root.children[path[0]],
-                2 => &self.frame_root.children[path[0]].children[path[1]],
-                3 => &self.frame_root.children[path[0]].children[path[1]].children[path[2]],
-                _ => unimplemented!(),
-            }
-        };
-
         // Walk forward from the destination state
 
         for i in (f_common_path.len()..new_frame_path.len()).rev() {
-            if let Some(parent_rot) = &get_dcm(&new_frame_path[0..=i]).parent_rotation {
-                if let Some(next_dcm) = parent_rot.dcm_to_parent(dt) {
-                    dcm *= next_dcm;
+            let node = self.try_frame_node(&new_frame_path[0..=i])?;
+            if let Some(parent_rot) = &node.parent_rotation {
+                match parent_rot.dcm_to_parent(dt) {
+                    Some(next_dcm) => dcm *= next_dcm,
+                    None => {
+                        return Err(NyxError::NoInterpolationData(format!(
+                            "orientation of `{}` is not available at {dt}",
+                            node.name
+                        )))
+                    }
                 }
             }
         }
-        // Walk backward from current state up to common node (we transpose all backward rotations)
-        for i in (f_common_path.len()..state_frame_path.len()).rev() {
-            if let Some(parent_rot) = &get_dcm(&state_frame_path[0..=i]).parent_rotation {
-                if let Some(next_dcm) = parent_rot.dcm_to_parent(dt) {
-                    dcm *= next_dcm.transpose();
+        // Walk backward from current state up to common node (we transpose all backward rotations, shallowest first)
+        for i in f_common_path.len()..state_frame_path.len() {
+            let node = self.try_frame_node(&state_frame_path[0..=i])?;
+            if let Some(parent_rot) = &node.parent_rotation {
+                match parent_rot.dcm_to_parent(dt) {
+                    Some(next_dcm) => dcm *= next_dcm.transpose(),
+                    None => {
+                        return Err(NyxError::NoInterpolationData(format!(
+                            "orientation of `{}` is not available at {dt}",
+                            node.name
+                        )))
+                    }
                 }
             }
         }
@@ -921,6 +1809,53 @@ impl Cosm {
         Ok(dcm_finite_differencing(pre_r_dcm, r_dcm, post_r_dcm))
     }
 
+    /// Returns the body fixed frame whose rotation model defines the rotation of this frame: the frame itself if it is body fixed,
+    /// otherwise the IAU frame of its ephemeris (e.g. `IAU Earth` for `EME2000` and `TEME`).
+    pub(crate) fn try_rotating_frame(&self, frame: &Frame) -> Result<Frame, NyxError> {
+        let frame_path = frame.try_frame_path()?;
+        if frame.is_body_fixed() {
+            return Ok(*frame);
+        }
+        if !frame_path.is_empty() {
+            let node = self.try_frame_node(&frame_path[..1])?;
+            for child in &node.children {
+                if child.name.to_lowercase().starts_with("iau ") {
+                    return Ok(child.frame);
+                }
+            }
+        }
+        Err(FrameError::NoRotationModel(format!("{frame}")).into())
+    }
+
+    /// Returns the angular velocity vector in rad/s of the body fixed frame with respect to the inertial frame, expressed in the body fixed frame.
+    /// If `frame` is inertial, this uses the rotation model of the IAU frame of its ephemeris.
+    pub fn try_angular_velocity_vector(
+        &self,
+        frame: &Frame,
+        epoch: Epoch,
+    ) -> Result<Vector3<f64>, NyxError> {
+        let fixed = self.try_rotating_frame(frame)?;
+        let inertial = self.try_frame_from_frame_path(&fixed.frame_path()[..1])?;
+        let (dcm, dcm_dt) = self.try_dcm_from_to_in_parts(&inertial, &fixed, epoch)?;
+        // The time derivative of the DCM is -[ω×] * DCM
+        let omega_skew = -dcm_dt * dcm.transpose();
+        Ok(Vector3::new(
+            0.5 * (omega_skew[(2, 1)] - omega_skew[(1, 2)]),
+            0.5 * (omega_skew[(0, 2)] - omega_skew[(2, 0)]),
+            0.5 * (omega_skew[(1, 0)] - omega_skew[(0, 1)]),
+        ))
+    }
+
+    /// Returns the angular velocity in rad/s of the provided frame, computed from its rotation model (cf. `try_angular_velocity_vector`).
+    pub fn try_angular_velocity(&self, frame: &Frame, epoch: Epoch) -> Result<f64, NyxError> {
+        Ok(self.try_angular_velocity_vector(frame, epoch)?.norm())
+    }
+
+    /// Returns the angular velocity in rad/s of the provided frame, computed from its rotation model, or panics.
+    pub fn angular_velocity(&self, frame: &Frame, epoch: Epoch) -> f64 {
+        self.try_angular_velocity(frame, epoch).unwrap()
+    }
+
     /// Return the position and velocity DCM (two 3x3 matrices) to go from the `from` frame to the `to` frame
     #[allow(clippy::identity_op)]
     pub fn try_dcm_from_to_in_parts(
@@ -948,75 +1883,27 @@ impl Cosm {
         state: &Orbit,
         new_frame: Frame,
     ) -> Result<Orbit, NyxError> {
-        let new_ephem_path = new_frame.ephem_path();
-        let state_ephem_path = state.frame.ephem_path();
+        let new_ephem_path = new_frame.try_ephem_path()?;
+        let state_ephem_path = state.frame.try_ephem_path()?;
 
-        // This doesn't make sense, but somehow the following algorithm only works when converting spacecraft states
-        let mut new_state = if state.rmag_km() > 0.0 {
-            *state
-        } else if state_ephem_path.is_empty() {
-            // SSB, let's invert this
-            -*state
-        } else {
-            *state
-        };
+        let mut new_state = *state;
 
         // If we only need a rotation, let's skip trying to find the translation
         if new_ephem_path != state_ephem_path {
             // Let's get the translation path between both both states.
             let e_common_path = self.find_common_root(&new_ephem_path, &state_ephem_path);
 
-            // This doesn't make sense, but somehow the following algorithm only works when converting spacecraft states
-            new_state = if state.rmag_km() > 0.0 {
-                // Walk backward from current state up to common node
-                for i in (e_common_path.len()..state_ephem_path.len()).rev() {
-                    let next_state =
-                        self.raw_celestial_state(&state_ephem_path[0..=i], state.epoch)?;
-                    new_state += next_state;
-                }
-
-                // Walk forward from the destination state
-                for i in (e_common_path.len()..new_ephem_path.len()).rev() {
-                    let next_state =
-                        self.raw_celestial_state(&new_ephem_path[0..=i], state.epoch)?;
-                    new_state -= next_state;
-                }
-
-                new_state
-            } else {
-                let mut negated_fwd = false;
-
-                // Walk forward from the destination state
-                for i in (e_common_path.len()..new_ephem_path.len()).rev() {
-                    let next_state =
-                        self.raw_celestial_state(&new_ephem_path[0..=i], state.epoch)?;
-                    if new_ephem_path.len() < state_ephem_path.len() && i == e_common_path.len() {
-                        // We just crossed the common point going forward, so let's add the opposite of this state
-                        new_state -= next_state;
-                        negated_fwd = true;
-                    } else {
-                        new_state += next_state;
-                    }
-                }
-                // Walk backward from current state up to common node
-                for i in (e_common_path.len()..state_ephem_path.len()).rev() {
-                    let next_state =
-                        self.raw_celestial_state(&state_ephem_path[0..=i], state.epoch)?;
-                    if !negated_fwd && i == e_common_path.len() {
-                        // We just crossed the common point (and haven't passed it going forward), so let's negate this state
-                        new_state -= next_state;
-                    } else {
-                        new_state += next_state;
-                    }
-                }
+            // Walk backward from current state up to common node
+            for i in (e_common_path.len()..state_ephem_path.len()).rev() {
+                let next_state = self.raw_celestial_state(&state_ephem_path[0..=i], state.epoch)?;
+                new_state += next_state;
+            }
 
-                if negated_fwd {
-                    // Because we negated the state going forward, let's flip it back to its correct orientation now.
-                    -new_state
-                } else {
-                    new_state
-                }
-            };
+            // Walk forward from the destination state
+            for i in (e_common_path.len()..new_ephem_path.len()).rev() {
+                let next_state = self.raw_celestial_state(&new_ephem_path[0..=i], state.epoch)?;
+                new_state -= next_state;
+            }
         }
         new_state.frame = new_frame;
         Ok(new_state)
@@ -1027,8 +1914,18 @@ impl Cosm {
         if state.frame == new_frame {
             return Ok(*state);
         }
+        // Translations are computed in the J2000 axes, so rotate other states into the J2000 frame of their ephemeris first
+        let state = if state.frame.try_frame_path()?.len() > 1 {
+            let j2k = self.try_frame_from_frame_path(&state.frame.try_frame_path()?[..1])?;
+            let mut j2k_state = *state;
+            j2k_state.rotate_by(self.try_dcm_from_to(&state.frame, &j2k, state.epoch)?);
+            j2k_state.frame = j2k;
+            j2k_state
+        } else {
+            *state
+        };
         // Let's perform the translation
-        let mut new_state = self.try_frame_translation(state, new_frame)?;
+        let mut new_state = self.try_frame_translation(&state, new_frame)?;
         // And now let's compute the rotation path
         new_state.rotate_by(self.try_dcm_from_to(&state.frame, &new_frame, state.epoch)?);
         Ok(new_state)
@@ -1039,6 +1936,67 @@ impl Cosm {
         self.try_frame_chg(state, new_frame).unwrap()
     }
 
+    /// Attempts to return the provided state as seen from the center of the new frame, with the requested light time and stellar aberration corrections.
+    ///
+    /// The motion of the state with respect to the center of its own frame during the light time is computed with a two body propagation
+    /// if the orbit is elliptical in an inertial frame, and linearly otherwise (e.g. for a ground station in a body fixed frame).
+    pub fn try_frame_chg_with_correction(
+        &self,
+        state: &Orbit,
+        new_frame: Frame,
+        correction: LightTimeCalc,
+    ) -> Result<Orbit, NyxError> {
+        if correction.is_none() {
+            return self.try_frame_chg(state, new_frame);
+        }
+
+        let ssb2k = self.frame_root.frame;
+        let obs = self.try_celestial_state(
+            &new_frame.try_ephem_path()?,
+            state.epoch,
+            ssb2k,
+            LightTimeCalc::None,
+        )?;
+
+        let rel = self.try_light_time_state(
+            &obs,
+            |epoch| {
+                let dt_s = (epoch - state.epoch).to_seconds();
+                let moved = if dt_s.abs() < std::f64::EPSILON {
+                    *state
+                } else if !state.frame.is_body_fixed()
+                    && state.frame.try_gm()? > 0.0
+                    && state.rmag_km() > 0.0
+                    && state.ecc() < 1.0
+                {
+                    state.at_epoch(epoch)?
+                } else {
+                    let mut moved = *state;
+                    moved.x_km += state.vx_km_s * dt_s;
+                    moved.y_km += state.vy_km_s * dt_s;
+                    moved.z_km += state.vz_km_s * dt_s;
+                    moved.epoch = epoch;
+                    moved
+                };
+                self.try_frame_chg(&moved, ssb2k)
+            },
+            correction,
+        )?;
+
+        self.try_from_ssb_axes(rel, new_frame)
+    }
+
+    /// Return the provided state as seen from the center of the new frame with the requested corrections, or panics
+    pub fn frame_chg_with_correction(
+        &self,
+        state: &Orbit,
+        new_frame: Frame,
+        correction: LightTimeCalc,
+    ) -> Orbit {
+        self.try_frame_chg_with_correction(state, new_frame, correction)
+            .unwrap()
+    }
+
     /// Returns the conversion path from the target ephemeris or frame `from` as seen from `to`.
     fn find_common_root(&self, from: &[usize], to: &[usize]) -> std::vec::Vec<usize> {
         let mut common_root = Vec::with_capacity(3); // Unlikely to be more than 3 items
@@ -0,0 +1,282 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::{Cosm, Frame, Orbit, OrbitDual};
+use crate::linalg::allocator::Allocator;
+use crate::linalg::{DefaultAllocator, Matrix6, SMatrix, Vector6};
+use crate::md::trajectory::{Interpolatable, Traj};
+use crate::md::StateParameter;
+use crate::od::estimate::Estimate;
+use crate::time::Epoch;
+use crate::NyxError;
+use std::fmt;
+
+/// The Keplerian elements used for the Keplerian covariance, in this order.
+pub const KEPLERIAN_COVAR_PARAMS: [StateParameter; 6] = [
+    StateParameter::SMA,
+    StateParameter::Eccentricity,
+    StateParameter::Inclination,
+    StateParameter::RAAN,
+    StateParameter::AoP,
+    StateParameter::TrueAnomaly,
+];
+
+/// The equinoctial elements used for the equinoctial covariance, in this order.
+pub const EQUINOCTIAL_COVAR_PARAMS: [StateParameter; 6] = [
+    StateParameter::SMA,
+    StateParameter::EquinoctialH,
+    StateParameter::EquinoctialK,
+    StateParameter::EquinoctialP,
+    StateParameter::EquinoctialQ,
+    StateParameter::MeanLongitude,
+];
+
+/// An orbit and its 6x6 Cartesian covariance, expressed in the frame of the orbit (km and km/s).
+///
+/// The covariance may be rotated into the local orbital frames (RIC, VNC, RCN) or into any other frame of the Cosm,
+/// mapped into any set of orbital elements (with Jacobians computed via the `OrbitDual`), and linearly mapped along a trajectory using its STM.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct OrbitCovariance {
+    /// The nominal orbit
+    pub nominal: Orbit,
+    /// The Cartesian covariance in the frame of the nominal orbit
+    pub covar: Matrix6<f64>,
+}
+
+impl OrbitCovariance {
+    /// Initializes a new orbit covariance, ensuring that the covariance is symmetric and positive semi definite.
+    pub fn new(nominal: Orbit, covar: Matrix6<f64>) -> Result<Self, NyxError> {
+        if covar.iter().any(|val| !val.is_finite()) {
+            return Err(NyxError::CovarianceMatrixNotPsd);
+        }
+        let scale = covar.amax().max(f64::EPSILON);
+        if (covar - covar.transpose()).amax() > 1e-12 * scale {
+            return Err(NyxError::CovarianceMatrixNotPsd);
+        }
+        for eigenval in covar.symmetric_eigenvalues().iter() {
+            if *eigenval < -1e-12 * scale {
+                return Err(NyxError::CovarianceMatrixNotPsd);
+            }
+        }
+        Ok(Self { nominal, covar })
+    }
+
+    /// Initializes a new orbit covariance from the diagonal of the Cartesian covariance
+    pub fn from_diag(nominal: Orbit, diag: Vector6<f64>) -> Result<Self, NyxError> {
+        Self::new(nominal, Matrix6::from_diagonal(&diag))
+    }
+
+    /// Initializes a new orbit covariance from a covariance expressed in a local orbital frame of the nominal (RIC, VNC or RCN).
+    pub fn from_local_covar(
+        nominal: Orbit,
+        local_frame: Frame,
+        covar: Matrix6<f64>,
+    ) -> Result<Self, NyxError> {
+        let dcm = nominal.dcm6x6_from_traj_frame(local_frame)?;
+        Self::new(nominal, dcm * covar * dcm.transpose())
+    }
+
+    /// Initializes a new orbit covariance from a covariance of the provided orbital elements, in the units of each parameter (e.g. km and degrees).
+    ///
+    /// Returns an error if these elements do not fully define the orbit, i.e. if their Jacobian is singular.
+    pub fn from_element_covar(
+        nominal: Orbit,
+        params: [StateParameter; 6],
+        covar: Matrix6<f64>,
+    ) -> Result<Self, NyxError> {
+        let jac_inv = Self::jacobian_of(&nominal, params)?
+            .try_inverse()
+            .ok_or(NyxError::SingularJacobian)?;
+        Self::new(nominal, jac_inv * covar * jac_inv.transpose())
+    }
+
+    /// Initializes a new orbit covariance from a Keplerian covariance, cf. [KEPLERIAN_COVAR_PARAMS] for the order of the elements.
+    pub fn from_keplerian_covar(nominal: Orbit, covar: Matrix6<f64>) -> Result<Self, NyxError> {
+        Self::from_element_covar(nominal, KEPLERIAN_COVAR_PARAMS, covar)
+    }
+
+    /// Initializes a new orbit covariance from an orbit determination estimate
+    pub fn from_estimate<E: Estimate<Orbit>>(estimate: &E) -> Self {
+        Self {
+            nominal: estimate.state(),
+            covar: estimate.covar(),
+        }
+    }
+
+    /// Returns the 1-sigma of each Cartesian component (km and km/s)
+    pub fn sigmas(&self) -> Vector6<f64> {
+        self.covar.diagonal().map(|var| var.sqrt())
+    }
+
+    /// Returns the covariance expressed in a local orbital frame of the nominal (RIC, VNC or RCN).
+    ///
+    /// Like `dcm6x6_from_traj_frame`, this ignores the rotation rate of the local frame, so the velocity covariance is the inertial one projected onto the local axes.
+    pub fn covar_in_local_frame(&self, local_frame: Frame) -> Result<Matrix6<f64>, NyxError> {
+        let dcm = self.nominal.dcm6x6_from_traj_frame(local_frame)?;
+        Ok(dcm.transpose() * self.covar * dcm)
+    }
+
+    /// Returns this covariance in another frame, e.g. a body fixed frame, accounting for the rotation rate between both frames.
+    pub fn try_in_frame(&self, new_frame: Frame, cosm: &Cosm) -> Result<Self, NyxError> {
+        let dcm = cosm.try_dcm_from_to(&self.nominal.frame, &new_frame, self.nominal.epoch)?;
+        Ok(Self {
+            nominal: cosm.try_frame_chg(&self.nominal, new_frame)?,
+            covar: dcm * self.covar * dcm.transpose(),
+        })
+    }
+
+    /// Returns the Jacobian of the provided parameters with respect to the Cartesian state, computed with hyperdual numbers.
+    pub fn jacobian<const N: usize>(
+        &self,
+        params: [StateParameter; N],
+    ) -> Result<SMatrix<f64, N, 6>, NyxError> {
+        Self::jacobian_of(&self.nominal, params)
+    }
+
+    /// Returns the covariance of the provided parameters, in the units of each parameter (e.g. km and degrees).
+    pub fn covar_of<const N: usize>(
+        &self,
+        params: [StateParameter; N],
+    ) -> Result<SMatrix<f64, N, N>, NyxError> {
+        let jac = self.jacobian(params)?;
+        Ok(jac * self.covar * jac.transpose())
+    }
+
+    /// Returns the 1-sigma of the provided parameter
+    pub fn sigma_for(&self, param: StateParameter) -> Result<f64, NyxError> {
+        Ok(self.covar_of([param])?[(0, 0)].sqrt())
+    }
+
+    /// Returns the Keplerian covariance, cf. [KEPLERIAN_COVAR_PARAMS] for the order of the elements.
+    pub fn keplerian_covar(&self) -> Result<Matrix6<f64>, NyxError> {
+        self.covar_of(KEPLERIAN_COVAR_PARAMS)
+    }
+
+    /// Returns the equinoctial covariance, cf. [EQUINOCTIAL_COVAR_PARAMS] for the order of the elements.
+    pub fn equinoctial_covar(&self) -> Result<Matrix6<f64>, NyxError> {
+        self.covar_of(EQUINOCTIAL_COVAR_PARAMS)
+    }
+
+    /// Linearly maps this covariance to the provided state using its STM, which must be the STM from the epoch of this covariance to that of the state.
+    ///
+    /// For spacecraft, only the orbital part of the STM is used.
+    pub fn mapped_to<S: Interpolatable>(&self, state: &S) -> Result<Self, NyxError>
+    where
+        DefaultAllocator: Allocator<f64, S::VecLength>
+            + Allocator<f64, S::Size>
+            + Allocator<f64, S::Size, S::Size>,
+    {
+        let stm: Matrix6<f64> = state.stm()?.fixed_view::<6, 6>(0, 0).into_owned();
+        Ok(Self {
+            nominal: *state.orbit(),
+            covar: stm * self.covar * stm.transpose(),
+        })
+    }
+
+    /// Linearly maps this covariance to every state of the trajectory using their STM.
+    ///
+    /// The trajectory must start at the epoch of this covariance and be propagated with its STM enabled (e.g. with `Orbit::with_stm`).
+    pub fn map_along<S: Interpolatable>(&self, traj: &Traj<S>) -> Result<Vec<Self>, NyxError>
+    where
+        DefaultAllocator: Allocator<f64, S::VecLength>
+            + Allocator<f64, S::Size>
+            + Allocator<f64, S::Size, S::Size>,
+    {
+        self.check_traj_start(traj)?;
+        traj.states
+            .iter()
+            .map(|state| self.mapped_to(state))
+            .collect()
+    }
+
+    /// Linearly maps this covariance to any epoch of the trajectory.
+    ///
+    /// The trajectory is interpolated for the nominal state, but the STM is not, so the covariance is mapped with the STM of the nearest trajectory node.
+    /// This assumes that the covariance barely changes between that node and the requested epoch, which holds for the short steps of an adaptive propagator.
+    pub fn mapped_at<S: Interpolatable>(
+        &self,
+        traj: &Traj<S>,
+        epoch: Epoch,
+    ) -> Result<Self, NyxError>
+    where
+        DefaultAllocator: Allocator<f64, S::VecLength>
+            + Allocator<f64, S::Size>
+            + Allocator<f64, S::Size, S::Size>,
+    {
+        self.check_traj_start(traj)?;
+        let nominal = *traj.at(epoch)?.orbit();
+        let nearest = traj
+            .states
+            .iter()
+            .min_by_key(|state| (state.epoch() - epoch).abs())
+            .unwrap();
+        Ok(Self {
+            nominal,
+            covar: self.mapped_to(nearest)?.covar,
+        })
+    }
+
+    fn check_traj_start<S: Interpolatable>(&self, traj: &Traj<S>) -> Result<(), NyxError>
+    where
+        DefaultAllocator: Allocator<f64, S::VecLength>
+            + Allocator<f64, S::Size>
+            + Allocator<f64, S::Size, S::Size>,
+    {
+        let first = traj.first();
+        if first.epoch() != self.nominal.epoch {
+            return Err(NyxError::NoStateData(format!(
+                "trajectory starts at {} but covariance is defined at {}",
+                first.epoch(),
+                self.nominal.epoch
+            )));
+        }
+        Ok(())
+    }
+
+    fn jacobian_of<const N: usize>(
+        nominal: &Orbit,
+        params: [StateParameter; N],
+    ) -> Result<SMatrix<f64, N, 6>, NyxError> {
+        let dual = OrbitDual::from(*nominal);
+        let mut jac = SMatrix::<f64, N, 6>::zeros();
+        for (i, param) in params.iter().enumerate() {
+            let partial = dual.partial_for(*param)?;
+            jac[(i, 0)] = partial.wtr_x();
+            jac[(i, 1)] = partial.wtr_y();
+            jac[(i, 2)] = partial.wtr_z();
+            jac[(i, 3)] = partial.wtr_vx();
+            jac[(i, 4)] = partial.wtr_vy();
+            jac[(i, 5)] = partial.wtr_vz();
+        }
+        if jac.iter().any(|val| !val.is_finite()) {
+            return Err(NyxError::PartialsUndefined);
+        }
+        Ok(jac)
+    }
+}
+
+impl fmt::Display for OrbitCovariance {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let sigmas = self.sigmas();
+        write!(
+            f,
+            "{}\t1-sigma: [{:.6}, {:.6}, {:.6}] km\t[{:.6}, {:.6}, {:.6}] km/s",
+            self.nominal, sigmas[0], sigmas[1], sigmas[2], sigmas[3], sigmas[4], sigmas[5]
+        )
+    }
+}
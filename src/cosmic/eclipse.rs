@@ -16,9 +16,14 @@
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
+use super::CelestialBody;
 pub use super::{Bodies, Cosm, Frame, LightTimeCalc, Orbit, Spacecraft};
+use crate::linalg::allocator::Allocator;
+use crate::linalg::{DefaultAllocator, Vector3};
+use crate::md::trajectory::{Interpolatable, Interval, IntervalSet, Traj};
 use crate::md::EventEvaluator;
-use crate::time::{Duration, Unit};
+use crate::time::{Duration, Epoch, Unit};
+use crate::NyxError;
 use std::cmp::{Eq, Ord, Ordering, PartialOrd};
 use std::convert::Into;
 use std::fmt;
@@ -116,6 +121,12 @@ pub struct EclipseLocator {
     pub light_source: Frame,
     pub shadow_bodies: Vec<Frame>,
     pub cosm: Arc<Cosm>,
+    /// Light time and stellar aberration corrections of the light source and shadow bodies as seen from the observer
+    pub correction: LightTimeCalc,
+    /// Set to true to model the shadow bodies as oblate spheroids using the flattening of their geoid, instead of spheres of their equatorial radius
+    pub oblate: bool,
+    /// Height of the atmosphere of the Earth in km, added to its radii to inflate its shadow, e.g. to account for the absorption and refraction of the light
+    pub earth_atmosphere_km: Option<f64>,
 }
 
 impl fmt::Display for EclipseLocator {
@@ -127,18 +138,76 @@ impl fmt::Display for EclipseLocator {
             "light-source: {}, shadows casted by: {}",
             self.light_source,
             shadow_bodies.join(", ")
-        )
+        )?;
+        if self.oblate {
+            write!(f, " (oblate)")?;
+        }
+        if let Some(height_km) = self.earth_atmosphere_km {
+            write!(f, " (Earth atmosphere: {height_km} km)")?;
+        }
+        Ok(())
     }
 }
 
 impl EclipseLocator {
+    /// Creates a new eclipse locator of the provided light source and spherical shadow bodies, without any light time correction.
+    pub fn new(light_source: Frame, shadow_bodies: Vec<Frame>, cosm: Arc<Cosm>) -> Self {
+        Self {
+            light_source,
+            shadow_bodies,
+            cosm,
+            correction: LightTimeCalc::None,
+            oblate: false,
+            earth_atmosphere_km: None,
+        }
+    }
+
+    /// Creates a new eclipse locator of the provided light source and spherical shadow bodies, which may be any body loaded in the Cosm (e.g. "Ganymede").
+    pub fn try_from_bodies<L: CelestialBody, B: CelestialBody>(
+        light_source: L,
+        shadow_bodies: &[B],
+        cosm: Arc<Cosm>,
+    ) -> Result<Self, NyxError> {
+        let light_source = light_source.try_frame(&cosm)?;
+        let shadow_bodies = shadow_bodies
+            .iter()
+            .map(|body| body.try_frame(&cosm))
+            .collect::<Result<Vec<Frame>, NyxError>>()?;
+        Ok(Self::new(light_source, shadow_bodies, cosm))
+    }
+
     /// Creates a new typical eclipse locator.
     /// The light source is the Sun, and the shadow bodies are the Earth and the Moon.
     pub fn cislunar(cosm: Arc<Cosm>) -> Self {
-        Self {
-            light_source: cosm.frame("Sun J2000"),
-            shadow_bodies: vec![cosm.frame("EME2000"), cosm.frame("Moon J2000")],
+        Self::new(
+            cosm.frame("Sun J2000"),
+            vec![cosm.frame("EME2000"), cosm.frame("Moon J2000")],
             cosm,
+        )
+    }
+
+    /// Models the shadow bodies as oblate spheroids, using the flattening of their geoid
+    pub fn with_oblate_bodies(mut self) -> Self {
+        self.oblate = true;
+        self
+    }
+
+    /// Inflates the radii of the Earth by the height of its atmosphere, in km
+    pub fn with_earth_atmosphere(mut self, height_km: f64) -> Self {
+        self.earth_atmosphere_km = Some(height_km);
+        self
+    }
+
+    /// Returns the shape of the provided shadow body in this eclipse locator
+    pub fn shadow_body(&self, frame: Frame) -> ShadowBody {
+        let inflation_km = match self.earth_atmosphere_km {
+            Some(height_km) if frame.ephem_path() == Bodies::Earth.ephem_path() => height_km,
+            _ => 0.0,
+        };
+        ShadowBody {
+            frame,
+            oblate: self.oblate,
+            inflation_km,
         }
     }
 
@@ -146,8 +215,13 @@ impl EclipseLocator {
     pub fn compute(&self, observer: &Orbit) -> EclipseState {
         let mut state = EclipseState::Visibilis;
         for eclipsing_body in &self.shadow_bodies {
-            let this_state =
-                eclipse_state(observer, self.light_source, *eclipsing_body, &self.cosm);
+            let this_state = eclipse_state_of_body(
+                observer,
+                self.light_source,
+                self.shadow_body(*eclipsing_body),
+                &self.cosm,
+                self.correction,
+            );
             if this_state > state {
                 state = this_state;
             }
@@ -155,6 +229,29 @@ impl EclipseLocator {
         state
     }
 
+    /// Returns the smallest angular distance in radians between the observer and the provided boundary of the shadow of any of the shadow bodies.
+    /// This is negative when the observer is inside that boundary, and smooth in time unlike the eclipse state.
+    pub fn boundary_margin(&self, observer: &Orbit, boundary: ShadowBoundary) -> f64 {
+        self.shadow_bodies
+            .iter()
+            .map(|eclipsing_body| {
+                let disks = apparent_disks(
+                    observer,
+                    self.light_source,
+                    self.shadow_body(*eclipsing_body),
+                    &self.cosm,
+                    self.correction,
+                );
+                match boundary {
+                    ShadowBoundary::Penumbra => {
+                        disks.separation - (disks.eb_radius + disks.ls_radius)
+                    }
+                    ShadowBoundary::Umbra => disks.separation - (disks.eb_radius - disks.ls_radius),
+                }
+            })
+            .fold(f64::INFINITY, f64::min)
+    }
+
     /// Creates an umbra event from this eclipse locator
     pub fn to_umbra_event(&self) -> UmbraEvent {
         UmbraEvent {
@@ -168,6 +265,178 @@ impl EclipseLocator {
             e_loc: self.clone(),
         }
     }
+
+    /// Creates an event which crosses zero exactly on the provided boundary of the shadow
+    pub fn to_boundary_event(&self, boundary: ShadowBoundary) -> ShadowBoundaryEvent {
+        ShadowBoundaryEvent {
+            e_loc: self.clone(),
+            boundary,
+        }
+    }
+
+    /// Finds all of the intervals of the trajectory which are within the provided boundary of the shadow, named "Umbra" or "Penumbra".
+    /// The penumbra intervals include the umbra. Refer to `find_eclipses` for the choice of the step.
+    pub fn shadow_intervals<S: Interpolatable>(
+        &self,
+        traj: &Traj<S>,
+        boundary: ShadowBoundary,
+        step: Duration,
+    ) -> Result<IntervalSet, NyxError>
+    where
+        DefaultAllocator: Allocator<f64, S::VecLength>
+            + Allocator<f64, S::Size>
+            + Allocator<f64, S::Size, S::Size>,
+    {
+        let name = match boundary {
+            ShadowBoundary::Penumbra => "Penumbra",
+            ShadowBoundary::Umbra => "Umbra",
+        };
+        Ok(traj
+            .find_intervals(&self.to_boundary_event(boundary), step)?
+            .with_name(name.to_string()))
+    }
+
+    /// Finds all of the eclipses of the trajectory, with the exact epochs of the penumbra and umbra entries and exits.
+    ///
+    /// The trajectory is sampled with the provided step, which must be shorter than the shortest penumbra (e.g. ten seconds in low Earth orbit),
+    /// and each boundary crossing is then found with a Brent solver. Eclipses which are ongoing at the start or at the end of the trajectory are truncated to its bounds.
+    pub fn find_eclipses<S: Interpolatable>(
+        &self,
+        traj: &Traj<S>,
+        step: Duration,
+    ) -> Result<Vec<EclipseArc>, NyxError>
+    where
+        DefaultAllocator: Allocator<f64, S::VecLength>
+            + Allocator<f64, S::Size>
+            + Allocator<f64, S::Size, S::Size>,
+    {
+        let penumbra = self.shadow_intervals(traj, ShadowBoundary::Penumbra, step)?;
+        let umbra = self.shadow_intervals(traj, ShadowBoundary::Umbra, step)?;
+
+        Ok(penumbra
+            .intervals()
+            .iter()
+            .map(|arc| {
+                // The umbra is always within the penumbra, but the boundaries are found independently
+                let overlapping: Vec<&Interval> = umbra
+                    .intervals()
+                    .iter()
+                    .filter(|u| u.start < arc.end && u.end > arc.start)
+                    .collect();
+                let umbra = match (overlapping.first(), overlapping.last()) {
+                    (Some(first), Some(last)) => {
+                        Some((first.start.max(arc.start), last.end.min(arc.end)))
+                    }
+                    _ => None,
+                };
+                EclipseArc {
+                    penumbra_entry: arc.start,
+                    penumbra_exit: arc.end,
+                    umbra,
+                }
+            })
+            .collect())
+    }
+}
+
+/// Boundaries of the shadow cast by an eclipsing body
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ShadowBoundary {
+    /// The light source starts being hidden
+    Penumbra,
+    /// The light source is fully hidden
+    Umbra,
+}
+
+impl fmt::Display for ShadowBoundary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Penumbra => write!(f, "penumbra"),
+            Self::Umbra => write!(f, "umbra"),
+        }
+    }
+}
+
+/// Shape of a shadow body
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ShadowBody {
+    pub frame: Frame,
+    /// Set to true to use the flattening of the geoid, i.e. an oblate spheroid, instead of a sphere of the equatorial radius
+    pub oblate: bool,
+    /// Height in km added to both the equatorial and polar radii
+    pub inflation_km: f64,
+}
+
+impl ShadowBody {
+    /// A sphere of the equatorial radius of the provided geoid
+    pub fn sphere(frame: Frame) -> Self {
+        Self {
+            frame,
+            oblate: false,
+            inflation_km: 0.0,
+        }
+    }
+
+    /// Equatorial radius of this shadow body, in km
+    pub fn equatorial_radius(&self) -> f64 {
+        self.frame.equatorial_radius() + self.inflation_km
+    }
+
+    /// Polar radius of this shadow body, in km
+    pub fn polar_radius(&self) -> f64 {
+        if self.oblate {
+            self.frame.equatorial_radius() * (1.0 - self.frame.flattening()) + self.inflation_km
+        } else {
+            self.equatorial_radius()
+        }
+    }
+}
+
+/// An eclipse of a trajectory, with the exact entry and exit epochs of the penumbra and umbra
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct EclipseArc {
+    /// Start of the penumbra, or the start of the trajectory if it starts in eclipse
+    pub penumbra_entry: Epoch,
+    /// End of the penumbra, or the end of the trajectory if it ends in eclipse
+    pub penumbra_exit: Epoch,
+    /// Entry and exit of the umbra, if the light source is fully hidden during this eclipse
+    pub umbra: Option<(Epoch, Epoch)>,
+}
+
+impl EclipseArc {
+    /// Total duration of this eclipse, penumbra included
+    pub fn duration(&self) -> Duration {
+        self.penumbra_exit - self.penumbra_entry
+    }
+
+    /// Duration of the umbra of this eclipse, which is zero if the light source is never fully hidden
+    pub fn umbra_duration(&self) -> Duration {
+        match self.umbra {
+            Some((entry, exit)) => exit - entry,
+            None => Duration::ZERO,
+        }
+    }
+
+    /// Duration of this eclipse spent in penumbra only
+    pub fn penumbra_duration(&self) -> Duration {
+        self.duration() - self.umbra_duration()
+    }
+}
+
+impl fmt::Display for EclipseArc {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "eclipse from {} to {} ({})",
+            self.penumbra_entry,
+            self.penumbra_exit,
+            self.duration()
+        )?;
+        if let Some((entry, exit)) = self.umbra {
+            write!(f, " with umbra from {entry} to {exit} ({})", exit - entry)?;
+        }
+        Ok(())
+    }
 }
 
 /// An event to find the darkest eclipse state (more than 98% shadow)
@@ -285,6 +554,47 @@ impl EventEvaluator<Spacecraft> for PenumbraEvent {
     }
 }
 
+/// An event whose value is the angular depth in radians within a boundary of the shadow, i.e. the opposite of `EclipseLocator::boundary_margin`, so it is positive in the shadow.
+pub struct ShadowBoundaryEvent {
+    e_loc: EclipseLocator,
+    boundary: ShadowBoundary,
+}
+
+impl fmt::Display for ShadowBoundaryEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} boundary event {}", self.boundary, self.e_loc)
+    }
+}
+
+impl<S: Interpolatable> EventEvaluator<S> for ShadowBoundaryEvent
+where
+    DefaultAllocator:
+        Allocator<f64, S::VecLength> + Allocator<f64, S::Size> + Allocator<f64, S::Size, S::Size>,
+{
+    fn eval(&self, state: &S) -> f64 {
+        -self.e_loc.boundary_margin(state.orbit(), self.boundary)
+    }
+
+    /// Stop searching when the time has converged to less than a microsecond
+    fn epoch_precision(&self) -> Duration {
+        1 * Unit::Microsecond
+    }
+
+    /// Finds the boundary within 10 nanoradians, i.e. a few centimeters in low Earth orbit
+    fn value_precision(&self) -> f64 {
+        1e-8
+    }
+
+    fn eval_string(&self, state: &S) -> String {
+        format!(
+            "{} ({} margin: {:.3e} rad)",
+            self.e_loc.compute(state.orbit()),
+            self.boundary,
+            self.e_loc.boundary_margin(state.orbit(), self.boundary)
+        )
+    }
+}
+
 /// Computes the umbra/visibilis/penumbra state between between two states accounting for eclipsing of the providing geoid.
 pub fn eclipse_state(
     observer: &Orbit,
@@ -292,42 +602,51 @@ pub fn eclipse_state(
     eclipsing_body: Frame,
     cosm: &Cosm,
 ) -> EclipseState {
-    // If the light source's radius is zero, just call the line of sight algorithm
+    eclipse_state_with_correction(
+        observer,
+        light_source,
+        eclipsing_body,
+        cosm,
+        LightTimeCalc::None,
+    )
+}
 
-    assert!(light_source.is_geoid() || light_source.is_celestial());
-    assert!(eclipsing_body.is_geoid());
+/// Computes the umbra/visibilis/penumbra state between between two states accounting for eclipsing of the providing geoid,
+/// where the light source and the eclipsing body are at their apparent positions as seen from the observer.
+pub fn eclipse_state_with_correction(
+    observer: &Orbit,
+    light_source: Frame,
+    eclipsing_body: Frame,
+    cosm: &Cosm,
+    correction: LightTimeCalc,
+) -> EclipseState {
+    eclipse_state_of_body(
+        observer,
+        light_source,
+        ShadowBody::sphere(eclipsing_body),
+        cosm,
+        correction,
+    )
+}
 
-    if light_source.equatorial_radius() < std::f64::EPSILON {
-        let observed = cosm.celestial_state(
-            &light_source.ephem_path(),
-            observer.epoch,
-            observer.frame,
-            LightTimeCalc::None,
-        );
-        return line_of_sight(observer, &observed, eclipsing_body, cosm);
+/// Computes the umbra/visibilis/penumbra state between between two states accounting for eclipsing of the provided shadow body,
+/// which may be oblate and inflated (e.g. by an atmosphere).
+pub fn eclipse_state_of_body(
+    observer: &Orbit,
+    light_source: Frame,
+    shadow_body: ShadowBody,
+    cosm: &Cosm,
+    correction: LightTimeCalc,
+) -> EclipseState {
+    let disks = apparent_disks(observer, light_source, shadow_body, cosm, correction);
+    if let Some(los) = disks.line_of_sight {
+        // The light source is a point
+        return los;
     }
-    // All of the computations happen with the observer as the center.
-    // `eb` stands for eclipsing body; `ls` stands for light source.
-    // Get the radius vector of the spacecraft to the eclipsing body
-    let r_eb = cosm.frame_chg(observer, eclipsing_body).radius();
-
-    // Get the radius vector of the light source to the spacecraft
-    let r_ls = -cosm.frame_chg(observer, light_source).radius();
-
-    // Compute the apparent radii of the light source and eclipsing body (preventing any NaN)
-    let r_ls_prime = if light_source.equatorial_radius() >= r_ls.norm() {
-        light_source.equatorial_radius()
-    } else {
-        (light_source.equatorial_radius() / r_ls.norm()).asin()
-    };
-    let r_eb_prime = if eclipsing_body.equatorial_radius() >= r_eb.norm() {
-        eclipsing_body.equatorial_radius()
-    } else {
-        (eclipsing_body.equatorial_radius() / r_eb.norm()).asin()
-    };
 
-    // Compute the apparent separation of both circles
-    let d_prime = (-(r_ls.dot(&r_eb)) / (r_eb.norm() * r_ls.norm())).acos();
+    let r_ls_prime = disks.ls_radius;
+    let r_eb_prime = disks.eb_radius;
+    let d_prime = disks.separation;
 
     if d_prime - r_ls_prime > r_eb_prime {
         // If the closest point where the apparent radius of the light source _starts_ is further
@@ -367,6 +686,107 @@ pub fn eclipse_state(
     }
 }
 
+/// Apparent disks of the light source and of the eclipsing body as seen from the observer
+struct ApparentDisks {
+    /// Apparent radius of the light source, in radians
+    ls_radius: f64,
+    /// Apparent radius of the eclipsing body, in radians
+    eb_radius: f64,
+    /// Apparent separation of the centers of both disks, in radians
+    separation: f64,
+    /// Line of sight to the light source, only set if it is a point
+    line_of_sight: Option<EclipseState>,
+}
+
+/// Computes the apparent disks of the light source and the shadow body as seen from the observer.
+///
+/// An oblate shadow body is handled by stretching space along its pole such that it becomes a sphere of its equatorial radius:
+/// this linear transformation preserves straight lines, and therefore which rays of light are blocked.
+fn apparent_disks(
+    observer: &Orbit,
+    light_source: Frame,
+    shadow_body: ShadowBody,
+    cosm: &Cosm,
+    correction: LightTimeCalc,
+) -> ApparentDisks {
+    let eclipsing_body = shadow_body.frame;
+    assert!(light_source.is_geoid() || light_source.is_celestial());
+    assert!(eclipsing_body.is_geoid());
+
+    // All of the computations happen with the observer as the center.
+    // `eb` stands for eclipsing body; `ls` stands for light source.
+    let (r_eb, r_ls) = if correction.is_none() {
+        // Get the radius vector of the spacecraft to the eclipsing body
+        let r_eb = cosm.frame_chg(observer, eclipsing_body).radius();
+
+        // Get the radius vector of the light source to the spacecraft
+        let r_ls = -cosm.frame_chg(observer, light_source).radius();
+        (r_eb, r_ls)
+    } else {
+        // Same vectors but with the apparent positions of the light source and eclipsing body, in the axes of the observer
+        let r_eb = -cosm
+            .celestial_state_from(&eclipsing_body.ephem_path(), observer, correction)
+            .radius();
+        let r_ls = cosm
+            .celestial_state_from(&light_source.ephem_path(), observer, correction)
+            .radius();
+        (r_eb, r_ls)
+    };
+
+    let eb_radius_km = shadow_body.equatorial_radius();
+    let stretch = eb_radius_km / shadow_body.polar_radius();
+    let (r_eb, r_ls) = if (stretch - 1.0).abs() > f64::EPSILON {
+        // The pole of the shadow body is the Z axis of its body fixed frame
+        let pole = cosm
+            .try_rotating_frame(&eclipsing_body)
+            .and_then(|fixed| {
+                cosm.try_position_dcm_from_to(&observer.frame, &fixed, observer.epoch)
+            })
+            .map(|dcm| dcm.row(2).transpose())
+            .unwrap_or_else(|e| {
+                warn!("{e}: using the Z axis as the pole of {eclipsing_body}");
+                Vector3::z()
+            });
+        let stretched = |r: Vector3<f64>| r + (stretch - 1.0) * pole.dot(&r) * pole;
+        (stretched(r_eb), stretched(r_ls))
+    } else {
+        (r_eb, r_ls)
+    };
+
+    let ls_radius_km = light_source.try_equatorial_radius().unwrap_or(0.0);
+
+    // Compute the apparent radii of the light source and eclipsing body (preventing any NaN)
+    let r_ls_prime = if ls_radius_km >= r_ls.norm() {
+        ls_radius_km
+    } else {
+        (ls_radius_km / r_ls.norm()).asin()
+    };
+    let r_eb_prime = if eb_radius_km >= r_eb.norm() {
+        eb_radius_km
+    } else {
+        (eb_radius_km / r_eb.norm()).asin()
+    };
+
+    // Compute the apparent separation of both circles
+    let d_prime = (-(r_ls.dot(&r_eb)) / (r_eb.norm() * r_ls.norm()))
+        .clamp(-1.0, 1.0)
+        .acos();
+
+    let line_of_sight = if ls_radius_km < f64::EPSILON {
+        // Line of sight from the observer to the point light source, relative to the eclipsing body
+        Some(los_from_vectors(&(r_eb + r_ls), &r_eb, eb_radius_km))
+    } else {
+        None
+    };
+
+    ApparentDisks {
+        ls_radius: r_ls_prime,
+        eb_radius: r_eb_prime,
+        separation: d_prime,
+        line_of_sight,
+    }
+}
+
 // Compute the area of the circular segment of radius r and chord length d
 fn circ_seg_area(r: f64, d: f64) -> f64 {
     r.powi(2) * (d / r).acos() - d * (r.powi(2) - d.powi(2)).sqrt()
@@ -390,14 +810,17 @@ pub fn line_of_sight(
     let r1 = &cosm.frame_chg(observed, eclipsing_body).radius();
     let r2 = &cosm.frame_chg(observer, eclipsing_body).radius();
 
+    los_from_vectors(r1, r2, eclipsing_body.equatorial_radius())
+}
+
+/// Line of sight between the two positions relative to the center of a spherical eclipsing body of the provided radius
+fn los_from_vectors(r1: &Vector3<f64>, r2: &Vector3<f64>, radius_km: f64) -> EclipseState {
     let r1sq = r1.dot(r1);
     let r2sq = r2.dot(r2);
     let r1dotr2 = r1.dot(r2);
 
     let tau = (r1sq - r1dotr2) / (r1sq + r2sq - 2.0 * r1dotr2);
-    if !(0.0..=1.0).contains(&tau)
-        || (1.0 - tau) * r1sq + r1dotr2 * tau > eclipsing_body.equatorial_radius().powi(2)
-    {
+    if !(0.0..=1.0).contains(&tau) || (1.0 - tau) * r1sq + r1dotr2 * tau > radius_km.powi(2) {
         EclipseState::Visibilis
     } else {
         EclipseState::Umbra
@@ -0,0 +1,445 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::{Frame, Orbit};
+use crate::linalg::{Vector3, Vector6};
+use crate::md::StateParameter;
+use crate::time::Epoch;
+use crate::utils::between_0_360;
+use crate::NyxError;
+
+/// Maximum number of Newton iterations when solving the equinoctial Kepler equation
+const MAX_ITER: usize = 100;
+
+/// Returns the unit vectors f and g of the equinoctial frame from the equinoctial p and q elements.
+///
+/// Source: Broucke and Cefola, "On the equinoctial orbit elements", Celestial Mechanics 5, 1972 (prograde formulation).
+pub(crate) fn equinoctial_basis(p: f64, q: f64) -> (Vector3<f64>, Vector3<f64>) {
+    let denom = 1.0 + p * p + q * q;
+    let f_hat = Vector3::new(1.0 - p * p + q * q, 2.0 * p * q, -2.0 * p) / denom;
+    let g_hat = Vector3::new(2.0 * p * q, 1.0 + p * p - q * q, 2.0 * q) / denom;
+    (f_hat, g_hat)
+}
+
+impl Orbit {
+    /// Creates a new Orbit from the equinoctial elements, which are not singular for circular or equatorial orbits.
+    ///
+    /// The elements are the semi major axis, h = e sin(ω+Ω), k = e cos(ω+Ω), p = tan(i/2) sin Ω, q = tan(i/2) cos Ω,
+    /// and the mean longitude λ = M+ω+Ω. Only elliptical and non retrograde equatorial orbits are supported.
+    /// **Units:** km, none, none, none, none, degrees
+    #[allow(clippy::too_many_arguments)]
+    pub fn equinoctial(
+        sma_km: f64,
+        h: f64,
+        k: f64,
+        p: f64,
+        q: f64,
+        mean_longitude_deg: f64,
+        epoch: Epoch,
+        frame: Frame,
+    ) -> Result<Self, NyxError> {
+        let ecc2 = h * h + k * k;
+        if ecc2 >= 1.0 || sma_km <= 0.0 {
+            return Err(NyxError::MathDomain(format!(
+                "equinoctial elements only defined for elliptical orbits (sma = {sma_km} km, ecc = {})",
+                ecc2.sqrt()
+            )));
+        }
+        let gm = frame.try_gm()?;
+
+        // Solve the equinoctial Kepler equation for the eccentric longitude F
+        let lambda = mean_longitude_deg.to_radians();
+        let mut ecc_lon = lambda;
+        let mut converged = false;
+        for _ in 0..MAX_ITER {
+            let (sin_f, cos_f) = ecc_lon.sin_cos();
+            let delta = (ecc_lon + h * cos_f - k * sin_f - lambda) / (1.0 - h * sin_f - k * cos_f);
+            ecc_lon -= delta;
+            if delta.abs() < 1e-15 {
+                converged = true;
+                break;
+            }
+        }
+        if !converged {
+            return Err(NyxError::MaxIterReached(format!(
+                "equinoctial Kepler equation did not converge after {MAX_ITER} iterations"
+            )));
+        }
+
+        let (sin_f, cos_f) = ecc_lon.sin_cos();
+        let beta = 1.0 / (1.0 + (1.0 - ecc2).sqrt());
+        let n = (gm / sma_km.powi(3)).sqrt();
+        let rmag = sma_km * (1.0 - k * cos_f - h * sin_f);
+
+        let x1 = sma_km * ((1.0 - h * h * beta) * cos_f + h * k * beta * sin_f - k);
+        let y1 = sma_km * ((1.0 - k * k * beta) * sin_f + h * k * beta * cos_f - h);
+        let vx1 = n * sma_km.powi(2) / rmag * (h * k * beta * cos_f - (1.0 - h * h * beta) * sin_f);
+        let vy1 = n * sma_km.powi(2) / rmag * ((1.0 - k * k * beta) * cos_f - h * k * beta * sin_f);
+
+        let (f_hat, g_hat) = equinoctial_basis(p, q);
+        let radius = x1 * f_hat + y1 * g_hat;
+        let velocity = vx1 * f_hat + vy1 * g_hat;
+
+        Ok(Self::cartesian(
+            radius[0],
+            radius[1],
+            radius[2],
+            velocity[0],
+            velocity[1],
+            velocity[2],
+            epoch,
+            frame,
+        ))
+    }
+
+    /// Returns the equinoctial p and q elements from the direction of the orbital momentum
+    fn equinoctial_pq(&self) -> (f64, f64) {
+        let w_hat = self.hvec() / self.hmag_km2_s();
+        (w_hat[0] / (1.0 + w_hat[2]), -w_hat[1] / (1.0 + w_hat[2]))
+    }
+
+    /// Returns the equinoctial h element, e sin(ω+Ω), which is also the g element of the modified equinoctial elements
+    pub fn equinoctial_h(&self) -> f64 {
+        let (p, q) = self.equinoctial_pq();
+        let (_, g_hat) = equinoctial_basis(p, q);
+        self.evec().dot(&g_hat)
+    }
+
+    /// Returns the equinoctial k element, e cos(ω+Ω), which is also the f element of the modified equinoctial elements
+    pub fn equinoctial_k(&self) -> f64 {
+        let (p, q) = self.equinoctial_pq();
+        let (f_hat, _) = equinoctial_basis(p, q);
+        self.evec().dot(&f_hat)
+    }
+
+    /// Returns the equinoctial p element, tan(i/2) sin Ω, which is also the k element of the modified equinoctial elements
+    pub fn equinoctial_p(&self) -> f64 {
+        self.equinoctial_pq().0
+    }
+
+    /// Returns the equinoctial q element, tan(i/2) cos Ω, which is also the h element of the modified equinoctial elements
+    pub fn equinoctial_q(&self) -> f64 {
+        self.equinoctial_pq().1
+    }
+
+    /// Returns the mean longitude (M+ω+Ω) in degrees, computed without going through the (possibly singular) Keplerian elements
+    pub fn mean_longitude_deg(&self) -> f64 {
+        let (p, q) = self.equinoctial_pq();
+        let (f_hat, g_hat) = equinoctial_basis(p, q);
+        let evec = self.evec();
+        let (h, k) = (evec.dot(&g_hat), evec.dot(&f_hat));
+        let x1 = self.radius().dot(&f_hat);
+        let y1 = self.radius().dot(&g_hat);
+
+        let root = (1.0 - h * h - k * k).sqrt();
+        let beta = 1.0 / (1.0 + root);
+        let sma_km = self.sma_km();
+        let sin_f = h + ((1.0 - h * h * beta) * y1 - h * k * beta * x1) / (sma_km * root);
+        let cos_f = k + ((1.0 - k * k * beta) * x1 - h * k * beta * y1) / (sma_km * root);
+        let ecc_lon = sin_f.atan2(cos_f);
+
+        between_0_360((ecc_lon + h * ecc_lon.cos() - k * ecc_lon.sin()).to_degrees())
+    }
+
+    /// Returns this state as an equinoctial Vector6 in [km, none, none, none, none, degrees], cf. `equinoctial`.
+    pub fn to_equinoctial_vec(self) -> Vector6<f64> {
+        let (p, q) = self.equinoctial_pq();
+        Vector6::new(
+            self.sma_km(),
+            self.equinoctial_h(),
+            self.equinoctial_k(),
+            p,
+            q,
+            self.mean_longitude_deg(),
+        )
+    }
+
+    /// Creates a new Orbit from the modified equinoctial elements, which are also valid for hyperbolic orbits.
+    ///
+    /// The elements are the semi parameter, f = e cos(ω+Ω), g = e sin(ω+Ω), h = tan(i/2) cos Ω, k = tan(i/2) sin Ω,
+    /// and the true longitude L = ν+ω+Ω.
+    /// **Units:** km, none, none, none, none, degrees
+    /// Source: Walker, Ireland and Owens, "A set of modified equinoctial orbit elements", Celestial Mechanics 36, 1985.
+    #[allow(clippy::too_many_arguments)]
+    pub fn modified_equinoctial(
+        semi_parameter_km: f64,
+        f: f64,
+        g: f64,
+        h: f64,
+        k: f64,
+        true_longitude_deg: f64,
+        epoch: Epoch,
+        frame: Frame,
+    ) -> Result<Self, NyxError> {
+        let (sin_l, cos_l) = true_longitude_deg.to_radians().sin_cos();
+        let w = 1.0 + f * cos_l + g * sin_l;
+        if semi_parameter_km <= 0.0 || w <= 0.0 {
+            return Err(NyxError::MathDomain(format!(
+                "invalid modified equinoctial elements (p = {semi_parameter_km} km, w = {w})"
+            )));
+        }
+        let gm = frame.try_gm()?;
+
+        let rmag = semi_parameter_km / w;
+        let s2 = 1.0 + h * h + k * k;
+        let alpha2 = h * h - k * k;
+        let hk = h * k;
+        let sqrt_gm_p = (gm / semi_parameter_km).sqrt();
+
+        Ok(Self::cartesian(
+            rmag / s2 * (cos_l + alpha2 * cos_l + 2.0 * hk * sin_l),
+            rmag / s2 * (sin_l - alpha2 * sin_l + 2.0 * hk * cos_l),
+            2.0 * rmag / s2 * (h * sin_l - k * cos_l),
+            -sqrt_gm_p / s2
+                * (sin_l + alpha2 * sin_l - 2.0 * hk * cos_l + g - 2.0 * f * hk + alpha2 * g),
+            -sqrt_gm_p / s2
+                * (-cos_l + alpha2 * cos_l + 2.0 * hk * sin_l - f + 2.0 * g * hk + alpha2 * f),
+            2.0 * sqrt_gm_p / s2 * (h * cos_l + k * sin_l + f * h + g * k),
+            epoch,
+            frame,
+        ))
+    }
+
+    /// Returns this state as a modified equinoctial Vector6 in [km, none, none, none, none, degrees], cf. `modified_equinoctial`.
+    pub fn to_modified_equinoctial_vec(self) -> Vector6<f64> {
+        let (p, q) = self.equinoctial_pq();
+        let (f_hat, g_hat) = equinoctial_basis(p, q);
+        let true_lon = self
+            .radius()
+            .dot(&g_hat)
+            .atan2(self.radius().dot(&f_hat))
+            .to_degrees();
+        Vector6::new(
+            self.hmag_km2_s().powi(2) / self.frame.gm(),
+            self.evec().dot(&f_hat),
+            self.evec().dot(&g_hat),
+            q,
+            p,
+            between_0_360(true_lon),
+        )
+    }
+
+    /// Creates a new Orbit from the Delaunay elements, i.e. the action variables L = √(μa), G = L√(1-e²) and H = G cos i,
+    /// and their conjugate angles, the mean anomaly, the argument of periapsis and the right ascension of the ascending node.
+    ///
+    /// **Units:** km^2/s, km^2/s, km^2/s, degrees, degrees, degrees
+    #[allow(clippy::too_many_arguments)]
+    pub fn delaunay(
+        l_km2_s: f64,
+        g_km2_s: f64,
+        h_km2_s: f64,
+        ma_deg: f64,
+        aop_deg: f64,
+        raan_deg: f64,
+        epoch: Epoch,
+        frame: Frame,
+    ) -> Result<Self, NyxError> {
+        if l_km2_s <= 0.0 || g_km2_s <= 0.0 || g_km2_s > l_km2_s || h_km2_s.abs() > g_km2_s {
+            return Err(NyxError::MathDomain(format!(
+                "invalid Delaunay elements (L = {l_km2_s}, G = {g_km2_s}, H = {h_km2_s})"
+            )));
+        }
+        let gm = frame.try_gm()?;
+        let ecc = (1.0 - (g_km2_s / l_km2_s).powi(2)).max(0.0).sqrt();
+        Self::keplerian_mean_anomaly(
+            l_km2_s.powi(2) / gm,
+            ecc,
+            (h_km2_s / g_km2_s).acos().to_degrees(),
+            raan_deg,
+            aop_deg,
+            ma_deg,
+            epoch,
+            frame,
+        )
+    }
+
+    /// Returns the Delaunay L element, √(μa), in km^2/s
+    pub fn delaunay_l_km2_s(&self) -> f64 {
+        (self.frame.gm() * self.sma_km()).sqrt()
+    }
+
+    /// Returns the Delaunay G element, i.e. the norm of the orbital momentum, in km^2/s
+    pub fn delaunay_g_km2_s(&self) -> f64 {
+        self.hmag_km2_s()
+    }
+
+    /// Returns the Delaunay H element, i.e. the Z component of the orbital momentum, in km^2/s
+    pub fn delaunay_h_km2_s(&self) -> f64 {
+        self.hz_km2_s()
+    }
+
+    /// Returns this state as a Delaunay Vector6 in [km^2/s, km^2/s, km^2/s, degrees, degrees, degrees], cf. `delaunay`.
+    pub fn to_delaunay_vec(self) -> Vector6<f64> {
+        Vector6::new(
+            self.delaunay_l_km2_s(),
+            self.delaunay_g_km2_s(),
+            self.delaunay_h_km2_s(),
+            self.ma_deg(),
+            self.aop_deg(),
+            self.raan_deg(),
+        )
+    }
+
+    /// Creates a new Orbit from its spherical elements: the radius magnitude, right ascension and declination of the position,
+    /// and the velocity magnitude, azimuth (from north, positive towards east) and flight path angle (from the local horizontal).
+    ///
+    /// **Units:** km, degrees, degrees, km/s, degrees, degrees
+    #[allow(clippy::too_many_arguments)]
+    pub fn spherical(
+        rmag_km: f64,
+        right_ascension_deg: f64,
+        declination_deg: f64,
+        vmag_km_s: f64,
+        azimuth_deg: f64,
+        fpa_deg: f64,
+        epoch: Epoch,
+        frame: Frame,
+    ) -> Self {
+        let (sin_ra, cos_ra) = right_ascension_deg.to_radians().sin_cos();
+        let (sin_dec, cos_dec) = declination_deg.to_radians().sin_cos();
+        let (sin_az, cos_az) = azimuth_deg.to_radians().sin_cos();
+        let (sin_fpa, cos_fpa) = fpa_deg.to_radians().sin_cos();
+
+        let r_hat = Vector3::new(cos_dec * cos_ra, cos_dec * sin_ra, sin_dec);
+        let east = Vector3::new(-sin_ra, cos_ra, 0.0);
+        let north = Vector3::new(-sin_dec * cos_ra, -sin_dec * sin_ra, cos_dec);
+
+        let radius = rmag_km * r_hat;
+        let velocity = vmag_km_s * (sin_fpa * r_hat + cos_fpa * (cos_az * north + sin_az * east));
+        Self::cartesian(
+            radius[0],
+            radius[1],
+            radius[2],
+            velocity[0],
+            velocity[1],
+            velocity[2],
+            epoch,
+            frame,
+        )
+    }
+
+    /// Returns the azimuth of the velocity in degrees, measured in the local horizontal plane from north and positive towards east
+    pub fn velocity_azimuth_deg(&self) -> f64 {
+        let (sin_ra, cos_ra) = self.right_ascension_deg().to_radians().sin_cos();
+        let (sin_dec, cos_dec) = self.declination_deg().to_radians().sin_cos();
+        let east = Vector3::new(-sin_ra, cos_ra, 0.0);
+        let north = Vector3::new(-sin_dec * cos_ra, -sin_dec * sin_ra, cos_dec);
+        let velocity = self.velocity();
+        between_0_360(velocity.dot(&east).atan2(velocity.dot(&north)).to_degrees())
+    }
+
+    /// Returns this state as a spherical Vector6 in [km, degrees, degrees, km/s, degrees, degrees], cf. `spherical`.
+    pub fn to_spherical_vec(self) -> Vector6<f64> {
+        Vector6::new(
+            self.rmag_km(),
+            self.right_ascension_deg(),
+            self.declination_deg(),
+            self.vmag_km_s(),
+            self.velocity_azimuth_deg(),
+            self.fpa_deg(),
+        )
+    }
+
+    /// Only updates the Cartesian state from the provided orbit, leaving the STM untouched
+    fn set_cartesian_from(&mut self, other: &Self) {
+        self.x_km = other.x_km;
+        self.y_km = other.y_km;
+        self.z_km = other.z_km;
+        self.vx_km_s = other.vx_km_s;
+        self.vy_km_s = other.vy_km_s;
+        self.vz_km_s = other.vz_km_s;
+    }
+
+    /// Sets one of the equinoctial elements (or the mean longitude), keeping the other equinoctial elements constant
+    pub(crate) fn set_equinoctial_element(
+        &mut self,
+        param: StateParameter,
+        val: f64,
+    ) -> Result<(), NyxError> {
+        let mut el = self.to_equinoctial_vec();
+        match param {
+            StateParameter::EquinoctialH => el[1] = val,
+            StateParameter::EquinoctialK => el[2] = val,
+            StateParameter::EquinoctialP => el[3] = val,
+            StateParameter::EquinoctialQ => el[4] = val,
+            StateParameter::MeanLongitude => el[5] = val,
+            _ => {
+                return Err(NyxError::StateParameterUnavailable(
+                    param,
+                    "not an equinoctial element".to_string(),
+                ))
+            }
+        }
+        let me = Self::equinoctial(
+            el[0], el[1], el[2], el[3], el[4], el[5], self.epoch, self.frame,
+        )?;
+        self.set_cartesian_from(&me);
+        Ok(())
+    }
+
+    /// Sets one of the Delaunay actions, keeping the other Delaunay elements constant
+    pub(crate) fn set_delaunay_element(
+        &mut self,
+        param: StateParameter,
+        val: f64,
+    ) -> Result<(), NyxError> {
+        let mut el = self.to_delaunay_vec();
+        match param {
+            StateParameter::DelaunayL => el[0] = val,
+            StateParameter::DelaunayG => el[1] = val,
+            StateParameter::DelaunayH => el[2] = val,
+            _ => {
+                return Err(NyxError::StateParameterUnavailable(
+                    param,
+                    "not a Delaunay action".to_string(),
+                ))
+            }
+        }
+        let me = Self::delaunay(
+            el[0], el[1], el[2], el[3], el[4], el[5], self.epoch, self.frame,
+        )?;
+        self.set_cartesian_from(&me);
+        Ok(())
+    }
+
+    /// Sets one of the spherical angles, keeping the other spherical elements constant
+    pub(crate) fn set_spherical_element(
+        &mut self,
+        param: StateParameter,
+        val: f64,
+    ) -> Result<(), NyxError> {
+        let mut el = self.to_spherical_vec();
+        match param {
+            StateParameter::RightAscension => el[1] = val,
+            StateParameter::Declination => el[2] = val,
+            StateParameter::VelocityAzimuth => el[4] = val,
+            StateParameter::FlightPathAngle => el[5] = val,
+            _ => {
+                return Err(NyxError::StateParameterUnavailable(
+                    param,
+                    "not a spherical angle".to_string(),
+                ))
+            }
+        }
+        let me = Self::spherical(
+            el[0], el[1], el[2], el[3], el[4], el[5], self.epoch, self.frame,
+        );
+        self.set_cartesian_from(&me);
+        Ok(())
+    }
+}
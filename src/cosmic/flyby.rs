@@ -0,0 +1,266 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::{BPlaneTarget, Frame, Orbit};
+use crate::linalg::Vector3;
+use crate::time::Epoch;
+use crate::NyxError;
+use std::f64::consts::PI;
+use std::fmt;
+
+/// Maximum number of bisections to find the radius of periapsis of a powered flyby
+const MAX_ITER: usize = 200;
+
+/// Designs a gravity assist, possibly powered, from the incoming and outgoing hyperbolic excess velocities with respect to the flyby body.
+///
+/// The excess velocities must be expressed in an inertial frame centered on the flyby body (e.g. the planet's J2000 frame),
+/// since the B-Plane is computed with respect to the Z axis of that frame.
+#[derive(Copy, Clone, Debug)]
+pub struct Flyby {
+    /// Incoming hyperbolic excess velocity, in km/s
+    pub vinf_in_km_s: Vector3<f64>,
+    /// Outgoing hyperbolic excess velocity, in km/s
+    pub vinf_out_km_s: Vector3<f64>,
+    /// Frame of the flyby body
+    pub frame: Frame,
+}
+
+/// The flyby which achieves the requested turn, with a tangential impulsive maneuver at periapsis if the excess velocities differ.
+#[derive(Copy, Clone, Debug)]
+pub struct FlybySolution {
+    /// Radius of periapsis, in km
+    pub periapsis_km: f64,
+    /// Angle between the incoming and outgoing excess velocities, in degrees
+    pub turn_angle_deg: f64,
+    /// Eccentricity of the incoming hyperbola
+    pub ecc_in: f64,
+    /// Eccentricity of the outgoing hyperbola
+    pub ecc_out: f64,
+    /// Speed at periapsis on the incoming hyperbola, in km/s
+    pub vp_in_km_s: f64,
+    /// Speed at periapsis on the outgoing hyperbola, in km/s
+    pub vp_out_km_s: f64,
+    /// Unit vector of the B vector of the incoming hyperbola
+    pub b_hat: Vector3<f64>,
+    /// Magnitude of the B vector (i.e. the impact parameter) of the incoming hyperbola, in km
+    pub b_mag_km: f64,
+    /// The B-Plane target of the incoming hyperbola, to be used with `try_achieve_b_plane`
+    pub b_plane_target: BPlaneTarget,
+    /// Incoming hyperbolic excess velocity, in km/s
+    pub vinf_in_km_s: Vector3<f64>,
+    /// Frame of the flyby body
+    pub frame: Frame,
+}
+
+impl Flyby {
+    /// Initializes a new flyby, the frame must have a gravitational parameter.
+    pub fn new(
+        vinf_in_km_s: Vector3<f64>,
+        vinf_out_km_s: Vector3<f64>,
+        frame: Frame,
+    ) -> Result<Self, NyxError> {
+        frame.try_gm()?;
+        if vinf_in_km_s.norm() < f64::EPSILON || vinf_out_km_s.norm() < f64::EPSILON {
+            return Err(NyxError::NotHyperbolic(
+                "Flyby requires non zero excess velocities".to_string(),
+            ));
+        }
+        Ok(Self {
+            vinf_in_km_s,
+            vinf_out_km_s,
+            frame,
+        })
+    }
+
+    /// Initializes a flyby from the heliocentric (or any other central body) velocities of the spacecraft before and after the flyby and the state of the flyby body.
+    pub fn from_heliocentric(
+        sc_vel_in_km_s: Vector3<f64>,
+        sc_vel_out_km_s: Vector3<f64>,
+        body: &Orbit,
+        frame: Frame,
+    ) -> Result<Self, NyxError> {
+        Self::new(
+            sc_vel_in_km_s - body.velocity(),
+            sc_vel_out_km_s - body.velocity(),
+            frame,
+        )
+    }
+
+    /// Returns the angle between the incoming and outgoing excess velocities, in degrees
+    pub fn turn_angle_deg(&self) -> f64 {
+        self.vinf_in_km_s
+            .normalize()
+            .dot(&self.vinf_out_km_s.normalize())
+            .clamp(-1.0, 1.0)
+            .acos()
+            .to_degrees()
+    }
+
+    /// Returns the change in excess velocity magnitude which must be provided by the maneuver at periapsis, in km/s
+    pub fn vinf_change_km_s(&self) -> f64 {
+        self.vinf_out_km_s.norm() - self.vinf_in_km_s.norm()
+    }
+
+    /// Computes the radius of periapsis which achieves the turn, the B-Plane target and the powered flyby maneuver.
+    ///
+    /// The turn angle of a flyby is the sum of the half turns of the incoming and outgoing hyperbolas, i.e. asin(1/e_in) + asin(1/e_out),
+    /// which decreases monotonically with the radius of periapsis, so it is found by bisection.
+    pub fn solve(&self) -> Result<FlybySolution, NyxError> {
+        let gm = self.frame.try_gm()?;
+        let turn_rad = self.turn_angle_deg().to_radians();
+        if !(1e-12..=PI - 1e-12).contains(&turn_rad) {
+            return Err(NyxError::MathDomain(format!(
+                "no flyby can achieve a turn angle of {:.6} deg",
+                turn_rad.to_degrees()
+            )));
+        }
+
+        let vinf_in_sq = self.vinf_in_km_s.norm_squared();
+        let vinf_out_sq = self.vinf_out_km_s.norm_squared();
+        let turn = |rp_km: f64| {
+            (1.0 / (1.0 + rp_km * vinf_in_sq / gm)).asin()
+                + (1.0 / (1.0 + rp_km * vinf_out_sq / gm)).asin()
+        };
+
+        // Bisection in log space since the radius of periapsis may span orders of magnitude
+        let (mut lo, mut hi) = (1e-6_f64.ln(), 1e12_f64.ln());
+        if turn(hi.exp()) > turn_rad || turn(lo.exp()) < turn_rad {
+            return Err(NyxError::MathDomain(format!(
+                "could not bracket the periapsis for a turn angle of {:.6} deg",
+                turn_rad.to_degrees()
+            )));
+        }
+        for _ in 0..MAX_ITER {
+            let mid = 0.5 * (lo + hi);
+            if turn(mid.exp()) > turn_rad {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+            if hi - lo < 1e-15 {
+                break;
+            }
+        }
+        let periapsis_km = (0.5 * (lo + hi)).exp();
+
+        let ecc_in = 1.0 + periapsis_km * vinf_in_sq / gm;
+        let ecc_out = 1.0 + periapsis_km * vinf_out_sq / gm;
+
+        // The outgoing excess velocity is bent away from the B vector
+        let s_hat = self.vinf_in_km_s.normalize();
+        let out_hat = self.vinf_out_km_s.normalize();
+        let b_hat = -(out_hat - out_hat.dot(&s_hat) * s_hat).normalize();
+        let b_mag_km = gm / vinf_in_sq * (ecc_in.powi(2) - 1.0).sqrt();
+
+        // Same B-Plane axes as `BPlane`
+        let t_hat = s_hat.cross(&Vector3::z()).normalize();
+        let r_hat = s_hat.cross(&t_hat);
+        let b_plane_target =
+            BPlaneTarget::from_bt_br(b_mag_km * b_hat.dot(&t_hat), b_mag_km * b_hat.dot(&r_hat));
+
+        Ok(FlybySolution {
+            periapsis_km,
+            turn_angle_deg: turn_rad.to_degrees(),
+            ecc_in,
+            ecc_out,
+            vp_in_km_s: (vinf_in_sq + 2.0 * gm / periapsis_km).sqrt(),
+            vp_out_km_s: (vinf_out_sq + 2.0 * gm / periapsis_km).sqrt(),
+            b_hat,
+            b_mag_km,
+            b_plane_target,
+            vinf_in_km_s: self.vinf_in_km_s,
+            frame: self.frame,
+        })
+    }
+}
+
+impl fmt::Display for Flyby {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Flyby of {}: v_inf in = {:.3} km/s\tv_inf out = {:.3} km/s\tturn angle = {:.3} deg",
+            self.frame,
+            self.vinf_in_km_s.norm(),
+            self.vinf_out_km_s.norm(),
+            self.turn_angle_deg()
+        )
+    }
+}
+
+impl FlybySolution {
+    /// Returns the magnitude of the tangential maneuver at periapsis, in km/s
+    pub fn dv_km_s(&self) -> f64 {
+        (self.vp_out_km_s - self.vp_in_km_s).abs()
+    }
+
+    /// Returns the altitude of the periapsis above the equatorial radius of the flyby body, in km
+    pub fn altitude_km(&self) -> Result<f64, NyxError> {
+        Ok(self.periapsis_km - self.frame.try_equatorial_radius()?)
+    }
+
+    /// Returns the eccentricity vector direction and the direction of the velocity at periapsis
+    fn periapsis_axes(&self) -> (Vector3<f64>, Vector3<f64>) {
+        let s_hat = self.vinf_in_km_s.normalize();
+        let sin_half = (1.0 - 1.0 / self.ecc_in.powi(2)).sqrt();
+        let e_hat = s_hat / self.ecc_in + sin_half * self.b_hat;
+        let n_hat = sin_half * s_hat - self.b_hat / self.ecc_in;
+        (e_hat, n_hat)
+    }
+
+    /// Returns the state at periapsis on the incoming hyperbola, i.e. before the maneuver, at the provided epoch
+    pub fn periapsis_in(&self, epoch: Epoch) -> Orbit {
+        let (e_hat, n_hat) = self.periapsis_axes();
+        let radius = self.periapsis_km * e_hat;
+        let velocity = self.vp_in_km_s * n_hat;
+        Orbit::cartesian(
+            radius[0],
+            radius[1],
+            radius[2],
+            velocity[0],
+            velocity[1],
+            velocity[2],
+            epoch,
+            self.frame,
+        )
+    }
+
+    /// Returns the state at periapsis on the outgoing hyperbola, i.e. after the maneuver, at the provided epoch
+    pub fn periapsis_out(&self, epoch: Epoch) -> Orbit {
+        let (_, n_hat) = self.periapsis_axes();
+        let mut orbit = self.periapsis_in(epoch);
+        let vp = self.vp_out_km_s * n_hat;
+        orbit.vx_km_s = vp[0];
+        orbit.vy_km_s = vp[1];
+        orbit.vz_km_s = vp[2];
+        orbit
+    }
+}
+
+impl fmt::Display for FlybySolution {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Flyby of {}: periapsis = {:.3} km\tturn angle = {:.3} deg\tΔv = {:.3} m/s\t{}",
+            self.frame,
+            self.periapsis_km,
+            self.turn_angle_deg,
+            self.dv_km_s() * 1e3,
+            self.b_plane_target
+        )
+    }
+}
@@ -17,11 +17,135 @@
 */
 
 use super::Bodies;
-use crate::time::{Duration, Unit};
+use crate::io::pck::PlanetaryConstants;
 use std::cmp::PartialEq;
 use std::convert::TryFrom;
-use std::f64::consts::PI;
 use std::fmt;
+use std::sync::Mutex;
+use thiserror::Error;
+
+/// Maximum depth of the ephemeris and frame trees of a Cosm: adding a frame or an ephemeris deeper than this returns
+/// a `FrameError::TreeTooDeep`. Each node may have up to 65536 children.
+pub const MAX_TREE_DEPTH: usize = 16;
+
+/// Path of a node in the ephemeris or frame tree of a Cosm, i.e. the position of each child starting from the root.
+/// The root itself has an empty path. This is stored inline so that frames remain `Copy`, which limits the depth of the trees
+/// to `MAX_TREE_DEPTH`.
+#[derive(Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct TreePath {
+    len: u8,
+    nodes: [u16; MAX_TREE_DEPTH],
+}
+
+impl TreePath {
+    /// Builds the path from the position of each child, starting from the root
+    pub fn try_from_slice(path: &[usize]) -> Result<Self, FrameError> {
+        let mut me = Self::default();
+        for pos in path {
+            me = me.child(*pos)?;
+        }
+        Ok(me)
+    }
+
+    /// Returns the path of the child at position `pos` of this node
+    pub fn child(&self, pos: usize) -> Result<Self, FrameError> {
+        if self.len() == MAX_TREE_DEPTH {
+            return Err(FrameError::TreeTooDeep(self.to_vec()));
+        }
+        let pos = u16::try_from(pos).map_err(|_| FrameError::TooManyChildren(self.to_vec()))?;
+        let mut me = *self;
+        me.nodes[me.len()] = pos;
+        me.len += 1;
+        Ok(me)
+    }
+
+    /// Returns the depth of this path, i.e. zero for the root
+    pub fn len(&self) -> usize {
+        usize::from(self.len)
+    }
+
+    /// Returns whether this is the path of the root
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the position of each child from the root
+    pub fn to_vec(&self) -> Vec<usize> {
+        self.nodes[..self.len()]
+            .iter()
+            .map(|pos| usize::from(*pos))
+            .collect()
+    }
+}
+
+impl fmt::Debug for TreePath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self.to_vec())
+    }
+}
+
+/// Orientation of a celestial or geoid frame, which defines its name and whether it is body fixed
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum FrameKind {
+    /// Inertial frame aligned with the J2000 axes
+    #[default]
+    J2000,
+    /// Body fixed frame from the IAU rotation model of the body
+    Iau,
+    /// Earth fixed frame computed from the IERS Earth orientation parameters
+    Itrf,
+    /// Earth fixed frame of a binary PCK
+    Itrf93,
+    /// Moon principal axes frame of a binary PCK
+    MoonPa,
+    /// True Equator Mean Equinox frame of the SGP4 propagator
+    Teme,
+    /// Any other body fixed frame (e.g. defined in a frames TOML file), with the index of the name it was registered with
+    Custom(u16),
+}
+
+lazy_static::lazy_static! {
+    /// Names of the custom frames, stored out of the frames to keep them small and `Copy`
+    static ref CUSTOM_FRAME_NAMES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+}
+
+impl FrameKind {
+    /// Returns the kind of a custom frame registered with this name.
+    /// Names are stored once, so loading the same frame several times returns the same kind.
+    pub(crate) fn custom(name: &str) -> Self {
+        let mut names = CUSTOM_FRAME_NAMES.lock().unwrap();
+        let idx = match names.iter().position(|known| known == name) {
+            Some(idx) => idx,
+            None => {
+                names.push(name.to_string());
+                names.len() - 1
+            }
+        };
+        FrameKind::Custom(u16::try_from(idx).expect("too many custom frame names"))
+    }
+
+    /// Returns the name a custom frame was registered with, if any
+    fn custom_name(idx: u16) -> Option<String> {
+        CUSTOM_FRAME_NAMES
+            .lock()
+            .unwrap()
+            .get(usize::from(idx))
+            .cloned()
+    }
+
+    /// Returns the name of this kind of frame, as used in the frame names
+    fn label(&self) -> &'static str {
+        match self {
+            FrameKind::J2000 => "J2000",
+            FrameKind::Iau => "IAU",
+            FrameKind::Itrf => "ITRF",
+            FrameKind::Itrf93 => "ITRF93",
+            FrameKind::MoonPa => "PA",
+            FrameKind::Teme => "TEME",
+            FrameKind::Custom(_) => "Custom",
+        }
+    }
+}
 
 #[allow(non_snake_case, clippy::upper_case_acronyms)]
 #[derive(Copy, Clone, PartialEq)]
@@ -29,8 +153,9 @@ pub enum Frame {
     /// Any celestial frame which only has a GM (e.g. 3 body frames)
     Celestial {
         gm: f64,
-        ephem_path: [Option<usize>; 3],
-        frame_path: [Option<usize>; 3],
+        ephem_path: TreePath,
+        frame_path: TreePath,
+        kind: FrameKind,
     },
     /// Any Geoid which has a GM, flattening value, etc.
     Geoid {
@@ -38,8 +163,9 @@ pub enum Frame {
         flattening: f64,
         equatorial_radius: f64,
         semi_major_radius: f64,
-        ephem_path: [Option<usize>; 3],
-        frame_path: [Option<usize>; 3],
+        ephem_path: TreePath,
+        frame_path: TreePath,
+        kind: FrameKind,
     },
     /// Velocity, Normal, Cross (called VNB in GMAT)
     VNC,
@@ -49,10 +175,43 @@ pub enum Frame {
     RIC,
     /// SEZ or topocentric frame. The positive horizontal vector S is due south , the positive horizontal vector E is east, and the vector Z normal to the surface of the earth (up) is the third axis.
     SEZ,
+    /// East, North, Up topocentric frame
+    ENU,
+    /// North, East, Down topocentric frame
+    NED,
     /// Used as a placeholder only
     Inertial,
 }
 
+/// Errors when querying the properties of a frame
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum FrameError {
+    /// The requested property is only defined for celestial and geoid frames
+    #[error("{0} is only defined for celestial and geoid frames, not {1}")]
+    NotCelestialOrGeoid(&'static str, Frame),
+    /// The requested property is only defined for geoid frames
+    #[error("{0} is only defined for geoid frames, not {1}")]
+    NotGeoid(&'static str, Frame),
+    /// No rotation model is available for this frame
+    #[error("no rotation model defined for {0}")]
+    NoRotationModel(String),
+    /// The planetary constants do not define the J2 of the central body of this frame
+    #[error("no J2 defined for the central body of {0}")]
+    NoJ2(Frame),
+    /// The operation requires a body fixed frame
+    #[error("{0} is not a body fixed frame")]
+    NotBodyFixed(Frame),
+    /// The operation requires a topocentric frame (SEZ, ENU or NED)
+    #[error("{0} is not a topocentric frame")]
+    NotTopocentric(Frame),
+    /// The ephemeris and frame trees of a Cosm are limited to `MAX_TREE_DEPTH` levels
+    #[error("cannot add a child to the tree node at {0:?}: trees are limited to {MAX_TREE_DEPTH} levels")]
+    TreeTooDeep(Vec<usize>),
+    /// Each node of the ephemeris and frame trees of a Cosm is limited to 65536 children
+    #[error("cannot add a child to the tree node at {0:?}: too many children")]
+    TooManyChildren(Vec<usize>),
+}
+
 impl Frame {
     pub fn is_geoid(&self) -> bool {
         matches!(self, Frame::Geoid { .. })
@@ -62,39 +221,57 @@ impl Frame {
         matches!(self, Frame::Celestial { .. })
     }
 
-    pub fn ephem_path(&self) -> Vec<usize> {
+    /// Returns the path of the ephemeris of this frame, or an error if this isn't a celestial or geoid frame
+    pub fn try_ephem_path(&self) -> Result<Vec<usize>, FrameError> {
         match self {
             Frame::Celestial { ephem_path, .. } | Frame::Geoid { ephem_path, .. } => {
-                let mut path = Vec::with_capacity(3);
-                for p in ephem_path.iter().flatten() {
-                    path.push(*p)
-                }
-                path
+                Ok(ephem_path.to_vec())
             }
-            _ => panic!("Frame is not Celestial or Geoid in kind"),
+            _ => Err(FrameError::NotCelestialOrGeoid("ephemeris path", *self)),
         }
     }
 
-    pub fn frame_path(&self) -> Vec<usize> {
+    /// Returns the path of the ephemeris of this frame, panics if this isn't a celestial or geoid frame
+    pub fn ephem_path(&self) -> Vec<usize> {
+        self.try_ephem_path().unwrap()
+    }
+
+    /// Returns the path of this frame in the frame tree, or an error if this isn't a celestial or geoid frame
+    pub fn try_frame_path(&self) -> Result<Vec<usize>, FrameError> {
         match self {
             Frame::Celestial { frame_path, .. } | Frame::Geoid { frame_path, .. } => {
-                let mut path = Vec::with_capacity(3);
-                for p in frame_path.iter().flatten() {
-                    path.push(*p)
-                }
-                path
+                Ok(frame_path.to_vec())
             }
-            _ => panic!("Frame is not Celestial or Geoid in kind"),
+            _ => Err(FrameError::NotCelestialOrGeoid("frame path", *self)),
         }
     }
 
-    pub fn gm(&self) -> f64 {
+    /// Returns the path of this frame in the frame tree, panics if this isn't a celestial or geoid frame
+    pub fn frame_path(&self) -> Vec<usize> {
+        self.try_frame_path().unwrap()
+    }
+
+    /// Returns the orientation of this frame, or an error if this isn't a celestial or geoid frame
+    pub fn try_kind(&self) -> Result<FrameKind, FrameError> {
         match self {
-            Frame::Celestial { gm, .. } | Frame::Geoid { gm, .. } => *gm,
-            _ => panic!("Frame is not Celestial or Geoid in kind"),
+            Frame::Celestial { kind, .. } | Frame::Geoid { kind, .. } => Ok(*kind),
+            _ => Err(FrameError::NotCelestialOrGeoid("frame kind", *self)),
         }
     }
 
+    /// Returns the gravitational parameter of this frame in km^3/s^2, or an error if this isn't a celestial or geoid frame
+    pub fn try_gm(&self) -> Result<f64, FrameError> {
+        match self {
+            Frame::Celestial { gm, .. } | Frame::Geoid { gm, .. } => Ok(*gm),
+            _ => Err(FrameError::NotCelestialOrGeoid("GM", *self)),
+        }
+    }
+
+    /// Returns the gravitational parameter of this frame in km^3/s^2, panics if this isn't a celestial or geoid frame
+    pub fn gm(&self) -> f64 {
+        self.try_gm().unwrap()
+    }
+
     /// Allows mutuating the GM for this frame
     pub fn gm_mut(&mut self, new_gm: f64) {
         match self {
@@ -103,22 +280,34 @@ impl Frame {
         }
     }
 
-    pub fn equatorial_radius(&self) -> f64 {
+    /// Returns the equatorial radius in km, or an error if this isn't a geoid frame
+    pub fn try_equatorial_radius(&self) -> Result<f64, FrameError> {
         match self {
             Frame::Geoid {
                 equatorial_radius, ..
-            } => *equatorial_radius,
-            _ => panic!("Frame is not Geoid in kind"),
+            } => Ok(*equatorial_radius),
+            _ => Err(FrameError::NotGeoid("equatorial radius", *self)),
         }
     }
 
-    pub fn flattening(&self) -> f64 {
+    /// Returns the equatorial radius in km, panics if this isn't a geoid frame
+    pub fn equatorial_radius(&self) -> f64 {
+        self.try_equatorial_radius().unwrap()
+    }
+
+    /// Returns the flattening, or an error if this isn't a geoid frame
+    pub fn try_flattening(&self) -> Result<f64, FrameError> {
         match self {
-            Frame::Geoid { flattening, .. } => *flattening,
-            _ => panic!("Frame is not Geoid in kind"),
+            Frame::Geoid { flattening, .. } => Ok(*flattening),
+            _ => Err(FrameError::NotGeoid("flattening", *self)),
         }
     }
 
+    /// Returns the flattening, panics if this isn't a geoid frame
+    pub fn flattening(&self) -> f64 {
+        self.try_flattening().unwrap()
+    }
+
     pub fn flattening_mut(&mut self, new_flattening: f64) {
         match self {
             Self::Geoid {
@@ -128,69 +317,97 @@ impl Frame {
         }
     }
 
-    pub fn semi_major_radius(&self) -> f64 {
+    /// Returns the semi major radius in km, or an error if this isn't a geoid frame
+    pub fn try_semi_major_radius(&self) -> Result<f64, FrameError> {
         match self {
             Frame::Geoid {
                 semi_major_radius, ..
-            } => *semi_major_radius,
-            _ => panic!("Frame is not Geoid in kind"),
+            } => Ok(*semi_major_radius),
+            _ => Err(FrameError::NotGeoid("semi major radius", *self)),
         }
     }
 
-    /// Returns the angular velocity for _some_ planets and moons
+    /// Returns the semi major radius in km, panics if this isn't a geoid frame
+    pub fn semi_major_radius(&self) -> f64 {
+        self.try_semi_major_radius().unwrap()
+    }
+
+    /// Returns the angular velocity in rad/s of the body of this frame, from the prime meridian rate of the embedded planetary constants
+    /// (negative for retrograde rotators like Venus), or an error if the body has no rotation model.
+    /// The barycenter of a planetary system intentionally has the rotation of its planet, cf. `PlanetaryConstants::rotation_body`.
+    /// Prefer `Cosm::try_angular_velocity` which computes it from the rotation model of any body with a body fixed frame.
     /// Source for Earth: G. Xu and Y. Xu, "GPS", DOI 10.1007/978-3-662-50367-6_2, 2016 (confirmed by https://hpiers.obspm.fr/eop-pc/models/constants.html)
-    /// Source for everything else: https://en.wikipedia.org/w/index.php?title=Day&oldid=1008298887
-    #[allow(clippy::identity_op)]
+    pub fn try_angular_velocity(&self) -> Result<f64, FrameError> {
+        match Bodies::try_from(self.try_ephem_path()?) {
+            Ok(Bodies::Earth) => Ok(7.292_115_146_706_4e-5),
+            Ok(body) => PlanetaryConstants::embedded()
+                .rotation_body(body.naif_id())
+                .and_then(|constants| constants.angular_velocity_rad_s())
+                .ok_or_else(|| FrameError::NoRotationModel(self.to_string())),
+            Err(_) => Err(FrameError::NoRotationModel(self.to_string())),
+        }
+    }
+
+    /// Returns the unnormalized J2 of the central body of this frame from the embedded planetary constants,
+    /// or an error if it is not defined (e.g. for a barycenter whose planet has no J2).
+    pub fn try_j2(&self) -> Result<f64, FrameError> {
+        Bodies::try_from(self.try_ephem_path()?)
+            .ok()
+            .and_then(|body| PlanetaryConstants::embedded().get(body.naif_id()))
+            .and_then(|constants| constants.j2)
+            .ok_or(FrameError::NoJ2(*self))
+    }
+
+    /// Returns the angular velocity in rad/s of the body of this frame, panics if it has no rotation model.
     pub fn angular_velocity(&self) -> f64 {
-        let period_to_mean_motion = |dur: Duration| -> f64 { 2.0 * PI / dur.to_seconds() };
-        match Bodies::try_from(self.ephem_path()).unwrap() {
-            Bodies::MercuryBarycenter | Bodies::Mercury => {
-                period_to_mean_motion(58 * Unit::Day + 15 * Unit::Hour + 30 * Unit::Minute)
-            }
-            Bodies::VenusBarycenter | Bodies::Venus => period_to_mean_motion(243 * Unit::Day),
-            Bodies::Earth => 7.292_115_146_706_4e-5,
-            Bodies::Luna => {
-                period_to_mean_motion(27 * Unit::Day + 7 * Unit::Hour + 12 * Unit::Minute)
-            }
-            Bodies::MarsBarycenter => period_to_mean_motion(1 * Unit::Day + 37 * Unit::Minute),
-            Bodies::JupiterBarycenter => period_to_mean_motion(9 * Unit::Hour + 56 * Unit::Minute),
-            Bodies::SaturnBarycenter => period_to_mean_motion(10 * Unit::Hour + 30 * Unit::Minute),
-            Bodies::UranusBarycenter => period_to_mean_motion(17 * Unit::Hour + 14 * Unit::Minute),
-            Bodies::NeptuneBarycenter => period_to_mean_motion(16 * Unit::Hour + 6 * Unit::Minute),
-            _ => unimplemented!(),
+        self.try_angular_velocity().unwrap()
+    }
+
+    /// Returns the name of the ephemeris of this frame, or its path if it isn't one of the default bodies (e.g. a spacecraft ephemeris)
+    fn ephem_name(&self) -> String {
+        match Bodies::try_from(self.ephem_path()) {
+            Ok(body) => body.name(),
+            Err(_) => format!("Ephem {:?}", self.ephem_path()),
         }
     }
 
     /// Returns whether this frame is body fixed or not
+    /// The TEME frame is quasi-inertial, so it isn't body fixed.
     pub fn is_body_fixed(&self) -> bool {
-        self.frame_path().len() == 2 || self.frame_path().len() == 3
+        matches!(self.try_kind(), Ok(kind) if !matches!(kind, FrameKind::J2000 | FrameKind::Teme))
+    }
+
+    /// Returns the name of the orientation of this frame, e.g. `J2000` or `IAU Fixed`
+    fn orientation_name(&self) -> &'static str {
+        match self.try_kind() {
+            Ok(FrameKind::Iau) => "IAU Fixed",
+            Ok(kind) => kind.label(),
+            Err(_) => "",
+        }
+    }
+
+    /// Returns whether this is a topocentric frame (SEZ, ENU or NED)
+    pub fn is_topocentric(&self) -> bool {
+        matches!(self, Frame::SEZ | Frame::ENU | Frame::NED)
     }
 }
 
 impl fmt::Display for Frame {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
-            Frame::Celestial { .. } | Frame::Geoid { .. } => {
-                if self.frame_path().len() == 2 {
-                    write!(
-                        f,
-                        "IAU {}",
-                        Bodies::try_from(self.ephem_path()).unwrap().name()
-                    )
-                } else {
-                    write!(
-                        f,
-                        "{} {}",
-                        Bodies::try_from(self.ephem_path()).unwrap().name(),
-                        match self.frame_path().len() {
-                            0 | 1 => "J2000".to_string(),
-                            2 => "IAU Fixed".to_string(),
-                            3 => "IAU Poles Fixed".to_string(),
-                            _ => "Custom".to_string(),
-                        }
-                    )
+            Frame::Celestial { kind, .. } | Frame::Geoid { kind, .. } => match kind {
+                FrameKind::Iau => write!(f, "IAU {}", self.ephem_name()),
+                FrameKind::Itrf | FrameKind::Itrf93 | FrameKind::Teme => {
+                    write!(f, "{}", kind.label())
                 }
-            }
+                FrameKind::MoonPa => write!(f, "Moon PA"),
+                FrameKind::J2000 => write!(f, "{} {}", self.ephem_name(), kind.label()),
+                // Custom frames are named as they were registered in the Cosm, so that this name can be looked up again
+                FrameKind::Custom(idx) => match FrameKind::custom_name(idx) {
+                    Some(name) => write!(f, "{name}"),
+                    None => write!(f, "{} {}", self.ephem_name(), kind.label()),
+                },
+            },
             othframe => write!(f, "{othframe:?}"),
         }
     }
@@ -204,13 +421,8 @@ impl fmt::Debug for Frame {
                 write!(
                     f,
                     "{} {} (μ = {:.06} km^3/s^2)",
-                    Bodies::try_from(self.ephem_path()).unwrap().name(),
-                    match self.frame_path().len() {
-                        0 | 1 => "J2000".to_string(),
-                        2 => "IAU Fixed".to_string(),
-                        3 => "IAU Poles Fixed".to_string(),
-                        _ => "Custom".to_string(),
-                    },
+                    self.ephem_name(),
+                    self.orientation_name(),
                     gm,
                 )
             }
@@ -223,13 +435,8 @@ impl fmt::Debug for Frame {
                 write!(
                     f,
                     "{} {} (μ = {:.06} km^3/s^2 , r = {:.06} km, f = {:.09})",
-                    Bodies::try_from(self.ephem_path()).unwrap().name(),
-                    match self.frame_path().len() {
-                        0 | 1 => "J2000".to_string(),
-                        2 => "IAU Fixed".to_string(),
-                        3 => "IAU Poles Fixed".to_string(),
-                        _ => "Custom".to_string(),
-                    },
+                    self.ephem_name(),
+                    self.orientation_name(),
                     gm,
                     equatorial_radius,
                     flattening,
@@ -239,6 +446,8 @@ impl fmt::Debug for Frame {
             Frame::RCN => write!(f, "RCN"),
             Frame::RIC => write!(f, "RIC"),
             Frame::SEZ => write!(f, "SEZ"),
+            Frame::ENU => write!(f, "ENU"),
+            Frame::NED => write!(f, "NED"),
             Frame::Inertial => write!(f, "Inertial"),
         }
     }
@@ -0,0 +1,254 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::rotations::ParentRotation;
+use crate::io::eop::{EarthOrientationParams, EopEntry};
+use crate::linalg::Matrix3;
+use crate::log::error;
+use crate::time::Epoch;
+use crate::utils::{r1, r2, r3};
+use std::f64::consts::TAU;
+use std::fmt;
+use std::sync::Arc;
+
+const ARCSEC_TO_RAD: f64 = std::f64::consts::PI / 648_000.0;
+/// Arcseconds in a full revolution
+const REVOLUTION_ARCSEC: f64 = 1_296_000.0;
+
+/// Largest terms of the IAU 1980 nutation series (all terms of 0.5 mas and above), from the Explanatory Supplement to the Astronomical Almanac.
+/// Columns are the multipliers of l, l', F, D, Omega, then the longitude coefficients and the obliquity coefficients (constant and rate per century) in units of 0.1 mas.
+#[rustfmt::skip]
+const NUTATION_1980: [([i8; 5], [f64; 2], [f64; 2]); 48] = [
+    ([0, 0, 0, 0, 1], [-171996.0, -174.2], [92025.0, 8.9]),
+    ([0, 0, 2, -2, 2], [-13187.0, -1.6], [5736.0, -3.1]),
+    ([0, 0, 2, 0, 2], [-2274.0, -0.2], [977.0, -0.5]),
+    ([0, 0, 0, 0, 2], [2062.0, 0.2], [-895.0, 0.5]),
+    ([0, 1, 0, 0, 0], [1426.0, -3.4], [54.0, -0.1]),
+    ([1, 0, 0, 0, 0], [712.0, 0.1], [-7.0, 0.0]),
+    ([0, 1, 2, -2, 2], [-517.0, 1.2], [224.0, -0.6]),
+    ([0, 0, 2, 0, 1], [-386.0, -0.4], [200.0, 0.0]),
+    ([1, 0, 2, 0, 2], [-301.0, 0.0], [129.0, -0.1]),
+    ([0, -1, 2, -2, 2], [217.0, -0.5], [-95.0, 0.3]),
+    ([1, 0, 0, -2, 0], [-158.0, 0.0], [-1.0, 0.0]),
+    ([0, 0, 2, -2, 1], [129.0, 0.1], [-70.0, 0.0]),
+    ([-1, 0, 2, 0, 2], [123.0, 0.0], [-53.0, 0.0]),
+    ([1, 0, 0, 0, 1], [63.0, 0.1], [-33.0, 0.0]),
+    ([0, 0, 0, 2, 0], [63.0, 0.0], [-2.0, 0.0]),
+    ([-1, 0, 2, 2, 2], [-59.0, 0.0], [26.0, 0.0]),
+    ([-1, 0, 0, 0, 1], [-58.0, -0.1], [32.0, 0.0]),
+    ([1, 0, 2, 0, 1], [-51.0, 0.0], [27.0, 0.0]),
+    ([2, 0, 0, -2, 0], [48.0, 0.0], [1.0, 0.0]),
+    ([-2, 0, 2, 0, 1], [46.0, 0.0], [-24.0, 0.0]),
+    ([0, 0, 2, 2, 2], [-38.0, 0.0], [16.0, 0.0]),
+    ([2, 0, 2, 0, 2], [-31.0, 0.0], [13.0, 0.0]),
+    ([2, 0, 0, 0, 0], [29.0, 0.0], [-1.0, 0.0]),
+    ([1, 0, 2, -2, 2], [29.0, 0.0], [-12.0, 0.0]),
+    ([0, 0, 2, 0, 0], [26.0, 0.0], [-1.0, 0.0]),
+    ([0, 0, 2, -2, 0], [-22.0, 0.0], [0.0, 0.0]),
+    ([-1, 0, 2, 0, 1], [21.0, 0.0], [-10.0, 0.0]),
+    ([0, 2, 0, 0, 0], [17.0, -0.1], [0.0, 0.0]),
+    ([0, 2, 2, -2, 2], [-16.0, 0.1], [7.0, 0.0]),
+    ([-1, 0, 0, 2, 1], [16.0, 0.0], [-8.0, 0.0]),
+    ([0, 1, 0, 0, 1], [-15.0, 0.0], [9.0, 0.0]),
+    ([1, 0, 0, -2, 1], [-13.0, 0.0], [7.0, 0.0]),
+    ([0, -1, 0, 0, 1], [-12.0, 0.0], [6.0, 0.0]),
+    ([2, 0, -2, 0, 0], [11.0, 0.0], [0.0, 0.0]),
+    ([-1, 0, 2, 2, 1], [-10.0, 0.0], [5.0, 0.0]),
+    ([1, 0, 2, 2, 2], [-8.0, 0.0], [3.0, 0.0]),
+    ([0, -1, 2, 0, 2], [-7.0, 0.0], [3.0, 0.0]),
+    ([0, 0, 2, 2, 1], [-7.0, 0.0], [3.0, 0.0]),
+    ([1, 1, 0, -2, 0], [-7.0, 0.0], [0.0, 0.0]),
+    ([0, 1, 2, 0, 2], [7.0, 0.0], [-3.0, 0.0]),
+    ([-2, 0, 0, 2, 1], [-6.0, 0.0], [3.0, 0.0]),
+    ([0, 0, 0, 2, 1], [-6.0, 0.0], [3.0, 0.0]),
+    ([2, 0, 2, -2, 2], [6.0, 0.0], [-3.0, 0.0]),
+    ([1, 0, 0, 2, 0], [6.0, 0.0], [0.0, 0.0]),
+    ([1, 0, 2, -2, 1], [6.0, 0.0], [-3.0, 0.0]),
+    ([0, 0, 0, -2, 1], [-5.0, 0.0], [3.0, 0.0]),
+    ([0, -1, 2, -2, 1], [-5.0, 0.0], [3.0, 0.0]),
+    ([2, 0, 2, 0, 1], [-5.0, 0.0], [3.0, 0.0]),
+];
+
+/// Mean obliquity of the ecliptic (IAU 1976) in radians, at the provided TT centuries past J2000
+fn mean_obliquity(t: f64) -> f64 {
+    (84_381.448 + t * (-46.815_0 + t * (-0.000_59 + t * 0.001_813))) * ARCSEC_TO_RAD
+}
+
+/// Returns the nutation in longitude and in obliquity (IAU 1980), in radians, at the provided TT centuries past J2000.
+/// Also returns the longitude of the ascending node of the Moon, used in the equation of the equinoxes.
+fn nutation_1980(t: f64) -> (f64, f64, f64) {
+    // Delaunay arguments, in arcseconds
+    let args = [
+        485_866.733 + t * (1325.0 * REVOLUTION_ARCSEC + 715_922.633 + t * (31.310 + t * 0.064)),
+        1_287_099.804 + t * (99.0 * REVOLUTION_ARCSEC + 1_292_581.224 + t * (-0.577 - t * 0.012)),
+        335_778.877 + t * (1342.0 * REVOLUTION_ARCSEC + 295_263.137 + t * (-13.257 + t * 0.011)),
+        1_072_261.307 + t * (1236.0 * REVOLUTION_ARCSEC + 1_105_601.328 + t * (-6.891 + t * 0.019)),
+        450_160.280 + t * (-5.0 * REVOLUTION_ARCSEC - 482_890.539 + t * (7.455 + t * 0.008)),
+    ]
+    .map(|arg| (arg % REVOLUTION_ARCSEC) * ARCSEC_TO_RAD);
+
+    let mut dpsi = 0.0;
+    let mut deps = 0.0;
+    for (mult, psi, eps) in NUTATION_1980.iter() {
+        let arg: f64 = mult
+            .iter()
+            .zip(args.iter())
+            .map(|(m, a)| f64::from(*m) * a)
+            .sum();
+        dpsi += (psi[0] + psi[1] * t) * arg.sin();
+        deps += (eps[0] + eps[1] * t) * arg.cos();
+    }
+
+    (
+        dpsi * 1e-4 * ARCSEC_TO_RAD,
+        deps * 1e-4 * ARCSEC_TO_RAD,
+        args[4],
+    )
+}
+
+/// Precession DCM (IAU 1976) from EME2000 to the mean of date frame, at the provided TT centuries past J2000
+fn precession_1976(t: f64) -> Matrix3<f64> {
+    let zeta = t * (2_306.218_1 + t * (0.301_88 + t * 0.017_998)) * ARCSEC_TO_RAD;
+    let theta = t * (2_004.310_9 + t * (-0.426_65 - t * 0.041_833)) * ARCSEC_TO_RAD;
+    let z = t * (2_306.218_1 + t * (1.094_68 + t * 0.018_203)) * ARCSEC_TO_RAD;
+    r3(-z) * r2(theta) * r3(-zeta)
+}
+
+/// Greenwich mean sidereal time (IAU 1982) in radians, at the provided UT1 centuries past J2000
+pub(crate) fn gmst_1982(t_ut1: f64) -> f64 {
+    let gmst_s = 67_310.548_41
+        + t_ut1 * (876_600.0 * 3_600.0 + 8_640_184.812_866 + t_ut1 * (0.093_104 - t_ut1 * 6.2e-6));
+    (gmst_s / 240.0).to_radians().rem_euclid(TAU)
+}
+
+/// Returns the DCM from EME2000 to the ITRF, using the provided Earth orientation parameters.
+///
+/// This is the equinox based chain of the IERS Conventions: IAU 1976 precession, IAU 1980 nutation, Greenwich apparent sidereal time, and polar motion.
+/// Following the IERS Conventions 2003 (section 5.5.6), the celestial pole offsets with respect to IAU 2000A are applied to the nutation
+/// along with the frame bias and the IAU 2000 precession rate corrections, which brings the accuracy to about ten milliarcseconds.
+pub fn itrf_dcm_from_j2000(epoch: Epoch, eop: &EopEntry) -> Matrix3<f64> {
+    let t = epoch.to_tt_centuries_j2k();
+    let t_ut1 = (epoch.to_mjd_utc_days() + eop.ut1_utc_s / 86_400.0 - 51_544.5) / 36_525.0;
+
+    let precession = precession_1976(t);
+
+    // Nutation, with the frame bias, the precession rate corrections, and the observed celestial pole offsets
+    let eps_mean = mean_obliquity(t);
+    let (dpsi_1980, deps_1980, omega) = nutation_1980(t);
+    let dpsi =
+        dpsi_1980 + (-0.041_775 - 0.299_65 * t + eop.dx_arcsec / eps_mean.sin()) * ARCSEC_TO_RAD;
+    let deps = deps_1980 + (-0.006_819_2 - 0.025_24 * t + eop.dy_arcsec) * ARCSEC_TO_RAD;
+    let eps_true = eps_mean + deps;
+    let nutation = r1(-eps_true) * r3(-dpsi) * r1(eps_mean);
+
+    // Sidereal time, with the equation of the equinoxes
+    let gast = gmst_1982(t_ut1)
+        + dpsi * eps_mean.cos()
+        + (0.002_64 * omega.sin() + 0.000_063 * (2.0 * omega).sin()) * ARCSEC_TO_RAD;
+
+    // Polar motion
+    let polar_motion =
+        r1(-eop.y_pole_arcsec * ARCSEC_TO_RAD) * r2(-eop.x_pole_arcsec * ARCSEC_TO_RAD);
+
+    polar_motion * r3(gast) * nutation * precession
+}
+
+/// Returns the DCM from EME2000 to the True Equator Mean Equinox (TEME) frame of date, used by the SGP4 propagator.
+///
+/// This is the rotation of Vallado's `teme2eci`: IAU 1976 precession, IAU 1980 nutation, and the equation of the equinoxes
+/// without the kinematic terms, such that TEME is the true of date frame rotated by the equation of the equinoxes.
+pub fn teme_dcm_from_j2000(epoch: Epoch) -> Matrix3<f64> {
+    let t = epoch.to_tt_centuries_j2k();
+    let eps_mean = mean_obliquity(t);
+    let (dpsi, deps, _) = nutation_1980(t);
+    let nutation = r1(-(eps_mean + deps)) * r3(-dpsi) * r1(eps_mean);
+    r3(dpsi * eps_mean.cos()) * nutation * precession_1976(t)
+}
+
+/// Orientation of the TEME frame with respect to EME2000, cf. `teme_dcm_from_j2000`.
+#[derive(Copy, Clone, Debug)]
+pub struct TemeRotation;
+
+impl ParentRotation for TemeRotation {
+    fn dcm_to_parent(&self, datetime: Epoch) -> Option<Matrix3<f64>> {
+        Some(teme_dcm_from_j2000(datetime))
+    }
+}
+
+/// Orientation of the ITRF with respect to EME2000, computed from the IERS Earth orientation parameters, cf. `itrf_dcm_from_j2000`.
+#[derive(Clone)]
+pub struct EopRotation {
+    /// The Earth orientation parameters, interpolated at each epoch
+    pub eop: Arc<EarthOrientationParams>,
+}
+
+impl fmt::Debug for EopRotation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "EopRotation with {} entries", self.eop.entries().len())
+    }
+}
+
+impl ParentRotation for EopRotation {
+    fn dcm_to_parent(&self, datetime: Epoch) -> Option<Matrix3<f64>> {
+        match self.eop.at(datetime) {
+            Ok(entry) => Some(itrf_dcm_from_j2000(datetime, &entry)),
+            Err(e) => {
+                error!("{}", e);
+                None
+            }
+        }
+    }
+}
+
+#[test]
+fn test_itrf_vallado() {
+    use crate::linalg::Vector3;
+    // Vallado, Fundamentals of Astrodynamics and Applications, 4th ed., example 3-14
+    let epoch = Epoch::from_gregorian_utc(2004, 4, 6, 7, 51, 28, 386_009_000);
+    let eop = EopEntry {
+        mjd_utc: epoch.to_mjd_utc_days(),
+        x_pole_arcsec: -0.140_682,
+        y_pole_arcsec: 0.333_309,
+        ut1_utc_s: -0.439_961_9,
+        dx_arcsec: 0.0,
+        dy_arcsec: 0.0,
+    };
+    let r_itrf = Vector3::new(-1_033.479_383_0, 7_901.295_275_4, 6_380.356_595_8);
+    let r_gcrf = Vector3::new(5_102.508_958, 6_123.011_401, 6_378.136_928);
+
+    let dcm = itrf_dcm_from_j2000(epoch, &eop);
+    assert!((dcm * dcm.transpose() - Matrix3::identity()).norm() < 1e-12);
+    let err_km = (dcm.transpose() * r_itrf - r_gcrf).norm();
+    // Ten milliarcseconds at this radius is about half a meter
+    assert!(err_km < 5e-4, "error of {} m", err_km * 1e3);
+}
+
+#[test]
+fn test_teme_vallado() {
+    use crate::linalg::Vector3;
+    // Vallado, Fundamentals of Astrodynamics and Applications, 4th ed., example 3-15
+    let epoch = Epoch::from_gregorian_utc(2004, 4, 6, 7, 51, 28, 386_009_000);
+    let r_teme = Vector3::new(5_094.180_162_10, 6_127.644_659_50, 6_380.344_532_70);
+    let r_gcrf = Vector3::new(5_102.508_958, 6_123.011_401, 6_378.136_928);
+
+    let dcm = teme_dcm_from_j2000(epoch);
+    assert!((dcm * dcm.transpose() - Matrix3::identity()).norm() < 1e-12);
+    let err_km = (dcm.transpose() * r_teme - r_gcrf).norm();
+    // Without the frame bias or the celestial pole offsets, the error is about a meter
+    assert!(err_km < 3e-3, "error of {} m", err_km * 1e3);
+}
@@ -0,0 +1,366 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::orbit::compute_mean_to_true_anomaly;
+use super::Orbit;
+use crate::utils::between_pm_180;
+use crate::NyxError;
+use std::f64::consts::{PI, TAU};
+
+/// Unnormalized J2 of the Earth, from the JGM-3 model.
+pub const EARTH_J2: f64 = 1.082_626_683_553_15e-3;
+
+/// Maximum number of iterations of the osculating to mean element conversion
+const MAX_ITER: usize = 50;
+
+/// Tolerance on the eccentric anomaly when solving Kepler's equation, in radians
+const KEPLER_TOL: f64 = 1e-14;
+
+/// Classical orbital elements in kilometers and radians, with the mean anomaly
+#[derive(Copy, Clone, Debug)]
+struct Elements {
+    sma_km: f64,
+    ecc: f64,
+    inc: f64,
+    raan: f64,
+    aop: f64,
+    ma: f64,
+}
+
+impl Elements {
+    fn from_orbit(orbit: &Orbit) -> Self {
+        Self {
+            sma_km: orbit.sma_km(),
+            ecc: orbit.ecc(),
+            inc: orbit.inc_deg().to_radians(),
+            raan: orbit.raan_deg().to_radians(),
+            aop: orbit.aop_deg().to_radians(),
+            ma: orbit.ma_deg().to_radians(),
+        }
+    }
+
+    fn true_anomaly(&self) -> Result<f64, NyxError> {
+        compute_mean_to_true_anomaly(self.ma.rem_euclid(TAU), self.ecc, KEPLER_TOL)
+    }
+
+    fn to_orbit(self, template: &Orbit) -> Result<Orbit, NyxError> {
+        let mut orbit = Orbit::keplerian(
+            self.sma_km,
+            self.ecc,
+            self.inc.to_degrees(),
+            self.raan.to_degrees().rem_euclid(360.0),
+            self.aop.to_degrees().rem_euclid(360.0),
+            self.true_anomaly()?.to_degrees(),
+            template.epoch,
+            template.frame,
+        );
+        orbit.stm = None;
+        Ok(orbit)
+    }
+
+    /// Returns the non singular elements (a, e cos ϖ, e sin ϖ, tan(i/2) cos Ω, tan(i/2) sin Ω, λ) used to iterate.
+    fn to_nonsingular(self) -> [f64; 6] {
+        let lon_peri = self.aop + self.raan;
+        let tan_half_inc = (self.inc / 2.0).tan();
+        [
+            self.sma_km,
+            self.ecc * lon_peri.cos(),
+            self.ecc * lon_peri.sin(),
+            tan_half_inc * self.raan.cos(),
+            tan_half_inc * self.raan.sin(),
+            self.ma + lon_peri,
+        ]
+    }
+
+    fn from_nonsingular(el: [f64; 6]) -> Self {
+        let ecc = el[1].hypot(el[2]);
+        let lon_peri = el[2].atan2(el[1]);
+        let raan = el[4].atan2(el[3]);
+        Self {
+            sma_km: el[0],
+            ecc,
+            inc: 2.0 * el[3].hypot(el[4]).atan(),
+            raan,
+            aop: lon_peri - raan,
+            ma: el[5] - lon_peri,
+        }
+    }
+
+    /// Applies the first order J2 periodic corrections of the Brouwer-Lyddane theory.
+    ///
+    /// Mean to osculating if `sign` is positive, and the first order approximation of the inverse if negative.
+    /// Source: Schaub and Junkins, Analytical Mechanics of Space Systems, appendix F (as implemented in Basilisk).
+    fn brouwer_lyddane(self, j2: f64, radius_km: f64, sign: f64) -> Result<Self, NyxError> {
+        let Self {
+            sma_km: a,
+            ecc: e,
+            inc: i,
+            raan,
+            aop: w,
+            ma: m,
+        } = self;
+        let f = self.true_anomaly()?;
+
+        let (sin_f, cos_f) = f.sin_cos();
+        let cos_i = i.cos();
+        let c2 = cos_i * cos_i;
+        let c4 = c2 * c2;
+        let c6 = c4 * c2;
+        let crit = 1.0 - 5.0 * c2;
+        if crit.abs() < 1e-3 {
+            return Err(NyxError::MathDomain(format!(
+                "Brouwer-Lyddane elements are singular at the critical inclination (i = {} deg)",
+                i.to_degrees()
+            )));
+        }
+
+        let gamma2 = sign * j2 / 2.0 * (radius_km / a).powi(2);
+        let eta = (1.0 - e * e).sqrt();
+        let eta2 = eta * eta;
+        let eta3 = eta2 * eta;
+        let eta6 = eta3 * eta3;
+        let gamma2p = gamma2 / (eta2 * eta2);
+        let a_r = (1.0 + e * cos_f) / eta2;
+        let a_r3 = a_r.powi(3);
+        let a_r2_eta2 = (a_r * eta).powi(2);
+        // Equation of the center, which is short periodic
+        let center = (f - m + PI).rem_euclid(TAU) - PI + e * sin_f;
+
+        let sma_km = a + a
+            * gamma2
+            * ((3.0 * c2 - 1.0) * (a_r3 - 1.0 / eta3)
+                + 3.0 * (1.0 - c2) * a_r3 * (2.0 * w + 2.0 * f).cos());
+
+        let long_period = 1.0 - 11.0 * c2 - 40.0 * c4 / crit;
+        let de1 = gamma2p / 8.0 * e * eta2 * long_period * (2.0 * w).cos();
+        let cos_terms = 3.0 * cos_f + 3.0 * e * cos_f.powi(2) + e * e * cos_f.powi(3);
+        let de = de1
+            + eta2 / 2.0
+                * (gamma2
+                    * ((3.0 * c2 - 1.0) / eta6 * (e * eta + e / (1.0 + eta) + cos_terms)
+                        + 3.0 * (1.0 - c2) / eta6 * (e + cos_terms) * (2.0 * w + 2.0 * f).cos())
+                    - gamma2p
+                        * (1.0 - c2)
+                        * (3.0 * (2.0 * w + f).cos() + (2.0 * w + 3.0 * f).cos()));
+
+        let di = -e * de1 / eta2 / i.tan()
+            + gamma2p / 2.0
+                * cos_i
+                * (1.0 - c2).sqrt()
+                * (3.0 * (2.0 * w + 2.0 * f).cos()
+                    + 3.0 * e * (2.0 * w + f).cos()
+                    + e * (2.0 * w + 3.0 * f).cos());
+
+        let short_sin = 3.0 * (2.0 * w + 2.0 * f).sin()
+            + 3.0 * e * (2.0 * w + f).sin()
+            + e * (2.0 * w + 3.0 * f).sin();
+        let node_long_period = 11.0 + 80.0 * c2 / crit + 200.0 * c4 / crit.powi(2);
+
+        let mean_lon = m + w + raan + gamma2p / 8.0 * eta3 * long_period * (2.0 * w).sin()
+            - gamma2p / 16.0
+                * (2.0 + e * e
+                    - 11.0 * (2.0 + 3.0 * e * e) * c2
+                    - 40.0 * (2.0 + 5.0 * e * e) * c4 / crit
+                    - 400.0 * e * e * c6 / crit.powi(2))
+                * (2.0 * w).sin()
+            + gamma2p / 4.0 * (-6.0 * crit * center + (3.0 - 5.0 * c2) * short_sin)
+            - gamma2p / 8.0 * e * e * cos_i * node_long_period * (2.0 * w).sin()
+            - gamma2p / 2.0 * cos_i * (6.0 * center - short_sin);
+
+        let e_dm = gamma2p / 8.0 * e * eta3 * long_period * (2.0 * w).sin()
+            - gamma2p / 4.0
+                * eta3
+                * (2.0 * (3.0 * c2 - 1.0) * (a_r2_eta2 + a_r + 1.0) * sin_f
+                    + 3.0
+                        * (1.0 - c2)
+                        * ((-a_r2_eta2 - a_r + 1.0) * (2.0 * w + f).sin()
+                            + (a_r2_eta2 + a_r + 1.0 / 3.0) * (2.0 * w + 3.0 * f).sin()));
+
+        let draan = -gamma2p / 8.0 * e * e * cos_i * node_long_period * (2.0 * w).sin()
+            - gamma2p / 2.0 * cos_i * (6.0 * center - short_sin);
+
+        // Lyddane's modification avoids the singularities at zero eccentricity and inclination
+        let d1 = (e + de) * m.sin() + e_dm * m.cos();
+        let d2 = (e + de) * m.cos() - e_dm * m.sin();
+        let ma = d1.atan2(d2);
+        let ecc = d1.hypot(d2);
+        let (sin_hi, cos_hi) = (i / 2.0).sin_cos();
+        let d3 = (sin_hi + cos_hi * di / 2.0) * raan.sin() + sin_hi * draan * raan.cos();
+        let d4 = (sin_hi + cos_hi * di / 2.0) * raan.cos() - sin_hi * draan * raan.sin();
+        let new_raan = d3.atan2(d4);
+        let inc = 2.0 * d3.hypot(d4).min(1.0).asin();
+
+        let elements = Self {
+            sma_km,
+            ecc,
+            inc,
+            raan: new_raan,
+            aop: mean_lon - ma - new_raan,
+            ma,
+        };
+
+        if [sma_km, ecc, inc, new_raan, mean_lon, ma]
+            .iter()
+            .all(|x| x.is_finite())
+        {
+            Ok(elements)
+        } else {
+            Err(NyxError::MathDomain(format!(
+                "Brouwer-Lyddane conversion is singular for {self:?}"
+            )))
+        }
+    }
+}
+
+impl Orbit {
+    /// Returns the J2 and the equatorial radius of the frame used by the mean element theories
+    fn mean_elements_constants(&self) -> Result<(f64, f64), NyxError> {
+        if !self.is_brouwer_short_valid() {
+            return Err(NyxError::MathDomain(format!(
+                "mean elements are not defined for {self:x}"
+            )));
+        }
+        Ok((self.frame.try_j2()?, self.frame.try_equatorial_radius()?))
+    }
+
+    /// Returns an orbit whose Keplerian elements are the Brouwer-Lyddane mean elements of this osculating state.
+    ///
+    /// The mean elements only include the J2 periodic terms, using the J2 and the equatorial radius of the central body of the frame.
+    /// They are first approximated by the reverse first order transformation, and then corrected until the osculating elements
+    /// are matched. The Cartesian state of the returned orbit has no physical meaning: only use its Keplerian elements.
+    pub fn try_brouwer_mean(&self) -> Result<Self, NyxError> {
+        let (j2, radius_km) = self.mean_elements_constants()?;
+        let osc = Elements::from_orbit(self);
+        let osc_ns = osc.to_nonsingular();
+
+        let mut mean = osc.brouwer_lyddane(j2, radius_km, -1.0)?;
+        for _ in 0..MAX_ITER {
+            let guess_ns = mean.brouwer_lyddane(j2, radius_km, 1.0)?.to_nonsingular();
+            let mut mean_ns = mean.to_nonsingular();
+            let mut max_err: f64 = 0.0;
+            for k in 0..6 {
+                let mut delta = osc_ns[k] - guess_ns[k];
+                if k == 5 {
+                    delta = between_pm_180(delta.to_degrees()).to_radians();
+                } else if k == 0 {
+                    // Relative error on the semi major axis
+                    max_err = max_err.max((delta / osc_ns[0]).abs());
+                    mean_ns[k] += delta;
+                    continue;
+                }
+                max_err = max_err.max(delta.abs());
+                mean_ns[k] += delta;
+            }
+            mean = Elements::from_nonsingular(mean_ns);
+            if max_err < 1e-13 {
+                return mean.to_orbit(self);
+            }
+        }
+        Err(NyxError::MaxIterReached(format!(
+            "Brouwer-Lyddane mean elements did not converge after {MAX_ITER} iterations"
+        )))
+    }
+
+    /// Interprets the Keplerian elements of this orbit as Brouwer-Lyddane mean elements, and returns the osculating state.
+    pub fn try_brouwer_mean_to_osculating(&self) -> Result<Self, NyxError> {
+        let (j2, radius_km) = self.mean_elements_constants()?;
+        Elements::from_orbit(self)
+            .brouwer_lyddane(j2, radius_km, 1.0)?
+            .to_orbit(self)
+    }
+
+    /// Returns an orbit whose Keplerian elements are the Kozai mean elements of this osculating state, in the SGP4 convention.
+    ///
+    /// This is not Kozai's mean element theory: these are the Brouwer-Lyddane mean elements where only the semi major axis
+    /// is converted to the Kozai mean motion, as in two-line element sets. Source: Hoots and Roehrich, Spacetrack Report #3, 1980.
+    pub fn try_kozai_mean(&self) -> Result<Self, NyxError> {
+        let mut mean = self.try_brouwer_mean()?;
+        let (j2, radius_km) = self.mean_elements_constants()?;
+        let brouwer_sma_km = mean.sma_km();
+        let d1 = kozai_d1(j2, mean.ecc(), mean.inc_deg());
+        // The Brouwer SMA is the Kozai SMA times (1 + δ)^(2/3), where δ depends on the Kozai SMA
+        let mut sma_km = brouwer_sma_km;
+        for _ in 0..MAX_ITER {
+            let next = brouwer_sma_km / (1.0 + kozai_delta(d1, sma_km / radius_km)).powf(2.0 / 3.0);
+            let converged = (next - sma_km).abs() < 1e-12 * sma_km;
+            sma_km = next;
+            if converged {
+                mean.set_sma_km(sma_km);
+                return Ok(mean);
+            }
+        }
+        Err(NyxError::MaxIterReached(format!(
+            "Kozai mean elements did not converge after {MAX_ITER} iterations"
+        )))
+    }
+
+    /// Interprets the Keplerian elements of this orbit as Kozai mean elements in the SGP4 convention (cf. `try_kozai_mean`), and returns the osculating state.
+    pub fn try_kozai_mean_to_osculating(&self) -> Result<Self, NyxError> {
+        let (j2, radius_km) = self.mean_elements_constants()?;
+        let d1 = kozai_d1(j2, self.ecc(), self.inc_deg());
+        let delta = kozai_delta(d1, self.sma_km() / radius_km);
+        let mut brouwer = *self;
+        brouwer.set_sma_km(self.sma_km() * (1.0 + delta).powf(2.0 / 3.0));
+        brouwer.try_brouwer_mean_to_osculating()
+    }
+
+    /// Returns the Brouwer-Lyddane mean semi major axis in km
+    pub fn try_mean_sma_km(&self) -> Result<f64, NyxError> {
+        Ok(self.try_brouwer_mean()?.sma_km())
+    }
+
+    /// Returns the Brouwer-Lyddane mean eccentricity
+    pub fn try_mean_ecc(&self) -> Result<f64, NyxError> {
+        Ok(self.try_brouwer_mean()?.ecc())
+    }
+
+    /// Returns the Brouwer-Lyddane mean inclination in degrees
+    pub fn try_mean_inc_deg(&self) -> Result<f64, NyxError> {
+        Ok(self.try_brouwer_mean()?.inc_deg())
+    }
+
+    /// Returns the Brouwer-Lyddane mean right ascension of the ascending node in degrees
+    pub fn try_mean_raan_deg(&self) -> Result<f64, NyxError> {
+        Ok(self.try_brouwer_mean()?.raan_deg())
+    }
+
+    /// Returns the Brouwer-Lyddane mean argument of periapsis in degrees
+    pub fn try_mean_aop_deg(&self) -> Result<f64, NyxError> {
+        Ok(self.try_brouwer_mean()?.aop_deg())
+    }
+
+    /// Returns the Brouwer-Lyddane mean argument of latitude (mean anomaly plus argument of periapsis) in degrees
+    pub fn try_mean_aol_deg(&self) -> Result<f64, NyxError> {
+        let mean = self.try_brouwer_mean()?;
+        Ok((mean.ma_deg() + mean.aop_deg()).rem_euclid(360.0))
+    }
+}
+
+/// Returns the numerator of δ in the un-Kozai'ing of the mean motion
+fn kozai_d1(j2: f64, ecc: f64, inc_deg: f64) -> f64 {
+    let cos_i = inc_deg.to_radians().cos();
+    let beta2 = 1.0 - ecc * ecc;
+    0.75 * j2 * (3.0 * cos_i * cos_i - 1.0) / (beta2 * beta2.sqrt())
+}
+
+/// Returns δ such that the Brouwer mean motion is the Kozai mean motion divided by 1 + δ, with the Kozai SMA in equatorial radii
+fn kozai_delta(d1: f64, kozai_sma: f64) -> f64 {
+    let del1 = d1 / kozai_sma.powi(2);
+    let adel = kozai_sma * (1.0 - del1 * del1 - del1 * (1.0 / 3.0 + 134.0 * del1 * del1 / 81.0));
+    d1 / adel.powi(2)
+}
@@ -199,28 +199,21 @@ impl Xb {
                 self.ephemeris_get_names(),
             )),
             Some(root) => {
-                if path.is_empty() {
-                    return Ok(root);
-                }
+                let mut node = root;
                 for pos in path {
-                    if root.children.get(*pos).is_none() {
-                        let hpath: String = path.iter().map(|p| format!("{p}")).collect::<String>();
-                        return Err(NyxError::ObjectNotFound(hpath, self.ephemeris_get_names()));
-                    }
-                }
-
-                // This is absolutely terrible, and there must be a better way to do it, but it's late.
-                match path.len() {
-                    1 => Ok(&self.ephemeris_root.as_ref().unwrap().children[path[0]]),
-                    2 => Ok(
-                        &self.ephemeris_root.as_ref().unwrap().children[path[0]].children[path[1]],
-                    ),
-                    3 => Ok(
-                        &self.ephemeris_root.as_ref().unwrap().children[path[0]].children[path[1]]
-                            .children[path[2]],
-                    ),
-                    _ => unimplemented!(),
+                    node = match node.children.get(*pos) {
+                        Some(child) => child,
+                        None => {
+                            let hpath: String =
+                                path.iter().map(|p| format!("{p}")).collect::<String>();
+                            return Err(NyxError::ObjectNotFound(
+                                hpath,
+                                self.ephemeris_get_names(),
+                            ));
+                        }
+                    };
                 }
+                Ok(node)
             }
         }
     }
@@ -308,14 +301,33 @@ pub use self::orbit::*;
 mod orbitdual;
 pub use self::orbitdual::*;
 
+// Re-Export the alternative element sets
+mod elements;
+
+// Re-Export the mean elements
+mod mean_elements;
+pub use self::mean_elements::EARTH_J2;
+
+// Re-Export the orbit covariance
+mod covariance;
+pub use self::covariance::*;
+
 // Re-Export B Plane
 mod bplane;
 pub use self::bplane::*;
 
+// Re-Export the flyby designer
+mod flyby;
+pub use self::flyby::*;
+
 // Re-Export spacecraft
 mod spacecraft;
 pub use self::spacecraft::*;
 
+// Re-Export the attitude
+mod attitude;
+pub use self::attitude::*;
+
 // Re-Export frames
 mod frames;
 pub use self::frames::*;
@@ -323,10 +335,21 @@ pub use self::frames::*;
 mod rotations;
 pub use self::rotations::*;
 
+// Re-Export the ITRF orientation from IERS EOP
+mod itrf;
+pub use self::itrf::*;
+
 mod cosm;
 mod xb;
 pub use self::cosm::*;
 
+// Re-Export the topocentric frames of surface points
+mod topocentric;
+pub use self::topocentric::*;
+
+/// The conjunction module finds the closest approaches between two trajectories and computes their probability of collision.
+pub mod conjunction;
+
 /// The eclipse module allows finding eclipses and (conversely) visibility between a state and another one (e.g. a planet or the Sun).
 pub mod eclipse;
 
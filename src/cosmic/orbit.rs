@@ -18,7 +18,7 @@
 
 use super::Cosm;
 use super::State;
-use super::{BPlane, Frame};
+use super::{BPlane, Frame, FrameKind, TreePath};
 use crate::io::orbit::OrbitSerde;
 use crate::io::{
     epoch_from_str, epoch_to_str, frame_from_str, frame_to_str, ConfigRepr, Configurable,
@@ -711,7 +711,7 @@ impl Orbit {
                 let c = n.cross(&r);
                 Ok(Matrix3::new(r[0], r[1], r[2], c[0], c[1], c[2], n[0], n[1], n[2]).transpose())
             }
-            Frame::SEZ => {
+            Frame::SEZ | Frame::ENU | Frame::NED => {
                 // From the GMAT MathSpec, page 30 section 2.6.9 and from `Calculate_RFT` in `TopocentricAxes.cpp`, this returns the
                 // rotation matrix from the topocentric frame (SEZ) to body fixed frame.
                 // In the GMAT MathSpec notation, R_{IF} is the DCM from body fixed to inertial. Similarly, R{FT} is from topocentric
@@ -733,6 +733,12 @@ impl Orbit {
                 let mut y_hat = Vector3::new(0.0, 0.0, 1.0).cross(&z_hat);
                 y_hat /= y_hat.norm();
                 let x_hat = y_hat.cross(&z_hat);
+                // The ENU and NED frames are permutations of the SEZ axes
+                let (x_hat, y_hat, z_hat) = match from {
+                    Frame::ENU => (y_hat, -x_hat, z_hat),
+                    Frame::NED => (-x_hat, y_hat, -z_hat),
+                    _ => (x_hat, y_hat, z_hat),
+                };
                 Ok(Matrix3::new(
                     x_hat[0], y_hat[0], z_hat[0], x_hat[1], y_hat[1], z_hat[1], x_hat[2], y_hat[2],
                     z_hat[2],
@@ -1192,9 +1198,9 @@ impl Orbit {
                 let ta = cos_nu.acos();
                 if ta.is_nan() {
                     if cos_nu > 1.0 {
-                        180.0
-                    } else {
                         0.0
+                    } else {
+                        180.0
                     }
                 } else if self.radius().dot(&self.velocity()) < 0.0 {
                     (2.0 * PI - ta).to_degrees()
@@ -1298,14 +1304,14 @@ impl Orbit {
         }
     }
 
-    /// Returns the flight path angle in degrees
+    /// Returns the flight path angle in degrees, i.e. the angle between the velocity and the local horizontal.
+    ///
+    /// This is computed from the Cartesian state, so it is also well defined for circular orbits.
     pub fn fpa_deg(&self) -> f64 {
-        let nu = self.ta_deg().to_radians();
-        let ecc = self.ecc();
-        let denom = (1.0 + 2.0 * ecc * nu.cos() + ecc.powi(2)).sqrt();
-        let sin_fpa = ecc * nu.sin() / denom;
-        let cos_fpa = 1.0 + ecc * nu.cos() / denom;
-        sin_fpa.atan2(cos_fpa).to_degrees()
+        self.radius()
+            .dot(&self.velocity())
+            .atan2(self.hmag_km2_s())
+            .to_degrees()
     }
 
     /// Returns the mean anomaly in degrees
@@ -2071,8 +2077,9 @@ impl State for Orbit {
     fn zeros() -> Self {
         let frame = Frame::Celestial {
             gm: 1.0,
-            ephem_path: [None, None, None],
-            frame_path: [None, None, None],
+            ephem_path: TreePath::default(),
+            frame_path: TreePath::default(),
+            kind: FrameKind::J2000,
         };
 
         Self {
@@ -2149,9 +2156,16 @@ impl State for Orbit {
             StateParameter::BLTOF => Ok(BPlane::new(*self)?.ltof_s.real()),
             StateParameter::C3 => Ok(self.c3_km2_s2()),
             StateParameter::Declination => Ok(self.declination_deg()),
+            StateParameter::DelaunayG => Ok(self.delaunay_g_km2_s()),
+            StateParameter::DelaunayH => Ok(self.delaunay_h_km2_s()),
+            StateParameter::DelaunayL => Ok(self.delaunay_l_km2_s()),
             StateParameter::EccentricAnomaly => Ok(self.ea_deg()),
             StateParameter::Eccentricity => Ok(self.ecc()),
             StateParameter::Energy => Ok(self.energy_km2_s2()),
+            StateParameter::EquinoctialH => Ok(self.equinoctial_h()),
+            StateParameter::EquinoctialK => Ok(self.equinoctial_k()),
+            StateParameter::EquinoctialP => Ok(self.equinoctial_p()),
+            StateParameter::EquinoctialQ => Ok(self.equinoctial_q()),
             StateParameter::FlightPathAngle => Ok(self.fpa_deg()),
             StateParameter::GeodeticHeight => Ok(self.geodetic_height_km()),
             StateParameter::GeodeticLatitude => Ok(self.geodetic_latitude_deg()),
@@ -2163,6 +2177,13 @@ impl State for Orbit {
             StateParameter::HyperbolicAnomaly => self.hyperbolic_anomaly_deg(),
             StateParameter::Inclination => Ok(self.inc_deg()),
             StateParameter::MeanAnomaly => Ok(self.ma_deg()),
+            StateParameter::MeanAoL => self.try_mean_aol_deg(),
+            StateParameter::MeanAoP => self.try_mean_aop_deg(),
+            StateParameter::MeanEccentricity => self.try_mean_ecc(),
+            StateParameter::MeanInclination => self.try_mean_inc_deg(),
+            StateParameter::MeanLongitude => Ok(self.mean_longitude_deg()),
+            StateParameter::MeanRAAN => self.try_mean_raan_deg(),
+            StateParameter::MeanSMA => self.try_mean_sma_km(),
             StateParameter::PeriapsisRadius => Ok(self.periapsis_km()),
             StateParameter::Period => Ok(self.period().to_seconds()),
             StateParameter::RightAscension => Ok(self.right_ascension_deg()),
@@ -2173,6 +2194,7 @@ impl State for Orbit {
             StateParameter::SMA => Ok(self.sma_km()),
             StateParameter::TrueAnomaly => Ok(self.ta_deg()),
             StateParameter::TrueLongitude => Ok(self.tlong_deg()),
+            StateParameter::VelocityAzimuth => Ok(self.velocity_azimuth_deg()),
             StateParameter::VelocityDeclination => Ok(self.velocity_declination_deg()),
             StateParameter::Vmag => Ok(self.vmag_km_s()),
             StateParameter::X => Ok(self.x_km),
@@ -2196,6 +2218,18 @@ impl State for Orbit {
             StateParameter::RAAN => self.set_raan_deg(val),
             StateParameter::SMA => self.set_sma_km(val),
             StateParameter::TrueAnomaly => self.set_ta_deg(val),
+            StateParameter::EquinoctialH
+            | StateParameter::EquinoctialK
+            | StateParameter::EquinoctialP
+            | StateParameter::EquinoctialQ
+            | StateParameter::MeanLongitude => self.set_equinoctial_element(param, val)?,
+            StateParameter::DelaunayG | StateParameter::DelaunayH | StateParameter::DelaunayL => {
+                self.set_delaunay_element(param, val)?
+            }
+            StateParameter::Declination
+            | StateParameter::FlightPathAngle
+            | StateParameter::RightAscension
+            | StateParameter::VelocityAzimuth => self.set_spherical_element(param, val)?,
             StateParameter::X => self.x_km = val,
             StateParameter::Y => self.y_km = val,
             StateParameter::Z => self.z_km = val,
@@ -2305,7 +2339,11 @@ impl Configurable for Orbit {
 ///
 /// If a numerical error occurs during computation, the function may return a MathDomain error. In the case of a
 /// non-converging iterative process, the function will return a MaxIterReached error after 1000 iterations.
-fn compute_mean_to_true_anomaly(ma_radians: f64, ecc: f64, tol: f64) -> Result<f64, NyxError> {
+pub(crate) fn compute_mean_to_true_anomaly(
+    ma_radians: f64,
+    ecc: f64,
+    tol: f64,
+) -> Result<f64, NyxError> {
     let rm = ma_radians;
     if ecc <= 1.0 {
         // Elliptical orbit
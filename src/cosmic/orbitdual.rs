@@ -16,10 +16,11 @@
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
-use super::{Frame, Orbit, ECC_EPSILON};
+use super::{Frame, Orbit, State, ECC_EPSILON};
 use crate::linalg::{Vector3, U7};
 use crate::md::StateParameter;
 use crate::time::Epoch;
+use crate::utils::{between_0_360, between_pm_180};
 use crate::{NyxError, TimeTagged};
 use hyperdual::linalg::norm;
 use hyperdual::{Float, OHyperdual};
@@ -158,6 +159,7 @@ impl OrbitDual {
             StateParameter::TrueLongitude => Ok(self.tlong()),
             StateParameter::FlightPathAngle => Ok(self.fpa()),
             StateParameter::MeanAnomaly => Ok(self.ma()),
+            StateParameter::TrueAnomaly => Ok(self.ta()),
             StateParameter::EccentricAnomaly => Ok(self.ea()),
             StateParameter::GeodeticHeight => Ok(self.geodetic_height()),
             StateParameter::GeodeticLatitude => Ok(self.geodetic_latitude()),
@@ -168,6 +170,21 @@ impl OrbitDual {
             StateParameter::HyperbolicAnomaly => self.hyperbolic_anomaly(),
             StateParameter::SemiParameter => Ok(self.semi_parameter()),
             StateParameter::SemiMinorAxis => Ok(self.semi_minor_axis()),
+            StateParameter::EquinoctialH => Ok(self.equinoctial_h()),
+            StateParameter::EquinoctialK => Ok(self.equinoctial_k()),
+            StateParameter::EquinoctialP => Ok(self.equinoctial_p()),
+            StateParameter::EquinoctialQ => Ok(self.equinoctial_q()),
+            StateParameter::MeanLongitude => Ok(self.mean_longitude()),
+            StateParameter::DelaunayL => Ok(self.delaunay_l()),
+            StateParameter::DelaunayG => Ok(self.delaunay_g()),
+            StateParameter::DelaunayH => Ok(self.delaunay_h()),
+            StateParameter::VelocityAzimuth => Ok(self.velocity_azimuth()),
+            StateParameter::MeanAoL
+            | StateParameter::MeanAoP
+            | StateParameter::MeanEccentricity
+            | StateParameter::MeanInclination
+            | StateParameter::MeanRAAN
+            | StateParameter::MeanSMA => self.mean_element(param),
             _ => Err(NyxError::PartialsUndefined),
         }
     }
@@ -378,14 +395,14 @@ impl OrbitDual {
                 let cos_nu = self.evec().dot(&self.radius()) / (self.ecc().dual * self.rmag().dual);
                 if (cos_nu.real().abs() - 1.0).abs() < EPSILON {
                     // This bug drove me crazy when writing SMD in Go in 2017.
-                    if cos_nu > 1.0 {
+                    if cos_nu.real() > 0.0 {
                         OrbitPartial {
-                            dual: OHyperdual::from(180.0),
+                            dual: OHyperdual::from(0.0),
                             param: StateParameter::TrueAnomaly,
                         }
                     } else {
                         OrbitPartial {
-                            dual: OHyperdual::from(0.0),
+                            dual: OHyperdual::from(180.0),
                             param: StateParameter::TrueAnomaly,
                         }
                     }
@@ -394,7 +411,7 @@ impl OrbitDual {
                     if ta.is_nan() {
                         warn!("TA is NaN");
                         OrbitPartial {
-                            dual: OHyperdual::from(0.0),
+                            dual: OHyperdual::from(if cos_nu.real() > 0.0 { 0.0 } else { 180.0 }),
                             param: StateParameter::TrueAnomaly,
                         }
                     } else if self.radius().dot(&self.velocity()) < 0.0 {
@@ -496,14 +513,12 @@ impl OrbitDual {
 
     /// Returns the flight path angle in degrees
     pub fn fpa(&self) -> OrbitPartial {
-        let nu = self.ta().dual.to_radians();
-        let ecc = self.ecc().dual;
-        let denom =
-            (OHyperdual::from(1.0) + OHyperdual::from(2.0) * ecc * nu.cos() + ecc.powi(2)).sqrt();
-        let sin_fpa = ecc * nu.sin() / denom;
-        let cos_fpa = OHyperdual::from(1.0) + ecc * nu.cos() / denom;
         OrbitPartial {
-            dual: sin_fpa.atan2(cos_fpa).to_degrees(),
+            dual: self
+                .radius()
+                .dot(&self.velocity())
+                .atan2(self.hmag().dual)
+                .to_degrees(),
             param: StateParameter::FlightPathAngle,
         }
     }
@@ -720,6 +735,173 @@ impl OrbitDual {
     }
 }
 
+impl OrbitDual {
+    /// Returns the equinoctial p and q elements, and the f and g unit vectors of the equinoctial frame
+    #[allow(clippy::type_complexity)]
+    fn equinoctial_frame(
+        &self,
+    ) -> (
+        OHyperdual<f64, U7>,
+        OHyperdual<f64, U7>,
+        Vector3<OHyperdual<f64, U7>>,
+        Vector3<OHyperdual<f64, U7>>,
+    ) {
+        let one = OHyperdual::from(1.0);
+        let two = OHyperdual::from(2.0);
+        let hvec = self.hvec();
+        let hmag = self.hmag().dual;
+        let w_z = hvec[2] / hmag;
+        let p = hvec[0] / hmag / (one + w_z);
+        let q = -hvec[1] / hmag / (one + w_z);
+        let denom = one + p * p + q * q;
+        let f_hat = Vector3::new(
+            (one - p * p + q * q) / denom,
+            two * p * q / denom,
+            -two * p / denom,
+        );
+        let g_hat = Vector3::new(
+            two * p * q / denom,
+            (one + p * p - q * q) / denom,
+            two * q / denom,
+        );
+        (p, q, f_hat, g_hat)
+    }
+
+    /// Returns the equinoctial h element, e sin(ω+Ω)
+    pub fn equinoctial_h(&self) -> OrbitPartial {
+        let (_, _, _, g_hat) = self.equinoctial_frame();
+        OrbitPartial {
+            dual: self.evec().dot(&g_hat),
+            param: StateParameter::EquinoctialH,
+        }
+    }
+
+    /// Returns the equinoctial k element, e cos(ω+Ω)
+    pub fn equinoctial_k(&self) -> OrbitPartial {
+        let (_, _, f_hat, _) = self.equinoctial_frame();
+        OrbitPartial {
+            dual: self.evec().dot(&f_hat),
+            param: StateParameter::EquinoctialK,
+        }
+    }
+
+    /// Returns the equinoctial p element, tan(i/2) sin Ω
+    pub fn equinoctial_p(&self) -> OrbitPartial {
+        OrbitPartial {
+            dual: self.equinoctial_frame().0,
+            param: StateParameter::EquinoctialP,
+        }
+    }
+
+    /// Returns the equinoctial q element, tan(i/2) cos Ω
+    pub fn equinoctial_q(&self) -> OrbitPartial {
+        OrbitPartial {
+            dual: self.equinoctial_frame().1,
+            param: StateParameter::EquinoctialQ,
+        }
+    }
+
+    /// Returns the mean longitude (M+ω+Ω) in degrees between 0 and 360.0
+    pub fn mean_longitude(&self) -> OrbitPartial {
+        let one = OHyperdual::from(1.0);
+        let (_, _, f_hat, g_hat) = self.equinoctial_frame();
+        let evec = self.evec();
+        let (h, k) = (evec.dot(&g_hat), evec.dot(&f_hat));
+        let x1 = self.radius().dot(&f_hat);
+        let y1 = self.radius().dot(&g_hat);
+
+        let root = (one - h * h - k * k).sqrt();
+        let beta = one / (one + root);
+        let sma = self.sma().dual;
+        let sin_f = h + ((one - h * h * beta) * y1 - h * k * beta * x1) / (sma * root);
+        let cos_f = k + ((one - k * k * beta) * x1 - h * k * beta * y1) / (sma * root);
+        let ecc_lon = sin_f.atan2(cos_f);
+        let lambda = (ecc_lon + h * ecc_lon.cos() - k * ecc_lon.sin()).to_degrees();
+
+        OrbitPartial {
+            dual: lambda + OHyperdual::from(between_0_360(lambda.real()) - lambda.real()),
+            param: StateParameter::MeanLongitude,
+        }
+    }
+
+    /// Returns the Delaunay L element, √(μa), in km^2/s
+    pub fn delaunay_l(&self) -> OrbitPartial {
+        OrbitPartial {
+            dual: (OHyperdual::from(self.frame.gm()) * self.sma().dual).sqrt(),
+            param: StateParameter::DelaunayL,
+        }
+    }
+
+    /// Returns the Delaunay G element, i.e. the norm of the orbital momentum, in km^2/s
+    pub fn delaunay_g(&self) -> OrbitPartial {
+        OrbitPartial {
+            dual: self.hmag().dual,
+            param: StateParameter::DelaunayG,
+        }
+    }
+
+    /// Returns the Delaunay H element, i.e. the Z component of the orbital momentum, in km^2/s
+    pub fn delaunay_h(&self) -> OrbitPartial {
+        OrbitPartial {
+            dual: self.hvec()[2],
+            param: StateParameter::DelaunayH,
+        }
+    }
+
+    /// Returns the azimuth of the velocity in degrees between 0 and 360.0, from north and positive towards east
+    pub fn velocity_azimuth(&self) -> OrbitPartial {
+        let rho2 = self.x.powi(2) + self.y.powi(2);
+        let rho = rho2.sqrt();
+        let v_east = (self.x * self.vy - self.y * self.vx) / rho;
+        let v_north = (rho2 * self.vz - self.z * (self.x * self.vx + self.y * self.vy))
+            / (rho * self.rmag().dual);
+        let azimuth = v_east.atan2(v_north).to_degrees();
+
+        OrbitPartial {
+            dual: azimuth + OHyperdual::from(between_0_360(azimuth.real()) - azimuth.real()),
+            param: StateParameter::VelocityAzimuth,
+        }
+    }
+
+    /// Returns the requested Brouwer-Lyddane mean element, whose partials are computed by central finite differencing
+    /// of the (iterative) osculating to mean conversion.
+    pub fn mean_element(&self, param: StateParameter) -> Result<OrbitPartial, NyxError> {
+        if !param.is_mean_element() {
+            return Err(NyxError::PartialsUndefined);
+        }
+        let components = [self.x, self.y, self.z, self.vx, self.vy, self.vz];
+        let mut state = [0.0; 6];
+        for (i, comp) in components.iter().enumerate() {
+            state[i] = comp.real();
+        }
+        let orbit_at = |state: &[f64; 6]| {
+            Orbit::cartesian(
+                state[0], state[1], state[2], state[3], state[4], state[5], self.dt, self.frame,
+            )
+        };
+        let value = orbit_at(&state).value(param)?;
+
+        let mut dual = OHyperdual::from(value);
+        for (i, comp) in components.iter().enumerate() {
+            // Perturb positions by a meter and velocities by a millimeter per second
+            let step = if i < 3 { 1e-3 } else { 1e-6 };
+            let mut plus = state;
+            plus[i] += step;
+            let mut minus = state;
+            minus[i] -= step;
+            let mut delta = orbit_at(&plus).value(param)? - orbit_at(&minus).value(param)?;
+            if param.unit() == "deg" {
+                delta = between_pm_180(delta);
+            }
+            // Chain rule: only keep the infinitesimal part of this component
+            dual +=
+                OHyperdual::from(delta / (2.0 * step)) * (*comp - OHyperdual::from(comp.real()));
+        }
+
+        Ok(OrbitPartial { param, dual })
+    }
+}
+
 impl TimeTagged for OrbitDual {
     fn epoch(&self) -> Epoch {
         self.dt
@@ -16,6 +16,7 @@
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
+use crate::io::bpc::Bpc;
 use crate::log::error;
 use crate::na::Matrix3;
 use crate::time::Epoch;
@@ -26,6 +27,7 @@ use std::collections::HashMap;
 use std::fmt;
 use std::io::{Error as IoError, ErrorKind as IoErrorKind};
 use std::str::FromStr;
+use std::sync::Arc;
 
 pub trait ParentRotation: Send + Sync + fmt::Debug {
     fn dcm_to_parent(&self, datetime: Epoch) -> Option<Matrix3<f64>>;
@@ -245,6 +247,27 @@ impl ParentRotation for Euler3AxisDt {
     }
 }
 
+/// Orientation of a body fixed frame (e.g. ITRF93) as defined by a binary PCK, relative to the J2000 parent frame.
+#[derive(Clone, Debug)]
+pub struct BpcRotation {
+    /// The binary PCK data, shared between all of the frames it defines
+    pub bpc: Arc<Bpc>,
+    /// NAIF frame class ID of this frame in the binary PCK
+    pub frame_id: i32,
+}
+
+impl ParentRotation for BpcRotation {
+    fn dcm_to_parent(&self, datetime: Epoch) -> Option<Matrix3<f64>> {
+        match self.bpc.try_dcm_from_j2000(self.frame_id, datetime) {
+            Ok(dcm) => Some(dcm),
+            Err(e) => {
+                error!("{}", e);
+                None
+            }
+        }
+    }
+}
+
 #[test]
 fn test_angle_unit_deser() {
     use std::str::FromStr;
@@ -20,7 +20,7 @@ use nalgebra::Vector3;
 use serde::{Deserialize, Serialize};
 
 use super::eclipse::Cosm;
-use super::{Orbit, State};
+use super::{Attitude, Orbit, State};
 use crate::dynamics::guidance::Thruster;
 use crate::errors::NyxError;
 use crate::io::{orbit_from_str, ConfigRepr, Configurable};
@@ -38,6 +38,9 @@ use crate::io::ConfigError;
 #[cfg(feature = "python")]
 use pyo3::prelude::*;
 
+/// Index of the attitude quaternion in the propagated vector of the spacecraft, right after the STM
+pub(crate) const ATTITUDE_OFFSET: usize = 90;
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[cfg_attr(feature = "python", pyclass)]
 pub enum GuidanceMode {
@@ -79,7 +82,8 @@ impl From<GuidanceMode> for f64 {
 
 /// A spacecraft state, composed of its orbit, its dry and fuel (wet) masses (in kg), its SRP configuration, its drag configuration, its thruster configuration, and its guidance mode.
 ///
-/// Optionally, the spacecraft state can also store the state transition matrix from the start of the propagation until the current time (i.e. trajectory STM, not step-size STM).
+/// Optionally, the spacecraft state can also store the state transition matrix from the start of the propagation until the current time (i.e. trajectory STM, not step-size STM),
+/// and its attitude, which is then propagated with the orbit.
 #[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 #[cfg_attr(feature = "python", pyclass)]
 #[cfg_attr(
@@ -108,6 +112,9 @@ pub struct Spacecraft {
     /// Optionally stores the state transition matrix from the start of the propagation until the current time (i.e. trajectory STM, not step-size STM)
     #[serde(skip)]
     pub stm: Option<OMatrix<f64, Const<9>, Const<9>>>,
+    /// Optional attitude of the spacecraft, which is not part of the STM
+    #[serde(default)]
+    pub attitude: Option<Attitude>,
 }
 
 impl Default for Spacecraft {
@@ -121,6 +128,7 @@ impl Default for Spacecraft {
             thruster: None,
             mode: GuidanceMode::default(),
             stm: None,
+            attitude: None,
         }
     }
 }
@@ -331,6 +339,18 @@ impl Spacecraft {
         me
     }
 
+    /// Returns a copy of the state with the provided attitude
+    pub fn with_attitude(self, attitude: Attitude) -> Self {
+        let mut me = self;
+        me.attitude = Some(attitude);
+        me
+    }
+
+    /// Returns the attitude of this spacecraft, or an error if it isn't set
+    pub fn try_attitude(&self) -> Result<Attitude, NyxError> {
+        self.attitude.ok_or(NyxError::AttitudeUnset)
+    }
+
     /// Returns a copy of the state with a new orbit
     pub fn with_orbit(self, orbit: Orbit) -> Self {
         let mut me = self;
@@ -393,6 +413,7 @@ impl PartialEq for Spacecraft {
             && (self.fuel_mass_kg - other.fuel_mass_kg).abs() < mass_tol
             && self.srp == other.srp
             && self.drag == other.drag
+            && self.attitude == other.attitude
     }
 }
 
@@ -473,7 +494,7 @@ impl fmt::UpperHex for Spacecraft {
 
 impl State for Spacecraft {
     type Size = Const<9>;
-    type VecLength = Const<90>;
+    type VecLength = Const<97>;
 
     fn reset_stm(&mut self) {
         self.orbit.reset_stm();
@@ -485,9 +506,10 @@ impl State for Spacecraft {
     }
 
     /// The vector is organized as such:
-    /// [X, Y, Z, Vx, Vy, Vz, Cr, Cd, Fuel mass, STM(9x9)]
-    fn as_vector(&self) -> Result<OVector<f64, Const<90>>, NyxError> {
-        let mut vector = OVector::<f64, Const<90>>::zeros();
+    /// [X, Y, Z, Vx, Vy, Vz, Cr, Cd, Fuel mass, STM(9x9), q0, q1, q2, q3, wx, wy, wz]
+    /// where the attitude quaternion is scalar first and is only set if the spacecraft has an attitude.
+    fn as_vector(&self) -> Result<OVector<f64, Const<97>>, NyxError> {
+        let mut vector = OVector::<f64, Const<97>>::zeros();
         // Set the orbit state info
         for (i, val) in self.orbit.to_cartesian_vec().iter().enumerate() {
             // Place the orbit state first, then skip three (Cr, Cd, Fuel), then copy orbit STM
@@ -508,17 +530,28 @@ impl State for Spacecraft {
                 vector[idx + Self::Size::dim()] = *stm_val;
             }
         }
+        if let Some(attitude) = self.attitude {
+            for (i, val) in attitude
+                .quaternion()
+                .iter()
+                .chain(attitude.omega_rad_s.iter())
+                .enumerate()
+            {
+                vector[i + ATTITUDE_OFFSET] = *val;
+            }
+        }
         Ok(vector)
     }
 
     /// Vector is expected to be organized as such:
-    /// [X, Y, Z, Vx, Vy, Vz, Cr, Cd, Fuel mass, STM(9x9)]
-    fn set(&mut self, epoch: Epoch, vector: &OVector<f64, Const<90>>) -> Result<(), NyxError> {
+    /// [X, Y, Z, Vx, Vy, Vz, Cr, Cd, Fuel mass, STM(9x9), q0, q1, q2, q3, wx, wy, wz]
+    /// where the attitude is ignored if this spacecraft does not have one.
+    fn set(&mut self, epoch: Epoch, vector: &OVector<f64, Const<97>>) -> Result<(), NyxError> {
         self.set_epoch(epoch);
         let sc_state =
             OVector::<f64, Self::Size>::from_column_slice(&vector.as_slice()[..Self::Size::dim()]);
         let sc_full_stm = OMatrix::<f64, Self::Size, Self::Size>::from_column_slice(
-            &vector.as_slice()[Self::Size::dim()..],
+            &vector.as_slice()[Self::Size::dim()..ATTITUDE_OFFSET],
         );
 
         if self.stm.is_some() {
@@ -544,6 +577,10 @@ impl State for Spacecraft {
         self.srp.cr = sc_state[6];
         self.drag.cd = sc_state[7];
         self.fuel_mass_kg = sc_state[8];
+        if self.attitude.is_some() {
+            let attitude = &vector.as_slice()[ATTITUDE_OFFSET..];
+            self.attitude = Some(Attitude::from_components(&attitude[..4], &attitude[4..]));
+        }
         Ok(())
     }
 
@@ -0,0 +1,227 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::{Cosm, Frame, FrameError, Orbit};
+use crate::linalg::{Matrix3, Matrix6, Vector3};
+use crate::time::Epoch;
+use crate::utils::between_0_360;
+use crate::NyxError;
+use std::fmt;
+
+/// A point fixed on the surface of (or above) a geoid, defined by its geodetic coordinates in a body fixed frame.
+///
+/// This is the origin of the topocentric frames (SEZ, ENU and NED), e.g. for a landing site or a ground antenna.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SurfacePoint {
+    /// Geodetic latitude in degrees
+    pub latitude_deg: f64,
+    /// Longitude in degrees
+    pub longitude_deg: f64,
+    /// Height above the ellipsoid in kilometers
+    pub height_km: f64,
+    /// Body fixed geoid frame of this point
+    pub frame: Frame,
+}
+
+/// Azimuth, elevation and range of an object as seen from a surface point, and their time derivatives.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct AzElRange {
+    pub epoch: Epoch,
+    /// Azimuth in degrees, measured from the North towards the East, between 0 and 360 degrees
+    pub azimuth_deg: f64,
+    /// Elevation above the local horizontal plane, in degrees
+    pub elevation_deg: f64,
+    /// Range in kilometers
+    pub range_km: f64,
+    /// Azimuth rate in degrees per second
+    pub azimuth_rate_deg_s: f64,
+    /// Elevation rate in degrees per second
+    pub elevation_rate_deg_s: f64,
+    /// Range rate in kilometers per second
+    pub range_rate_km_s: f64,
+}
+
+impl SurfacePoint {
+    /// Initializes a new surface point, returns an error if the frame is not a body fixed geoid.
+    pub fn try_new(
+        latitude_deg: f64,
+        longitude_deg: f64,
+        height_km: f64,
+        frame: Frame,
+    ) -> Result<Self, NyxError> {
+        frame.try_flattening()?;
+        if !frame.is_body_fixed() {
+            return Err(FrameError::NotBodyFixed(frame).into());
+        }
+        Ok(Self {
+            latitude_deg,
+            longitude_deg,
+            height_km,
+            frame,
+        })
+    }
+
+    /// Initializes a new surface point, panics if the frame is not a body fixed geoid.
+    pub fn new(latitude_deg: f64, longitude_deg: f64, height_km: f64, frame: Frame) -> Self {
+        Self::try_new(latitude_deg, longitude_deg, height_km, frame).unwrap()
+    }
+
+    /// Returns the position of this point in its body fixed frame, in kilometers.
+    pub fn radius(&self) -> Vector3<f64> {
+        self.to_orbit(Epoch::from_tai_seconds(0.0)).radius()
+    }
+
+    /// Returns this point as a state in its body fixed frame at the provided epoch: its velocity is zero in that frame.
+    pub fn to_orbit(&self, epoch: Epoch) -> Orbit {
+        Orbit::from_altlatlong(
+            self.latitude_deg,
+            self.longitude_deg,
+            self.height_km,
+            0.0,
+            epoch,
+            self.frame,
+        )
+    }
+
+    /// Returns the DCM from the body fixed frame of this point to the requested topocentric frame (SEZ, ENU or NED).
+    pub fn try_dcm_to_topocentric(&self, topo_frame: Frame) -> Result<Matrix3<f64>, NyxError> {
+        let (sin_lat, cos_lat) = self.latitude_deg.to_radians().sin_cos();
+        let (sin_long, cos_long) = self.longitude_deg.to_radians().sin_cos();
+        // Local axes of the ellipsoid normal, expressed in the body fixed frame
+        let east = Vector3::new(-sin_long, cos_long, 0.0);
+        let north = Vector3::new(-sin_lat * cos_long, -sin_lat * sin_long, cos_lat);
+        let up = Vector3::new(cos_lat * cos_long, cos_lat * sin_long, sin_lat);
+
+        let (x_hat, y_hat, z_hat) = match topo_frame {
+            Frame::SEZ => (-north, east, up),
+            Frame::ENU => (east, north, up),
+            Frame::NED => (north, east, -up),
+            _ => return Err(FrameError::NotTopocentric(topo_frame).into()),
+        };
+        // Each row is a topocentric axis
+        Ok(Matrix3::from_rows(&[
+            x_hat.transpose(),
+            y_hat.transpose(),
+            z_hat.transpose(),
+        ]))
+    }
+
+    /// Returns the DCM from the body fixed frame of this point to the requested topocentric frame, panics if the frame isn't topocentric.
+    pub fn dcm_to_topocentric(&self, topo_frame: Frame) -> Matrix3<f64> {
+        self.try_dcm_to_topocentric(topo_frame).unwrap()
+    }
+
+    /// Returns the provided state relative to this point and expressed in the requested topocentric frame (SEZ, ENU or NED).
+    ///
+    /// The velocity is the velocity as seen from the rotating body fixed frame, where this point is fixed.
+    pub fn try_frame_chg(
+        &self,
+        state: &Orbit,
+        topo_frame: Frame,
+        cosm: &Cosm,
+    ) -> Result<Orbit, NyxError> {
+        let dcm3 = self.try_dcm_to_topocentric(topo_frame)?;
+        let fixed = cosm.try_frame_chg(state, self.frame)?;
+
+        let mut dcm = Matrix6::zeros();
+        dcm.fixed_view_mut::<3, 3>(0, 0).copy_from(&dcm3);
+        dcm.fixed_view_mut::<3, 3>(3, 3).copy_from(&dcm3);
+
+        let mut topo = (fixed - self.to_orbit(fixed.epoch)).without_stm();
+        topo.rotate_by(dcm);
+        topo.frame = topo_frame;
+        Ok(topo)
+    }
+
+    /// Returns the provided state in the requested topocentric frame of this point, panics on error.
+    pub fn frame_chg(&self, state: &Orbit, topo_frame: Frame, cosm: &Cosm) -> Orbit {
+        self.try_frame_chg(state, topo_frame, cosm).unwrap()
+    }
+
+    /// Computes the azimuth, elevation and range of the provided state as seen from this point, and their rates.
+    ///
+    /// Source: Vallado, section 4.4.3 and algorithm 27.
+    pub fn try_azimuth_elevation_range(
+        &self,
+        state: &Orbit,
+        cosm: &Cosm,
+    ) -> Result<AzElRange, NyxError> {
+        let rho = self.try_frame_chg(state, Frame::SEZ, cosm)?;
+        Ok(AzElRange::from_sez(&rho))
+    }
+
+    /// Computes the azimuth, elevation and range of the provided state as seen from this point, panics on error.
+    pub fn azimuth_elevation_range(&self, state: &Orbit, cosm: &Cosm) -> AzElRange {
+        self.try_azimuth_elevation_range(state, cosm).unwrap()
+    }
+}
+
+impl fmt::Display for SurfacePoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:.6} deg lat, {:.6} deg long, {:.3} km height ({})",
+            self.latitude_deg, self.longitude_deg, self.height_km, self.frame
+        )
+    }
+}
+
+impl AzElRange {
+    /// Computes the azimuth, elevation and range from a relative state in the SEZ frame.
+    pub fn from_sez(rho: &Orbit) -> Self {
+        let range_km = rho.rmag_km();
+        let range_rate_km_s = rho.radius().dot(&rho.velocity()) / range_km;
+        let horizontal_km2 = rho.x_km.powi(2) + rho.y_km.powi(2);
+
+        let elevation_deg = (rho.z_km / range_km).asin().to_degrees();
+        if horizontal_km2.sqrt() < 1e-6 * range_km {
+            warn!("object nearly overhead (el = {elevation_deg} deg), azimuth may be incorrect");
+        }
+        let azimuth_deg = between_0_360(rho.y_km.atan2(-rho.x_km).to_degrees());
+
+        let azimuth_rate = (rho.vx_km_s * rho.y_km - rho.vy_km_s * rho.x_km) / horizontal_km2;
+        let elevation_rate =
+            (rho.vz_km_s - range_rate_km_s * rho.z_km / range_km) / horizontal_km2.sqrt();
+
+        Self {
+            epoch: rho.epoch,
+            azimuth_deg,
+            elevation_deg,
+            range_km,
+            azimuth_rate_deg_s: azimuth_rate.to_degrees(),
+            elevation_rate_deg_s: elevation_rate.to_degrees(),
+            range_rate_km_s,
+        }
+    }
+}
+
+impl fmt::Display for AzElRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: az = {:.6} deg, el = {:.6} deg, range = {:.3} km (rates: {:.6} deg/s, {:.6} deg/s, {:.6} km/s)",
+            self.epoch,
+            self.azimuth_deg,
+            self.elevation_deg,
+            self.range_km,
+            self.azimuth_rate_deg_s,
+            self.elevation_rate_deg_s,
+            self.range_rate_km_s
+        )
+    }
+}
@@ -0,0 +1,508 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::{ForceModel, MacroModel};
+use crate::cosmic::eclipse::EclipseLocator;
+use crate::cosmic::{Bodies, Cosm, Frame, LightTimeCalc, Orbit, Spacecraft, AU, SPEED_OF_LIGHT};
+use crate::errors::NyxError;
+use crate::linalg::{Const, OMatrix, Vector3, Vector6};
+use crate::time::{Epoch, Unit};
+use std::f64::consts::TAU;
+use std::fmt;
+use std::sync::Arc;
+
+/// A `SurfaceModel` provides the albedo and infrared emissivity of the surface of a celestial body.
+pub trait SurfaceModel: Send + Sync + fmt::Display {
+    /// Returns the albedo at the provided body fixed latitude and longitude in degrees.
+    fn albedo(&self, lat_deg: f64, long_deg: f64, epoch: Epoch) -> f64;
+    /// Returns the infrared emissivity at the provided body fixed latitude and longitude in degrees.
+    fn emissivity(&self, lat_deg: f64, long_deg: f64, epoch: Epoch) -> f64;
+}
+
+/// A surface of constant albedo and emissivity.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct UniformSurface {
+    pub albedo: f64,
+    pub emissivity: f64,
+}
+
+impl fmt::Display for UniformSurface {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "uniform surface (albedo = {}, emissivity = {})",
+            self.albedo, self.emissivity
+        )
+    }
+}
+
+impl SurfaceModel for UniformSurface {
+    fn albedo(&self, _lat_deg: f64, _long_deg: f64, _epoch: Epoch) -> f64 {
+        self.albedo
+    }
+
+    fn emissivity(&self, _lat_deg: f64, _long_deg: f64, _epoch: Epoch) -> f64 {
+        self.emissivity
+    }
+}
+
+/// The latitude dependent albedo and emissivity model of the Earth from Knocke, Ries and Tapley, 1988, "Earth radiation pressure effects on satellites".
+///
+/// Both are second order Legendre polynomials of the sine of the latitude, whose first order term varies with the season.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct KnockeModel {
+    /// Constant term of the albedo
+    pub a0: f64,
+    /// Constant, cosine and sine coefficients of the seasonal first order term of the albedo
+    pub c: [f64; 3],
+    /// Second order term of the albedo
+    pub a2: f64,
+    /// Constant term of the emissivity
+    pub e0: f64,
+    /// Constant, cosine and sine coefficients of the seasonal first order term of the emissivity
+    pub k: [f64; 3],
+    /// Second order term of the emissivity
+    pub e2: f64,
+    /// Reference epoch of the seasonal variations
+    pub ref_epoch: Epoch,
+}
+
+impl KnockeModel {
+    /// Returns the first order Legendre coefficient, given its constant, cosine and sine coefficients, at the provided epoch.
+    fn seasonal(&self, coeffs: &[f64; 3], epoch: Epoch) -> f64 {
+        let angle = TAU * (epoch - self.ref_epoch).to_unit(Unit::Day) / 365.25;
+        coeffs[0] + coeffs[1] * angle.cos() + coeffs[2] * angle.sin()
+    }
+}
+
+impl Default for KnockeModel {
+    /// The coefficients published by Knocke et al., whose reference epoch is the 1981 December solstice.
+    fn default() -> Self {
+        Self {
+            a0: 0.34,
+            c: [0.0, 0.10, 0.0],
+            a2: 0.29,
+            e0: 0.68,
+            k: [0.0, -0.07, 0.0],
+            e2: -0.18,
+            ref_epoch: Epoch::from_gregorian_utc_at_midnight(1981, 12, 22),
+        }
+    }
+}
+
+impl fmt::Display for KnockeModel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Knocke albedo and emissivity")
+    }
+}
+
+impl SurfaceModel for KnockeModel {
+    fn albedo(&self, lat_deg: f64, _long_deg: f64, epoch: Epoch) -> f64 {
+        let sin_lat = lat_deg.to_radians().sin();
+        self.a0
+            + self.seasonal(&self.c, epoch) * sin_lat
+            + self.a2 * 0.5 * (3.0 * sin_lat.powi(2) - 1.0)
+    }
+
+    fn emissivity(&self, lat_deg: f64, _long_deg: f64, epoch: Epoch) -> f64 {
+        let sin_lat = lat_deg.to_radians().sin();
+        self.e0
+            + self.seasonal(&self.k, epoch) * sin_lat
+            + self.e2 * 0.5 * (3.0 * sin_lat.powi(2) - 1.0)
+    }
+}
+
+/// A grid of albedo and emissivity values, e.g. monthly averages of satellite radiation budget data.
+///
+/// The cells are equally spaced in latitude from the South to the North pole and in longitude from -180 to 180 degrees,
+/// and the values are stored latitude band after latitude band.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GriddedSurface {
+    pub num_lat: usize,
+    pub num_long: usize,
+    pub albedo: Vec<f64>,
+    pub emissivity: Vec<f64>,
+}
+
+impl GriddedSurface {
+    /// Initializes a grid, returns an error if the number of values does not match the number of cells.
+    pub fn new(
+        num_lat: usize,
+        num_long: usize,
+        albedo: Vec<f64>,
+        emissivity: Vec<f64>,
+    ) -> Result<Self, NyxError> {
+        if num_lat == 0 || num_long == 0 {
+            return Err(NyxError::MathDomain(
+                "surface grid must have at least one cell".to_string(),
+            ));
+        }
+        for (name, values) in [("albedo", &albedo), ("emissivity", &emissivity)] {
+            if values.len() != num_lat * num_long {
+                return Err(NyxError::MathDomain(format!(
+                    "expected {} {name} values for a {num_lat}x{num_long} grid but got {}",
+                    num_lat * num_long,
+                    values.len()
+                )));
+            }
+        }
+        Ok(Self {
+            num_lat,
+            num_long,
+            albedo,
+            emissivity,
+        })
+    }
+
+    /// Returns the index of the cell containing the provided latitude and longitude in degrees.
+    fn cell(&self, lat_deg: f64, long_deg: f64) -> usize {
+        let lat_idx = (((lat_deg + 90.0) / 180.0 * self.num_lat as f64).floor() as usize)
+            .min(self.num_lat - 1);
+        let long_idx = ((long_deg + 180.0).rem_euclid(360.0) / 360.0 * self.num_long as f64).floor()
+            as usize
+            % self.num_long;
+        lat_idx * self.num_long + long_idx
+    }
+}
+
+impl fmt::Display for GriddedSurface {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}x{} surface grid", self.num_lat, self.num_long)
+    }
+}
+
+impl SurfaceModel for GriddedSurface {
+    fn albedo(&self, lat_deg: f64, long_deg: f64, _epoch: Epoch) -> f64 {
+        self.albedo[self.cell(lat_deg, long_deg)]
+    }
+
+    fn emissivity(&self, lat_deg: f64, long_deg: f64, _epoch: Epoch) -> f64 {
+        self.emissivity[self.cell(lat_deg, long_deg)]
+    }
+}
+
+/// Defines how the absorbed solar flux is emitted back in the infrared.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ThermalEmission {
+    /// The body emits uniformly, i.e. each element emits its emissivity times a quarter of the solar flux, like the Earth.
+    Uniform,
+    /// The body emits as it absorbs, i.e. each sunlit element emits its emissivity times the solar flux it receives, like the Moon.
+    Sunlit,
+}
+
+/// Radiation pressure of the sunlight reflected (albedo) and of the infrared radiation emitted by a celestial body.
+///
+/// The disk of the body seen from the spacecraft is discretized in a central element and rings of elements of equal nadir angle around it, each of which is a Lambertian emitter
+/// (cf. Knocke, Ries and Tapley, 1988). The sunlit fraction of each element accounts for the eclipse locator, e.g. for the shadow of the Moon on the Earth.
+/// The force applies on the SRP area and Cr of the spacecraft, or on the plates of the macro-model if set.
+#[derive(Clone)]
+pub struct AlbedoPressure {
+    /// Body fixed frame of the reflecting and emitting body
+    pub body_frame: Frame,
+    /// Albedo and emissivity of the surface
+    pub surface: Arc<dyn SurfaceModel>,
+    /// Model of the infrared emission
+    pub emission: ThermalEmission,
+    /// solar flux at 1 AU, in W/m^2
+    pub phi: f64,
+    /// Number of rings of surface elements, ring `k` has `6k` elements and ring 0 is the element at nadir
+    pub rings: usize,
+    /// Eclipse locator of the surface elements
+    pub e_loc: EclipseLocator,
+    /// Optional multi-plate geometry of the spacecraft
+    pub macro_model: Option<Arc<MacroModel>>,
+}
+
+impl AlbedoPressure {
+    /// Earth albedo and infrared pressure with the Knocke model and three rings of elements, where the Moon may shadow the Earth.
+    pub fn earth(cosm: Arc<Cosm>) -> Arc<Self> {
+        Arc::new(Self {
+            body_frame: cosm.frame("IAU Earth"),
+            surface: Arc::new(KnockeModel::default()),
+            emission: ThermalEmission::Uniform,
+            phi: 1367.0,
+            rings: 3,
+            e_loc: EclipseLocator::new(
+                cosm.frame("Sun J2000"),
+                vec![cosm.frame("Moon J2000")],
+                cosm.clone(),
+            ),
+            macro_model: None,
+        })
+    }
+
+    /// Moon albedo and infrared pressure with a uniform albedo of 0.12 and three rings of elements, where the Earth may shadow the Moon.
+    pub fn moon(cosm: Arc<Cosm>) -> Arc<Self> {
+        Arc::new(Self {
+            body_frame: cosm.frame("IAU Moon"),
+            surface: Arc::new(UniformSurface {
+                albedo: 0.12,
+                emissivity: 0.88,
+            }),
+            emission: ThermalEmission::Sunlit,
+            phi: 1367.0,
+            rings: 3,
+            e_loc: EclipseLocator::new(
+                cosm.frame("Sun J2000"),
+                vec![cosm.frame("EME2000")],
+                cosm.clone(),
+            ),
+            macro_model: None,
+        })
+    }
+
+    /// Returns a copy of this model with the provided surface model
+    pub fn with_surface(&self, surface: Arc<dyn SurfaceModel>) -> Arc<Self> {
+        let mut me = self.clone();
+        me.surface = surface;
+        Arc::new(me)
+    }
+
+    /// Returns a copy of this model with the provided number of rings of surface elements
+    pub fn with_rings(&self, rings: usize) -> Arc<Self> {
+        let mut me = self.clone();
+        me.rings = rings;
+        Arc::new(me)
+    }
+
+    /// Returns a copy of this model which uses the provided macro-model instead of the SRP area and Cr of the spacecraft
+    pub fn with_macro_model(&self, macro_model: Arc<MacroModel>) -> Arc<Self> {
+        let mut me = self.clone();
+        me.macro_model = Some(macro_model);
+        Arc::new(me)
+    }
+
+    /// Returns the albedo and infrared irradiances in W/m^2 at the spacecraft, along with the unit direction from each surface element
+    /// to the spacecraft in the body fixed frame.
+    pub fn irradiances(&self, orbit: &Orbit) -> Result<Vec<(f64, f64, Vector3<f64>)>, NyxError> {
+        let cosm = &self.e_loc.cosm;
+        let radius_km = self.body_frame.try_equatorial_radius()?;
+        let sc = cosm.try_frame_chg(orbit, self.body_frame)?.radius();
+        let sc_rmag = sc.norm();
+        if sc_rmag <= radius_km {
+            return Err(NyxError::MathDomain(format!(
+                "spacecraft at {sc_rmag} km is below the surface of {}",
+                self.body_frame
+            )));
+        }
+
+        let sun = cosm
+            .try_celestial_state(
+                Bodies::Sun.ephem_path(),
+                orbit.epoch,
+                self.body_frame,
+                LightTimeCalc::None,
+            )?
+            .radius();
+        let sun_unit = sun / sun.norm();
+        // Solar flux at the body in W/m^2
+        let flux = self.phi * (AU / sun.norm()).powi(2);
+
+        // Local axes centered on the sub-spacecraft point
+        let z_hat = sc / sc_rmag;
+        let x_hat = z_hat
+            .cross(&Vector3::z())
+            .try_normalize(1e-12)
+            .unwrap_or_else(Vector3::x);
+        let y_hat = z_hat.cross(&x_hat);
+
+        // Half angle of the visible disk of the body, as seen from the spacecraft
+        let disk_rad = (radius_km / sc_rmag).asin();
+        let ring_rad = disk_rad / (self.rings as f64 + 1.0);
+
+        let mut irradiances = Vec::new();
+        for ring in 0..=self.rings {
+            let num_elements = if ring == 0 { 1 } else { 6 * ring };
+            let nadir_min = ring_rad * ring as f64;
+            let nadir_max = nadir_min + ring_rad;
+            let nadir = if ring == 0 {
+                0.0
+            } else {
+                nadir_min + ring_rad / 2.0
+            };
+            // A Lambertian element of radiance M/pi subtending a solid angle dΩ causes an irradiance of M dΩ / pi
+            let view_factor = 2.0 * (nadir_min.cos() - nadir_max.cos()) / num_elements as f64;
+            // Distance from the spacecraft to the surface along the center of the element
+            let dist_km = sc_rmag * nadir.cos()
+                - (radius_km.powi(2) - (sc_rmag * nadir.sin()).powi(2))
+                    .max(0.0)
+                    .sqrt();
+
+            for idx in 0..num_elements {
+                let azimuth = TAU * (idx as f64 + 0.5) / num_elements as f64;
+                let to_sc_unit = nadir.cos() * z_hat
+                    - nadir.sin() * (azimuth.cos() * x_hat + azimuth.sin() * y_hat);
+                let normal = (sc - dist_km * to_sc_unit) / radius_km;
+
+                let lat_deg = normal[2].asin().to_degrees();
+                let long_deg = normal[1].atan2(normal[0]).to_degrees();
+
+                let cos_sun = normal.dot(&sun_unit);
+                let sunlit_flux = if cos_sun > 0.0 {
+                    let element = Orbit::cartesian(
+                        radius_km * normal[0],
+                        radius_km * normal[1],
+                        radius_km * normal[2],
+                        0.0,
+                        0.0,
+                        0.0,
+                        orbit.epoch,
+                        self.body_frame,
+                    );
+                    let visibility: f64 = self.e_loc.compute(&element).into();
+                    visibility * flux * cos_sun
+                } else {
+                    0.0
+                };
+
+                let albedo = self.surface.albedo(lat_deg, long_deg, orbit.epoch) * sunlit_flux;
+                let infrared = self.surface.emissivity(lat_deg, long_deg, orbit.epoch)
+                    * match self.emission {
+                        ThermalEmission::Uniform => flux / 4.0,
+                        ThermalEmission::Sunlit => sunlit_flux,
+                    };
+
+                irradiances.push((albedo * view_factor, infrared * view_factor, to_sc_unit));
+            }
+        }
+
+        Ok(irradiances)
+    }
+}
+
+impl fmt::Display for AlbedoPressure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "albedo and IR pressure of {} with {} and {} rings",
+            self.body_frame, self.surface, self.rings
+        )?;
+        match &self.macro_model {
+            Some(macro_model) => write!(f, " and {macro_model}"),
+            None => Ok(()),
+        }
+    }
+}
+
+impl ForceModel for AlbedoPressure {
+    fn eom(&self, ctx: &Spacecraft) -> Result<Vector3<f64>, NyxError> {
+        let dcm = self
+            .e_loc
+            .cosm
+            .try_dcm_from_to_in_parts(&self.body_frame, &ctx.orbit.frame, ctx.orbit.epoch)?
+            .0;
+        let dcm_body_to_inertial = match &self.macro_model {
+            Some(macro_model) => Some(macro_model.dcm_body_to_inertial(ctx)?),
+            None => None,
+        };
+
+        let mut force = Vector3::zeros();
+        for (albedo, infrared, to_sc_unit) in self.irradiances(&ctx.orbit)? {
+            // in N/(m^2)
+            let pressure = (albedo + infrared) / SPEED_OF_LIGHT;
+            let direction = dcm * to_sc_unit;
+            force += match (&self.macro_model, &dcm_body_to_inertial) {
+                (Some(macro_model), Some(dcm_body)) => {
+                    macro_model.srp_force_n(dcm_body, &-direction, pressure)
+                }
+                _ => ctx.srp.cr * ctx.srp.area_m2 * pressure * direction,
+            };
+        }
+        // Note the 1e-3 is to convert the force from N to kN
+        Ok(1e-3 * force)
+    }
+
+    /// The partials are computed by central differences, and with respect to the velocity only if the macro-model uses a pointing law.
+    fn dual_eom(
+        &self,
+        ctx: &Spacecraft,
+    ) -> Result<(Vector3<f64>, OMatrix<f64, Const<3>, Const<9>>), NyxError> {
+        const POSITION_STEP_KM: f64 = 1e-3;
+        const VELOCITY_STEP_KM_S: f64 = 1e-6;
+        let force = self.eom(ctx)?;
+
+        let num_partials = match &self.macro_model {
+            Some(macro_model) if ctx.attitude.is_none() && macro_model.pointing.is_some() => 6,
+            _ => 3,
+        };
+
+        let mut grad = OMatrix::<f64, Const<3>, Const<9>>::zeros();
+        for j in 0..num_partials {
+            let step = if j < 3 {
+                POSITION_STEP_KM
+            } else {
+                VELOCITY_STEP_KM_S
+            };
+            let mut delta = Vector6::zeros();
+            delta[j] = step;
+            let mut plus = *ctx;
+            plus.orbit = ctx.orbit + delta;
+            let mut minus = *ctx;
+            minus.orbit = ctx.orbit + -delta;
+            grad.set_column(j, &((self.eom(&plus)? - self.eom(&minus)?) / (2.0 * step)));
+        }
+
+        if self.macro_model.is_none() {
+            // The force is linear in Cr
+            let mut unit_cr = *ctx;
+            unit_cr.srp.cr = 1.0;
+            grad.set_column(6, &self.eom(&unit_cr)?);
+        }
+
+        Ok((force, grad))
+    }
+}
+
+#[cfg(test)]
+mod ut_albedo {
+    use super::*;
+
+    #[test]
+    fn knocke() {
+        let model = KnockeModel::default();
+        let epoch = model.ref_epoch;
+        // At the reference epoch, the northern hemisphere (in winter) is brighter
+        assert!(model.albedo(60.0, 0.0, epoch) > model.albedo(-60.0, 0.0, epoch));
+        // Six months later, the southern hemisphere is brighter
+        let later = epoch + Unit::Day * 182.625;
+        assert!(model.albedo(-60.0, 0.0, later) > model.albedo(60.0, 0.0, later));
+        // The poles are brighter and colder than the equator
+        assert!(model.albedo(90.0, 0.0, epoch) > model.albedo(0.0, 0.0, epoch));
+        assert!(model.emissivity(90.0, 0.0, epoch) < model.emissivity(0.0, 0.0, epoch));
+        assert!((model.albedo(0.0, 0.0, epoch) - (0.34 - 0.145)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn gridded() {
+        let grid = GriddedSurface::new(
+            2,
+            4,
+            vec![0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8],
+            vec![0.9; 8],
+        )
+        .unwrap();
+        let epoch = Epoch::from_gregorian_utc_at_midnight(2020, 1, 1);
+        assert_eq!(grid.albedo(-45.0, -135.0, epoch), 0.1);
+        assert_eq!(grid.albedo(-45.0, 45.0, epoch), 0.3);
+        assert_eq!(grid.albedo(45.0, 135.0, epoch), 0.8);
+        assert_eq!(grid.albedo(90.0, 180.0, epoch), 0.5);
+        assert_eq!(grid.emissivity(10.0, 10.0, epoch), 0.9);
+
+        assert!(GriddedSurface::new(2, 4, vec![0.1; 7], vec![0.9; 8]).is_err());
+    }
+}
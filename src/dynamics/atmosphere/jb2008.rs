@@ -0,0 +1,602 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::utc_day_of_year;
+use crate::io::space_weather::SpaceWeather;
+use crate::time::{Epoch, Unit};
+use crate::NyxError;
+use std::f64::consts::{FRAC_PI_2, LN_10, PI, TAU};
+
+const AVOGADRO: f64 = 6.02257E26;
+const RSTAR: f64 = 8314.32;
+/// Molecular weights of N2, O2, O, Ar, He and H
+const AMW: [f64; 6] = [28.0134, 31.9988, 15.9994, 39.9480, 4.0026, 1.00797];
+/// Thermal diffusion coefficients
+const ALPHA: [f64; 5] = [0.0, 0.0, 0.0, 0.0, -0.38];
+/// Fractional abundances of N2, O2, Ar and He at sea level
+const FRAC: [f64; 4] = [0.78110, 0.20955, 9.3400E-3, 1.2890E-5];
+/// Integration steps in log-altitude below 105 km, below 500 km and above 500 km
+const R1: f64 = 0.010;
+const R2: f64 = 0.025;
+const R3: f64 = 0.075;
+/// Boole's rule weights
+const WT: [f64; 5] = [
+    0.311111111111111,
+    1.422222222222222,
+    0.533333333333333,
+    1.422222222222222,
+    0.311111111111111,
+];
+/// Exospheric density correction above 1000 km
+const CHT: [f64; 4] = [0.22, -0.20E-02, 0.115E-02, -0.211E-05];
+
+/// Inputs of the JB2008 model at a given point and time
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Jb2008Input {
+    /// Modified Julian date in UTC
+    pub mjd_utc: f64,
+    /// Fractional day of the year, 1.0 being January 1st at midnight
+    pub day_of_year: f64,
+    /// Right ascension of the Sun in degrees
+    pub sun_ra_deg: f64,
+    /// Declination of the Sun in degrees
+    pub sun_dec_deg: f64,
+    /// Right ascension of the point in degrees
+    pub ra_deg: f64,
+    /// Latitude of the point in degrees
+    pub lat_deg: f64,
+    /// Altitude of the point in km
+    pub alt_km: f64,
+    /// F10.7 solar flux, one day before the epoch
+    pub f10: f64,
+    /// 81-day centered average of F10.7, one day before the epoch
+    pub f10b: f64,
+    /// S10.7 EUV index, one day before the epoch
+    pub s10: f64,
+    /// 81-day centered average of S10.7, one day before the epoch
+    pub s10b: f64,
+    /// M10.7 proxy, two days before the epoch
+    pub m10: f64,
+    /// 81-day centered average of M10.7, two days before the epoch
+    pub m10b: f64,
+    /// Y10.7 X-ray index, five days before the epoch
+    pub y10: f64,
+    /// 81-day centered average of Y10.7, five days before the epoch
+    pub y10b: f64,
+    /// Temperature change in Kelvin due to the geomagnetic storms
+    pub dst_dtc: f64,
+}
+
+impl Jb2008Input {
+    /// Builds the input at the provided epoch and position from the space weather indices, which must include the JB2008 solar indices.
+    ///
+    /// The storm temperature change is read from the DTC file if loaded, else it is computed from the 3-hour Kp index 6.7 hours before the epoch (Jacchia 1970).
+    #[allow(clippy::too_many_arguments)]
+    pub fn from_space_weather(
+        epoch: Epoch,
+        sun_ra_deg: f64,
+        sun_dec_deg: f64,
+        ra_deg: f64,
+        lat_deg: f64,
+        alt_km: f64,
+        space_weather: &SpaceWeather,
+    ) -> Result<Self, NyxError> {
+        let day_lag_1 = space_weather.solar_indices_at(epoch - Unit::Day * 1)?;
+        let day_lag_2 = space_weather.solar_indices_at(epoch - Unit::Day * 2)?;
+        let day_lag_5 = space_weather.solar_indices_at(epoch - Unit::Day * 5)?;
+        let dst_dtc = if space_weather.has_dtc() {
+            space_weather.dtc_at(epoch)?
+        } else {
+            let kp = space_weather.kp_3h(epoch - Unit::Hour * 6.7)?;
+            28.0 * kp + 0.03 * kp.exp()
+        };
+        let (doy, sec) = utc_day_of_year(epoch);
+
+        Ok(Self {
+            mjd_utc: epoch.to_mjd_utc_days(),
+            day_of_year: doy + sec / 86_400.0,
+            sun_ra_deg,
+            sun_dec_deg,
+            ra_deg,
+            lat_deg,
+            alt_km,
+            f10: day_lag_1.f10,
+            f10b: day_lag_1.f81c,
+            s10: day_lag_1.s10,
+            s10b: day_lag_1.s81c,
+            m10: day_lag_2.m10,
+            m10b: day_lag_2.m81c,
+            y10: day_lag_5.y10,
+            y10b: day_lag_5.y81c,
+            dst_dtc,
+        })
+    }
+}
+
+/// Outputs of the JB2008 model
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Jb2008Output {
+    /// Total mass density in kg/m^3
+    pub rho_kg_m3: f64,
+    /// Exospheric temperature in Kelvin
+    pub t_exo_k: f64,
+    /// Temperature at altitude in Kelvin
+    pub t_k: f64,
+}
+
+/// Computes the Jacchia-Bowman 2008 empirical density model of Bowman et al. (2008), valid from 90 km upward.
+pub fn jb2008(input: &Jb2008Input) -> Jb2008Output {
+    let sat_ra = input.ra_deg.to_radians();
+    let sat_lat = input.lat_deg.to_radians();
+    let sun_ra = input.sun_ra_deg.to_radians();
+    let sun_dec = input.sun_dec_deg.to_radians();
+    let alt = input.alt_km;
+
+    // Nighttime minimum of the global exospheric temperature (Eq. 14)
+    let fn_ = (input.f10b / 240.0).powf(0.25).min(1.0);
+    let fsb = input.f10b * fn_ + input.s10b * (1.0 - fn_);
+    let tsubc = 392.4
+        + 3.227 * fsb
+        + 0.298 * (input.f10 - input.f10b)
+        + 2.259 * (input.s10 - input.s10b)
+        + 0.312 * (input.m10 - input.m10b)
+        + 0.178 * (input.y10 - input.y10b);
+
+    // Diurnal variation (Eq. 15 to 17)
+    let eta = 0.5 * (sat_lat - sun_dec).abs();
+    let theta = 0.5 * (sat_lat + sun_dec).abs();
+    let h = sat_ra - sun_ra;
+    let tau = h - 0.64577182 + 0.10471976 * (h + 0.75049158).sin();
+    let local_solar_time_h = ((h + PI).to_degrees() / 15.0).rem_euclid(24.0);
+    let c = eta.cos().powf(2.5);
+    let s = theta.sin().powf(2.5);
+    let df = s + (c - s) * (0.5 * tau).cos().abs().powi(3);
+    let tsubl = tsubc * (1.0 + 0.31 * df);
+
+    // Local exospheric temperature, with the local solar time and latitude correction and the storm effect
+    let dtclst = dtsub(input.f10, local_solar_time_h, sat_lat, alt);
+    let tinf = tsubl + input.dst_dtc + dtclst;
+
+    // Temperature profile (Eq. 9 to 13)
+    let tsubx = 444.3807 + 0.02385 * tinf - 392.8292 * (-0.0021357 * tinf).exp();
+    let gsubx = 0.054285714 * (tsubx - 183.0);
+    let tc3 = (tinf - tsubx) / FRAC_PI_2;
+    let tc = [tsubx, gsubx, tc3, gsubx / tc3];
+
+    // Barometric equation from 90 to 105 km (Eq. 5)
+    let z1 = 90.0;
+    let z2 = alt.min(105.0);
+    let ambar1 = xambar(z1);
+    let tloc1 = xlocal(z1, &tc);
+    let mut ambar2 = ambar1;
+    let mut tloc2 = tloc1;
+    let mut gravl = grav(z1);
+    let mut ain = ambar1 * gravl / tloc1;
+    let n = steps(z1, z2, R1);
+    let zr = (z2 / z1).powf(1.0 / n as f64);
+    let mut zend = z1;
+    let mut sum2 = 0.0;
+    for _ in 0..n {
+        let mut z = zend;
+        zend = zr * z;
+        let dz = 0.25 * (zend - z);
+        let mut sum1 = WT[0] * ain;
+        for wt in WT.iter().skip(1) {
+            z += dz;
+            ambar2 = xambar(z);
+            tloc2 = xlocal(z, &tc);
+            gravl = grav(z);
+            ain = ambar2 * gravl / tloc2;
+            sum1 += wt * ain;
+        }
+        sum2 += dz * sum1;
+    }
+    let fact1 = 1000.0 / RSTAR;
+    let rho = 3.46E-6 * ambar2 * tloc1 * (-fact1 * sum2).exp() / ambar1 / tloc2;
+
+    // Number densities at the top of the mixed region (Eq. 2 to 4)
+    let anm = AVOGADRO * rho;
+    let an = anm / ambar2;
+    let fact2 = anm / 28.960;
+    let mut aln = [0.0; 6];
+    aln[0] = (FRAC[0] * fact2).ln();
+    aln[3] = (FRAC[2] * fact2).ln();
+    aln[4] = (FRAC[3] * fact2).ln();
+    aln[1] = (fact2 * (1.0 + FRAC[1]) - an).ln();
+    aln[2] = (2.0 * (an - fact2)).ln();
+
+    let t_k = if alt <= 105.0 {
+        // Negligible hydrogen
+        aln[5] = aln[4] - 25.0;
+        tloc2
+    } else {
+        // Diffusion equation above 105 km (Eq. 6)
+        let z3 = alt.min(500.0);
+        let n = steps(z2, z3, R2);
+        let zr = (z3 / z2).powf(1.0 / n as f64);
+        let mut tloc3 = tloc2;
+        let mut sum2 = 0.0;
+        ain = gravl / tloc2;
+        for _ in 0..n {
+            let mut z = zend;
+            zend = zr * z;
+            let dz = 0.25 * (zend - z);
+            let mut sum1 = WT[0] * ain;
+            for wt in WT.iter().skip(1) {
+                z += dz;
+                tloc3 = xlocal(z, &tc);
+                gravl = grav(z);
+                ain = gravl / tloc3;
+                sum1 += wt * ain;
+            }
+            sum2 += dz * sum1;
+        }
+
+        let z4 = alt.max(500.0);
+        let n = steps(z3, z4, if alt > 500.0 { R3 } else { R2 });
+        let zr = (z4 / z3).powf(1.0 / n as f64);
+        let mut tloc4 = tloc3;
+        let mut sum3 = 0.0;
+        for _ in 0..n {
+            let mut z = zend;
+            zend = zr * z;
+            let dz = 0.25 * (zend - z);
+            let mut sum1 = WT[0] * ain;
+            for wt in WT.iter().skip(1) {
+                z += dz;
+                tloc4 = xlocal(z, &tc);
+                gravl = grav(z);
+                ain = gravl / tloc4;
+                sum1 += wt * ain;
+            }
+            sum3 += dz * sum1;
+        }
+
+        let (t_k, altr, fact2, hsign) = if alt > 500.0 {
+            (tloc4, (tloc4 / tloc2).ln(), fact1 * (sum2 + sum3), -1.0)
+        } else {
+            (tloc3, (tloc3 / tloc2).ln(), fact1 * sum2, 1.0)
+        };
+        for i in 0..5 {
+            aln[i] -= (1.0 + ALPHA[i]) * altr + fact2 * AMW[i];
+        }
+
+        // Hydrogen (Eq. 7)
+        let al10t5 = tinf.log10();
+        let alnh5 = (5.5 * al10t5 - 39.40) * al10t5 + 73.13;
+        aln[5] = LN_10 * (alnh5 + 6.0) + hsign * ((tloc4 / tloc3).ln() + fact1 * sum3 * AMW[5]);
+        t_k
+    };
+
+    // Seasonal-latitudinal variation (Eq. 24)
+    let capphi = ((input.mjd_utc - 36204.0) / 365.2422).rem_euclid(1.0);
+    let dlrsl = 0.02
+        * (alt - 90.0)
+        * (-0.045 * (alt - 90.0)).exp()
+        * sat_lat.signum()
+        * (TAU * capphi + 1.72).sin()
+        * sat_lat.sin().powi(2);
+
+    // Semiannual variation (Eq. 23)
+    let dlrsa = if alt < 2000.0 {
+        let (fzz, drlog) = semian08(input.day_of_year, alt, input.f10b, input.s10b, input.m10b);
+        if fzz < 0.0 {
+            0.0
+        } else {
+            drlog
+        }
+    } else {
+        0.0
+    };
+
+    // Mass density
+    let dlr = LN_10 * (dlrsl + dlrsa);
+    let mut sumnm = 0.0;
+    for (ln_n, amw) in aln.iter().zip(AMW.iter()) {
+        sumnm += (ln_n + dlr).exp() * amw;
+    }
+    let rho = sumnm / AVOGADRO;
+
+    // High altitude exospheric density correction
+    let fex = if (1000.0..1500.0).contains(&alt) {
+        let zeta = (alt - 1000.0) * 0.002;
+        let f15c = CHT[0] + CHT[1] * input.f10b + CHT[2] * 1500.0 + CHT[3] * input.f10b * 1500.0;
+        let f15c_zeta = (CHT[2] + CHT[3] * input.f10b) * 500.0;
+        let fex2 = 3.0 * f15c - f15c_zeta - 3.0;
+        let fex3 = f15c_zeta - 2.0 * f15c + 2.0;
+        1.0 + fex2 * zeta.powi(2) + fex3 * zeta.powi(3)
+    } else if alt >= 1500.0 {
+        CHT[0] + CHT[1] * input.f10b + CHT[2] * alt + CHT[3] * input.f10b * alt
+    } else {
+        1.0
+    };
+
+    Jb2008Output {
+        rho_kg_m3: fex * rho,
+        t_exo_k: tinf,
+        t_k,
+    }
+}
+
+/// Number of integration steps between two altitudes for the provided log-altitude step
+fn steps(z_from: f64, z_to: f64, r: f64) -> usize {
+    ((z_to / z_from).ln() / r) as usize + 1
+}
+
+/// Mean molecular mass below 105 km
+fn xambar(z: f64) -> f64 {
+    const C: [f64; 7] = [
+        28.15204, -8.5586E-2, 1.2840E-4, -1.0056E-5, -1.0210E-5, 1.5044E-6, 9.9826E-8,
+    ];
+    let dz = z - 100.0;
+    C.iter().rev().fold(0.0, |amb, c| dz * amb + c)
+}
+
+/// Local temperature profile (Eq. 10)
+fn xlocal(z: f64, tc: &[f64; 4]) -> f64 {
+    let dz = z - 125.0;
+    if dz <= 0.0 {
+        ((-9.8204695E-6 * dz - 7.3039742E-4) * dz * dz + 1.0) * dz * tc[1] + tc[0]
+    } else {
+        tc[0] + tc[2] * (tc[3] * dz * (1.0 + 4.5E-6 * dz.powf(2.5))).atan()
+    }
+}
+
+/// Gravity in m/s^2 at the provided altitude
+fn grav(z: f64) -> f64 {
+    9.80665 / (1.0 + z / 6356.766).powi(2)
+}
+
+/// Semiannual variation: returns the height dependent amplitude and the log of the density variation
+fn semian08(day: f64, ht: f64, f10b: f64, s10b: f64, m10b: f64) -> (f64, f64) {
+    const FZM: [f64; 5] = [0.2689, -0.1176E-01, 0.2782E-01, -0.2782E-01, 0.3470E-03];
+    const GTM: [f64; 10] = [
+        -0.3633,
+        0.8506E-01,
+        0.2401,
+        -0.1897,
+        -0.2554,
+        -0.1790E-01,
+        0.5650E-03,
+        -0.6407E-03,
+        -0.3418E-02,
+        -0.1252E-02,
+    ];
+    // Height dependent amplitude
+    let fsmb = f10b - 0.70 * s10b - 0.04 * m10b;
+    let htz = ht / 1000.0;
+    let fzz = (FZM[0]
+        + FZM[1] * fsmb
+        + FZM[2] * fsmb * htz
+        + FZM[3] * fsmb * htz * htz
+        + FZM[4] * fsmb * fsmb * htz)
+        .max(1.0E-6);
+
+    // Yearly periodic variation
+    let fsmb = f10b - 0.75 * s10b - 0.37 * m10b;
+    let tau = (day - 1.0) / 365.0;
+    let (sin1p, cos1p) = (TAU * tau).sin_cos();
+    let (sin2p, cos2p) = (2.0 * TAU * tau).sin_cos();
+    let gtz = GTM[0]
+        + GTM[1] * sin1p
+        + GTM[2] * cos1p
+        + GTM[3] * sin2p
+        + GTM[4] * cos2p
+        + GTM[5] * fsmb
+        + GTM[6] * fsmb * sin1p
+        + GTM[7] * fsmb * cos1p
+        + GTM[8] * fsmb * sin2p
+        + GTM[9] * fsmb * cos2p;
+    (fzz, fzz * gtz)
+}
+
+/// Correction of the exospheric temperature for the local solar time and latitude
+fn dtsub(f10: f64, xlst: f64, xlat: f64, zht: f64) -> f64 {
+    const B: [f64; 19] = [
+        -0.457512297E+01,
+        -0.512114909E+01,
+        -0.693003609E+02,
+        0.203716701E+03,
+        0.703316291E+03,
+        -0.194349234E+04,
+        0.110651308E+04,
+        -0.174378996E+03,
+        0.188594601E+04,
+        -0.709371517E+04,
+        0.922454523E+04,
+        -0.384508073E+04,
+        -0.645841789E+01,
+        0.409703319E+02,
+        -0.482006560E+03,
+        0.181870931E+04,
+        -0.237389204E+04,
+        0.996703815E+03,
+        0.361416936E+02,
+    ];
+    const C: [f64; 23] = [
+        -0.155986211E+02,
+        -0.512114909E+01,
+        -0.693003609E+02,
+        0.203716701E+03,
+        0.703316291E+03,
+        -0.194349234E+04,
+        0.110651308E+04,
+        -0.220835117E+03,
+        0.143256989E+04,
+        -0.318481844E+04,
+        0.328981513E+04,
+        -0.135332119E+04,
+        0.199956489E+02,
+        -0.127093998E+02,
+        0.212825156E+02,
+        -0.275555432E+01,
+        0.110234982E+02,
+        0.148881951E+03,
+        -0.751640284E+03,
+        0.637876542E+03,
+        0.127093998E+02,
+        -0.212825156E+02,
+        0.275555432E+01,
+    ];
+    let tx = xlst / 24.0;
+    let ycs = xlat.cos();
+    let f = (f10 - 100.0) / 100.0;
+
+    // Correction at 200 km and its slope with respect to the scaled altitude, for altitudes from 120 to 240 km
+    let c_slope = C[0]
+        + B[1] * f
+        + C[2] * tx * f
+        + C[3] * tx.powi(2) * f
+        + C[4] * tx.powi(3) * f
+        + C[5] * tx.powi(4) * f
+        + C[6] * tx.powi(5) * f
+        + C[7] * tx * ycs
+        + C[8] * tx.powi(2) * ycs
+        + C[9] * tx.powi(3) * ycs
+        + C[10] * tx.powi(4) * ycs
+        + C[11] * tx.powi(5) * ycs
+        + C[12] * ycs
+        + C[13] * f * ycs
+        + C[14] * tx * f * ycs
+        + C[15] * tx.powi(2) * f * ycs;
+    let c_200 = C[16]
+        + C[17] * tx * ycs
+        + C[18] * tx.powi(2) * ycs
+        + C[19] * tx.powi(3) * ycs
+        + C[20] * f * ycs
+        + C[21] * tx * f * ycs
+        + C[22] * tx.powi(2) * f * ycs;
+
+    // Correction and its slope with respect to the scaled altitude, from 300 to 800 km
+    let b_slope = B[12] * ycs
+        + B[13] * tx * ycs
+        + B[14] * tx.powi(2) * ycs
+        + B[15] * tx.powi(3) * ycs
+        + B[16] * tx.powi(4) * ycs
+        + B[17] * tx.powi(5) * ycs;
+    let b_at = |h: f64| {
+        B[0] + B[1] * f
+            + B[2] * tx * f
+            + B[3] * tx.powi(2) * f
+            + B[4] * tx.powi(3) * f
+            + B[5] * tx.powi(4) * f
+            + B[6] * tx.powi(5) * f
+            + B[7] * tx * ycs
+            + B[8] * tx.powi(2) * ycs
+            + B[9] * tx.powi(3) * ycs
+            + B[10] * tx.powi(4) * ycs
+            + B[11] * tx.powi(5) * ycs
+            + b_slope * h
+            + B[18] * ycs
+    };
+
+    if (120.0..=200.0).contains(&zht) {
+        let cc = 3.0 * c_200 - c_slope;
+        let dd = c_200 - cc;
+        let zp = (zht - 120.0) / 80.0;
+        cc * zp * zp + dd * zp * zp * zp
+    } else if zht > 200.0 && zht <= 240.0 {
+        let h = (zht - 200.0) / 50.0;
+        c_slope * h + c_200
+    } else if zht > 240.0 && zht <= 300.0 {
+        let aa = c_slope * 0.8 + c_200;
+        let bb = c_slope;
+        let dtc300 = b_at(3.0);
+        let cc = 3.0 * dtc300 - b_slope - 3.0 * aa - 2.0 * bb;
+        let dd = dtc300 - aa - bb - cc;
+        let zp = (zht - 240.0) / 60.0;
+        aa + bb * zp + cc * zp * zp + dd * zp * zp * zp
+    } else if zht > 300.0 && zht <= 600.0 {
+        b_at(zht / 100.0)
+    } else if zht > 600.0 && zht <= 800.0 {
+        let zp = (zht - 600.0) / 100.0;
+        let aa = b_at(6.0);
+        let bb = b_slope;
+        let cc = -(3.0 * aa + 4.0 * bb) / 4.0;
+        let dd = (aa + bb) / 4.0;
+        aa + bb * zp + cc * zp * zp + dd * zp * zp * zp
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod ut_jb2008 {
+    use super::*;
+
+    fn moderate_activity(alt_km: f64) -> Jb2008Input {
+        Jb2008Input {
+            mjd_utc: 60005.5,
+            day_of_year: 70.5,
+            sun_ra_deg: 350.0,
+            sun_dec_deg: -4.0,
+            ra_deg: 80.0,
+            lat_deg: 30.0,
+            alt_km,
+            f10: 150.0,
+            f10b: 140.0,
+            s10: 145.0,
+            s10b: 135.0,
+            m10: 148.0,
+            m10b: 138.0,
+            y10: 140.0,
+            y10b: 130.0,
+            dst_dtc: 30.0,
+        }
+    }
+
+    #[test]
+    fn jb2008_profile() {
+        // Lower boundary density
+        let out = jb2008(&moderate_activity(90.0));
+        println!("{out:?}");
+        assert!((out.rho_kg_m3 - 3.46E-6).abs() / 3.46E-6 < 0.05);
+
+        let out = jb2008(&moderate_activity(400.0));
+        println!("{out:?}");
+        assert!(out.rho_kg_m3 > 1e-12 && out.rho_kg_m3 < 1e-11);
+        assert!(out.t_exo_k > 800.0 && out.t_exo_k < 1400.0);
+        assert!((out.t_k - out.t_exo_k).abs() < 50.0);
+
+        // Density decreases with altitude
+        let mut prev_rho = f64::INFINITY;
+        for alt_km in [
+            90.0, 100.0, 120.0, 200.0, 250.0, 350.0, 500.0, 700.0, 1000.0, 1200.0, 2000.0,
+        ] {
+            let rho = jb2008(&moderate_activity(alt_km)).rho_kg_m3;
+            assert!(rho < prev_rho, "{alt_km} km: {rho:e}");
+            prev_rho = rho;
+        }
+
+        // And increases with the solar activity
+        let mut active = moderate_activity(400.0);
+        active.f10 = 250.0;
+        active.f10b = 230.0;
+        active.s10 = 240.0;
+        active.s10b = 220.0;
+        active.m10 = 245.0;
+        active.m10b = 225.0;
+        active.y10 = 235.0;
+        active.y10b = 215.0;
+        active.dst_dtc = 150.0;
+        let active_out = jb2008(&active);
+        println!("{active_out:?}");
+        assert!(active_out.t_exo_k > out.t_exo_k + 300.0);
+        assert!(active_out.rho_kg_m3 > 2.0 * out.rho_kg_m3);
+    }
+}
@@ -0,0 +1,35 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::time::{Epoch, Unit};
+
+/// The NRLMSISE-00 empirical atmosphere model
+pub mod nrlmsise00;
+pub use self::nrlmsise00::*;
+
+/// The Jacchia-Bowman 2008 empirical density model
+pub mod jb2008;
+pub use self::jb2008::*;
+
+/// Returns the UTC day of year (starting at 1) and the UTC seconds in that day
+pub(crate) fn utc_day_of_year(epoch: Epoch) -> (f64, f64) {
+    let (year, _, _, _, _, _, _) = epoch.to_gregorian_utc();
+    let days = (epoch - Epoch::from_gregorian_utc_at_midnight(year, 1, 1)).to_unit(Unit::Day);
+    let day = days.floor();
+    (day + 1.0, (days - day) * 86_400.0)
+}
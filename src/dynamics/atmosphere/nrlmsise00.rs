@@ -0,0 +1,1553 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::utc_day_of_year;
+use crate::io::pck::{parse_text_kernel, KernelValue};
+use crate::io::space_weather::SpaceWeather;
+use crate::time::{Epoch, Unit};
+use crate::NyxError;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::read_to_string;
+
+const DGTR: f64 = 1.74533E-2;
+const DR: f64 = 1.72142E-2;
+const HR: f64 = 0.2618;
+const SR: f64 = 7.2722E-5;
+const RGAS: f64 = 831.4;
+/// Nodes of the lower thermosphere, the first node is replaced by the joining altitude of the Bates profile
+const ZN1: [f64; 5] = [120.0, 110.0, 100.0, 90.0, 72.5];
+/// Nodes of the mesosphere and upper stratosphere
+const ZN2: [f64; 4] = [72.5, 55.0, 45.0, 32.5];
+/// Nodes of the lower stratosphere and troposphere
+const ZN3: [f64; 5] = [32.5, 20.0, 15.0, 10.0, 0.0];
+const ZMIX: f64 = 62.5;
+
+/// Inputs of the NRLMSISE-00 model at a given point and time
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Nrlmsise00Input {
+    /// Day of year, starting at 1
+    pub doy: f64,
+    /// Seconds in the day (UT)
+    pub sec: f64,
+    /// Geodetic altitude in km
+    pub alt_km: f64,
+    /// Geodetic latitude in degrees
+    pub g_lat_deg: f64,
+    /// Geodetic longitude in degrees
+    pub g_long_deg: f64,
+    /// Local apparent solar time in hours
+    pub lst_h: f64,
+    /// 81-day average of F10.7 centered on the day
+    pub f107a: f64,
+    /// Daily F10.7 of the previous day
+    pub f107: f64,
+    /// Daily magnetic index
+    pub ap: f64,
+    /// Magnetic index history: daily Ap, the 3-hour ap at the epoch, 3, 6 and 9 hours before the epoch,
+    /// and the averages of the eight 3-hour ap from 12 to 33 hours and from 36 to 57 hours before the epoch.
+    /// If set, the model uses this history instead of the daily Ap.
+    pub ap_history: Option<[f64; 7]>,
+}
+
+impl Nrlmsise00Input {
+    /// Builds the input at the provided epoch and geodetic position from the space weather indices.
+    ///
+    /// The local solar time is computed from the longitude, and the ap history is computed from the 3-hour ap indices.
+    pub fn from_space_weather(
+        epoch: Epoch,
+        g_lat_deg: f64,
+        g_long_deg: f64,
+        alt_km: f64,
+        space_weather: &SpaceWeather,
+    ) -> Result<Self, NyxError> {
+        let (doy, sec) = utc_day_of_year(epoch);
+        let today = space_weather.at(epoch)?;
+        let yesterday = space_weather.at(epoch - Unit::Day * 1)?;
+
+        let ap_at = |hours_before: f64| space_weather.ap_3h(epoch - Unit::Hour * hours_before);
+        let ap_avg = |from_hours_before: f64| -> Result<f64, NyxError> {
+            let mut sum = 0.0;
+            for i in 0..8 {
+                sum += ap_at(from_hours_before + 3.0 * i as f64)?;
+            }
+            Ok(sum / 8.0)
+        };
+
+        Ok(Self {
+            doy,
+            sec,
+            alt_km,
+            g_lat_deg,
+            g_long_deg,
+            lst_h: (sec / 3600.0 + g_long_deg / 15.0).rem_euclid(24.0),
+            f107a: today.f107_obs_ctr81,
+            f107: yesterday.f107_obs,
+            ap: today.ap_avg,
+            ap_history: Some([
+                today.ap_avg,
+                ap_at(0.0)?,
+                ap_at(3.0)?,
+                ap_at(6.0)?,
+                ap_at(9.0)?,
+                ap_avg(12.0)?,
+                ap_avg(36.0)?,
+            ]),
+        })
+    }
+}
+
+/// Outputs of the NRLMSISE-00 model, in SI units
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Nrlmsise00Output {
+    /// Number density of helium in m^-3
+    pub he: f64,
+    /// Number density of atomic oxygen in m^-3
+    pub o: f64,
+    /// Number density of molecular nitrogen in m^-3
+    pub n2: f64,
+    /// Number density of molecular oxygen in m^-3
+    pub o2: f64,
+    /// Number density of argon in m^-3
+    pub ar: f64,
+    /// Total mass density in kg/m^3
+    pub rho_kg_m3: f64,
+    /// Number density of hydrogen in m^-3
+    pub h: f64,
+    /// Number density of atomic nitrogen in m^-3
+    pub n: f64,
+    /// Number density of anomalous oxygen in m^-3
+    pub anomalous_o: f64,
+    /// Exospheric temperature in Kelvin
+    pub t_exo_k: f64,
+    /// Temperature at altitude in Kelvin
+    pub t_k: f64,
+}
+
+impl Nrlmsise00Output {
+    fn from_arrays(d: [f64; 9], t: [f64; 2]) -> Self {
+        Self {
+            he: d[0],
+            o: d[1],
+            n2: d[2],
+            o2: d[3],
+            ar: d[4],
+            rho_kg_m3: d[5],
+            h: d[6],
+            n: d[7],
+            anomalous_o: d[8],
+            t_exo_k: t[0],
+            t_k: t[1],
+        }
+    }
+}
+
+/// The NRLMSISE-00 empirical atmosphere model of Picone et al. (2002), ported from the reference C implementation of Dominik Brodowski.
+///
+/// The model coefficients are those of the data file of that implementation (`nrlmsise-00_data.c`), which is in the public domain,
+/// and are loaded from a text kernel (cf. `from_text`) rather than from that C source.
+/// The outputs are always in SI units, i.e. the first switch of the model is ignored.
+#[derive(Clone)]
+pub struct Nrlmsise00 {
+    pt: Vec<f64>,
+    pd: Vec<Vec<f64>>,
+    ps: Vec<f64>,
+    pdl: Vec<Vec<f64>>,
+    ptl: Vec<Vec<f64>>,
+    pma: Vec<Vec<f64>>,
+    ptm: Vec<f64>,
+    pdm: Vec<Vec<f64>>,
+    pavgm: Vec<f64>,
+    /// Switches of the variations of the model: 0 turns the variation off, 1 turns it on, and 2 keeps only its cross terms.
+    /// For example, switch 9 is the magnetic activity and switch 7 is the diurnal variation. All switches are on by default.
+    pub switches: [i32; 24],
+}
+
+/// Removes the table of the provided name from the kernel variables, checking its length
+fn kernel_table(
+    tables: &mut HashMap<String, Vec<f64>>,
+    name: &str,
+    len: usize,
+) -> Result<Vec<f64>, NyxError> {
+    let keyword = format!("NRLMSISE00_{name}");
+    match tables.remove(&keyword) {
+        Some(values) if values.len() == len => Ok(values),
+        Some(values) => Err(NyxError::LoadingError(format!(
+            "{keyword} has {} values instead of {len}",
+            values.len()
+        ))),
+        None => Err(NyxError::LoadingError(format!("{keyword} not found"))),
+    }
+}
+
+/// Removes the table of the provided name from the kernel variables, as `rows` rows of `cols` values
+fn kernel_table_2d(
+    tables: &mut HashMap<String, Vec<f64>>,
+    name: &str,
+    rows: usize,
+    cols: usize,
+) -> Result<Vec<Vec<f64>>, NyxError> {
+    Ok(kernel_table(tables, name, rows * cols)?
+        .chunks(cols)
+        .map(|row| row.to_vec())
+        .collect())
+}
+
+impl Nrlmsise00 {
+    /// Loads the model coefficients from a text kernel, cf. `from_text`
+    pub fn from_file(path: &str) -> Result<Self, NyxError> {
+        Self::from_text(
+            &read_to_string(path).map_err(|e| NyxError::FileUnreadable(format!("{path}: {e}")))?,
+        )
+    }
+
+    /// Parses the model coefficients from the content of a text kernel in the format of the SPICE text kernels.
+    ///
+    /// Each coefficient array of the reference implementation is defined by the variable of its upper case name prefixed with `NRLMSISE00_`,
+    /// and the two dimensional arrays are flattened row by row, e.g. `NRLMSISE00_PD = ( ... )` lists the 9 rows of 150 values of `pd`.
+    /// The `pt`, `pd`, `ps`, `pdl`, `ptl`, `pma`, `ptm`, `pdm` and `pavgm` arrays are required, and all other variables are ignored.
+    pub fn from_text(content: &str) -> Result<Self, NyxError> {
+        let mut tables = HashMap::new();
+        for (keyword, values) in parse_text_kernel(content)? {
+            if !keyword.starts_with("NRLMSISE00_") {
+                debug!("ignoring kernel variable {keyword}");
+                continue;
+            }
+            let values = values
+                .into_iter()
+                .map(|value| match value {
+                    KernelValue::Number(num) => Ok(num),
+                    KernelValue::Text(text) => Err(NyxError::LoadingError(format!(
+                        "{keyword}: expected numbers, got `{text}`"
+                    ))),
+                })
+                .collect::<Result<Vec<f64>, NyxError>>()?;
+            tables.insert(keyword, values);
+        }
+
+        Ok(Self {
+            pt: kernel_table(&mut tables, "PT", 150)?,
+            pd: kernel_table_2d(&mut tables, "PD", 9, 150)?,
+            ps: kernel_table(&mut tables, "PS", 150)?,
+            pdl: kernel_table_2d(&mut tables, "PDL", 2, 25)?,
+            ptl: kernel_table_2d(&mut tables, "PTL", 4, 100)?,
+            pma: kernel_table_2d(&mut tables, "PMA", 10, 100)?,
+            ptm: kernel_table(&mut tables, "PTM", 10)?,
+            pdm: kernel_table_2d(&mut tables, "PDM", 8, 10)?,
+            pavgm: kernel_table(&mut tables, "PAVGM", 10)?,
+            switches: [1; 24],
+        })
+    }
+
+    /// Computes the neutral atmosphere at the provided input (`gtd7` in the reference implementation).
+    /// The total mass density does not include the anomalous oxygen.
+    pub fn gtd7(&self, input: &Nrlmsise00Input) -> Nrlmsise00Output {
+        let (d, t) = Gtd7::new(self, input).gtd7(self, input);
+        Nrlmsise00Output::from_arrays(d, t)
+    }
+
+    /// Computes the neutral atmosphere at the provided input, where the total mass density includes the anomalous oxygen (`gtd7d` in the reference implementation).
+    /// This is the effective density which should be used for drag computations.
+    pub fn gtd7d(&self, input: &Nrlmsise00Input) -> Nrlmsise00Output {
+        let (mut d, t) = Gtd7::new(self, input).gtd7(self, input);
+        d[5] = 1.66E-24
+            * (4.0 * d[0]
+                + 16.0 * d[1]
+                + 28.0 * d[2]
+                + 32.0 * d[3]
+                + 40.0 * d[4]
+                + d[6]
+                + 14.0 * d[7]
+                + 16.0 * d[8])
+            / 1000.0;
+        Nrlmsise00Output::from_arrays(d, t)
+    }
+
+    /// Returns the total mass density in kg/m^3 for drag computations
+    pub fn density_kg_m3(&self, input: &Nrlmsise00Input) -> f64 {
+        self.gtd7d(input).rho_kg_m3
+    }
+}
+
+impl fmt::Debug for Nrlmsise00 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "NRLMSISE-00 (switches: {:?})", self.switches)
+    }
+}
+
+/// Evaluation state of the model (the common blocks of the reference implementation)
+#[derive(Default)]
+struct Gtd7 {
+    sw: [f64; 24],
+    swc: [f64; 24],
+    gsurf: f64,
+    re: f64,
+    dm28: f64,
+    meso_tn1: [f64; 5],
+    meso_tn2: [f64; 4],
+    meso_tn3: [f64; 5],
+    meso_tgn1: [f64; 2],
+    meso_tgn2: [f64; 2],
+    meso_tgn3: [f64; 2],
+    dfa: f64,
+    plg: [[f64; 9]; 4],
+    ctloc: f64,
+    stloc: f64,
+    c2tloc: f64,
+    s2tloc: f64,
+    s3tloc: f64,
+    c3tloc: f64,
+    apdf: f64,
+    apt: [f64; 4],
+}
+
+/// Latitude variation of gravity, returns the surface gravity in cm/s^2 and the effective radius in km
+fn glatf(lat: f64) -> (f64, f64) {
+    let c2 = (2.0 * DGTR * lat).cos();
+    let gv = 980.616 * (1.0 - 0.0026373 * c2);
+    let reff = 2.0 * gv / (3.085462E-6 + 2.27E-9 * c2) * 1.0E-5;
+    (gv, reff)
+}
+
+/// Chemistry/dissociation correction
+fn ccor(alt: f64, r: f64, h1: f64, zh: f64) -> f64 {
+    let e = (alt - zh) / h1;
+    if e > 70.0 {
+        return 1.0;
+    } else if e < -70.0 {
+        return r.exp();
+    }
+    (r / (1.0 + e.exp())).exp()
+}
+
+/// Chemistry/dissociation correction with two scale lengths
+fn ccor2(alt: f64, r: f64, h1: f64, zh: f64, h2: f64) -> f64 {
+    let e1 = (alt - zh) / h1;
+    let e2 = (alt - zh) / h2;
+    if e1 > 70.0 || e2 > 70.0 {
+        return 1.0;
+    } else if e1 < -70.0 && e2 < -70.0 {
+        return r.exp();
+    }
+    (r / (1.0 + 0.5 * (e1.exp() + e2.exp()))).exp()
+}
+
+/// Turbopause correction: combines the diffusive density `dd` and the fully mixed density `dm`
+fn dnet(mut dd: f64, dm: f64, zhm: f64, xmm: f64, xm: f64) -> f64 {
+    let a = zhm / (xmm - xm);
+    if !(dm > 0.0 && dd > 0.0) {
+        if dd == 0.0 && dm == 0.0 {
+            dd = 1.0;
+        }
+        if dm == 0.0 {
+            return dd;
+        }
+        if dd == 0.0 {
+            return dm;
+        }
+    }
+    let ylog = a * (dm / dd).ln();
+    if ylog < -10.0 {
+        dd
+    } else if ylog > 10.0 {
+        dm
+    } else {
+        dd * (1.0 + ylog.exp()).powf(1.0 / a)
+    }
+}
+
+/// Integrates the cubic spline from xa[0] to x
+fn splini(xa: &[f64], ya: &[f64], y2a: &[f64], x: f64) -> f64 {
+    let n = xa.len();
+    let mut yi = 0.0;
+    let mut klo = 0;
+    let mut khi = 1;
+    while x > xa[klo] && khi < n {
+        let xx = if khi < n - 1 && x >= xa[khi] {
+            xa[khi]
+        } else {
+            x
+        };
+        let h = xa[khi] - xa[klo];
+        let a = (xa[khi] - xx) / h;
+        let b = (xx - xa[klo]) / h;
+        let a2 = a * a;
+        let b2 = b * b;
+        yi += ((1.0 - a2) * ya[klo] / 2.0
+            + b2 * ya[khi] / 2.0
+            + ((-(1.0 + a2 * a2) / 4.0 + a2 / 2.0) * y2a[klo]
+                + (b2 * b2 / 4.0 - b2 / 2.0) * y2a[khi])
+                * h
+                * h
+                / 6.0)
+            * h;
+        klo += 1;
+        khi += 1;
+    }
+    yi
+}
+
+/// Interpolates the cubic spline at x
+fn splint(xa: &[f64], ya: &[f64], y2a: &[f64], x: f64) -> f64 {
+    let mut klo = 0;
+    let mut khi = xa.len() - 1;
+    while khi - klo > 1 {
+        let k = (khi + klo) / 2;
+        if xa[k] > x {
+            khi = k;
+        } else {
+            klo = k;
+        }
+    }
+    let h = xa[khi] - xa[klo];
+    let a = (xa[khi] - x) / h;
+    let b = (x - xa[klo]) / h;
+    a * ya[klo]
+        + b * ya[khi]
+        + ((a * a * a - a) * y2a[klo] + (b * b * b - b) * y2a[khi]) * h * h / 6.0
+}
+
+/// Computes the second derivatives of the cubic spline through the nodes, with the provided end derivatives
+fn spline(x: &[f64], y: &[f64], yp1: f64, ypn: f64, y2: &mut [f64]) {
+    let n = x.len();
+    let mut u = [0.0; 10];
+    if yp1 > 0.99E30 {
+        y2[0] = 0.0;
+        u[0] = 0.0;
+    } else {
+        y2[0] = -0.5;
+        u[0] = (3.0 / (x[1] - x[0])) * ((y[1] - y[0]) / (x[1] - x[0]) - yp1);
+    }
+    for i in 1..n - 1 {
+        let sig = (x[i] - x[i - 1]) / (x[i + 1] - x[i - 1]);
+        let p = sig * y2[i - 1] + 2.0;
+        y2[i] = (sig - 1.0) / p;
+        u[i] = (6.0
+            * ((y[i + 1] - y[i]) / (x[i + 1] - x[i]) - (y[i] - y[i - 1]) / (x[i] - x[i - 1]))
+            / (x[i + 1] - x[i - 1])
+            - sig * u[i - 1])
+            / p;
+    }
+    let (qn, un) = if ypn > 0.99E30 {
+        (0.0, 0.0)
+    } else {
+        (
+            0.5,
+            (3.0 / (x[n - 1] - x[n - 2])) * (ypn - (y[n - 1] - y[n - 2]) / (x[n - 1] - x[n - 2])),
+        )
+    };
+    y2[n - 1] = (un - qn * u[n - 2]) / (qn * y2[n - 2] + 1.0);
+    for k in (0..n - 1).rev() {
+        y2[k] = y2[k] * y2[k + 1] + u[k];
+    }
+}
+
+/// 3-hour magnetic activity function (Eq. A24d)
+fn g0(a: f64, p24: f64, p25: f64) -> f64 {
+    a - 4.0 + (p25 - 1.0) * (a - 4.0 + ((-p24.abs() * (a - 4.0)).exp() - 1.0) / p24.abs())
+}
+
+/// Eq. A24c
+fn sumex(ex: f64) -> f64 {
+    1.0 + (1.0 - ex.powi(19)) / (1.0 - ex) * ex.sqrt()
+}
+
+/// Eq. A24a
+fn sg0(ex: f64, p24: f64, p25: f64, ap: &[f64; 7]) -> f64 {
+    (g0(ap[1], p24, p25)
+        + (g0(ap[2], p24, p25) * ex
+            + g0(ap[3], p24, p25) * ex * ex
+            + g0(ap[4], p24, p25) * ex.powi(3)
+            + (g0(ap[5], p24, p25) * ex.powi(4) + g0(ap[6], p24, p25) * ex.powi(12))
+                * (1.0 - ex.powi(8))
+                / (1.0 - ex)))
+        / sumex(ex)
+}
+
+impl Gtd7 {
+    fn new(model: &Nrlmsise00, input: &Nrlmsise00Input) -> Self {
+        let mut switches = model.switches;
+        switches[0] = 1;
+        if input.ap_history.is_some() && switches[9] != 0 {
+            switches[9] = -1;
+        }
+        let mut me = Self::default();
+        for (i, switch) in switches.iter().enumerate() {
+            if i == 9 {
+                me.sw[i] = *switch as f64;
+                me.swc[i] = *switch as f64;
+            } else {
+                me.sw[i] = if *switch == 1 { 1.0 } else { 0.0 };
+                me.swc[i] = if *switch > 0 { 1.0 } else { 0.0 };
+            }
+        }
+        me
+    }
+
+    fn zeta(&self, zz: f64, zl: f64) -> f64 {
+        (zz - zl) * (self.re + zl) / (self.re + zz)
+    }
+
+    fn scalh(&self, alt: f64, xm: f64, temp: f64) -> f64 {
+        let g = self.gsurf / (1.0 + alt / self.re).powi(2);
+        RGAS * temp / (g * xm)
+    }
+
+    /// Temperature and density profiles of the lower atmosphere
+    fn densm(&self, alt: f64, d0: f64, xm: f64, tz: &mut f64) -> f64 {
+        let mut densm_tmp = d0;
+        if alt > ZN2[0] {
+            return if xm == 0.0 { *tz } else { d0 };
+        }
+
+        // Stratosphere and mesosphere temperature
+        let z = if alt > ZN2[3] { alt } else { ZN2[3] };
+        let (z1, z2) = (ZN2[0], ZN2[3]);
+        let (t1, t2) = (self.meso_tn2[0], self.meso_tn2[3]);
+        let zg = self.zeta(z, z1);
+        let zgdif = self.zeta(z2, z1);
+        let mut xs = [0.0; 4];
+        let mut ys = [0.0; 4];
+        let mut y2out = [0.0; 4];
+        for k in 0..4 {
+            xs[k] = self.zeta(ZN2[k], z1) / zgdif;
+            ys[k] = 1.0 / self.meso_tn2[k];
+        }
+        let yd1 = -self.meso_tgn2[0] / (t1 * t1) * zgdif;
+        let yd2 =
+            -self.meso_tgn2[1] / (t2 * t2) * zgdif * ((self.re + z2) / (self.re + z1)).powi(2);
+        spline(&xs, &ys, yd1, yd2, &mut y2out);
+        let x = zg / zgdif;
+        *tz = 1.0 / splint(&xs, &ys, &y2out, x);
+        if xm != 0.0 {
+            // Stratosphere and mesosphere density
+            let glb = self.gsurf / (1.0 + z1 / self.re).powi(2);
+            let gamm = xm * glb * zgdif / RGAS;
+            let expl = (gamm * splini(&xs, &ys, &y2out, x)).min(50.0);
+            densm_tmp *= (t1 / *tz) * (-expl).exp();
+        }
+
+        if alt > ZN3[0] {
+            return if xm == 0.0 { *tz } else { densm_tmp };
+        }
+
+        // Troposphere and stratosphere temperature
+        let z = alt;
+        let (z1, z2) = (ZN3[0], ZN3[4]);
+        let (t1, t2) = (self.meso_tn3[0], self.meso_tn3[4]);
+        let zg = self.zeta(z, z1);
+        let zgdif = self.zeta(z2, z1);
+        let mut xs = [0.0; 5];
+        let mut ys = [0.0; 5];
+        let mut y2out = [0.0; 5];
+        for k in 0..5 {
+            xs[k] = self.zeta(ZN3[k], z1) / zgdif;
+            ys[k] = 1.0 / self.meso_tn3[k];
+        }
+        let yd1 = -self.meso_tgn3[0] / (t1 * t1) * zgdif;
+        let yd2 =
+            -self.meso_tgn3[1] / (t2 * t2) * zgdif * ((self.re + z2) / (self.re + z1)).powi(2);
+        spline(&xs, &ys, yd1, yd2, &mut y2out);
+        let x = zg / zgdif;
+        *tz = 1.0 / splint(&xs, &ys, &y2out, x);
+        if xm != 0.0 {
+            // Troposphere and stratosphere density
+            let glb = self.gsurf / (1.0 + z1 / self.re).powi(2);
+            let gamm = xm * glb * zgdif / RGAS;
+            let expl = (gamm * splini(&xs, &ys, &y2out, x)).min(50.0);
+            densm_tmp *= (t1 / *tz) * (-expl).exp();
+        }
+
+        if xm == 0.0 {
+            *tz
+        } else {
+            densm_tmp
+        }
+    }
+
+    /// Temperature and density profiles of the thermosphere
+    #[allow(clippy::too_many_arguments)]
+    fn densu(
+        &mut self,
+        alt: f64,
+        dlb: f64,
+        tinf: f64,
+        tlb: f64,
+        xm: f64,
+        alpha: f64,
+        tz: &mut f64,
+        zlb: f64,
+        s2: f64,
+        zn1: &[f64; 5],
+    ) -> f64 {
+        let mut xs = [0.0; 5];
+        let mut ys = [0.0; 5];
+        let mut y2out = [0.0; 5];
+        let mut x = 0.0;
+        let mut z1 = 0.0;
+        let mut t1 = 0.0;
+        let mut zgdif = 0.0;
+
+        // Joining altitude of the Bates and spline profiles
+        let za = zn1[0];
+        let z = if alt > za { alt } else { za };
+        // Geopotential altitude difference from ZLB
+        let zg2 = self.zeta(z, zlb);
+        // Bates temperature
+        let tt = tinf - (tinf - tlb) * (-s2 * zg2).exp();
+        let ta = tt;
+        *tz = tt;
+        let mut densu_temp = *tz;
+
+        if alt < za {
+            // Temperature gradient at ZA from the Bates profile
+            let dta = (tinf - ta) * s2 * ((self.re + zlb) / (self.re + za)).powi(2);
+            self.meso_tgn1[0] = dta;
+            self.meso_tn1[0] = ta;
+            let z = if alt > zn1[4] { alt } else { zn1[4] };
+            z1 = zn1[0];
+            let z2 = zn1[4];
+            t1 = self.meso_tn1[0];
+            let t2 = self.meso_tn1[4];
+            let zg = self.zeta(z, z1);
+            zgdif = self.zeta(z2, z1);
+            for k in 0..5 {
+                xs[k] = self.zeta(zn1[k], z1) / zgdif;
+                ys[k] = 1.0 / self.meso_tn1[k];
+            }
+            let yd1 = -self.meso_tgn1[0] / (t1 * t1) * zgdif;
+            let yd2 =
+                -self.meso_tgn1[1] / (t2 * t2) * zgdif * ((self.re + z2) / (self.re + z1)).powi(2);
+            spline(&xs, &ys, yd1, yd2, &mut y2out);
+            x = zg / zgdif;
+            *tz = 1.0 / splint(&xs, &ys, &y2out, x);
+            densu_temp = *tz;
+        }
+        if xm == 0.0 {
+            return densu_temp;
+        }
+
+        // Density above ZA
+        let glb = self.gsurf / (1.0 + zlb / self.re).powi(2);
+        let gamma = xm * glb / (s2 * RGAS * tinf);
+        let mut expl = (-s2 * gamma * zg2).exp();
+        if expl > 50.0 || tt <= 0.0 {
+            expl = 50.0;
+        }
+        densu_temp = dlb * (tlb / tt).powf(1.0 + alpha + gamma) * expl;
+        if alt >= za {
+            return densu_temp;
+        }
+
+        // Density below ZA
+        let glb = self.gsurf / (1.0 + z1 / self.re).powi(2);
+        let gamm = xm * glb * zgdif / RGAS;
+        let mut expl = gamm * splini(&xs, &ys, &y2out, x);
+        if expl > 50.0 || *tz <= 0.0 {
+            expl = 50.0;
+        }
+        densu_temp * (t1 / *tz).powf(1.0 + alpha) * (-expl).exp()
+    }
+
+    /// G(L) function of the upper thermosphere
+    fn globe7(&mut self, p: &[f64], input: &Nrlmsise00Input) -> f64 {
+        let mut t = [0.0; 15];
+        let tloc = input.lst_h;
+
+        // Legendre polynomials
+        let c = (input.g_lat_deg * DGTR).sin();
+        let s = (input.g_lat_deg * DGTR).cos();
+        let c2 = c * c;
+        let c4 = c2 * c2;
+        let s2 = s * s;
+        let mut plg = [[0.0; 9]; 4];
+        plg[0][1] = c;
+        plg[0][2] = 0.5 * (3.0 * c2 - 1.0);
+        plg[0][3] = 0.5 * (5.0 * c * c2 - 3.0 * c);
+        plg[0][4] = (35.0 * c4 - 30.0 * c2 + 3.0) / 8.0;
+        plg[0][5] = (63.0 * c2 * c2 * c - 70.0 * c2 * c + 15.0 * c) / 8.0;
+        plg[0][6] = (11.0 * c * plg[0][5] - 5.0 * plg[0][4]) / 6.0;
+        plg[1][1] = s;
+        plg[1][2] = 3.0 * c * s;
+        plg[1][3] = 1.5 * (5.0 * c2 - 1.0) * s;
+        plg[1][4] = 2.5 * (7.0 * c2 * c - 3.0 * c) * s;
+        plg[1][5] = 1.875 * (21.0 * c4 - 14.0 * c2 + 1.0) * s;
+        plg[1][6] = (11.0 * c * plg[1][5] - 6.0 * plg[1][4]) / 5.0;
+        plg[2][2] = 3.0 * s2;
+        plg[2][3] = 15.0 * s2 * c;
+        plg[2][4] = 7.5 * (7.0 * c2 - 1.0) * s2;
+        plg[2][5] = 3.0 * c * plg[2][4] - 2.0 * plg[2][3];
+        plg[2][6] = (11.0 * c * plg[2][5] - 7.0 * plg[2][4]) / 4.0;
+        plg[2][7] = (13.0 * c * plg[2][6] - 8.0 * plg[2][5]) / 5.0;
+        plg[3][3] = 15.0 * s2 * s;
+        plg[3][4] = 105.0 * s2 * s * c;
+        plg[3][5] = (9.0 * c * plg[3][4] - 7.0 * plg[3][3]) / 2.0;
+        plg[3][6] = (11.0 * c * plg[3][5] - 8.0 * plg[3][4]) / 3.0;
+        self.plg = plg;
+
+        if !(self.sw[7] == 0.0 && self.sw[8] == 0.0 && self.sw[14] == 0.0) {
+            self.stloc = (HR * tloc).sin();
+            self.ctloc = (HR * tloc).cos();
+            self.s2tloc = (2.0 * HR * tloc).sin();
+            self.c2tloc = (2.0 * HR * tloc).cos();
+            self.s3tloc = (3.0 * HR * tloc).sin();
+            self.c3tloc = (3.0 * HR * tloc).cos();
+        }
+
+        let cd32 = (DR * (input.doy - p[31])).cos();
+        let cd18 = (2.0 * DR * (input.doy - p[17])).cos();
+        let cd14 = (DR * (input.doy - p[13])).cos();
+        let cd39 = (2.0 * DR * (input.doy - p[38])).cos();
+
+        // F10.7 effect
+        let df = input.f107 - input.f107a;
+        self.dfa = input.f107a - 150.0;
+        let dfa = self.dfa;
+        t[0] = p[19] * df * (1.0 + p[59] * dfa) + p[20] * df * df + p[21] * dfa + p[29] * dfa * dfa;
+        let f1 = 1.0 + (p[47] * dfa + p[19] * df + p[20] * df * df) * self.swc[1];
+        let f2 = 1.0 + (p[49] * dfa + p[19] * df + p[20] * df * df) * self.swc[1];
+
+        // Time independent
+        t[1] = (p[1] * plg[0][2] + p[2] * plg[0][4] + p[22] * plg[0][6])
+            + (p[14] * plg[0][2]) * dfa * self.swc[1]
+            + p[26] * plg[0][1];
+        // Symmetrical annual
+        t[2] = p[18] * cd32;
+        // Symmetrical semiannual
+        t[3] = (p[15] + p[16] * plg[0][2]) * cd18;
+        // Asymmetrical annual
+        t[4] = f1 * (p[9] * plg[0][1] + p[10] * plg[0][3]) * cd14;
+        // Asymmetrical semiannual
+        t[5] = p[37] * plg[0][1] * cd39;
+
+        // Diurnal
+        if self.sw[7] != 0.0 {
+            let t71 = (p[11] * plg[1][2]) * cd14 * self.swc[5];
+            let t72 = (p[12] * plg[1][2]) * cd14 * self.swc[5];
+            t[6] = f2
+                * ((p[3] * plg[1][1] + p[4] * plg[1][3] + p[27] * plg[1][5] + t71) * self.ctloc
+                    + (p[6] * plg[1][1] + p[7] * plg[1][3] + p[28] * plg[1][5] + t72) * self.stloc);
+        }
+
+        // Semidiurnal
+        if self.sw[8] != 0.0 {
+            let t81 = (p[23] * plg[2][3] + p[35] * plg[2][5]) * cd14 * self.swc[5];
+            let t82 = (p[33] * plg[2][3] + p[36] * plg[2][5]) * cd14 * self.swc[5];
+            t[7] = f2
+                * ((p[5] * plg[2][2] + p[41] * plg[2][4] + t81) * self.c2tloc
+                    + (p[8] * plg[2][2] + p[42] * plg[2][4] + t82) * self.s2tloc);
+        }
+
+        // Terdiurnal
+        if self.sw[14] != 0.0 {
+            t[13] = f2
+                * ((p[39] * plg[3][3]
+                    + (p[93] * plg[3][4] + p[46] * plg[3][6]) * cd14 * self.swc[5])
+                    * self.s3tloc
+                    + (p[40] * plg[3][3]
+                        + (p[94] * plg[3][4] + p[48] * plg[3][6]) * cd14 * self.swc[5])
+                        * self.c3tloc);
+        }
+
+        // Magnetic activity
+        if self.sw[9] == -1.0 {
+            let ap = input.ap_history.unwrap_or([input.ap; 7]);
+            if p[51] != 0.0 {
+                let exp1 = (-10800.0 * p[51].abs()
+                    / (1.0 + p[138] * (45.0 - input.g_lat_deg.abs())))
+                .exp()
+                .min(0.99999);
+                self.apt[0] = sg0(exp1, p[24].max(1.0E-4), p[25], &ap);
+                t[8] = self.apt[0]
+                    * (p[50]
+                        + p[96] * plg[0][2]
+                        + p[54] * plg[0][4]
+                        + (p[125] * plg[0][1] + p[126] * plg[0][3] + p[127] * plg[0][5])
+                            * cd14
+                            * self.swc[5]
+                        + (p[128] * plg[1][1] + p[129] * plg[1][3] + p[130] * plg[1][5])
+                            * self.swc[7]
+                            * (HR * (tloc - p[131])).cos());
+            }
+        } else {
+            let apd = input.ap - 4.0;
+            let p44 = if p[43] < 0.0 { 1.0E-5 } else { p[43] };
+            let p45 = p[44];
+            self.apdf = apd + (p45 - 1.0) * (apd + ((-p44 * apd).exp() - 1.0) / p44);
+            if self.sw[9] != 0.0 {
+                t[8] = self.apdf
+                    * (p[32]
+                        + p[45] * plg[0][2]
+                        + p[34] * plg[0][4]
+                        + (p[100] * plg[0][1] + p[101] * plg[0][3] + p[102] * plg[0][5])
+                            * cd14
+                            * self.swc[5]
+                        + (p[121] * plg[1][1] + p[122] * plg[1][3] + p[123] * plg[1][5])
+                            * self.swc[7]
+                            * (HR * (tloc - p[124])).cos());
+            }
+        }
+
+        if self.sw[10] != 0.0 && input.g_long_deg > -1000.0 {
+            let (sin_long, cos_long) = (DGTR * input.g_long_deg).sin_cos();
+            // Longitudinal
+            if self.sw[11] != 0.0 {
+                t[10] = (1.0 + p[80] * dfa * self.swc[1])
+                    * ((p[64] * plg[1][2]
+                        + p[65] * plg[1][4]
+                        + p[66] * plg[1][6]
+                        + p[103] * plg[1][1]
+                        + p[104] * plg[1][3]
+                        + p[105] * plg[1][5]
+                        + self.swc[5]
+                            * (p[109] * plg[1][1] + p[110] * plg[1][3] + p[111] * plg[1][5])
+                            * cd14)
+                        * cos_long
+                        + (p[90] * plg[1][2]
+                            + p[91] * plg[1][4]
+                            + p[92] * plg[1][6]
+                            + p[106] * plg[1][1]
+                            + p[107] * plg[1][3]
+                            + p[108] * plg[1][5]
+                            + self.swc[5]
+                                * (p[112] * plg[1][1] + p[113] * plg[1][3] + p[114] * plg[1][5])
+                                * cd14)
+                            * sin_long);
+            }
+
+            // UT and mixed UT and longitude
+            if self.sw[12] != 0.0 {
+                t[11] = (1.0 + p[95] * plg[0][1])
+                    * (1.0 + p[81] * dfa * self.swc[1])
+                    * (1.0 + p[119] * plg[0][1] * self.swc[5] * cd14)
+                    * ((p[68] * plg[0][1] + p[69] * plg[0][3] + p[70] * plg[0][5])
+                        * (SR * (input.sec - p[71])).cos());
+                t[11] += self.swc[11]
+                    * (p[76] * plg[2][3] + p[77] * plg[2][5] + p[78] * plg[2][7])
+                    * (SR * (input.sec - p[79]) + 2.0 * DGTR * input.g_long_deg).cos()
+                    * (1.0 + p[137] * dfa * self.swc[1]);
+            }
+
+            // UT, longitude and magnetic activity
+            if self.sw[13] != 0.0 {
+                if self.sw[9] == -1.0 {
+                    if p[51] != 0.0 {
+                        t[12] = self.apt[0]
+                            * self.swc[11]
+                            * (1.0 + p[132] * plg[0][1])
+                            * ((p[52] * plg[1][2] + p[98] * plg[1][4] + p[67] * plg[1][6])
+                                * (DGTR * (input.g_long_deg - p[97])).cos())
+                            + self.apt[0]
+                                * self.swc[11]
+                                * self.swc[5]
+                                * (p[133] * plg[1][1] + p[134] * plg[1][3] + p[135] * plg[1][5])
+                                * cd14
+                                * (DGTR * (input.g_long_deg - p[136])).cos()
+                            + self.apt[0]
+                                * self.swc[12]
+                                * (p[55] * plg[0][1] + p[56] * plg[0][3] + p[57] * plg[0][5])
+                                * (SR * (input.sec - p[58])).cos();
+                    }
+                } else {
+                    t[12] = self.apdf
+                        * self.swc[11]
+                        * (1.0 + p[120] * plg[0][1])
+                        * ((p[60] * plg[1][2] + p[61] * plg[1][4] + p[62] * plg[1][6])
+                            * (DGTR * (input.g_long_deg - p[63])).cos())
+                        + self.apdf
+                            * self.swc[11]
+                            * self.swc[5]
+                            * (p[115] * plg[1][1] + p[116] * plg[1][3] + p[117] * plg[1][5])
+                            * cd14
+                            * (DGTR * (input.g_long_deg - p[118])).cos()
+                        + self.apdf
+                            * self.swc[12]
+                            * (p[83] * plg[0][1] + p[84] * plg[0][3] + p[85] * plg[0][5])
+                            * (SR * (input.sec - p[75])).cos();
+                }
+            }
+        }
+
+        // Parameters not used: 82, 89, 99, 139-149
+        p[30]
+            + self.sw[1..15]
+                .iter()
+                .zip(t.iter())
+                .map(|(sw, t)| sw.abs() * t)
+                .sum::<f64>()
+    }
+
+    /// Version of G(L) for the lower atmosphere
+    fn glob7s(&self, p: &[f64], input: &Nrlmsise00Input) -> f64 {
+        let mut t = [0.0; 14];
+        let plg = &self.plg;
+        let cd32 = (DR * (input.doy - p[31])).cos();
+        let cd18 = (2.0 * DR * (input.doy - p[17])).cos();
+        let cd14 = (DR * (input.doy - p[13])).cos();
+        let cd39 = (2.0 * DR * (input.doy - p[38])).cos();
+
+        // F10.7
+        t[0] = p[21] * self.dfa;
+        // Time independent
+        t[1] = p[1] * plg[0][2]
+            + p[2] * plg[0][4]
+            + p[22] * plg[0][6]
+            + p[26] * plg[0][1]
+            + p[14] * plg[0][3]
+            + p[59] * plg[0][5];
+        // Symmetrical annual
+        t[2] = (p[18] + p[47] * plg[0][2] + p[29] * plg[0][4]) * cd32;
+        // Symmetrical semiannual
+        t[3] = (p[15] + p[16] * plg[0][2] + p[30] * plg[0][4]) * cd18;
+        // Asymmetrical annual
+        t[4] = (p[9] * plg[0][1] + p[10] * plg[0][3] + p[20] * plg[0][5]) * cd14;
+        // Asymmetrical semiannual
+        t[5] = (p[37] * plg[0][1]) * cd39;
+
+        // Diurnal
+        if self.sw[7] != 0.0 {
+            let t71 = p[11] * plg[1][2] * cd14 * self.swc[5];
+            let t72 = p[12] * plg[1][2] * cd14 * self.swc[5];
+            t[6] = (p[3] * plg[1][1] + p[4] * plg[1][3] + t71) * self.ctloc
+                + (p[6] * plg[1][1] + p[7] * plg[1][3] + t72) * self.stloc;
+        }
+
+        // Semidiurnal
+        if self.sw[8] != 0.0 {
+            let t81 = (p[23] * plg[2][3] + p[35] * plg[2][5]) * cd14 * self.swc[5];
+            let t82 = (p[33] * plg[2][3] + p[36] * plg[2][5]) * cd14 * self.swc[5];
+            t[7] = (p[5] * plg[2][2] + p[41] * plg[2][4] + t81) * self.c2tloc
+                + (p[8] * plg[2][2] + p[42] * plg[2][4] + t82) * self.s2tloc;
+        }
+
+        // Terdiurnal
+        if self.sw[14] != 0.0 {
+            t[13] = p[39] * plg[3][3] * self.s3tloc + p[40] * plg[3][3] * self.c3tloc;
+        }
+
+        // Magnetic activity
+        if self.sw[9] == 1.0 {
+            t[8] = self.apdf * (p[32] + p[45] * plg[0][2] * self.swc[2]);
+        } else if self.sw[9] == -1.0 {
+            t[8] = p[50] * self.apt[0] + p[96] * plg[0][2] * self.apt[0] * self.swc[2];
+        }
+
+        // Longitudinal
+        if !(self.sw[10] == 0.0 || self.sw[11] == 0.0 || input.g_long_deg <= -1000.0) {
+            let (sin_long, cos_long) = (DGTR * input.g_long_deg).sin_cos();
+            t[10] = (1.0
+                + plg[0][1]
+                    * (p[80] * self.swc[5] * (DR * (input.doy - p[81])).cos()
+                        + p[85] * self.swc[6] * (2.0 * DR * (input.doy - p[86])).cos())
+                + p[83] * self.swc[3] * (DR * (input.doy - p[84])).cos()
+                + p[87] * self.swc[4] * (2.0 * DR * (input.doy - p[88])).cos())
+                * ((p[64] * plg[1][2]
+                    + p[65] * plg[1][4]
+                    + p[66] * plg[1][6]
+                    + p[74] * plg[1][1]
+                    + p[75] * plg[1][3]
+                    + p[76] * plg[1][5])
+                    * cos_long
+                    + (p[90] * plg[1][2]
+                        + p[91] * plg[1][4]
+                        + p[92] * plg[1][6]
+                        + p[77] * plg[1][1]
+                        + p[78] * plg[1][3]
+                        + p[79] * plg[1][5])
+                        * sin_long);
+        }
+
+        self.sw[1..15]
+            .iter()
+            .zip(t.iter())
+            .map(|(sw, t)| sw.abs() * t)
+            .sum()
+    }
+
+    /// Thermospheric portion of the model, valid above 72.5 km
+    fn gts7(&mut self, m: &Nrlmsise00, input: &Nrlmsise00Input) -> ([f64; 9], [f64; 2]) {
+        const ALPHA: [f64; 9] = [-0.38, 0.0, 0.0, 0.0, 0.17, 0.0, -0.38, 0.0, 0.0];
+        const ALTL: [f64; 8] = [200.0, 300.0, 160.0, 250.0, 240.0, 450.0, 320.0, 450.0];
+        let (pdl, pdm, ptm) = (&m.pdl, &m.pdm, &m.ptm);
+        let mut d = [0.0; 9];
+        let mut t = [0.0; 2];
+        let mut tz = 0.0;
+
+        let za = pdl[1][15];
+        let mut zn1 = ZN1;
+        zn1[0] = za;
+
+        // Exospheric temperature variations are not important below ZA
+        let tinf = if input.alt_km > zn1[0] {
+            ptm[0] * m.pt[0] * (1.0 + self.sw[16] * self.globe7(&m.pt, input))
+        } else {
+            ptm[0] * m.pt[0]
+        };
+        t[0] = tinf;
+
+        // Gradient variations are not important below the last node
+        let g0 = if input.alt_km > zn1[4] {
+            ptm[3] * m.ps[0] * (1.0 + self.sw[19] * self.globe7(&m.ps, input))
+        } else {
+            ptm[3] * m.ps[0]
+        };
+        let tlb = ptm[1] * (1.0 + self.sw[17] * self.globe7(&m.pd[3], input)) * m.pd[3][0];
+        let s = g0 / (tinf - tlb);
+
+        // Lower thermosphere temperature variations are not significant for the density above 300 km
+        if input.alt_km < 300.0 {
+            self.meso_tn1[1] =
+                ptm[6] * m.ptl[0][0] / (1.0 - self.sw[18] * self.glob7s(&m.ptl[0], input));
+            self.meso_tn1[2] =
+                ptm[2] * m.ptl[1][0] / (1.0 - self.sw[18] * self.glob7s(&m.ptl[1], input));
+            self.meso_tn1[3] =
+                ptm[7] * m.ptl[2][0] / (1.0 - self.sw[18] * self.glob7s(&m.ptl[2], input));
+            self.meso_tn1[4] = ptm[4] * m.ptl[3][0]
+                / (1.0 - self.sw[18] * self.sw[20] * self.glob7s(&m.ptl[3], input));
+            self.meso_tgn1[1] = ptm[8]
+                * m.pma[8][0]
+                * (1.0 + self.sw[18] * self.sw[20] * self.glob7s(&m.pma[8], input))
+                * self.meso_tn1[4]
+                * self.meso_tn1[4]
+                / (ptm[4] * m.ptl[3][0]).powi(2);
+        } else {
+            self.meso_tn1[1] = ptm[6] * m.ptl[0][0];
+            self.meso_tn1[2] = ptm[2] * m.ptl[1][0];
+            self.meso_tn1[3] = ptm[7] * m.ptl[2][0];
+            self.meso_tn1[4] = ptm[4] * m.ptl[3][0];
+            self.meso_tgn1[1] = ptm[8] * m.pma[8][0] * self.meso_tn1[4] * self.meso_tn1[4]
+                / (ptm[4] * m.ptl[3][0]).powi(2);
+        }
+
+        // N2 variation factor at Zlb
+        let g28 = self.sw[21] * self.globe7(&m.pd[2], input);
+        // Variation of the turbopause height
+        let zhf = pdl[1][24]
+            * (1.0
+                + self.sw[5]
+                    * pdl[0][24]
+                    * (DGTR * input.g_lat_deg).sin()
+                    * (DR * (input.doy - m.pt[13])).cos());
+        let xmm = pdm[2][4];
+        let z = input.alt_km;
+
+        // N2 density
+        let db28 = pdm[2][0] * g28.exp() * m.pd[2][0];
+        d[2] = self.densu(
+            z, db28, tinf, tlb, 28.0, ALPHA[2], &mut t[1], ptm[5], s, &zn1,
+        );
+        let zh28 = pdm[2][2] * zhf;
+        let zhm28 = pdm[2][3] * pdl[1][5];
+        let xmd = 28.0 - xmm;
+        let b28 = self.densu(
+            zh28,
+            db28,
+            tinf,
+            tlb,
+            xmd,
+            ALPHA[2] - 1.0,
+            &mut tz,
+            ptm[5],
+            s,
+            &zn1,
+        );
+        if self.sw[15] != 0.0 && z <= ALTL[2] {
+            self.dm28 = self.densu(z, b28, tinf, tlb, xmm, ALPHA[2], &mut tz, ptm[5], s, &zn1);
+            d[2] = dnet(d[2], self.dm28, zhm28, xmm, 28.0);
+        }
+
+        // He density
+        let g4 = self.sw[21] * self.globe7(&m.pd[0], input);
+        let db04 = pdm[0][0] * g4.exp() * m.pd[0][0];
+        d[0] = self.densu(
+            z, db04, tinf, tlb, 4.0, ALPHA[0], &mut t[1], ptm[5], s, &zn1,
+        );
+        if self.sw[15] != 0.0 && z < ALTL[0] {
+            let zh04 = pdm[0][2];
+            let b04 = self.densu(
+                zh04,
+                db04,
+                tinf,
+                tlb,
+                4.0 - xmm,
+                ALPHA[0] - 1.0,
+                &mut t[1],
+                ptm[5],
+                s,
+                &zn1,
+            );
+            let dm04 = self.densu(z, b04, tinf, tlb, xmm, 0.0, &mut t[1], ptm[5], s, &zn1);
+            d[0] = dnet(d[0], dm04, zhm28, xmm, 4.0);
+            // Correction to the specified mixing ratio at the ground
+            let rl = (b28 * pdm[0][1] / b04).ln();
+            let zc04 = pdm[0][4] * pdl[1][0];
+            let hc04 = pdm[0][5] * pdl[1][1];
+            d[0] *= ccor(z, rl, hc04, zc04);
+        }
+
+        // O density
+        let g16 = self.sw[21] * self.globe7(&m.pd[1], input);
+        let db16 = pdm[1][0] * g16.exp() * m.pd[1][0];
+        d[1] = self.densu(
+            z, db16, tinf, tlb, 16.0, ALPHA[1], &mut t[1], ptm[5], s, &zn1,
+        );
+        if self.sw[15] != 0.0 && z <= ALTL[1] {
+            let zh16 = pdm[1][2];
+            let b16 = self.densu(
+                zh16,
+                db16,
+                tinf,
+                tlb,
+                16.0 - xmm,
+                ALPHA[1] - 1.0,
+                &mut t[1],
+                ptm[5],
+                s,
+                &zn1,
+            );
+            let dm16 = self.densu(z, b16, tinf, tlb, xmm, 0.0, &mut t[1], ptm[5], s, &zn1);
+            d[1] = dnet(d[1], dm16, zhm28, xmm, 16.0);
+            let rl =
+                pdm[1][1] * pdl[1][16] * (1.0 + self.sw[1] * pdl[0][23] * (input.f107a - 150.0));
+            let hc16 = pdm[1][5] * pdl[1][3];
+            let zc16 = pdm[1][4] * pdl[1][2];
+            let hc216 = pdm[1][5] * pdl[1][4];
+            d[1] *= ccor2(z, rl, hc16, zc16, hc216);
+            // Chemistry correction
+            let hcc16 = pdm[1][7] * pdl[1][13];
+            let zcc16 = pdm[1][6] * pdl[1][12];
+            let rc16 = pdm[1][3] * pdl[1][14];
+            d[1] *= ccor(z, rc16, hcc16, zcc16);
+        }
+
+        // O2 density
+        let g32 = self.sw[21] * self.globe7(&m.pd[4], input);
+        let db32 = pdm[3][0] * g32.exp() * m.pd[4][0];
+        d[3] = self.densu(
+            z, db32, tinf, tlb, 32.0, ALPHA[3], &mut t[1], ptm[5], s, &zn1,
+        );
+        if self.sw[15] != 0.0 {
+            if z <= ALTL[3] {
+                let zh32 = pdm[3][2];
+                let b32 = self.densu(
+                    zh32,
+                    db32,
+                    tinf,
+                    tlb,
+                    32.0 - xmm,
+                    ALPHA[3] - 1.0,
+                    &mut t[1],
+                    ptm[5],
+                    s,
+                    &zn1,
+                );
+                let dm32 = self.densu(z, b32, tinf, tlb, xmm, 0.0, &mut t[1], ptm[5], s, &zn1);
+                d[3] = dnet(d[3], dm32, zhm28, xmm, 32.0);
+                // Correction to the specified mixing ratio at the ground
+                let rl = (b28 * pdm[3][1] / b32).ln();
+                let hc32 = pdm[3][5] * pdl[1][7];
+                let zc32 = pdm[3][4] * pdl[1][6];
+                d[3] *= ccor(z, rl, hc32, zc32);
+            }
+            // Correction for the departure from the diffusive equilibrium above Zlb
+            let hcc32 = pdm[3][7] * pdl[1][22];
+            let hcc232 = pdm[3][7] * pdl[0][22];
+            let zcc32 = pdm[3][6] * pdl[1][21];
+            let rc32 =
+                pdm[3][3] * pdl[1][23] * (1.0 + self.sw[1] * pdl[0][23] * (input.f107a - 150.0));
+            d[3] *= ccor2(z, rc32, hcc32, zcc32, hcc232);
+        }
+
+        // Ar density
+        let g40 = self.sw[21] * self.globe7(&m.pd[5], input);
+        let db40 = pdm[4][0] * g40.exp() * m.pd[5][0];
+        d[4] = self.densu(
+            z, db40, tinf, tlb, 40.0, ALPHA[4], &mut t[1], ptm[5], s, &zn1,
+        );
+        if self.sw[15] != 0.0 && z <= ALTL[4] {
+            let zh40 = pdm[4][2];
+            let b40 = self.densu(
+                zh40,
+                db40,
+                tinf,
+                tlb,
+                40.0 - xmm,
+                ALPHA[4] - 1.0,
+                &mut t[1],
+                ptm[5],
+                s,
+                &zn1,
+            );
+            let dm40 = self.densu(z, b40, tinf, tlb, xmm, 0.0, &mut t[1], ptm[5], s, &zn1);
+            d[4] = dnet(d[4], dm40, zhm28, xmm, 40.0);
+            // Correction to the specified mixing ratio at the ground
+            let rl = (b28 * pdm[4][1] / b40).ln();
+            let hc40 = pdm[4][5] * pdl[1][9];
+            let zc40 = pdm[4][4] * pdl[1][8];
+            d[4] *= ccor(z, rl, hc40, zc40);
+        }
+
+        // H density
+        let g1 = self.sw[21] * self.globe7(&m.pd[6], input);
+        let db01 = pdm[5][0] * g1.exp() * m.pd[6][0];
+        d[6] = self.densu(
+            z, db01, tinf, tlb, 1.0, ALPHA[6], &mut t[1], ptm[5], s, &zn1,
+        );
+        if self.sw[15] != 0.0 && z <= ALTL[6] {
+            let zh01 = pdm[5][2];
+            let b01 = self.densu(
+                zh01,
+                db01,
+                tinf,
+                tlb,
+                1.0 - xmm,
+                ALPHA[6] - 1.0,
+                &mut t[1],
+                ptm[5],
+                s,
+                &zn1,
+            );
+            let dm01 = self.densu(z, b01, tinf, tlb, xmm, 0.0, &mut t[1], ptm[5], s, &zn1);
+            d[6] = dnet(d[6], dm01, zhm28, xmm, 1.0);
+            // Correction to the specified mixing ratio at the ground
+            let rl = (b28 * pdm[5][1] * pdl[1][17].abs() / b01).ln();
+            let hc01 = pdm[5][5] * pdl[1][11];
+            let zc01 = pdm[5][4] * pdl[1][10];
+            d[6] *= ccor(z, rl, hc01, zc01);
+            // Chemistry correction
+            let hcc01 = pdm[5][7] * pdl[1][19];
+            let zcc01 = pdm[5][6] * pdl[1][18];
+            let rc01 = pdm[5][3] * pdl[1][20];
+            d[6] *= ccor(z, rc01, hcc01, zcc01);
+        }
+
+        // N density
+        let g14 = self.sw[21] * self.globe7(&m.pd[7], input);
+        let db14 = pdm[6][0] * g14.exp() * m.pd[7][0];
+        d[7] = self.densu(
+            z, db14, tinf, tlb, 14.0, ALPHA[7], &mut t[1], ptm[5], s, &zn1,
+        );
+        if self.sw[15] != 0.0 && z <= ALTL[7] {
+            let zh14 = pdm[6][2];
+            let b14 = self.densu(
+                zh14,
+                db14,
+                tinf,
+                tlb,
+                14.0 - xmm,
+                ALPHA[7] - 1.0,
+                &mut t[1],
+                ptm[5],
+                s,
+                &zn1,
+            );
+            let dm14 = self.densu(z, b14, tinf, tlb, xmm, 0.0, &mut t[1], ptm[5], s, &zn1);
+            d[7] = dnet(d[7], dm14, zhm28, xmm, 14.0);
+            // Correction to the specified mixing ratio at the ground
+            let rl = (b28 * pdm[6][1] * pdl[0][2].abs() / b14).ln();
+            let hc14 = pdm[6][5] * pdl[0][1];
+            let zc14 = pdm[6][4] * pdl[0][0];
+            d[7] *= ccor(z, rl, hc14, zc14);
+            // Chemistry correction
+            let hcc14 = pdm[6][7] * pdl[0][4];
+            let zcc14 = pdm[6][6] * pdl[0][3];
+            let rc14 = pdm[6][3] * pdl[0][5];
+            d[7] *= ccor(z, rc14, hcc14, zcc14);
+        }
+
+        // Anomalous oxygen density
+        let g16h = self.sw[21] * self.globe7(&m.pd[8], input);
+        let db16h = pdm[7][0] * g16h.exp() * m.pd[8][0];
+        let tho = pdm[7][9] * pdl[0][6];
+        let dd = self.densu(
+            z, db16h, tho, tho, 16.0, ALPHA[8], &mut t[1], ptm[5], s, &zn1,
+        );
+        let zsht = pdm[7][5];
+        let zmho = pdm[7][4];
+        let zsho = self.scalh(zmho, 16.0, tho);
+        d[8] = dd * (-zsht / zsho * ((-(z - zmho) / zsht).exp() - 1.0)).exp();
+
+        // Total mass density
+        d[5] = 1.66E-24
+            * (4.0 * d[0]
+                + 16.0 * d[1]
+                + 28.0 * d[2]
+                + 32.0 * d[3]
+                + 40.0 * d[4]
+                + d[6]
+                + 14.0 * d[7]);
+
+        // Temperature at altitude
+        self.densu(
+            z.abs(),
+            1.0,
+            tinf,
+            tlb,
+            0.0,
+            0.0,
+            &mut t[1],
+            ptm[5],
+            s,
+            &zn1,
+        );
+
+        // Convert to SI units: number densities in m^-3 and mass density in kg/m^3
+        for di in d.iter_mut() {
+            *di *= 1.0E6;
+        }
+        d[5] /= 1000.0;
+        (d, t)
+    }
+
+    /// Full model, with the lower atmosphere below 72.5 km
+    fn gtd7(&mut self, m: &Nrlmsise00, input: &Nrlmsise00Input) -> ([f64; 9], [f64; 2]) {
+        let (pma, pavgm, pdm) = (&m.pma, &m.pavgm, &m.pdm);
+
+        // Latitude variation of gravity (none for switch 2 off)
+        let xlat = if self.sw[2] == 0.0 {
+            45.0
+        } else {
+            input.g_lat_deg
+        };
+        (self.gsurf, self.re) = glatf(xlat);
+        let xmm = pdm[2][4];
+
+        // Thermosphere and mesosphere, above 72.5 km
+        let mut thermo_input = *input;
+        thermo_input.alt_km = input.alt_km.max(ZN2[0]);
+        let (thermo_d, thermo_t) = self.gts7(m, &thermo_input);
+        let dm28m = self.dm28 * 1.0E6;
+        let mut d = [0.0; 9];
+        let mut t = thermo_t;
+        if input.alt_km >= ZN2[0] {
+            return (thermo_d, t);
+        }
+
+        // Lower mesosphere and upper stratosphere, between 32.5 and 72.5 km
+        self.meso_tgn2[0] = self.meso_tgn1[1];
+        self.meso_tn2[0] = self.meso_tn1[4];
+        self.meso_tn2[1] = pma[0][0] * pavgm[0] / (1.0 - self.sw[20] * self.glob7s(&pma[0], input));
+        self.meso_tn2[2] = pma[1][0] * pavgm[1] / (1.0 - self.sw[20] * self.glob7s(&pma[1], input));
+        self.meso_tn2[3] =
+            pma[2][0] * pavgm[2] / (1.0 - self.sw[20] * self.sw[22] * self.glob7s(&pma[2], input));
+        self.meso_tgn2[1] = pavgm[8]
+            * pma[9][0]
+            * (1.0 + self.sw[20] * self.sw[22] * self.glob7s(&pma[9], input))
+            * self.meso_tn2[3]
+            * self.meso_tn2[3]
+            / (pma[2][0] * pavgm[2]).powi(2);
+        self.meso_tn3[0] = self.meso_tn2[3];
+
+        if input.alt_km < ZN3[0] {
+            // Lower stratosphere and troposphere, below 32.5 km
+            self.meso_tgn3[0] = self.meso_tgn2[1];
+            self.meso_tn3[1] =
+                pma[3][0] * pavgm[3] / (1.0 - self.sw[22] * self.glob7s(&pma[3], input));
+            self.meso_tn3[2] =
+                pma[4][0] * pavgm[4] / (1.0 - self.sw[22] * self.glob7s(&pma[4], input));
+            self.meso_tn3[3] =
+                pma[5][0] * pavgm[5] / (1.0 - self.sw[22] * self.glob7s(&pma[5], input));
+            self.meso_tn3[4] =
+                pma[6][0] * pavgm[6] / (1.0 - self.sw[22] * self.glob7s(&pma[6], input));
+            self.meso_tgn3[1] = pma[7][0]
+                * pavgm[7]
+                * (1.0 + self.sw[22] * self.glob7s(&pma[7], input))
+                * self.meso_tn3[4]
+                * self.meso_tn3[4]
+                / (pma[6][0] * pavgm[6]).powi(2);
+        }
+
+        // Linear transition to full mixing below 72.5 km
+        let dmc = if input.alt_km > ZMIX {
+            1.0 - (ZN2[0] - input.alt_km) / (ZN2[0] - ZMIX)
+        } else {
+            0.0
+        };
+        let dz28 = thermo_d[2];
+        let mut tz = 0.0;
+
+        // N2 density
+        let dmr = thermo_d[2] / dm28m - 1.0;
+        d[2] = self.densm(input.alt_km, dm28m, xmm, &mut tz) * (1.0 + dmr * dmc);
+        // He density
+        let dmr = thermo_d[0] / (dz28 * pdm[0][1]) - 1.0;
+        d[0] = d[2] * pdm[0][1] * (1.0 + dmr * dmc);
+        // O2 density
+        let dmr = thermo_d[3] / (dz28 * pdm[3][1]) - 1.0;
+        d[3] = d[2] * pdm[3][1] * (1.0 + dmr * dmc);
+        // Ar density
+        let dmr = thermo_d[4] / (dz28 * pdm[4][1]) - 1.0;
+        d[4] = d[2] * pdm[4][1] * (1.0 + dmr * dmc);
+        // Atomic oxygen, hydrogen and atomic nitrogen are negligible
+
+        // Total mass density in kg/m^3
+        d[5] = 1.66E-24 * (4.0 * d[0] + 32.0 * d[3] + 28.0 * d[2] + 40.0 * d[4]) / 1000.0;
+
+        // Temperature at altitude
+        self.densm(input.alt_km, 1.0, 0.0, &mut tz);
+        t[1] = tz;
+        (d, t)
+    }
+}
+
+#[cfg(test)]
+mod ut_nrlmsise00 {
+    use super::*;
+
+    /// Builds a text kernel of the model coefficients, where only the base values are set.
+    fn base_data() -> String {
+        fn array(name: &str, values: &[f64]) -> String {
+            let values: Vec<String> = values.iter().map(|v| format!("{v:.5E}")).collect();
+            format!("   NRLMSISE00_{name} = ( {} )\n", values.join(" "))
+        }
+        let base = |len: usize, first: f64| {
+            let mut values = vec![0.0; len];
+            values[0] = first;
+            if len == 150 {
+                // Shape of the magnetic activity function
+                values[43] = 1.0;
+            }
+            values
+        };
+        let pd: Vec<f64> = (0..9).flat_map(|_| base(150, 1.0)).collect();
+        let ptl: Vec<f64> = (0..4).flat_map(|_| base(100, 1.0)).collect();
+        let pma: Vec<f64> = (0..10).flat_map(|_| base(100, 1.0)).collect();
+        let mut pdl = vec![1.0; 50];
+        pdl[23] = 0.0;
+        pdl[24] = 0.0;
+        pdl[25 + 15] = 120.0;
+        let ptm = [
+            1.04130E+03,
+            3.86000E+02,
+            1.95000E+02,
+            1.66728E+01,
+            2.13000E+02,
+            1.20000E+02,
+            2.40000E+02,
+            1.87000E+02,
+            -2.00000E+00,
+            0.00000E+00,
+        ];
+        let pdm = [
+            [
+                2.456E+07,
+                6.71072E-06,
+                1.0E+02,
+                0.0,
+                1.1E+02,
+                1.0E+01,
+                0.0,
+                0.0,
+                0.0,
+                0.0,
+            ],
+            [
+                8.594E+10, 1.0, 1.05E+02, -8.0, 1.1E+02, 1.0E+01, 9.0E+01, 2.0, 0.0, 0.0,
+            ],
+            [
+                2.81E+11, 0.0, 1.05E+02, 2.8E+01, 2.895E+01, 0.0, 0.0, 0.0, 0.0, 0.0,
+            ],
+            [
+                3.3E+10, 2.6827E-01, 1.05E+02, 1.0, 1.1E+02, 1.0E+01, 1.1E+02, -1.0E+01, 0.0, 0.0,
+            ],
+            [
+                1.33E+09,
+                1.19615E-02,
+                1.05E+02,
+                0.0,
+                1.1E+02,
+                1.0E+01,
+                0.0,
+                0.0,
+                0.0,
+                0.0,
+            ],
+            [
+                1.761E+05, 1.0, 9.5E+01, -8.0, 1.1E+02, 1.0E+01, 9.0E+01, 2.0, 0.0, 0.0,
+            ],
+            [
+                1.0E+07, 1.0, 1.05E+02, -8.0, 1.1E+02, 1.0E+01, 9.0E+01, 2.0, 0.0, 0.0,
+            ],
+            [
+                1.0E+06, 1.0, 1.05E+02, -8.0, 5.5E+02, 7.6E+01, 9.0E+01, 2.0, 0.0, 4.0E+03,
+            ],
+        ]
+        .concat();
+        let pavgm = [
+            2.61E+02, 2.64E+02, 2.29E+02, 2.17E+02, 2.17E+02, 2.23E+02, 2.8676E+02, -2.9394, 2.5,
+            0.0,
+        ];
+        [
+            "NRLMSISE-00 coefficients\n\\begindata\n".to_string(),
+            array("PT", &base(150, 0.986573)),
+            array("PD", &pd),
+            array("PS", &base(150, 1.0)),
+            array("PDL", &pdl),
+            array("PTL", &ptl),
+            array("PMA", &pma),
+            array("SAM", &base(100, 0.0)),
+            array("PTM", &ptm),
+            array("PDM", &pdm),
+            array("PAVGM", &pavgm),
+            "\\begintext\n".to_string(),
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn nrlmsise00_base_profile() {
+        let model = Nrlmsise00::from_text(&base_data()).unwrap();
+        println!("{model:?}");
+        let mut input = Nrlmsise00Input {
+            doy: 172.0,
+            sec: 29000.0,
+            alt_km: 1000.0,
+            g_lat_deg: 60.0,
+            g_long_deg: -70.0,
+            lst_h: 16.0,
+            f107a: 150.0,
+            f107: 150.0,
+            ap: 4.0,
+            ap_history: None,
+        };
+
+        // Without any variation, the exospheric temperature is the mean exospheric temperature of the model
+        let out = model.gtd7d(&input);
+        println!("{out:?}");
+        assert!((out.t_exo_k - 1041.30 * 0.986573).abs() < 1e-9);
+        assert!((out.t_k - out.t_exo_k).abs() < 1e-3);
+
+        // The density decreases with altitude through all of the layers
+        let mut prev_rho = f64::INFINITY;
+        for alt_km in [0.0, 10.0, 30.0, 50.0, 70.0, 100.0, 200.0, 400.0, 800.0] {
+            input.alt_km = alt_km;
+            let out = model.gtd7d(&input);
+            assert!(out.rho_kg_m3.is_finite() && out.rho_kg_m3 > 0.0);
+            assert!(out.rho_kg_m3 < prev_rho, "{alt_km} km: {out:?}");
+            prev_rho = out.rho_kg_m3;
+        }
+
+        // The temperature of the lowest node is the mean ground temperature of the model
+        input.alt_km = 0.0;
+        assert!((model.gtd7(&input).t_k - 286.76).abs() < 1e-9);
+
+        // Missing or truncated arrays
+        assert!(Nrlmsise00::from_text("\\begindata\nNRLMSISE00_PT = ( 1.0 2.0 )\n").is_err());
+        assert!(Nrlmsise00::from_text(&base_data().replace("PAVGM", "PAVG")).is_err());
+    }
+}
@@ -0,0 +1,230 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::{Dynamics, ForceModel};
+use crate::cosmic::{Attitude, Spacecraft, ATTITUDE_OFFSET};
+use crate::errors::NyxError;
+use crate::linalg::{Const, Matrix3, OVector, Vector3, Vector4};
+use crate::State;
+
+use std::fmt;
+use std::sync::Arc;
+
+/// The `TorqueModel` trait handles the torques acting on the spacecraft, computed from the osculating state, which must have an attitude.
+pub trait TorqueModel: Send + Sync + fmt::Display {
+    /// Returns the torque in the body frame, in N·m, given the inertia tensor of the spacecraft in kg·m^2.
+    fn eom(&self, ctx: &Spacecraft, inertia_kg_m2: &Matrix3<f64>)
+        -> Result<Vector3<f64>, NyxError>;
+}
+
+/// Gravity gradient torque from the central body of the frame of the spacecraft orbit.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct GravityGradient;
+
+impl GravityGradient {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self)
+    }
+}
+
+impl fmt::Display for GravityGradient {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "gravity gradient torque")
+    }
+}
+
+impl TorqueModel for GravityGradient {
+    fn eom(
+        &self,
+        ctx: &Spacecraft,
+        inertia_kg_m2: &Matrix3<f64>,
+    ) -> Result<Vector3<f64>, NyxError> {
+        let attitude = ctx.try_attitude()?;
+        let gm = ctx.orbit.frame.try_gm()?;
+        let rmag = ctx.orbit.rmag_km();
+        // Nadir direction in the body frame: the km cancel out in mu / r^3, which is in s^-2.
+        let r_hat = attitude.inertial_to_body(&(ctx.orbit.radius() / rmag));
+        Ok(3.0 * gm / rmag.powi(3) * r_hat.cross(&(inertia_kg_m2 * r_hat)))
+    }
+}
+
+/// Torque caused by a force model (e.g. solar radiation pressure or drag) applied at a center of pressure offset from the center of mass.
+#[derive(Clone)]
+pub struct PressureTorque {
+    /// Force model which is applied at the center of pressure
+    pub model: Arc<dyn ForceModel>,
+    /// Position of the center of pressure with respect to the center of mass, in the body frame, in meters
+    pub center_of_pressure_m: Vector3<f64>,
+}
+
+impl PressureTorque {
+    pub fn new(model: Arc<dyn ForceModel>, center_of_pressure_m: Vector3<f64>) -> Arc<Self> {
+        Arc::new(Self {
+            model,
+            center_of_pressure_m,
+        })
+    }
+}
+
+impl fmt::Display for PressureTorque {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "torque of {} at [{:.3}, {:.3}, {:.3}] m",
+            self.model,
+            self.center_of_pressure_m[0],
+            self.center_of_pressure_m[1],
+            self.center_of_pressure_m[2]
+        )
+    }
+}
+
+impl TorqueModel for PressureTorque {
+    fn eom(
+        &self,
+        ctx: &Spacecraft,
+        _inertia_kg_m2: &Matrix3<f64>,
+    ) -> Result<Vector3<f64>, NyxError> {
+        let attitude = ctx.try_attitude()?;
+        // Force models return kg·km/s^2, so convert to Newtons.
+        let force_n = attitude.inertial_to_body(&self.model.eom(ctx)?) * 1e3;
+        Ok(self.center_of_pressure_m.cross(&force_n))
+    }
+}
+
+/// Rigid body attitude dynamics: kinematics of the attitude quaternion and Euler's equations with the provided torque models.
+///
+/// These dynamics only propagate the attitude. To propagate the attitude with the orbit, add them to the `SpacecraftDynamics`.
+#[derive(Clone)]
+pub struct AttitudeDynamics {
+    /// Inertia tensor of the spacecraft about its center of mass, in the body frame, in kg·m^2
+    pub inertia_kg_m2: Matrix3<f64>,
+    inertia_inv: Matrix3<f64>,
+    pub torque_models: Vec<Arc<dyn TorqueModel>>,
+}
+
+impl AttitudeDynamics {
+    /// Initializes torque free attitude dynamics, returns an error if the inertia tensor isn't symmetric positive definite.
+    pub fn new(inertia_kg_m2: Matrix3<f64>) -> Result<Self, NyxError> {
+        if (inertia_kg_m2 - inertia_kg_m2.transpose()).norm() > 1e-9 * inertia_kg_m2.norm()
+            || inertia_kg_m2.cholesky().is_none()
+        {
+            return Err(NyxError::MathDomain(format!(
+                "inertia tensor must be symmetric positive definite: {inertia_kg_m2}"
+            )));
+        }
+        Ok(Self {
+            inertia_kg_m2,
+            inertia_inv: inertia_kg_m2.try_inverse().unwrap(),
+            torque_models: Vec::new(),
+        })
+    }
+
+    /// Initializes torque free attitude dynamics from the principal moments of inertia, in kg·m^2.
+    pub fn from_principal_moments(moments_kg_m2: Vector3<f64>) -> Result<Self, NyxError> {
+        Self::new(Matrix3::from_diagonal(&moments_kg_m2))
+    }
+
+    /// Clone these dynamics and add a torque model
+    pub fn with_model(self, torque_model: Arc<dyn TorqueModel>) -> Self {
+        let mut me = self;
+        me.torque_models.push(torque_model);
+        me
+    }
+
+    /// Returns the total torque in the body frame, in N·m
+    pub fn torque(&self, ctx: &Spacecraft) -> Result<Vector3<f64>, NyxError> {
+        let mut torque = Vector3::zeros();
+        for model in &self.torque_models {
+            torque += model.eom(ctx, &self.inertia_kg_m2)?;
+        }
+        Ok(torque)
+    }
+
+    /// Returns the time derivatives of the attitude quaternion (scalar first) and of the angular velocity (Euler's equations).
+    pub fn attitude_rates(
+        &self,
+        ctx: &Spacecraft,
+    ) -> Result<(Vector4<f64>, Vector3<f64>), NyxError> {
+        let attitude = ctx.try_attitude()?;
+        let omega = attitude.omega_rad_s;
+        let omega_dot =
+            self.inertia_inv * (self.torque(ctx)? - omega.cross(&(self.inertia_kg_m2 * omega)));
+        Ok((attitude.quaternion_rate(), omega_dot))
+    }
+
+    /// Angular momentum of the spacecraft in the inertial frame, in kg·m^2/s
+    pub fn angular_momentum(&self, attitude: &Attitude) -> Vector3<f64> {
+        attitude.body_to_inertial(&(self.inertia_kg_m2 * attitude.omega_rad_s))
+    }
+
+    /// Rotational kinetic energy of the spacecraft, in J
+    pub fn rotational_energy(&self, attitude: &Attitude) -> f64 {
+        0.5 * attitude
+            .omega_rad_s
+            .dot(&(self.inertia_kg_m2 * attitude.omega_rad_s))
+    }
+}
+
+impl fmt::Display for AttitudeDynamics {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let torque_models: String = if self.torque_models.is_empty() {
+            "No torque models;".to_string()
+        } else {
+            self.torque_models
+                .iter()
+                .map(|x| format!("{x}; "))
+                .collect()
+        };
+        write!(
+            f,
+            "Attitude dynamics (I = diag[{:.3}, {:.3}, {:.3}] kg m^2): {}",
+            self.inertia_kg_m2[(0, 0)],
+            self.inertia_kg_m2[(1, 1)],
+            self.inertia_kg_m2[(2, 2)],
+            torque_models
+        )
+    }
+}
+
+impl Dynamics for AttitudeDynamics {
+    type HyperdualSize = Const<9>;
+    type StateType = Spacecraft;
+
+    /// Propagates only the attitude, the orbit of the spacecraft is kept fixed.
+    fn eom(
+        &self,
+        delta_t: f64,
+        state: &OVector<f64, Const<97>>,
+        ctx: &Self::StateType,
+    ) -> Result<OVector<f64, Const<97>>, NyxError> {
+        let osc_sc = ctx.set_with_delta_seconds(delta_t, state);
+        let (q_dot, omega_dot) = self.attitude_rates(&osc_sc)?;
+
+        let mut d_x = OVector::<f64, Const<97>>::zeros();
+        for (i, val) in q_dot.iter().chain(omega_dot.iter()).enumerate() {
+            d_x[i + ATTITUDE_OFFSET] = *val;
+        }
+        Ok(d_x)
+    }
+
+    fn finally(&self, next_state: Self::StateType) -> Result<Self::StateType, NyxError> {
+        next_state.try_attitude()?;
+        Ok(next_state)
+    }
+}
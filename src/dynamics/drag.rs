@@ -16,26 +16,149 @@
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
-use super::ForceModel;
-use crate::cosmic::{Cosm, Frame, Spacecraft};
+use super::atmosphere::{jb2008, Jb2008Input, Nrlmsise00, Nrlmsise00Input};
+use super::{ForceModel, MacroModel};
+use crate::cosmic::{Bodies, Cosm, Frame, LightTimeCalc, Orbit, Spacecraft};
 use crate::errors::NyxError;
-use crate::linalg::{Matrix3, Vector3};
+use crate::io::space_weather::SpaceWeather;
+use crate::linalg::{Const, Matrix3, OMatrix, Vector3};
 use std::fmt;
 use std::sync::Arc;
 
 /// Density in kg/m^3 and altitudes in meters, not kilometers!
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 pub enum AtmDensity {
     Constant(f64),
-    Exponential { rho0: f64, r0: f64, ref_alt_m: f64 },
-    StdAtm { max_alt_m: f64 },
+    Exponential {
+        rho0: f64,
+        r0: f64,
+        ref_alt_m: f64,
+    },
+    StdAtm {
+        max_alt_m: f64,
+    },
+    /// NRLMSISE-00 model, driven by the F10.7 and Ap indices of the space weather
+    Nrlmsise00 {
+        model: Arc<Nrlmsise00>,
+        space_weather: Arc<SpaceWeather>,
+    },
+    /// Jacchia-Bowman 2008 model, driven by the JB2008 solar indices of the space weather, and its DTC values or Kp index
+    Jb2008 {
+        space_weather: Arc<SpaceWeather>,
+    },
 }
 
-/// `ConstantDrag` implements a constant drag model as defined in Vallado, 4th ed., page 551, with an important caveat.
+/// A model of the velocity of the atmosphere with respect to the co-rotating atmosphere.
+pub trait WindModel: Send + Sync + fmt::Display {
+    /// Returns the wind velocity in km/s in the axes of the drag frame, at the provided state in the drag frame.
+    fn wind_km_s(&self, osc: &Orbit) -> Result<Vector3<f64>, NyxError>;
+}
+
+/// `HorizontalWind` is a constant wind, defined by its eastward and northward components in the local horizontal plane.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct HorizontalWind {
+    /// Eastward component of the wind in m/s
+    pub east_m_s: f64,
+    /// Northward component of the wind in m/s
+    pub north_m_s: f64,
+}
+
+impl HorizontalWind {
+    pub fn new(east_m_s: f64, north_m_s: f64) -> Arc<Self> {
+        Arc::new(Self {
+            east_m_s,
+            north_m_s,
+        })
+    }
+}
+
+impl fmt::Display for HorizontalWind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "horizontal wind of {} m/s east and {} m/s north",
+            self.east_m_s, self.north_m_s
+        )
+    }
+}
+
+impl WindModel for HorizontalWind {
+    fn wind_km_s(&self, osc: &Orbit) -> Result<Vector3<f64>, NyxError> {
+        let (sin_lat, cos_lat) = osc.geodetic_latitude_deg().to_radians().sin_cos();
+        let (sin_long, cos_long) = osc.geodetic_longitude_deg().to_radians().sin_cos();
+        let east = Vector3::new(-sin_long, cos_long, 0.0);
+        let north = Vector3::new(-sin_lat * cos_long, -sin_lat * sin_long, cos_lat);
+        Ok((self.east_m_s * east + self.north_m_s * north) * 1e-3)
+    }
+}
+
+/// Velocity of the spacecraft relative to the atmosphere, which co-rotates with the drag frame.
+struct RelativeVelocity {
+    /// State of the spacecraft in the drag frame
+    osc: Orbit,
+    /// Relative velocity in km/s in the axes of the integration frame
+    velocity: Vector3<f64>,
+    /// Rotation from the drag frame to the integration frame
+    dcm: Matrix3<f64>,
+    /// Partials of the relative velocity with respect to the position, i.e. minus the skew matrix of the rotation rate of the atmosphere
+    partials: Matrix3<f64>,
+}
+
+impl RelativeVelocity {
+    fn new(
+        cosm: &Cosm,
+        orbit: &Orbit,
+        drag_frame: Frame,
+        wind: Option<&dyn WindModel>,
+    ) -> Result<Self, NyxError> {
+        // The velocity in the body fixed drag frame already excludes the co-rotation (ω × r) of the atmosphere
+        let osc = cosm.try_frame_chg(orbit, drag_frame)?;
+        let (dcm, dcm_dt) =
+            cosm.try_dcm_from_to_in_parts(&drag_frame, &orbit.frame, orbit.epoch)?;
+        let wind = match wind {
+            Some(wind) => wind.wind_km_s(&osc)?,
+            None => Vector3::zeros(),
+        };
+        Ok(Self {
+            osc,
+            velocity: dcm * (osc.velocity() - wind),
+            dcm,
+            partials: -dcm_dt * dcm.transpose(),
+        })
+    }
+
+    /// Returns the drag force in kN and its partials with respect to the position, velocity and Cd, provided the density and its gradient in the integration frame.
+    /// The wind is assumed independent of the state.
+    fn drag(
+        &self,
+        rho: f64,
+        grad_rho: Vector3<f64>,
+        cd: f64,
+        area_m2: f64,
+    ) -> (Vector3<f64>, OMatrix<f64, Const<3>, Const<9>>) {
+        let velocity = self.velocity;
+        let speed = velocity.norm();
+        // Note the 1e3 is to convert the drag from kg/m^3 * m^2 * km^2/s^2 to kN (as the velocity is in km/s)
+        let k = -0.5e3 * cd * area_m2;
+        let force = k * rho * speed * velocity;
+
+        let mut grad = OMatrix::<f64, Const<3>, Const<9>>::zeros();
+        // The drag is linear in Cd
+        grad.set_column(7, &(-0.5e3 * area_m2 * rho * speed * velocity));
+        if speed > 0.0 {
+            let dforce_dv =
+                k * rho * (speed * Matrix3::identity() + velocity * velocity.transpose() / speed);
+            let dforce_dr = dforce_dv * self.partials + k * speed * velocity * grad_rho.transpose();
+            grad.fixed_view_mut::<3, 3>(0, 0).copy_from(&dforce_dr);
+            grad.fixed_view_mut::<3, 3>(0, 3).copy_from(&dforce_dv);
+        }
+        (force, grad)
+    }
+}
+
+/// `ConstantDrag` implements a constant drag model as defined in Vallado, 4th ed., page 551.
 ///
-/// **WARNING:** This basic model assumes that the velocity of the spacecraft is identical to the velocity of the upper atmosphere,
-/// This is a **bad** assumption and **should not** be used for high fidelity simulations.
-/// This will be resolved after https://gitlab.com/chrisrabotin/nyx/issues/93 is implemented.
+/// The velocity of the spacecraft is computed relative to the atmosphere, which co-rotates with the drag frame.
 #[derive(Clone)]
 pub struct ConstantDrag {
     /// atmospheric density in kg/m^3
@@ -58,23 +181,32 @@ impl fmt::Display for ConstantDrag {
 
 impl ForceModel for ConstantDrag {
     fn eom(&self, ctx: &Spacecraft) -> Result<Vector3<f64>, NyxError> {
-        let osc = self.cosm.frame_chg(&ctx.orbit, self.drag_frame);
-        let velocity = osc.velocity();
-        Ok(-0.5 * self.rho * ctx.drag.cd * ctx.drag.area_m2 * velocity.norm() * velocity)
+        Ok(self.dual_eom(ctx)?.0)
     }
 
-    fn dual_eom(&self, _osc_ctx: &Spacecraft) -> Result<(Vector3<f64>, Matrix3<f64>), NyxError> {
-        Err(NyxError::PartialsUndefined)
+    fn dual_eom(
+        &self,
+        ctx: &Spacecraft,
+    ) -> Result<(Vector3<f64>, OMatrix<f64, Const<3>, Const<9>>), NyxError> {
+        let rel = RelativeVelocity::new(&self.cosm, &ctx.orbit, self.drag_frame, None)?;
+        Ok(rel.drag(self.rho, Vector3::zeros(), ctx.drag.cd, ctx.drag.area_m2))
     }
 }
 
-/// `Drag` implements all three drag models.
+/// `Drag` implements all of the atmospheric density models.
+///
+/// The velocity of the spacecraft is computed relative to the atmosphere, which co-rotates with the drag frame, and to the optional wind.
+/// If a macro-model is set, the drag area is that of its plates facing the atmosphere instead of the drag area of the spacecraft.
 #[derive(Clone)]
 pub struct Drag {
     /// Density computation method
     pub density: AtmDensity,
     /// Frame to compute the drag in
     pub drag_frame: Frame,
+    /// Optional wind model of the atmosphere
+    pub wind: Option<Arc<dyn WindModel>>,
+    /// Optional multi-plate geometry of the spacecraft
+    pub macro_model: Option<Arc<MacroModel>>,
     /// a Cosm reference is needed to convert to the state around the correct planet
     pub cosm: Arc<Cosm>,
 }
@@ -89,6 +221,8 @@ impl Drag {
                 ref_alt_m: 88_667.0,
             },
             drag_frame: cosm.frame("IAU Earth"),
+            wind: None,
+            macro_model: None,
             cosm,
         })
     }
@@ -100,51 +234,96 @@ impl Drag {
                 max_alt_m: 1_000_000.0,
             },
             drag_frame: cosm.frame("IAU Earth"),
+            wind: None,
+            macro_model: None,
             cosm,
         })
     }
-}
 
-impl fmt::Display for Drag {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(
-            f,
-            "\tDrag density {:?} in frame {}",
-            self.density, self.drag_frame
-        )
+    /// Drag model which uses the NRLMSISE-00 model for atmospheric density, with the indices of the provided space weather
+    pub fn nrlmsise00(
+        model: Arc<Nrlmsise00>,
+        space_weather: Arc<SpaceWeather>,
+        cosm: Arc<Cosm>,
+    ) -> Arc<Self> {
+        Arc::new(Self {
+            density: AtmDensity::Nrlmsise00 {
+                model,
+                space_weather,
+            },
+            drag_frame: cosm.frame("IAU Earth"),
+            wind: None,
+            macro_model: None,
+            cosm,
+        })
     }
-}
 
-impl ForceModel for Drag {
-    fn eom(&self, ctx: &Spacecraft) -> Result<Vector3<f64>, NyxError> {
-        let integration_frame = ctx.orbit.frame;
-        let osc = self.cosm.frame_chg(&ctx.orbit, self.drag_frame);
-        match self.density {
-            AtmDensity::Constant(rho) => {
-                let velocity = osc.velocity();
-                Ok(-0.5 * rho * ctx.drag.cd * ctx.drag.area_m2 * velocity.norm() * velocity)
+    /// Drag model which uses the JB2008 model for atmospheric density, with the indices of the provided space weather
+    pub fn jb2008(space_weather: Arc<SpaceWeather>, cosm: Arc<Cosm>) -> Arc<Self> {
+        Arc::new(Self {
+            density: AtmDensity::Jb2008 { space_weather },
+            drag_frame: cosm.frame("IAU Earth"),
+            wind: None,
+            macro_model: None,
+            cosm,
+        })
+    }
+
+    /// Returns a copy of this drag model with the provided wind model
+    pub fn with_wind(&self, wind: Arc<dyn WindModel>) -> Arc<Self> {
+        let mut me = self.clone();
+        me.wind = Some(wind);
+        Arc::new(me)
+    }
+
+    /// Returns a copy of this drag model which uses the provided macro-model instead of the drag area of the spacecraft
+    pub fn with_macro_model(&self, macro_model: Arc<MacroModel>) -> Arc<Self> {
+        let mut me = self.clone();
+        me.macro_model = Some(macro_model);
+        Arc::new(me)
+    }
+
+    /// Returns the drag area in m^2, which is that of the macro-model facing the atmosphere if it is set.
+    fn area_m2(&self, ctx: &Spacecraft, rel: &RelativeVelocity) -> Result<f64, NyxError> {
+        match &self.macro_model {
+            Some(macro_model) => {
+                let dcm = macro_model.dcm_body_to_inertial(ctx)?;
+                let sun = self.cosm.celestial_state(
+                    Bodies::Sun.ephem_path(),
+                    ctx.orbit.epoch,
+                    ctx.orbit.frame,
+                    LightTimeCalc::None,
+                );
+                let sun_unit = (sun.radius() - ctx.orbit.radius()).normalize();
+                Ok(match rel.velocity.try_normalize(0.0) {
+                    Some(velocity_unit) => {
+                        macro_model.drag_area_m2(&dcm, &velocity_unit, &sun_unit)
+                    }
+                    None => 0.0,
+                })
             }
+            None => Ok(ctx.drag.area_m2),
+        }
+    }
+
+    /// Returns the atmospheric density in kg/m^3 at the provided orbit, which must be in the drag frame
+    pub fn density_kg_m3(&self, osc: &Orbit) -> Result<f64, NyxError> {
+        match &self.density {
+            AtmDensity::Constant(rho) => Ok(*rho),
 
             AtmDensity::Exponential {
                 rho0,
                 r0,
                 ref_alt_m,
-            } => {
-                let rho = rho0
-                    * (-(osc.rmag_km() - (r0 + self.drag_frame.equatorial_radius())) / ref_alt_m)
-                        .exp();
-
-                let velocity_integr_frame = self.cosm.frame_chg(&osc, integration_frame).velocity();
-
-                let velocity = velocity_integr_frame - osc.velocity();
-                Ok(-0.5 * rho * ctx.drag.cd * ctx.drag.area_m2 * velocity.norm() * velocity)
-            }
+            } => Ok(rho0
+                * (-(osc.rmag_km() - (r0 + self.drag_frame.equatorial_radius())) / ref_alt_m)
+                    .exp()),
 
             AtmDensity::StdAtm { max_alt_m } => {
                 let altitude_km = osc.rmag_km() - self.drag_frame.equatorial_radius();
-                let rho = if altitude_km > max_alt_m / 1_000.0 {
+                if altitude_km > max_alt_m / 1_000.0 {
                     // Use a constant density
-                    10.0_f64.powf((-7e-5) * altitude_km - 14.464)
+                    Ok(10.0_f64.powf((-7e-5) * altitude_km - 14.464))
                 } else {
                     // Code from AVS/Schaub's Basilisk
                     // Calculating the density based on a scaled 6th order polynomial fit to the log of density
@@ -157,18 +336,117 @@ impl ForceModel for Drag {
                             - 12.575;
 
                     /* Calculating density by raising 10 to the log of density */
-                    10.0_f64.powf(logdensity)
-                };
+                    Ok(10.0_f64.powf(logdensity))
+                }
+            }
 
-                let velocity_integr_frame = self.cosm.frame_chg(&osc, integration_frame).velocity();
+            AtmDensity::Nrlmsise00 {
+                model,
+                space_weather,
+            } => {
+                let input = Nrlmsise00Input::from_space_weather(
+                    osc.epoch,
+                    osc.geodetic_latitude_deg(),
+                    osc.geodetic_longitude_deg(),
+                    osc.geodetic_height_km(),
+                    space_weather,
+                )?;
+                Ok(model.density_kg_m3(&input))
+            }
 
-                let velocity = velocity_integr_frame - osc.velocity();
-                Ok(-0.5 * rho * ctx.drag.cd * ctx.drag.area_m2 * velocity.norm() * velocity)
+            AtmDensity::Jb2008 { space_weather } => {
+                let inertial = self
+                    .cosm
+                    .try_frame_from_ephem_path(&self.drag_frame.ephem_path())?;
+                let sun = self.cosm.celestial_state(
+                    Bodies::Sun.ephem_path(),
+                    osc.epoch,
+                    inertial,
+                    LightTimeCalc::None,
+                );
+                let sc = self.cosm.frame_chg(osc, inertial);
+                let input = Jb2008Input::from_space_weather(
+                    osc.epoch,
+                    sun.right_ascension_deg(),
+                    sun.declination_deg(),
+                    sc.right_ascension_deg(),
+                    osc.geodetic_latitude_deg(),
+                    osc.geodetic_height_km(),
+                    space_weather,
+                )?;
+                Ok(jb2008(&input).rho_kg_m3)
             }
         }
     }
+}
+
+impl fmt::Display for Drag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "\tDrag density {:?} in frame {}",
+            self.density, self.drag_frame
+        )?;
+        if let Some(wind) = &self.wind {
+            write!(f, " with {wind}")?;
+        }
+        match &self.macro_model {
+            Some(macro_model) => write!(f, " and {macro_model}"),
+            None => Ok(()),
+        }
+    }
+}
+
+impl ForceModel for Drag {
+    fn eom(&self, ctx: &Spacecraft) -> Result<Vector3<f64>, NyxError> {
+        let rel = RelativeVelocity::new(
+            &self.cosm,
+            &ctx.orbit,
+            self.drag_frame,
+            self.wind.as_deref(),
+        )?;
+        let rho = self.density_kg_m3(&rel.osc)?;
+        let area_m2 = self.area_m2(ctx, &rel)?;
+        Ok(rel.drag(rho, Vector3::zeros(), ctx.drag.cd, area_m2).0)
+    }
+
+    /// The density gradient is computed by central differences along the radial direction, i.e. the horizontal gradients are neglected.
+    /// The drag area of the macro-model is assumed constant.
+    fn dual_eom(
+        &self,
+        ctx: &Spacecraft,
+    ) -> Result<(Vector3<f64>, OMatrix<f64, Const<3>, Const<9>>), NyxError> {
+        const RADIAL_STEP_KM: f64 = 0.1;
+        let rel = RelativeVelocity::new(
+            &self.cosm,
+            &ctx.orbit,
+            self.drag_frame,
+            self.wind.as_deref(),
+        )?;
+        let rho = self.density_kg_m3(&rel.osc)?;
+
+        let r_hat = rel.osc.radius() / rel.osc.rmag_km();
+        let mut up = rel.osc;
+        let mut down = rel.osc;
+        for (i, (up_km, down_km)) in [
+            (&mut up.x_km, &mut down.x_km),
+            (&mut up.y_km, &mut down.y_km),
+            (&mut up.z_km, &mut down.z_km),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            *up_km += RADIAL_STEP_KM * r_hat[i];
+            *down_km -= RADIAL_STEP_KM * r_hat[i];
+        }
+        let drho_dr =
+            (self.density_kg_m3(&up)? - self.density_kg_m3(&down)?) / (2.0 * RADIAL_STEP_KM);
 
-    fn dual_eom(&self, _osc_ctx: &Spacecraft) -> Result<(Vector3<f64>, Matrix3<f64>), NyxError> {
-        Err(NyxError::PartialsUndefined)
+        Ok(rel.drag(
+            rho,
+            drho_dr * (rel.dcm * r_hat),
+            ctx.drag.cd,
+            self.area_m2(ctx, &rel)?,
+        ))
     }
 }
@@ -0,0 +1,300 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::pointing::PointingLaw;
+use crate::cosmic::Spacecraft;
+use crate::errors::NyxError;
+use crate::linalg::{Matrix3, Vector3};
+use std::fmt;
+use std::sync::Arc;
+
+/// A flat plate of a spacecraft macro-model, defined in the body frame.
+///
+/// The optical coefficients are the fractions of the incoming photons which are specularly reflected, diffusely reflected and absorbed, and sum to one.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Plate {
+    /// Unit normal of the illuminated face of the plate in the body frame, ignored if the plate tracks the Sun
+    pub normal: Vector3<f64>,
+    /// Area of the plate in m^2
+    pub area_m2: f64,
+    /// Specular reflection coefficient
+    pub specular: f64,
+    /// Diffuse reflection coefficient
+    pub diffuse: f64,
+    /// Absorption coefficient
+    pub absorptive: f64,
+    /// If set, the plate rotates about this body axis to face the Sun as closely as possible, like a solar array
+    pub rotation_axis: Option<Vector3<f64>>,
+}
+
+impl Plate {
+    /// Initializes a fixed plate, returns an error if the normal is zero, the area is negative, or the optical coefficients are not valid fractions.
+    pub fn new(
+        normal: Vector3<f64>,
+        area_m2: f64,
+        specular: f64,
+        diffuse: f64,
+    ) -> Result<Self, NyxError> {
+        if normal.norm() < f64::EPSILON {
+            return Err(NyxError::MathDomain(
+                "plate normal must not be zero".to_string(),
+            ));
+        }
+        if area_m2 < 0.0 {
+            return Err(NyxError::MathDomain(format!(
+                "plate area must be positive: {area_m2} m^2"
+            )));
+        }
+        if specular < 0.0 || diffuse < 0.0 || specular + diffuse > 1.0 {
+            return Err(NyxError::MathDomain(format!(
+                "specular ({specular}) and diffuse ({diffuse}) coefficients must be positive and sum to at most one"
+            )));
+        }
+        Ok(Self {
+            normal: normal.normalize(),
+            area_m2,
+            specular,
+            diffuse,
+            absorptive: 1.0 - specular - diffuse,
+            rotation_axis: None,
+        })
+    }
+
+    /// Initializes a solar array which rotates about the provided body axis to face the Sun.
+    pub fn solar_array(
+        rotation_axis: Vector3<f64>,
+        area_m2: f64,
+        specular: f64,
+        diffuse: f64,
+    ) -> Result<Self, NyxError> {
+        let mut me = Self::new(rotation_axis, area_m2, specular, diffuse)?;
+        me.rotation_axis = Some(me.normal);
+        Ok(me)
+    }
+
+    /// Returns the unit normal of the plate in the body frame, given the unit direction of the Sun in the body frame.
+    pub fn normal_body(&self, sun_body: &Vector3<f64>) -> Vector3<f64> {
+        match self.rotation_axis {
+            Some(axis) => {
+                let in_plane = sun_body - sun_body.dot(&axis) * axis;
+                if in_plane.norm() < f64::EPSILON {
+                    // The Sun is along the rotation axis, so the array is edge on
+                    axis.cross(&Vector3::x())
+                        .try_normalize(0.0)
+                        .unwrap_or(Vector3::y())
+                } else {
+                    in_plane.normalize()
+                }
+            }
+            None => self.normal,
+        }
+    }
+
+    /// Returns the solar radiation pressure force on this plate in N in the body frame, given the unit direction of the Sun in the body frame
+    /// and the solar radiation pressure in N/m^2. Only the face along the normal is illuminated.
+    pub fn srp_force_n(&self, sun_body: &Vector3<f64>, pressure_n_m2: f64) -> Vector3<f64> {
+        let normal = self.normal_body(sun_body);
+        let cos_theta = normal.dot(sun_body);
+        if cos_theta <= 0.0 {
+            return Vector3::zeros();
+        }
+        -pressure_n_m2
+            * self.area_m2
+            * cos_theta
+            * ((1.0 - self.specular) * sun_body
+                + 2.0 * (self.specular * cos_theta + self.diffuse / 3.0) * normal)
+    }
+
+    /// Returns the area in m^2 of this plate projected onto the plane normal to the unit direction of the velocity relative to the atmosphere in the body frame.
+    /// Solar arrays are hit by the flow on both faces, other plates only on the face along the normal.
+    pub fn projected_area_m2(&self, velocity_body: &Vector3<f64>, sun_body: &Vector3<f64>) -> f64 {
+        let cos_theta = self.normal_body(sun_body).dot(velocity_body);
+        if self.rotation_axis.is_some() {
+            self.area_m2 * cos_theta.abs()
+        } else {
+            self.area_m2 * cos_theta.max(0.0)
+        }
+    }
+}
+
+/// A multi-plate macro-model of the spacecraft geometry used by the solar radiation pressure and drag models instead of the spacecraft areas.
+///
+/// The orientation of the plates is that of the attitude of the spacecraft if it is set, else that of the optional pointing law.
+#[derive(Clone)]
+pub struct MacroModel {
+    /// Plates of the macro-model
+    pub plates: Vec<Plate>,
+    /// Pointing law used if the spacecraft does not have an attitude
+    pub pointing: Option<Arc<dyn PointingLaw>>,
+}
+
+impl MacroModel {
+    pub fn new(plates: Vec<Plate>) -> Self {
+        Self {
+            plates,
+            pointing: None,
+        }
+    }
+
+    /// Builds a box-wing model: a bus whose opposite faces along each body axis have the provided areas, and a solar array rotating about the provided axis.
+    pub fn box_wing(
+        bus_areas_m2: Vector3<f64>,
+        bus_specular: f64,
+        bus_diffuse: f64,
+        array_area_m2: f64,
+        array_axis: Vector3<f64>,
+        array_specular: f64,
+        array_diffuse: f64,
+    ) -> Result<Self, NyxError> {
+        let mut plates = Vec::with_capacity(7);
+        for (i, axis) in [Vector3::x(), Vector3::y(), Vector3::z()]
+            .into_iter()
+            .enumerate()
+        {
+            for normal in [axis, -axis] {
+                plates.push(Plate::new(
+                    normal,
+                    bus_areas_m2[i],
+                    bus_specular,
+                    bus_diffuse,
+                )?);
+            }
+        }
+        plates.push(Plate::solar_array(
+            array_axis,
+            array_area_m2,
+            array_specular,
+            array_diffuse,
+        )?);
+        Ok(Self::new(plates))
+    }
+
+    /// Clone this macro-model and orient it with the provided pointing law when the spacecraft does not have an attitude
+    pub fn with_pointing(self, pointing: Arc<dyn PointingLaw>) -> Self {
+        let mut me = self;
+        me.pointing = Some(pointing);
+        me
+    }
+
+    /// Returns the DCM from the body frame to the frame of the orbit of the spacecraft.
+    pub fn dcm_body_to_inertial(&self, sc: &Spacecraft) -> Result<Matrix3<f64>, NyxError> {
+        match (sc.attitude, &self.pointing) {
+            (Some(attitude), _) => Ok(attitude.dcm_body_to_inertial()),
+            (None, Some(pointing)) => pointing.dcm_body_to_inertial(&sc.orbit),
+            (None, None) => Err(NyxError::AttitudeUnset),
+        }
+    }
+
+    /// Returns the solar radiation pressure force in N in the inertial frame, given the body to inertial DCM, the unit direction of the Sun
+    /// in the inertial frame and the solar radiation pressure in N/m^2.
+    pub fn srp_force_n(
+        &self,
+        dcm_body_to_inertial: &Matrix3<f64>,
+        sun_unit: &Vector3<f64>,
+        pressure_n_m2: f64,
+    ) -> Vector3<f64> {
+        let sun_body = dcm_body_to_inertial.transpose() * sun_unit;
+        dcm_body_to_inertial
+            * self
+                .plates
+                .iter()
+                .map(|plate| plate.srp_force_n(&sun_body, pressure_n_m2))
+                .sum::<Vector3<f64>>()
+    }
+
+    /// Returns the area in m^2 facing the atmosphere, given the body to inertial DCM, and the unit directions of the velocity relative to the atmosphere and of the Sun in the inertial frame.
+    pub fn drag_area_m2(
+        &self,
+        dcm_body_to_inertial: &Matrix3<f64>,
+        velocity_unit: &Vector3<f64>,
+        sun_unit: &Vector3<f64>,
+    ) -> f64 {
+        let velocity_body = dcm_body_to_inertial.transpose() * velocity_unit;
+        let sun_body = dcm_body_to_inertial.transpose() * sun_unit;
+        self.plates
+            .iter()
+            .map(|plate| plate.projected_area_m2(&velocity_body, &sun_body))
+            .sum()
+    }
+}
+
+impl fmt::Display for MacroModel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "macro-model of {} plates", self.plates.len())?;
+        match &self.pointing {
+            Some(pointing) => write!(f, " with {pointing}"),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod ut_macro_model {
+    use super::*;
+
+    #[test]
+    fn plate_srp() {
+        let sun = Vector3::x();
+        // Perfect absorber facing the Sun: the force is along the photons
+        let absorber = Plate::new(Vector3::x(), 2.0, 0.0, 0.0).unwrap();
+        assert!((absorber.srp_force_n(&sun, 1e-5) - Vector3::new(-2e-5, 0.0, 0.0)).norm() < 1e-20);
+        // Perfect mirror facing the Sun: twice the force
+        let mirror = Plate::new(Vector3::x(), 2.0, 1.0, 0.0).unwrap();
+        assert!((mirror.srp_force_n(&sun, 1e-5) - Vector3::new(-4e-5, 0.0, 0.0)).norm() < 1e-20);
+        // Mirror at 45 degrees: the force is along the normal
+        let tilted = Plate::new(Vector3::new(1.0, 1.0, 0.0), 2.0, 1.0, 0.0).unwrap();
+        let force = tilted.srp_force_n(&sun, 1e-5);
+        assert!(force.normalize().dot(&-tilted.normal) > 1.0 - 1e-12);
+        // The back face is not illuminated
+        let back = Plate::new(-Vector3::x(), 2.0, 0.3, 0.3).unwrap();
+        assert_eq!(back.srp_force_n(&sun, 1e-5), Vector3::zeros());
+
+        assert!(Plate::new(Vector3::x(), 1.0, 0.6, 0.6).is_err());
+        assert!(Plate::new(Vector3::zeros(), 1.0, 0.1, 0.1).is_err());
+    }
+
+    #[test]
+    fn box_wing() {
+        let model = MacroModel::box_wing(
+            Vector3::new(1.0, 2.0, 3.0),
+            0.2,
+            0.3,
+            10.0,
+            Vector3::y(),
+            0.1,
+            0.1,
+        )
+        .unwrap();
+        assert_eq!(model.plates.len(), 7);
+
+        // The array faces the Sun when it is in the plane normal to the rotation axis
+        let sun = Vector3::new(1.0, 0.0, 1.0).normalize();
+        assert!((model.plates[6].normal_body(&sun) - sun).norm() < 1e-12);
+
+        // Velocity along +X: the +X bus face and the edge on array
+        let dcm = Matrix3::identity();
+        let area = model.drag_area_m2(&dcm, &Vector3::x(), &Vector3::z());
+        assert!((area - 1.0).abs() < 1e-12);
+        // Velocity along +Z with the Sun along Z: the +Z bus face and the full array
+        let area = model.drag_area_m2(&dcm, &Vector3::z(), &Vector3::z());
+        assert!((area - 13.0).abs() < 1e-12);
+
+        // No attitude and no pointing law
+        assert!(model.dcm_body_to_inertial(&Spacecraft::default()).is_err());
+    }
+}
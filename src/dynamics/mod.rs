@@ -18,7 +18,7 @@
 
 use crate::cosmic::Orbit;
 use crate::linalg::allocator::Allocator;
-use crate::linalg::{DefaultAllocator, DimName, Matrix3, OMatrix, OVector, Vector3};
+use crate::linalg::{Const, DefaultAllocator, DimName, Matrix3, OMatrix, OVector, Vector3};
 use crate::State;
 use hyperdual::{OHyperdual, Owned};
 
@@ -38,9 +38,6 @@ pub use self::orbital::*;
 /// This module allows loading gravity models from [PDS](http://pds-geosciences.wustl.edu/), [EGM2008](http://earth-info.nga.mil/GandG/wgs84/gravitymod/egm2008/) and GMAT's own COF files.
 // pub mod gravity;
 
-/// The drag module handles drag in a very basic fashion. Do not use for high fidelity dynamics.
-// pub mod drag;
-
 /// The spacecraft module allows for simulation of spacecraft dynamics in general, including propulsion/maneuvers.
 pub mod spacecraft;
 pub use self::spacecraft::*;
@@ -48,6 +45,9 @@ pub use self::spacecraft::*;
 /// Defines a few examples of guidance laws.
 pub mod guidance;
 
+/// Defines attitude pointing laws and sensor fields of view.
+pub mod pointing;
+
 /// Defines some velocity change controllers.
 pub mod deltavctrl;
 
@@ -55,14 +55,29 @@ pub mod deltavctrl;
 pub mod solarpressure;
 pub use self::solarpressure::*;
 
+/// Defines the albedo and infrared radiation pressure models of the Earth and the Moon
+pub mod albedo;
+pub use self::albedo::*;
+
 /// Define drag models
 pub mod drag;
 pub use self::drag::*;
 
+/// Define the multi-plate macro-model of the spacecraft geometry used by the SRP and drag models.
+pub mod macro_model;
+pub use self::macro_model::*;
+
+/// Define the empirical atmosphere models used by the drag models.
+pub mod atmosphere;
+
 /// Define the spherical harmonic models.
 pub mod sph_harmonics;
 pub use self::sph_harmonics::*;
 
+/// Define the rigid body attitude dynamics and torque models.
+pub mod attitude;
+pub use self::attitude::*;
+
 /// The `Dynamics` trait handles and stores any equation of motion *and* the state is integrated.
 ///
 /// Its design is such that several of the provided dynamics can be combined fairly easily. However,
@@ -135,7 +150,11 @@ pub trait ForceModel: Send + Sync + fmt::Display {
 
     /// Force models must implement their partials, although those will only be called if the propagation requires the
     /// computation of the STM. The `osc_ctx` is the osculating context, i.e. it changes for each sub-step of the integrator.
-    fn dual_eom(&self, osc_ctx: &Spacecraft) -> Result<(Vector3<f64>, Matrix3<f64>), NyxError>;
+    /// The partials are those of the force with respect to the spacecraft state, i.e. its position, velocity, Cr, Cd and fuel mass, in this order.
+    fn dual_eom(
+        &self,
+        osc_ctx: &Spacecraft,
+    ) -> Result<(Vector3<f64>, OMatrix<f64, Const<3>, Const<9>>), NyxError>;
 }
 
 /// The `AccelModel` trait handles immutable dynamics which return an acceleration. Those can be added directly to Orbital Dynamics for example.
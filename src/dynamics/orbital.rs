@@ -17,7 +17,7 @@
 */
 
 use super::{AccelModel, Dynamics, NyxError};
-use crate::cosmic::{Bodies, Cosm, Frame, LightTimeCalc, Orbit};
+use crate::cosmic::{CelestialBody, Cosm, Frame, LightTimeCalc, Orbit};
 use crate::linalg::{Const, Matrix3, Matrix6, OVector, Vector3, Vector6};
 use crate::State;
 use hyperdual::linalg::norm;
@@ -36,8 +36,8 @@ pub struct OrbitalDynamics {
 }
 
 impl OrbitalDynamics {
-    /// Initialize point mass dynamics given the EXB IDs and a Cosm
-    pub fn point_masses(bodies: &[Bodies], cosm: Arc<Cosm>) -> Self {
+    /// Initialize point mass dynamics given the bodies (e.g. `Bodies`, or any body loaded in the Cosm) and a Cosm
+    pub fn point_masses<B: CelestialBody>(bodies: &[B], cosm: Arc<Cosm>) -> Self {
         // Create the point masses
         Self::new(vec![PointMasses::new(bodies, cosm)])
     }
@@ -92,7 +92,7 @@ impl Dynamics for OrbitalDynamics {
         let (new_state, new_stm) = if ctx.stm.is_some() {
             let (state, grad) = self.dual_eom(delta_t_s, &osc)?;
 
-            let stm_dt = ctx.stm()? * grad;
+            let stm_dt = grad * osc.stm()?;
             // Rebuild the STM as a vector.
             let stm_as_vec = OVector::<f64, Const<36>>::from_column_slice(stm_dt.as_slice());
             (state, stm_as_vec)
@@ -185,24 +185,41 @@ pub struct PointMasses {
 }
 
 impl PointMasses {
-    /// Initializes the multibody point mass dynamics with the provided list of bodies
-    pub fn new(bodies: &[Bodies], cosm: Arc<Cosm>) -> Arc<Self> {
+    /// Initializes the multibody point mass dynamics with the provided list of bodies.
+    /// Bodies may be any of the default `Bodies`, a `Body` of the Cosm, or the name of a body loaded in the Cosm (e.g. "Phobos").
+    /// Panics if a body is not loaded in the Cosm.
+    pub fn new<B: CelestialBody>(bodies: &[B], cosm: Arc<Cosm>) -> Arc<Self> {
         Arc::new(Self::with_correction(bodies, cosm, LightTimeCalc::None))
     }
 
-    /// Initializes the multibody point mass dynamics with the provided list of bodies, and accounting for some light time correction
-    pub fn with_correction(bodies: &[Bodies], cosm: Arc<Cosm>, correction: LightTimeCalc) -> Self {
+    /// Initializes the multibody point mass dynamics with the provided list of bodies, and accounting for some light time correction.
+    /// Panics if a body is not loaded in the Cosm.
+    pub fn with_correction<B: CelestialBody>(
+        bodies: &[B],
+        cosm: Arc<Cosm>,
+        correction: LightTimeCalc,
+    ) -> Self {
+        Self::try_with_correction(bodies, cosm, correction).unwrap()
+    }
+
+    /// Initializes the multibody point mass dynamics with the provided list of bodies, and accounting for some light time correction,
+    /// or returns an error if a body is not loaded in the Cosm.
+    pub fn try_with_correction<B: CelestialBody>(
+        bodies: &[B],
+        cosm: Arc<Cosm>,
+        correction: LightTimeCalc,
+    ) -> Result<Self, NyxError> {
         let mut refs = Vec::with_capacity(bodies.len());
         // Check that these celestial bodies exist and build their references
         for body in bodies {
-            refs.push(cosm.frame_from_ephem_path(body.ephem_path()));
+            refs.push(body.try_frame(&cosm)?);
         }
 
-        Self {
+        Ok(Self {
             bodies: refs,
             cosm,
             correction,
-        }
+        })
     }
 
     /// Allows using bodies by name, defined in the non-default XB
@@ -0,0 +1,498 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::cosmic::{Attitude, Bodies, Cosm, Frame, LightTimeCalc, Orbit};
+use crate::errors::NyxError;
+use crate::linalg::allocator::Allocator;
+use crate::linalg::{DefaultAllocator, Matrix3, Vector3};
+use crate::md::trajectory::Interpolatable;
+use crate::md::EventEvaluator;
+use crate::time::{Duration, Unit};
+use nalgebra::UnitQuaternion;
+use std::fmt;
+use std::sync::Arc;
+
+/// Time step used to estimate the angular velocity of a pointing law, in seconds
+const RATE_STEP_S: f64 = 0.5;
+
+/// The `PointingLaw` trait defines the orientation of the spacecraft body axes, e.g. to point an instrument, independently of any thrust.
+pub trait PointingLaw: fmt::Display + Send + Sync {
+    /// Returns the DCM whose columns are the body axes expressed in the frame of the provided orbit.
+    fn dcm_body_to_inertial(&self, orbit: &Orbit) -> Result<Matrix3<f64>, NyxError>;
+
+    /// Returns the attitude of this pointing law. The angular velocity is estimated by differentiating the pointing over one second
+    /// of two-body motion, which is exact enough for most pointing laws.
+    fn attitude(&self, orbit: &Orbit) -> Result<Attitude, NyxError> {
+        let before = self.dcm_body_to_inertial(&two_body_step(orbit, -RATE_STEP_S))?;
+        let after = self.dcm_body_to_inertial(&two_body_step(orbit, RATE_STEP_S))?;
+        let q_before = UnitQuaternion::from_matrix(&before);
+        let q_after = UnitQuaternion::from_matrix(&after);
+        let omega_rad_s = (q_before.inverse() * q_after).scaled_axis() / (2.0 * RATE_STEP_S);
+        Ok(Attitude::from_dcm(
+            &self.dcm_body_to_inertial(orbit)?,
+            omega_rad_s,
+        ))
+    }
+}
+
+/// Aligns the body `boresight` with the `primary` direction and the body `secondary_axis` as closely as possible with the `secondary` direction.
+///
+/// If the primary and secondary directions are colinear, the inertial axis which is the furthest from the primary direction is used as the secondary direction.
+pub fn align_axes(
+    boresight: &Vector3<f64>,
+    secondary_axis: &Vector3<f64>,
+    primary: &Vector3<f64>,
+    secondary: &Vector3<f64>,
+) -> Result<Matrix3<f64>, NyxError> {
+    let body_cross = boresight.cross(secondary_axis);
+    if body_cross.norm() < 1e-12 * boresight.norm() * secondary_axis.norm() {
+        return Err(NyxError::MathDomain(format!(
+            "boresight {boresight} and secondary axis {secondary_axis} are colinear"
+        )));
+    }
+    if primary.norm() < f64::EPSILON {
+        return Err(NyxError::MathDomain(
+            "cannot point towards a zero vector".to_string(),
+        ));
+    }
+
+    let p_hat = primary.normalize();
+    let mut inertial_cross = p_hat.cross(secondary);
+    if inertial_cross.norm() < 1e-9 * secondary.norm() {
+        let fallback = [Vector3::x(), Vector3::y(), Vector3::z()]
+            .into_iter()
+            .min_by(|a, b| a.dot(&p_hat).abs().total_cmp(&b.dot(&p_hat).abs()))
+            .unwrap();
+        inertial_cross = p_hat.cross(&fallback);
+    }
+
+    let b1 = boresight.normalize();
+    let b2 = body_cross.normalize();
+    let i2 = inertial_cross.normalize();
+    let body_triad = Matrix3::from_columns(&[b1, b2, b1.cross(&b2)]);
+    let inertial_triad = Matrix3::from_columns(&[p_hat, i2, p_hat.cross(&i2)]);
+    Ok(inertial_triad * body_triad.transpose())
+}
+
+/// Applies a first order two body step to the orbit, used to differentiate the pointing laws.
+fn two_body_step(orbit: &Orbit, step_s: f64) -> Orbit {
+    let mut next = *orbit;
+    let accel = match orbit.frame.try_gm() {
+        Ok(gm) => -gm / orbit.rmag_km().powi(3) * orbit.radius(),
+        Err(_) => Vector3::zeros(),
+    };
+    let radius = orbit.radius() + orbit.velocity() * step_s + 0.5 * accel * step_s.powi(2);
+    let velocity = orbit.velocity() + accel * step_s;
+    next.x_km = radius[0];
+    next.y_km = radius[1];
+    next.z_km = radius[2];
+    next.vx_km_s = velocity[0];
+    next.vy_km_s = velocity[1];
+    next.vz_km_s = velocity[2];
+    next.epoch += step_s * Unit::Second;
+    next
+}
+
+/// Points the boresight towards the center of the central body of the orbit, with the secondary axis towards the velocity.
+///
+/// With the default axes, +Z points to nadir and +X along the velocity.
+#[derive(Copy, Clone, Debug)]
+pub struct NadirPointing {
+    pub boresight: Vector3<f64>,
+    pub secondary_axis: Vector3<f64>,
+}
+
+impl NadirPointing {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    pub fn with_axes(boresight: Vector3<f64>, secondary_axis: Vector3<f64>) -> Arc<Self> {
+        Arc::new(Self {
+            boresight,
+            secondary_axis,
+        })
+    }
+}
+
+impl Default for NadirPointing {
+    fn default() -> Self {
+        Self {
+            boresight: Vector3::z(),
+            secondary_axis: Vector3::x(),
+        }
+    }
+}
+
+impl fmt::Display for NadirPointing {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Nadir pointing")
+    }
+}
+
+impl PointingLaw for NadirPointing {
+    fn dcm_body_to_inertial(&self, orbit: &Orbit) -> Result<Matrix3<f64>, NyxError> {
+        align_axes(
+            &self.boresight,
+            &self.secondary_axis,
+            &-orbit.radius(),
+            &orbit.velocity(),
+        )
+    }
+}
+
+/// Points the boresight along the velocity, with the secondary axis towards the nadir.
+///
+/// With the default axes, +X points along the velocity and +Z to nadir.
+#[derive(Copy, Clone, Debug)]
+pub struct VelocityPointing {
+    pub boresight: Vector3<f64>,
+    pub secondary_axis: Vector3<f64>,
+}
+
+impl VelocityPointing {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    pub fn with_axes(boresight: Vector3<f64>, secondary_axis: Vector3<f64>) -> Arc<Self> {
+        Arc::new(Self {
+            boresight,
+            secondary_axis,
+        })
+    }
+}
+
+impl Default for VelocityPointing {
+    fn default() -> Self {
+        Self {
+            boresight: Vector3::x(),
+            secondary_axis: Vector3::z(),
+        }
+    }
+}
+
+impl fmt::Display for VelocityPointing {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Velocity pointing")
+    }
+}
+
+impl PointingLaw for VelocityPointing {
+    fn dcm_body_to_inertial(&self, orbit: &Orbit) -> Result<Matrix3<f64>, NyxError> {
+        align_axes(
+            &self.boresight,
+            &self.secondary_axis,
+            &orbit.velocity(),
+            &-orbit.radius(),
+        )
+    }
+}
+
+/// Holds a fixed orientation with respect to the frame of the orbit.
+#[derive(Copy, Clone, Debug)]
+pub struct InertialPointing {
+    pub dcm_body_to_inertial: Matrix3<f64>,
+}
+
+impl InertialPointing {
+    pub fn new(dcm_body_to_inertial: Matrix3<f64>) -> Arc<Self> {
+        Arc::new(Self {
+            dcm_body_to_inertial,
+        })
+    }
+
+    /// Holds the orientation of the provided attitude
+    pub fn from_attitude(attitude: &Attitude) -> Arc<Self> {
+        Self::new(attitude.dcm_body_to_inertial())
+    }
+}
+
+impl fmt::Display for InertialPointing {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Inertial hold")
+    }
+}
+
+impl PointingLaw for InertialPointing {
+    fn dcm_body_to_inertial(&self, _orbit: &Orbit) -> Result<Matrix3<f64>, NyxError> {
+        Ok(self.dcm_body_to_inertial)
+    }
+}
+
+/// Points the boresight towards a celestial body, with the secondary axis towards the orbit normal.
+///
+/// With the default axes, +Z points towards the target and +X towards the orbit normal.
+#[derive(Clone)]
+pub struct BodyTracking {
+    pub target: Frame,
+    pub boresight: Vector3<f64>,
+    pub secondary_axis: Vector3<f64>,
+    pub correction: LightTimeCalc,
+    pub cosm: Arc<Cosm>,
+}
+
+impl BodyTracking {
+    /// Tracks the target without any light time correction
+    pub fn new(target: Frame, cosm: Arc<Cosm>) -> Arc<Self> {
+        Arc::new(Self {
+            target,
+            boresight: Vector3::z(),
+            secondary_axis: Vector3::x(),
+            correction: LightTimeCalc::None,
+            cosm,
+        })
+    }
+
+    pub fn with_axes(
+        target: Frame,
+        boresight: Vector3<f64>,
+        secondary_axis: Vector3<f64>,
+        correction: LightTimeCalc,
+        cosm: Arc<Cosm>,
+    ) -> Arc<Self> {
+        Arc::new(Self {
+            target,
+            boresight,
+            secondary_axis,
+            correction,
+            cosm,
+        })
+    }
+
+    /// Returns the direction of the target from the spacecraft, in the frame of the orbit.
+    pub fn target_direction(&self, orbit: &Orbit) -> Result<Vector3<f64>, NyxError> {
+        let target = self.cosm.try_celestial_state_from(
+            &self.target.try_ephem_path()?,
+            orbit,
+            self.correction,
+        )?;
+        Ok(target.radius())
+    }
+}
+
+impl fmt::Display for BodyTracking {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Tracking {} ({:?})", self.target, self.correction)
+    }
+}
+
+impl PointingLaw for BodyTracking {
+    fn dcm_body_to_inertial(&self, orbit: &Orbit) -> Result<Matrix3<f64>, NyxError> {
+        align_axes(
+            &self.boresight,
+            &self.secondary_axis,
+            &self.target_direction(orbit)?,
+            &orbit.hvec(),
+        )
+    }
+}
+
+/// Points the boresight towards the Sun, with the secondary axis towards the orbit normal.
+#[derive(Clone)]
+pub struct SunPointing {
+    tracking: BodyTracking,
+}
+
+impl SunPointing {
+    pub fn new(cosm: Arc<Cosm>) -> Arc<Self> {
+        Self::with_axes(Vector3::z(), Vector3::x(), cosm)
+    }
+
+    pub fn with_axes(
+        boresight: Vector3<f64>,
+        secondary_axis: Vector3<f64>,
+        cosm: Arc<Cosm>,
+    ) -> Arc<Self> {
+        Arc::new(Self {
+            tracking: BodyTracking {
+                target: cosm.frame_from_ephem_path(Bodies::Sun.ephem_path()),
+                boresight,
+                secondary_axis,
+                correction: LightTimeCalc::None,
+                cosm,
+            },
+        })
+    }
+}
+
+impl fmt::Display for SunPointing {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Sun pointing")
+    }
+}
+
+impl PointingLaw for SunPointing {
+    fn dcm_body_to_inertial(&self, orbit: &Orbit) -> Result<Matrix3<f64>, NyxError> {
+        self.tracking.dcm_body_to_inertial(orbit)
+    }
+}
+
+/// Field of view of a sensor mounted on the spacecraft body.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum FieldOfView {
+    /// Circular field of view around the boresight
+    Conical {
+        boresight: Vector3<f64>,
+        half_angle_deg: f64,
+    },
+    /// Rectangular field of view, whose height is along the `up` axis
+    Rectangular {
+        boresight: Vector3<f64>,
+        up: Vector3<f64>,
+        half_width_deg: f64,
+        half_height_deg: f64,
+    },
+}
+
+impl FieldOfView {
+    /// Returns by how many degrees the direction (in the body frame) is inside of the field of view: this is negative outside of it.
+    pub fn margin_deg(&self, direction_body: &Vector3<f64>) -> f64 {
+        let dir = direction_body.normalize();
+        match self {
+            Self::Conical {
+                boresight,
+                half_angle_deg,
+            } => {
+                let angle_deg = boresight
+                    .normalize()
+                    .dot(&dir)
+                    .clamp(-1.0, 1.0)
+                    .acos()
+                    .to_degrees();
+                half_angle_deg - angle_deg
+            }
+            Self::Rectangular {
+                boresight,
+                up,
+                half_width_deg,
+                half_height_deg,
+            } => {
+                let bore = boresight.normalize();
+                let up = (up - up.dot(&bore) * bore).normalize();
+                let right = up.cross(&bore);
+                let along = dir.dot(&bore);
+                let horizontal_deg = dir.dot(&right).atan2(along).to_degrees();
+                let vertical_deg = dir.dot(&up).atan2(along).to_degrees();
+                (half_width_deg - horizontal_deg.abs()).min(half_height_deg - vertical_deg.abs())
+            }
+        }
+    }
+
+    /// Returns whether the direction (in the body frame) is in the field of view
+    pub fn contains(&self, direction_body: &Vector3<f64>) -> bool {
+        self.margin_deg(direction_body) >= 0.0
+    }
+}
+
+impl fmt::Display for FieldOfView {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Conical { half_angle_deg, .. } => {
+                write!(f, "conical FOV of {half_angle_deg} deg")
+            }
+            Self::Rectangular {
+                half_width_deg,
+                half_height_deg,
+                ..
+            } => write!(
+                f,
+                "rectangular FOV of {half_width_deg} x {half_height_deg} deg"
+            ),
+        }
+    }
+}
+
+/// An event which is positive when the target celestial body is in the field of view of a sensor, given a pointing law.
+#[derive(Clone)]
+pub struct FovEvent {
+    /// Name of the sensor, e.g. "Star tracker"
+    pub name: String,
+    pub fov: FieldOfView,
+    pub pointing: Arc<dyn PointingLaw>,
+    pub target: Frame,
+    pub cosm: Arc<Cosm>,
+}
+
+impl FovEvent {
+    pub fn new(
+        name: String,
+        fov: FieldOfView,
+        pointing: Arc<dyn PointingLaw>,
+        target: Frame,
+        cosm: Arc<Cosm>,
+    ) -> Self {
+        Self {
+            name,
+            fov,
+            pointing,
+            target,
+            cosm,
+        }
+    }
+
+    /// Returns by how many degrees the target is in the field of view, negative outside of it
+    pub fn margin_deg(&self, orbit: &Orbit) -> Result<f64, NyxError> {
+        let target = self.cosm.try_celestial_state_from(
+            &self.target.try_ephem_path()?,
+            orbit,
+            LightTimeCalc::None,
+        )?;
+        let dcm = self.pointing.dcm_body_to_inertial(orbit)?;
+        Ok(self.fov.margin_deg(&(dcm.transpose() * target.radius())))
+    }
+}
+
+impl fmt::Display for FovEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} in {} {} ({})",
+            self.target, self.name, self.fov, self.pointing
+        )
+    }
+}
+
+impl<S: Interpolatable> EventEvaluator<S> for FovEvent
+where
+    DefaultAllocator:
+        Allocator<f64, S::VecLength> + Allocator<f64, S::Size> + Allocator<f64, S::Size, S::Size>,
+{
+    fn eval(&self, state: &S) -> f64 {
+        self.margin_deg(state.orbit()).unwrap()
+    }
+
+    /// Stop searching when the time has converged to less than a microsecond
+    fn epoch_precision(&self) -> Duration {
+        1 * Unit::Microsecond
+    }
+
+    /// Finds the edge of the field of view within a micro degree
+    fn value_precision(&self) -> f64 {
+        1e-6
+    }
+
+    fn eval_string(&self, state: &S) -> String {
+        format!(
+            "{}: {:.3} deg from the edge of the {}",
+            self.target,
+            self.margin_deg(state.orbit()).unwrap(),
+            self.name
+        )
+    }
+}
@@ -16,32 +16,36 @@
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
-use super::ForceModel;
+use super::{ForceModel, MacroModel};
 use crate::cosmic::eclipse::EclipseLocator;
 use crate::cosmic::{Cosm, Frame, Spacecraft, AU, SPEED_OF_LIGHT};
 use crate::errors::NyxError;
-use crate::linalg::{Const, Matrix3, Vector3};
+use crate::linalg::{Const, OMatrix, Vector3, Vector6};
 use hyperdual::{hyperspace_from_vector, linalg::norm, Float, OHyperdual};
 use std::fmt;
 use std::sync::Arc;
 
 /// Computation of solar radiation pressure is based on STK: http://help.agi.com/stk/index.htm#gator/eq-solar.htm .
+///
+/// If a macro-model is set, the force is the sum of that on each of its plates and the SRP area and Cr of the spacecraft are not used.
 #[derive(Clone)]
 pub struct SolarPressure {
     /// solar flux at 1 AU, in W/m^2
     pub phi: f64,
     pub e_loc: EclipseLocator,
+    /// Optional multi-plate geometry of the spacecraft
+    pub macro_model: Option<Arc<MacroModel>>,
 }
 
 impl SolarPressure {
     /// Will set the solar flux at 1 AU to: Phi = 1367.0
     pub fn default_raw(shadow_bodies: Vec<Frame>, cosm: Arc<Cosm>) -> Self {
-        let e_loc = EclipseLocator {
-            light_source: cosm.frame("Sun J2000"),
-            shadow_bodies,
-            cosm,
-        };
-        Self { phi: 1367.0, e_loc }
+        let e_loc = EclipseLocator::new(cosm.frame("Sun J2000"), shadow_bodies, cosm);
+        Self {
+            phi: 1367.0,
+            e_loc,
+            macro_model: None,
+        }
     }
 
     /// Accounts for the shadowing of only one body and will set the solar flux at 1 AU to: Phi = 1367.0
@@ -55,6 +59,63 @@ impl SolarPressure {
         me.phi = flux_w_m2;
         Arc::new(me)
     }
+
+    /// Returns a copy of this SRP model which uses the provided macro-model instead of the SRP area and Cr of the spacecraft
+    pub fn with_macro_model(&self, macro_model: Arc<MacroModel>) -> Arc<Self> {
+        let mut me = self.clone();
+        me.macro_model = Some(macro_model);
+        Arc::new(me)
+    }
+
+    /// Returns the SRP force in kN of the macro-model, given the position of the spacecraft with respect to the Sun and the shadowing factor.
+    fn macro_model_force(
+        &self,
+        macro_model: &MacroModel,
+        ctx: &Spacecraft,
+        r_sun: &Vector3<f64>,
+        k: f64,
+    ) -> Result<Vector3<f64>, NyxError> {
+        let dcm = macro_model.dcm_body_to_inertial(ctx)?;
+        // in N/(m^2)
+        let flux_pressure = (k * self.phi / SPEED_OF_LIGHT) * (AU / r_sun.norm()).powi(2);
+        // Note the 1e-3 is to convert the SRP from N to kN
+        Ok(1e-3 * macro_model.srp_force_n(&dcm, &(-r_sun / r_sun.norm()), flux_pressure))
+    }
+
+    /// Returns the SRP force of the macro-model and its partials, computed by central differences with a constant shadowing factor.
+    /// The partials with respect to the velocity are only non-zero if the attitude is that of a pointing law.
+    fn macro_model_dual_eom(
+        &self,
+        macro_model: &MacroModel,
+        ctx: &Spacecraft,
+        r_sun: &Vector3<f64>,
+    ) -> Result<(Vector3<f64>, OMatrix<f64, Const<3>, Const<9>>), NyxError> {
+        const POSITION_STEP_KM: f64 = 1e-3;
+        const VELOCITY_STEP_KM_S: f64 = 1e-6;
+        let k: f64 = self.e_loc.compute(&ctx.orbit).into();
+        let force = self.macro_model_force(macro_model, ctx, r_sun, k)?;
+
+        let mut grad = OMatrix::<f64, Const<3>, Const<9>>::zeros();
+        for j in 0..6 {
+            let step = if j < 3 {
+                POSITION_STEP_KM
+            } else {
+                VELOCITY_STEP_KM_S
+            };
+            let mut delta = Vector6::zeros();
+            delta[j] = step;
+            let mut plus = *ctx;
+            plus.orbit = ctx.orbit + delta;
+            let mut minus = *ctx;
+            minus.orbit = ctx.orbit + -delta;
+            let delta_r = delta.fixed_rows::<3>(0).into_owned();
+            let force_plus = self.macro_model_force(macro_model, &plus, &(r_sun + delta_r), k)?;
+            let force_minus = self.macro_model_force(macro_model, &minus, &(r_sun - delta_r), k)?;
+            grad.set_column(j, &((force_plus - force_minus) / (2.0 * step)));
+        }
+
+        Ok((force, grad))
+    }
 }
 
 impl ForceModel for SolarPressure {
@@ -67,11 +128,15 @@ impl ForceModel for SolarPressure {
             .frame_chg(osc, self.e_loc.light_source)
             .radius();
 
-        let r_sun_unit = r_sun / r_sun.norm();
-
         // Compute the shaddowing factor.
         let k: f64 = self.e_loc.compute(osc).into();
 
+        if let Some(macro_model) = &self.macro_model {
+            return self.macro_model_force(macro_model, ctx, &r_sun, k);
+        }
+
+        let r_sun_unit = r_sun / r_sun.norm();
+
         let r_sun_au = r_sun.norm() / AU;
         // in N/(m^2)
         let flux_pressure = (k * self.phi / SPEED_OF_LIGHT) * (1.0 / r_sun_au).powi(2);
@@ -80,7 +145,10 @@ impl ForceModel for SolarPressure {
         Ok(1e-3 * ctx.srp.cr * ctx.srp.area_m2 * flux_pressure * r_sun_unit)
     }
 
-    fn dual_eom(&self, ctx: &Spacecraft) -> Result<(Vector3<f64>, Matrix3<f64>), NyxError> {
+    fn dual_eom(
+        &self,
+        ctx: &Spacecraft,
+    ) -> Result<(Vector3<f64>, OMatrix<f64, Const<3>, Const<9>>), NyxError> {
         let osc = &ctx.orbit;
 
         // Compute the position of the Sun as seen from the spacecraft
@@ -90,6 +158,10 @@ impl ForceModel for SolarPressure {
             .frame_chg(osc, self.e_loc.light_source)
             .radius();
 
+        if let Some(macro_model) = &self.macro_model {
+            return self.macro_model_dual_eom(macro_model, ctx, &r_sun);
+        }
+
         let r_sun_d: Vector3<OHyperdual<f64, Const<9>>> = hyperspace_from_vector(&r_sun);
         let r_sun_unit = r_sun_d / norm(&r_sun_d);
 
@@ -113,13 +185,15 @@ impl ForceModel for SolarPressure {
 
         // Extract result into Vector6 and Matrix6
         let mut dx = Vector3::zeros();
-        let mut grad = Matrix3::zeros();
+        let mut grad = OMatrix::<f64, Const<3>, Const<9>>::zeros();
         for i in 0..3 {
             dx[i] += dual_force[i].real();
-            // NOTE: Although the hyperdual state is of size 7, we're only setting the values up to 3 (Matrix3)
+            // NOTE: The SRP does not depend on the velocity, so only the position partials are set
             for j in 0..3 {
                 grad[(i, j)] += dual_force[i][j + 1];
             }
+            // The SRP is linear in Cr
+            grad[(i, 6)] = 1e-3 * ctx.srp.area_m2 * flux_pressure.real() * r_sun_unit[i].real();
         }
 
         Ok((dx, grad))
@@ -132,6 +206,10 @@ impl fmt::Display for SolarPressure {
             f,
             "SRP with φ = {} W/m^2 and eclipse {}",
             self.phi, self.e_loc
-        )
+        )?;
+        match &self.macro_model {
+            Some(macro_model) => write!(f, " and {macro_model}"),
+            None => Ok(()),
+        }
     }
 }
@@ -16,9 +16,11 @@
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
+use super::attitude::AttitudeDynamics;
 use super::guidance::GuidanceLaw;
 use super::orbital::OrbitalDynamics;
 use super::{AccelModel, Dynamics, ForceModel};
+use crate::cosmic::ATTITUDE_OFFSET;
 pub use crate::cosmic::{GuidanceMode, Spacecraft, STD_GRAVITY};
 use crate::errors::NyxError;
 use crate::io::dynamics::DynamicsSerde;
@@ -48,6 +50,7 @@ const NORM_ERR: f64 = 1e-4;
 /// A generic spacecraft dynamics with associated force models, guidance law, and flag specifying whether to decrement the fuel mass or not.
 /// Note: when developing new guidance laws, it is recommended to _not_ enable fuel decrement until the guidance law seems to work without proper physics.
 /// Note: if the spacecraft runs out of fuel, the propagation segment will return an error.
+/// Note: if the spacecraft has an attitude, it is propagated with the orbit, with the attitude dynamics if set, and at a constant angular velocity otherwise.
 #[derive(Clone)]
 #[cfg_attr(feature = "python", pyclass)]
 pub struct SpacecraftDynamics {
@@ -55,6 +58,7 @@ pub struct SpacecraftDynamics {
     pub force_models: Vec<Arc<dyn ForceModel>>,
     pub guid_law: Option<Arc<dyn GuidanceLaw>>,
     pub decrement_mass: bool,
+    pub attitude_dyn: Option<AttitudeDynamics>,
 }
 
 impl SpacecraftDynamics {
@@ -66,6 +70,7 @@ impl SpacecraftDynamics {
             guid_law: Some(guid_law),
             force_models: Vec::new(),
             decrement_mass: true,
+            attitude_dyn: None,
         }
     }
 
@@ -80,6 +85,7 @@ impl SpacecraftDynamics {
             guid_law: Some(guid_law),
             force_models: Vec::new(),
             decrement_mass: false,
+            attitude_dyn: None,
         }
     }
 
@@ -90,6 +96,7 @@ impl SpacecraftDynamics {
             guid_law: None,
             force_models: Vec::new(),
             decrement_mass: true,
+            attitude_dyn: None,
         }
     }
 
@@ -100,6 +107,7 @@ impl SpacecraftDynamics {
             guid_law: None,
             force_models: vec![force_model],
             decrement_mass: true,
+            attitude_dyn: None,
         }
     }
 
@@ -125,6 +133,13 @@ impl SpacecraftDynamics {
         me
     }
 
+    /// Clone these dynamics and propagate the attitude of the spacecraft with the provided attitude dynamics
+    pub fn with_attitude_dynamics(self, attitude_dyn: AttitudeDynamics) -> Self {
+        let mut me = self;
+        me.attitude_dyn = Some(attitude_dyn);
+        me
+    }
+
     /// A shortcut to spacecraft.guid_law if a guidance law is defined for these dynamics
     pub fn guidance_achieved(&self, state: &Spacecraft) -> Result<bool, NyxError> {
         match &self.guid_law {
@@ -140,6 +155,7 @@ impl SpacecraftDynamics {
             guid_law: Some(guid_law),
             force_models: self.force_models.clone(),
             decrement_mass: self.decrement_mass,
+            attitude_dyn: self.attitude_dyn.clone(),
         }
     }
 
@@ -150,6 +166,7 @@ impl SpacecraftDynamics {
             guid_law: Some(guid_law),
             force_models: self.force_models.clone(),
             decrement_mass: false,
+            attitude_dyn: self.attitude_dyn.clone(),
         }
     }
 
@@ -160,6 +177,7 @@ impl SpacecraftDynamics {
             guid_law: None,
             force_models: self.force_models.clone(),
             decrement_mass: self.decrement_mass,
+            attitude_dyn: self.attitude_dyn.clone(),
         }
     }
 }
@@ -227,7 +245,11 @@ impl fmt::Display for SpacecraftDynamics {
             self.guid_law.is_some(),
             force_models,
             self.orbital_dyn
-        )
+        )?;
+        if let Some(attitude_dyn) = &self.attitude_dyn {
+            write!(f, " {attitude_dyn}")?;
+        }
+        Ok(())
     }
 }
 
@@ -254,19 +276,19 @@ impl Dynamics for SpacecraftDynamics {
     fn eom(
         &self,
         delta_t: f64,
-        state: &OVector<f64, Const<90>>,
+        state: &OVector<f64, Const<97>>,
         ctx: &Self::StateType,
-    ) -> Result<OVector<f64, Const<90>>, NyxError> {
+    ) -> Result<OVector<f64, Const<97>>, NyxError> {
         // Rebuild the osculating state for the EOM context.
         let osc_sc = ctx.set_with_delta_seconds(delta_t, state);
-        let mut d_x = OVector::<f64, Const<90>>::zeros();
+        let mut d_x = OVector::<f64, Const<97>>::zeros();
 
         if ctx.orbit.stm.is_some() {
             // Call the gradient (also called the dual EOM function of the force models)
             let (state, grad) = self.dual_eom(delta_t, &osc_sc)?;
 
             // Apply the gradient to the STM
-            let stm_dt = ctx.stm()? * grad;
+            let stm_dt = grad * osc_sc.stm()?;
 
             // Rebuild the state vectors
             for (i, val) in state.iter().enumerate() {
@@ -343,6 +365,17 @@ impl Dynamics for SpacecraftDynamics {
             }
             d_x[8] += fuel_rate;
         }
+
+        // And finally the attitude, which is not part of the STM.
+        if let Some(attitude) = osc_sc.attitude {
+            let (q_dot, omega_dot) = match &self.attitude_dyn {
+                Some(attitude_dyn) => attitude_dyn.attitude_rates(&osc_sc)?,
+                None => (attitude.quaternion_rate(), Vector3::zeros()),
+            };
+            for (i, val) in q_dot.iter().chain(omega_dot.iter()).enumerate() {
+                d_x[i + ATTITUDE_OFFSET] = *val;
+            }
+        }
         Ok(d_x)
     }
 
@@ -380,10 +413,12 @@ impl Dynamics for SpacecraftDynamics {
             for i in 0..3 {
                 // Add the velocity changes
                 d_x[i + 3] += model_frc[i] / total_mass;
-                // Add the velocity partials
-                for j in 1..4 {
-                    grad[(i + 3, j - 1)] += model_grad[(i, j - 1)] / total_mass;
+                // Add the position, velocity, Cr, Cd and fuel mass partials
+                for j in 0..9 {
+                    grad[(i + 3, j)] += model_grad[(i, j)] / total_mass;
                 }
+                // And the partial of the acceleration with respect to the fuel mass, since a = F / m
+                grad[(i + 3, 8)] -= model_frc[i] / total_mass.powi(2);
             }
         }
 
@@ -17,10 +17,12 @@
 */
 
 use super::thiserror::Error;
+use crate::cosmic::FrameError;
 use crate::io::ConfigError;
 use crate::md::trajectory::TrajError;
 use crate::md::StateParameter;
 pub use crate::md::TargetingError;
+use crate::propagators::Sgp4Error;
 pub use crate::time::Errors as TimeErrors;
 use crate::Spacecraft;
 use std::convert::From;
@@ -48,6 +50,9 @@ pub enum NyxError {
     /// The operation was expecting the state to have an STM, but it isn't present.
     #[error("The operation was expecting the state to have an STM, but it isn't present.")]
     StateTransitionMatrixUnset,
+    /// The operation was expecting the spacecraft to have an attitude, but it isn't set.
+    #[error("The operation was expecting the spacecraft to have an attitude, but it isn't set.")]
+    AttitudeUnset,
     /// The sensitivity matrix must be updated prior to a filter measurement update
     #[error("The sensitivity matrix must be updated prior to a filter measurement update")]
     SensitivityNotUpdated,
@@ -152,6 +157,12 @@ pub enum NyxError {
     /// Configuration file error
     #[error("Config error: {0}")]
     ConfigError(ConfigError),
+    /// Frame error
+    #[error("Frame error: {0}")]
+    FrameError(FrameError),
+    /// SGP4 propagation error
+    #[error("SGP4 error: {0}")]
+    Sgp4(Sgp4Error),
 }
 
 impl From<TimeErrors> for NyxError {
@@ -171,3 +182,15 @@ impl From<ConfigError> for NyxError {
         NyxError::ConfigError(e)
     }
 }
+
+impl From<FrameError> for NyxError {
+    fn from(e: FrameError) -> Self {
+        NyxError::FrameError(e)
+    }
+}
+
+impl From<Sgp4Error> for NyxError {
+    fn from(e: Sgp4Error) -> Self {
+        NyxError::Sgp4(e)
+    }
+}
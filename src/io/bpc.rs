@@ -0,0 +1,219 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::daf::{ChebyshevRecords, Daf};
+use super::spk::SPK_J2000;
+use crate::linalg::{Matrix3, Vector3};
+use crate::time::Epoch;
+use crate::utils::{r1, r3};
+use crate::NyxError;
+
+/// NAIF ID of the ecliptic of J2000 frame
+pub const ECLIPJ2000: i32 = 17;
+/// Obliquity of the ecliptic at J2000 used by SPICE to define ECLIPJ2000, in arcseconds
+const J2000_OBLIQUITY_ARCSEC: f64 = 84_381.448;
+
+/// A single binary PCK segment, i.e. the Euler angles of a body fixed frame with respect to an inertial frame over a time span.
+#[derive(Clone, Debug)]
+pub struct BpcSegment {
+    /// NAIF frame class ID of the body fixed frame (e.g. 3000 for ITRF93)
+    pub frame_id: i32,
+    /// NAIF ID of the inertial reference frame of the Euler angles (e.g. 1 for J2000, 17 for ECLIPJ2000)
+    pub inertial_frame: i32,
+    /// Start of the validity of this segment in ephemeris time seconds past J2000
+    pub start_et_s: f64,
+    /// End of the validity of this segment in ephemeris time seconds past J2000
+    pub end_et_s: f64,
+    records: ChebyshevRecords,
+}
+
+impl BpcSegment {
+    /// Returns whether this segment covers the provided ephemeris time
+    pub fn covers(&self, et_s: f64) -> bool {
+        self.start_et_s - 1e-6 <= et_s && et_s <= self.end_et_s + 1e-6
+    }
+
+    /// Returns the three Euler angles (phi, delta, w) in radians, such that the rotation from the inertial frame to the body fixed frame is R3(w) * R1(delta) * R3(phi).
+    pub fn euler_angles(&self, et_s: f64) -> Vector3<f64> {
+        let angles = self.records.evaluate(et_s, 3);
+        Vector3::new(angles[0].0, angles[1].0, angles[2].0)
+    }
+
+    /// Returns the DCM from the J2000 frame to the body fixed frame at the provided ephemeris time
+    pub fn dcm_from_j2000(&self, et_s: f64) -> Matrix3<f64> {
+        let angles = self.euler_angles(et_s);
+        let dcm = r3(angles[2]) * r1(angles[1]) * r3(angles[0]);
+        if self.inertial_frame == ECLIPJ2000 {
+            dcm * r1((J2000_OBLIQUITY_ARCSEC / 3600.0).to_radians())
+        } else {
+            dcm
+        }
+    }
+}
+
+/// A SPICE binary PCK file, e.g. the high precision Earth orientation `earth_latest_high_prec.bpc`.
+///
+/// Only type 2 segments (Chebyshev polynomials of the Euler angles) are supported, relative to either J2000 or ECLIPJ2000.
+/// Refer to https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/req/pck.html for the specification.
+#[derive(Clone, Debug, Default)]
+pub struct Bpc {
+    /// All of the supported segments, in the order in which they were loaded
+    pub segments: Vec<BpcSegment>,
+}
+
+impl Bpc {
+    /// Loads the binary PCK from the provided file path
+    pub fn from_file(path: &str) -> Result<Self, NyxError> {
+        Self::from_daf(&Daf::from_file(path)?)
+    }
+
+    /// Parses the provided buffer as a binary PCK
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Self, NyxError> {
+        Self::from_daf(&Daf::from_bytes(bytes)?)
+    }
+
+    /// Reads all of the supported segments from the provided DAF
+    pub fn from_daf(daf: &Daf) -> Result<Self, NyxError> {
+        if daf.nd != 2 || daf.ni != 5 {
+            return Err(NyxError::LoadingError(format!(
+                "`{}` is not a binary PCK: ND = {} and NI = {}",
+                daf.id_word, daf.nd, daf.ni
+            )));
+        }
+
+        let mut me = Self::default();
+
+        for summary in daf.summaries()? {
+            let frame_id = summary.integers[0];
+            let inertial_frame = summary.integers[1];
+            let data_type = summary.integers[2];
+
+            if data_type != 2 {
+                warn!("skipping binary PCK segment of frame {frame_id}: type {data_type} is not supported");
+                continue;
+            } else if inertial_frame != SPK_J2000 && inertial_frame != ECLIPJ2000 {
+                warn!("skipping binary PCK segment of frame {frame_id}: inertial frame {inertial_frame} is not supported");
+                continue;
+            }
+
+            let words = daf.read_doubles(summary.start_addr, summary.end_addr)?;
+
+            me.segments.push(BpcSegment {
+                frame_id,
+                inertial_frame,
+                start_et_s: summary.doubles[0],
+                end_et_s: summary.doubles[1],
+                records: ChebyshevRecords::from_words(&words).map_err(|e| {
+                    NyxError::InvalidInterpolationData(format!(
+                        "binary PCK segment of frame {frame_id}: {e}"
+                    ))
+                })?,
+            });
+        }
+
+        Ok(me)
+    }
+
+    /// Returns the list of the body fixed frame IDs defined in this binary PCK
+    pub fn frame_ids(&self) -> Vec<i32> {
+        let mut ids = Vec::new();
+        for seg in &self.segments {
+            if !ids.contains(&seg.frame_id) {
+                ids.push(seg.frame_id);
+            }
+        }
+        ids
+    }
+
+    /// Returns the DCM from J2000 to the requested body fixed frame at the provided epoch.
+    /// As in SPICE, segments loaded last take precedence over those loaded first.
+    pub fn try_dcm_from_j2000(
+        &self,
+        frame_id: i32,
+        epoch: Epoch,
+    ) -> Result<Matrix3<f64>, NyxError> {
+        let et_s = epoch.to_et_seconds();
+        match self
+            .segments
+            .iter()
+            .rev()
+            .find(|seg| seg.frame_id == frame_id && seg.covers(et_s))
+        {
+            Some(seg) => Ok(seg.dcm_from_j2000(et_s)),
+            None => Err(NyxError::NoInterpolationData(format!(
+                "no binary PCK segment of frame {frame_id} covers {epoch}"
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod ut_bpc {
+    use super::*;
+    use crate::io::daf_writer::{build_bpc, DafArray};
+
+    #[test]
+    fn bpc_type2_euler_angles() {
+        // A single record of 100 s with a constant pole and a linearly increasing prime meridian
+        let (phi, delta) = (0.1, 1.2);
+        let mut words = vec![50.0, 50.0];
+        words.extend_from_slice(&[phi, 0.0]);
+        words.extend_from_slice(&[delta, 0.0]);
+        words.extend_from_slice(&[1.0, 0.5]);
+        words.extend_from_slice(&[0.0, 100.0, 8.0, 1.0]);
+
+        let bpc = Bpc::from_bytes(build_bpc(&[DafArray::bpc(
+            3000,
+            SPK_J2000,
+            0.0,
+            100.0,
+            words.clone(),
+        )]))
+        .unwrap();
+        assert_eq!(bpc.frame_ids(), vec![3000]);
+
+        for et_s in [0.0, 25.0, 100.0] {
+            let dcm = bpc
+                .try_dcm_from_j2000(3000, Epoch::from_et_seconds(et_s))
+                .unwrap();
+            let w = 1.0 + 0.5 * (et_s - 50.0) / 50.0;
+            let expected = r3(w) * r1(delta) * r3(phi);
+            assert!((dcm - expected).norm() < 1e-9);
+            // Must be a proper rotation
+            assert!((dcm * dcm.transpose() - Matrix3::identity()).norm() < 1e-12);
+        }
+
+        assert!(bpc
+            .try_dcm_from_j2000(3000, Epoch::from_et_seconds(150.0))
+            .is_err());
+        assert!(bpc
+            .try_dcm_from_j2000(31006, Epoch::from_et_seconds(50.0))
+            .is_err());
+
+        // The same angles relative to the ecliptic include the obliquity rotation
+        let bpc = Bpc::from_bytes(build_bpc(&[DafArray::bpc(
+            3000, ECLIPJ2000, 0.0, 100.0, words,
+        )]))
+        .unwrap();
+        let dcm = bpc
+            .try_dcm_from_j2000(3000, Epoch::from_et_seconds(50.0))
+            .unwrap();
+        let expected = r3(1.0) * r1(delta) * r3(phi) * r1(84_381.448_f64.to_radians() / 3600.0);
+        assert!((dcm - expected).norm() < 1e-9);
+    }
+}
@@ -0,0 +1,309 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::NyxError;
+use std::convert::TryInto;
+use std::fs::File;
+use std::io::Read;
+
+/// Size of a DAF record in bytes
+pub const RCRD_LEN: usize = 1024;
+/// Size of a double precision word in bytes
+const DBL_LEN: usize = 8;
+
+/// Endianness of the binary data in a DAF file
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Endian {
+    Little,
+    Big,
+}
+
+/// A summary of an array stored in a DAF, i.e. its double and integer components and its start and end addresses.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DafSummary {
+    /// The double precision components of the summary (e.g. start and end epochs in SPK and PCK files)
+    pub doubles: Vec<f64>,
+    /// The integer components of the summary, _excluding_ the initial and final addresses
+    pub integers: Vec<i32>,
+    /// Initial address of the array (one-indexed double precision word)
+    pub start_addr: usize,
+    /// Final address of the array (one-indexed double precision word)
+    pub end_addr: usize,
+}
+
+/// A Double precision Array File (DAF), the binary container of SPICE SPK and binary PCK kernels.
+///
+/// Refer to https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/req/daf.html for the specification.
+#[derive(Clone, Debug)]
+pub struct Daf {
+    /// File identification word, e.g. `DAF/SPK` or `DAF/PCK`
+    pub id_word: String,
+    /// Internal file name
+    pub internal_name: String,
+    /// Number of double precision components in each summary
+    pub nd: usize,
+    /// Number of integer components in each summary
+    pub ni: usize,
+    endian: Endian,
+    bytes: Vec<u8>,
+}
+
+impl Daf {
+    /// Loads the DAF from the provided file path
+    pub fn from_file(path: &str) -> Result<Self, NyxError> {
+        let mut bytes = Vec::new();
+        File::open(path)
+            .map_err(|e| NyxError::LoadingError(format!("{path}: {e}")))?
+            .read_to_end(&mut bytes)
+            .map_err(|e| NyxError::FileUnreadable(format!("{path}: {e}")))?;
+        Self::from_bytes(bytes)
+    }
+
+    /// Parses the provided buffer as a DAF
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Self, NyxError> {
+        if bytes.len() < RCRD_LEN {
+            return Err(NyxError::LoadingError(format!(
+                "DAF buffer of {} bytes is smaller than a single record",
+                bytes.len()
+            )));
+        }
+
+        let id_word = String::from_utf8_lossy(&bytes[0..8]).trim().to_string();
+        if !id_word.starts_with("DAF/") && !id_word.starts_with("NAIF/DAF") {
+            return Err(NyxError::LoadingError(format!(
+                "not a DAF file: identification word is `{id_word}`"
+            )));
+        }
+
+        let endian = match String::from_utf8_lossy(&bytes[88..96]).trim() {
+            "LTL-IEEE" => Endian::Little,
+            "BIG-IEEE" => Endian::Big,
+            fmt => {
+                return Err(NyxError::LoadingError(format!(
+                    "unsupported DAF binary format `{fmt}`"
+                )))
+            }
+        };
+
+        let mut me = Self {
+            id_word,
+            internal_name: String::from_utf8_lossy(&bytes[16..76]).trim().to_string(),
+            nd: 0,
+            ni: 0,
+            endian,
+            bytes,
+        };
+
+        me.nd = me.read_i32(8)? as usize;
+        me.ni = me.read_i32(12)? as usize;
+
+        if me.nd < 2 || me.ni < 3 {
+            return Err(NyxError::LoadingError(format!(
+                "invalid DAF summary format ND = {}, NI = {}",
+                me.nd, me.ni
+            )));
+        }
+
+        Ok(me)
+    }
+
+    /// Returns the number of double precision words in a single summary
+    fn summary_size(&self) -> usize {
+        self.nd + self.ni.div_ceil(2)
+    }
+
+    fn read_i32(&self, byte_offset: usize) -> Result<i32, NyxError> {
+        let raw: [u8; 4] = self
+            .bytes
+            .get(byte_offset..byte_offset + 4)
+            .ok_or_else(|| NyxError::LoadingError(format!("DAF truncated at byte {byte_offset}")))?
+            .try_into()
+            .unwrap();
+        Ok(match self.endian {
+            Endian::Little => i32::from_le_bytes(raw),
+            Endian::Big => i32::from_be_bytes(raw),
+        })
+    }
+
+    fn read_f64(&self, byte_offset: usize) -> Result<f64, NyxError> {
+        let raw: [u8; 8] = self
+            .bytes
+            .get(byte_offset..byte_offset + DBL_LEN)
+            .ok_or_else(|| NyxError::LoadingError(format!("DAF truncated at byte {byte_offset}")))?
+            .try_into()
+            .unwrap();
+        Ok(match self.endian {
+            Endian::Little => f64::from_le_bytes(raw),
+            Endian::Big => f64::from_be_bytes(raw),
+        })
+    }
+
+    /// Reads the double precision words between the one-indexed start and end addresses (inclusive)
+    pub fn read_doubles(&self, start_addr: usize, end_addr: usize) -> Result<Vec<f64>, NyxError> {
+        if start_addr == 0 || end_addr < start_addr {
+            return Err(NyxError::LoadingError(format!(
+                "invalid DAF address range {start_addr} -- {end_addr}"
+            )));
+        }
+        (start_addr..=end_addr)
+            .map(|addr| self.read_f64((addr - 1) * DBL_LEN))
+            .collect()
+    }
+
+    /// Returns all of the array summaries of this DAF, in the order in which they are stored in the file.
+    pub fn summaries(&self) -> Result<Vec<DafSummary>, NyxError> {
+        let mut summaries = Vec::new();
+        let mut record = self.read_i32(76)? as usize;
+        let ss = self.summary_size();
+
+        while record > 0 {
+            let rcrd_start = (record - 1) * RCRD_LEN;
+            let next = self.read_f64(rcrd_start)? as usize;
+            let count = self.read_f64(rcrd_start + 2 * DBL_LEN)? as usize;
+
+            for sno in 0..count {
+                let sum_start = rcrd_start + 3 * DBL_LEN + sno * ss * DBL_LEN;
+
+                let doubles = (0..self.nd)
+                    .map(|i| self.read_f64(sum_start + i * DBL_LEN))
+                    .collect::<Result<Vec<f64>, NyxError>>()?;
+
+                let int_start = sum_start + self.nd * DBL_LEN;
+                let mut integers = (0..self.ni)
+                    .map(|i| self.read_i32(int_start + i * 4))
+                    .collect::<Result<Vec<i32>, NyxError>>()?;
+
+                // The last two integers are always the initial and final addresses
+                let end_addr = integers.pop().unwrap() as usize;
+                let start_addr = integers.pop().unwrap() as usize;
+
+                summaries.push(DafSummary {
+                    doubles,
+                    integers,
+                    start_addr,
+                    end_addr,
+                });
+            }
+
+            record = next;
+        }
+
+        Ok(summaries)
+    }
+}
+
+/// Fixed length Chebyshev records, as used in SPK types 2 and 3 and in binary PCK type 2.
+///
+/// Each record starts with its midpoint and its radius (in ephemeris seconds), followed by the coefficients of each component.
+#[derive(Clone, Debug)]
+pub(crate) struct ChebyshevRecords {
+    init_s: f64,
+    interval_s: f64,
+    record_size: usize,
+    num_records: usize,
+    records: Vec<f64>,
+}
+
+impl ChebyshevRecords {
+    /// Builds the records from the words of the DAF array, whose last four words are INIT, INTLEN, RSIZE and N.
+    pub(crate) fn from_words(words: &[f64]) -> Result<Self, NyxError> {
+        let n = words.len();
+        if n < 4 {
+            return Err(NyxError::InvalidInterpolationData(format!(
+                "Chebyshev array of {n} words is too short"
+            )));
+        }
+        let record_size = words[n - 2] as usize;
+        let num_records = words[n - 1] as usize;
+        if record_size < 3 || num_records == 0 || record_size * num_records + 4 != n {
+            return Err(NyxError::InvalidInterpolationData(format!(
+                "Chebyshev array has {num_records} records of {record_size} words but {n} words"
+            )));
+        }
+        Ok(Self {
+            init_s: words[n - 4],
+            interval_s: words[n - 3],
+            record_size,
+            num_records,
+            records: words[..n - 4].to_vec(),
+        })
+    }
+
+    /// Evaluates each of the `components` series of the record covering the provided ephemeris time.
+    /// Returns the value and the time derivative (per second) of each component.
+    pub(crate) fn evaluate(&self, et_s: f64, components: usize) -> Vec<(f64, f64)> {
+        let mut index = ((et_s - self.init_s) / self.interval_s).floor().max(0.0) as usize;
+        if index >= self.num_records {
+            // The last epoch is covered by the last record
+            index = self.num_records - 1;
+        }
+
+        let record = &self.records[index * self.record_size..(index + 1) * self.record_size];
+        let (mid_s, radius_s) = (record[0], record[1]);
+        let s = (et_s - mid_s) / radius_s;
+        let num_coeffs = (self.record_size - 2) / components;
+
+        (0..components)
+            .map(|i| {
+                let (val, deriv) =
+                    chebyshev_eval(&record[2 + i * num_coeffs..2 + (i + 1) * num_coeffs], s);
+                (val, deriv / radius_s)
+            })
+            .collect()
+    }
+}
+
+/// Evaluates a Chebyshev series and its derivative (with respect to the normalized time) at `s` in [-1, 1]
+pub(crate) fn chebyshev_eval(coeffs: &[f64], s: f64) -> (f64, f64) {
+    let mut val = 0.0;
+    let mut deriv = 0.0;
+    // T_{n-2}, T_{n-1} and their derivatives
+    let (mut t_2, mut t_1) = (1.0, s);
+    let (mut dt_2, mut dt_1) = (0.0, 1.0);
+
+    for (n, coeff) in coeffs.iter().enumerate() {
+        let (t_n, dt_n) = match n {
+            0 => (1.0, 0.0),
+            1 => (s, 1.0),
+            _ => {
+                let t_n = 2.0 * s * t_1 - t_2;
+                let dt_n = 2.0 * t_1 + 2.0 * s * dt_1 - dt_2;
+                t_2 = t_1;
+                t_1 = t_n;
+                dt_2 = dt_1;
+                dt_1 = dt_n;
+                (t_n, dt_n)
+            }
+        };
+        val += coeff * t_n;
+        deriv += coeff * dt_n;
+    }
+
+    (val, deriv)
+}
+
+#[test]
+fn test_chebyshev_eval() {
+    // f(s) = 1 + 2 s + 3 (2 s^2 - 1) ; f'(s) = 2 + 12 s
+    let coeffs = [1.0, 2.0, 3.0];
+    for s in [-1.0, -0.3, 0.0, 0.5, 1.0] {
+        let (val, deriv) = chebyshev_eval(&coeffs, s);
+        assert!((val - (1.0 + 2.0 * s + 3.0 * (2.0 * s * s - 1.0))).abs() < 1e-14);
+        assert!((deriv - (2.0 + 12.0 * s)).abs() < 1e-14);
+    }
+}
@@ -0,0 +1,96 @@
+//! Writes small little endian DAF files (SPK and binary PCK) used as fixtures by the tests.
+
+/// Length of a DAF record in bytes
+const RCRD_LEN: usize = 1024;
+
+/// A single array of a DAF file
+pub struct DafArray {
+    /// Double precision components of the summary (e.g. the start and end epochs)
+    pub doubles: Vec<f64>,
+    /// Integer components of the summary, without the initial and final addresses which are computed when writing
+    pub integers: Vec<i32>,
+    /// Content of the array
+    pub words: Vec<f64>,
+}
+
+impl DafArray {
+    /// An SPK segment of the target with respect to the center in the J2000 frame
+    pub fn spk(
+        target: i32,
+        center: i32,
+        data_type: i32,
+        start: f64,
+        end: f64,
+        words: Vec<f64>,
+    ) -> Self {
+        Self {
+            doubles: vec![start, end],
+            integers: vec![target, center, 1, data_type],
+            words,
+        }
+    }
+
+    /// A binary PCK type 2 segment of the frame with respect to the inertial frame
+    pub fn bpc(frame_id: i32, inertial_frame: i32, start: f64, end: f64, words: Vec<f64>) -> Self {
+        Self {
+            doubles: vec![start, end],
+            integers: vec![frame_id, inertial_frame, 2],
+            words,
+        }
+    }
+}
+
+/// Builds an SPK with all of these segments
+pub fn build_spk(segments: &[DafArray]) -> Vec<u8> {
+    build_daf(b"DAF/SPK ", 6, segments)
+}
+
+/// Builds a binary PCK with all of these segments
+pub fn build_bpc(segments: &[DafArray]) -> Vec<u8> {
+    build_daf(b"DAF/PCK ", 5, segments)
+}
+
+/// Builds a DAF with a single summary record, followed by the name record and the arrays
+fn build_daf(id_word: &[u8; 8], ni: usize, arrays: &[DafArray]) -> Vec<u8> {
+    let nd = 2;
+    let summary_size = nd + ni.div_ceil(2);
+    assert!(3 + arrays.len() * summary_size <= RCRD_LEN / 8);
+
+    let mut bytes = vec![0_u8; 3 * RCRD_LEN];
+    bytes[0..8].copy_from_slice(id_word);
+    bytes[8..12].copy_from_slice(&(nd as i32).to_le_bytes());
+    bytes[12..16].copy_from_slice(&(ni as i32).to_le_bytes());
+    bytes[76..80].copy_from_slice(&2_i32.to_le_bytes());
+    bytes[80..84].copy_from_slice(&2_i32.to_le_bytes());
+    bytes[88..96].copy_from_slice(b"LTL-IEEE");
+
+    let sr = RCRD_LEN;
+    bytes[sr + 16..sr + 24].copy_from_slice(&(arrays.len() as f64).to_le_bytes());
+    for (i, array) in arrays.iter().enumerate() {
+        assert_eq!(array.doubles.len(), nd);
+        assert_eq!(array.integers.len() + 2, ni);
+        let start_addr = bytes.len() / 8 + 1;
+        let end_addr = start_addr + array.words.len() - 1;
+
+        let sum_start = sr + 24 + i * summary_size * 8;
+        for (j, double) in array.doubles.iter().enumerate() {
+            bytes[sum_start + 8 * j..sum_start + 8 * (j + 1)]
+                .copy_from_slice(&double.to_le_bytes());
+        }
+        let int_start = sum_start + 8 * nd;
+        for (j, int) in array
+            .integers
+            .iter()
+            .copied()
+            .chain([start_addr as i32, end_addr as i32])
+            .enumerate()
+        {
+            bytes[int_start + 4 * j..int_start + 4 * (j + 1)].copy_from_slice(&int.to_le_bytes());
+        }
+
+        for word in &array.words {
+            bytes.extend_from_slice(&word.to_le_bytes());
+        }
+    }
+    bytes
+}
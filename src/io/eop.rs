@@ -0,0 +1,320 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::time::Epoch;
+use crate::NyxError;
+use std::fs::read_to_string;
+
+/// Earth orientation parameters of a single epoch, as published by the IERS.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct EopEntry {
+    /// Modified Julian Date in UTC (entries are usually at 0h UTC)
+    pub mjd_utc: f64,
+    /// X coordinate of the pole, in arcseconds
+    pub x_pole_arcsec: f64,
+    /// Y coordinate of the pole, in arcseconds
+    pub y_pole_arcsec: f64,
+    /// UT1 - UTC, in seconds
+    pub ut1_utc_s: f64,
+    /// Celestial pole offset dX with respect to the IAU 2000A nutation, in arcseconds
+    pub dx_arcsec: f64,
+    /// Celestial pole offset dY with respect to the IAU 2000A nutation, in arcseconds
+    pub dy_arcsec: f64,
+}
+
+/// A table of IERS Earth orientation parameters, from either the `finals2000A` files (Bulletin A, which include predictions)
+/// or the C04 series, interpolated linearly between entries.
+///
+/// Data available at https://datacenter.iers.org/products/eop/ .
+#[derive(Clone, Debug)]
+pub struct EarthOrientationParams {
+    entries: Vec<EopEntry>,
+}
+
+/// Returns TAI - UTC in seconds at the provided UTC MJD
+fn tai_utc_s(mjd_utc: f64) -> f64 {
+    Epoch::from_mjd_utc(mjd_utc)
+        .leap_seconds(true)
+        .unwrap_or(0.0)
+}
+
+impl EarthOrientationParams {
+    /// Builds the table from the provided entries, which are sorted by epoch.
+    /// The MJDs must be finite and unique.
+    pub fn from_entries(mut entries: Vec<EopEntry>) -> Result<Self, NyxError> {
+        if entries.len() < 2 {
+            return Err(NyxError::NoInterpolationData(format!(
+                "{} EOP entries is not enough to interpolate",
+                entries.len()
+            )));
+        }
+        if let Some(entry) = entries.iter().find(|entry| !entry.mjd_utc.is_finite()) {
+            return Err(NyxError::NoInterpolationData(format!(
+                "EOP entry with a non finite MJD: {}",
+                entry.mjd_utc
+            )));
+        }
+        entries.sort_by(|a, b| a.mjd_utc.total_cmp(&b.mjd_utc));
+        if let Some(pair) = entries
+            .windows(2)
+            .find(|pair| pair[1].mjd_utc <= pair[0].mjd_utc)
+        {
+            return Err(NyxError::NoInterpolationData(format!(
+                "duplicate EOP entries at MJD {}",
+                pair[0].mjd_utc
+            )));
+        }
+        Ok(Self { entries })
+    }
+
+    /// Loads an IERS `finals2000A` file (e.g. `finals2000A.all` or `finals2000A.daily`)
+    pub fn from_finals2000a_file(path: &str) -> Result<Self, NyxError> {
+        Self::from_finals2000a(
+            &read_to_string(path).map_err(|e| NyxError::FileUnreadable(format!("{path}: {e}")))?,
+        )
+    }
+
+    /// Parses the content of an IERS `finals2000A` file, using the Bulletin A values.
+    /// Lines without polar motion (i.e. beyond the predictions) are skipped, and missing celestial pole offsets are set to zero.
+    pub fn from_finals2000a(content: &str) -> Result<Self, NyxError> {
+        // Parses the fixed width column between the one-indexed start and end columns (inclusive)
+        fn column(
+            line: &str,
+            lno: usize,
+            start: usize,
+            end: usize,
+        ) -> Result<Option<f64>, NyxError> {
+            match line.get(start - 1..end.min(line.len())) {
+                Some(field) if !field.trim().is_empty() => {
+                    field.trim().parse::<f64>().map(Some).map_err(|e| {
+                        NyxError::LoadingError(format!(
+                            "finals2000A line {}: could not parse `{field}`: {e}",
+                            lno + 1
+                        ))
+                    })
+                }
+                _ => Ok(None),
+            }
+        }
+
+        let mut entries = Vec::new();
+        for (lno, line) in content.lines().enumerate() {
+            let (mjd_utc, x_pole_arcsec, y_pole_arcsec, ut1_utc_s) = match (
+                column(line, lno, 8, 15)?,
+                column(line, lno, 19, 27)?,
+                column(line, lno, 38, 46)?,
+                column(line, lno, 59, 68)?,
+            ) {
+                (Some(mjd), Some(x), Some(y), Some(dut1)) => (mjd, x, y, dut1),
+                _ => continue,
+            };
+
+            entries.push(EopEntry {
+                mjd_utc,
+                x_pole_arcsec,
+                y_pole_arcsec,
+                ut1_utc_s,
+                dx_arcsec: column(line, lno, 98, 106)?.unwrap_or(0.0) * 1e-3,
+                dy_arcsec: column(line, lno, 117, 125)?.unwrap_or(0.0) * 1e-3,
+            });
+        }
+
+        Self::from_entries(entries)
+    }
+
+    /// Loads an IERS C04 file (either the 14 C04 or 20 C04 series, relative to IAU 2000A)
+    pub fn from_c04_file(path: &str) -> Result<Self, NyxError> {
+        Self::from_c04(
+            &read_to_string(path).map_err(|e| NyxError::FileUnreadable(format!("{path}: {e}")))?,
+        )
+    }
+
+    /// Parses the content of an IERS C04 file. Header lines are skipped.
+    ///
+    /// The 14 C04 series columns start with `year month day MJD x y UT1-UTC LOD dX dY`, whereas the
+    /// 20 C04 series columns start with `year month day hour MJD x y UT1-UTC dX dY`.
+    pub fn from_c04(content: &str) -> Result<Self, NyxError> {
+        let mut entries = Vec::new();
+        for line in content.lines() {
+            let fields = match line
+                .split_whitespace()
+                .map(|field| field.parse::<f64>())
+                .collect::<Result<Vec<f64>, _>>()
+            {
+                Ok(fields) if fields.len() >= 10 => fields,
+                _ => continue,
+            };
+
+            // The MJD is after the hour in the 20 C04 series
+            entries.push(if fields[3] < 24.0 {
+                EopEntry {
+                    mjd_utc: fields[4],
+                    x_pole_arcsec: fields[5],
+                    y_pole_arcsec: fields[6],
+                    ut1_utc_s: fields[7],
+                    dx_arcsec: fields[8],
+                    dy_arcsec: fields[9],
+                }
+            } else {
+                EopEntry {
+                    mjd_utc: fields[3],
+                    x_pole_arcsec: fields[4],
+                    y_pole_arcsec: fields[5],
+                    ut1_utc_s: fields[6],
+                    dx_arcsec: fields[8],
+                    dy_arcsec: fields[9],
+                }
+            });
+        }
+
+        Self::from_entries(entries)
+    }
+
+    /// Returns all of the entries of this table, sorted by epoch
+    pub fn entries(&self) -> &[EopEntry] {
+        &self.entries
+    }
+
+    /// Returns the Earth orientation parameters at the provided epoch, linearly interpolated between the two closest entries.
+    /// UT1 - UTC is interpolated as UT1 - TAI to account for leap seconds.
+    pub fn at(&self, epoch: Epoch) -> Result<EopEntry, NyxError> {
+        let mjd_utc = epoch.to_mjd_utc_days();
+        let first = self.entries.first().unwrap();
+        let last = self.entries.last().unwrap();
+        if mjd_utc < first.mjd_utc || mjd_utc > last.mjd_utc {
+            return Err(NyxError::NoInterpolationData(format!(
+                "{epoch} is outside of the EOP data (MJD UTC {} to {})",
+                first.mjd_utc, last.mjd_utc
+            )));
+        }
+
+        // Index of the first entry after the requested epoch
+        let idx = self
+            .entries
+            .partition_point(|entry| entry.mjd_utc <= mjd_utc)
+            .clamp(1, self.entries.len() - 1);
+        let (prev, next) = (&self.entries[idx - 1], &self.entries[idx]);
+        let frac = (mjd_utc - prev.mjd_utc) / (next.mjd_utc - prev.mjd_utc);
+        let interp = |prev_val: f64, next_val: f64| prev_val + frac * (next_val - prev_val);
+
+        let ut1_tai_s = interp(
+            prev.ut1_utc_s - tai_utc_s(prev.mjd_utc),
+            next.ut1_utc_s - tai_utc_s(next.mjd_utc),
+        );
+
+        Ok(EopEntry {
+            mjd_utc,
+            x_pole_arcsec: interp(prev.x_pole_arcsec, next.x_pole_arcsec),
+            y_pole_arcsec: interp(prev.y_pole_arcsec, next.y_pole_arcsec),
+            ut1_utc_s: ut1_tai_s + tai_utc_s(mjd_utc),
+            dx_arcsec: interp(prev.dx_arcsec, next.dx_arcsec),
+            dy_arcsec: interp(prev.dy_arcsec, next.dy_arcsec),
+        })
+    }
+}
+
+#[cfg(test)]
+mod ut_eop {
+    use super::*;
+
+    #[test]
+    fn eop_finals2000a() {
+        // Around the leap second at the end of 2016
+        let finals = "\
+161230 57752.00 I  0.045405 0.000024  0.275930 0.000030  I 0.5925020 0.0000111  1.1264 0.0075  I     0.083    0.150     0.122    0.080  0.045413  0.275925  0.5925026     0.072     0.123
+161231 57753.00 I  0.043980 0.000024  0.275800 0.000031  I 0.5913620 0.0000093  1.1411 0.0070  I     0.095    0.150     0.121    0.080  0.043998  0.275793  0.5913625     0.075     0.121
+17 1 1 57754.00 I  0.042570 0.000025  0.275460 0.000032  I-0.4097932 0.0000108  1.1931 0.0074  I     0.105    0.150     0.118    0.080  0.042576  0.275471 -0.4097923     0.082     0.123
+17 1 2 57755.00 P  0.041150 0.002500  0.275130 0.003200  P-0.4109620 0.0040000                 P     0.110    0.100     0.120    0.100
+17 1 3 57756.00                                                                                                                                                                                       ";
+        let eop = EarthOrientationParams::from_finals2000a(finals).unwrap();
+        assert_eq!(eop.entries().len(), 4);
+        let first = eop.entries()[0];
+        assert_eq!(first.mjd_utc, 57752.0);
+        assert_eq!(first.x_pole_arcsec, 0.045405);
+        assert_eq!(first.y_pole_arcsec, 0.27593);
+        assert_eq!(first.ut1_utc_s, 0.592502);
+        assert!((first.dx_arcsec - 0.083e-3).abs() < 1e-15);
+        assert!((first.dy_arcsec - 0.122e-3).abs() < 1e-15);
+        // Predictions without celestial pole offsets
+        assert!((eop.entries()[3].dx_arcsec - 0.110e-3).abs() < 1e-15);
+
+        let noon = eop.at(Epoch::from_mjd_utc(57752.5)).unwrap();
+        assert!((noon.x_pole_arcsec - (0.045405 + 0.043980) / 2.0).abs() < 1e-9);
+        assert!((noon.ut1_utc_s - (0.5925020 + 0.5913620) / 2.0).abs() < 1e-9);
+
+        // UT1 - UTC jumps at the leap second, so the interpolation must be continuous in UT1 - TAI
+        let noon = eop.at(Epoch::from_mjd_utc(57753.5)).unwrap();
+        assert!((noon.ut1_utc_s - (0.5913620 - 0.4097932 + 1.0) / 2.0 + 1.0).abs() < 1e-9);
+
+        assert!(eop.at(Epoch::from_mjd_utc(57756.0)).is_err());
+        assert!(eop.at(Epoch::from_mjd_utc(57751.0)).is_err());
+    }
+
+    #[test]
+    fn eop_c04() {
+        let c04_14 = "\
+                      EARTH ORIENTATION PARAMETER (EOP) PRODUCT CENTER CENTER (PARIS OBSERVATORY)
+      Date      MJD      x          y        UT1-UTC       LOD         dX        dY        x Err     y Err   UT1-UTC Err  LOD Err     dX Err       dY Err
+                         \"          \"           s           s          \"         \"           \"          \"          s         s            \"           \"
+     (0h UTC)
+
+2004   4   6  53101  -0.140682   0.333309  -0.4399619   0.0015563  -0.000205  -0.000136   0.000044   0.000040  0.0000093  0.0000074    0.000090    0.000090
+2004   4   7  53102  -0.139199   0.333714  -0.4415317   0.0015750  -0.000200  -0.000139   0.000046   0.000042  0.0000092  0.0000071    0.000089    0.000089
+";
+        let eop = EarthOrientationParams::from_c04(c04_14).unwrap();
+        assert_eq!(eop.entries().len(), 2);
+        assert_eq!(eop.entries()[0].mjd_utc, 53101.0);
+        assert_eq!(eop.entries()[0].ut1_utc_s, -0.4399619);
+        assert_eq!(eop.entries()[1].dy_arcsec, -0.000139);
+
+        let c04_20 = "\
+# YR  MM  DD  HH       MJD        x(\")        y(\")  UT1-UTC(s)       dX(\")      dY(\")       xrt(\")      yrt(\")      LOD(s)        x Er        y Er  UT1-UTC Er      dX Er       dY Er       xrt Er      yrt Er      LOD Er
+2004   4   6   0  53101.00  -0.140688   0.333313  -0.4399605   -0.000180   -0.000127    0.000000    0.000000   0.0015570   0.000040   0.000040   0.0000100    0.000050   0.000050    0.000000   0.000000   0.0000100
+2004   4   7   0  53102.00  -0.139207   0.333719  -0.4415313   -0.000186   -0.000129    0.000000    0.000000   0.0015710   0.000040   0.000040   0.0000100    0.000050   0.000050    0.000000   0.000000   0.0000100
+";
+        let eop = EarthOrientationParams::from_c04(c04_20).unwrap();
+        assert_eq!(eop.entries().len(), 2);
+        assert_eq!(eop.entries()[1].mjd_utc, 53102.0);
+        assert_eq!(eop.entries()[0].x_pole_arcsec, -0.140688);
+        assert_eq!(eop.entries()[0].ut1_utc_s, -0.4399605);
+        assert_eq!(eop.entries()[0].dx_arcsec, -0.000180);
+    }
+
+    #[test]
+    fn eop_invalid_entries() {
+        let entry = |mjd_utc| EopEntry {
+            mjd_utc,
+            ..Default::default()
+        };
+
+        // Unsorted entries are fine
+        let eop = EarthOrientationParams::from_entries(vec![entry(2.0), entry(1.0)]).unwrap();
+        assert_eq!(eop.entries()[0].mjd_utc, 1.0);
+
+        for mjds in [
+            vec![1.0, 2.0, 1.0],
+            vec![1.0, f64::NAN],
+            vec![f64::NEG_INFINITY, 1.0],
+        ] {
+            assert!(matches!(
+                EarthOrientationParams::from_entries(mjds.into_iter().map(entry).collect()),
+                Err(NyxError::NoInterpolationData(_))
+            ));
+        }
+    }
+}
@@ -16,7 +16,7 @@
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
-use crate::cosmic::Frame;
+use crate::cosmic::{Frame, FrameKind, TreePath};
 use serde_derive::Deserialize;
 use std::collections::HashMap;
 
@@ -59,8 +59,9 @@ impl FrameSerde {
             flattening: self.flattening,
             equatorial_radius: self.equatorial_radius,
             semi_major_radius: self.semi_major_radius,
-            ephem_path: [None, None, None],
-            frame_path: [None, None, None],
+            ephem_path: TreePath::default(),
+            frame_path: TreePath::default(),
+            kind: FrameKind::J2000,
         }
     }
 }
@@ -42,9 +42,18 @@ use typed_builder::TypedBuilder;
 use self::orbit::OrbitSerde;
 use crate::cosmic::{Cosm, Frame};
 
+/// Handles loading of SPICE binary PCK orientation kernels (e.g. the high precision Earth orientation)
+pub mod bpc;
 /// Handles writing to an XYZV file
 pub mod cosmo;
+/// Handles reading of the SPICE Double precision Array Files (DAF), the container of SPK and binary PCK kernels
+pub mod daf;
+/// Writes small DAF files used as fixtures by the tests
+#[cfg(test)]
+pub(crate) mod daf_writer;
 pub mod dynamics;
+/// Handles loading of the IERS Earth orientation parameters (finals2000A and C04 files)
+pub mod eop;
 pub mod estimate;
 /// Handles reading from frames defined in input files
 pub mod frame_serde;
@@ -52,6 +61,14 @@ pub mod frame_serde;
 pub mod gravity;
 pub mod matrices;
 pub mod orbit;
+/// Handles loading of SPICE text planetary constants kernels (e.g. pck00010.tpc and gm_de431.tpc)
+pub mod pck;
+/// Handles loading of the space weather indices (CelesTrak F10.7 and Ap, and the JB2008 SOLFSMY and DTCFILE files)
+pub mod space_weather;
+/// Handles loading of SPICE SPK ephemeris kernels (e.g. the JPL DE files)
+pub mod spk;
+/// Handles parsing of two-line element sets (TLE), e.g. from CelesTrak
+pub mod tle;
 pub mod tracking_data;
 pub mod trajectory_data;
 
@@ -0,0 +1,615 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::cosmic::Frame;
+use crate::linalg::Vector3;
+use crate::NyxError;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::read_to_string;
+
+lazy_static::lazy_static! {
+    /// Planetary constants embedded in Nyx, cf. `data/embed/pck_constants.tpc`
+    static ref EMBEDDED_PCK: PlanetaryConstants =
+        PlanetaryConstants::from_text(include_str!("../../data/embed/pck_constants.tpc"))
+            .expect("could not parse the embedded planetary constants");
+}
+
+/// The physical constants of a celestial body, as defined in a text planetary constants kernel.
+/// Kernels usually only define some of these, so all of them are optional.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BodyConstants {
+    /// NAIF ID of this body, e.g. 401 for Phobos
+    pub naif_id: i32,
+    /// Name of this body (`NAIF_BODY_NAME`)
+    pub name: Option<String>,
+    /// Gravitational parameter in km^3/s^2 (`BODYnnn_GM`)
+    pub gm_km3_s2: Option<f64>,
+    /// Triaxial radii in km: the largest and smallest equatorial radii, then the polar radius (`BODYnnn_RADII`)
+    pub radii_km: Option<Vector3<f64>>,
+    /// Unnormalized J2 zonal harmonic (`BODYnnn_J2`)
+    pub j2: Option<f64>,
+    /// Right ascension of the pole in degrees, as a polynomial of the TDB centuries past J2000 (`BODYnnn_POLE_RA`)
+    pub pole_ra_deg: Option<[f64; 3]>,
+    /// Declination of the pole in degrees, as a polynomial of the TDB centuries past J2000 (`BODYnnn_POLE_DEC`)
+    pub pole_dec_deg: Option<[f64; 3]>,
+    /// Prime meridian in degrees, as a polynomial of the TDB days past J2000 (`BODYnnn_PM`)
+    pub pm_deg: Option<[f64; 3]>,
+}
+
+impl BodyConstants {
+    /// Initializes the constants of this body, all of which are unset.
+    pub fn new(naif_id: i32) -> Self {
+        Self {
+            naif_id,
+            ..Default::default()
+        }
+    }
+
+    /// Overwrites the constants of this body with those set in `other`.
+    pub fn update_from(&mut self, other: &Self) {
+        if other.name.is_some() {
+            self.name = other.name.clone();
+        }
+        if other.gm_km3_s2.is_some() {
+            self.gm_km3_s2 = other.gm_km3_s2;
+        }
+        if other.radii_km.is_some() {
+            self.radii_km = other.radii_km;
+        }
+        if other.j2.is_some() {
+            self.j2 = other.j2;
+        }
+        if other.pole_ra_deg.is_some() {
+            self.pole_ra_deg = other.pole_ra_deg;
+        }
+        if other.pole_dec_deg.is_some() {
+            self.pole_dec_deg = other.pole_dec_deg;
+        }
+        if other.pm_deg.is_some() {
+            self.pm_deg = other.pm_deg;
+        }
+    }
+
+    /// Returns whether the pole and the prime meridian of this body are defined
+    pub fn has_rotation(&self) -> bool {
+        self.pole_ra_deg.is_some() && self.pole_dec_deg.is_some() && self.pm_deg.is_some()
+    }
+
+    /// Returns the largest equatorial radius in km
+    pub fn equatorial_radius_km(&self) -> Option<f64> {
+        self.radii_km.map(|radii| radii[0])
+    }
+
+    /// Returns the polar radius in km
+    pub fn polar_radius_km(&self) -> Option<f64> {
+        self.radii_km.map(|radii| radii[2])
+    }
+
+    /// Returns the flattening between the largest equatorial radius and the polar radius
+    pub fn flattening(&self) -> Option<f64> {
+        self.radii_km.map(|radii| (radii[0] - radii[2]) / radii[0])
+    }
+
+    /// Returns the rotation rate of the prime meridian at J2000 in rad/s, negative for retrograde rotators (e.g. Venus)
+    pub fn angular_velocity_rad_s(&self) -> Option<f64> {
+        self.pm_deg.map(|pm| pm[1].to_radians() / 86_400.0)
+    }
+
+    /// Returns the right ascension, declination and prime meridian expressions of the rotation model, in degrees,
+    /// as functions of `T` (centuries) and `d` (days) as expected by `Euler3AxisDt::from_ra_dec_w`.
+    pub fn rotation_exprs(&self) -> Option<(String, String, String)> {
+        fn polynomial(coeffs: &[f64; 3], var: &str) -> String {
+            let mut expr = format!("{}", coeffs[0]);
+            for (power, coeff) in coeffs.iter().enumerate().skip(1) {
+                if *coeff != 0.0 {
+                    let sign = if *coeff < 0.0 { '-' } else { '+' };
+                    let term = if power == 1 {
+                        var.to_string()
+                    } else {
+                        format!("{var}^{power}")
+                    };
+                    expr.push_str(&format!(" {sign} {:e}*{term}", coeff.abs()));
+                }
+            }
+            expr
+        }
+
+        match (self.pole_ra_deg, self.pole_dec_deg, self.pm_deg) {
+            (Some(ra), Some(dec), Some(pm)) => Some((
+                polynomial(&ra, "T"),
+                polynomial(&dec, "T"),
+                polynomial(&pm, "d"),
+            )),
+            _ => None,
+        }
+    }
+
+    /// Returns this frame with the GM and shape of this body, when they are defined.
+    /// A celestial frame becomes a geoid if the radii are defined.
+    pub fn update_frame(&self, frame: Frame) -> Frame {
+        match frame {
+            Frame::Celestial {
+                gm,
+                ephem_path,
+                frame_path,
+                kind,
+            } => match self.radii_km {
+                Some(radii) => Frame::Geoid {
+                    gm: self.gm_km3_s2.unwrap_or(gm),
+                    flattening: self.flattening().unwrap(),
+                    equatorial_radius: radii[0],
+                    semi_major_radius: radii[0],
+                    ephem_path,
+                    frame_path,
+                    kind,
+                },
+                None => Frame::Celestial {
+                    gm: self.gm_km3_s2.unwrap_or(gm),
+                    ephem_path,
+                    frame_path,
+                    kind,
+                },
+            },
+            Frame::Geoid {
+                gm,
+                flattening,
+                equatorial_radius,
+                semi_major_radius,
+                ephem_path,
+                frame_path,
+                kind,
+            } => Frame::Geoid {
+                gm: self.gm_km3_s2.unwrap_or(gm),
+                flattening: self.flattening().unwrap_or(flattening),
+                equatorial_radius: self.equatorial_radius_km().unwrap_or(equatorial_radius),
+                semi_major_radius: self.equatorial_radius_km().unwrap_or(semi_major_radius),
+                ephem_path,
+                frame_path,
+                kind,
+            },
+            _ => frame,
+        }
+    }
+}
+
+impl fmt::Display for BodyConstants {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} (NAIF {})",
+            self.name.as_deref().unwrap_or("unnamed body"),
+            self.naif_id
+        )?;
+        if let Some(gm) = self.gm_km3_s2 {
+            write!(f, "\tGM = {gm} km^3/s^2")?;
+        }
+        if let Some(radii) = self.radii_km {
+            write!(f, "\tradii = ({}, {}, {}) km", radii[0], radii[1], radii[2])?;
+        }
+        if let Some(j2) = self.j2 {
+            write!(f, "\tJ2 = {j2:e}")?;
+        }
+        if let Some(rate) = self.angular_velocity_rad_s() {
+            write!(f, "\tω = {rate:e} rad/s")?;
+        }
+        Ok(())
+    }
+}
+
+/// A value of a text kernel assignment
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum KernelValue {
+    Number(f64),
+    Text(String),
+}
+
+/// Splits the data sections of a SPICE text kernel into its assignments, in order.
+pub(crate) fn parse_text_kernel(
+    content: &str,
+) -> Result<Vec<(String, Vec<KernelValue>)>, NyxError> {
+    // Only keep the data sections
+    let mut data = String::new();
+    let mut in_data = false;
+    for line in content.lines() {
+        match line.trim() {
+            "\\begindata" => in_data = true,
+            "\\begintext" => in_data = false,
+            _ if in_data => {
+                data.push_str(line);
+                data.push('\n');
+            }
+            _ => {}
+        }
+    }
+
+    let mut assignments: Vec<(String, Vec<KernelValue>)> = Vec::new();
+    let mut chars = data.chars().peekable();
+    let mut keyword: Option<String> = None;
+    let mut values: Vec<KernelValue> = Vec::new();
+    let mut in_list = false;
+
+    let finish = |keyword: &mut Option<String>,
+                  values: &mut Vec<KernelValue>,
+                  assignments: &mut Vec<(String, Vec<KernelValue>)>| {
+        if let Some(keyword) = keyword.take() {
+            assignments.push((keyword, std::mem::take(values)));
+        }
+    };
+
+    while let Some(c) = chars.next() {
+        match c {
+            _ if c.is_whitespace() || c == ',' => {}
+            '(' if keyword.is_some() && !in_list => in_list = true,
+            ')' if in_list => {
+                in_list = false;
+                finish(&mut keyword, &mut values, &mut assignments);
+            }
+            '\'' if keyword.is_some() => {
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        // Quotes are escaped by doubling them
+                        Some('\'') if chars.peek() == Some(&'\'') => {
+                            chars.next();
+                            text.push('\'');
+                        }
+                        Some('\'') => break,
+                        Some(c) => text.push(c),
+                        None => {
+                            return Err(NyxError::LoadingError(format!(
+                                "unterminated string `{text}`"
+                            )))
+                        }
+                    }
+                }
+                values.push(KernelValue::Text(text));
+                if !in_list {
+                    finish(&mut keyword, &mut values, &mut assignments);
+                }
+            }
+            _ => {
+                let mut token = c.to_string();
+                while let Some(&next) = chars.peek() {
+                    if next.is_whitespace() || "(),='".contains(next) {
+                        break;
+                    }
+                    token.push(next);
+                    chars.next();
+                }
+
+                if keyword.is_none() {
+                    // This must be the name of the variable, followed by `=` or `+=`
+                    let name = token.trim_end_matches('+').to_string();
+                    while chars.peek().is_some_and(|c| c.is_whitespace()) {
+                        chars.next();
+                    }
+                    // Appending with `+=` is treated as an assignment
+                    if chars.peek() == Some(&'+') {
+                        chars.next();
+                    }
+                    if chars.next() != Some('=') {
+                        return Err(NyxError::LoadingError(format!(
+                            "expected an assignment after `{name}`"
+                        )));
+                    }
+                    keyword = Some(name);
+                } else {
+                    let value = token.replace(['D', 'd'], "E").parse::<f64>().map_err(|e| {
+                        NyxError::LoadingError(format!(
+                            "{}: could not parse `{token}`: {e}",
+                            keyword.as_ref().unwrap()
+                        ))
+                    })?;
+                    values.push(KernelValue::Number(value));
+                    if !in_list {
+                        finish(&mut keyword, &mut values, &mut assignments);
+                    }
+                }
+            }
+        }
+    }
+
+    if let Some(keyword) = keyword {
+        return Err(NyxError::LoadingError(format!(
+            "{keyword}: unterminated assignment"
+        )));
+    }
+
+    Ok(assignments)
+}
+
+/// A database of the physical constants of celestial bodies, loaded from SPICE text planetary constants kernels (e.g. `pck00010.tpc` or `gm_de431.tpc`).
+///
+/// Only the data between `\begindata` and `\begintext` is read, and only the `BODYnnn_GM`, `BODYnnn_RADII`, `BODYnnn_J2`, `BODYnnn_POLE_RA`,
+/// `BODYnnn_POLE_DEC` and `BODYnnn_PM` keywords and the body names (`NAIF_BODY_NAME` and `NAIF_BODY_CODE`) are used.
+/// The nutation and precession terms of the rotation models are ignored.
+#[derive(Clone, Debug, Default)]
+pub struct PlanetaryConstants {
+    bodies: HashMap<i32, BodyConstants>,
+    names: HashMap<String, i32>,
+}
+
+impl PlanetaryConstants {
+    /// Returns the planetary constants embedded in Nyx, which cover the bodies of the JPL DE files and their main moons.
+    pub fn embedded() -> &'static Self {
+        &EMBEDDED_PCK
+    }
+
+    /// Loads a text planetary constants kernel
+    pub fn from_file(path: &str) -> Result<Self, NyxError> {
+        Self::from_text(
+            &read_to_string(path).map_err(|e| NyxError::FileUnreadable(format!("{path}: {e}")))?,
+        )
+    }
+
+    /// Parses the content of a text planetary constants kernel
+    pub fn from_text(content: &str) -> Result<Self, NyxError> {
+        let mut pck = Self::default();
+        let mut names: Vec<String> = Vec::new();
+        let mut codes: Vec<f64> = Vec::new();
+
+        for (keyword, values) in parse_text_kernel(content)? {
+            let numbers = || -> Result<Vec<f64>, NyxError> {
+                values
+                    .iter()
+                    .map(|value| match value {
+                        KernelValue::Number(num) => Ok(*num),
+                        KernelValue::Text(text) => Err(NyxError::LoadingError(format!(
+                            "{keyword}: expected numbers, got `{text}`"
+                        ))),
+                    })
+                    .collect()
+            };
+
+            if keyword == "NAIF_BODY_NAME" {
+                for value in &values {
+                    match value {
+                        KernelValue::Text(name) => names.push(name.clone()),
+                        KernelValue::Number(num) => {
+                            return Err(NyxError::LoadingError(format!(
+                                "NAIF_BODY_NAME: expected names, got `{num}`"
+                            )))
+                        }
+                    }
+                }
+                continue;
+            } else if keyword == "NAIF_BODY_CODE" {
+                codes.extend(numbers()?);
+                continue;
+            }
+
+            let (naif_id, item) = match keyword
+                .strip_prefix("BODY")
+                .and_then(|rest| rest.split_once('_'))
+                .and_then(|(id, item)| Some((id.parse::<i32>().ok()?, item)))
+            {
+                Some(parsed) => parsed,
+                None => {
+                    debug!("ignoring kernel variable {keyword}");
+                    continue;
+                }
+            };
+
+            let numbers = numbers()?;
+            let polynomial = || -> Result<[f64; 3], NyxError> {
+                if numbers.is_empty() || numbers.len() > 3 {
+                    return Err(NyxError::LoadingError(format!(
+                        "{keyword}: expected one to three coefficients, got {}",
+                        numbers.len()
+                    )));
+                }
+                let mut coeffs = [0.0; 3];
+                coeffs[..numbers.len()].copy_from_slice(&numbers);
+                Ok(coeffs)
+            };
+            let scalar = || -> Result<f64, NyxError> {
+                match numbers.as_slice() {
+                    [value] => Ok(*value),
+                    _ => Err(NyxError::LoadingError(format!(
+                        "{keyword}: expected one value, got {}",
+                        numbers.len()
+                    ))),
+                }
+            };
+
+            let body = pck
+                .bodies
+                .entry(naif_id)
+                .or_insert_with(|| BodyConstants::new(naif_id));
+            match item {
+                "GM" => body.gm_km3_s2 = Some(scalar()?),
+                "J2" => body.j2 = Some(scalar()?),
+                "RADII" => match numbers.as_slice() {
+                    [a, b, c] => body.radii_km = Some(Vector3::new(*a, *b, *c)),
+                    _ => {
+                        return Err(NyxError::LoadingError(format!(
+                            "{keyword}: expected three radii, got {}",
+                            numbers.len()
+                        )))
+                    }
+                },
+                "POLE_RA" => body.pole_ra_deg = Some(polynomial()?),
+                "POLE_DEC" => body.pole_dec_deg = Some(polynomial()?),
+                "PM" => body.pm_deg = Some(polynomial()?),
+                _ => debug!("ignoring kernel variable {keyword}"),
+            }
+        }
+
+        if names.len() != codes.len() {
+            return Err(NyxError::LoadingError(format!(
+                "{} NAIF_BODY_NAME for {} NAIF_BODY_CODE",
+                names.len(),
+                codes.len()
+            )));
+        }
+        for (name, code) in names.into_iter().zip(codes) {
+            let naif_id = code as i32;
+            pck.names.insert(name.to_lowercase(), naif_id);
+            pck.bodies
+                .entry(naif_id)
+                .or_insert_with(|| BodyConstants::new(naif_id))
+                .name = Some(name);
+        }
+
+        Ok(pck)
+    }
+
+    /// Returns the constants of this body. The barycenters of the planetary systems (NAIF IDs 1 to 9) also have the shape and rotation
+    /// of their planet (e.g. the Mars barycenter has the radii of Mars), but not its GM.
+    pub fn get(&self, naif_id: i32) -> Option<BodyConstants> {
+        let own = self.bodies.get(&naif_id);
+        let planet = if (1..=9).contains(&naif_id) {
+            self.bodies.get(&(naif_id * 100 + 99))
+        } else {
+            None
+        };
+        match (own, planet) {
+            (Some(own), None) => Some(own.clone()),
+            (own, Some(planet)) => {
+                let mut constants = BodyConstants {
+                    naif_id,
+                    name: None,
+                    gm_km3_s2: None,
+                    ..planet.clone()
+                };
+                if let Some(own) = own {
+                    constants.update_from(own);
+                }
+                Some(constants)
+            }
+            (None, None) => None,
+        }
+    }
+
+    /// Returns the body whose rotation model defines the rotation of this body: the body itself, or the planet for the barycenter of a planetary system.
+    ///
+    /// The fallback to the planet is intended: the DE files only provide the barycenters of the outer planetary systems (e.g. `Pluto Barycenter J2000`),
+    /// so their frames rotate like their planet (e.g. at the rate of `BODY999_PM`) unless the kernel defines a rotation model for the barycenter itself.
+    pub fn rotation_body(&self, naif_id: i32) -> Option<&BodyConstants> {
+        match self.bodies.get(&naif_id) {
+            Some(body) if body.has_rotation() => Some(body),
+            _ if (1..=9).contains(&naif_id) => self
+                .bodies
+                .get(&(naif_id * 100 + 99))
+                .filter(|planet| planet.has_rotation()),
+            _ => None,
+        }
+    }
+
+    /// Returns the NAIF ID of the body of this name (case insensitive)
+    pub fn naif_id(&self, name: &str) -> Option<i32> {
+        self.names.get(&name.trim().to_lowercase()).copied()
+    }
+
+    /// Adds or overwrites the constants of this body
+    pub fn insert(&mut self, constants: BodyConstants) {
+        if let Some(name) = &constants.name {
+            self.names.insert(name.to_lowercase(), constants.naif_id);
+        }
+        self.bodies
+            .entry(constants.naif_id)
+            .or_insert_with(|| BodyConstants::new(constants.naif_id))
+            .update_from(&constants);
+    }
+
+    /// Overwrites these constants with those of `other`, e.g. to apply a kernel on top of the embedded constants.
+    pub fn append(&mut self, other: &Self) {
+        for constants in other.bodies.values() {
+            self.insert(constants.clone());
+        }
+        for (name, naif_id) in &other.names {
+            self.names.insert(name.clone(), *naif_id);
+        }
+    }
+
+    /// Returns the NAIF IDs of the bodies defined in these constants, sorted
+    pub fn naif_ids(&self) -> Vec<i32> {
+        let mut ids = self.bodies.keys().copied().collect::<Vec<i32>>();
+        ids.sort_unstable();
+        ids
+    }
+
+    /// Returns the number of bodies defined in these constants
+    pub fn len(&self) -> usize {
+        self.bodies.len()
+    }
+
+    /// Returns whether no body is defined in these constants
+    pub fn is_empty(&self) -> bool {
+        self.bodies.is_empty()
+    }
+}
+
+#[test]
+fn test_parse_text_pck() {
+    let pck = PlanetaryConstants::from_text(
+        r#"KPL/PCK
+    This text is ignored: BODY399_GM = ( 1.0 )
+\begindata
+    BODY502_GM       = ( 3.202738774922892D+03 )
+    BODY502_RADII    = ( 1562.6  1560.3  1559.5 )
+    BODY502_POLE_RA  = ( 268.08  -0.009  0. )
+    BODY502_POLE_DEC = (  64.51   0.003  0. )
+    BODY502_PM       = (  36.022  101.3747235  0. )
+    BODY502_NUT_PREC_RA = ( 0 0 0 1.086 0.060 0.015 0.009 )
+    BODY399_J2 = 1.08262668e-3
+    NAIF_BODY_NAME += ( 'EUROPA', 'Europa''s twin' )
+    NAIF_BODY_CODE += ( 502,
+                        -502 )
+\begintext
+    BODY502_GM = ( 2.0 )
+"#,
+    )
+    .unwrap();
+
+    let europa = pck.get(502).unwrap();
+    println!("{europa}");
+    assert_eq!(europa.gm_km3_s2, Some(3_202.738_774_922_892));
+    assert_eq!(europa.radii_km, Some(Vector3::new(1562.6, 1560.3, 1559.5)));
+    assert_eq!(europa.pole_ra_deg, Some([268.08, -0.009, 0.0]));
+    assert_eq!(europa.pm_deg, Some([36.022, 101.3747235, 0.0]));
+    assert_eq!(europa.name.as_deref(), Some("EUROPA"));
+    assert_eq!(pck.naif_id("europa"), Some(502));
+    assert_eq!(pck.naif_id("Europa's twin"), Some(-502));
+    assert_eq!(pck.get(399).unwrap().j2, Some(1.082_626_68e-3));
+    assert_eq!(pck.len(), 3);
+
+    let (ra, dec, w) = europa.rotation_exprs().unwrap();
+    assert_eq!(ra, "268.08 - 9e-3*T");
+    assert_eq!(dec, "64.51 + 3e-3*T");
+    assert_eq!(w, "36.022 + 1.013747235e2*d");
+
+    // Overwriting constants, and barycenters have the shape and rotation of their planet
+    let mut pck = PlanetaryConstants::embedded().clone();
+    let mars_bary = pck.get(4).unwrap();
+    assert_eq!(mars_bary.gm_km3_s2, Some(42_828.375_214));
+    assert_eq!(mars_bary.equatorial_radius_km(), Some(3396.19));
+    assert_eq!(pck.rotation_body(4).unwrap().naif_id, 499);
+    pck.append(
+        &PlanetaryConstants::from_text("\\begindata\nBODY499_RADII = ( 3400 3400 3380 )\n")
+            .unwrap(),
+    );
+    assert_eq!(pck.get(4).unwrap().equatorial_radius_km(), Some(3400.0));
+    assert_eq!(pck.get(499).unwrap().gm_km3_s2, Some(42_828.373_620_699_1));
+
+    // Malformed kernels
+    assert!(PlanetaryConstants::from_text("\\begindata\nBODY499_RADII = ( 3400 3380 )\n").is_err());
+    assert!(PlanetaryConstants::from_text("\\begindata\nBODY499_GM = ( 1.0 \n").is_err());
+    assert!(PlanetaryConstants::from_text("\\begindata\nBODY499_GM = ( abc )\n").is_err());
+}
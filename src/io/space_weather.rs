@@ -0,0 +1,574 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::time::{Epoch, Unit};
+use crate::NyxError;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::read_to_string;
+
+/// Daily space weather indices of a single day, as published by CelesTrak.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct SpaceWeatherEntry {
+    /// Modified Julian Date in UTC of the start of the day
+    pub mjd_utc: f64,
+    /// Planetary 3-hour Kp indices, starting at 0h UTC
+    pub kp: [f64; 8],
+    /// Planetary 3-hour ap indices, starting at 0h UTC
+    pub ap: [f64; 8],
+    /// Daily planetary Ap index
+    pub ap_avg: f64,
+    /// Observed 10.7 cm solar radio flux, in solar flux units
+    pub f107_obs: f64,
+    /// 81-day average of the observed F10.7 centered on this day
+    pub f107_obs_ctr81: f64,
+    /// 81-day average of the observed F10.7 ending on this day
+    pub f107_obs_lst81: f64,
+}
+
+/// Daily solar indices used by the Jacchia-Bowman 2008 model, as published in the `SOLFSMY.TXT` file.
+///
+/// All indices are in solar flux units, and the 81-day averages are centered on the day of the entry.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct SolarIndicesEntry {
+    /// Modified Julian Date in UTC of the entry
+    pub mjd_utc: f64,
+    /// 10.7 cm solar radio flux
+    pub f10: f64,
+    /// 81-day centered average of F10
+    pub f81c: f64,
+    /// Extreme ultraviolet index (26-34 nm)
+    pub s10: f64,
+    /// 81-day centered average of S10
+    pub s81c: f64,
+    /// Mg II core-to-wing ratio index (far ultraviolet)
+    pub m10: f64,
+    /// 81-day centered average of M10
+    pub m81c: f64,
+    /// Mixed solar X-ray and Lyman-alpha index
+    pub y10: f64,
+    /// 81-day centered average of Y10
+    pub y81c: f64,
+}
+
+/// A table of space weather indices used to drive the atmospheric density models, interpolated linearly by epoch.
+///
+/// The daily F10.7 and geomagnetic indices are loaded from the CelesTrak CSV files (e.g. `SW-All.csv` or `SW-Last5Years.csv`),
+/// available at https://celestrak.org/SpaceData/ . The Jacchia-Bowman 2008 solar indices (`SOLFSMY.TXT`) and
+/// temperature corrections (`DTCFILE.TXT`) are optional and may be added to the table.
+#[derive(Clone)]
+pub struct SpaceWeather {
+    entries: Vec<SpaceWeatherEntry>,
+    solar_indices: Vec<SolarIndicesEntry>,
+    /// Hourly exospheric temperature changes due to geomagnetic storms, as (MJD UTC, dTc in Kelvin)
+    dtc: Vec<(f64, f64)>,
+}
+
+/// Returns the index of the entry following the requested MJD and the interpolation fraction from the previous entry,
+/// or None if the MJD is outside of the data
+fn bracket<T>(items: &[T], mjd_utc: f64, mjd_of: impl Fn(&T) -> f64) -> Option<(usize, f64)> {
+    if items.len() < 2 || mjd_utc < mjd_of(&items[0]) || mjd_utc > mjd_of(&items[items.len() - 1]) {
+        return None;
+    }
+    let idx = items
+        .partition_point(|item| mjd_of(item) <= mjd_utc)
+        .clamp(1, items.len() - 1);
+    let (prev, next) = (mjd_of(&items[idx - 1]), mjd_of(&items[idx]));
+    Some((idx, (mjd_utc - prev) / (next - prev)))
+}
+
+/// Returns the MJD UTC of the start of the provided day of year
+fn mjd_of_day_of_year(year: i32, day_of_year: f64) -> f64 {
+    (Epoch::from_gregorian_utc_at_midnight(year, 1, 1) + (day_of_year - 1.0) * Unit::Day)
+        .to_mjd_utc_days()
+}
+
+impl SpaceWeather {
+    /// Builds the table from the provided daily entries, which are sorted by epoch.
+    pub fn from_entries(mut entries: Vec<SpaceWeatherEntry>) -> Result<Self, NyxError> {
+        if entries.len() < 2 {
+            return Err(NyxError::NoInterpolationData(format!(
+                "{} space weather entries is not enough to interpolate",
+                entries.len()
+            )));
+        }
+        entries.sort_by(|a, b| a.mjd_utc.partial_cmp(&b.mjd_utc).unwrap());
+        Ok(Self {
+            entries,
+            solar_indices: Vec::new(),
+            dtc: Vec::new(),
+        })
+    }
+
+    /// Loads a CelesTrak space weather CSV file (e.g. `SW-All.csv`)
+    pub fn from_celestrak_csv_file(path: &str) -> Result<Self, NyxError> {
+        Self::from_celestrak_csv(
+            &read_to_string(path).map_err(|e| NyxError::FileUnreadable(format!("{path}: {e}")))?,
+        )
+    }
+
+    /// Parses the content of a CelesTrak space weather CSV file.
+    ///
+    /// The columns are found from the header, and the Kp indices are stored in tenths in the file (e.g. 33 for Kp = 3.3).
+    /// Days without F10.7 or without any geomagnetic index (e.g. the monthly predictions) are skipped, and the 3-hour indices
+    /// of the daily predictions are set to their daily average.
+    pub fn from_celestrak_csv(content: &str) -> Result<Self, NyxError> {
+        let mut lines = content.lines().enumerate();
+        let columns: HashMap<&str, usize> = match lines.next() {
+            Some((_, header)) => header
+                .split(',')
+                .enumerate()
+                .map(|(idx, name)| (name.trim(), idx))
+                .collect(),
+            None => {
+                return Err(NyxError::LoadingError(
+                    "space weather file is empty".to_string(),
+                ))
+            }
+        };
+        let column = |name: &str| -> Result<usize, NyxError> {
+            columns.get(name).copied().ok_or_else(|| {
+                NyxError::LoadingError(format!("space weather file has no {name} column"))
+            })
+        };
+        let date_col = column("DATE")?;
+        let kp_cols = (1..=8)
+            .map(|i| column(&format!("KP{i}")))
+            .collect::<Result<Vec<_>, _>>()?;
+        let ap_cols = (1..=8)
+            .map(|i| column(&format!("AP{i}")))
+            .collect::<Result<Vec<_>, _>>()?;
+        let ap_avg_col = column("AP_AVG")?;
+        let f107_col = column("F10.7_OBS")?;
+        let ctr81_col = column("F10.7_OBS_CENTER81")?;
+        let lst81_col = column("F10.7_OBS_LAST81")?;
+
+        let mut entries = Vec::new();
+        for (lno, line) in lines {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let fields: Vec<&str> = line.split(',').map(|field| field.trim()).collect();
+            // Parses an optional numeric field
+            let value = |col: usize| -> Result<Option<f64>, NyxError> {
+                match fields.get(col) {
+                    Some(field) if !field.is_empty() => {
+                        field.parse::<f64>().map(Some).map_err(|e| {
+                            NyxError::LoadingError(format!(
+                                "space weather line {}: could not parse `{field}`: {e}",
+                                lno + 1
+                            ))
+                        })
+                    }
+                    _ => Ok(None),
+                }
+            };
+
+            let f107_obs = match value(f107_col)? {
+                Some(f107) => f107,
+                None => continue,
+            };
+            let ap_3h = ap_cols
+                .iter()
+                .map(|col| value(*col))
+                .collect::<Result<Vec<_>, _>>()?;
+            let ap_avg = match value(ap_avg_col)? {
+                Some(ap_avg) => ap_avg,
+                None if ap_3h.iter().all(|ap| ap.is_some()) => {
+                    ap_3h.iter().map(|ap| ap.unwrap()).sum::<f64>() / 8.0
+                }
+                None => continue,
+            };
+            let kp_3h = kp_cols
+                .iter()
+                .map(|col| value(*col))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let date = fields.get(date_col).copied().unwrap_or_default();
+            let ymd = date
+                .split('-')
+                .map(|part| part.parse::<i32>())
+                .collect::<Result<Vec<i32>, _>>();
+            let mjd_utc = match ymd {
+                Ok(ymd) if ymd.len() == 3 => {
+                    Epoch::from_gregorian_utc_at_midnight(ymd[0], ymd[1] as u8, ymd[2] as u8)
+                        .to_mjd_utc_days()
+                }
+                _ => {
+                    return Err(NyxError::LoadingError(format!(
+                        "space weather line {}: invalid date `{date}`",
+                        lno + 1
+                    )))
+                }
+            };
+
+            let mut entry = SpaceWeatherEntry {
+                mjd_utc,
+                ap_avg,
+                f107_obs,
+                f107_obs_ctr81: value(ctr81_col)?.unwrap_or(f107_obs),
+                f107_obs_lst81: value(lst81_col)?.unwrap_or(f107_obs),
+                ..Default::default()
+            };
+            for i in 0..8 {
+                entry.ap[i] = ap_3h[i].unwrap_or(ap_avg);
+                entry.kp[i] = match kp_3h[i] {
+                    Some(kp) => kp / 10.0,
+                    None => ap_to_kp(entry.ap[i]),
+                };
+            }
+            // The centered average is not available at the end of the observations
+            if value(ctr81_col)?.is_none() {
+                entry.f107_obs_ctr81 = entry.f107_obs_lst81;
+            }
+            entries.push(entry);
+        }
+
+        Self::from_entries(entries)
+    }
+
+    /// Adds the Jacchia-Bowman 2008 solar indices from a `SOLFSMY.TXT` file
+    pub fn with_solfsmy_file(self, path: &str) -> Result<Self, NyxError> {
+        self.with_solfsmy(
+            &read_to_string(path).map_err(|e| NyxError::FileUnreadable(format!("{path}: {e}")))?,
+        )
+    }
+
+    /// Adds the Jacchia-Bowman 2008 solar indices from the content of a `SOLFSMY.TXT` file.
+    ///
+    /// Each line is `YYYY DDD JulianDay F10 F81c S10 S81c M10 M81c Y10 Y81c Ssrc`, and comment lines start with `#`.
+    pub fn with_solfsmy(mut self, content: &str) -> Result<Self, NyxError> {
+        let mut solar_indices = Vec::new();
+        for line in content.lines() {
+            if line.trim_start().starts_with('#') {
+                continue;
+            }
+            let fields = line
+                .split_whitespace()
+                .take(11)
+                .map(|field| field.parse::<f64>())
+                .collect::<Result<Vec<f64>, _>>();
+            match fields {
+                Ok(fields) if fields.len() == 11 => solar_indices.push(SolarIndicesEntry {
+                    mjd_utc: fields[2] - 2_400_000.5,
+                    f10: fields[3],
+                    f81c: fields[4],
+                    s10: fields[5],
+                    s81c: fields[6],
+                    m10: fields[7],
+                    m81c: fields[8],
+                    y10: fields[9],
+                    y81c: fields[10],
+                }),
+                _ => continue,
+            }
+        }
+        if solar_indices.len() < 2 {
+            return Err(NyxError::NoInterpolationData(format!(
+                "{} JB2008 solar indices is not enough to interpolate",
+                solar_indices.len()
+            )));
+        }
+        solar_indices.sort_by(|a, b| a.mjd_utc.partial_cmp(&b.mjd_utc).unwrap());
+        self.solar_indices = solar_indices;
+        Ok(self)
+    }
+
+    /// Adds the Jacchia-Bowman 2008 geomagnetic storm temperature changes from a `DTCFILE.TXT` file
+    pub fn with_dtcfile_file(self, path: &str) -> Result<Self, NyxError> {
+        self.with_dtcfile(
+            &read_to_string(path).map_err(|e| NyxError::FileUnreadable(format!("{path}: {e}")))?,
+        )
+    }
+
+    /// Adds the Jacchia-Bowman 2008 geomagnetic storm temperature changes from the content of a `DTCFILE.TXT` file.
+    ///
+    /// Each line is `DTC YYYY DDD` followed by the 24 hourly values in Kelvin, starting at 0h UTC.
+    pub fn with_dtcfile(mut self, content: &str) -> Result<Self, NyxError> {
+        let mut dtc = Vec::new();
+        for line in content.lines() {
+            let mut tokens = line.split_whitespace();
+            if tokens.next() != Some("DTC") {
+                continue;
+            }
+            let fields = tokens
+                .map(|field| field.parse::<f64>())
+                .collect::<Result<Vec<f64>, _>>()
+                .map_err(|e| NyxError::LoadingError(format!("DTC line `{line}`: {e}")))?;
+            if fields.len() < 26 {
+                return Err(NyxError::LoadingError(format!(
+                    "DTC line `{line}` does not have 24 hourly values"
+                )));
+            }
+            let day_start = mjd_of_day_of_year(fields[0] as i32, fields[1]);
+            for (hour, value) in fields[2..26].iter().enumerate() {
+                dtc.push((day_start + hour as f64 / 24.0, *value));
+            }
+        }
+        if dtc.len() < 2 {
+            return Err(NyxError::NoInterpolationData(
+                "no DTC values found".to_string(),
+            ));
+        }
+        dtc.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        self.dtc = dtc;
+        Ok(self)
+    }
+
+    /// Returns all of the daily entries of this table, sorted by epoch
+    pub fn entries(&self) -> &[SpaceWeatherEntry] {
+        &self.entries
+    }
+
+    /// Returns whether the Jacchia-Bowman 2008 solar indices were loaded
+    pub fn has_solar_indices(&self) -> bool {
+        !self.solar_indices.is_empty()
+    }
+
+    /// Returns whether the Jacchia-Bowman 2008 storm temperature changes were loaded
+    pub fn has_dtc(&self) -> bool {
+        !self.dtc.is_empty()
+    }
+
+    /// Returns the daily indices at the provided epoch, linearly interpolated between the two closest days.
+    /// The 3-hour indices are those of the day of the epoch.
+    pub fn at(&self, epoch: Epoch) -> Result<SpaceWeatherEntry, NyxError> {
+        let mjd_utc = epoch.to_mjd_utc_days();
+        let (idx, frac) = bracket(&self.entries, mjd_utc, |entry| entry.mjd_utc)
+            .ok_or_else(|| self.out_of_range(epoch))?;
+        let (prev, next) = (&self.entries[idx - 1], &self.entries[idx]);
+        let interp = |prev_val: f64, next_val: f64| prev_val + frac * (next_val - prev_val);
+        let day = if frac < 1.0 { prev } else { next };
+
+        Ok(SpaceWeatherEntry {
+            mjd_utc,
+            kp: day.kp,
+            ap: day.ap,
+            ap_avg: interp(prev.ap_avg, next.ap_avg),
+            f107_obs: interp(prev.f107_obs, next.f107_obs),
+            f107_obs_ctr81: interp(prev.f107_obs_ctr81, next.f107_obs_ctr81),
+            f107_obs_lst81: interp(prev.f107_obs_lst81, next.f107_obs_lst81),
+        })
+    }
+
+    /// Returns the 3-hour ap index of the interval containing the provided epoch
+    pub fn ap_3h(&self, epoch: Epoch) -> Result<f64, NyxError> {
+        let (entry, slot) = self.three_hour_slot(epoch)?;
+        Ok(entry.ap[slot])
+    }
+
+    /// Returns the 3-hour Kp index of the interval containing the provided epoch
+    pub fn kp_3h(&self, epoch: Epoch) -> Result<f64, NyxError> {
+        let (entry, slot) = self.three_hour_slot(epoch)?;
+        Ok(entry.kp[slot])
+    }
+
+    /// Returns the Jacchia-Bowman 2008 solar indices at the provided epoch, linearly interpolated between the two closest entries.
+    pub fn solar_indices_at(&self, epoch: Epoch) -> Result<SolarIndicesEntry, NyxError> {
+        if !self.has_solar_indices() {
+            return Err(NyxError::NoInterpolationData(
+                "the JB2008 solar indices (SOLFSMY) were not loaded".to_string(),
+            ));
+        }
+        let mjd_utc = epoch.to_mjd_utc_days();
+        let (idx, frac) =
+            bracket(&self.solar_indices, mjd_utc, |entry| entry.mjd_utc).ok_or_else(|| {
+                NyxError::NoInterpolationData(format!(
+                    "{epoch} is outside of the JB2008 solar indices (MJD UTC {} to {})",
+                    self.solar_indices[0].mjd_utc,
+                    self.solar_indices[self.solar_indices.len() - 1].mjd_utc
+                ))
+            })?;
+        let (prev, next) = (&self.solar_indices[idx - 1], &self.solar_indices[idx]);
+        let interp = |prev_val: f64, next_val: f64| prev_val + frac * (next_val - prev_val);
+
+        Ok(SolarIndicesEntry {
+            mjd_utc,
+            f10: interp(prev.f10, next.f10),
+            f81c: interp(prev.f81c, next.f81c),
+            s10: interp(prev.s10, next.s10),
+            s81c: interp(prev.s81c, next.s81c),
+            m10: interp(prev.m10, next.m10),
+            m81c: interp(prev.m81c, next.m81c),
+            y10: interp(prev.y10, next.y10),
+            y81c: interp(prev.y81c, next.y81c),
+        })
+    }
+
+    /// Returns the Jacchia-Bowman 2008 storm temperature change in Kelvin at the provided epoch, linearly interpolated between the hourly values.
+    pub fn dtc_at(&self, epoch: Epoch) -> Result<f64, NyxError> {
+        let mjd_utc = epoch.to_mjd_utc_days();
+        let (idx, frac) = bracket(&self.dtc, mjd_utc, |(mjd, _)| *mjd).ok_or_else(|| {
+            NyxError::NoInterpolationData(format!(
+                "{epoch} is outside of the JB2008 DTC values ({} loaded)",
+                self.dtc.len()
+            ))
+        })?;
+        Ok(self.dtc[idx - 1].1 + frac * (self.dtc[idx].1 - self.dtc[idx - 1].1))
+    }
+
+    /// Returns the day containing the provided epoch and the index of its 3-hour interval
+    fn three_hour_slot(&self, epoch: Epoch) -> Result<(&SpaceWeatherEntry, usize), NyxError> {
+        let mjd_utc = epoch.to_mjd_utc_days();
+        let (idx, frac) = bracket(&self.entries, mjd_utc, |entry| entry.mjd_utc)
+            .ok_or_else(|| self.out_of_range(epoch))?;
+        let entry = if frac < 1.0 {
+            &self.entries[idx - 1]
+        } else {
+            &self.entries[idx]
+        };
+        let slot = (((mjd_utc - entry.mjd_utc) * 8.0).floor() as usize).min(7);
+        Ok((entry, slot))
+    }
+
+    fn out_of_range(&self, epoch: Epoch) -> NyxError {
+        NyxError::NoInterpolationData(format!(
+            "{epoch} is outside of the space weather data (MJD UTC {} to {})",
+            self.entries[0].mjd_utc,
+            self.entries[self.entries.len() - 1].mjd_utc
+        ))
+    }
+}
+
+impl fmt::Debug for SpaceWeather {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "SpaceWeather {{ {} days from MJD UTC {} to {}, {} JB2008 solar indices, {} DTC values }}",
+            self.entries.len(),
+            self.entries[0].mjd_utc,
+            self.entries[self.entries.len() - 1].mjd_utc,
+            self.solar_indices.len(),
+            self.dtc.len()
+        )
+    }
+}
+
+/// Converts a 3-hour ap index to the equivalent Kp index, by interpolation of the standard conversion table
+pub fn ap_to_kp(ap: f64) -> f64 {
+    const AP: [f64; 28] = [
+        0.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 9.0, 12.0, 15.0, 18.0, 22.0, 27.0, 32.0, 39.0, 48.0,
+        56.0, 67.0, 80.0, 94.0, 111.0, 132.0, 154.0, 179.0, 207.0, 236.0, 300.0, 400.0,
+    ];
+    if ap <= 0.0 {
+        return 0.0;
+    } else if ap >= AP[27] {
+        return 9.0;
+    }
+    let idx = AP.partition_point(|val| *val <= ap);
+    let kp_prev = (idx - 1) as f64 / 3.0;
+    kp_prev + (ap - AP[idx - 1]) / (AP[idx] - AP[idx - 1]) / 3.0
+}
+
+#[cfg(test)]
+mod ut_space_weather {
+    use super::*;
+
+    #[test]
+    fn celestrak_csv() {
+        let csv = "\
+DATE,BSRN,ND,KP1,KP2,KP3,KP4,KP5,KP6,KP7,KP8,KP_SUM,AP1,AP2,AP3,AP4,AP5,AP6,AP7,AP8,AP_AVG,CP,C9,ISN,F10.7_OBS,F10.7_ADJ,F10.7_DATA_TYPE,F10.7_OBS_CENTER81,F10.7_OBS_LAST81,F10.7_ADJ_CENTER81,F10.7_ADJ_LAST81
+2023-03-01,2585,13,33,27,30,20,17,23,27,30,207,18,12,15,7,6,9,12,15,12,0.6,3,143,151.4,150.1,OBS,153.2,155.0,152.8,154.5
+2023-03-02,2585,14,20,13,17,10,7,13,23,37,140,7,5,6,4,3,5,9,22,8,0.4,2,124,160.2,158.9,OBS,153.6,155.2,153.0,154.7
+2023-03-03,2585,15,,,,,,,,,,,,,,,,,,10,,,,165.0,,PRD,,,,
+2023-04-01,2586,1,,,,,,,,,,,,,,,,,,,,,,150.0,,PRM,,,,
+";
+        let sw = SpaceWeather::from_celestrak_csv(csv).unwrap();
+        assert_eq!(sw.entries().len(), 3, "monthly predictions are skipped");
+        let first = sw.entries()[0];
+        assert_eq!(first.mjd_utc, 60004.0);
+        assert_eq!(first.kp[0], 3.3);
+        assert_eq!(first.ap, [18.0, 12.0, 15.0, 7.0, 6.0, 9.0, 12.0, 15.0]);
+        assert_eq!(first.f107_obs_ctr81, 153.2);
+        // Daily predictions without 3-hour indices
+        let pred = sw.entries()[2];
+        assert_eq!(pred.ap, [10.0; 8]);
+        assert!(pred.kp[0] > 7.0 / 3.0 && pred.kp[0] < 8.0 / 3.0);
+        assert_eq!(pred.f107_obs_ctr81, 165.0);
+
+        let noon = sw.at(Epoch::from_mjd_utc(60004.5)).unwrap();
+        assert!((noon.f107_obs - (151.4 + 160.2) / 2.0).abs() < 1e-12);
+        assert!((noon.ap_avg - 10.0).abs() < 1e-12);
+        assert_eq!(noon.ap, first.ap);
+        assert_eq!(sw.ap_3h(Epoch::from_mjd_utc(60004.5)).unwrap(), 6.0);
+        assert_eq!(sw.ap_3h(Epoch::from_mjd_utc(60005.99)).unwrap(), 22.0);
+        assert_eq!(sw.kp_3h(Epoch::from_mjd_utc(60005.0)).unwrap(), 2.0);
+
+        assert!(sw.at(Epoch::from_mjd_utc(60003.9)).is_err());
+        assert!(sw.at(Epoch::from_mjd_utc(60006.1)).is_err());
+        assert!(sw.solar_indices_at(Epoch::from_mjd_utc(60004.5)).is_err());
+    }
+
+    #[test]
+    fn jb2008_indices() {
+        let solfsmy = "\
+# SOLFSMY.TXT
+# YYYY DDD   JulianDay  F10   F81c  S10   S81c  M10   M81c  Y10   Y81c  Ssrc
+  2023  60 2460005.0  151.4 153.2 140.1 141.0 145.2 146.3 150.9 151.0 4BH8
+  2023  61 2460006.0  160.2 153.6 142.3 141.2 147.8 146.5 152.1 151.2 4BH8
+";
+        let dtcfile = "\
+DTC 2023  60  50  31  31  31  31  31  31  31  31  31  31  31  31  31  31  31  31  31  31  31  31  31  31  31
+DTC 2023  61  40  40  40  40  40  40  40  40  40  40  40  40  40  40  40  40  40  40  40  40  40  40  40  60
+";
+        let csv = "\
+DATE,KP1,KP2,KP3,KP4,KP5,KP6,KP7,KP8,AP1,AP2,AP3,AP4,AP5,AP6,AP7,AP8,AP_AVG,F10.7_OBS,F10.7_OBS_CENTER81,F10.7_OBS_LAST81
+2023-03-01,33,27,30,20,17,23,27,30,18,12,15,7,6,9,12,15,12,151.4,153.2,155.0
+2023-03-02,20,13,17,10,7,13,23,37,7,5,6,4,3,5,9,22,8,160.2,153.6,155.2
+";
+        let sw = SpaceWeather::from_celestrak_csv(csv)
+            .unwrap()
+            .with_solfsmy(solfsmy)
+            .unwrap()
+            .with_dtcfile(dtcfile)
+            .unwrap();
+        println!("{sw:?}");
+        assert!(sw.has_solar_indices() && sw.has_dtc());
+
+        let indices = sw.solar_indices_at(Epoch::from_mjd_utc(60004.5)).unwrap();
+        assert_eq!(indices.f10, 151.4);
+        assert_eq!(indices.y81c, 151.0);
+        let indices = sw.solar_indices_at(Epoch::from_mjd_utc(60005.0)).unwrap();
+        assert!((indices.s10 - 141.2).abs() < 1e-12);
+
+        assert_eq!(sw.dtc_at(Epoch::from_mjd_utc(60004.0)).unwrap(), 50.0);
+        assert!(
+            (sw.dtc_at(Epoch::from_mjd_utc(60004.0 + 0.5 / 24.0))
+                .unwrap()
+                - 40.5)
+                .abs()
+                < 1e-6
+        );
+        assert!(
+            (sw.dtc_at(Epoch::from_mjd_utc(60005.0 + 22.5 / 24.0))
+                .unwrap()
+                - 50.0)
+                .abs()
+                < 1e-6
+        );
+        assert!(sw.dtc_at(Epoch::from_mjd_utc(60006.0)).is_err());
+    }
+
+    #[test]
+    fn ap_kp_conversion() {
+        assert_eq!(ap_to_kp(0.0), 0.0);
+        assert!((ap_to_kp(15.0) - 3.0).abs() < 1e-12);
+        assert!((ap_to_kp(400.0) - 9.0).abs() < 1e-12);
+        assert!((ap_to_kp(7.0) - 2.0).abs() < 1e-12);
+    }
+}
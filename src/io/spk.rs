@@ -0,0 +1,420 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::daf::{ChebyshevRecords, Daf};
+use crate::linalg::Vector6;
+use crate::polyfit::hermite::hermite_eval;
+use crate::time::Epoch;
+use crate::NyxError;
+
+/// NAIF ID of the J2000 frame, the only SPK orientation supported by Cosm.
+pub const SPK_J2000: i32 = 1;
+
+/// Tolerance on the segment boundaries, to absorb the round-off of the conversion of an Epoch to ephemeris time
+const EPOCH_TOL_S: f64 = 1e-6;
+
+/// The interpolation data of an SPK segment.
+#[derive(Clone, Debug)]
+enum SpkData {
+    /// Types 2 and 3: Chebyshev polynomials over fixed length intervals, position only (type 2) or position and velocity (type 3)
+    Chebyshev {
+        records: ChebyshevRecords,
+        with_velocity: bool,
+    },
+    /// Types 9 and 13: Lagrange (type 9) or Hermite (type 13) interpolation of unequally spaced discrete states
+    Discrete {
+        window_size: usize,
+        epochs: Vec<f64>,
+        states: Vec<f64>,
+        hermite: bool,
+    },
+}
+
+/// A single SPK segment, i.e. the state of a target with respect to a center over a time span.
+#[derive(Clone, Debug)]
+pub struct SpkSegment {
+    /// NAIF ID of the target body
+    pub target: i32,
+    /// NAIF ID of the center body
+    pub center: i32,
+    /// NAIF ID of the orientation frame
+    pub frame: i32,
+    /// SPK data type (2, 3, 9 or 13)
+    pub data_type: i32,
+    /// Start of the validity of this segment in ephemeris time seconds past J2000
+    pub start_et_s: f64,
+    /// End of the validity of this segment in ephemeris time seconds past J2000
+    pub end_et_s: f64,
+    data: SpkData,
+}
+
+impl SpkSegment {
+    /// Returns whether this segment covers the provided ephemeris time
+    pub fn covers(&self, et_s: f64) -> bool {
+        self.start_et_s - EPOCH_TOL_S <= et_s && et_s <= self.end_et_s + EPOCH_TOL_S
+    }
+
+    /// Evaluates this segment at the provided ephemeris time, returning the position (km) and velocity (km/s)
+    pub fn evaluate(&self, et_s: f64) -> Result<Vector6<f64>, NyxError> {
+        if !self.covers(et_s) {
+            return Err(NyxError::NoInterpolationData(format!(
+                "SPK segment {} w.r.t. {} does not cover ET {et_s} s",
+                self.target, self.center
+            )));
+        }
+
+        match &self.data {
+            SpkData::Chebyshev {
+                records,
+                with_velocity,
+            } => {
+                let mut state = Vector6::zeros();
+                if *with_velocity {
+                    for (i, (val, _)) in records.evaluate(et_s, 6).iter().enumerate() {
+                        state[i] = *val;
+                    }
+                } else {
+                    for (i, (pos, vel)) in records.evaluate(et_s, 3).iter().enumerate() {
+                        state[i] = *pos;
+                        state[i + 3] = *vel;
+                    }
+                }
+                Ok(state)
+            }
+            SpkData::Discrete {
+                window_size,
+                epochs,
+                states,
+                hermite,
+            } => {
+                let first = window_start(epochs, *window_size, et_s);
+                let xs = &epochs[first..first + window_size];
+
+                let mut state = Vector6::zeros();
+                for i in 0..3 {
+                    let ys = (first..first + window_size)
+                        .map(|j| states[6 * j + i])
+                        .collect::<Vec<f64>>();
+                    let ydots = (first..first + window_size)
+                        .map(|j| states[6 * j + i + 3])
+                        .collect::<Vec<f64>>();
+
+                    if *hermite {
+                        let (pos, vel) = hermite_eval(xs, &ys, &ydots, et_s)?;
+                        state[i] = pos;
+                        state[i + 3] = vel;
+                    } else {
+                        state[i] = lagrange_eval(xs, &ys, et_s)?;
+                        state[i + 3] = lagrange_eval(xs, &ydots, et_s)?;
+                    }
+                }
+
+                Ok(state)
+            }
+        }
+    }
+}
+
+/// A SPICE SPK file, i.e. a set of segments of ephemeris data.
+///
+/// Supports Chebyshev types 2 and 3, and Lagrange and Hermite types 9 and 13, which cover the JPL DE files and most spacecraft ephemerides.
+/// Refer to https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/req/spk.html for the specification.
+#[derive(Clone, Debug, Default)]
+pub struct Spk {
+    /// All of the supported segments, in the order in which they were loaded
+    pub segments: Vec<SpkSegment>,
+}
+
+impl Spk {
+    /// Loads the SPK from the provided file path (e.g. `de438s.bsp`)
+    pub fn from_file(path: &str) -> Result<Self, NyxError> {
+        Self::from_daf(&Daf::from_file(path)?)
+    }
+
+    /// Parses the provided buffer as an SPK
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Self, NyxError> {
+        Self::from_daf(&Daf::from_bytes(bytes)?)
+    }
+
+    /// Reads all of the supported segments from the provided DAF
+    pub fn from_daf(daf: &Daf) -> Result<Self, NyxError> {
+        if daf.nd != 2 || daf.ni != 6 {
+            return Err(NyxError::LoadingError(format!(
+                "`{}` is not an SPK: ND = {} and NI = {}",
+                daf.id_word, daf.nd, daf.ni
+            )));
+        }
+
+        let mut me = Self::default();
+
+        for summary in daf.summaries()? {
+            let target = summary.integers[0];
+            let center = summary.integers[1];
+            let frame = summary.integers[2];
+            let data_type = summary.integers[3];
+
+            let words = daf.read_doubles(summary.start_addr, summary.end_addr)?;
+
+            let data = match data_type {
+                2 | 3 => SpkData::Chebyshev {
+                    records: ChebyshevRecords::from_words(&words).map_err(|e| {
+                        NyxError::InvalidInterpolationData(format!(
+                            "SPK type {data_type} segment of {target}: {e}"
+                        ))
+                    })?,
+                    with_velocity: data_type == 3,
+                },
+                9 | 13 => {
+                    let n = words.len();
+                    if n < 2 {
+                        return Err(NyxError::LoadingError(format!(
+                            "SPK type {data_type} segment of {target} has {n} words"
+                        )));
+                    }
+                    let num_states = words[n - 1] as usize;
+                    if num_states == 0 || num_states > n {
+                        return Err(NyxError::LoadingError(format!(
+                            "SPK type {data_type} segment of {target} has {} states",
+                            words[n - 1]
+                        )));
+                    }
+                    // Type 9 stores the polynomial degree, type 13 stores the window size minus one.
+                    let window_size = (words[n - 2] as usize).saturating_add(1);
+                    let num_dir = (num_states - 1) / 100;
+                    if 7 * num_states + num_dir + 2 != n {
+                        return Err(NyxError::InvalidInterpolationData(format!(
+                            "SPK type {data_type} segment of {target} has {num_states} states but {n} words"
+                        )));
+                    }
+                    if window_size > num_states {
+                        return Err(NyxError::InvalidInterpolationData(format!(
+                            "SPK type {data_type} segment of {target} has a window of {window_size} but only {num_states} states"
+                        )));
+                    }
+                    SpkData::Discrete {
+                        window_size,
+                        states: words[..6 * num_states].to_vec(),
+                        epochs: words[6 * num_states..7 * num_states].to_vec(),
+                        hermite: data_type == 13,
+                    }
+                }
+                _ => {
+                    warn!("skipping SPK segment of {target} w.r.t. {center}: type {data_type} is not supported");
+                    continue;
+                }
+            };
+
+            me.segments.push(SpkSegment {
+                target,
+                center,
+                frame,
+                data_type,
+                start_et_s: summary.doubles[0],
+                end_et_s: summary.doubles[1],
+                data,
+            });
+        }
+
+        Ok(me)
+    }
+
+    /// Returns the state of the target with respect to the center at the provided epoch, in the frame of the segment.
+    /// As in SPICE, segments loaded last take precedence over those loaded first.
+    pub fn try_state(
+        &self,
+        target: i32,
+        center: i32,
+        epoch: Epoch,
+    ) -> Result<Vector6<f64>, NyxError> {
+        let et_s = epoch.to_et_seconds();
+        match self
+            .segments
+            .iter()
+            .rev()
+            .find(|seg| seg.target == target && seg.center == center && seg.covers(et_s))
+        {
+            Some(seg) => seg.evaluate(et_s),
+            None => Err(NyxError::NoInterpolationData(format!(
+                "no SPK segment of {target} w.r.t. {center} covers {epoch}"
+            ))),
+        }
+    }
+}
+
+/// Returns the index of the first state of the interpolation window for the provided time, using the SPICE windowing rules.
+fn window_start(epochs: &[f64], window_size: usize, et_s: f64) -> usize {
+    // Index of the first epoch strictly greater than the requested time
+    let high = epochs.partition_point(|epoch| *epoch <= et_s);
+
+    let first = if window_size % 2 == 1 {
+        // Odd windows are centered on the closest epoch
+        let near = if high == 0 {
+            0
+        } else if high == epochs.len() || et_s - epochs[high - 1] <= epochs[high] - et_s {
+            high - 1
+        } else {
+            high
+        };
+        near as i64 - ((window_size - 1) / 2) as i64
+    } else {
+        // Even windows are centered on the interval containing the requested time
+        high as i64 - (window_size / 2) as i64
+    };
+
+    first.clamp(0, (epochs.len() - window_size) as i64) as usize
+}
+
+/// Evaluates the Lagrange polynomial through the provided points at `x_eval`
+fn lagrange_eval(xs: &[f64], ys: &[f64], x_eval: f64) -> Result<f64, NyxError> {
+    let mut val = 0.0;
+    for (i, (x_i, y_i)) in xs.iter().zip(ys).enumerate() {
+        let mut basis = 1.0;
+        for (j, x_j) in xs.iter().enumerate() {
+            if i != j {
+                let denom = x_i - x_j;
+                if denom.abs() < f64::EPSILON {
+                    return Err(NyxError::MathDomain(format!(
+                        "duplicate abscissa data: denominator near zero ({denom:e})"
+                    )));
+                }
+                basis *= (x_eval - x_j) / denom;
+            }
+        }
+        val += basis * y_i;
+    }
+    Ok(val)
+}
+
+#[cfg(test)]
+mod ut_spk {
+    use super::*;
+    use crate::io::daf_writer::{build_spk, DafArray};
+
+    #[test]
+    fn spk_type2_chebyshev() {
+        // Two records of 10 s each, with x = 1 + 2 s, y = 3 s^2 (in Chebyshev), z = -4
+        let mut words = Vec::new();
+        for mid in [5.0, 15.0] {
+            words.extend_from_slice(&[mid, 5.0]);
+            words.extend_from_slice(&[1.0, 2.0, 0.0]);
+            words.extend_from_slice(&[0.0, 0.0, 3.0]);
+            words.extend_from_slice(&[-4.0, 0.0, 0.0]);
+        }
+        words.extend_from_slice(&[0.0, 10.0, 11.0, 2.0]);
+
+        let spk =
+            Spk::from_bytes(build_spk(&[DafArray::spk(-10, 399, 2, 0.0, 20.0, words)])).unwrap();
+        assert_eq!(spk.segments.len(), 1);
+
+        for et_s in [0.0, 2.5, 10.0, 17.5, 20.0] {
+            let state = spk
+                .try_state(-10, 399, Epoch::from_et_seconds(et_s))
+                .unwrap();
+            let mid = if et_s < 10.0 { 5.0 } else { 15.0 };
+            let s = (et_s - mid) / 5.0;
+            assert!((state[0] - (1.0 + 2.0 * s)).abs() < 1e-7);
+            assert!((state[1] - 3.0 * (2.0 * s * s - 1.0)).abs() < 1e-7);
+            assert!((state[2] + 4.0).abs() < 1e-12);
+            assert!((state[3] - 2.0 / 5.0).abs() < 1e-7);
+            assert!((state[4] - 12.0 * s / 5.0).abs() < 1e-7);
+            assert!(state[5].abs() < 1e-12);
+        }
+
+        assert!(spk
+            .try_state(-10, 399, Epoch::from_et_seconds(25.0))
+            .is_err());
+        assert!(spk.try_state(-10, 0, Epoch::from_et_seconds(5.0)).is_err());
+    }
+
+    #[test]
+    fn spk_type13_hermite_and_type9_lagrange() {
+        // Circular motion sampled every 10 seconds: x = cos(w t), y = sin(w t)
+        let w = 1e-2;
+        let epochs = (0..20).map(|i| f64::from(i) * 10.0).collect::<Vec<f64>>();
+        let mut words = Vec::new();
+        for t in &epochs {
+            words.extend_from_slice(&[
+                (w * t).cos(),
+                (w * t).sin(),
+                0.5,
+                -w * (w * t).sin(),
+                w * (w * t).cos(),
+                0.0,
+            ]);
+        }
+        words.extend_from_slice(&epochs);
+
+        for (data_type, trailer) in [(13, 7.0), (9, 7.0)] {
+            let mut seg_words = words.clone();
+            seg_words.extend_from_slice(&[trailer, 20.0]);
+            let spk = Spk::from_bytes(build_spk(&[DafArray::spk(
+                -20, 301, data_type, 0.0, 190.0, seg_words,
+            )]))
+            .unwrap();
+
+            for t in [0.0, 33.3, 95.0, 187.1] {
+                let state = spk.try_state(-20, 301, Epoch::from_et_seconds(t)).unwrap();
+                assert!((state[0] - (w * t).cos()).abs() < 1e-9, "type {data_type}");
+                assert!((state[1] - (w * t).sin()).abs() < 1e-9, "type {data_type}");
+                assert!((state[2] - 0.5).abs() < 1e-12, "type {data_type}");
+                assert!(
+                    (state[3] + w * (w * t).sin()).abs() < 1e-10,
+                    "type {data_type}"
+                );
+                assert!(
+                    (state[4] - w * (w * t).cos()).abs() < 1e-10,
+                    "type {data_type}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn spk_invalid_discrete_segments() {
+        for (data_type, words) in [
+            (13, vec![1.0]),
+            (13, vec![3.0, 0.0]),
+            (9, vec![0.0, 0.0, -1.0]),
+            (9, vec![3.0, f64::NAN]),
+            (13, vec![3.0, 1e300]),
+        ] {
+            let bytes = build_spk(&[DafArray::spk(-30, 399, data_type, 0.0, 10.0, words.clone())]);
+            assert!(
+                matches!(Spk::from_bytes(bytes), Err(NyxError::LoadingError(_))),
+                "type {data_type}: {words:?}"
+            );
+        }
+
+        // A single state with a huge window
+        let mut words = vec![0.0; 7];
+        words.extend_from_slice(&[1e300, 1.0]);
+        assert!(
+            Spk::from_bytes(build_spk(&[DafArray::spk(-30, 399, 13, 0.0, 10.0, words)])).is_err()
+        );
+    }
+
+    #[test]
+    fn spk_window_start() {
+        let epochs = [0.0, 1.0, 2.0, 3.0, 4.0, 5.0];
+        assert_eq!(window_start(&epochs, 4, 2.5), 1);
+        assert_eq!(window_start(&epochs, 4, 0.1), 0);
+        assert_eq!(window_start(&epochs, 4, 5.0), 2);
+        assert_eq!(window_start(&epochs, 3, 2.4), 1);
+        assert_eq!(window_start(&epochs, 3, 2.6), 2);
+    }
+}
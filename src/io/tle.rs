@@ -0,0 +1,240 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::time::{Epoch, Unit};
+use crate::NyxError;
+use std::fmt;
+use std::fs::read_to_string;
+use std::str::FromStr;
+
+/// A two-line element set, as published by the 18th Space Defense Squadron (e.g. on CelesTrak or Space-Track).
+///
+/// The elements are SGP4 mean elements in the TEME frame: use `Sgp4` to propagate them.
+/// Format reference: https://celestrak.org/columns/v04n03/
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tle {
+    /// Name of the object, from the optional title line
+    pub name: Option<String>,
+    /// NORAD catalog number
+    pub norad_id: u32,
+    /// Classification (U for unclassified)
+    pub classification: char,
+    /// International designator (launch year, launch number, and piece), may be empty
+    pub intl_designator: String,
+    /// Epoch of the elements (UTC)
+    pub epoch: Epoch,
+    /// First derivative of the mean motion divided by two, in revolutions per day squared
+    pub mean_motion_dot: f64,
+    /// Second derivative of the mean motion divided by six, in revolutions per day cubed
+    pub mean_motion_ddot: f64,
+    /// B* drag term, in inverse Earth radii
+    pub bstar: f64,
+    /// Ephemeris type (always zero in published element sets)
+    pub ephemeris_type: u8,
+    /// Element set number
+    pub element_number: u32,
+    /// Inclination in degrees
+    pub inclination_deg: f64,
+    /// Right ascension of the ascending node in degrees
+    pub raan_deg: f64,
+    /// Eccentricity
+    pub eccentricity: f64,
+    /// Argument of perigee in degrees
+    pub aop_deg: f64,
+    /// Mean anomaly in degrees
+    pub mean_anomaly_deg: f64,
+    /// Mean motion in revolutions per day
+    pub mean_motion_rev_day: f64,
+    /// Revolution number at epoch
+    pub rev_number: u32,
+}
+
+/// Computes the modulo 10 checksum of the first 68 characters of a TLE line: digits count for their value and minus signs for one.
+fn checksum(line: &str) -> u32 {
+    line.chars()
+        .take(68)
+        .map(|c| match c {
+            '-' => 1,
+            _ => c.to_digit(10).unwrap_or(0),
+        })
+        .sum::<u32>()
+        % 10
+}
+
+/// Returns the field between the one-indexed start and end columns (inclusive), trimmed
+fn field(line: &str, start: usize, end: usize) -> &str {
+    line.get(start - 1..end.min(line.len()))
+        .unwrap_or("")
+        .trim()
+}
+
+/// Parses the field between the one-indexed start and end columns (inclusive), with the provided description for errors
+fn parse_field<T: FromStr>(line: &str, start: usize, end: usize, name: &str) -> Result<T, NyxError>
+where
+    T::Err: fmt::Display,
+{
+    let value = field(line, start, end);
+    value
+        .parse::<T>()
+        .map_err(|e| NyxError::LoadingError(format!("TLE {name}: could not parse `{value}`: {e}")))
+}
+
+/// Parses a field with an assumed leading decimal point and an exponent, e.g. ` 28098-4` is 0.28098e-4
+fn parse_exp_field(line: &str, start: usize, end: usize, name: &str) -> Result<f64, NyxError> {
+    let value = field(line, start, end);
+    if value.is_empty() {
+        return Ok(0.0);
+    }
+    let (mantissa, sign) = match value.strip_prefix('-') {
+        Some(rest) => (rest, -1.0),
+        None => (value.strip_prefix('+').unwrap_or(value), 1.0),
+    };
+    let (digits, exponent) = match mantissa.rfind(['-', '+']) {
+        Some(idx) => mantissa.split_at(idx),
+        None => (mantissa, "0"),
+    };
+    let err = |e: &dyn fmt::Display| {
+        NyxError::LoadingError(format!("TLE {name}: could not parse `{value}`: {e}"))
+    };
+    let digits = format!("0.{}", digits.trim());
+    let mantissa = digits.parse::<f64>().map_err(|e| err(&e))?;
+    let exponent = exponent.parse::<i32>().map_err(|e| err(&e))?;
+    Ok(sign * mantissa * 10_f64.powi(exponent))
+}
+
+impl Tle {
+    /// Parses a TLE from its two lines, checking the line numbers, the catalog numbers and the checksums.
+    pub fn from_lines(line1: &str, line2: &str) -> Result<Self, NyxError> {
+        let (line1, line2) = (line1.trim_end(), line2.trim_end());
+        for (num, line) in [('1', line1), ('2', line2)] {
+            if !line.starts_with(num) || line.len() < 68 {
+                return Err(NyxError::LoadingError(format!(
+                    "TLE line {num} is invalid: `{line}`"
+                )));
+            }
+            if let Some(expected) = line.chars().nth(68).and_then(|c| c.to_digit(10)) {
+                if checksum(line) != expected {
+                    return Err(NyxError::LoadingError(format!(
+                        "TLE line {num} checksum is {} but expected {expected}: `{line}`",
+                        checksum(line)
+                    )));
+                }
+            }
+        }
+
+        let norad_id = parse_field(line1, 3, 7, "catalog number")?;
+        if parse_field::<u32>(line2, 3, 7, "catalog number")? != norad_id {
+            return Err(NyxError::LoadingError(format!(
+                "TLE lines are of different objects: `{line1}` and `{line2}`"
+            )));
+        }
+
+        // Two digit years from 57 to 99 are in the twentieth century
+        let year = parse_field::<i32>(line1, 19, 20, "epoch year")?;
+        let year = if year < 57 { 2000 + year } else { 1900 + year };
+        let day_of_year: f64 = parse_field(line1, 21, 32, "epoch day")?;
+        let epoch =
+            Epoch::from_gregorian_utc_at_midnight(year, 1, 1) + (day_of_year - 1.0) * Unit::Day;
+
+        Ok(Self {
+            name: None,
+            norad_id,
+            classification: line1.chars().nth(7).unwrap_or('U'),
+            intl_designator: field(line1, 10, 17).to_string(),
+            epoch,
+            mean_motion_dot: parse_field(line1, 34, 43, "mean motion derivative")?,
+            mean_motion_ddot: parse_exp_field(line1, 45, 52, "mean motion second derivative")?,
+            bstar: parse_exp_field(line1, 54, 61, "B*")?,
+            ephemeris_type: field(line1, 63, 63).parse().unwrap_or(0),
+            element_number: field(line1, 65, 68).parse().unwrap_or(0),
+            inclination_deg: parse_field(line2, 9, 16, "inclination")?,
+            raan_deg: parse_field(line2, 18, 25, "right ascension")?,
+            eccentricity: parse_field::<f64>(line2, 27, 33, "eccentricity")? * 1e-7,
+            aop_deg: parse_field(line2, 35, 42, "argument of perigee")?,
+            mean_anomaly_deg: parse_field(line2, 44, 51, "mean anomaly")?,
+            mean_motion_rev_day: parse_field(line2, 53, 63, "mean motion")?,
+            rev_number: field(line2, 64, 68).parse().unwrap_or(0),
+        })
+    }
+
+    /// Parses all of the TLEs in the provided content, with or without title lines (e.g. a CelesTrak group file).
+    pub fn load_many(content: &str) -> Result<Vec<Self>, NyxError> {
+        let mut tles = Vec::new();
+        let mut name = None;
+        let mut lines = content.lines().filter(|line| !line.trim().is_empty());
+        while let Some(line) = lines.next() {
+            if line.starts_with("1 ") {
+                let line2 = lines.next().ok_or_else(|| {
+                    NyxError::LoadingError(format!("TLE line 2 missing after `{line}`"))
+                })?;
+                let mut tle = Self::from_lines(line, line2)?;
+                tle.name = name.take();
+                tles.push(tle);
+            } else {
+                // Title lines may be prefixed by a zero (three line element sets)
+                let title = line.strip_prefix("0 ").unwrap_or(line).trim();
+                name = Some(title.to_string());
+            }
+        }
+        Ok(tles)
+    }
+
+    /// Loads all of the TLEs of the provided file, cf. `load_many`.
+    pub fn from_file(path: &str) -> Result<Vec<Self>, NyxError> {
+        Self::load_many(
+            &read_to_string(path).map_err(|e| NyxError::FileUnreadable(format!("{path}: {e}")))?,
+        )
+    }
+}
+
+impl FromStr for Tle {
+    type Err = NyxError;
+
+    /// Parses a single TLE, with or without its title line
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut tles = Self::load_many(s)?;
+        match tles.len() {
+            1 => Ok(tles.remove(0)),
+            n => Err(NyxError::LoadingError(format!(
+                "expected exactly one TLE but found {n}"
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for Tle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "TLE {}{} @ {}: n = {} rev/day, e = {}, i = {} deg, Ω = {} deg, ω = {} deg, M = {} deg, B* = {}",
+            self.norad_id,
+            match &self.name {
+                Some(name) => format!(" ({name})"),
+                None => String::new(),
+            },
+            self.epoch,
+            self.mean_motion_rev_day,
+            self.eccentricity,
+            self.inclination_deg,
+            self.raan_deg,
+            self.aop_deg,
+            self.mean_anomaly_deg,
+            self.bstar
+        )
+    }
+}
@@ -52,6 +52,12 @@ pub enum StateParameter {
     Cr,
     /// Declination (deg)
     Declination,
+    /// Delaunay G, i.e. the norm of the orbital momentum (km^2/s)
+    DelaunayG,
+    /// Delaunay H, i.e. the Z component of the orbital momentum (km^2/s)
+    DelaunayH,
+    /// Delaunay L, √(μa) (km^2/s)
+    DelaunayL,
     /// Dry mass (kg)
     DryMass,
     /// The epoch of the state
@@ -62,6 +68,14 @@ pub enum StateParameter {
     Eccentricity,
     /// Specific energy
     Energy,
+    /// Equinoctial h, e sin(ω+Ω), also the g of the modified equinoctial elements (no unit)
+    EquinoctialH,
+    /// Equinoctial k, e cos(ω+Ω), also the f of the modified equinoctial elements (no unit)
+    EquinoctialK,
+    /// Equinoctial p, tan(i/2) sin Ω, also the k of the modified equinoctial elements (no unit)
+    EquinoctialP,
+    /// Equinoctial q, tan(i/2) cos Ω, also the h of the modified equinoctial elements (no unit)
+    EquinoctialQ,
     /// Flight path angle (deg)
     FlightPathAngle,
     /// fuel mass in kilograms
@@ -90,6 +104,20 @@ pub enum StateParameter {
     Isp,
     /// Mean anomaly (deg)
     MeanAnomaly,
+    /// Brouwer-Lyddane mean argument of latitude (deg)
+    MeanAoL,
+    /// Brouwer-Lyddane mean argument of periapse (deg)
+    MeanAoP,
+    /// Brouwer-Lyddane mean eccentricity (no unit)
+    MeanEccentricity,
+    /// Brouwer-Lyddane mean inclination (deg)
+    MeanInclination,
+    /// Mean longitude, M+ω+Ω (deg)
+    MeanLongitude,
+    /// Brouwer-Lyddane mean right ascension of the ascending node (deg)
+    MeanRAAN,
+    /// Brouwer-Lyddane mean semi major axis (km)
+    MeanSMA,
     /// Periapsis, shortcut for TA == 0.0
     Periapsis,
     /// Radius of periapse (km)
@@ -114,6 +142,8 @@ pub enum StateParameter {
     TrueAnomaly,
     /// True longitude
     TrueLongitude,
+    /// Azimuth of the velocity in the local horizontal plane, from north towards east (deg)
+    VelocityAzimuth,
     /// Velocity declination (deg)
     VelocityDeclination,
     /// Norm of the velocity vector (km/s)
@@ -137,7 +167,12 @@ impl StateParameter {
     /// Returns the default event finding precision in the unit of that parameter
     pub fn default_event_precision(&self) -> f64 {
         match self {
-            Self::Eccentricity => 1e-5,
+            Self::Eccentricity
+            | Self::MeanEccentricity
+            | Self::EquinoctialH
+            | Self::EquinoctialK
+            | Self::EquinoctialP
+            | Self::EquinoctialQ => 1e-5,
             // Non anomaly angles
             Self::AoL
             | Self::AoP
@@ -146,15 +181,21 @@ impl StateParameter {
             | Self::GeodeticLongitude
             | Self::FlightPathAngle
             | Self::Inclination
+            | Self::MeanAoP
+            | Self::MeanInclination
+            | Self::MeanRAAN
             | Self::RightAscension
             | Self::RAAN
             | Self::TrueLongitude
+            | Self::VelocityAzimuth
             | Self::VelocityDeclination => 1e-1,
 
             // Anomaly angles
             Self::Apoapsis
             | Self::Periapsis
             | Self::MeanAnomaly
+            | Self::MeanAoL
+            | Self::MeanLongitude
             | Self::EccentricAnomaly
             | Self::HyperbolicAnomaly
             | Self::TrueAnomaly => 1e-3,
@@ -168,6 +209,7 @@ impl StateParameter {
             | Self::HX
             | Self::HY
             | Self::HZ
+            | Self::MeanSMA
             | Self::PeriapsisRadius
             | Self::Rmag
             | Self::SemiParameter
@@ -181,7 +223,7 @@ impl StateParameter {
             Self::C3 | Self::VX | Self::VY | Self::VZ | Self::Vmag => 1e-3,
 
             // Special
-            Self::Energy => 1e-3,
+            Self::Energy | Self::DelaunayG | Self::DelaunayH | Self::DelaunayL => 1e-3,
             Self::DryMass | Self::FuelMass => 1e-3,
             Self::Period => 1e-1,
             _ => unimplemented!("{self} cannot be used for event finding"),
@@ -198,6 +240,19 @@ impl StateParameter {
         !self.is_for_spacecraft() && !matches!(self, Self::Apoapsis | Self::Periapsis | Self::Epoch)
     }
 
+    /// Returns whether this parameter is a (Brouwer-Lyddane) mean orbital element
+    pub const fn is_mean_element(&self) -> bool {
+        matches!(
+            &self,
+            Self::MeanAoL
+                | Self::MeanAoP
+                | Self::MeanEccentricity
+                | Self::MeanInclination
+                | Self::MeanRAAN
+                | Self::MeanSMA
+        )
+    }
+
     /// Returns whether this parameter is only applicable to a spacecraft state
     pub const fn is_for_spacecraft(&self) -> bool {
         matches!(
@@ -225,10 +280,16 @@ impl StateParameter {
             | Self::RightAscension
             | Self::RAAN
             | Self::TrueLongitude
+            | Self::VelocityAzimuth
             | Self::VelocityDeclination
             | Self::Apoapsis
             | Self::Periapsis
             | Self::MeanAnomaly
+            | Self::MeanAoL
+            | Self::MeanAoP
+            | Self::MeanInclination
+            | Self::MeanRAAN
+            | Self::MeanLongitude
             | Self::EccentricAnomaly
             | Self::HyperbolicAnomaly
             | Self::TrueAnomaly => "deg",
@@ -242,6 +303,7 @@ impl StateParameter {
             | Self::HX
             | Self::HY
             | Self::HZ
+            | Self::MeanSMA
             | Self::PeriapsisRadius
             | Self::Rmag
             | Self::SemiParameter
@@ -255,6 +317,7 @@ impl StateParameter {
             Self::VX | Self::VY | Self::VZ | Self::Vmag => "km/s",
 
             Self::C3 | Self::Energy => "km^2/s^2",
+            Self::DelaunayG | Self::DelaunayH | Self::DelaunayL => "km^2/s",
 
             Self::DryMass | Self::FuelMass => "kg",
             Self::Isp => "isp",
@@ -322,11 +385,18 @@ impl FromStr for StateParameter {
             "cd" => Ok(Self::Cd),
             "cr" => Ok(Self::Cr),
             "declin" => Ok(Self::Declination),
+            "delaunay_g" => Ok(Self::DelaunayG),
+            "delaunay_h" => Ok(Self::DelaunayH),
+            "delaunay_l" => Ok(Self::DelaunayL),
             "dry_mass" => Ok(Self::DryMass),
             "apoapsis_radius" => Ok(Self::ApoapsisRadius),
             "ea" => Ok(Self::EccentricAnomaly),
             "ecc" => Ok(Self::Eccentricity),
             "energy" => Ok(Self::Energy),
+            "equinoctial_h" => Ok(Self::EquinoctialH),
+            "equinoctial_k" => Ok(Self::EquinoctialK),
+            "equinoctial_p" => Ok(Self::EquinoctialP),
+            "equinoctial_q" => Ok(Self::EquinoctialQ),
             "fpa" => Ok(Self::FlightPathAngle),
             "fuel_mass" => Ok(Self::FuelMass),
             "guidance_mode" | "mode" => Ok(Self::GuidanceMode),
@@ -341,6 +411,13 @@ impl FromStr for StateParameter {
             "inc" => Ok(Self::Inclination),
             "isp" => Ok(Self::Isp),
             "ma" => Ok(Self::MeanAnomaly),
+            "mean_aol" => Ok(Self::MeanAoL),
+            "mean_aop" => Ok(Self::MeanAoP),
+            "mean_ecc" => Ok(Self::MeanEccentricity),
+            "mean_inc" => Ok(Self::MeanInclination),
+            "mean_longitude" => Ok(Self::MeanLongitude),
+            "mean_raan" => Ok(Self::MeanRAAN),
+            "mean_sma" => Ok(Self::MeanSMA),
             "periapsis_radius" => Ok(Self::PeriapsisRadius),
             "period" => Ok(Self::Period),
             "right_asc" => Ok(Self::RightAscension),
@@ -352,6 +429,7 @@ impl FromStr for StateParameter {
             "ta" => Ok(Self::TrueAnomaly),
             "tlong" => Ok(Self::TrueLongitude),
             "thrust" => Ok(Self::Thrust),
+            "vazimuth" => Ok(Self::VelocityAzimuth),
             "vdeclin" => Ok(Self::VelocityDeclination),
             "vmag" => Ok(Self::Vmag),
             "x" => Ok(Self::X),
@@ -381,12 +459,19 @@ impl fmt::Display for StateParameter {
             Self::Cd => "cd",
             Self::Cr => "cr",
             Self::Declination => "declin",
+            Self::DelaunayG => "delaunay_g",
+            Self::DelaunayH => "delaunay_h",
+            Self::DelaunayL => "delaunay_l",
             Self::DryMass => "dry_mass",
             Self::Epoch => "epoch",
             Self::ApoapsisRadius => "apoapsis_radius",
             Self::EccentricAnomaly => "ea",
             Self::Eccentricity => "ecc",
             Self::Energy => "energy",
+            Self::EquinoctialH => "equinoctial_h",
+            Self::EquinoctialK => "equinoctial_k",
+            Self::EquinoctialP => "equinoctial_p",
+            Self::EquinoctialQ => "equinoctial_q",
             Self::FlightPathAngle => "fpa",
             Self::FuelMass => "fuel_mass",
             Self::GuidanceMode => "guidance_mode",
@@ -401,6 +486,13 @@ impl fmt::Display for StateParameter {
             Self::Inclination => "inc",
             Self::Isp => "isp",
             Self::MeanAnomaly => "ma",
+            Self::MeanAoL => "mean_aol",
+            Self::MeanAoP => "mean_aop",
+            Self::MeanEccentricity => "mean_ecc",
+            Self::MeanInclination => "mean_inc",
+            Self::MeanLongitude => "mean_longitude",
+            Self::MeanRAAN => "mean_raan",
+            Self::MeanSMA => "mean_sma",
             Self::PeriapsisRadius => "periapsis_radius",
             Self::Period => "period",
             Self::RightAscension => "right_asc",
@@ -412,6 +504,7 @@ impl fmt::Display for StateParameter {
             Self::Thrust => "thrust",
             Self::TrueAnomaly => "ta",
             Self::TrueLongitude => "tlong",
+            Self::VelocityAzimuth => "vazimuth",
             Self::VelocityDeclination => "vdeclin",
             Self::Vmag => "vmag",
             Self::X => "x",
@@ -448,11 +541,18 @@ mod ut_state_param {
             StateParameter::Cd,
             StateParameter::Cr,
             StateParameter::Declination,
+            StateParameter::DelaunayG,
+            StateParameter::DelaunayH,
+            StateParameter::DelaunayL,
             StateParameter::DryMass,
             StateParameter::ApoapsisRadius,
             StateParameter::EccentricAnomaly,
             StateParameter::Eccentricity,
             StateParameter::Energy,
+            StateParameter::EquinoctialH,
+            StateParameter::EquinoctialK,
+            StateParameter::EquinoctialP,
+            StateParameter::EquinoctialQ,
             StateParameter::FlightPathAngle,
             StateParameter::FuelMass,
             StateParameter::GuidanceMode,
@@ -467,6 +567,13 @@ mod ut_state_param {
             StateParameter::Inclination,
             StateParameter::Isp,
             StateParameter::MeanAnomaly,
+            StateParameter::MeanAoL,
+            StateParameter::MeanAoP,
+            StateParameter::MeanEccentricity,
+            StateParameter::MeanInclination,
+            StateParameter::MeanLongitude,
+            StateParameter::MeanRAAN,
+            StateParameter::MeanSMA,
             StateParameter::PeriapsisRadius,
             StateParameter::Period,
             StateParameter::RightAscension,
@@ -478,6 +585,7 @@ mod ut_state_param {
             StateParameter::Thrust,
             StateParameter::TrueAnomaly,
             StateParameter::TrueLongitude,
+            StateParameter::VelocityAzimuth,
             StateParameter::VelocityDeclination,
             StateParameter::Vmag,
             StateParameter::X,
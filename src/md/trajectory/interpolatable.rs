@@ -19,7 +19,7 @@
 pub(crate) const INTERPOLATION_SAMPLES: usize = 13;
 
 use super::StateParameter;
-use crate::cosmic::Frame;
+use crate::cosmic::{Attitude, Frame};
 use crate::linalg::allocator::Allocator;
 use crate::linalg::DefaultAllocator;
 use crate::polyfit::hermite::hermite_eval;
@@ -124,6 +124,7 @@ impl Interpolatable for Orbit {
             .filter(|p| {
                 p.is_orbital()
                     && !p.is_b_plane()
+                    && !p.is_mean_element()
                     && !matches!(
                         p,
                         StateParameter::X
@@ -178,6 +179,27 @@ impl Interpolatable for Spacecraft {
         me.fuel_mass_kg += fuel_kg_dt
             * (epoch.to_tdb_seconds() - states.first().unwrap().epoch().to_tdb_seconds());
 
+        // The attitude is interpolated between the two states bracketing the epoch: slerp for the orientation, linear for the rates
+        if self.attitude.is_some() && states.len() > 1 {
+            let after_idx = states
+                .iter()
+                .position(|sc| sc.epoch() >= epoch)
+                .unwrap_or(states.len() - 1)
+                .max(1);
+            let (before, after) = (&states[after_idx - 1], &states[after_idx]);
+            if let (Some(att_before), Some(att_after)) = (before.attitude, after.attitude) {
+                let t = ((epoch - before.epoch()).to_seconds()
+                    / (after.epoch() - before.epoch()).to_seconds())
+                .clamp(0.0, 1.0);
+                let q = att_before
+                    .q
+                    .try_slerp(&att_after.q, t, 1e-12)
+                    .unwrap_or(att_before.q);
+                let omega = att_before.omega_rad_s.lerp(&att_after.omega_rad_s, t);
+                me.attitude = Some(Attitude::new(q, omega));
+            }
+        }
+
         Ok(me)
     }
 
@@ -0,0 +1,405 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::io::watermark::pq_writer;
+use crate::io::ExportCfg;
+use crate::time::{Duration, Epoch};
+use arrow::array::{Array, Float64Builder, StringBuilder};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::record_batch::RecordBatch;
+use parquet::arrow::ArrowWriter;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// A time interval, from its start (included) to its end (excluded)
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Interval {
+    pub start: Epoch,
+    pub end: Epoch,
+}
+
+impl Interval {
+    pub fn new(start: Epoch, end: Epoch) -> Self {
+        Self { start, end }
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.end - self.start
+    }
+
+    /// Returns whether the provided epoch is within this interval
+    pub fn contains(&self, epoch: Epoch) -> bool {
+        self.start <= epoch && epoch < self.end
+    }
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} to {} ({})", self.start, self.end, self.duration())
+    }
+}
+
+/// A named set of sorted and disjoint intervals within a domain, e.g. all of the eclipses of a trajectory.
+///
+/// The boolean operations (union, intersection, complement) return new sets, named after the operation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IntervalSet {
+    /// Name of this set, used as the type of its intervals in the reports
+    pub name: String,
+    /// Interval in which this set is defined, e.g. the span of the trajectory it was computed from
+    pub domain: Interval,
+    intervals: Vec<Interval>,
+}
+
+impl IntervalSet {
+    /// Creates a new set from the provided intervals, which are clipped to the domain, sorted, and merged if they overlap.
+    pub fn new(name: String, domain: Interval, intervals: Vec<Interval>) -> Self {
+        let mut intervals: Vec<Interval> = intervals
+            .into_iter()
+            .map(|interval| {
+                Interval::new(
+                    interval.start.max(domain.start),
+                    interval.end.min(domain.end),
+                )
+            })
+            .filter(|interval| interval.start < interval.end)
+            .collect();
+        intervals.sort_by_key(|i| i.start);
+
+        let mut merged: Vec<Interval> = Vec::with_capacity(intervals.len());
+        for interval in intervals {
+            match merged.last_mut() {
+                Some(last) if interval.start <= last.end => last.end = last.end.max(interval.end),
+                _ => merged.push(interval),
+            }
+        }
+
+        Self {
+            name,
+            domain,
+            intervals: merged,
+        }
+    }
+
+    /// Returns a copy of this set with the provided name
+    pub fn with_name(mut self, name: String) -> Self {
+        self.name = name;
+        self
+    }
+
+    /// Returns the sorted and disjoint intervals of this set
+    pub fn intervals(&self) -> &[Interval] {
+        &self.intervals
+    }
+
+    pub fn len(&self) -> usize {
+        self.intervals.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// Returns whether the provided epoch is in any of the intervals of this set
+    pub fn contains(&self, epoch: Epoch) -> bool {
+        self.intervals
+            .iter()
+            .any(|interval| interval.contains(epoch))
+    }
+
+    /// Returns the sum of the durations of all of the intervals
+    pub fn total_duration(&self) -> Duration {
+        self.intervals
+            .iter()
+            .fold(Duration::ZERO, |total, interval| {
+                total + interval.duration()
+            })
+    }
+
+    /// Returns the intervals which are in either set, in the union of both domains
+    pub fn union(&self, other: &Self) -> Self {
+        Self::new(
+            format!("{} or {}", self.name, other.name),
+            Interval::new(
+                self.domain.start.min(other.domain.start),
+                self.domain.end.max(other.domain.end),
+            ),
+            self.intervals
+                .iter()
+                .chain(other.intervals.iter())
+                .copied()
+                .collect(),
+        )
+    }
+
+    /// Returns the intervals which are in both sets, in the intersection of both domains
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut intervals = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < self.intervals.len() && j < other.intervals.len() {
+            let (a, b) = (self.intervals[i], other.intervals[j]);
+            let start = a.start.max(b.start);
+            let end = a.end.min(b.end);
+            if start < end {
+                intervals.push(Interval::new(start, end));
+            }
+            // Move on from whichever interval ends first
+            if a.end < b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+
+        let start = self.domain.start.max(other.domain.start);
+        Self::new(
+            format!("{} and {}", self.name, other.name),
+            Interval::new(start, self.domain.end.min(other.domain.end).max(start)),
+            intervals,
+        )
+    }
+
+    /// Returns the intervals of the domain which are not in this set
+    pub fn complement(&self) -> Self {
+        let mut intervals = Vec::with_capacity(self.intervals.len() + 1);
+        let mut start = self.domain.start;
+        for interval in &self.intervals {
+            intervals.push(Interval::new(start, interval.start));
+            start = interval.end;
+        }
+        intervals.push(Interval::new(start, self.domain.end));
+
+        Self::new(format!("not {}", self.name), self.domain, intervals)
+    }
+
+    /// Returns the intervals of this set which are not in the other set
+    pub fn difference(&self, other: &Self) -> Self {
+        let outside =
+            Self::new(other.name.clone(), self.domain, other.intervals.clone()).complement();
+        let mut diff = self.intersection(&outside);
+        diff.name = format!("{} and not {}", self.name, other.name);
+        diff.domain = self.domain;
+        diff
+    }
+
+    /// Store these intervals to a parquet file, with one row per interval.
+    pub fn to_parquet<P: AsRef<Path>>(
+        &self,
+        path: P,
+        cfg: ExportCfg,
+    ) -> Result<PathBuf, Box<dyn Error>> {
+        intervals_to_parquet(&[self], path, cfg)
+    }
+}
+
+impl fmt::Display for IntervalSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} intervals for a total of {} between {} and {}",
+            self.name,
+            self.len(),
+            self.total_duration(),
+            self.domain.start,
+            self.domain.end
+        )?;
+        for interval in &self.intervals {
+            write!(f, "\n\t{interval}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Store the intervals of all of the provided sets to a single parquet file, sorted by their start epoch, where the type of each interval is the name of its set.
+///
+/// The start and end epochs of the export configuration, if set, only keep the intervals which overlap them.
+pub fn intervals_to_parquet<P: AsRef<Path>>(
+    sets: &[&IntervalSet],
+    path: P,
+    cfg: ExportCfg,
+) -> Result<PathBuf, Box<dyn Error>> {
+    let path_buf = cfg.actual_path(path);
+
+    if cfg.step.is_some() {
+        warn!("The `step` parameter in the export is not supported for intervals.");
+    }
+
+    if cfg.fields.is_some() {
+        warn!("The `fields` parameter in the export is not supported for intervals.");
+    }
+
+    let mut rows: Vec<(&Interval, &str)> = sets
+        .iter()
+        .flat_map(|set| {
+            set.intervals()
+                .iter()
+                .map(|interval| (interval, set.name.as_str()))
+        })
+        .filter(|(interval, _)| {
+            cfg.start_epoch.is_none_or(|start| interval.end > start)
+                && cfg.end_epoch.is_none_or(|end| interval.start < end)
+        })
+        .collect();
+    rows.sort_by_key(|r| r.0.start);
+
+    // Build the schema
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("Start:Gregorian UTC", DataType::Utf8, false),
+        Field::new("End:Gregorian UTC", DataType::Utf8, false),
+        Field::new("Start:TAI (s)", DataType::Float64, false),
+        Field::new("End:TAI (s)", DataType::Float64, false),
+        Field::new("Duration (s)", DataType::Float64, false),
+        Field::new("Type", DataType::Utf8, false),
+    ]));
+
+    let mut utc_start = StringBuilder::new();
+    let mut utc_end = StringBuilder::new();
+    let mut tai_start = Float64Builder::new();
+    let mut tai_end = Float64Builder::new();
+    let mut duration = Float64Builder::new();
+    let mut kind = StringBuilder::new();
+    for (interval, name) in &rows {
+        utc_start.append_value(format!("{}", interval.start));
+        utc_end.append_value(format!("{}", interval.end));
+        tai_start.append_value(interval.start.to_tai_seconds());
+        tai_end.append_value(interval.end.to_tai_seconds());
+        duration.append_value(interval.duration().to_seconds());
+        kind.append_value(name);
+    }
+
+    let record: Vec<Arc<dyn Array>> = vec![
+        Arc::new(utc_start.finish()),
+        Arc::new(utc_end.finish()),
+        Arc::new(tai_start.finish()),
+        Arc::new(tai_end.finish()),
+        Arc::new(duration.finish()),
+        Arc::new(kind.finish()),
+    ];
+
+    let mut metadata = HashMap::new();
+    metadata.insert("Purpose".to_string(), "Intervals".to_string());
+    if let Some(add_meta) = cfg.metadata {
+        for (k, v) in add_meta {
+            metadata.insert(k, v);
+        }
+    }
+
+    let props = pq_writer(Some(metadata));
+
+    let file = File::create(&path_buf)?;
+    let mut writer = ArrowWriter::try_new(file, schema.clone(), props).unwrap();
+
+    let batch = RecordBatch::try_new(schema, record)?;
+    writer.write(&batch)?;
+    writer.close()?;
+
+    info!("Intervals written to {}", path_buf.display());
+
+    Ok(path_buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::Unit;
+
+    #[test]
+    fn interval_algebra() {
+        let t0 = Epoch::from_gregorian_utc_at_midnight(2023, 1, 1);
+        let at = |minutes: i64| t0 + minutes * Unit::Minute;
+        let domain = Interval::new(at(0), at(100));
+
+        // Overlapping and touching intervals are merged, and everything is clipped to the domain
+        let a = IntervalSet::new(
+            "A".to_string(),
+            domain,
+            vec![
+                Interval::new(at(50), at(60)),
+                Interval::new(at(-10), at(10)),
+                Interval::new(at(55), at(70)),
+                Interval::new(at(70), at(75)),
+                Interval::new(at(90), at(120)),
+            ],
+        );
+        assert_eq!(
+            a.intervals(),
+            &[
+                Interval::new(at(0), at(10)),
+                Interval::new(at(50), at(75)),
+                Interval::new(at(90), at(100)),
+            ]
+        );
+        assert_eq!(a.total_duration(), 45 * Unit::Minute);
+        assert!(a.contains(at(60)));
+        assert!(!a.contains(at(75)));
+
+        let b = IntervalSet::new(
+            "B".to_string(),
+            domain,
+            vec![Interval::new(at(5), at(55)), Interval::new(at(80), at(95))],
+        );
+
+        let union = a.union(&b);
+        assert_eq!(union.name, "A or B");
+        assert_eq!(
+            union.intervals(),
+            &[Interval::new(at(0), at(75)), Interval::new(at(80), at(100))]
+        );
+
+        let inter = a.intersection(&b);
+        assert_eq!(
+            inter.intervals(),
+            &[
+                Interval::new(at(5), at(10)),
+                Interval::new(at(50), at(55)),
+                Interval::new(at(90), at(95)),
+            ]
+        );
+
+        let not_a = a.complement();
+        assert_eq!(
+            not_a.intervals(),
+            &[Interval::new(at(10), at(50)), Interval::new(at(75), at(90))]
+        );
+        assert_eq!(not_a.complement().intervals(), a.intervals());
+        assert_eq!(
+            a.total_duration() + not_a.total_duration(),
+            domain.duration()
+        );
+
+        let diff = a.difference(&b);
+        assert_eq!(diff.name, "A and not B");
+        assert_eq!(
+            diff.intervals(),
+            &[
+                Interval::new(at(0), at(5)),
+                Interval::new(at(55), at(75)),
+                Interval::new(at(95), at(100)),
+            ]
+        );
+        // De Morgan
+        assert_eq!(
+            a.union(&b).complement().intervals(),
+            not_a.intersection(&b.complement()).intervals()
+        );
+    }
+}
@@ -17,6 +17,7 @@
 */
 
 mod interpolatable;
+mod interval;
 mod orbit_traj;
 mod sc_traj;
 mod traj;
@@ -24,6 +25,7 @@ mod traj_it;
 
 pub use interpolatable::Interpolatable;
 pub(crate) use interpolatable::INTERPOLATION_SAMPLES;
+pub use interval::{intervals_to_parquet, Interval, IntervalSet};
 pub use traj::Traj;
 
 pub use crate::io::ExportCfg;
@@ -18,7 +18,7 @@
 
 use super::TrajError;
 use super::{ExportCfg, Traj};
-use crate::cosmic::{Cosm, Frame, Orbit};
+use crate::cosmic::{Cosm, Frame, Orbit, SurfacePoint};
 use crate::errors::NyxError;
 use crate::io::watermark::prj_name_ver;
 use crate::md::prelude::StateParameter;
@@ -60,6 +60,28 @@ impl Traj<Orbit> {
         Ok(traj)
     }
 
+    /// Converts this trajectory into the provided topocentric frame (SEZ, ENU or NED) of a surface point.
+    /// Each state is converted individually, so the same aliasing warning as `to_frame` applies.
+    pub fn to_topocentric_frame(
+        &self,
+        point: &SurfacePoint,
+        topo_frame: Frame,
+        cosm: Arc<Cosm>,
+    ) -> Result<Self, NyxError> {
+        if self.states.is_empty() {
+            return Err(NyxError::Trajectory(TrajError::CreationError(
+                "No trajectory to convert".to_string(),
+            )));
+        }
+        let mut traj = Self::new();
+        for state in &self.states {
+            traj.states
+                .push(point.try_frame_chg(state, topo_frame, &cosm)?);
+        }
+        traj.finalize();
+        Ok(traj)
+    }
+
     /// Exports this trajectory to the provided filename in parquet format with only the epoch, the geodetic latitude, longitude, and height at one state per minute.
     /// Must provide a body fixed frame to correctly compute the latitude and longitude.
     #[allow(clippy::identity_op)]
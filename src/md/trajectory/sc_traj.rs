@@ -18,7 +18,7 @@
 
 use super::TrajError;
 use super::{ExportCfg, Traj};
-use crate::cosmic::{Cosm, Frame, Orbit, Spacecraft};
+use crate::cosmic::{Cosm, Frame, Orbit, Spacecraft, SurfacePoint};
 use crate::errors::NyxError;
 use crate::md::prelude::StateParameter;
 use crate::md::EventEvaluator;
@@ -55,6 +55,30 @@ impl Traj<Spacecraft> {
         Ok(traj)
     }
 
+    /// Converts this trajectory into the provided topocentric frame (SEZ, ENU or NED) of a surface point
+    pub fn to_topocentric_frame(
+        &self,
+        point: &SurfacePoint,
+        topo_frame: Frame,
+        cosm: Arc<Cosm>,
+    ) -> Result<Self, NyxError> {
+        if self.states.is_empty() {
+            return Err(NyxError::Trajectory(TrajError::CreationError(
+                "No trajectory to convert".to_string(),
+            )));
+        }
+        let mut traj = Self::new();
+        for state in &self.states {
+            traj.states.push(state.with_orbit(point.try_frame_chg(
+                &state.orbit,
+                topo_frame,
+                &cosm,
+            )?));
+        }
+        traj.finalize();
+        Ok(traj)
+    }
+
     /// A shortcut to `to_parquet_with_cfg`
     pub fn to_parquet_with_step<P: AsRef<Path>>(
         &self,
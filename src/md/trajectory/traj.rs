@@ -16,6 +16,7 @@
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
+use super::interval::{Interval, IntervalSet};
 use super::traj_it::TrajIterator;
 use super::{ExportCfg, INTERPOLATION_SAMPLES};
 use super::{Interpolatable, TrajError};
@@ -418,6 +419,51 @@ where
         Ok((min_state, max_state))
     }
 
+    /// Find all of the intervals during which the evaluation of the event is positive, e.g. the visibility of a ground station.
+    ///
+    /// Unlike `find_all`, the trajectory is sampled with the provided step, which must be shorter than the shortest interval and the shortest gap between intervals.
+    /// Each change of sign is then found with a Brent solver. Intervals which are ongoing at the start or at the end of the trajectory are truncated to its bounds.
+    /// The returned set is named after the event.
+    pub fn find_intervals<E>(&self, event: &E, step: Duration) -> Result<IntervalSet, NyxError>
+    where
+        E: EventEvaluator<S>,
+    {
+        let start_epoch = self.first().epoch();
+        let end_epoch = self.last().epoch();
+        let mut epochs: Vec<Epoch> = TimeSeries::inclusive(start_epoch, end_epoch, step).collect();
+        if epochs.last() != Some(&end_epoch) {
+            epochs.push(end_epoch);
+        }
+
+        let mut intervals = Vec::new();
+        let mut prev_epoch = epochs[0];
+        let mut prev_inside = event.eval(&self.at(prev_epoch)?) > 0.0;
+        let mut entry = if prev_inside { Some(prev_epoch) } else { None };
+        for epoch in epochs.iter().skip(1) {
+            let inside = event.eval(&self.at(*epoch)?) > 0.0;
+            if inside != prev_inside {
+                let crossing = self.find_bracketed(prev_epoch, *epoch, event)?.epoch();
+                match entry.take() {
+                    Some(entry) => intervals.push(Interval::new(entry, crossing)),
+                    None => entry = Some(crossing),
+                }
+            }
+            prev_epoch = *epoch;
+            prev_inside = inside;
+        }
+        if let Some(entry) = entry {
+            intervals.push(Interval::new(entry, end_epoch));
+        }
+
+        let set = IntervalSet::new(
+            format!("{event}"),
+            Interval::new(start_epoch, end_epoch),
+            intervals,
+        );
+        info!("{set}");
+        Ok(set)
+    }
+
     /// Store this trajectory arc to a parquet file with the default configuration (depends on the state type, search for `export_params` in the documentation for details).
     pub fn to_parquet_simple<P: AsRef<Path>>(&self, path: P) -> Result<PathBuf, Box<dyn Error>> {
         self.to_parquet(path, None, ExportCfg::default())
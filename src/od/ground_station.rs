@@ -19,11 +19,14 @@
 use super::msr::RangeDoppler;
 use super::noise::GaussMarkov;
 use super::TrackingDeviceSim;
-use crate::cosmic::{Cosm, Frame, Orbit};
+use crate::cosmic::{AzElRange, Cosm, Frame, Orbit, SurfacePoint};
 use crate::io::{frame_from_str, frame_to_str, ConfigRepr, Configurable};
+use crate::linalg::allocator::Allocator;
+use crate::linalg::DefaultAllocator;
 use crate::md::prelude::Traj;
-use crate::time::Epoch;
-use crate::utils::between_0_360;
+use crate::md::trajectory::Interpolatable;
+use crate::md::EventEvaluator;
+use crate::time::{Epoch, Unit};
 use crate::{NyxError, Spacecraft};
 use hifitime::Duration;
 use rand_pcg::Pcg64Mcg;
@@ -152,44 +155,32 @@ impl GroundStation {
     }
 
     /// Computes the azimuth and elevation of the provided object seen from this ground station, both in degrees.
+    /// The azimuth is measured from the North towards the East.
     /// Also returns the ground station's orbit in the frame of the receiver
     pub fn azimuth_elevation_of(&self, rx: Orbit, cosm: &Cosm) -> (f64, f64, Orbit, Orbit) {
-        // Start by converting the receiver spacecraft into the ground station frame.
-        let rx_gs_frame = cosm.frame_chg(&rx, self.frame);
-
-        let dt = rx.epoch;
-        // Then, compute the rotation matrix from the body fixed frame of the ground station to its topocentric frame SEZ.
-        let tx_gs_frame = self.to_orbit(dt);
-        // Note: we're only looking at the radii so we don't need to apply the transport theorem here.
-        let dcm_topo2fixed = tx_gs_frame.dcm_from_traj_frame(Frame::SEZ).unwrap();
-
-        // Now, rotate the spacecraft in the SEZ frame to compute its elevation as seen from the ground station.
-        // We transpose the DCM so that it's the fixed to topocentric rotation.
-        let rx_sez = rx_gs_frame.with_position_rotated_by(dcm_topo2fixed.transpose());
-        let tx_sez = tx_gs_frame.with_position_rotated_by(dcm_topo2fixed.transpose());
-        // Now, let's compute the range ρ.
-        let rho_sez = rx_sez - tx_sez;
-
-        // Finally, compute the elevation (math is the same as declination)
-        // Source: Vallado, section 4.4.3
-        // Only the sine is needed as per Vallado, and the formula is the same as the declination
-        // because we're in the SEZ frame.
-        let elevation_deg = rho_sez.declination_deg();
-        if (elevation_deg - 90.0).abs() < 1e-6 {
-            warn!("object nearly overhead (el = {elevation_deg} deg), azimuth may be incorrect");
-        }
-        // For the elevation, we need to perform a quadrant check because it's measured from 0 to 360 degrees.
-        let azimuth_deg = between_0_360((-rho_sez.y_km.atan2(rho_sez.x_km)).to_degrees());
+        // Convert the receiver into the SEZ frame of the ground station to compute its elevation and azimuth.
+        let rho_sez = self.surface_point().frame_chg(&rx, Frame::SEZ, cosm);
+        let az_el = AzElRange::from_sez(&rho_sez);
 
         // Return elevation in degrees and rx/tx in the inertial frame of the spacecraft
         (
-            azimuth_deg,
-            elevation_deg,
+            az_el.azimuth_deg,
+            az_el.elevation_deg,
             rx,
-            cosm.frame_chg(&tx_gs_frame, rx.frame),
+            cosm.frame_chg(&self.to_orbit(rx.epoch), rx.frame),
         )
     }
 
+    /// Returns the location of this ground station as a surface point, which defines its topocentric frames
+    pub fn surface_point(&self) -> SurfacePoint {
+        SurfacePoint {
+            latitude_deg: self.latitude_deg,
+            longitude_deg: self.longitude_deg,
+            height_km: self.height_km,
+            frame: self.frame,
+        }
+    }
+
     /// Return this ground station as an orbit in its current frame
     pub fn to_orbit(&self, epoch: Epoch) -> Orbit {
         Orbit::from_geodesic(
@@ -201,6 +192,14 @@ impl GroundStation {
         )
     }
 
+    /// Creates an event whose value is the elevation of the object above the elevation mask, which is positive when it is visible from this ground station.
+    pub fn to_visibility_event(&self, cosm: Arc<Cosm>) -> VisibilityEvent {
+        VisibilityEvent {
+            station: self.clone(),
+            cosm,
+        }
+    }
+
     /// Returns the timestamp noise, range noise, and doppler noise for this ground station at the provided epoch.
     fn noises(
         &mut self,
@@ -412,6 +411,51 @@ impl fmt::Display for GroundStation {
     }
 }
 
+/// An event whose value is the elevation of the object above the elevation mask of the ground station, in degrees.
+pub struct VisibilityEvent {
+    pub station: GroundStation,
+    pub cosm: Arc<Cosm>,
+}
+
+impl fmt::Display for VisibilityEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} visibility", self.station.name)
+    }
+}
+
+impl<S: Interpolatable> EventEvaluator<S> for VisibilityEvent
+where
+    DefaultAllocator:
+        Allocator<f64, S::VecLength> + Allocator<f64, S::Size> + Allocator<f64, S::Size, S::Size>,
+{
+    fn eval(&self, state: &S) -> f64 {
+        let (_, elevation, _, _) = self
+            .station
+            .azimuth_elevation_of(*state.orbit(), &self.cosm);
+        elevation - self.station.elevation_mask_deg
+    }
+
+    /// Stop searching when the time has converged to less than a microsecond
+    fn epoch_precision(&self) -> Duration {
+        1 * Unit::Microsecond
+    }
+
+    /// Finds the crossing of the elevation mask within a micro degree
+    fn value_precision(&self) -> f64 {
+        1e-6
+    }
+
+    fn eval_string(&self, state: &S) -> String {
+        let (azimuth, elevation, _, _) = self
+            .station
+            .azimuth_elevation_of(*state.orbit(), &self.cosm);
+        format!(
+            "{}: azimuth = {azimuth:.3} deg, elevation = {elevation:.3} deg (mask: {:.3} deg)",
+            self.station.name, self.station.elevation_mask_deg
+        )
+    }
+}
+
 #[test]
 fn test_load_single() {
     use std::env;
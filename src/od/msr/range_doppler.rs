@@ -18,7 +18,7 @@
 
 use crate::cosmic::Orbit;
 use crate::linalg::allocator::Allocator;
-use crate::linalg::{DefaultAllocator, OMatrix, OVector, Vector2, U2};
+use crate::linalg::{Const, DefaultAllocator, OMatrix, OVector, Vector2, U2};
 use crate::od::msr::RangeMsr;
 use crate::od::{EstimateFrom, Measurement};
 use crate::{Spacecraft, TimeTagged};
@@ -222,15 +222,24 @@ impl EstimateFrom<Spacecraft, RangeDoppler> for Spacecraft {
         from
     }
 
+    /// The range and Doppler only directly depend on the orbit: the Cr, Cd and fuel mass are observed through the STM.
     fn sensitivity(
-        _msr: &RangeDoppler,
-        _receiver: Self,
-        _transmitter: Orbit,
+        msr: &RangeDoppler,
+        receiver: Self,
+        transmitter: Orbit,
     ) -> OMatrix<f64, <RangeDoppler as Measurement>::MeasurementSize, Self::Size>
     where
         DefaultAllocator:
             Allocator<f64, <RangeDoppler as Measurement>::MeasurementSize, Self::Size>,
     {
-        todo!("cannot yet estimate a full spacecraft state")
+        let mut h_tilde = OMatrix::<f64, U2, Const<9>>::zeros();
+        h_tilde
+            .fixed_view_mut::<2, 6>(0, 0)
+            .copy_from(&<Orbit as EstimateFrom<Orbit, RangeDoppler>>::sensitivity(
+                msr,
+                receiver.orbit,
+                transmitter,
+            ));
+        h_tilde
     }
 }
@@ -131,7 +131,7 @@ where
             hdrs.push(field.to_field(more_meta.clone()));
         }
 
-        let cov_hdrs = match <S as State>::Size::dim() {
+        let cov_hdrs: Vec<String> = match <S as State>::Size::dim() {
             6 => {
                 // Add orbit 1-sigma covariance info, plotting to perform computations as desired
                 vec![
@@ -157,6 +157,20 @@ where
                     "Covariance VyVz",
                     "Covariance VzVz",
                 ]
+                .iter()
+                .map(|hdr| hdr.to_string())
+                .collect()
+            }
+            9 => {
+                // Add the spacecraft 1-sigma covariance info, i.e. the orbit followed by Cr, Cd and fuel mass
+                let names = ["X", "Y", "Z", "Vx", "Vy", "Vz", "Cr", "Cd", "Fuel"];
+                let mut hdrs = Vec::new();
+                for (i, name_i) in names.iter().enumerate() {
+                    for name_j in &names[i..] {
+                        hdrs.push(format!("Covariance {name_i}{name_j}"));
+                    }
+                }
+                hdrs
             }
             _ => todo!(
                 "exporting a state of size {} is not yet supported",
@@ -275,11 +289,12 @@ where
             // Create a full DCM and only rotate the orbit part of it.
             let mut dcm = OMatrix::<f64, S::Size, S::Size>::identity();
             for i in 0..6 {
-                for j in i..6 {
+                for j in 0..6 {
                     dcm[(i, j)] = dcm6x6[(i, j)];
                 }
             }
-            let ric_covar = &dcm * s.covar() * &dcm.transpose();
+            // The DCM rotates from the RIC frame into the inertial frame
+            let ric_covar = &dcm.transpose() * s.covar() * &dcm;
 
             ric_covariances.push(ric_covar);
         }
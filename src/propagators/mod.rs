@@ -31,6 +31,8 @@ mod rk_methods;
 pub use rk_methods::*;
 mod options;
 pub use options::*;
+mod sgp4;
+pub use sgp4::*;
 
 use crate::time::Duration;
 
@@ -0,0 +1,1216 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::cosmic::{gmst_1982, Cosm, Frame, Orbit};
+use crate::io::tle::Tle;
+use crate::linalg::Vector3;
+use crate::md::trajectory::{Traj, TrajError};
+use crate::time::{Duration, Epoch, TimeSeries, Unit};
+use crate::NyxError;
+use std::f64::consts::{PI, TAU};
+use std::fmt;
+use thiserror::Error;
+
+/// Earth rotation rate in radians per minute
+const RPTIM: f64 = 4.375_269_088_011_3e-3;
+
+/// Earth gravity constants used by SGP4. Published TLEs are generated with WGS-72.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Sgp4Gravity {
+    /// WGS-72 with the legacy value of `xke` (as in the original Spacetrack Report #3)
+    Wgs72Old,
+    /// WGS-72, which should be used with published TLEs
+    #[default]
+    Wgs72,
+    /// WGS-84
+    Wgs84,
+}
+
+/// Gravity constants in the units of SGP4: earth radii and minutes
+#[derive(Copy, Clone, Debug)]
+struct GravityConsts {
+    radius_km: f64,
+    xke: f64,
+    j2: f64,
+    j3oj2: f64,
+    j4: f64,
+}
+
+impl Sgp4Gravity {
+    fn consts(&self) -> GravityConsts {
+        let (mu, radius_km, j2, j3, j4): (f64, f64, f64, f64, f64) = match self {
+            Self::Wgs72Old | Self::Wgs72 => (
+                398_600.8,
+                6_378.135,
+                0.001_082_616,
+                -0.000_002_538_81,
+                -0.000_001_655_97,
+            ),
+            Self::Wgs84 => (
+                398_600.5,
+                6_378.137,
+                0.001_082_629_989_05,
+                -0.000_002_532_153_06,
+                -0.000_001_610_987_61,
+            ),
+        };
+        let xke = match self {
+            Self::Wgs72Old => 0.074_366_916_1,
+            _ => 60.0 / (radius_km * radius_km * radius_km / mu).sqrt(),
+        };
+        GravityConsts {
+            radius_km,
+            xke,
+            j2,
+            j3oj2: j3 / j2,
+            j4,
+        }
+    }
+}
+
+/// Errors of the SGP4 propagator, following the error codes of Vallado's implementation.
+#[derive(Error, Copy, Clone, Debug, PartialEq)]
+pub enum Sgp4Error {
+    /// The mean eccentricity is out of bounds
+    #[error("mean eccentricity {0} is not in [0, 1) at {1} minutes from epoch")]
+    MeanEccentricity(f64, f64),
+    /// The mean motion is negative
+    #[error("mean motion {0} rad/min is not positive at {1} minutes from epoch")]
+    MeanMotion(f64, f64),
+    /// The eccentricity after the lunar and solar perturbations is out of bounds
+    #[error("perturbed eccentricity {0} is not in [0, 1] at {1} minutes from epoch")]
+    PerturbedEccentricity(f64, f64),
+    /// The semi-latus rectum is negative
+    #[error("semi-latus rectum is negative at {0} minutes from epoch")]
+    SemiLatusRectum(f64),
+    /// The object is below the surface of the Earth
+    #[error("object has decayed at {0} minutes from epoch")]
+    Decayed(f64),
+}
+
+/// Lunar and solar terms of the deep space (SDP4) model
+#[derive(Copy, Clone, Debug, Default)]
+struct DeepSpace {
+    e3: f64,
+    ee2: f64,
+    se2: f64,
+    se3: f64,
+    sgh2: f64,
+    sgh3: f64,
+    sgh4: f64,
+    sh2: f64,
+    sh3: f64,
+    si2: f64,
+    si3: f64,
+    sl2: f64,
+    sl3: f64,
+    sl4: f64,
+    xgh2: f64,
+    xgh3: f64,
+    xgh4: f64,
+    xh2: f64,
+    xh3: f64,
+    xi2: f64,
+    xi3: f64,
+    xl2: f64,
+    xl3: f64,
+    xl4: f64,
+    zmol: f64,
+    zmos: f64,
+    // Secular rates
+    dedt: f64,
+    didt: f64,
+    dmdt: f64,
+    dnodt: f64,
+    domdt: f64,
+    // Resonance terms: 0 for none, 1 for synchronous and 2 for half-day orbits
+    irez: u8,
+    d2201: f64,
+    d2211: f64,
+    d3210: f64,
+    d3222: f64,
+    d4410: f64,
+    d4422: f64,
+    d5220: f64,
+    d5232: f64,
+    d5421: f64,
+    d5433: f64,
+    del1: f64,
+    del2: f64,
+    del3: f64,
+    xfact: f64,
+    xlamo: f64,
+}
+
+/// The SGP4 (near Earth) and SDP4 (deep space, i.e. periods of 225 minutes or more) analytical propagators of TLEs.
+///
+/// This is a port of the reference implementation of Vallado et al., "Revisiting Spacetrack Report #3", AIAA 2006-6753,
+/// in its "improved" operation mode. The states are returned in the TEME frame of the provided `Cosm`.
+#[derive(Clone)]
+pub struct Sgp4 {
+    /// The element set
+    pub tle: Tle,
+    /// The TEME frame of the generated states
+    pub frame: Frame,
+    gravity: Sgp4Gravity,
+    consts: GravityConsts,
+    // Mean elements at epoch, in radians, and the un-Kozai'd mean motion in radians per minute
+    ecco: f64,
+    inclo: f64,
+    nodeo: f64,
+    argpo: f64,
+    mo: f64,
+    bstar: f64,
+    no_unkozai: f64,
+    deep_space: Option<Box<DeepSpace>>,
+    gsto: f64,
+    isimp: bool,
+    aycof: f64,
+    con41: f64,
+    cc1: f64,
+    cc4: f64,
+    cc5: f64,
+    d2: f64,
+    d3: f64,
+    d4: f64,
+    delmo: f64,
+    eta: f64,
+    argpdot: f64,
+    omgcof: f64,
+    sinmao: f64,
+    t2cof: f64,
+    t3cof: f64,
+    t4cof: f64,
+    t5cof: f64,
+    x1mth2: f64,
+    x7thm1: f64,
+    mdot: f64,
+    nodedot: f64,
+    xlcof: f64,
+    xmcof: f64,
+    nodecf: f64,
+}
+
+impl Sgp4 {
+    /// Initializes the propagator of this TLE with the WGS-72 constants, and the TEME frame of the provided Cosm.
+    pub fn from_tle(tle: Tle, cosm: &Cosm) -> Result<Self, NyxError> {
+        Self::with_gravity(tle, Sgp4Gravity::default(), cosm.try_frame("TEME")?)
+    }
+
+    /// Initializes the propagator of this TLE with the provided gravity constants.
+    /// The states will be returned in the provided frame, which should be the TEME frame of a Cosm.
+    pub fn with_gravity(tle: Tle, gravity: Sgp4Gravity, frame: Frame) -> Result<Self, NyxError> {
+        let consts = gravity.consts();
+        let GravityConsts {
+            radius_km,
+            xke,
+            j2,
+            j3oj2,
+            j4,
+        } = consts;
+        let x2o3 = 2.0 / 3.0;
+
+        let ecco = tle.eccentricity;
+        let inclo = tle.inclination_deg.to_radians();
+        let nodeo = tle.raan_deg.to_radians();
+        let argpo = tle.aop_deg.to_radians();
+        let mo = tle.mean_anomaly_deg.to_radians();
+        let no_kozai = tle.mean_motion_rev_day * TAU / 1440.0;
+        let bstar = tle.bstar;
+        // Days since 1949 December 31 00:00 UT
+        let epoch_days = tle.epoch.to_jde_utc_days() - 2_433_281.5;
+
+        // Initialization of the mean elements (Vallado's `initl`)
+        let eccsq = ecco * ecco;
+        let omeosq = 1.0 - eccsq;
+        let rteosq = omeosq.sqrt();
+        let cosio = inclo.cos();
+        let cosio2 = cosio * cosio;
+        let ak = (xke / no_kozai).powf(x2o3);
+        let d1 = 0.75 * j2 * (3.0 * cosio2 - 1.0) / (rteosq * omeosq);
+        let mut del = d1 / (ak * ak);
+        let adel = ak * (1.0 - del * del - del * (1.0 / 3.0 + 134.0 * del * del / 81.0));
+        del = d1 / (adel * adel);
+        let no_unkozai = no_kozai / (1.0 + del);
+        let ao = (xke / no_unkozai).powf(x2o3);
+        let sinio = inclo.sin();
+        let po = ao * omeosq;
+        let con42 = 1.0 - 5.0 * cosio2;
+        let con41 = -con42 - cosio2 - cosio2;
+        let posq = po * po;
+        let rp = ao * (1.0 - ecco);
+        let gsto = gmst_1982((epoch_days + 2_433_281.5 - 2_451_545.0) / 36_525.0);
+
+        if !(0.0..1.0).contains(&ecco) {
+            return Err(NyxError::Sgp4(Sgp4Error::MeanEccentricity(ecco, 0.0)));
+        } else if no_unkozai <= 0.0 {
+            return Err(NyxError::Sgp4(Sgp4Error::MeanMotion(no_unkozai, 0.0)));
+        }
+
+        let ss = 78.0 / radius_km + 1.0;
+        let qzms2t = ((120.0 - 78.0) / radius_km).powi(4);
+
+        // Drag is simplified for perigees below 220 km
+        let mut isimp = rp < 220.0 / radius_km + 1.0;
+        let mut sfour = ss;
+        let mut qzms24 = qzms2t;
+        let perige = (rp - 1.0) * radius_km;
+        if perige < 156.0 {
+            sfour = if perige < 98.0 { 20.0 } else { perige - 78.0 };
+            qzms24 = ((120.0 - sfour) / radius_km).powi(4);
+            sfour = sfour / radius_km + 1.0;
+        }
+        let pinvsq = 1.0 / posq;
+
+        let tsi = 1.0 / (ao - sfour);
+        let eta = ao * ecco * tsi;
+        let etasq = eta * eta;
+        let eeta = ecco * eta;
+        let psisq = (1.0 - etasq).abs();
+        let coef = qzms24 * tsi.powi(4);
+        let coef1 = coef / psisq.powf(3.5);
+        let cc2 = coef1
+            * no_unkozai
+            * (ao * (1.0 + 1.5 * etasq + eeta * (4.0 + etasq))
+                + 0.375 * j2 * tsi / psisq * con41 * (8.0 + 3.0 * etasq * (8.0 + etasq)));
+        let cc1 = bstar * cc2;
+        let cc3 = if ecco > 1.0e-4 {
+            -2.0 * coef * tsi * j3oj2 * no_unkozai * sinio / ecco
+        } else {
+            0.0
+        };
+        let x1mth2 = 1.0 - cosio2;
+        let cc4 = 2.0
+            * no_unkozai
+            * coef1
+            * ao
+            * omeosq
+            * (eta * (2.0 + 0.5 * etasq) + ecco * (0.5 + 2.0 * etasq)
+                - j2 * tsi / (ao * psisq)
+                    * (-3.0 * con41 * (1.0 - 2.0 * eeta + etasq * (1.5 - 0.5 * eeta))
+                        + 0.75
+                            * x1mth2
+                            * (2.0 * etasq - eeta * (1.0 + etasq))
+                            * (2.0 * argpo).cos()));
+        let cc5 = 2.0 * coef1 * ao * omeosq * (1.0 + 2.75 * (etasq + eeta) + eeta * etasq);
+        let cosio4 = cosio2 * cosio2;
+        let temp1 = 1.5 * j2 * pinvsq * no_unkozai;
+        let temp2 = 0.5 * temp1 * j2 * pinvsq;
+        let temp3 = -0.46875 * j4 * pinvsq * pinvsq * no_unkozai;
+        let mdot = no_unkozai
+            + 0.5 * temp1 * rteosq * con41
+            + 0.0625 * temp2 * rteosq * (13.0 - 78.0 * cosio2 + 137.0 * cosio4);
+        let argpdot = -0.5 * temp1 * con42
+            + 0.0625 * temp2 * (7.0 - 114.0 * cosio2 + 395.0 * cosio4)
+            + temp3 * (3.0 - 36.0 * cosio2 + 49.0 * cosio4);
+        let xhdot1 = -temp1 * cosio;
+        let nodedot = xhdot1
+            + (0.5 * temp2 * (4.0 - 19.0 * cosio2) + 2.0 * temp3 * (3.0 - 7.0 * cosio2)) * cosio;
+        let xpidot = argpdot + nodedot;
+        let omgcof = bstar * cc3 * argpo.cos();
+        let xmcof = if ecco > 1.0e-4 {
+            -x2o3 * coef * bstar / eeta
+        } else {
+            0.0
+        };
+        let nodecf = 3.5 * omeosq * xhdot1 * cc1;
+        let t2cof = 1.5 * cc1;
+        // Avoids a division by zero for an inclination of 180 degrees
+        let xlcof = -0.25 * j3oj2 * sinio * (3.0 + 5.0 * cosio) / (1.0 + cosio).max(1.5e-12);
+        let aycof = -0.5 * j3oj2 * sinio;
+        let delmo = (1.0 + eta * mo.cos()).powi(3);
+        let sinmao = mo.sin();
+        let x7thm1 = 7.0 * cosio2 - 1.0;
+
+        let deep_space = if TAU / no_unkozai >= 225.0 {
+            isimp = true;
+            let mut ds = DeepSpace::default();
+            let dscom = dscom(epoch_days, ecco, argpo, inclo, nodeo, no_unkozai, &mut ds);
+            dsinit(
+                &mut ds, &dscom, xke, argpo, gsto, mo, mdot, no_unkozai, nodeo, nodedot, xpidot,
+                ecco, eccsq, inclo,
+            );
+            Some(Box::new(ds))
+        } else {
+            None
+        };
+
+        let (mut d2, mut d3, mut d4, mut t3cof, mut t4cof, mut t5cof) =
+            (0.0, 0.0, 0.0, 0.0, 0.0, 0.0);
+        if !isimp {
+            let cc1sq = cc1 * cc1;
+            d2 = 4.0 * ao * tsi * cc1sq;
+            let temp = d2 * tsi * cc1 / 3.0;
+            d3 = (17.0 * ao + sfour) * temp;
+            d4 = 0.5 * temp * ao * tsi * (221.0 * ao + 31.0 * sfour) * cc1;
+            t3cof = d2 + 2.0 * cc1sq;
+            t4cof = 0.25 * (3.0 * d3 + cc1 * (12.0 * d2 + 10.0 * cc1sq));
+            t5cof = 0.2
+                * (3.0 * d4 + 12.0 * cc1 * d3 + 6.0 * d2 * d2 + 15.0 * cc1sq * (2.0 * d2 + cc1sq));
+        }
+
+        let me = Self {
+            tle,
+            frame,
+            gravity,
+            consts,
+            ecco,
+            inclo,
+            nodeo,
+            argpo,
+            mo,
+            bstar,
+            no_unkozai,
+            deep_space,
+            gsto,
+            isimp,
+            aycof,
+            con41,
+            cc1,
+            cc4,
+            cc5,
+            d2,
+            d3,
+            d4,
+            delmo,
+            eta,
+            argpdot,
+            omgcof,
+            sinmao,
+            t2cof,
+            t3cof,
+            t4cof,
+            t5cof,
+            x1mth2,
+            x7thm1,
+            mdot,
+            nodedot,
+            xlcof,
+            xmcof,
+            nodecf,
+        };
+
+        // Check that the elements can be propagated
+        me.propagate_minutes(0.0)?;
+        Ok(me)
+    }
+
+    /// Returns the gravity constants of this propagator
+    pub fn gravity(&self) -> Sgp4Gravity {
+        self.gravity
+    }
+
+    /// Returns whether this TLE is propagated with the deep space (SDP4) model
+    pub fn is_deep_space(&self) -> bool {
+        self.deep_space.is_some()
+    }
+
+    /// Returns the position (km) and velocity (km/s) in TEME at the provided minutes from the TLE epoch.
+    pub fn propagate_minutes(
+        &self,
+        tsince: f64,
+    ) -> Result<(Vector3<f64>, Vector3<f64>), Sgp4Error> {
+        let GravityConsts {
+            radius_km,
+            xke,
+            j2,
+            j3oj2,
+            ..
+        } = self.consts;
+        let x2o3 = 2.0 / 3.0;
+        let t = tsince;
+
+        // Secular gravity and atmospheric drag
+        let xmdf = self.mo + self.mdot * t;
+        let argpdf = self.argpo + self.argpdot * t;
+        let nodedf = self.nodeo + self.nodedot * t;
+        let mut argpm = argpdf;
+        let mut mm = xmdf;
+        let t2 = t * t;
+        let mut nodem = nodedf + self.nodecf * t2;
+        let mut tempa = 1.0 - self.cc1 * t;
+        let mut tempe = self.bstar * self.cc4 * t;
+        let mut templ = self.t2cof * t2;
+
+        if !self.isimp {
+            let delomg = self.omgcof * t;
+            let delm = self.xmcof * ((1.0 + self.eta * xmdf.cos()).powi(3) - self.delmo);
+            let temp = delomg + delm;
+            mm = xmdf + temp;
+            argpm = argpdf - temp;
+            let t3 = t2 * t;
+            let t4 = t3 * t;
+            tempa = tempa - self.d2 * t2 - self.d3 * t3 - self.d4 * t4;
+            tempe += self.bstar * self.cc5 * (mm.sin() - self.sinmao);
+            templ += self.t3cof * t3 + t4 * (self.t4cof + t * self.t5cof);
+        }
+
+        let mut nm = self.no_unkozai;
+        let mut em = self.ecco;
+        let mut inclm = self.inclo;
+        if let Some(ds) = &self.deep_space {
+            dspace(
+                ds,
+                self.argpo,
+                self.argpdot,
+                t,
+                self.gsto,
+                self.no_unkozai,
+                &mut em,
+                &mut argpm,
+                &mut inclm,
+                &mut mm,
+                &mut nodem,
+                &mut nm,
+            );
+        }
+
+        if nm <= 0.0 {
+            return Err(Sgp4Error::MeanMotion(nm, t));
+        }
+        let am = (xke / nm).powf(x2o3) * tempa * tempa;
+        nm = xke / am.powf(1.5);
+        em -= tempe;
+        if !(-0.001..1.0).contains(&em) {
+            return Err(Sgp4Error::MeanEccentricity(em, t));
+        }
+        if em < 1.0e-6 {
+            em = 1.0e-6;
+        }
+        mm += self.no_unkozai * templ;
+        let xlm = (mm + argpm + nodem) % TAU;
+        nodem %= TAU;
+        argpm %= TAU;
+        mm = (xlm - argpm - nodem) % TAU;
+
+        // Lunar and solar periodics
+        let mut ep = em;
+        let mut xincp = inclm;
+        let mut argpp = argpm;
+        let mut nodep = nodem;
+        let mut mp = mm;
+        let (mut sinip, mut cosip) = inclm.sin_cos();
+        let (mut aycof, mut xlcof) = (self.aycof, self.xlcof);
+        let (mut con41, mut x1mth2, mut x7thm1) = (self.con41, self.x1mth2, self.x7thm1);
+
+        if let Some(ds) = &self.deep_space {
+            dpper(ds, t, &mut ep, &mut xincp, &mut nodep, &mut argpp, &mut mp);
+            if xincp < 0.0 {
+                xincp = -xincp;
+                nodep += PI;
+                argpp -= PI;
+            }
+            if !(0.0..=1.0).contains(&ep) {
+                return Err(Sgp4Error::PerturbedEccentricity(ep, t));
+            }
+
+            (sinip, cosip) = xincp.sin_cos();
+            aycof = -0.5 * j3oj2 * sinip;
+            xlcof = -0.25 * j3oj2 * sinip * (3.0 + 5.0 * cosip) / (1.0 + cosip).max(1.5e-12);
+
+            let cosisq = cosip * cosip;
+            con41 = 3.0 * cosisq - 1.0;
+            x1mth2 = 1.0 - cosisq;
+            x7thm1 = 7.0 * cosisq - 1.0;
+        }
+
+        // Long period periodics
+        let axnl = ep * argpp.cos();
+        let temp = 1.0 / (am * (1.0 - ep * ep));
+        let aynl = ep * argpp.sin() + temp * aycof;
+        let xl = mp + argpp + nodep + temp * xlcof * axnl;
+
+        // Kepler's equation
+        let u = (xl - nodep) % TAU;
+        let mut eo1 = u;
+        let mut tem5: f64 = 9999.9;
+        let (mut sineo1, mut coseo1) = (0.0, 0.0);
+        let mut ktr = 1;
+        while tem5.abs() >= 1.0e-12 && ktr <= 10 {
+            (sineo1, coseo1) = eo1.sin_cos();
+            tem5 = 1.0 - coseo1 * axnl - sineo1 * aynl;
+            tem5 = (u - aynl * coseo1 + axnl * sineo1 - eo1) / tem5;
+            tem5 = tem5.clamp(-0.95, 0.95);
+            eo1 += tem5;
+            ktr += 1;
+        }
+
+        // Short period preliminary quantities
+        let ecose = axnl * coseo1 + aynl * sineo1;
+        let esine = axnl * sineo1 - aynl * coseo1;
+        let el2 = axnl * axnl + aynl * aynl;
+        let pl = am * (1.0 - el2);
+        if pl < 0.0 {
+            return Err(Sgp4Error::SemiLatusRectum(t));
+        }
+        let rl = am * (1.0 - ecose);
+        let rdotl = am.sqrt() * esine / rl;
+        let rvdotl = pl.sqrt() / rl;
+        let betal = (1.0 - el2).sqrt();
+        let temp = esine / (1.0 + betal);
+        let sinu = am / rl * (sineo1 - aynl - axnl * temp);
+        let cosu = am / rl * (coseo1 - axnl + aynl * temp);
+        let mut su = sinu.atan2(cosu);
+        let sin2u = (cosu + cosu) * sinu;
+        let cos2u = 1.0 - 2.0 * sinu * sinu;
+        let temp = 1.0 / pl;
+        let temp1 = 0.5 * j2 * temp;
+        let temp2 = temp1 * temp;
+
+        // Short period periodics
+        let mrt = rl * (1.0 - 1.5 * temp2 * betal * con41) + 0.5 * temp1 * x1mth2 * cos2u;
+        su -= 0.25 * temp2 * x7thm1 * sin2u;
+        let xnode = nodep + 1.5 * temp2 * cosip * sin2u;
+        let xinc = xincp + 1.5 * temp2 * cosip * sinip * cos2u;
+        let mvt = rdotl - nm * temp1 * x1mth2 * sin2u / xke;
+        let rvdot = rvdotl + nm * temp1 * (x1mth2 * cos2u + 1.5 * con41) / xke;
+
+        // Orientation vectors
+        let (sinsu, cossu) = su.sin_cos();
+        let (snod, cnod) = xnode.sin_cos();
+        let (sini, cosi) = xinc.sin_cos();
+        let xmx = -snod * cosi;
+        let xmy = cnod * cosi;
+        let u_vec = Vector3::new(
+            xmx * sinsu + cnod * cossu,
+            xmy * sinsu + snod * cossu,
+            sini * sinsu,
+        );
+        let v_vec = Vector3::new(
+            xmx * cossu - cnod * sinsu,
+            xmy * cossu - snod * sinsu,
+            sini * cossu,
+        );
+
+        if mrt < 1.0 {
+            return Err(Sgp4Error::Decayed(t));
+        }
+
+        let vkmpersec = radius_km * xke / 60.0;
+        Ok((
+            mrt * radius_km * u_vec,
+            (mvt * u_vec + rvdot * v_vec) * vkmpersec,
+        ))
+    }
+
+    /// Returns the state at the provided epoch, in the TEME frame of this propagator.
+    pub fn at(&self, epoch: Epoch) -> Result<Orbit, NyxError> {
+        let tsince = (epoch - self.tle.epoch).to_unit(Unit::Minute);
+        let (r, v) = self.propagate_minutes(tsince)?;
+        Ok(Orbit::cartesian(
+            r[0], r[1], r[2], v[0], v[1], v[2], epoch, self.frame,
+        ))
+    }
+
+    /// Returns the trajectory between the provided epochs (inclusive), sampled with the provided step.
+    ///
+    /// Note that the SGP4 velocity is not exactly the derivative of its position (the difference is about a meter per second),
+    /// so the trajectory is only exact at the sampled epochs.
+    pub fn to_traj(
+        &self,
+        start: Epoch,
+        end: Epoch,
+        step: Duration,
+    ) -> Result<Traj<Orbit>, NyxError> {
+        let mut traj = Traj::new();
+        for epoch in TimeSeries::inclusive(start, end, step) {
+            traj.states.push(self.at(epoch)?);
+        }
+        if traj.states.len() < 2 {
+            return Err(NyxError::Trajectory(TrajError::CreationError(
+                "SGP4 trajectory must have at least two states".to_string(),
+            )));
+        }
+        traj.finalize();
+        Ok(traj)
+    }
+}
+
+impl fmt::Debug for Sgp4 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} {:?} for {}",
+            if self.is_deep_space() { "SDP4" } else { "SGP4" },
+            self.gravity,
+            self.tle
+        )
+    }
+}
+
+impl fmt::Display for Sgp4 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+/// Lunar or solar coefficients computed by `dscom`
+#[derive(Copy, Clone, Debug, Default)]
+struct LunisolarTerms {
+    s1: f64,
+    s2: f64,
+    s3: f64,
+    s4: f64,
+    s5: f64,
+    s6: f64,
+    s7: f64,
+    z1: f64,
+    z2: f64,
+    z3: f64,
+    z11: f64,
+    z12: f64,
+    z13: f64,
+    z21: f64,
+    z22: f64,
+    z23: f64,
+    z31: f64,
+    z32: f64,
+    z33: f64,
+}
+
+/// Intermediate values of `dscom` which are needed by `dsinit`
+struct DsCom {
+    cosim: f64,
+    sinim: f64,
+    emsq: f64,
+    em: f64,
+    nm: f64,
+    solar: LunisolarTerms,
+    lunar: LunisolarTerms,
+}
+
+/// Computes the lunar and solar terms of the deep space model at epoch (Vallado's `dscom`)
+fn dscom(
+    epoch: f64,
+    ep: f64,
+    argpp: f64,
+    inclp: f64,
+    nodep: f64,
+    np: f64,
+    ds: &mut DeepSpace,
+) -> DsCom {
+    const ZES: f64 = 0.01675;
+    const ZEL: f64 = 0.05490;
+    const C1SS: f64 = 2.9864797e-6;
+    const C1L: f64 = 4.7968065e-7;
+    const ZSINIS: f64 = 0.39785416;
+    const ZCOSIS: f64 = 0.91744867;
+    const ZCOSGS: f64 = 0.1945905;
+    const ZSINGS: f64 = -0.98088458;
+
+    let nm = np;
+    let em = ep;
+    let (snodm, cnodm) = nodep.sin_cos();
+    let (sinomm, cosomm) = argpp.sin_cos();
+    let (sinim, cosim) = inclp.sin_cos();
+    let emsq = em * em;
+    let betasq = 1.0 - emsq;
+    let rtemsq = betasq.sqrt();
+
+    let day = epoch + 18_261.5;
+    let xnodce = (4.5236020 - 9.2422029e-4 * day) % TAU;
+    let (stem, ctem) = xnodce.sin_cos();
+    let zcosil = 0.91375164 - 0.03568096 * ctem;
+    let zsinil = (1.0 - zcosil * zcosil).sqrt();
+    let zsinhl = 0.089683511 * stem / zsinil;
+    let zcoshl = (1.0 - zsinhl * zsinhl).sqrt();
+    let gam = 5.8351514 + 0.0019443680 * day;
+    let zx = 0.39785416 * stem / zsinil;
+    let zy = zcoshl * ctem + 0.91744867 * zsinhl * stem;
+    let zx = gam + zx.atan2(zy) - xnodce;
+    let (zsingl, zcosgl) = zx.sin_cos();
+
+    let terms =
+        |zcosg: f64, zsing: f64, zcosi: f64, zsini: f64, zcosh: f64, zsinh: f64, cc: f64| {
+            let a1 = zcosg * zcosh + zsing * zcosi * zsinh;
+            let a3 = -zsing * zcosh + zcosg * zcosi * zsinh;
+            let a7 = -zcosg * zsinh + zsing * zcosi * zcosh;
+            let a8 = zsing * zsini;
+            let a9 = zsing * zsinh + zcosg * zcosi * zcosh;
+            let a10 = zcosg * zsini;
+            let a2 = cosim * a7 + sinim * a8;
+            let a4 = cosim * a9 + sinim * a10;
+            let a5 = -sinim * a7 + cosim * a8;
+            let a6 = -sinim * a9 + cosim * a10;
+
+            let x1 = a1 * cosomm + a2 * sinomm;
+            let x2 = a3 * cosomm + a4 * sinomm;
+            let x3 = -a1 * sinomm + a2 * cosomm;
+            let x4 = -a3 * sinomm + a4 * cosomm;
+            let x5 = a5 * sinomm;
+            let x6 = a6 * sinomm;
+            let x7 = a5 * cosomm;
+            let x8 = a6 * cosomm;
+
+            let z31 = 12.0 * x1 * x1 - 3.0 * x3 * x3;
+            let z32 = 24.0 * x1 * x2 - 6.0 * x3 * x4;
+            let z33 = 12.0 * x2 * x2 - 3.0 * x4 * x4;
+            let z1 = 3.0 * (a1 * a1 + a2 * a2) + z31 * emsq;
+            let z2 = 6.0 * (a1 * a3 + a2 * a4) + z32 * emsq;
+            let z3 = 3.0 * (a3 * a3 + a4 * a4) + z33 * emsq;
+            let s3 = cc / nm;
+            let s4 = s3 * rtemsq;
+
+            LunisolarTerms {
+                s1: -15.0 * em * s4,
+                s2: -0.5 * s3 / rtemsq,
+                s3,
+                s4,
+                s5: x1 * x3 + x2 * x4,
+                s6: x2 * x3 + x1 * x4,
+                s7: x2 * x4 - x1 * x3,
+                z1: z1 + z1 + betasq * z31,
+                z2: z2 + z2 + betasq * z32,
+                z3: z3 + z3 + betasq * z33,
+                z11: -6.0 * a1 * a5 + emsq * (-24.0 * x1 * x7 - 6.0 * x3 * x5),
+                z12: -6.0 * (a1 * a6 + a3 * a5)
+                    + emsq * (-24.0 * (x2 * x7 + x1 * x8) - 6.0 * (x3 * x6 + x4 * x5)),
+                z13: -6.0 * a3 * a6 + emsq * (-24.0 * x2 * x8 - 6.0 * x4 * x6),
+                z21: 6.0 * a2 * a5 + emsq * (24.0 * x1 * x5 - 6.0 * x3 * x7),
+                z22: 6.0 * (a4 * a5 + a2 * a6)
+                    + emsq * (24.0 * (x2 * x5 + x1 * x6) - 6.0 * (x4 * x7 + x3 * x8)),
+                z23: 6.0 * a4 * a6 + emsq * (24.0 * x2 * x6 - 6.0 * x4 * x8),
+                z31,
+                z32,
+                z33,
+            }
+        };
+
+    let solar = terms(ZCOSGS, ZSINGS, ZCOSIS, ZSINIS, cnodm, snodm, C1SS);
+    let lunar = terms(
+        zcosgl,
+        zsingl,
+        zcosil,
+        zsinil,
+        zcoshl * cnodm + zsinhl * snodm,
+        snodm * zcoshl - cnodm * zsinhl,
+        C1L,
+    );
+
+    ds.zmol = (4.7199672 + 0.22997150 * day - gam) % TAU;
+    ds.zmos = (6.2565837 + 0.017201977 * day) % TAU;
+
+    let s = &solar;
+    ds.se2 = 2.0 * s.s1 * s.s6;
+    ds.se3 = 2.0 * s.s1 * s.s7;
+    ds.si2 = 2.0 * s.s2 * s.z12;
+    ds.si3 = 2.0 * s.s2 * (s.z13 - s.z11);
+    ds.sl2 = -2.0 * s.s3 * s.z2;
+    ds.sl3 = -2.0 * s.s3 * (s.z3 - s.z1);
+    ds.sl4 = -2.0 * s.s3 * (-21.0 - 9.0 * emsq) * ZES;
+    ds.sgh2 = 2.0 * s.s4 * s.z32;
+    ds.sgh3 = 2.0 * s.s4 * (s.z33 - s.z31);
+    ds.sgh4 = -18.0 * s.s4 * ZES;
+    ds.sh2 = -2.0 * s.s2 * s.z22;
+    ds.sh3 = -2.0 * s.s2 * (s.z23 - s.z21);
+
+    let l = &lunar;
+    ds.ee2 = 2.0 * l.s1 * l.s6;
+    ds.e3 = 2.0 * l.s1 * l.s7;
+    ds.xi2 = 2.0 * l.s2 * l.z12;
+    ds.xi3 = 2.0 * l.s2 * (l.z13 - l.z11);
+    ds.xl2 = -2.0 * l.s3 * l.z2;
+    ds.xl3 = -2.0 * l.s3 * (l.z3 - l.z1);
+    ds.xl4 = -2.0 * l.s3 * (-21.0 - 9.0 * emsq) * ZEL;
+    ds.xgh2 = 2.0 * l.s4 * l.z32;
+    ds.xgh3 = 2.0 * l.s4 * (l.z33 - l.z31);
+    ds.xgh4 = -18.0 * l.s4 * ZEL;
+    ds.xh2 = -2.0 * l.s2 * l.z22;
+    ds.xh3 = -2.0 * l.s2 * (l.z23 - l.z21);
+
+    DsCom {
+        cosim,
+        sinim,
+        emsq,
+        em,
+        nm,
+        solar,
+        lunar,
+    }
+}
+
+/// Applies the lunar and solar periodics to the mean elements (Vallado's `dpper`, in the improved operation mode)
+#[allow(clippy::too_many_arguments)]
+fn dpper(
+    ds: &DeepSpace,
+    t: f64,
+    ep: &mut f64,
+    inclp: &mut f64,
+    nodep: &mut f64,
+    argpp: &mut f64,
+    mp: &mut f64,
+) {
+    const ZNS: f64 = 1.19459e-5;
+    const ZES: f64 = 0.01675;
+    const ZNL: f64 = 1.5835218e-4;
+    const ZEL: f64 = 0.05490;
+
+    // Solar terms
+    let zm = ds.zmos + ZNS * t;
+    let zf = zm + 2.0 * ZES * zm.sin();
+    let sinzf = zf.sin();
+    let f2 = 0.5 * sinzf * sinzf - 0.25;
+    let f3 = -0.5 * sinzf * zf.cos();
+    let ses = ds.se2 * f2 + ds.se3 * f3;
+    let sis = ds.si2 * f2 + ds.si3 * f3;
+    let sls = ds.sl2 * f2 + ds.sl3 * f3 + ds.sl4 * sinzf;
+    let sghs = ds.sgh2 * f2 + ds.sgh3 * f3 + ds.sgh4 * sinzf;
+    let shs = ds.sh2 * f2 + ds.sh3 * f3;
+
+    // Lunar terms
+    let zm = ds.zmol + ZNL * t;
+    let zf = zm + 2.0 * ZEL * zm.sin();
+    let sinzf = zf.sin();
+    let f2 = 0.5 * sinzf * sinzf - 0.25;
+    let f3 = -0.5 * sinzf * zf.cos();
+    let sel = ds.ee2 * f2 + ds.e3 * f3;
+    let sil = ds.xi2 * f2 + ds.xi3 * f3;
+    let sll = ds.xl2 * f2 + ds.xl3 * f3 + ds.xl4 * sinzf;
+    let sghl = ds.xgh2 * f2 + ds.xgh3 * f3 + ds.xgh4 * sinzf;
+    let shll = ds.xh2 * f2 + ds.xh3 * f3;
+
+    // The periodics at epoch (peo, pinco, etc.) are zero
+    let pe = ses + sel;
+    let pinc = sis + sil;
+    let pl = sls + sll;
+    let mut pgh = sghs + sghl;
+    let mut ph = shs + shll;
+
+    *inclp += pinc;
+    *ep += pe;
+    let (sinip, cosip) = inclp.sin_cos();
+
+    if *inclp >= 0.2 {
+        // Apply the periodics directly
+        ph /= sinip;
+        pgh -= cosip * ph;
+        *argpp += pgh;
+        *nodep += ph;
+        *mp += pl;
+    } else {
+        // Apply the periodics with the Lyddane modification
+        let (sinop, cosop) = nodep.sin_cos();
+        let mut alfdp = sinip * sinop;
+        let mut betdp = sinip * cosop;
+        let dalf = ph * cosop + pinc * cosip * sinop;
+        let dbet = -ph * sinop + pinc * cosip * cosop;
+        alfdp += dalf;
+        betdp += dbet;
+        *nodep %= TAU;
+        let mut xls = *mp + *argpp + cosip * *nodep;
+        let dls = pl + pgh - pinc * *nodep * sinip;
+        xls += dls;
+        let xnoh = *nodep;
+        *nodep = alfdp.atan2(betdp);
+        if (xnoh - *nodep).abs() > PI {
+            if *nodep < xnoh {
+                *nodep += TAU;
+            } else {
+                *nodep -= TAU;
+            }
+        }
+        *mp += pl;
+        *argpp = xls - *mp - cosip * *nodep;
+    }
+}
+
+/// Initializes the secular rates and the resonance terms of the deep space model (Vallado's `dsinit`)
+#[allow(clippy::too_many_arguments)]
+fn dsinit(
+    ds: &mut DeepSpace,
+    com: &DsCom,
+    xke: f64,
+    argpo: f64,
+    gsto: f64,
+    mo: f64,
+    mdot: f64,
+    no: f64,
+    nodeo: f64,
+    nodedot: f64,
+    xpidot: f64,
+    ecco: f64,
+    eccsq: f64,
+    inclm: f64,
+) {
+    const Q22: f64 = 1.7891679e-6;
+    const Q31: f64 = 2.1460748e-6;
+    const Q33: f64 = 2.2123015e-7;
+    const ROOT22: f64 = 1.7891679e-6;
+    const ROOT44: f64 = 7.3636953e-9;
+    const ROOT54: f64 = 2.1765803e-9;
+    const ROOT32: f64 = 3.7393792e-7;
+    const ROOT52: f64 = 1.1428639e-7;
+    const ZNL: f64 = 1.5835218e-4;
+    const ZNS: f64 = 1.19459e-5;
+
+    let DsCom {
+        cosim,
+        sinim,
+        emsq,
+        em,
+        nm,
+        solar: s,
+        lunar: l,
+    } = *com;
+
+    ds.irez = if nm < 0.0052359877 && nm > 0.0034906585 {
+        1
+    } else if (8.26e-3..=9.24e-3).contains(&nm) && em >= 0.5 {
+        2
+    } else {
+        0
+    };
+
+    // Solar terms
+    let ses = s.s1 * ZNS * s.s5;
+    let sis = s.s2 * ZNS * (s.z11 + s.z13);
+    let sls = -ZNS * s.s3 * (s.z1 + s.z3 - 14.0 - 6.0 * emsq);
+    let sghs = s.s4 * ZNS * (s.z31 + s.z33 - 6.0);
+    let equatorial = !(5.2359877e-2..=PI - 5.2359877e-2).contains(&inclm);
+    let mut shs = if equatorial {
+        0.0
+    } else {
+        -ZNS * s.s2 * (s.z21 + s.z23)
+    };
+    if sinim != 0.0 {
+        shs /= sinim;
+    }
+    let sgs = sghs - cosim * shs;
+
+    // Lunar terms
+    ds.dedt = ses + l.s1 * ZNL * l.s5;
+    ds.didt = sis + l.s2 * ZNL * (l.z11 + l.z13);
+    ds.dmdt = sls - ZNL * l.s3 * (l.z1 + l.z3 - 14.0 - 6.0 * emsq);
+    let sghl = l.s4 * ZNL * (l.z31 + l.z33 - 6.0);
+    let shll = if equatorial {
+        0.0
+    } else {
+        -ZNL * l.s2 * (l.z21 + l.z23)
+    };
+    ds.domdt = sgs + sghl;
+    ds.dnodt = shs;
+    if sinim != 0.0 {
+        ds.domdt -= cosim / sinim * shll;
+        ds.dnodt += shll / sinim;
+    }
+
+    // Resonance terms
+    let theta = gsto % TAU;
+    if ds.irez == 0 {
+        return;
+    }
+    let aonv = (nm / xke).powf(2.0 / 3.0);
+
+    if ds.irez == 2 {
+        // Geopotential resonance for 12 hour orbits
+        let cosisq = cosim * cosim;
+        let em = ecco;
+        let emsq = eccsq;
+        let eoc = em * emsq;
+        let g201 = -0.306 - (em - 0.64) * 0.440;
+        let (g211, g310, g322, g410, g422, g520);
+        if em <= 0.65 {
+            g211 = 3.616 - 13.2470 * em + 16.2900 * emsq;
+            g310 = -19.302 + 117.3900 * em - 228.4190 * emsq + 156.5910 * eoc;
+            g322 = -18.9068 + 109.7927 * em - 214.6334 * emsq + 146.5816 * eoc;
+            g410 = -41.122 + 242.6940 * em - 471.0940 * emsq + 313.9530 * eoc;
+            g422 = -146.407 + 841.8800 * em - 1629.014 * emsq + 1083.4350 * eoc;
+            g520 = -532.114 + 3017.977 * em - 5740.032 * emsq + 3708.2760 * eoc;
+        } else {
+            g211 = -72.099 + 331.819 * em - 508.738 * emsq + 266.724 * eoc;
+            g310 = -346.844 + 1582.851 * em - 2415.925 * emsq + 1246.113 * eoc;
+            g322 = -342.585 + 1554.908 * em - 2366.899 * emsq + 1215.972 * eoc;
+            g410 = -1052.797 + 4758.686 * em - 7193.992 * emsq + 3651.957 * eoc;
+            g422 = -3581.690 + 16178.110 * em - 24462.770 * emsq + 12422.520 * eoc;
+            g520 = if em > 0.715 {
+                -5149.66 + 29936.92 * em - 54087.36 * emsq + 31324.56 * eoc
+            } else {
+                1464.74 - 4664.75 * em + 3763.64 * emsq
+            };
+        }
+        let (g533, g521, g532);
+        if em < 0.7 {
+            g533 = -919.22770 + 4988.6100 * em - 9064.7700 * emsq + 5542.21 * eoc;
+            g521 = -822.71072 + 4568.6173 * em - 8491.4146 * emsq + 5337.524 * eoc;
+            g532 = -853.66600 + 4690.2500 * em - 8624.7700 * emsq + 5341.4 * eoc;
+        } else {
+            g533 = -37995.780 + 161616.52 * em - 229838.20 * emsq + 109377.94 * eoc;
+            g521 = -51752.104 + 218913.95 * em - 309468.16 * emsq + 146349.42 * eoc;
+            g532 = -40023.880 + 170470.89 * em - 242699.48 * emsq + 115605.82 * eoc;
+        }
+
+        let sini2 = sinim * sinim;
+        let f220 = 0.75 * (1.0 + 2.0 * cosim + cosisq);
+        let f221 = 1.5 * sini2;
+        let f321 = 1.875 * sinim * (1.0 - 2.0 * cosim - 3.0 * cosisq);
+        let f322 = -1.875 * sinim * (1.0 + 2.0 * cosim - 3.0 * cosisq);
+        let f441 = 35.0 * sini2 * f220;
+        let f442 = 39.3750 * sini2 * sini2;
+        let f522 = 9.84375
+            * sinim
+            * (sini2 * (1.0 - 2.0 * cosim - 5.0 * cosisq)
+                + 0.33333333 * (-2.0 + 4.0 * cosim + 6.0 * cosisq));
+        let f523 = sinim
+            * (4.92187512 * sini2 * (-2.0 - 4.0 * cosim + 10.0 * cosisq)
+                + 6.56250012 * (1.0 + 2.0 * cosim - 3.0 * cosisq));
+        let f542 =
+            29.53125 * sinim * (2.0 - 8.0 * cosim + cosisq * (-12.0 + 8.0 * cosim + 10.0 * cosisq));
+        let f543 =
+            29.53125 * sinim * (-2.0 - 8.0 * cosim + cosisq * (12.0 + 8.0 * cosim - 10.0 * cosisq));
+
+        let xno2 = nm * nm;
+        let ainv2 = aonv * aonv;
+        let mut temp1 = 3.0 * xno2 * ainv2;
+        let mut temp = temp1 * ROOT22;
+        ds.d2201 = temp * f220 * g201;
+        ds.d2211 = temp * f221 * g211;
+        temp1 *= aonv;
+        temp = temp1 * ROOT32;
+        ds.d3210 = temp * f321 * g310;
+        ds.d3222 = temp * f322 * g322;
+        temp1 *= aonv;
+        temp = 2.0 * temp1 * ROOT44;
+        ds.d4410 = temp * f441 * g410;
+        ds.d4422 = temp * f442 * g422;
+        temp1 *= aonv;
+        temp = temp1 * ROOT52;
+        ds.d5220 = temp * f522 * g520;
+        ds.d5232 = temp * f523 * g532;
+        temp = 2.0 * temp1 * ROOT54;
+        ds.d5421 = temp * f542 * g521;
+        ds.d5433 = temp * f543 * g533;
+        ds.xlamo = (mo + nodeo + nodeo - theta - theta) % TAU;
+        ds.xfact = mdot + ds.dmdt + 2.0 * (nodedot + ds.dnodt - RPTIM) - no;
+    } else {
+        // Synchronous resonance terms
+        let g200 = 1.0 + emsq * (-2.5 + 0.8125 * emsq);
+        let g310 = 1.0 + 2.0 * emsq;
+        let g300 = 1.0 + emsq * (-6.0 + 6.60937 * emsq);
+        let f220 = 0.75 * (1.0 + cosim) * (1.0 + cosim);
+        let f311 = 0.9375 * sinim * sinim * (1.0 + 3.0 * cosim) - 0.75 * (1.0 + cosim);
+        let f330 = 1.875 * (1.0 + cosim).powi(3);
+        let del1 = 3.0 * nm * nm * aonv * aonv;
+        ds.del2 = 2.0 * del1 * f220 * g200 * Q22;
+        ds.del3 = 3.0 * del1 * f330 * g300 * Q33 * aonv;
+        ds.del1 = del1 * f311 * g310 * Q31 * aonv;
+        ds.xlamo = (mo + nodeo + argpo - theta) % TAU;
+        ds.xfact = mdot + xpidot - RPTIM + ds.dmdt + ds.domdt + ds.dnodt - no;
+    }
+}
+
+/// Applies the deep space secular effects and integrates the resonance effects from epoch (Vallado's `dspace`)
+#[allow(clippy::too_many_arguments)]
+fn dspace(
+    ds: &DeepSpace,
+    argpo: f64,
+    argpdot: f64,
+    t: f64,
+    gsto: f64,
+    no: f64,
+    em: &mut f64,
+    argpm: &mut f64,
+    inclm: &mut f64,
+    mm: &mut f64,
+    nodem: &mut f64,
+    nm: &mut f64,
+) {
+    const FASX2: f64 = 0.13130908;
+    const FASX4: f64 = 2.8843198;
+    const FASX6: f64 = 0.37448087;
+    const G22: f64 = 5.7686396;
+    const G32: f64 = 0.95240898;
+    const G44: f64 = 1.8014998;
+    const G52: f64 = 1.0508330;
+    const G54: f64 = 4.4108898;
+    const STEPP: f64 = 720.0;
+    const STEP2: f64 = 259_200.0;
+
+    let theta = (gsto + t * RPTIM) % TAU;
+    *em += ds.dedt * t;
+    *inclm += ds.didt * t;
+    *argpm += ds.domdt * t;
+    *nodem += ds.dnodt * t;
+    *mm += ds.dmdt * t;
+
+    if ds.irez == 0 {
+        return;
+    }
+
+    // The resonance is integrated from epoch with fixed steps of half a day, followed by a Taylor expansion
+    let delt = if t > 0.0 { STEPP } else { -STEPP };
+    let mut atime = 0.0;
+    let mut xni = no;
+    let mut xli = ds.xlamo;
+    let (xndt, xldot, xnddt, ft) = loop {
+        let (xndt, xldot, xnddt);
+        if ds.irez != 2 {
+            // Near synchronous resonance terms
+            xndt = ds.del1 * (xli - FASX2).sin()
+                + ds.del2 * (2.0 * (xli - FASX4)).sin()
+                + ds.del3 * (3.0 * (xli - FASX6)).sin();
+            xldot = xni + ds.xfact;
+            xnddt = (ds.del1 * (xli - FASX2).cos()
+                + 2.0 * ds.del2 * (2.0 * (xli - FASX4)).cos()
+                + 3.0 * ds.del3 * (3.0 * (xli - FASX6)).cos())
+                * xldot;
+        } else {
+            // Near half day resonance terms
+            let xomi = argpo + argpdot * atime;
+            let x2omi = xomi + xomi;
+            let x2li = xli + xli;
+            xndt = ds.d2201 * (x2omi + xli - G22).sin()
+                + ds.d2211 * (xli - G22).sin()
+                + ds.d3210 * (xomi + xli - G32).sin()
+                + ds.d3222 * (-xomi + xli - G32).sin()
+                + ds.d4410 * (x2omi + x2li - G44).sin()
+                + ds.d4422 * (x2li - G44).sin()
+                + ds.d5220 * (xomi + xli - G52).sin()
+                + ds.d5232 * (-xomi + xli - G52).sin()
+                + ds.d5421 * (xomi + x2li - G54).sin()
+                + ds.d5433 * (-xomi + x2li - G54).sin();
+            xldot = xni + ds.xfact;
+            xnddt = (ds.d2201 * (x2omi + xli - G22).cos()
+                + ds.d2211 * (xli - G22).cos()
+                + ds.d3210 * (xomi + xli - G32).cos()
+                + ds.d3222 * (-xomi + xli - G32).cos()
+                + ds.d5220 * (xomi + xli - G52).cos()
+                + ds.d5232 * (-xomi + xli - G52).cos()
+                + 2.0
+                    * (ds.d4410 * (x2omi + x2li - G44).cos()
+                        + ds.d4422 * (x2li - G44).cos()
+                        + ds.d5421 * (xomi + x2li - G54).cos()
+                        + ds.d5433 * (-xomi + x2li - G54).cos()))
+                * xldot;
+        }
+
+        if (t - atime).abs() < STEPP {
+            break (xndt, xldot, xnddt, t - atime);
+        }
+        xli += xldot * delt + xndt * STEP2;
+        xni += xndt * delt + xnddt * STEP2;
+        atime += delt;
+    };
+
+    *nm = xni + xndt * ft + xnddt * ft * ft * 0.5;
+    let xl = xli + xldot * ft + xndt * ft * ft * 0.5;
+    if ds.irez != 1 {
+        *mm = xl - 2.0 * *nodem + 2.0 * theta;
+    } else {
+        *mm = xl - *nodem - *argpm + theta;
+    }
+}
@@ -9,6 +9,7 @@ use crate::cosmic::GuidanceMode;
 pub use crate::cosmic::Orbit;
 pub use crate::cosmic::{DragConfig, Spacecraft, SrpConfig};
 use crate::dynamics::guidance::Thruster;
+use crate::NyxError;
 use std::sync::Arc;
 
 pub(crate) fn register_cosmic(py: Python<'_>, parent_module: &PyModule) -> PyResult<()> {
@@ -42,26 +43,26 @@ impl Frame {
     pub fn is_celestial(&self) -> bool {
         self.inner.is_celestial()
     }
-    pub fn ephem_path(&self) -> Vec<usize> {
-        self.inner.ephem_path()
+    pub fn ephem_path(&self) -> PyResult<Vec<usize>> {
+        Ok(self.inner.try_ephem_path().map_err(NyxError::from)?)
     }
-    pub fn frame_path(&self) -> Vec<usize> {
-        self.inner.frame_path()
+    pub fn frame_path(&self) -> PyResult<Vec<usize>> {
+        Ok(self.inner.try_frame_path().map_err(NyxError::from)?)
     }
-    pub fn gm(&self) -> f64 {
-        self.inner.gm()
+    pub fn gm(&self) -> PyResult<f64> {
+        Ok(self.inner.try_gm().map_err(NyxError::from)?)
     }
-    pub fn equatorial_radius(&self) -> f64 {
-        self.inner.equatorial_radius()
+    pub fn equatorial_radius(&self) -> PyResult<f64> {
+        Ok(self.inner.try_equatorial_radius().map_err(NyxError::from)?)
     }
-    pub fn flattening(&self) -> f64 {
-        self.inner.flattening()
+    pub fn flattening(&self) -> PyResult<f64> {
+        Ok(self.inner.try_flattening().map_err(NyxError::from)?)
     }
-    pub fn semi_major_radius(&self) -> f64 {
-        self.inner.semi_major_radius()
+    pub fn semi_major_radius(&self) -> PyResult<f64> {
+        Ok(self.inner.try_semi_major_radius().map_err(NyxError::from)?)
     }
-    pub fn angular_velocity(&self) -> f64 {
-        self.inner.angular_velocity()
+    pub fn angular_velocity(&self) -> PyResult<f64> {
+        Ok(self.inner.try_angular_velocity().map_err(NyxError::from)?)
     }
 }
 
@@ -49,6 +49,7 @@ impl Spacecraft {
             thruster,
             mode: mode.unwrap_or(GuidanceMode::Coast),
             stm: None,
+            attitude: None,
             srp: srp.unwrap_or_else(|| SrpConfig::default()),
             drag: drag.unwrap_or_else(|| DragConfig::default()),
         }
@@ -0,0 +1,132 @@
+extern crate nyx_space as nyx;
+
+use nyx::cosmic::eclipse::{EclipseLocator, EclipseState};
+use nyx::cosmic::{Bodies, CelestialBody, Cosm, EphemerisSource, LightTimeCalc, Orbit};
+use nyx::dynamics::{AccelModel, OrbitalDynamics, PointMasses};
+use nyx::io::pck::PlanetaryConstants;
+use nyx::propagators::Propagator;
+use nyx::time::{Epoch, Unit};
+use std::sync::Arc;
+
+#[test]
+fn default_bodies() {
+    let cosm = Cosm::de438();
+
+    let bodies = cosm.bodies();
+    for body in &bodies {
+        println!("{body}");
+    }
+    let earth = cosm.try_body("Earth").unwrap();
+    assert_eq!(earth.naif_id, Some(399));
+    assert_eq!(earth.source, EphemerisSource::Xb);
+    assert_eq!(earth.ephem_path, Bodies::Earth.ephem_path());
+    assert_eq!(cosm.try_body("luna").unwrap().naif_id, Some(301));
+    assert_eq!(
+        cosm.try_body_from_naif(4).unwrap().ephem_path,
+        Bodies::MarsBarycenter.ephem_path()
+    );
+    assert!(cosm.try_body("Vulcan").is_err());
+
+    // All of the identifiers of a body lead to the same frame
+    let eme2k = cosm.frame("EME2000");
+    assert_eq!(Bodies::Earth.try_frame(&cosm).unwrap(), eme2k);
+    assert_eq!(earth.try_frame(&cosm).unwrap(), eme2k);
+    assert_eq!("Earth".try_frame(&cosm).unwrap(), eme2k);
+    assert_eq!(cosm.frame("Earth"), eme2k);
+
+    // And they may be mixed in the point masses
+    let by_enum = PointMasses::new(&[Bodies::Sun, Bodies::Luna], cosm.clone());
+    let by_name = PointMasses::new(&["Sun", "Moon"], cosm.clone());
+    assert_eq!(by_enum.bodies, by_name.bodies);
+    assert!(
+        PointMasses::try_with_correction(&["Sun", "Vulcan"], cosm, LightTimeCalc::None).is_err()
+    );
+}
+
+#[test]
+fn custom_bodies() {
+    let mut cosm = Cosm::de438_raw();
+    let start = Epoch::from_gregorian_utc_at_noon(2023, 3, 1);
+
+    // Ganymede is in the planetary constants, so its GM and radii are set when loading its ephemeris
+    let jupiter = cosm.frame("Jupiter Barycenter J2000");
+    let ganymede_orbit =
+        Orbit::keplerian(1_070_400.0, 0.0013, 0.2, 10.0, 20.0, 30.0, start, jupiter);
+    let (_, traj) = Propagator::default(OrbitalDynamics::two_body())
+        .with(ganymede_orbit)
+        .for_duration_with_traj(Unit::Day * 1)
+        .unwrap();
+    let ganymede = cosm.append_body_ephemeris(503, "Ganymede", traj).unwrap();
+    println!("{ganymede}");
+    assert_eq!(ganymede.source, EphemerisSource::Trajectory);
+    let ganymede_frame = cosm.frame("Ganymede");
+    assert_eq!(ganymede_frame.equatorial_radius(), 2631.2);
+    assert!(cosm.try_frame("IAU Ganymede").is_ok());
+
+    // A small body with its own NAIF ID, whose constants are then loaded from a kernel
+    let sun = cosm.frame("Sun J2000");
+    let eros_orbit = Orbit::keplerian(218.1e6, 0.2227, 10.83, 304.3, 178.9, 90.0, start, sun);
+    let (_, traj) = Propagator::default(OrbitalDynamics::two_body())
+        .with(eros_orbit)
+        .for_duration_with_traj(Unit::Day * 1)
+        .unwrap();
+    let eros = cosm.append_body_ephemeris(2_000_433, "Eros", traj).unwrap();
+    assert_eq!(eros.naif_id, Some(2_000_433));
+    assert_eq!(cosm.frame("Eros").gm(), 0.0);
+    cosm.append_pck(
+        PlanetaryConstants::from_text(
+            "\\begindata\nBODY2000433_GM = ( 4.463D-4 )\nBODY2000433_RADII = ( 17.0 5.5 5.5 )\n",
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    let eros_frame = cosm.frame("Eros J2000");
+    assert_eq!(eros_frame.gm(), 4.463e-4);
+    assert_eq!(eros_frame.equatorial_radius(), 17.0);
+    assert_eq!(cosm.try_body("eros").unwrap(), eros);
+    // NAIF IDs are unique
+    let (_, traj) = Propagator::default(OrbitalDynamics::two_body())
+        .with(eros_orbit)
+        .for_duration_with_traj(Unit::Hour * 1)
+        .unwrap();
+    assert!(cosm
+        .append_body_ephemeris(2_000_433, "Eros 2", traj)
+        .is_err());
+
+    let cosm = Arc::new(cosm);
+
+    // A spacecraft behind Ganymede as seen from the Sun
+    let epoch = start + Unit::Hour * 6;
+    let ganymede_pos = cosm
+        .celestial_state(&ganymede.ephem_path, epoch, jupiter, LightTimeCalc::None)
+        .radius();
+    let sun_pos = cosm
+        .celestial_state(
+            Bodies::Sun.ephem_path(),
+            epoch,
+            jupiter,
+            LightTimeCalc::None,
+        )
+        .radius();
+    let sc_pos = ganymede_pos - (sun_pos - ganymede_pos).normalize() * 5_000.0;
+    let sc = Orbit::cartesian(
+        sc_pos[0], sc_pos[1], sc_pos[2], 0.0, 0.0, 10.0, epoch, jupiter,
+    );
+
+    let eclipses = EclipseLocator::try_from_bodies("Sun", &["Ganymede"], cosm.clone()).unwrap();
+    println!("{eclipses}");
+    assert_eq!(eclipses.compute(&sc), EclipseState::Umbra);
+    assert!(EclipseLocator::try_from_bodies(Bodies::Sun, &["Vulcan"], cosm.clone()).is_err());
+
+    // The point mass acceleration of Ganymede
+    let pm = PointMasses::new(&[ganymede.clone()], cosm.clone());
+    println!("{pm}");
+    let accel = pm.eom(&sc).unwrap();
+    let r_j = sc.radius() - ganymede_pos;
+    let gm = ganymede_frame.gm();
+    let expected = -gm * (r_j / r_j.norm().powi(3) + ganymede_pos / ganymede_pos.norm().powi(3));
+    assert!((accel - expected).norm() / expected.norm() < 1e-10);
+    // Mixed with default bodies
+    let pm = PointMasses::new(&[cosm.try_body("Sun").unwrap(), ganymede], cosm);
+    assert_eq!(pm.bodies.len(), 2);
+}
@@ -0,0 +1,132 @@
+extern crate nyx_space as nyx;
+
+use super::daf_writer::{build_bpc, DafArray};
+use nyx::cosmic::{Cosm, Orbit};
+use nyx::od::GroundStation;
+use nyx::time::{Epoch, TimeSeries, Unit};
+
+/// Offset of the prime meridian of the ITRF93 fixture with respect to the IAU Earth frame
+const W_OFFSET_DEG: f64 = 0.01;
+
+/// Builds a binary PCK of ITRF93 which follows the IAU Earth rotation model with the prime meridian offset by `W_OFFSET_DEG`,
+/// from the end of 2019 to 2022 in records of 30 days. It is committed as `data/tests/bpc/itrf93_iau_earth.bpc`.
+fn itrf93_iau_earth_bpc() -> Vec<u8> {
+    let start_et_s = Epoch::from_gregorian_utc_at_midnight(2019, 12, 31).to_et_seconds();
+    let intlen = 30.0 * 86_400.0;
+    let rad = intlen / 2.0;
+    let num_records = 25;
+    let (day_s, century_s) = (86_400.0, 86_400.0 * 36_525.0);
+
+    let mut words = Vec::new();
+    for k in 0..num_records {
+        let mid = start_et_s + (f64::from(k) + 0.5) * intlen;
+        words.extend_from_slice(&[mid, rad]);
+        // The angles are linear in time, so their Chebyshev coefficients are the value at the middle and the rate times the radius.
+        // The Euler angles are the right ascension plus 90 degrees, 90 degrees minus the declination, and the prime meridian.
+        for (at_mid_deg, rate_deg_s) in [
+            (90.0 - 0.641 * mid / century_s, -0.641 / century_s),
+            (0.557 * mid / century_s, 0.557 / century_s),
+            (
+                190.147 + 360.985_623_5 * mid / day_s + W_OFFSET_DEG,
+                360.985_623_5 / day_s,
+            ),
+        ] {
+            words.extend_from_slice(&[at_mid_deg.to_radians(), (rate_deg_s * rad).to_radians()]);
+        }
+    }
+    words.extend_from_slice(&[start_et_s, intlen, 8.0, f64::from(num_records)]);
+
+    build_bpc(&[DafArray::bpc(
+        3000,
+        1,
+        start_et_s,
+        start_et_s + f64::from(num_records) * intlen,
+        words,
+    )])
+}
+
+/// Validates the ITRF93 frame from a binary PCK against the IAU Earth frame, using a small fixture which only differs from
+/// the IAU model by the offset of the prime meridian. The high precision Earth orientation files of NAIF also include
+/// nutation and polar motion, so they match the IAU Earth frame to about a tenth of a degree.
+#[test]
+fn bpc_itrf93_vs_iau_earth() {
+    assert_eq!(
+        std::fs::read("data/tests/bpc/itrf93_iau_earth.bpc").unwrap(),
+        itrf93_iau_earth_bpc()
+    );
+
+    let mut cosm = Cosm::de438_raw();
+    cosm.append_bpc_file("data/tests/bpc/itrf93_iau_earth.bpc")
+        .unwrap();
+
+    let eme2k = cosm.frame("EME2000");
+    let iau_earth = cosm.frame("IAU Earth");
+    let itrf93 = cosm.frame("ITRF93");
+    // Same constants as the J2000 frame
+    assert_eq!(itrf93.gm(), eme2k.gm());
+    assert_eq!(itrf93.equatorial_radius(), eme2k.equatorial_radius());
+    assert_eq!(itrf93.ephem_path(), eme2k.ephem_path());
+
+    let start = Epoch::from_gregorian_utc_at_midnight(2020, 1, 1);
+    let end = Epoch::from_gregorian_utc_at_midnight(2022, 1, 1);
+
+    for epoch in TimeSeries::inclusive(start, end, 7 * Unit::Day) {
+        let dcm = cosm
+            .try_position_dcm_from_to(&iau_earth, &itrf93, epoch)
+            .unwrap();
+        let angle_deg = ((dcm.trace() - 1.0) / 2.0).min(1.0).acos().to_degrees();
+        assert!(
+            (angle_deg - W_OFFSET_DEG).abs() < 1e-6,
+            "{epoch}: {angle_deg} deg"
+        );
+
+        // Round trip through ITRF93
+        let state = Orbit::keplerian(42_164.0, 1e-4, 0.1, 30.0, 40.0, 50.0, epoch, eme2k);
+        let fixed = cosm.frame_chg(&state, itrf93);
+        // A GEO is almost fixed in ITRF93
+        assert!(fixed.vmag_km_s() < 0.05, "{epoch}: {fixed}");
+        let back = cosm.frame_chg(&fixed, eme2k);
+        assert!((back.radius() - state.radius()).norm() < 1e-6);
+        assert!((back.velocity() - state.velocity()).norm() < 1e-9);
+    }
+
+    assert!(cosm
+        .try_position_dcm_from_to(
+            &eme2k,
+            &itrf93,
+            Epoch::from_gregorian_utc_at_midnight(1950, 1, 1)
+        )
+        .is_err());
+}
+
+/// A ground station defined in ITRF93 is the same as one in the IAU Earth frame, east by the offset of the prime meridian
+#[test]
+fn bpc_itrf93_ground_station() {
+    let mut cosm = Cosm::de438_raw();
+    cosm.append_bpc_file("data/tests/bpc/itrf93_iau_earth.bpc")
+        .unwrap();
+    let eme2k = cosm.frame("EME2000");
+
+    let itrf93_gs =
+        GroundStation::from_point("ITRF93".to_string(), 40.0, -3.0, 0.8, cosm.frame("ITRF93"));
+    let iau_gs = GroundStation::from_point(
+        "IAU Earth".to_string(),
+        40.0,
+        -3.0 + W_OFFSET_DEG,
+        0.8,
+        cosm.frame("IAU Earth"),
+    );
+
+    let start = Epoch::from_gregorian_utc_at_midnight(2021, 3, 1);
+    for epoch in TimeSeries::inclusive(start, start + 1 * Unit::Day, 3 * Unit::Hour) {
+        let itrf93_pos = cosm.frame_chg(&itrf93_gs.to_orbit(epoch), eme2k).radius();
+        let iau_pos = cosm.frame_chg(&iau_gs.to_orbit(epoch), eme2k).radius();
+        assert!((itrf93_pos - iau_pos).norm() < 1e-5, "{epoch}");
+
+        let geo = Orbit::keplerian(42_164.0, 1e-4, 0.1, 30.0, 40.0, 50.0, epoch, eme2k);
+        let (itrf93_az, itrf93_el, _, _) = itrf93_gs.azimuth_elevation_of(geo, &cosm);
+        let (iau_az, iau_el, _, _) = iau_gs.azimuth_elevation_of(geo, &cosm);
+        assert!((itrf93_az - iau_az).abs() < 1e-6, "{epoch}");
+        assert!((itrf93_el - iau_el).abs() < 1e-6, "{epoch}");
+    }
+}
@@ -0,0 +1,199 @@
+extern crate nyx_space as nyx;
+
+use nyx::cosmic::conjunction::{
+    ClosestApproachLocator, Conjunction, ConjunctionObject, EncounterPlane, PcMethod,
+};
+use nyx::cosmic::{Cosm, Frame, Orbit, OrbitCovariance};
+use nyx::io::ExportCfg;
+use nyx::linalg::{Matrix2, Vector2, Vector3, Vector6};
+use nyx::md::prelude::*;
+use nyx::md::EventEvaluator;
+use nyx::time::{Epoch, Unit};
+use std::path::PathBuf;
+
+#[test]
+fn conjunction_probability_methods() {
+    // Isotropic covariance with a zero miss distance has a closed form solution
+    let sigma = 0.1;
+    let radius = 0.02;
+    let plane = EncounterPlane {
+        miss_km: Vector2::zeros(),
+        covar: Matrix2::from_diagonal(&Vector2::new(sigma * sigma, sigma * sigma)),
+        hard_body_radius_km: radius,
+    };
+    let expected = 1.0 - (-radius * radius / (2.0 * sigma * sigma)).exp();
+    for (method, pc) in [
+        ("Foster", plane.foster().unwrap()),
+        ("Chan", plane.chan().unwrap()),
+        ("Alfano", plane.alfano().unwrap()),
+    ] {
+        println!("{method}: {pc:e} (expected {expected:e})");
+        assert!((pc - expected).abs() / expected < 1e-8, "{method}");
+    }
+
+    // Elongated and rotated covariance with a miss distance: Foster and Alfano are numerically exact, Chan is an approximation
+    let (sin, cos) = 30.0_f64.to_radians().sin_cos();
+    let rot = Matrix2::new(cos, -sin, sin, cos);
+    let plane = EncounterPlane {
+        miss_km: Vector2::new(0.15, 0.0),
+        covar: rot
+            * Matrix2::from_diagonal(&Vector2::new(0.2_f64.powi(2), 0.05_f64.powi(2)))
+            * rot.transpose(),
+        hard_body_radius_km: 0.01,
+    };
+    let (sigma_major, sigma_minor, _) = plane.principal_axes().unwrap();
+    assert!((sigma_major - 0.2).abs() < 1e-12);
+    assert!((sigma_minor - 0.05).abs() < 1e-12);
+
+    let foster = plane.foster().unwrap();
+    let alfano = plane.alfano().unwrap();
+    let chan = plane.chan().unwrap();
+    println!("Foster: {foster:e}\tAlfano: {alfano:e}\tChan: {chan:e}");
+    assert!(foster > 1e-4 && foster < 1e-2);
+    assert!((foster - alfano).abs() / foster < 1e-6);
+    assert!((foster - chan).abs() / foster < 0.05);
+
+    // Very large miss distances lead to negligible probabilities
+    let far = EncounterPlane {
+        miss_km: Vector2::new(50.0, 0.0),
+        ..plane
+    };
+    assert!(far.foster().unwrap() < 1e-100);
+    assert!(far.chan().unwrap() < 1e-100);
+    assert!(far.alfano().unwrap() < 1e-100);
+
+    // Degenerate covariances are rejected
+    let singular = EncounterPlane {
+        covar: Matrix2::from_diagonal(&Vector2::new(0.01, 0.0)),
+        ..plane
+    };
+    assert!(singular.chan().is_err());
+}
+
+#[test]
+fn conjunction_screening() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2023, 3, 1);
+    let tca = epoch + 3 * Unit::Hour;
+
+    let setup = Propagator::default(OrbitalDynamics::two_body());
+
+    // Build a secondary which crosses the orbit of the primary at a right angle, 50 m above it, at the chosen epoch
+    let primary_init = Orbit::keplerian(7000.0, 1e-3, 51.6, 45.0, 30.0, 10.0, epoch, eme2k);
+    let primary_at_tca = setup.with(primary_init).until_epoch(tca).unwrap();
+    let r_hat = primary_at_tca.radius().normalize();
+    let h_hat = primary_at_tca.hvec().normalize();
+    let offset = 0.05;
+    let secondary_at_tca = Orbit::cartesian_vec(
+        &Vector6::from_iterator(
+            (primary_at_tca.radius() + offset * r_hat)
+                .iter()
+                .chain((primary_at_tca.vmag_km_s() * h_hat).iter())
+                .copied(),
+        ),
+        tca,
+        eme2k,
+    );
+    let secondary_init = setup.with(secondary_at_tca).until_epoch(epoch).unwrap();
+
+    let (_, mut primary_traj) = setup
+        .with(primary_init.with_stm())
+        .for_duration_with_traj(6 * Unit::Hour)
+        .unwrap();
+    primary_traj.name = Some("ASSET".to_string());
+    let (_, mut secondary_traj) = setup
+        .with(secondary_init.with_stm())
+        .for_duration_with_traj(6 * Unit::Hour)
+        .unwrap();
+    secondary_traj.name = Some("DEBRIS".to_string());
+
+    // The locator finds the closest approach with a Brent solver
+    let locator = ClosestApproachLocator::new(&secondary_traj, cosm.clone());
+    let found = locator.find_all(&primary_traj, 5 * Unit::Minute).unwrap();
+    assert!(!found.is_empty());
+    for state in &found {
+        assert!(locator.eval(state).abs() < 1e-6);
+    }
+    let closest = found
+        .iter()
+        .min_by_key(|state| (state.epoch - tca).abs())
+        .unwrap();
+    println!("{}", locator.eval_string(closest));
+    assert!((closest.epoch - tca).abs() < 1 * Unit::Millisecond);
+
+    // Screen with covariances
+    let pos_var = 0.1_f64.powi(2);
+    let vel_var = 1e-5_f64.powi(2);
+    let diag = Vector6::new(pos_var, pos_var, pos_var, vel_var, vel_var, vel_var);
+    let primary = ConjunctionObject::new(
+        &primary_traj,
+        OrbitCovariance::from_diag(primary_init, diag).unwrap(),
+    );
+    let secondary = ConjunctionObject::new(
+        &secondary_traj,
+        OrbitCovariance::from_diag(secondary_init, diag).unwrap(),
+    );
+
+    let conjunctions =
+        Conjunction::screen(&primary, &secondary, 0.02, 1.0, 5 * Unit::Minute, cosm).unwrap();
+    assert!(!conjunctions.is_empty());
+    let conj = conjunctions
+        .iter()
+        .find(|conj| (conj.tca() - tca).abs() < 1 * Unit::Millisecond)
+        .unwrap();
+    println!("{conj}");
+    assert_eq!(conj.primary_name, Some("ASSET".to_string()));
+    assert!((conj.miss_distance_km() - offset).abs() < 1e-6);
+    assert!(
+        (conj.relative_speed_km_s() - primary_at_tca.vmag_km_s() * 2.0_f64.sqrt()).abs() < 1e-3
+    );
+    // The miss is radial, up to the propagation errors of the construction of the secondary
+    let ric = conj.relative_state_ric().unwrap();
+    assert!((ric[0] - offset).abs() < 1e-6);
+    assert!(Vector3::new(ric[1], ric[2], 0.0).norm() < 1e-3);
+    // The covariance has grown along track since the start of the trajectories
+    let primary_ric = conj.primary.covar_in_local_frame(Frame::RIC).unwrap();
+    assert!(primary_ric[(1, 1)] > pos_var);
+
+    // Encounter plane: the miss vector is along the first axis
+    let plane = conj.encounter_plane();
+    assert!((plane.miss_km[0] - offset).abs() < 1e-6);
+    assert!(plane.miss_km[1].abs() < 1e-9);
+
+    let foster = conj.probability(PcMethod::Foster).unwrap();
+    let chan = conj.probability(PcMethod::Chan).unwrap();
+    let alfano = conj.probability(PcMethod::Alfano).unwrap();
+    let samples = 200_000;
+    let mc = conj
+        .probability(PcMethod::MonteCarlo { samples, seed: 0 })
+        .unwrap();
+    let mc_sigma = (foster * (1.0 - foster) / samples as f64).sqrt();
+    println!("Foster: {foster:e}\tChan: {chan:e}\tAlfano: {alfano:e}\tMC: {mc:e} +/- {mc_sigma:e}");
+    assert!(foster > 1e-4);
+    assert!((foster - alfano).abs() / foster < 1e-6);
+    assert!((foster - chan).abs() / foster < 0.1);
+    assert!((mc - foster).abs() < 4.0 * mc_sigma);
+
+    // And export it as a CDM
+    let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "output_data", "conjunction.cdm"]
+        .iter()
+        .collect();
+    let cfg = ExportCfg::from_metadata(vec![("originator".to_string(), "OPS".to_string())]);
+    let path = conj.to_cdm_file(path, PcMethod::Foster, cfg).unwrap();
+    let cdm = std::fs::read_to_string(path).unwrap();
+    for key in [
+        "CCSDS_CDM_VERS = 1.0",
+        "ORIGINATOR = OPS",
+        "TCA = 2023-03-01T0",
+        "MISS_DISTANCE = 50.000 [m]",
+        "COLLISION_PROBABILITY_METHOD = FOSTER-1992",
+        "OBJECT = OBJECT1",
+        "OBJECT_NAME = DEBRIS",
+        "REF_FRAME = EME2000",
+        "CR_R = ",
+        "CNDOT_NDOT = ",
+    ] {
+        assert!(cdm.contains(key), "{key} not in CDM:\n{cdm}");
+    }
+}
@@ -0,0 +1,178 @@
+extern crate nyx_space as nyx;
+
+use nyx::cosmic::{Cosm, Frame, Orbit, OrbitCovariance, KEPLERIAN_COVAR_PARAMS};
+use nyx::linalg::{Matrix6, Vector6};
+use nyx::md::prelude::*;
+use nyx::od::estimate::KfEstimate;
+use nyx::time::{Epoch, Unit};
+use nyx::NyxError;
+
+fn covar_diag() -> Vector6<f64> {
+    // 100 m and 10 cm/s in position and velocity
+    Vector6::new(1e-2, 2e-2, 5e-3, 1e-8, 4e-8, 1e-8)
+}
+
+#[test]
+fn covariance_frames() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let iau_earth = cosm.frame("IAU Earth");
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2022, 3, 1);
+
+    let orbit = Orbit::keplerian(7000.0, 1e-2, 51.6, 45.0, 30.0, 10.0, epoch, eme2k);
+    let cov = OrbitCovariance::from_diag(orbit, covar_diag()).unwrap();
+    assert!((cov.sigmas()[0] - 0.1).abs() < 1e-12);
+
+    // Rotations preserve the trace of the position and velocity blocks
+    let pos_trace = |covar: &Matrix6<f64>| covar.fixed_view::<3, 3>(0, 0).trace();
+    let vel_trace = |covar: &Matrix6<f64>| covar.fixed_view::<3, 3>(3, 3).trace();
+    for local_frame in [Frame::RIC, Frame::VNC, Frame::RCN] {
+        let local = cov.covar_in_local_frame(local_frame).unwrap();
+        assert!((pos_trace(&local) - pos_trace(&cov.covar)).abs() < 1e-14);
+        assert!((vel_trace(&local) - vel_trace(&cov.covar)).abs() < 1e-20);
+
+        let back = OrbitCovariance::from_local_covar(orbit, local_frame, local).unwrap();
+        assert!((back.covar - cov.covar).amax() < 1e-15);
+    }
+
+    // A covariance purely along the radial direction stays in the radial direction
+    let mut ric = Matrix6::zeros();
+    ric[(0, 0)] = 1.0;
+    let radial = OrbitCovariance::from_local_covar(orbit, Frame::RIC, ric).unwrap();
+    let r_hat = orbit.radius() / orbit.rmag_km();
+    let pos_covar = radial.covar.fixed_view::<3, 3>(0, 0);
+    assert!((pos_covar - r_hat * r_hat.transpose()).amax() < 1e-12);
+
+    // In the body fixed frame, the position covariance is rotated but the velocity covariance also includes the rotation of the frame
+    let fixed = cov.try_in_frame(iau_earth, &cosm).unwrap();
+    assert_eq!(fixed.nominal.frame, iau_earth);
+    assert!((pos_trace(&fixed.covar) - pos_trace(&cov.covar)).abs() < 1e-12);
+    assert!(vel_trace(&fixed.covar) > vel_trace(&cov.covar));
+    let back = fixed.try_in_frame(eme2k, &cosm).unwrap();
+    assert!((back.nominal.radius() - orbit.radius()).norm() < 1e-6);
+    assert!((back.covar - cov.covar).amax() < 1e-9);
+
+    // Invalid covariances are rejected
+    let mut asym = Matrix6::from_diagonal(&covar_diag());
+    asym[(0, 1)] = 1e-3;
+    assert!(matches!(
+        OrbitCovariance::new(orbit, asym),
+        Err(NyxError::CovarianceMatrixNotPsd)
+    ));
+    assert!(OrbitCovariance::from_diag(orbit, -covar_diag()).is_err());
+
+    // And the covariance may be initialized from an estimate
+    let estimate = KfEstimate::from_diag(orbit, covar_diag());
+    let from_estimate = OrbitCovariance::from_estimate(&estimate);
+    assert_eq!(from_estimate, cov);
+}
+
+#[test]
+fn covariance_elements() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2022, 3, 1);
+
+    let orbit = Orbit::keplerian(24_400.0, 0.72, 7.0, 110.0, 178.0, 60.0, epoch, eme2k);
+    let cov = OrbitCovariance::from_diag(orbit, covar_diag()).unwrap();
+
+    // Check the hyperdual Jacobian against finite differencing
+    let jac = cov.jacobian(KEPLERIAN_COVAR_PARAMS).unwrap();
+    for j in 0..6 {
+        let pert = if j < 3 { 1e-4 } else { 1e-7 };
+        let mut state = orbit.to_cartesian_vec();
+        state[j] += pert;
+        let plus = Orbit::cartesian_vec(&state, epoch, eme2k);
+        state[j] -= 2.0 * pert;
+        let minus = Orbit::cartesian_vec(&state, epoch, eme2k);
+        for (i, param) in KEPLERIAN_COVAR_PARAMS.iter().enumerate() {
+            let fd = (plus.value(*param).unwrap() - minus.value(*param).unwrap()) / (2.0 * pert);
+            let err = (fd - jac[(i, j)]).abs() / jac[(i, j)].abs().max(1e-6);
+            assert!(err < 1e-4, "d{param:?}/dx{j}: {fd} vs {}", jac[(i, j)]);
+        }
+    }
+
+    // Round trip through the Keplerian and equinoctial elements
+    let kep = cov.keplerian_covar().unwrap();
+    let back = OrbitCovariance::from_keplerian_covar(orbit, kep).unwrap();
+    assert!((back.covar - cov.covar).amax() < 1e-12);
+
+    let sma_sigma = cov.sigma_for(StateParameter::SMA).unwrap();
+    assert!((sma_sigma - kep[(0, 0)].sqrt()).abs() < 1e-12);
+    let eq = cov.equinoctial_covar().unwrap();
+    assert!((eq[(0, 0)] - kep[(0, 0)]).abs() < 1e-12);
+
+    let two = cov
+        .covar_of([StateParameter::Periapsis, StateParameter::Apoapsis])
+        .unwrap();
+    assert_eq!(two.nrows(), 2);
+    assert!((two[(0, 1)] - two[(1, 0)]).abs() < 1e-12);
+
+    // The Keplerian elements are singular for circular equatorial orbits
+    let circ = Orbit::keplerian(7000.0, 0.0, 0.0, 0.0, 0.0, 10.0, epoch, eme2k);
+    assert!(OrbitCovariance::from_keplerian_covar(circ, kep).is_err());
+}
+
+#[test]
+fn covariance_stm_mapping() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2022, 3, 1);
+
+    let orbit = Orbit::keplerian(7000.0, 1e-2, 51.6, 45.0, 30.0, 10.0, epoch, eme2k);
+    let cov = OrbitCovariance::from_diag(orbit, covar_diag()).unwrap();
+
+    let setup = Propagator::default(OrbitalDynamics::two_body());
+    let (last, traj) = setup
+        .with(orbit.with_stm())
+        .for_duration_with_traj(2 * Unit::Hour)
+        .unwrap();
+
+    let mapped = cov.map_along(&traj).unwrap();
+    assert_eq!(mapped.len(), traj.states.len());
+    assert_eq!(mapped[0].covar, cov.covar);
+    let final_cov = cov.mapped_to(&last).unwrap();
+    assert_eq!(mapped.last().unwrap().covar, final_cov.covar);
+
+    // Compare with the finite differenced STM
+    let mut stm_fd = Matrix6::zeros();
+    for j in 0..6 {
+        let pert = if j < 3 { 1e-3 } else { 1e-6 };
+        let mut state = orbit.to_cartesian_vec();
+        state[j] += pert;
+        let plus = setup
+            .with(Orbit::cartesian_vec(&state, epoch, eme2k))
+            .for_duration(2 * Unit::Hour)
+            .unwrap();
+        state[j] -= 2.0 * pert;
+        let minus = setup
+            .with(Orbit::cartesian_vec(&state, epoch, eme2k))
+            .for_duration(2 * Unit::Hour)
+            .unwrap();
+        let col = (plus.to_cartesian_vec() - minus.to_cartesian_vec()) / (2.0 * pert);
+        for i in 0..6 {
+            stm_fd[(i, j)] = col[i];
+        }
+    }
+    let expected = stm_fd * cov.covar * stm_fd.transpose();
+    let rel_err = (final_cov.covar - expected).amax() / expected.amax();
+    assert!(rel_err < 1e-4, "{rel_err}");
+
+    // The uncertainty grows mostly along track
+    let ric = final_cov.covar_in_local_frame(Frame::RIC).unwrap();
+    println!("{final_cov}\nRIC covariance: {ric:.3e}");
+    assert!(ric[(1, 1)] > ric[(0, 0)]);
+    assert!(ric[(1, 1)] > cov.covar_in_local_frame(Frame::RIC).unwrap()[(1, 1)]);
+
+    // The trajectory must start at the epoch of the covariance, and have an STM
+    let later = OrbitCovariance::from_diag(last, covar_diag()).unwrap();
+    assert!(later.map_along(&traj).is_err());
+    let (_, no_stm) = setup
+        .with(orbit)
+        .for_duration_with_traj(10 * Unit::Minute)
+        .unwrap();
+    assert!(matches!(
+        cov.map_along(&no_stm),
+        Err(NyxError::StateTransitionMatrixUnset)
+    ));
+}
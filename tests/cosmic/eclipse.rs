@@ -1,8 +1,9 @@
 extern crate nyx_space as nyx;
 
-use nyx::cosmic::eclipse::{EclipseLocator, EclipseState};
+use nyx::cosmic::eclipse::{EclipseLocator, EclipseState, ShadowBoundary};
 use nyx::cosmic::{Bodies, Cosm, Orbit};
 use nyx::dynamics::orbital::OrbitalDynamics;
+use nyx::md::EventEvaluator;
 use nyx::propagators::{PropOpts, Propagator};
 use nyx::time::{Epoch, Unit};
 use std::sync::mpsc;
@@ -35,11 +36,7 @@ fn leo_sun_earth_eclipses() {
     });
 
     // Initialize the EclipseLocator
-    let e_loc = EclipseLocator {
-        light_source: cosm.frame("Sun J2000"),
-        shadow_bodies: vec![eme2k],
-        cosm,
-    };
+    let e_loc = EclipseLocator::new(cosm.frame("Sun J2000"), vec![eme2k], cosm);
 
     // Receive the states on the main thread.
     let mut prev_eclipse_state = EclipseState::Umbra;
@@ -84,11 +81,7 @@ fn geo_sun_earth_eclipses() {
     });
 
     // Initialize the EclipseLocator
-    let e_loc = EclipseLocator {
-        light_source: cosm.frame("Sun J2000"),
-        shadow_bodies: vec![eme2k],
-        cosm,
-    };
+    let e_loc = EclipseLocator::new(cosm.frame("Sun J2000"), vec![eme2k], cosm);
 
     // Receive the states on the main thread.
     let mut prev_eclipse_state = EclipseState::Umbra;
@@ -104,3 +97,80 @@ fn geo_sun_earth_eclipses() {
 
     assert_eq!(cnt_changes, 15, "wrong number of eclipse state changes");
 }
+
+#[test]
+fn leo_oblate_eclipse_arcs() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let start_time = Epoch::from_gregorian_tai_at_midnight(2020, 6, 1);
+    let leo = Orbit::keplerian(7000.0, 1e-3, 80.0, 30.0, 0.0, 0.0, start_time, eme2k);
+
+    let (_, traj) = Propagator::default(OrbitalDynamics::two_body())
+        .with(leo)
+        .for_duration_with_traj(6 * Unit::Hour)
+        .unwrap();
+
+    let sphere = EclipseLocator::new(cosm.frame("Sun J2000"), vec![eme2k], cosm.clone());
+    let oblate = sphere.clone().with_oblate_bodies();
+    let atmosphere = oblate.clone().with_earth_atmosphere(50.0);
+
+    let sphere_arcs = sphere.find_eclipses(&traj, 10 * Unit::Second).unwrap();
+    let oblate_arcs = oblate.find_eclipses(&traj, 10 * Unit::Second).unwrap();
+    let atmosphere_arcs = atmosphere.find_eclipses(&traj, 10 * Unit::Second).unwrap();
+
+    // About one eclipse per orbit
+    assert!(sphere_arcs.len() >= 3);
+    assert_eq!(sphere_arcs.len(), oblate_arcs.len());
+    assert_eq!(sphere_arcs.len(), atmosphere_arcs.len());
+
+    let penumbra = oblate.to_boundary_event(ShadowBoundary::Penumbra);
+    let umbra = oblate.to_boundary_event(ShadowBoundary::Umbra);
+    for (i, arc) in oblate_arcs.iter().enumerate() {
+        println!("{arc}");
+        let (umbra_entry, umbra_exit) = arc.umbra.unwrap();
+        assert!(arc.penumbra_entry < umbra_entry);
+        assert!(umbra_entry < umbra_exit);
+        assert!(umbra_exit < arc.penumbra_exit);
+
+        // The epochs are exactly on the boundaries of the shadow
+        for (event, epoch) in [
+            (&penumbra, arc.penumbra_entry),
+            (&penumbra, arc.penumbra_exit),
+            (&umbra, umbra_entry),
+            (&umbra, umbra_exit),
+        ] {
+            let state = traj.at(epoch).unwrap();
+            assert!(
+                event.eval(&state).abs() < 1e-8,
+                "{}",
+                event.eval_string(&state)
+            );
+        }
+        let just_before = traj.at(arc.penumbra_entry - 1 * Unit::Second).unwrap();
+        assert_eq!(oblate.compute(&just_before), EclipseState::Visibilis);
+        let in_umbra = traj.at(umbra_entry + 1 * Unit::Second).unwrap();
+        assert_eq!(oblate.compute(&in_umbra), EclipseState::Umbra);
+        let in_penumbra = traj.at(umbra_exit + 1 * Unit::Second).unwrap();
+        assert!(matches!(
+            oblate.compute(&in_penumbra),
+            EclipseState::Penumbra(_)
+        ));
+
+        // The shadow of the oblate Earth is smaller than that of the spherical Earth, and the atmosphere inflates it
+        assert!(arc.duration() < sphere_arcs[i].duration());
+        assert!(arc.umbra_duration() < sphere_arcs[i].umbra_duration());
+        assert!(arc.duration() < atmosphere_arcs[i].duration());
+        assert!(arc.penumbra_duration() > 0 * Unit::Second);
+    }
+
+    // The durations match a fine sampling of the eclipse state
+    let arc = sphere_arcs[1];
+    let in_eclipse = traj
+        .every(1 * Unit::Second)
+        .filter(|state| sphere.compute(state) != EclipseState::Visibilis)
+        .filter(|state| state.epoch >= sphere_arcs[0].penumbra_exit)
+        .filter(|state| state.epoch <= sphere_arcs[2].penumbra_entry)
+        .count();
+    assert!((arc.duration().to_seconds() - in_eclipse as f64).abs() <= 1.0);
+}
@@ -0,0 +1,268 @@
+extern crate nyx_space as nyx;
+
+use nyx::cosmic::{Cosm, Orbit, OrbitDual};
+use nyx::mc::GaussianGenerator;
+use nyx::md::optimizer::*;
+use nyx::md::prelude::*;
+use nyx::utils::between_pm_180;
+use rand_distr::Distribution;
+use rand_pcg::Pcg64Mcg;
+
+const ELEMENT_PARAMS: [StateParameter; 9] = [
+    StateParameter::EquinoctialH,
+    StateParameter::EquinoctialK,
+    StateParameter::EquinoctialP,
+    StateParameter::EquinoctialQ,
+    StateParameter::MeanLongitude,
+    StateParameter::DelaunayL,
+    StateParameter::DelaunayG,
+    StateParameter::DelaunayH,
+    StateParameter::VelocityAzimuth,
+];
+
+fn assert_same_state(orbit: &Orbit, expected: &Orbit, what: &str) {
+    let r_err = (orbit.radius() - expected.radius()).norm();
+    let v_err = (orbit.velocity() - expected.velocity()).norm();
+    assert!(r_err < 1e-7, "{what}: {r_err} km");
+    assert!(v_err < 1e-10, "{what}: {v_err} km/s");
+}
+
+#[test]
+fn element_sets_round_trip() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2021, 6, 1);
+
+    let orbits = [
+        Orbit::keplerian(8_000.0, 0.2, 30.0, 60.0, 60.0, 45.0, epoch, eme2k),
+        Orbit::keplerian(26_554.0, 0.72, 63.4, 280.0, 270.0, 190.0, epoch, eme2k),
+        // Near circular and near equatorial, where the AoP and RAAN are ill-defined
+        Orbit::keplerian(42_164.0, 1e-9, 1e-9, 10.0, 20.0, 30.0, epoch, eme2k),
+        Orbit::cartesian(42_164.0, 0.0, 0.0, 0.0, 3.074_66, 0.0, epoch, eme2k),
+    ];
+
+    for orbit in &orbits {
+        let eq = orbit.to_equinoctial_vec();
+        let from_eq =
+            Orbit::equinoctial(eq[0], eq[1], eq[2], eq[3], eq[4], eq[5], epoch, eme2k).unwrap();
+        assert_same_state(&from_eq, orbit, "equinoctial");
+
+        let mee = orbit.to_modified_equinoctial_vec();
+        let from_mee = Orbit::modified_equinoctial(
+            mee[0], mee[1], mee[2], mee[3], mee[4], mee[5], epoch, eme2k,
+        )
+        .unwrap();
+        assert_same_state(&from_mee, orbit, "modified equinoctial");
+
+        let sph = orbit.to_spherical_vec();
+        let from_sph =
+            Orbit::spherical(sph[0], sph[1], sph[2], sph[3], sph[4], sph[5], epoch, eme2k);
+        assert_same_state(&from_sph, orbit, "spherical");
+
+        // All of these are finite, unlike the Keplerian angles of the singular orbits
+        for param in ELEMENT_PARAMS {
+            assert!(orbit.value(param).unwrap().is_finite(), "{param}");
+        }
+    }
+
+    // Delaunay elements are singular like the Keplerian elements, so only check them on the regular orbits
+    for orbit in &orbits[..2] {
+        let del = orbit.to_delaunay_vec();
+        let from_del =
+            Orbit::delaunay(del[0], del[1], del[2], del[3], del[4], del[5], epoch, eme2k).unwrap();
+        assert_same_state(&from_del, orbit, "Delaunay");
+    }
+
+    // The modified equinoctial elements also support hyperbolic orbits
+    let hyperbola = Orbit::keplerian(-20_000.0, 1.5, 20.0, 30.0, 40.0, 20.0, epoch, eme2k);
+    let mee = hyperbola.to_modified_equinoctial_vec();
+    let from_mee =
+        Orbit::modified_equinoctial(mee[0], mee[1], mee[2], mee[3], mee[4], mee[5], epoch, eme2k)
+            .unwrap();
+    assert_same_state(&from_mee, &hyperbola, "hyperbolic modified equinoctial");
+    assert!(Orbit::equinoctial(-20_000.0, 1.0, 0.5, 0.0, 0.0, 0.0, epoch, eme2k).is_err());
+    assert!(Orbit::delaunay(1.0, 2.0, 0.0, 0.0, 0.0, 0.0, epoch, eme2k).is_err());
+}
+
+#[test]
+fn element_sets_definitions() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2021, 6, 1);
+
+    let orbit = Orbit::keplerian(8_000.0, 0.2, 30.0, 60.0, 60.0, 45.0, epoch, eme2k);
+    let lon_peri = (orbit.aop_deg() + orbit.raan_deg()).to_radians();
+    let tan_half_inc = (orbit.inc_deg().to_radians() / 2.0).tan();
+    let raan = orbit.raan_deg().to_radians();
+
+    assert!((orbit.equinoctial_h() - orbit.ecc() * lon_peri.sin()).abs() < 1e-12);
+    assert!((orbit.equinoctial_k() - orbit.ecc() * lon_peri.cos()).abs() < 1e-12);
+    assert!((orbit.equinoctial_p() - tan_half_inc * raan.sin()).abs() < 1e-12);
+    assert!((orbit.equinoctial_q() - tan_half_inc * raan.cos()).abs() < 1e-12);
+    let mean_lon = orbit.ma_deg() + orbit.aop_deg() + orbit.raan_deg();
+    assert!(between_pm_180(orbit.mean_longitude_deg() - mean_lon).abs() < 1e-9);
+
+    let mee = orbit.to_modified_equinoctial_vec();
+    assert!((mee[0] - orbit.semi_parameter_km()).abs() < 1e-8);
+    assert_eq!(mee[1], orbit.equinoctial_k());
+    assert_eq!(mee[2], orbit.equinoctial_h());
+    assert_eq!(mee[3], orbit.equinoctial_q());
+    assert_eq!(mee[4], orbit.equinoctial_p());
+    assert!(between_pm_180(mee[5] - orbit.tlong_deg()).abs() < 1e-9);
+
+    let gm = eme2k.gm();
+    let l = (gm * orbit.sma_km()).sqrt();
+    assert!((orbit.delaunay_l_km2_s() - l).abs() < 1e-9);
+    let g = l * (1.0 - orbit.ecc().powi(2)).sqrt();
+    assert!((orbit.delaunay_g_km2_s() - g).abs() < 1e-8);
+    assert!((orbit.delaunay_h_km2_s() - g * orbit.inc_deg().to_radians().cos()).abs() < 1e-8);
+
+    // Flight path angle, from its definition with the true anomaly
+    let ta = orbit.ta_deg().to_radians();
+    let fpa = (orbit.ecc() * ta.sin()).atan2(1.0 + orbit.ecc() * ta.cos());
+    assert!((orbit.fpa_deg() - fpa.to_degrees()).abs() < 1e-10);
+
+    // A circular equatorial prograde orbit flies due east
+    let geo = Orbit::keplerian(42_164.0, 0.0, 0.0, 0.0, 0.0, 0.0, epoch, eme2k);
+    assert!((geo.velocity_azimuth_deg() - 90.0).abs() < 1e-10);
+    assert!(geo.fpa_deg().abs() < 1e-10);
+
+    // Setting a single element keeps the others of the same set
+    let mut changed = orbit;
+    changed
+        .set_value(StateParameter::EquinoctialH, 0.1)
+        .unwrap();
+    let (before, after) = (orbit.to_equinoctial_vec(), changed.to_equinoctial_vec());
+    assert!((after[1] - 0.1).abs() < 1e-12);
+    for i in [0, 2, 3, 4] {
+        assert!((after[i] - before[i]).abs() < 1e-9 * before[i].abs().max(1.0));
+    }
+    assert!(between_pm_180(after[5] - before[5]).abs() < 1e-9);
+
+    let mut changed = orbit;
+    changed
+        .set_value(StateParameter::VelocityAzimuth, 45.0)
+        .unwrap();
+    assert!((changed.velocity_azimuth_deg() - 45.0).abs() < 1e-10);
+    assert!((changed.rmag_km() - orbit.rmag_km()).abs() < 1e-9);
+    assert!((changed.fpa_deg() - orbit.fpa_deg()).abs() < 1e-10);
+
+    let mut changed = orbit;
+    changed
+        .set_value(StateParameter::DelaunayH, 0.5 * g)
+        .unwrap();
+    assert!((changed.inc_deg() - 60.0).abs() < 1e-9);
+    assert!((changed.sma_km() - orbit.sma_km()).abs() < 1e-8);
+}
+
+#[test]
+fn element_sets_partials() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2021, 6, 1);
+
+    let orbit = Orbit::keplerian(8_000.0, 0.2, 30.0, 60.0, 60.0, 45.0, epoch, eme2k);
+    let dual = OrbitDual::from(orbit);
+
+    for param in ELEMENT_PARAMS {
+        let partial = dual.partial_for(param).unwrap();
+        assert!((partial.real() - orbit.value(param).unwrap()).abs() < 1e-9);
+
+        // Compare the partials with respect to the velocity to central finite differences
+        let step = 1e-6;
+        let mut fd = [0.0; 3];
+        for (i, fd_i) in fd.iter_mut().enumerate() {
+            let mut plus = orbit;
+            let mut minus = orbit;
+            match i {
+                0 => {
+                    plus.vx_km_s += step;
+                    minus.vx_km_s -= step;
+                }
+                1 => {
+                    plus.vy_km_s += step;
+                    minus.vy_km_s -= step;
+                }
+                _ => {
+                    plus.vz_km_s += step;
+                    minus.vz_km_s -= step;
+                }
+            }
+            *fd_i = (plus.value(param).unwrap() - minus.value(param).unwrap()) / (2.0 * step);
+        }
+        let analytical = [partial.wtr_vx(), partial.wtr_vy(), partial.wtr_vz()];
+        for (fd_i, an_i) in fd.iter().zip(analytical) {
+            assert!(
+                (fd_i - an_i).abs() < 1e-5 * fd_i.abs().max(1.0),
+                "{param}: {fd_i} vs {an_i}"
+            );
+        }
+    }
+}
+
+#[test]
+fn element_sets_events_and_targeting() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2021, 6, 1);
+
+    let orbit = Orbit::keplerian(8_000.0, 0.2, 30.0, 60.0, 60.0, 0.0, epoch, eme2k);
+
+    // The mean longitude increases linearly in the two body problem
+    let setup = Propagator::default(OrbitalDynamics::two_body());
+    let (_, traj) = setup
+        .with(orbit)
+        .for_duration_with_traj(orbit.period())
+        .unwrap();
+    let target = between_pm_180(orbit.mean_longitude_deg() + 90.0).rem_euclid(360.0);
+    let found = traj
+        .find_all(&Event::new(StateParameter::MeanLongitude, target))
+        .unwrap();
+    assert_eq!(found.len(), 1);
+    let expected = epoch + orbit.period() / 4.0;
+    assert!((found[0].epoch - expected).abs() < 1 * Unit::Second);
+
+    // Target an equinoctial element with the hyperdual targeter
+    let spacecraft = Spacecraft::new(orbit.with_stm(), 100.0, 0.0, 0.0, 0.0, 0.0, 0.0);
+    let setup = Propagator::default_dp78(SpacecraftDynamics::new(OrbitalDynamics::two_body()));
+    let desired = orbit.equinoctial_k() + 0.01;
+    let objectives = [Objective::new(StateParameter::EquinoctialK, desired)];
+    let tgt = Optimizer::delta_v(&setup, objectives);
+    let target_epoch = epoch + orbit.period() / 10.0;
+    let solution = tgt
+        .try_achieve_dual(spacecraft, epoch, target_epoch)
+        .unwrap();
+    println!("{solution}");
+    assert!((solution.achieved_state.orbit.equinoctial_k() - desired).abs() < 1e-5);
+
+    // And disperse them
+    let generator =
+        GaussianGenerator::from_std_dev(orbit, StateParameter::EquinoctialH, 1e-3).unwrap();
+    for dispersed in generator.sample_iter(Pcg64Mcg::new(0)).take(10) {
+        let delta = dispersed.state.equinoctial_h() - orbit.equinoctial_h();
+        assert!((delta - dispersed.actual_dispersions[0].1).abs() < 1e-9);
+        assert!((dispersed.state.sma_km() - orbit.sma_km()).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn fpa_eccentric_orbit() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2021, 6, 1);
+
+    // At a true anomaly of 90 degrees, tan(fpa) = e sin(ta) / (1 + e cos(ta)) = e
+    let orbit = Orbit::keplerian(10_000.0, 0.5, 30.0, 60.0, 60.0, 90.0, epoch, eme2k);
+    let expected = 0.5_f64.atan().to_degrees();
+    assert!((expected - 26.565_051_177_077_99).abs() < 1e-12);
+    assert!((orbit.fpa_deg() - expected).abs() < 1e-10);
+
+    let dual = OrbitDual::from(orbit)
+        .partial_for(StateParameter::FlightPathAngle)
+        .unwrap();
+    assert!((dual.real() - expected).abs() < 1e-10);
+
+    // And the spacecraft flies downward after the apoapsis
+    let orbit = Orbit::keplerian(10_000.0, 0.5, 30.0, 60.0, 60.0, 270.0, epoch, eme2k);
+    assert!((orbit.fpa_deg() + expected).abs() < 1e-10);
+}
@@ -0,0 +1,66 @@
+extern crate nyx_space as nyx;
+
+use nyx::cosmic::{Cosm, Orbit};
+use nyx::io::eop::{EarthOrientationParams, EopEntry};
+use nyx::time::{Epoch, TimeSeries, Unit};
+
+#[test]
+fn eop_itrf_frame() {
+    let mut cosm = Cosm::de438_raw();
+
+    // Two days of EOP from the IERS 14 C04 series
+    let eop = EarthOrientationParams::from_entries(vec![
+        EopEntry {
+            mjd_utc: 53101.0,
+            x_pole_arcsec: -0.140682,
+            y_pole_arcsec: 0.333309,
+            ut1_utc_s: -0.4399619,
+            dx_arcsec: -0.000205,
+            dy_arcsec: -0.000136,
+        },
+        EopEntry {
+            mjd_utc: 53103.0,
+            x_pole_arcsec: -0.137716,
+            y_pole_arcsec: 0.334136,
+            ut1_utc_s: -0.4431216,
+            dx_arcsec: -0.000196,
+            dy_arcsec: -0.000142,
+        },
+    ])
+    .unwrap();
+
+    let eop_copy = eop.clone();
+    let itrf = cosm.append_eop(eop).unwrap();
+    assert_eq!(cosm.frame("ITRF"), itrf);
+
+    let eme2k = cosm.frame("EME2000");
+    let iau_earth = cosm.frame("IAU Earth");
+    assert_eq!(itrf.gm(), eme2k.gm());
+    assert_eq!(itrf.ephem_path(), eme2k.ephem_path());
+
+    // The velocity transformation requires the orientation one second before and after the epoch
+    let start = Epoch::from_mjd_utc(53101.0) + Unit::Second;
+    for epoch in TimeSeries::inclusive(start, start + 47 * Unit::Hour, 3 * Unit::Hour) {
+        // The IAU Earth frame ignores nutation, UT1 and polar motion
+        let dcm = cosm
+            .try_position_dcm_from_to(&iau_earth, &itrf, epoch)
+            .unwrap();
+        let angle_deg = ((dcm.trace() - 1.0) / 2.0).min(1.0).acos().to_degrees();
+        assert!(angle_deg < 0.1, "{epoch}: {angle_deg} deg");
+
+        let state = Orbit::keplerian(42_164.0, 1e-4, 0.1, 30.0, 40.0, 50.0, epoch, eme2k);
+        let fixed = cosm.frame_chg(&state, itrf);
+        // A GEO is almost fixed in the ITRF
+        assert!(fixed.vmag_km_s() < 0.05, "{epoch}: {fixed}");
+        let back = cosm.frame_chg(&fixed, eme2k);
+        assert!((back.radius() - state.radius()).norm() < 1e-6);
+        assert!((back.velocity() - state.velocity()).norm() < 1e-9);
+    }
+
+    // Outside of the EOP data
+    assert!(cosm
+        .try_position_dcm_from_to(&eme2k, &itrf, start + 3 * Unit::Day)
+        .is_err());
+    // Cannot be added twice
+    assert!(cosm.append_eop(eop_copy).is_err());
+}
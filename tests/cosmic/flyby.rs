@@ -0,0 +1,114 @@
+extern crate nyx_space as nyx;
+
+use nalgebra::Rotation3;
+use nyx::cosmic::{try_achieve_b_plane, Cosm, Flyby, Orbit};
+use nyx::dynamics::OrbitalDynamics;
+use nyx::linalg::Vector3;
+use nyx::propagators::Propagator;
+use nyx::time::{Epoch, Unit};
+
+/// Returns the direction of the outgoing (or incoming) asymptote of a hyperbolic orbit
+fn asymptote(orbit: &Orbit, outgoing: bool) -> Vector3<f64> {
+    let ecc = orbit.ecc();
+    let e_hat = orbit.evec().normalize();
+    let n_hat = orbit.hvec().normalize().cross(&e_hat);
+    let sign = if outgoing { -1.0 } else { 1.0 };
+    sign * e_hat / ecc + (1.0 - 1.0 / ecc.powi(2)).sqrt() * n_hat
+}
+
+#[test]
+fn unpowered_flyby() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2023, 3, 1);
+    let gm = eme2k.gm();
+
+    let vinf_in = Vector3::new(3.0, 1.0, 0.5);
+    let turn_deg: f64 = 40.0;
+    let axis = Vector3::new(0.2, -0.4, 1.0).cross(&vinf_in).normalize();
+    let vinf_out = Rotation3::new(axis * turn_deg.to_radians()) * vinf_in;
+
+    let flyby = Flyby::new(vinf_in, vinf_out, eme2k).unwrap();
+    println!("{flyby}");
+    assert!((flyby.turn_angle_deg() - turn_deg).abs() < 1e-10);
+
+    let sol = flyby.solve().unwrap();
+    println!("{sol}");
+    // Classical unpowered flyby relation
+    let vinf_sq = vinf_in.norm_squared();
+    let expected_rp = gm / vinf_sq * (1.0 / (0.5 * turn_deg.to_radians()).sin() - 1.0);
+    assert!((sol.periapsis_km - expected_rp).abs() < 1e-6);
+    assert!(sol.dv_km_s() < 1e-12);
+    assert!(sol.altitude_km().unwrap() > 0.0);
+
+    // The periapsis state has the expected B-Plane and asymptotes
+    let periapsis = sol.periapsis_in(epoch);
+    assert!((periapsis.rmag_km() - sol.periapsis_km).abs() < 1e-6);
+    let b_plane = periapsis.b_plane().unwrap();
+    println!("{b_plane}");
+    assert!((b_plane.b_dot_t() - sol.b_plane_target.b_t_km).abs() < 1e-6);
+    assert!((b_plane.b_dot_r() - sol.b_plane_target.b_r_km).abs() < 1e-6);
+    assert!((asymptote(&periapsis, false) - vinf_in.normalize()).norm() < 1e-9);
+    assert!((asymptote(&periapsis, true) - vinf_out.normalize()).norm() < 1e-9);
+
+    // Target that B-Plane from a perturbed approach
+    let (approach, _) = Propagator::default(OrbitalDynamics::two_body())
+        .with(periapsis)
+        .for_duration_with_traj(-1 * Unit::Day)
+        .unwrap();
+    let mut perturbed = approach;
+    perturbed.vx_km_s += 1e-3;
+    perturbed.vz_km_s -= 1e-3;
+    let (dv, achieved) = try_achieve_b_plane(perturbed, sol.b_plane_target).unwrap();
+    println!("{dv} km/s leads to {achieved}");
+    assert!((achieved.b_dot_t() - sol.b_plane_target.b_t_km).abs() < 1e-3);
+    assert!((achieved.b_dot_r() - sol.b_plane_target.b_r_km).abs() < 1e-3);
+    // The least squares correction is at most as large as the perturbation
+    assert!(dv.norm() < 1.01 * 2.0_f64.sqrt() * 1e-3);
+
+    // Impossible turns
+    assert!(Flyby::new(vinf_in, -vinf_in, eme2k)
+        .unwrap()
+        .solve()
+        .is_err());
+    assert!(Flyby::new(vinf_in, vinf_in, eme2k)
+        .unwrap()
+        .solve()
+        .is_err());
+    assert!(Flyby::new(Vector3::zeros(), vinf_in, eme2k).is_err());
+}
+
+#[test]
+fn powered_flyby() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2023, 3, 1);
+    let gm = eme2k.gm();
+
+    let vinf_in = Vector3::new(-2.0, 4.0, 1.0);
+    let axis = Vector3::z().cross(&vinf_in).normalize();
+    let vinf_out = 1.2 * (Rotation3::new(axis * 60.0_f64.to_radians()) * vinf_in);
+
+    let flyby = Flyby::new(vinf_in, vinf_out, eme2k).unwrap();
+    let sol = flyby.solve().unwrap();
+    println!("{sol}");
+    assert!((flyby.vinf_change_km_s() - 0.2 * vinf_in.norm()).abs() < 1e-12);
+
+    // The half turns of both hyperbolas add up to the turn angle
+    let half_turns = (1.0 / sol.ecc_in).asin() + (1.0 / sol.ecc_out).asin();
+    assert!((half_turns.to_degrees() - 60.0).abs() < 1e-9);
+
+    let before = sol.periapsis_in(epoch);
+    let after = sol.periapsis_out(epoch);
+    // The maneuver is tangential at periapsis
+    let dv = after.velocity() - before.velocity();
+    assert!((dv.norm() - sol.dv_km_s()).abs() < 1e-12);
+    assert!(dv.dot(&before.radius()).abs() < 1e-6);
+
+    // Each hyperbola has the requested excess velocity
+    for (orbit, vinf, outgoing) in [(before, vinf_in, false), (after, vinf_out, true)] {
+        let vinf_mag = (orbit.vmag_km_s().powi(2) - 2.0 * gm / orbit.rmag_km()).sqrt();
+        assert!((vinf_mag - vinf.norm()).abs() < 1e-9);
+        assert!((asymptote(&orbit, outgoing) - vinf.normalize()).norm() < 1e-9);
+    }
+}
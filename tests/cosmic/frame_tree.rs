@@ -0,0 +1,355 @@
+extern crate nyx_space as nyx;
+
+use super::daf_writer::{build_bpc, DafArray};
+use nyx::cosmic::{Cosm, Frame, FrameError, Orbit, MAX_TREE_DEPTH};
+use nyx::io::bpc::Bpc;
+use nyx::io::eop::{EarthOrientationParams, EopEntry};
+use nyx::time::Epoch;
+use nyx::NyxError;
+
+#[test]
+fn deep_frame_tree() {
+    let mut cosm = Cosm::de438_raw();
+
+    // Each frame is twisted by ten degrees about the Z axis of its parent
+    let twist_toml = |name: &str, parent: &str| {
+        format!(
+            r#"
+            [frames.{name}]
+            inherit = "{parent}"
+            gm = -1
+            flattening = -1
+            equatorial_radius = -1
+            semi_major_radius = -1
+            [frames.{name}.rotation]
+            right_asc = "-90.0"
+            declin = "90.0"
+            w = "10.0"
+            angle_unit = "degrees"
+            "#
+        )
+    };
+    let mut parent = "Moon J2000".to_string();
+    for level in 1..=5 {
+        let name = format!("moon_twist_{level}");
+        cosm.append_frames(&twist_toml(&name, &parent)).unwrap();
+        parent = name.replace('_', " ");
+    }
+
+    let moon_j2k = cosm.frame("Moon J2000");
+    let twist1 = cosm.frame("moon twist 1");
+    let twist5 = cosm.frame("moon twist 5");
+    assert_eq!(twist5.frame_path().len(), 6);
+    assert_eq!(twist5.to_string(), "moon twist 5");
+    assert_eq!(twist5.ephem_path(), moon_j2k.ephem_path());
+    assert_eq!(twist5.gm(), moon_j2k.gm());
+    assert_eq!(cosm.frame_from_frame_path(&twist5.frame_path()), twist5);
+    assert!(cosm.try_frame_from_frame_path(&[0, 99]).is_err());
+
+    let epoch = Epoch::from_gregorian_tai_at_noon(2022, 3, 1);
+    let dcm1 = cosm
+        .try_position_dcm_from_to(&moon_j2k, &twist1, epoch)
+        .unwrap();
+    let dcm5 = cosm
+        .try_position_dcm_from_to(&moon_j2k, &twist5, epoch)
+        .unwrap();
+    assert!((dcm5 - dcm1.pow(5)).norm() < 1e-12);
+
+    // Translation into the deepest frame from another ephemeris, and back
+    let eme2k = cosm.frame("EME2000");
+    let state = Orbit::keplerian(7_000.0, 0.01, 30.0, 40.0, 50.0, 60.0, epoch, eme2k);
+    let deep = cosm.frame_chg(&state, twist5);
+    assert_eq!(deep.frame, twist5);
+    let in_moon_j2k = cosm.frame_chg(&state, moon_j2k);
+    assert!((deep.radius() - dcm5 * in_moon_j2k.radius()).norm() < 1e-6);
+    let back = cosm.frame_chg(&deep, eme2k);
+    assert!((back.radius() - state.radius()).norm() < 1e-6);
+    assert!((back.velocity() - state.velocity()).norm() < 1e-9);
+
+    let llo = Orbit::keplerian(1_900.0, 0.01, 80.0, 40.0, 50.0, 60.0, epoch, moon_j2k);
+    let llo_deep = cosm.frame_chg(&llo, twist5);
+    assert!((llo_deep.radius() - dcm5 * llo.radius()).norm() < 1e-6);
+    assert!((llo_deep.rmag_km() - llo.rmag_km()).abs() < 1e-9);
+    let back = cosm.frame_chg(&llo_deep, moon_j2k);
+    assert!((back.radius() - llo.radius()).norm() < 1e-6);
+    assert!((back.velocity() - llo.velocity()).norm() < 1e-9);
+
+    // The frame tree is limited to MAX_TREE_DEPTH levels, and deeper frames are rejected
+    for level in 6..MAX_TREE_DEPTH {
+        let name = format!("moon_twist_{level}");
+        cosm.append_frames(&twist_toml(&name, &parent)).unwrap();
+        parent = name.replace('_', " ");
+    }
+    assert_eq!(cosm.frame(&parent).frame_path().len(), MAX_TREE_DEPTH);
+    assert!(matches!(
+        cosm.append_frames(&twist_toml("moon_twist_too_deep", &parent)),
+        Err(NyxError::FrameError(FrameError::TreeTooDeep(_)))
+    ));
+    assert!(cosm.try_frame("moon twist too deep").is_err());
+}
+
+#[test]
+fn non_commuting_frame_tree() {
+    let mut cosm = Cosm::de438_raw();
+
+    // A thirty degree tilt about the X axis of Moon J2000, then a twenty five degree twist about the Z axis of the tilted frame
+    for (name, parent, declin, w) in [
+        ("moon_tilt", "Moon J2000", "60.0", "0.0"),
+        ("moon_tilt_twist", "moon tilt", "90.0", "25.0"),
+    ] {
+        cosm.append_frames(&format!(
+            r#"
+            [frames.{name}]
+            inherit = "{parent}"
+            gm = -1
+            flattening = -1
+            equatorial_radius = -1
+            semi_major_radius = -1
+            [frames.{name}.rotation]
+            right_asc = "-90.0"
+            declin = "{declin}"
+            w = "{w}"
+            angle_unit = "degrees"
+            "#
+        ))
+        .unwrap();
+    }
+
+    let moon_j2k = cosm.frame("Moon J2000");
+    let tilt = cosm.frame("moon tilt");
+    let twist = cosm.frame("moon tilt twist");
+    assert_eq!(twist.frame_path().len(), 3);
+
+    let epoch = Epoch::from_gregorian_tai_at_noon(2022, 3, 1);
+    let tilt_dcm = cosm
+        .try_position_dcm_from_to(&moon_j2k, &tilt, epoch)
+        .unwrap();
+    let twist_dcm = cosm.try_position_dcm_from_to(&tilt, &twist, epoch).unwrap();
+    // Make sure that both rotations do not commute, or this test would not catch an inverted product
+    assert!((twist_dcm * tilt_dcm - tilt_dcm * twist_dcm).norm() > 0.1);
+
+    let forward = cosm
+        .try_position_dcm_from_to(&moon_j2k, &twist, epoch)
+        .unwrap();
+    assert!((forward - twist_dcm * tilt_dcm).norm() < 1e-12);
+    let backward = cosm
+        .try_position_dcm_from_to(&twist, &moon_j2k, epoch)
+        .unwrap();
+    assert!((backward - forward.transpose()).norm() < 1e-12);
+
+    // Round trips through another ephemeris and through the parent frame
+    let eme2k = cosm.frame("EME2000");
+    let llo = Orbit::keplerian(1_900.0, 0.01, 80.0, 40.0, 50.0, 60.0, epoch, twist);
+    for other in [eme2k, moon_j2k, tilt] {
+        let there = cosm.frame_chg(&llo, other);
+        let back = cosm.frame_chg(&there, twist);
+        assert!(
+            (back.radius() - llo.radius()).norm() < 1e-6,
+            "round trip through {other}"
+        );
+        assert!((back.velocity() - llo.velocity()).norm() < 1e-9);
+    }
+    let in_moon_j2k = cosm.frame_chg(&llo, moon_j2k);
+    assert!((in_moon_j2k.radius() - backward * llo.radius()).norm() < 1e-6);
+}
+
+#[test]
+fn frame_try_accessors() {
+    let cosm = Cosm::de438_raw();
+    let eme2k = cosm.frame("EME2000");
+    let ssb = cosm.frame("SSB J2000");
+
+    assert_eq!(eme2k.try_gm().unwrap(), eme2k.gm());
+    assert_eq!(ssb.try_ephem_path().unwrap(), ssb.ephem_path());
+    assert_eq!(
+        ssb.try_equatorial_radius(),
+        Err(FrameError::NotGeoid("equatorial radius", ssb))
+    );
+    assert_eq!(
+        Frame::VNC.try_gm(),
+        Err(FrameError::NotCelestialOrGeoid("GM", Frame::VNC))
+    );
+    assert!(Frame::RIC.try_frame_path().is_err());
+    assert!(Frame::SEZ.try_flattening().is_err());
+    assert!(!Frame::Inertial.is_body_fixed());
+
+    // Frame errors are surfaced by the Cosm instead of panicking
+    let epoch = Epoch::from_gregorian_tai_at_noon(2022, 3, 1);
+    let state = Orbit::keplerian(7_000.0, 0.01, 30.0, 40.0, 50.0, 60.0, epoch, eme2k);
+    assert_eq!(
+        cosm.try_frame_chg(&state, Frame::VNC),
+        Err(NyxError::FrameError(FrameError::NotCelestialOrGeoid(
+            "ephemeris path",
+            Frame::VNC
+        )))
+    );
+
+    // The rotation rates come from the planetary constants, and barycenters rotate like their planet
+    assert!(eme2k.try_angular_velocity().is_ok());
+    let pluto_rate = 56.3625225_f64.to_radians() / 86_400.0;
+    assert_eq!(
+        cosm.frame("Pluto Barycenter J2000")
+            .try_angular_velocity()
+            .unwrap(),
+        pluto_rate
+    );
+    assert!(ssb.try_angular_velocity().is_err());
+}
+
+#[test]
+fn angular_velocity_from_rotation_model() {
+    let cosm = Cosm::de438_raw();
+    let epoch = Epoch::from_gregorian_tai_at_noon(2022, 3, 1);
+
+    for name in [
+        "EME2000",
+        "IAU Earth",
+        "Moon J2000",
+        "IAU Mars",
+        "IAU Jupiter",
+    ] {
+        let frame = cosm.frame(name);
+        let omega = cosm.try_angular_velocity(&frame, epoch).unwrap();
+        // The hardcoded rates are rounded periods
+        let expected = frame.angular_velocity();
+        assert!(
+            (omega - expected).abs() / expected < 1e-3,
+            "{name}: {omega} != {expected} rad/s"
+        );
+    }
+
+    // The rotation of the IAU frames is mostly about their Z axis
+    let omega = cosm
+        .try_angular_velocity_vector(&cosm.frame("IAU Earth"), epoch)
+        .unwrap();
+    assert!(omega[2] / omega.norm() > 1.0 - 1e-6);
+
+    // The barycenter of Pluto rotates like Pluto, i.e. at the rate of BODY999_PM
+    let omega = cosm
+        .try_angular_velocity(&cosm.frame("Pluto Barycenter J2000"), epoch)
+        .unwrap();
+    let expected = 56.3625225_f64.to_radians() / 86_400.0;
+    assert!(
+        (omega - expected).abs() / expected < 1e-6,
+        "Pluto: {omega} != {expected} rad/s"
+    );
+
+    // Frames without a rotation model
+    assert!(cosm
+        .try_angular_velocity(&cosm.frame("SSB J2000"), epoch)
+        .is_err());
+    assert!(cosm.try_angular_velocity(&Frame::VNC, epoch).is_err());
+}
+
+#[test]
+fn frame_names_serde() {
+    let mut cosm = Cosm::de438_raw();
+
+    let itrf = cosm
+        .append_eop(
+            EarthOrientationParams::from_entries(vec![
+                EopEntry {
+                    mjd_utc: 53101.0,
+                    ..Default::default()
+                },
+                EopEntry {
+                    mjd_utc: 53103.0,
+                    ..Default::default()
+                },
+            ])
+            .unwrap(),
+        )
+        .unwrap();
+
+    // A single day of constant Euler angles for ITRF93
+    let words = vec![
+        43_200.0, 43_200.0, 0.1, 0.0, 1.2, 0.0, 0.3, 0.0, 0.0, 86_400.0, 8.0, 1.0,
+    ];
+    cosm.append_bpc(
+        Bpc::from_bytes(build_bpc(&[DafArray::bpc(3000, 1, 0.0, 86_400.0, words)])).unwrap(),
+    )
+    .unwrap();
+    let itrf93 = cosm.frame("ITRF93");
+
+    // Custom frames are named as they were registered
+    cosm.append_frames(
+        r#"
+        [frames.Earth_Tilted]
+        inherit = "EME2000"
+        gm = -1
+        flattening = -1
+        equatorial_radius = -1
+        semi_major_radius = -1
+        [frames.Earth_Tilted.rotation]
+        right_asc = "0.0"
+        declin = "80.0"
+        w = "0.0"
+        angle_unit = "degrees"
+        "#,
+    )
+    .unwrap();
+    let tilted = cosm.frame("Earth Tilted");
+
+    let eme2k = cosm.frame("EME2000");
+    let iau_earth = cosm.frame("IAU Earth");
+    let teme = cosm.frame("TEME");
+    for (name, frame) in [
+        ("Earth J2000", eme2k),
+        ("IAU Earth", iau_earth),
+        ("ITRF", itrf),
+        ("ITRF93", itrf93),
+        ("TEME", teme),
+        ("Earth Tilted", tilted),
+    ] {
+        assert_eq!(frame.to_string(), name);
+        assert_eq!(cosm.try_frame(name).unwrap(), frame, "{name}");
+        if name != "IAU Earth" {
+            assert_ne!(frame, iau_earth, "{name}");
+        }
+
+        // Orbits are serialized with the name of their frame
+        let orbit = Orbit::keplerian(
+            7_000.0,
+            0.01,
+            30.0,
+            40.0,
+            50.0,
+            60.0,
+            Epoch::from_mjd_utc(53102.0),
+            frame,
+        );
+        let yaml = serde_yaml::to_string(&orbit).unwrap();
+        assert!(yaml.contains(&format!("frame: {name}")), "{yaml}");
+    }
+    assert!(itrf.is_body_fixed());
+    assert!(itrf93.is_body_fixed());
+    assert!(tilted.is_body_fixed());
+
+    // The default Cosm has a TEME frame, so these orbits go through serde unchanged
+    let orbit = Orbit::keplerian(
+        7_000.0,
+        0.01,
+        30.0,
+        40.0,
+        50.0,
+        60.0,
+        Epoch::from_mjd_utc(53102.0),
+        teme,
+    );
+    let deser: Orbit = serde_yaml::from_str(&serde_yaml::to_string(&orbit).unwrap()).unwrap();
+    assert_eq!(deser.frame, teme);
+    assert_eq!(deser, orbit);
+
+    // But it has no ITRF93, which must not be read back as IAU Earth
+    let orbit = Orbit::keplerian(
+        7_000.0,
+        0.01,
+        30.0,
+        40.0,
+        50.0,
+        60.0,
+        Epoch::from_mjd_utc(53102.0),
+        itrf93,
+    );
+    assert!(serde_yaml::from_str::<Orbit>(&serde_yaml::to_string(&orbit).unwrap()).is_err());
+}
@@ -0,0 +1,136 @@
+extern crate nyx_space as nyx;
+
+use nyx::cosmic::eclipse::{EclipseLocator, EclipseState};
+use nyx::cosmic::{Bodies, Cosm, LightTimeCalc, Orbit, SPEED_OF_LIGHT_KMS};
+use nyx::time::{Epoch, TimeSeries, Unit};
+use std::mem::discriminant;
+use std::str::FromStr;
+use std::sync::Arc;
+
+#[test]
+fn light_time_modes() {
+    assert_eq!(
+        LightTimeCalc::from_str("xcn+s").unwrap(),
+        LightTimeCalc::XCNS
+    );
+    assert_eq!(LightTimeCalc::from_str("LT").unwrap(), LightTimeCalc::LT);
+    assert!(LightTimeCalc::from_str("CN+X").is_err());
+    assert!(LightTimeCalc::XLT.is_transmission());
+    assert!(!LightTimeCalc::LT.is_converged());
+    assert!(LightTimeCalc::Aberration.has_aberration());
+
+    let cosm = Cosm::de438_raw();
+    let eme2k = cosm.frame("EME2000");
+    let ssb = cosm.frame("SSB J2000");
+    let mars = Bodies::MarsBarycenter.ephem_path();
+    let epoch = Epoch::from_gregorian_tai_at_noon(2022, 3, 1);
+
+    let earth_ssb = cosm.celestial_state(&eme2k.ephem_path(), epoch, ssb, LightTimeCalc::None);
+    let geom = cosm.celestial_state(mars, epoch, eme2k, LightTimeCalc::None);
+
+    // Converged reception and transmission: the light time matches the distance to the target at the light time epoch
+    for (correction, sign) in [(LightTimeCalc::CN, -1.0), (LightTimeCalc::XCN, 1.0)] {
+        let apparent = cosm.celestial_state(mars, epoch, eme2k, correction);
+        let lt_s = apparent.rmag_km() / SPEED_OF_LIGHT_KMS;
+        let mars_ssb = cosm.celestial_state(
+            mars,
+            epoch + sign * lt_s * Unit::Second,
+            ssb,
+            LightTimeCalc::None,
+        );
+        let expected = mars_ssb.radius() - earth_ssb.radius();
+        assert!(
+            (apparent.radius() - expected).norm() < 1e-6,
+            "{correction:?}: {:.3e} km",
+            (apparent.radius() - expected).norm()
+        );
+        assert!((apparent.radius() - geom.radius()).norm() > 1.0);
+    }
+
+    // Historical names
+    let cn = cosm.celestial_state(mars, epoch, eme2k, LightTimeCalc::CN);
+    let light_time = cosm.celestial_state(mars, epoch, eme2k, LightTimeCalc::LightTime);
+    assert!((cn.radius() - light_time.radius()).norm() < 1e-9);
+
+    // A single iteration is close to the converged solution but not exactly it
+    let lt = cosm.celestial_state(mars, epoch, eme2k, LightTimeCalc::LT);
+    let delta_km = (lt.radius() - cn.radius()).norm();
+    assert!(delta_km > 0.0 && delta_km < 100.0, "{delta_km} km");
+
+    // Stellar aberration is of the order of v/c, and opposite for the transmission case
+    let v_by_c = earth_ssb.vmag_km_s() / SPEED_OF_LIGHT_KMS;
+    let cns = cosm.celestial_state(mars, epoch, eme2k, LightTimeCalc::CNS);
+    let angle = cns.r_hat().dot(&cn.r_hat()).min(1.0).acos();
+    assert!(angle > 0.0 && angle <= v_by_c * 1.01, "{angle} rad");
+    let xcn = cosm.celestial_state(mars, epoch, eme2k, LightTimeCalc::XCN);
+    let xcns = cosm.celestial_state(mars, epoch, eme2k, LightTimeCalc::XCNS);
+    let x_angle = xcns.r_hat().dot(&xcn.r_hat()).min(1.0).acos();
+    assert!((x_angle - angle).abs() < 1e-3 * angle);
+    assert!((cns.r_hat() - cn.r_hat()).dot(&(xcns.r_hat() - xcn.r_hat())) < 0.0);
+}
+
+#[test]
+fn frame_chg_with_light_time() {
+    let cosm = Cosm::de438_raw();
+    let eme2k = cosm.frame("EME2000");
+    let mars2k = cosm.frame("Mars Barycenter J2000");
+    let ssb = cosm.frame("SSB J2000");
+    let epoch = Epoch::from_gregorian_tai_at_noon(2022, 3, 1);
+
+    // Low Mars orbit as seen from the Earth
+    let lmo = Orbit::keplerian(3_800.0, 0.01, 93.0, 40.0, 50.0, 60.0, epoch, mars2k);
+    assert_eq!(
+        cosm.frame_chg_with_correction(&lmo, eme2k, LightTimeCalc::None),
+        cosm.frame_chg(&lmo, eme2k)
+    );
+
+    let apparent = cosm.frame_chg_with_correction(&lmo, eme2k, LightTimeCalc::CN);
+    assert_eq!(apparent.frame, eme2k);
+    let lt_s = apparent.rmag_km() / SPEED_OF_LIGHT_KMS;
+    let lt_epoch = epoch - lt_s * Unit::Second;
+    let earth_ssb = cosm.celestial_state(&eme2k.ephem_path(), epoch, ssb, LightTimeCalc::None);
+    let lmo_ssb = cosm.frame_chg(&lmo.at_epoch(lt_epoch).unwrap(), ssb);
+    assert!((apparent.radius() - (lmo_ssb.radius() - earth_ssb.radius())).norm() < 1e-3);
+    // The spacecraft moved by several hundred kilometers during the light time
+    assert!((apparent.radius() - cosm.frame_chg(&lmo, eme2k).radius()).norm() > 100.0);
+
+    // Body fixed states are correctly translated, with or without corrections
+    let iau_earth = cosm.frame("IAU Earth");
+    let fixed = Orbit::cartesian(6_000.0, 1_000.0, 500.0, 0.0, 0.0, 0.0, epoch, iau_earth);
+    let in_mars2k = cosm.frame_chg(&fixed, mars2k);
+    let via_eme2k = cosm.frame_chg(&cosm.frame_chg(&fixed, eme2k), mars2k);
+    assert!((in_mars2k.radius() - via_eme2k.radius()).norm() < 1e-6);
+    let back = cosm.frame_chg(&in_mars2k, iau_earth);
+    assert!((back.radius() - fixed.radius()).norm() < 1e-6);
+    assert!((back.velocity() - fixed.velocity()).norm() < 1e-9);
+    let from_mars = cosm.frame_chg_with_correction(&fixed, mars2k, LightTimeCalc::XCN);
+    assert!((from_mars.rmag_km() - in_mars2k.rmag_km()).abs() < 1e5);
+}
+
+#[test]
+fn eclipse_with_light_time() {
+    let cosm = Arc::new(Cosm::de438_raw());
+    let eme2k = cosm.frame("EME2000");
+    let epoch = Epoch::from_gregorian_tai_at_noon(2022, 3, 1);
+
+    let mut e_loc = EclipseLocator::cislunar(cosm.clone());
+    let geometric = e_loc.clone();
+    e_loc.correction = LightTimeCalc::CNS;
+
+    let leo = Orbit::keplerian(7_000.0, 0.001, 30.0, 40.0, 50.0, 60.0, epoch, eme2k);
+    let mut umbra = 0;
+    let mut differences = 0;
+    for epoch in TimeSeries::inclusive(epoch, epoch + leo.period(), 10 * Unit::Second) {
+        let state = leo.at_epoch(epoch).unwrap();
+        let corrected = e_loc.compute(&state);
+        if corrected == EclipseState::Umbra {
+            umbra += 1;
+        }
+        if discriminant(&corrected) != discriminant(&geometric.compute(&state)) {
+            differences += 1;
+        }
+    }
+    // The apparent Sun moves by a few seconds of arc, so the eclipse state only changes at the edges of the penumbra
+    assert!(umbra > 0);
+    assert!(differences <= 4, "{differences} differences");
+}
@@ -0,0 +1,217 @@
+extern crate nyx_space as nyx;
+
+use nyx::cosmic::{Cosm, FrameError, Orbit, EARTH_J2};
+use nyx::dynamics::sph_harmonics::Harmonics;
+use nyx::io::gravity::HarmonicsMem;
+use nyx::md::prelude::*;
+use nyx::md::EventEvaluator;
+use nyx::time::{Epoch, Unit};
+use nyx::utils::between_pm_180;
+use nyx::NyxError;
+use std::str::FromStr;
+
+#[test]
+fn mean_elements_round_trip() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2022, 3, 1);
+
+    // Near circular low Earth, sun synchronous, and GTO-like orbits
+    for (sma, ecc, inc, raan, aop, ta) in [
+        (7000.0, 1e-3, 28.5, 45.0, 30.0, 10.0),
+        (7078.0, 1.5e-3, 98.2, 220.0, 90.0, 250.0),
+        (24_400.0, 0.72, 7.0, 110.0, 178.0, 60.0),
+    ] {
+        let osc = Orbit::keplerian(sma, ecc, inc, raan, aop, ta, epoch, eme2k);
+
+        let brouwer = osc.try_brouwer_mean().unwrap();
+        assert_eq!(brouwer.epoch, osc.epoch);
+        assert_eq!(brouwer.frame, osc.frame);
+        // The short periodic J2 terms are of the order of J2 * a, i.e. ten kilometers in LEO
+        assert!((brouwer.sma_km() - osc.sma_km()).abs() > 1e-3);
+        assert!((brouwer.sma_km() - osc.sma_km()).abs() < 1e-2 * osc.sma_km());
+        let back = brouwer.try_brouwer_mean_to_osculating().unwrap();
+        let err = (back.radius() - osc.radius()).norm();
+        assert!(err < 1e-8, "Brouwer round trip error of {err} km");
+        assert!((back.velocity() - osc.velocity()).norm() < 1e-11);
+
+        let kozai = osc.try_kozai_mean().unwrap();
+        // Kozai and Brouwer only differ in their semi major axis
+        assert!((kozai.ecc() - brouwer.ecc()).abs() < 1e-12);
+        assert!((kozai.inc_deg() - brouwer.inc_deg()).abs() < 1e-10);
+        assert!(between_pm_180(kozai.ma_deg() - brouwer.ma_deg()).abs() < 1e-8);
+        assert!(kozai.sma_km() != brouwer.sma_km());
+        let back = kozai.try_kozai_mean_to_osculating().unwrap();
+        let err = (back.radius() - osc.radius()).norm();
+        assert!(err < 1e-8, "Kozai round trip error of {err} km");
+
+        // And through the state parameters
+        assert_eq!(
+            osc.value(StateParameter::MeanSMA).unwrap(),
+            brouwer.sma_km()
+        );
+        assert_eq!(
+            osc.value(StateParameter::MeanEccentricity).unwrap(),
+            brouwer.ecc()
+        );
+        assert_eq!(
+            osc.value(StateParameter::MeanInclination).unwrap(),
+            brouwer.inc_deg()
+        );
+        assert_eq!(
+            osc.value(StateParameter::MeanRAAN).unwrap(),
+            brouwer.raan_deg()
+        );
+        assert_eq!(
+            osc.value(StateParameter::MeanAoP).unwrap(),
+            brouwer.aop_deg()
+        );
+        let aol = osc.value(StateParameter::MeanAoL).unwrap();
+        assert!(between_pm_180(aol - brouwer.ma_deg() - brouwer.aop_deg()).abs() < 1e-10);
+
+        // The partials used by the targeters are finite differenced, so check them against the osculating ones
+        let dual = OrbitDual::from(osc);
+        let mean_sma = dual.partial_for(StateParameter::MeanSMA).unwrap();
+        assert_eq!(mean_sma.real(), brouwer.sma_km());
+        let sma = dual.partial_for(StateParameter::SMA).unwrap();
+        let rel_err = (mean_sma.wtr_vx() - sma.wtr_vx()).abs() / sma.wtr_vx().abs();
+        assert!(rel_err < 0.05, "{rel_err}");
+    }
+
+    // Out of the validity domain of the theory
+    let too_low = Orbit::keplerian(6500.0, 0.6, 51.6, 0.0, 0.0, 0.0, epoch, eme2k);
+    assert!(!too_low.is_brouwer_short_valid());
+    assert!(matches!(
+        too_low.try_brouwer_mean(),
+        Err(NyxError::MathDomain(_))
+    ));
+    assert!(too_low.value(StateParameter::MeanSMA).is_err());
+    let critical = Orbit::keplerian(7500.0, 0.01, 63.4349, 0.0, 0.0, 0.0, epoch, eme2k);
+    assert!(critical.try_brouwer_mean().is_err());
+}
+
+#[test]
+fn mean_elements_j2_propagation() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let iau_earth = cosm.frame("IAU Earth");
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2022, 3, 1);
+
+    let harmonics = Harmonics::from_stor(iau_earth, HarmonicsMem::j2_jgm3(), cosm.clone());
+    let dynamics = OrbitalDynamics::from_model(harmonics);
+
+    let osc = Orbit::keplerian(7078.0, 1.5e-3, 98.2, 220.0, 90.0, 0.0, epoch, eme2k);
+    let setup = Propagator::default(dynamics);
+    let (_, traj) = setup
+        .with(osc)
+        .for_duration_with_traj(3 * Unit::Hour)
+        .unwrap();
+
+    let spread = |param: StateParameter| {
+        let (mut min, mut max) = (f64::INFINITY, f64::NEG_INFINITY);
+        for state in traj.every(5 * Unit::Minute) {
+            let value = state.value(param).unwrap();
+            min = min.min(value);
+            max = max.max(value);
+        }
+        max - min
+    };
+
+    // The osculating elements oscillate with J2, but the (first order) mean elements barely change
+    let osc_sma = spread(StateParameter::SMA);
+    let mean_sma = spread(StateParameter::MeanSMA);
+    println!("SMA spread: osculating {osc_sma} km\tmean {mean_sma} km");
+    assert!(osc_sma > 10.0);
+    assert!(mean_sma < 0.1);
+
+    let osc_ecc = spread(StateParameter::Eccentricity);
+    let mean_ecc = spread(StateParameter::MeanEccentricity);
+    println!("ECC spread: osculating {osc_ecc}\tmean {mean_ecc}");
+    assert!(mean_ecc < osc_ecc / 10.0);
+
+    let osc_inc = spread(StateParameter::Inclination);
+    let mean_inc = spread(StateParameter::MeanInclination);
+    println!("INC spread: osculating {osc_inc} deg\tmean {mean_inc} deg");
+    assert!(mean_inc < osc_inc / 10.0);
+
+    // Mean elements may also be used as events
+    let event = Event::new(StateParameter::MeanAoL, 180.0);
+    let found = traj.find_all(&event).unwrap();
+    assert!(!found.is_empty());
+    for state in &found {
+        let aol = state.value(StateParameter::MeanAoL).unwrap();
+        assert!(between_pm_180(aol - 180.0).abs() < 1e-2, "{aol}");
+        assert!(event.eval(state).abs() < 1e-2);
+    }
+}
+
+#[test]
+fn mean_elements_frame_j2() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let mars = cosm.frame("Mars Barycenter J2000");
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2022, 3, 1);
+
+    assert_eq!(eme2k.try_j2(), Ok(EARTH_J2));
+    // The barycenter uses the constants of the planet
+    let mars_j2 = mars.try_j2().unwrap();
+    assert_eq!(mars_j2, 1.960_45e-3);
+
+    // The same orbit scaled to the radius of Mars: the short periodic terms of the mean elements scale with J2
+    let ratio = 7078.0 / eme2k.equatorial_radius();
+    let earth_osc = Orbit::keplerian(7078.0, 1.5e-3, 98.2, 220.0, 90.0, 250.0, epoch, eme2k);
+    let mars_osc = Orbit::keplerian(
+        ratio * mars.equatorial_radius(),
+        1.5e-3,
+        98.2,
+        220.0,
+        90.0,
+        250.0,
+        epoch,
+        mars,
+    );
+    let rel_offset = |osc: Orbit| {
+        let mean = osc.try_brouwer_mean().unwrap();
+        let back = mean.try_brouwer_mean_to_osculating().unwrap();
+        assert!((back.radius() - osc.radius()).norm() < 1e-8);
+        (mean.sma_km() - osc.sma_km()) / osc.sma_km()
+    };
+    let j2_ratio = rel_offset(mars_osc) / rel_offset(earth_osc);
+    assert!((j2_ratio / (mars_j2 / EARTH_J2) - 1.0).abs() < 1e-2);
+    let back = mars_osc
+        .try_kozai_mean()
+        .unwrap()
+        .try_kozai_mean_to_osculating()
+        .unwrap();
+    assert!((back.radius() - mars_osc.radius()).norm() < 1e-8);
+
+    // Uranus has no J2 in the planetary constants
+    let uranus = cosm.frame("Uranus Barycenter J2000");
+    assert!(matches!(uranus.try_j2(), Err(FrameError::NoJ2(_))));
+    let osc = Orbit::keplerian(40_000.0, 1e-3, 10.0, 0.0, 0.0, 0.0, epoch, uranus);
+    assert!(matches!(
+        osc.try_brouwer_mean(),
+        Err(NyxError::FrameError(FrameError::NoJ2(_)))
+    ));
+    assert!(osc.try_kozai_mean_to_osculating().is_err());
+    assert!(osc.value(StateParameter::MeanSMA).is_err());
+}
+
+#[test]
+fn mean_elements_state_parameters() {
+    for (repr, param) in [
+        ("mean_sma", StateParameter::MeanSMA),
+        ("mean_ecc", StateParameter::MeanEccentricity),
+        ("mean_inc", StateParameter::MeanInclination),
+        ("mean_raan", StateParameter::MeanRAAN),
+        ("mean_aop", StateParameter::MeanAoP),
+        ("mean_aol", StateParameter::MeanAoL),
+    ] {
+        assert_eq!(StateParameter::from_str(repr).unwrap(), param);
+        assert!(param.is_mean_element());
+        assert!(param.is_orbital());
+    }
+    assert!(!StateParameter::SMA.is_mean_element());
+    assert_eq!(StateParameter::MeanSMA.unit(), "km");
+    assert_eq!(StateParameter::MeanAoL.unit(), "deg");
+}
@@ -1,3 +1,21 @@
+mod body_registry;
+mod bpc;
 mod bplane;
+mod conjunction;
+mod covariance;
+#[path = "../../src/io/daf_writer.rs"]
+mod daf_writer;
 mod eclipse;
+mod elements;
+mod eop;
+mod flyby;
+mod frame_tree;
+mod light_time;
+mod mean_elements;
 mod orbit;
+mod pck;
+mod pointing;
+mod spk;
+mod tle;
+mod topocentric;
+mod traj_ephem;
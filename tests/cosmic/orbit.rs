@@ -629,3 +629,57 @@ fn orbit_dual_test() {
         );
     }
 }
+
+#[test]
+fn ta_at_apsides() {
+    let cosm = Cosm::de438_gmat();
+    let eme2k = cosm.frame("EME2000");
+    let dt = Epoch::from_mjd_tai(21_545.0);
+
+    // Rounding errors regularly push the cosine of the true anomaly slightly out of [-1, 1] at the apsides
+    let (mut num_above, mut num_below) = (0, 0);
+    for i in 0..50 {
+        for ecc in [0.01, 0.1, 0.3, 0.5, 0.7] {
+            let (inc, raan, aop) = (3.0 * i as f64, 7.0 * i as f64, 11.0 * i as f64);
+            let sma = (6_600.0 + 37.0 * i as f64) / (1.0 - ecc);
+
+            let peri = Orbit::keplerian(sma, ecc, inc, raan, aop, 0.0, dt, eme2k);
+            let cos_nu = peri.evec().dot(&peri.radius()) / (peri.ecc() * peri.rmag_km());
+            if cos_nu > 1.0 {
+                num_above += 1;
+                assert_eq!(peri.ta_deg(), 0.0, "cos(ta) = {cos_nu}");
+            }
+
+            let apo = Orbit::keplerian(sma, ecc, inc, raan, aop, 180.0, dt, eme2k);
+            let cos_nu = apo.evec().dot(&apo.radius()) / (apo.ecc() * apo.rmag_km());
+            if cos_nu < -1.0 {
+                num_below += 1;
+                assert_eq!(apo.ta_deg(), 180.0, "cos(ta) = {cos_nu}");
+            }
+        }
+    }
+    assert!(num_above > 0 && num_below > 0);
+}
+
+#[test]
+fn orbit_dual_ta_at_apsides() {
+    use nyx::cosmic::OrbitDual;
+    let cosm = Cosm::de438_gmat();
+    let eme2k = cosm.frame("EME2000");
+    let dt = Epoch::from_mjd_tai(21_545.0);
+
+    for i in 0..50 {
+        for ecc in [0.01, 0.1, 0.3, 0.5, 0.7] {
+            let (inc, raan, aop) = (3.0 * i as f64, 7.0 * i as f64, 11.0 * i as f64);
+            let sma = (6_600.0 + 37.0 * i as f64) / (1.0 - ecc);
+
+            let peri = Orbit::keplerian(sma, ecc, inc, raan, aop, 0.0, dt, eme2k);
+            let ta = OrbitDual::from(peri).ta().real();
+            assert!(ta.min(360.0 - ta) < 1e-5, "periapsis: {ta} deg");
+
+            let apo = Orbit::keplerian(sma, ecc, inc, raan, aop, 180.0, dt, eme2k);
+            let ta = OrbitDual::from(apo).ta().real();
+            assert!((ta - 180.0).abs() < 1e-5, "apoapsis: {ta} deg");
+        }
+    }
+}
@@ -0,0 +1,130 @@
+extern crate nyx_space as nyx;
+
+use nyx::cosmic::{Cosm, Orbit, EARTH_J2};
+use nyx::dynamics::OrbitalDynamics;
+use nyx::io::pck::PlanetaryConstants;
+use nyx::propagators::Propagator;
+use nyx::time::{Epoch, Unit};
+
+/// Converts a prime meridian rate in degrees per day to rad/s
+fn deg_day_to_rad_s(rate: f64) -> f64 {
+    rate.to_radians() / 86_400.0
+}
+
+#[test]
+fn embedded_constants() {
+    let cosm = Cosm::de438();
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2023, 3, 1);
+
+    let earth = cosm.try_body_constants(&cosm.frame("EME2000")).unwrap();
+    println!("{earth}");
+    assert_eq!(earth.naif_id, 399);
+    assert_eq!(earth.j2, Some(EARTH_J2));
+    assert!(cosm
+        .try_body_constants(&cosm.frame("IAU Earth"))
+        .unwrap()
+        .has_rotation());
+
+    // Barycenters have the shape and rotation of their planet
+    let mars = cosm
+        .try_body_constants(&cosm.frame("Mars Barycenter J2000"))
+        .unwrap();
+    println!("{mars}");
+    assert_eq!(mars.naif_id, 4);
+    assert_eq!(mars.equatorial_radius_km(), Some(3396.19));
+    assert!((mars.flattening().unwrap() - 19.99 / 3396.19).abs() < 1e-15);
+
+    // Bodies without an IAU frame in the TOML get one from the planetary constants
+    for (name, rate_deg_day) in [("IAU Mercury", 6.138_502_5), ("IAU Pluto", 56.362_522_5)] {
+        let frame = cosm.frame(name);
+        assert!(frame.is_body_fixed());
+        let rate = cosm.try_angular_velocity(&frame, epoch).unwrap();
+        println!("{name}: {rate:e} rad/s");
+        assert!((rate / deg_day_to_rad_s(rate_deg_day) - 1.0).abs() < 1e-6);
+    }
+
+    // The rotation rates of the frames no longer rely on hardcoded periods
+    let mercury = cosm.frame("Mercury Barycenter J2000");
+    assert!((mercury.angular_velocity() - deg_day_to_rad_s(6.138_502_5)).abs() < 1e-15);
+    let venus = cosm.frame("Venus Barycenter J2000");
+    assert!(
+        venus.angular_velocity() < 0.0,
+        "Venus is a retrograde rotator"
+    );
+    assert!((cosm.frame("EME2000").angular_velocity() - 7.292_115_146_706_4e-5).abs() < 1e-20);
+    assert!(cosm.frame("SSB J2000").try_angular_velocity().is_err());
+}
+
+#[test]
+fn user_defined_moon() {
+    let mut cosm = Cosm::de438_raw();
+    let mars = cosm.frame("Mars Barycenter J2000");
+    let start = Epoch::from_gregorian_utc_at_noon(2023, 3, 1);
+
+    // Phobos is not in the DE files, so provide its ephemeris
+    let phobos = Orbit::keplerian(9_376.0, 0.0151, 1.08, 10.0, 20.0, 30.0, start, mars);
+    let (_, traj) = Propagator::default(OrbitalDynamics::two_body())
+        .with(phobos)
+        .for_duration_with_traj(Unit::Day * 1)
+        .unwrap();
+
+    let phobos_j2k = cosm.append_traj_ephemeris("Phobos", traj).unwrap();
+    println!("{phobos_j2k:?}");
+    assert!(phobos_j2k.is_geoid());
+    assert_eq!(phobos_j2k.gm(), 7.087_546_066_894_452e-4);
+    assert_eq!(phobos_j2k.equatorial_radius(), 13.0);
+    assert!((phobos_j2k.flattening() - 3.9 / 13.0).abs() < 1e-15);
+    assert_eq!(
+        cosm.try_body_constants(&phobos_j2k).unwrap().naif_id,
+        401,
+        "Phobos is found by name"
+    );
+
+    // Phobos rotates synchronously
+    let iau_phobos = cosm.frame("IAU Phobos");
+    let rate = cosm.try_angular_velocity(&iau_phobos, start).unwrap();
+    let mean_motion = 2.0 * std::f64::consts::PI / phobos.period().to_seconds();
+    println!("Phobos rotation rate: {rate:e} rad/s\tmean motion: {mean_motion:e} rad/s");
+    assert!((rate / mean_motion - 1.0).abs() < 0.01);
+
+    // And a spacecraft may be expressed in the body fixed frame of Phobos
+    let sc = Orbit::keplerian(9_376.0, 0.0151, 1.08, 10.0, 20.0, 60.0, start, mars);
+    let sc_phobos = cosm.frame_chg(&sc, iau_phobos);
+    println!("{sc_phobos}");
+    assert!((sc_phobos.rmag_km() - (sc.radius() - phobos.radius()).norm()).abs() < 1e-6);
+}
+
+#[test]
+fn override_constants() {
+    let mut cosm = Cosm::de438_raw();
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2023, 3, 1);
+    let eme2k = cosm.frame("EME2000");
+
+    let kernel = PlanetaryConstants::from_text(
+        r#"KPL/PCK
+Mars with a rounder shape and a slower rotation
+\begindata
+    BODY4_GM     = ( 42828.0 )
+    BODY499_RADII = ( 3400.0  3400.0  3390.0 )
+    BODY499_PM    = ( 176.630  350.0  0.0 )
+\begintext
+"#,
+    )
+    .unwrap();
+    cosm.append_pck(kernel).unwrap();
+
+    for name in ["Mars Barycenter J2000", "IAU Mars"] {
+        let frame = cosm.frame(name);
+        assert_eq!(frame.gm(), 42_828.0);
+        assert_eq!(frame.equatorial_radius(), 3400.0);
+        assert!((frame.flattening() - 10.0 / 3400.0).abs() < 1e-15);
+    }
+    let rate = cosm
+        .try_angular_velocity(&cosm.frame("IAU Mars"), epoch)
+        .unwrap();
+    assert!((rate / deg_day_to_rad_s(350.0) - 1.0).abs() < 1e-6);
+
+    // Other bodies are not affected
+    assert_eq!(cosm.frame("EME2000"), eme2k);
+    assert!(cosm.append_pck_file("data/does-not-exist.tpc").is_err());
+}
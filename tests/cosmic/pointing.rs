@@ -0,0 +1,149 @@
+extern crate nyx_space as nyx;
+
+use nyx::cosmic::{Attitude, Bodies, Cosm, LightTimeCalc, Orbit};
+use nyx::dynamics::pointing::{
+    BodyTracking, FieldOfView, FovEvent, InertialPointing, NadirPointing, PointingLaw, SunPointing,
+    VelocityPointing,
+};
+use nyx::dynamics::OrbitalDynamics;
+use nyx::linalg::Vector3;
+use nyx::md::EventEvaluator;
+use nyx::propagators::Propagator;
+use nyx::time::{Epoch, Unit};
+
+#[test]
+fn pointing_laws() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2023, 3, 1);
+    let orbit = Orbit::keplerian(7000.0, 0.0, 51.6, 45.0, 30.0, 10.0, epoch, eme2k);
+
+    let is_rotation = |dcm: &nyx::linalg::Matrix3<f64>| {
+        (dcm * dcm.transpose() - nyx::linalg::Matrix3::identity()).norm() < 1e-12
+            && (dcm.determinant() - 1.0).abs() < 1e-12
+    };
+
+    // Nadir: +Z to nadir, +X along the velocity (circular orbit)
+    let nadir = NadirPointing::new();
+    let dcm = nadir.dcm_body_to_inertial(&orbit).unwrap();
+    assert!(is_rotation(&dcm));
+    assert!((dcm * Vector3::z() + orbit.radius().normalize()).norm() < 1e-12);
+    assert!((dcm * Vector3::x() - orbit.velocity().normalize()).norm() < 1e-9);
+    // The body rotates at the orbital rate about the negative orbit normal (-Y)
+    let attitude = nadir.attitude(&orbit).unwrap();
+    let mean_motion = (eme2k.gm() / 7000.0_f64.powi(3)).sqrt();
+    println!("{attitude}");
+    assert!((attitude.omega_rad_s + Vector3::y() * mean_motion).norm() < 1e-9);
+
+    // Velocity pointing: +X along the velocity, +Z to nadir
+    let velocity = VelocityPointing::new();
+    let dcm = velocity.dcm_body_to_inertial(&orbit).unwrap();
+    assert!(is_rotation(&dcm));
+    assert!((dcm * Vector3::x() - orbit.velocity().normalize()).norm() < 1e-12);
+    assert!((dcm * Vector3::z() + orbit.radius().normalize()).norm() < 1e-9);
+
+    // Sun pointing and Moon tracking
+    let sun_dir = cosm
+        .celestial_state_from(Bodies::Sun.ephem_path(), &orbit, LightTimeCalc::None)
+        .radius()
+        .normalize();
+    let dcm = SunPointing::new(cosm.clone())
+        .dcm_body_to_inertial(&orbit)
+        .unwrap();
+    assert!(is_rotation(&dcm));
+    assert!((dcm * Vector3::z() - sun_dir).norm() < 1e-12);
+    assert!((dcm * Vector3::x()).dot(&sun_dir).abs() < 1e-12);
+
+    let moon_dir = cosm
+        .celestial_state_from(Bodies::Luna.ephem_path(), &orbit, LightTimeCalc::None)
+        .radius()
+        .normalize();
+    let tracking = BodyTracking::with_axes(
+        cosm.frame("Luna"),
+        Vector3::y(),
+        Vector3::z(),
+        LightTimeCalc::None,
+        cosm.clone(),
+    );
+    let dcm = tracking.dcm_body_to_inertial(&orbit).unwrap();
+    assert!((dcm * Vector3::y() - moon_dir).norm() < 1e-12);
+
+    // Inertial hold
+    let hold = Attitude::from_mrp(Vector3::new(0.1, 0.2, -0.3), Vector3::zeros());
+    let dcm = InertialPointing::from_attitude(&hold)
+        .dcm_body_to_inertial(&orbit)
+        .unwrap();
+    assert!((dcm - hold.dcm_body_to_inertial()).norm() < 1e-15);
+
+    // Colinear body axes cannot define an attitude
+    assert!(NadirPointing::with_axes(Vector3::z(), -Vector3::z())
+        .dcm_body_to_inertial(&orbit)
+        .is_err());
+}
+
+#[test]
+fn field_of_view() {
+    let conical = FieldOfView::Conical {
+        boresight: Vector3::z(),
+        half_angle_deg: 10.0,
+    };
+    let (s, c) = 5.0_f64.to_radians().sin_cos();
+    assert!((conical.margin_deg(&Vector3::new(s, 0.0, c)) - 5.0).abs() < 1e-12);
+    assert!(conical.contains(&Vector3::z()));
+    assert!(!conical.contains(&Vector3::x()));
+
+    let rectangular = FieldOfView::Rectangular {
+        boresight: Vector3::z(),
+        up: Vector3::y(),
+        half_width_deg: 20.0,
+        half_height_deg: 5.0,
+    };
+    let (s, c) = 15.0_f64.to_radians().sin_cos();
+    assert!(rectangular.contains(&Vector3::new(s, 0.0, c)));
+    assert!(!rectangular.contains(&Vector3::new(0.0, s, c)));
+    assert!(!rectangular.contains(&-Vector3::z()));
+    println!("{conical}\t{rectangular}");
+}
+
+#[test]
+fn sun_in_star_tracker() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2023, 3, 1);
+    // Orbit in the ecliptic plane, so the zenith sweeps past the Sun on every revolution
+    let orbit = Orbit::keplerian(7000.0, 0.0, 23.44, 0.0, 0.0, 0.0, epoch, eme2k);
+    let (_, traj) = Propagator::default(OrbitalDynamics::two_body())
+        .with(orbit)
+        .for_duration_with_traj(1 * Unit::Day)
+        .unwrap();
+
+    // Zenith pointing star tracker on a nadir pointing spacecraft
+    let half_angle_deg = 30.0;
+    let event = FovEvent::new(
+        "Star tracker".to_string(),
+        FieldOfView::Conical {
+            boresight: -Vector3::z(),
+            half_angle_deg,
+        },
+        NadirPointing::new(),
+        cosm.frame("Sun J2000"),
+        cosm,
+    );
+    println!("{event}");
+
+    let in_fov = traj.find_intervals(&event, 1 * Unit::Minute).unwrap();
+    println!("{in_fov}");
+
+    let period = orbit.period();
+    let revolutions = (1 * Unit::Day).to_seconds() / period.to_seconds();
+    assert!((in_fov.len() as f64 - revolutions).abs() <= 1.0);
+    for interval in in_fov.intervals() {
+        // The Sun is close to the orbit plane, so it stays in the FOV for one sixth of the orbit
+        let fraction = interval.duration().to_seconds() / period.to_seconds();
+        if interval.start > traj.first().epoch && interval.end < traj.last().epoch {
+            assert!((fraction - 2.0 * half_angle_deg / 360.0).abs() < 0.01);
+        }
+        let mid = traj.at(interval.start + interval.duration() * 0.5).unwrap();
+        assert!(event.eval(&mid) > 0.0, "{}", event.eval_string(&mid));
+    }
+}
@@ -0,0 +1,343 @@
+extern crate nyx_space as nyx;
+
+use super::daf_writer::{build_spk, DafArray};
+use nyx::cosmic::{Bodies, Cosm, LightTimeCalc};
+use nyx::io::spk::Spk;
+use nyx::linalg::Vector6;
+use nyx::time::{Epoch, TimeSeries, Unit};
+
+/// Validates the SPK reader against the de438 XB. Requires `de438s.bsp` from
+/// https://naif.jpl.nasa.gov/pub/naif/generic_kernels/spk/planets/ in the `data` folder.
+#[test]
+#[ignore]
+fn spk_de438s_vs_xb() {
+    let cosm_xb = Cosm::de438();
+    let cosm_spk = Cosm::from_spk("data/de438s.bsp").unwrap();
+
+    let eme2k_xb = cosm_xb.frame("EME2000");
+    let eme2k_spk = cosm_spk.frame("EME2000");
+    // Both Cosm must have the same frames at the same paths
+    assert_eq!(eme2k_xb.ephem_path(), eme2k_spk.ephem_path());
+    assert_eq!(eme2k_xb.frame_path(), eme2k_spk.frame_path());
+
+    let bodies = [
+        Bodies::Sun,
+        Bodies::MercuryBarycenter,
+        Bodies::VenusBarycenter,
+        Bodies::EarthBarycenter,
+        Bodies::Luna,
+        Bodies::MarsBarycenter,
+        Bodies::JupiterBarycenter,
+        Bodies::SaturnBarycenter,
+        Bodies::UranusBarycenter,
+        Bodies::NeptuneBarycenter,
+        Bodies::PlutoBarycenter,
+    ];
+
+    let start = Epoch::from_gregorian_tai_at_midnight(2002, 2, 7);
+    let end = Epoch::from_gregorian_tai_at_midnight(2049, 12, 1);
+
+    for epoch in TimeSeries::inclusive(start, end, 97 * Unit::Day) {
+        for body in &bodies {
+            let raw_xb = cosm_xb
+                .raw_celestial_state(body.ephem_path(), epoch)
+                .unwrap();
+            let raw_spk = cosm_spk
+                .raw_celestial_state(body.ephem_path(), epoch)
+                .unwrap();
+
+            // The XB was generated from these same Chebyshev coefficients
+            let delta = raw_xb - raw_spk;
+            assert!(
+                delta.rmag_km() < 1e-6,
+                "{body:?} @ {epoch}: {} km",
+                delta.rmag_km()
+            );
+            assert!(
+                delta.vmag_km_s() < 1e-9,
+                "{body:?} @ {epoch}: {} km/s",
+                delta.vmag_km_s()
+            );
+
+            let xb_state =
+                cosm_xb.celestial_state(body.ephem_path(), epoch, eme2k_xb, LightTimeCalc::None);
+            let spk_state =
+                cosm_spk.celestial_state(body.ephem_path(), epoch, eme2k_spk, LightTimeCalc::None);
+            assert!((xb_state - spk_state).rmag_km() < 1e-5);
+        }
+    }
+}
+
+/// Validates the Cosm built from an SPK against the de438 XB, using an SPK of Hermite interpolation (type 13) sampled from the XB itself
+/// over eight days. Unlike `spk_de438s_vs_xb`, this does not require any JPL file.
+#[test]
+fn spk_sampled_from_xb() {
+    let cosm_xb = Cosm::de438();
+
+    // Each body with the center of its ephemeris in the XB
+    let bodies = [
+        (Bodies::Sun, Bodies::SSB),
+        (Bodies::MercuryBarycenter, Bodies::SSB),
+        (Bodies::VenusBarycenter, Bodies::SSB),
+        (Bodies::EarthBarycenter, Bodies::SSB),
+        (Bodies::Earth, Bodies::EarthBarycenter),
+        (Bodies::Luna, Bodies::EarthBarycenter),
+        (Bodies::MarsBarycenter, Bodies::SSB),
+        (Bodies::JupiterBarycenter, Bodies::SSB),
+        (Bodies::SaturnBarycenter, Bodies::SSB),
+        (Bodies::UranusBarycenter, Bodies::SSB),
+        (Bodies::NeptuneBarycenter, Bodies::SSB),
+        (Bodies::PlutoBarycenter, Bodies::SSB),
+    ];
+
+    // Fewer than 100 states per segment, so that the segments do not need an epoch directory
+    let start = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+    let step = 2 * Unit::Hour;
+    let epochs = TimeSeries::inclusive(start, start + 8 * Unit::Day, step).collect::<Vec<_>>();
+    let start_et_s = epochs[0].to_et_seconds();
+    let end_et_s = epochs[epochs.len() - 1].to_et_seconds();
+
+    let segments = bodies
+        .iter()
+        .map(|(body, center)| {
+            let mut words = Vec::new();
+            for epoch in &epochs {
+                let state = cosm_xb
+                    .raw_celestial_state(body.ephem_path(), *epoch)
+                    .unwrap();
+                words.extend(state.to_cartesian_vec().iter());
+            }
+            words.extend(epochs.iter().map(|epoch| epoch.to_et_seconds()));
+            words.extend_from_slice(&[7.0, epochs.len() as f64]);
+            DafArray::spk(
+                body.naif_id(),
+                center.naif_id(),
+                13,
+                start_et_s,
+                end_et_s,
+                words,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let cosm_spk = Cosm::try_from_spk(Spk::from_bytes(build_spk(&segments)).unwrap()).unwrap();
+    let eme2k_xb = cosm_xb.frame("EME2000");
+    let eme2k_spk = cosm_spk.frame("EME2000");
+    assert_eq!(eme2k_xb.ephem_path(), eme2k_spk.ephem_path());
+
+    // In between the samples
+    for epoch in TimeSeries::inclusive(
+        start + 17 * Unit::Minute,
+        start + 7 * Unit::Day,
+        7 * Unit::Hour,
+    ) {
+        for (body, _) in &bodies {
+            let raw_xb = cosm_xb
+                .raw_celestial_state(body.ephem_path(), epoch)
+                .unwrap();
+            let raw_spk = cosm_spk
+                .raw_celestial_state(body.ephem_path(), epoch)
+                .unwrap();
+            // Down to the numerical precision of the states of the outer planets
+            let delta = raw_xb - raw_spk;
+            assert!(
+                delta.rmag_km() < 1e-6 + 1e-14 * raw_xb.rmag_km(),
+                "{body:?} @ {epoch}: {} km",
+                delta.rmag_km()
+            );
+            assert!(
+                delta.vmag_km_s() < 1e-9 + 1e-16 * raw_xb.rmag_km(),
+                "{body:?} @ {epoch}: {} km/s",
+                delta.vmag_km_s()
+            );
+
+            let xb_state =
+                cosm_xb.celestial_state(body.ephem_path(), epoch, eme2k_xb, LightTimeCalc::None);
+            let spk_state =
+                cosm_spk.celestial_state(body.ephem_path(), epoch, eme2k_spk, LightTimeCalc::None);
+            assert!((xb_state - spk_state).rmag_km() < 1e-5);
+        }
+    }
+}
+
+/// Motion of constant acceleration, i.e. position = a + b t + c t^2 on each axis, which Chebyshev and Hermite interpolations represent exactly
+struct Quadratic {
+    a: [f64; 3],
+    b: [f64; 3],
+    c: [f64; 3],
+}
+
+impl Quadratic {
+    fn state(&self, t: f64) -> Vector6<f64> {
+        let mut state = Vector6::zeros();
+        for i in 0..3 {
+            state[i] = self.a[i] + self.b[i] * t + self.c[i] * t * t;
+            state[i + 3] = self.b[i] + 2.0 * self.c[i] * t;
+        }
+        state
+    }
+
+    /// Type 2 (position only) or type 3 (position and velocity) segment over records of `intlen` seconds
+    fn chebyshev_words(&self, with_velocity: bool, num_records: usize, intlen: f64) -> Vec<f64> {
+        let rad = intlen / 2.0;
+        let mut words = Vec::new();
+        for k in 0..num_records {
+            let mid = (k as f64 + 0.5) * intlen;
+            words.extend_from_slice(&[mid, rad]);
+            // With s = (t - mid) / rad, the position is A + B s + C s^2, i.e. (A + C / 2) T0 + B T1 + C / 2 T2
+            for i in 0..3 {
+                let (a, b, c) = (
+                    self.a[i] + self.b[i] * mid + self.c[i] * mid * mid,
+                    (self.b[i] + 2.0 * self.c[i] * mid) * rad,
+                    self.c[i] * rad * rad,
+                );
+                words.extend_from_slice(&[a + c / 2.0, b, c / 2.0]);
+            }
+            if with_velocity {
+                for i in 0..3 {
+                    words.extend_from_slice(&[
+                        self.b[i] + 2.0 * self.c[i] * mid,
+                        2.0 * self.c[i] * rad,
+                        0.0,
+                    ]);
+                }
+            }
+        }
+        let rsize = if with_velocity { 2 + 6 * 3 } else { 2 + 3 * 3 };
+        words.extend_from_slice(&[0.0, intlen, rsize as f64, num_records as f64]);
+        words
+    }
+
+    /// Type 13 segment of Hermite interpolation over windows of 4 states, sampled every `step` seconds
+    fn hermite_words(&self, num_states: usize, step: f64) -> Vec<f64> {
+        let epochs = (0..num_states)
+            .map(|k| k as f64 * step)
+            .collect::<Vec<f64>>();
+        let mut words = Vec::new();
+        for t in &epochs {
+            words.extend(self.state(*t).iter());
+        }
+        words.extend_from_slice(&epochs);
+        words.extend_from_slice(&[3.0, num_states as f64]);
+        words
+    }
+}
+
+/// Builds a Cosm from an SPK of the Earth Moon barycenter (type 2), the Earth (type 3) and the Moon (type 13) over four days past J2000,
+/// committed as `data/tests/spk/emb_earth_moon.bsp`
+#[test]
+fn spk_types_2_3_13_fixture() {
+    let duration_s = 4.0 * 86_400.0;
+    let emb = Quadratic {
+        a: [-2.6e7, 1.33e8, 5.8e7],
+        b: [-29.8, -4.9, -2.1],
+        c: [3e-6, -1e-6, 4e-7],
+    };
+    let earth = Quadratic {
+        a: [-4_000.0, 2_100.0, 900.0],
+        b: [-0.006, -0.010, -0.002],
+        c: [1e-10, 2e-10, -3e-11],
+    };
+    let moon = Quadratic {
+        a: [330_000.0, -170_000.0, -75_000.0],
+        b: [0.5, 0.9, 0.2],
+        c: [-8e-9, -1.6e-8, 3e-9],
+    };
+
+    let bytes = build_spk(&[
+        DafArray::spk(
+            3,
+            0,
+            2,
+            0.0,
+            duration_s,
+            emb.chebyshev_words(false, 4, 86_400.0),
+        ),
+        DafArray::spk(
+            399,
+            3,
+            3,
+            0.0,
+            duration_s,
+            earth.chebyshev_words(true, 2, 2.0 * 86_400.0),
+        ),
+        DafArray::spk(
+            301,
+            3,
+            13,
+            0.0,
+            duration_s,
+            moon.hermite_words(33, 10_800.0),
+        ),
+    ]);
+
+    // The committed fixture is this exact SPK, so that the file loading is tested too
+    assert_eq!(
+        std::fs::read("data/tests/spk/emb_earth_moon.bsp").unwrap(),
+        bytes
+    );
+    let spk = Spk::from_file("data/tests/spk/emb_earth_moon.bsp").unwrap();
+    assert_eq!(
+        spk.segments
+            .iter()
+            .map(|seg| seg.data_type)
+            .collect::<Vec<i32>>(),
+        vec![2, 3, 13]
+    );
+
+    let cosm = Cosm::try_from_spk(spk).unwrap();
+    let ssb = cosm.frame("SSB");
+    let eme2k = cosm.frame("EME2000");
+    let luna = cosm.frame("Luna");
+    assert_eq!(eme2k.gm(), 398_600.435_436);
+
+    for hours in [0.0, 1.3, 23.9, 24.0, 50.5, 77.7, 96.0] {
+        let epoch = Epoch::from_et_seconds(hours * 3_600.0);
+        let t = epoch.to_et_seconds();
+
+        let earth_ssb = cosm.celestial_state(&eme2k.ephem_path(), epoch, ssb, LightTimeCalc::None);
+        let expected = emb.state(t) + earth.state(t);
+        assert!(
+            (earth_ssb.to_cartesian_vec() - expected)
+                .fixed_rows::<3>(0)
+                .norm()
+                < 1e-5,
+            "{hours} h: {earth_ssb}"
+        );
+        assert!(
+            (earth_ssb.to_cartesian_vec() - expected)
+                .fixed_rows::<3>(3)
+                .norm()
+                < 1e-9,
+            "{hours} h: {earth_ssb}"
+        );
+
+        let moon_earth =
+            cosm.celestial_state(&luna.ephem_path(), epoch, eme2k, LightTimeCalc::None);
+        let expected = moon.state(t) - earth.state(t);
+        assert!(
+            (moon_earth.to_cartesian_vec() - expected)
+                .fixed_rows::<3>(0)
+                .norm()
+                < 1e-6,
+            "{hours} h: {moon_earth}"
+        );
+        assert!(
+            (moon_earth.to_cartesian_vec() - expected)
+                .fixed_rows::<3>(3)
+                .norm()
+                < 1e-10,
+            "{hours} h: {moon_earth}"
+        );
+    }
+
+    // Outside of the segments
+    assert!(cosm
+        .try_celestial_state(
+            &luna.ephem_path(),
+            Epoch::from_et_seconds(duration_s + 3_600.0),
+            eme2k,
+            LightTimeCalc::None
+        )
+        .is_err());
+}
@@ -0,0 +1,230 @@
+extern crate nyx_space as nyx;
+
+use nyx::cosmic::{teme_dcm_from_j2000, Cosm};
+use nyx::io::tle::Tle;
+use nyx::linalg::Vector3;
+use nyx::propagators::{Sgp4, Sgp4Gravity};
+use nyx::time::{Epoch, Unit};
+use nyx::NyxError;
+
+const VANGUARD_1: &str = "VANGUARD 1
+1 00005U 58002B   00179.78495062  .00000023  00000-0  28098-4 0  4753
+2 00005  34.2682 348.7242 1859667 331.7664  19.3264 10.82419157413667";
+
+const MOLNIYA: &str = "1 11801U          80230.29629788  .01431103  00000-0  14311-1      13
+2 11801  46.7916 230.4354 7318036  47.4722  10.4117  2.28537848    13";
+
+const MOLNIYA_3_8_L1: &str =
+    "1 08195U 75081A   06176.33215444  .00000099  00000-0  11873-3 0   813";
+const MOLNIYA_3_8_L2: &str =
+    "2 08195  64.1586 279.0717 6877146 264.7651  20.2257  2.00491383225656";
+
+const GEO_28626_L1: &str = "1 28626U 05008A   06176.46683397 -.00000205  00000-0  10000-3 0  2190";
+const GEO_28626_L2: &str = "2 28626   0.0019 286.9433 0000335  13.7918  55.6504  1.00270176  4891";
+
+#[test]
+fn tle_parsing() {
+    let tle: Tle = VANGUARD_1.parse().unwrap();
+    assert_eq!(tle.name, Some("VANGUARD 1".to_string()));
+    assert_eq!(tle.norad_id, 5);
+    assert_eq!(tle.classification, 'U');
+    assert_eq!(tle.intl_designator, "58002B");
+    assert_eq!(tle.element_number, 475);
+    assert_eq!(tle.rev_number, 41366);
+    assert_eq!(tle.inclination_deg, 34.2682);
+    assert_eq!(tle.raan_deg, 348.7242);
+    assert!((tle.eccentricity - 0.1859667).abs() < 1e-15);
+    assert_eq!(tle.mean_motion_rev_day, 10.82419157);
+    assert_eq!(tle.mean_motion_dot, 2.3e-7);
+    assert_eq!(tle.mean_motion_ddot, 0.0);
+    assert!((tle.bstar - 2.8098e-5).abs() < 1e-15);
+    // Day 179.78495062 of 2000
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2000, 6, 27) + 0.78495062 * Unit::Day;
+    assert!((tle.epoch - epoch).abs() < 1 * Unit::Microsecond);
+
+    // Element sets without an international designator, title line, or checksum
+    let tle: Tle = MOLNIYA.parse().unwrap();
+    assert_eq!(tle.name, None);
+    assert!(tle.intl_designator.is_empty());
+    assert!((tle.bstar - 0.014311).abs() < 1e-15);
+    assert!(Epoch::from_gregorian_utc_at_midnight(1980, 8, 17) < tle.epoch);
+
+    // Several sets, with the three line format
+    let many = format!("0 {VANGUARD_1}\n\n{MOLNIYA}\n");
+    let tles = Tle::load_many(&many).unwrap();
+    assert_eq!(tles.len(), 2);
+    assert_eq!(tles[0].name, Some("VANGUARD 1".to_string()));
+    assert_eq!(tles[1].norad_id, 11801);
+    assert!(many.parse::<Tle>().is_err());
+
+    // Errors
+    let lines: Vec<&str> = VANGUARD_1.lines().collect();
+    let bad_checksum = lines[1].replace("4753", "4754");
+    assert!(matches!(
+        Tle::from_lines(&bad_checksum, lines[2]),
+        Err(NyxError::LoadingError(_))
+    ));
+    let other_object = MOLNIYA.lines().nth(1).unwrap();
+    assert!(Tle::from_lines(lines[1], other_object).is_err());
+    assert!(Tle::from_lines(lines[2], lines[1]).is_err());
+    assert!(Tle::from_lines(lines[1], "2 00005").is_err());
+}
+
+#[test]
+fn sgp4_vallado_vectors() {
+    // Test cases of Vallado et al., "Revisiting Spacetrack Report #3", AIAA 2006-6753, with WGS-72
+    let cosm = Cosm::de438();
+    let teme = cosm.frame("TEME");
+    let check = |sgp4: &Sgp4, tsince: f64, r: [f64; 3], v: [f64; 3]| {
+        let (r_km, v_km_s) = sgp4.propagate_minutes(tsince).unwrap();
+        let r_err = (r_km - Vector3::from(r)).norm();
+        let v_err = (v_km_s - Vector3::from(v)).norm();
+        assert!(r_err < 1e-6, "{tsince} min: {r_err} km");
+        assert!(v_err < 1e-7, "{tsince} min: {v_err} km/s");
+    };
+
+    let vanguard =
+        Sgp4::with_gravity(VANGUARD_1.parse().unwrap(), Sgp4Gravity::Wgs72, teme).unwrap();
+    assert!(!vanguard.is_deep_space());
+    check(
+        &vanguard,
+        0.0,
+        [7022.46529266, -1400.08296755, 0.03995155],
+        [1.893841015, 6.405893759, 4.534807250],
+    );
+    check(
+        &vanguard,
+        360.0,
+        [-7154.03120202, -3783.17682504, -3536.19412294],
+        [4.741887409, -4.151817765, -2.093935425],
+    );
+    check(
+        &vanguard,
+        720.0,
+        [-7134.59340119, 6531.68641334, 3260.27186483],
+        [-4.113793027, -2.911922039, -2.557327851],
+    );
+    check(
+        &vanguard,
+        1080.0,
+        [5568.53901181, 4492.06992591, 3863.87641983],
+        [-4.209106476, 5.159719888, 2.744852980],
+    );
+
+    let tle = Tle::from_lines(
+        "1 06251U 62025E   06176.82412014  .00008885  00000-0  12808-3 0  3985",
+        "2 06251  58.0579  54.0425 0030035 139.1568 221.1854 15.56387291  6774",
+    )
+    .unwrap();
+    let dmsp = Sgp4::with_gravity(tle, Sgp4Gravity::Wgs72, teme).unwrap();
+    check(
+        &dmsp,
+        0.0,
+        [3988.31022699, 5498.96657235, 0.90055879],
+        [-3.290032738, 2.357652820, 6.496623475],
+    );
+
+    let molniya = Sgp4::with_gravity(MOLNIYA.parse().unwrap(), Sgp4Gravity::Wgs72, teme).unwrap();
+    assert!(molniya.is_deep_space());
+    check(
+        &molniya,
+        0.0,
+        [7473.37102491, 428.94748312, 5828.74846783],
+        [5.107155401, 6.444680305, -0.186133297],
+    );
+
+    // Molniya 3-8, in the half day resonance
+    let molniya_3_8 = Tle::from_lines(MOLNIYA_3_8_L1, MOLNIYA_3_8_L2).unwrap();
+    let molniya_3_8 = Sgp4::with_gravity(molniya_3_8, Sgp4Gravity::Wgs72, teme).unwrap();
+    assert!(molniya_3_8.is_deep_space());
+    check(
+        &molniya_3_8,
+        0.0,
+        [2349.89483350, -14785.93811562, 0.02119378],
+        [2.721488096, -3.256811655, 4.498416672],
+    );
+}
+
+#[test]
+fn sgp4_resonance_integration() {
+    // The resonances are integrated from epoch with steps of half a day, so the trajectory must be as smooth
+    // across a step (e.g. at 720 min) as it is a minute later
+    let cosm = Cosm::de438();
+    let teme = cosm.frame("TEME");
+    let second_diff = |sgp4: &Sgp4, tsince: f64| {
+        let dt_min = 1e-3;
+        let (r_before, _) = sgp4.propagate_minutes(tsince - dt_min).unwrap();
+        let (r_km, _) = sgp4.propagate_minutes(tsince).unwrap();
+        let (r_after, _) = sgp4.propagate_minutes(tsince + dt_min).unwrap();
+        r_before + r_after - 2.0 * r_km
+    };
+    let check_smooth = |sgp4: &Sgp4, tsince: f64| {
+        let err = (second_diff(sgp4, tsince) - second_diff(sgp4, tsince + 1.0)).norm();
+        assert!(err < 1e-6, "{tsince} min: {err} km");
+    };
+
+    let molniya_3_8 = Tle::from_lines(MOLNIYA_3_8_L1, MOLNIYA_3_8_L2).unwrap();
+    let molniya_3_8 = Sgp4::with_gravity(molniya_3_8, Sgp4Gravity::Wgs72, teme).unwrap();
+    let geo = Tle::from_lines(GEO_28626_L1, GEO_28626_L2).unwrap();
+    let geo = Sgp4::with_gravity(geo, Sgp4Gravity::Wgs72, teme).unwrap();
+    assert!(geo.is_deep_space());
+
+    for tsince in [-1440.0, -720.0, 360.0, 720.0, 1440.0, 2880.0] {
+        check_smooth(&molniya_3_8, tsince);
+        check_smooth(&geo, tsince);
+    }
+
+    // The resonances keep the orbits near their commensurate periods over several days, forward and backward
+    for tsince in (-10..=10).map(|day| f64::from(day) * 1440.0) {
+        let (r_km, _) = geo.propagate_minutes(tsince).unwrap();
+        assert!((r_km.norm() - 42_164.0).abs() < 50.0, "{tsince} min");
+        let (r_km, v_km_s) = molniya_3_8.propagate_minutes(tsince).unwrap();
+        // Vis-viva with the WGS-72 gravitational parameter
+        let sma_km = 1.0 / (2.0 / r_km.norm() - v_km_s.norm_squared() / 398_600.8);
+        assert!(
+            (sma_km - 26_560.0).abs() < 50.0,
+            "{tsince} min: {sma_km} km"
+        );
+    }
+}
+
+#[test]
+fn sgp4_teme_frame() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let tle: Tle = VANGUARD_1.parse().unwrap();
+    let sgp4 = Sgp4::from_tle(tle.clone(), &cosm).unwrap();
+    assert_eq!(sgp4.gravity(), Sgp4Gravity::Wgs72);
+    assert_eq!(sgp4.frame, cosm.frame("Earth TEME"));
+    assert_eq!(sgp4.frame.to_string(), "TEME");
+    // TEME is quasi-inertial
+    assert!(!sgp4.frame.is_body_fixed());
+
+    let epoch = tle.epoch + 2 * Unit::Hour;
+    let state = sgp4.at(epoch).unwrap();
+    assert_eq!(state.frame, sgp4.frame);
+    // So it rotates with the IAU Earth frame, like EME2000
+    assert_eq!(
+        cosm.try_angular_velocity(&sgp4.frame, epoch).unwrap(),
+        cosm.try_angular_velocity(&eme2k, epoch).unwrap()
+    );
+    assert_eq!(state.epoch, epoch);
+    let (r_km, _) = sgp4.propagate_minutes(120.0).unwrap();
+    assert!((state.radius() - r_km).norm() < 1e-6);
+
+    // TEME is in the frame tree of the Cosm, and only differs from EME2000 by precession and nutation
+    let state_eme2k = cosm.frame_chg(&state, eme2k);
+    let expected = teme_dcm_from_j2000(epoch).transpose() * state.radius();
+    assert!((state_eme2k.radius() - expected).norm() < 1e-6);
+    assert!((state_eme2k.rmag_km() - state.rmag_km()).abs() < 1e-6);
+    // Half a year of precession is about 25 arcseconds, i.e. close to a kilometer at that altitude
+    assert!((state_eme2k.radius() - state.radius()).norm() > 0.5);
+
+    let traj = sgp4
+        .to_traj(tle.epoch, tle.epoch + 6 * Unit::Hour, 1 * Unit::Minute)
+        .unwrap();
+    assert_eq!(traj.states.len(), 361);
+    assert_eq!(traj.last().epoch, tle.epoch + 6 * Unit::Hour);
+    let node = traj.at(tle.epoch + 1 * Unit::Hour).unwrap();
+    assert_eq!(node, sgp4.at(node.epoch).unwrap());
+}
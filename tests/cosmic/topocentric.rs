@@ -0,0 +1,143 @@
+extern crate nyx_space as nyx;
+
+use nyx::cosmic::{Cosm, Frame, FrameError, Orbit, SurfacePoint};
+use nyx::dynamics::OrbitalDynamics;
+use nyx::linalg::{Matrix3, Vector3};
+use nyx::od::GroundStation;
+use nyx::propagators::Propagator;
+use nyx::time::{Epoch, Unit};
+use nyx::NyxError;
+
+#[test]
+fn topocentric_dcms() {
+    let cosm = Cosm::de438_raw();
+    let iau_earth = cosm.frame("IAU Earth");
+    let epoch = Epoch::from_gregorian_tai_at_noon(2022, 3, 1);
+
+    let point = SurfacePoint::new(40.427222, 4.250556, 0.834939, iau_earth);
+    let sez = point.dcm_to_topocentric(Frame::SEZ);
+    let enu = point.dcm_to_topocentric(Frame::ENU);
+    let ned = point.dcm_to_topocentric(Frame::NED);
+    for dcm in [sez, enu, ned] {
+        assert!((dcm * dcm.transpose() - Matrix3::identity()).norm() < 1e-12);
+    }
+    // SEZ and NED are right handed, and are permutations of the ENU axes
+    assert!((sez.determinant() - 1.0).abs() < 1e-12);
+    assert!((ned.determinant() - 1.0).abs() < 1e-12);
+    assert!((sez.row(0) + enu.row(1)).norm() < 1e-12);
+    assert!((ned.row(0) - enu.row(1)).norm() < 1e-12);
+    assert!((ned.row(2) + enu.row(2)).norm() < 1e-12);
+
+    // Matches the topocentric to body fixed rotation of the state of that point
+    let state = point.to_orbit(epoch);
+    assert_eq!(state.vmag_km_s(), 0.0);
+    for frame in [Frame::SEZ, Frame::ENU, Frame::NED] {
+        let dcm = state.dcm_from_traj_frame(frame).unwrap();
+        assert!((dcm.transpose() - point.dcm_to_topocentric(frame)).norm() < 1e-9);
+    }
+
+    // Errors
+    assert_eq!(
+        point.try_dcm_to_topocentric(Frame::RIC),
+        Err(NyxError::FrameError(FrameError::NotTopocentric(Frame::RIC)))
+    );
+    let eme2k = cosm.frame("EME2000");
+    assert_eq!(
+        SurfacePoint::try_new(0.0, 0.0, 0.0, eme2k),
+        Err(NyxError::FrameError(FrameError::NotBodyFixed(eme2k)))
+    );
+    assert!(SurfacePoint::try_new(0.0, 0.0, 0.0, Frame::VNC).is_err());
+}
+
+#[test]
+fn azimuth_elevation_range() {
+    let cosm = Cosm::de438_raw();
+    let iau_earth = cosm.frame("IAU Earth");
+    let eme2k = cosm.frame("EME2000");
+    let epoch = Epoch::from_gregorian_tai_at_noon(2022, 3, 1);
+
+    let point = SurfacePoint::new(-35.398333, 148.981944, 0.691750, iau_earth);
+    let enu = point.dcm_to_topocentric(Frame::ENU);
+    let fixed_at = |offset: Vector3<f64>| {
+        let r = point.radius() + enu.transpose() * offset;
+        Orbit::cartesian(r[0], r[1], r[2], 0.0, 0.0, 0.0, epoch, iau_earth)
+    };
+
+    // Overhead, then on the horizon due north and due east
+    let overhead = point.azimuth_elevation_range(&fixed_at(Vector3::new(0.0, 0.0, 100.0)), &cosm);
+    assert!((overhead.elevation_deg - 90.0).abs() < 1e-9);
+    assert!((overhead.range_km - 100.0).abs() < 1e-9);
+    assert!(overhead.range_rate_km_s.abs() < 1e-12);
+    let north = point.azimuth_elevation_range(&fixed_at(Vector3::new(0.0, 10.0, 0.0)), &cosm);
+    assert!(north.elevation_deg.abs() < 1e-9);
+    assert!(north.azimuth_deg < 1e-9 || (north.azimuth_deg - 360.0).abs() < 1e-9);
+    let east = point.azimuth_elevation_range(&fixed_at(Vector3::new(10.0, 0.0, 10.0)), &cosm);
+    assert!((east.azimuth_deg - 90.0).abs() < 1e-9);
+    assert!((east.elevation_deg - 45.0).abs() < 1e-9);
+
+    // The rates match a finite difference of the angles and range of a spacecraft
+    let leo = Orbit::keplerian(7_000.0, 1e-3, 51.6, 130.0, 20.0, 30.0, epoch, eme2k);
+    let aer = point.azimuth_elevation_range(&leo, &cosm);
+    let step_s = 0.5;
+    let before =
+        point.azimuth_elevation_range(&leo.at_epoch(epoch - step_s * Unit::Second).unwrap(), &cosm);
+    let after =
+        point.azimuth_elevation_range(&leo.at_epoch(epoch + step_s * Unit::Second).unwrap(), &cosm);
+    let az_rate = (after.azimuth_deg - before.azimuth_deg) / (2.0 * step_s);
+    let el_rate = (after.elevation_deg - before.elevation_deg) / (2.0 * step_s);
+    let range_rate = (after.range_km - before.range_km) / (2.0 * step_s);
+    assert!((aer.azimuth_rate_deg_s - az_rate).abs() < 1e-6);
+    assert!((aer.elevation_rate_deg_s - el_rate).abs() < 1e-6);
+    assert!((aer.range_rate_km_s - range_rate).abs() < 1e-6);
+
+    // The topocentric states are consistent with one another
+    let sez = point.frame_chg(&leo, Frame::SEZ, &cosm);
+    let ned = point.frame_chg(&leo, Frame::NED, &cosm);
+    assert_eq!(sez.frame, Frame::SEZ);
+    assert!((sez.rmag_km() - aer.range_km).abs() < 1e-9);
+    assert!((ned.x_km + sez.x_km).abs() < 1e-9);
+    assert!((ned.vz_km_s + sez.vz_km_s).abs() < 1e-12);
+
+    // Ground stations use the same definition of the azimuth and elevation
+    let gs = GroundStation::from_point(
+        "Canberra".to_string(),
+        point.latitude_deg,
+        point.longitude_deg,
+        point.height_km,
+        iau_earth,
+    );
+    assert_eq!(gs.surface_point(), point);
+    let (az_deg, el_deg, _, _) = gs.azimuth_elevation_of(leo, &cosm);
+    assert!((az_deg - aer.azimuth_deg).abs() < 1e-12);
+    assert!((el_deg - aer.elevation_deg).abs() < 1e-12);
+}
+
+#[test]
+fn lunar_surface_trajectory() {
+    let cosm = Cosm::de438();
+    let iau_moon = cosm.frame("IAU Moon");
+    let moon_j2k = cosm.frame("Moon J2000");
+    let epoch = Epoch::from_gregorian_tai_at_noon(2022, 3, 1);
+
+    // A lander near the lunar south pole and a spacecraft in a low lunar orbit
+    let site = SurfacePoint::new(-89.5, 222.0, 0.0, iau_moon);
+    let llo = Orbit::keplerian(1_837.0, 1e-3, 89.9, 0.0, 0.0, 0.0, epoch, moon_j2k);
+    let (_, traj) = Propagator::default(OrbitalDynamics::two_body())
+        .with(llo)
+        .for_duration_with_traj(Unit::Hour * 2)
+        .unwrap();
+
+    let topo_traj = traj
+        .to_topocentric_frame(&site, Frame::ENU, cosm.clone())
+        .unwrap();
+    assert_eq!(topo_traj.states.len(), traj.states.len());
+    for (state, topo) in traj.states.iter().zip(topo_traj.states.iter()) {
+        assert_eq!(topo.frame, Frame::ENU);
+        let expected = site.frame_chg(state, Frame::ENU, &cosm);
+        assert!((topo.radius() - expected.radius()).norm() < 1e-9);
+        // Elevation computed from the ENU components
+        let aer = site.azimuth_elevation_range(state, &cosm);
+        let el_deg = (topo.z_km / topo.rmag_km()).asin().to_degrees();
+        assert!((el_deg - aer.elevation_deg).abs() < 1e-9);
+    }
+}
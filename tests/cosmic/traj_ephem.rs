@@ -0,0 +1,77 @@
+extern crate nyx_space as nyx;
+
+use nyx::cosmic::{Cosm, LightTimeCalc, Orbit};
+use nyx::dynamics::OrbitalDynamics;
+use nyx::propagators::Propagator;
+use nyx::time::{Epoch, TimeSeries, Unit};
+use std::sync::Arc;
+
+#[test]
+fn traj_as_ephemeris() {
+    let _ = pretty_env_logger::try_init();
+    let mut cosm = Cosm::de438_raw();
+    let eme2k = cosm.frame("EME2000");
+    let luna = cosm.frame("Luna");
+
+    let start = Epoch::from_gregorian_utc_at_noon(2021, 1, 1);
+    // A relay satellite in GEO
+    let relay = Orbit::keplerian(42_164.0, 1e-4, 0.1, 30.0, 40.0, 50.0, start, eme2k);
+    let (_, relay_traj) = Propagator::default(OrbitalDynamics::two_body())
+        .with(relay)
+        .for_duration_with_traj(Unit::Day * 1)
+        .unwrap();
+
+    let relay_frame = cosm
+        .append_traj_ephemeris("Relay", relay_traj.clone())
+        .unwrap();
+    assert_eq!(cosm.frame("Relay J2000"), relay_frame);
+    assert_eq!(relay_frame.gm(), 0.0);
+    assert_eq!(relay_frame.ephem_path()[..2], eme2k.ephem_path()[..]);
+    // Names must be unique
+    assert!(cosm
+        .append_traj_ephemeris("Relay", relay_traj.clone())
+        .is_err());
+
+    let cosm = Arc::new(cosm);
+
+    // A user spacecraft in LEO
+    let user = Orbit::keplerian(7_000.0, 1e-3, 51.6, 10.0, 20.0, 30.0, start, eme2k);
+
+    for epoch in TimeSeries::inclusive(start, start + Unit::Hour * 23, Unit::Minute * 17) {
+        let expected = relay_traj.at(epoch).unwrap();
+        // State of the relay as seen from the Earth
+        let relay_state =
+            cosm.celestial_state(&relay_frame.ephem_path(), epoch, eme2k, LightTimeCalc::None);
+        assert!((relay_state.radius() - expected.radius()).norm() < 1e-9);
+        assert!((relay_state.velocity() - expected.velocity()).norm() < 1e-12);
+
+        // State of the user as seen from the relay
+        let mut user_state = user;
+        user_state.epoch = epoch;
+        let from_relay = cosm.frame_chg(&user_state, relay_frame);
+        assert!((from_relay.radius() - (user_state.radius() - expected.radius())).norm() < 1e-9);
+        // And back
+        let back = cosm.frame_chg(&from_relay, eme2k);
+        assert!((back.radius() - user_state.radius()).norm() < 1e-9);
+
+        // The Moon as seen from the relay must be the Moon from the Earth minus the relay
+        let moon_from_relay =
+            cosm.celestial_state(&luna.ephem_path(), epoch, relay_frame, LightTimeCalc::None);
+        let moon_from_earth =
+            cosm.celestial_state(&luna.ephem_path(), epoch, eme2k, LightTimeCalc::None);
+        assert!(
+            (moon_from_relay.radius() - (moon_from_earth.radius() - expected.radius())).norm()
+                < 1e-6
+        );
+    }
+
+    // Outside of the trajectory
+    assert!(cosm
+        .try_celestial_state(
+            &relay_frame.ephem_path(),
+            start + Unit::Day * 2,
+            eme2k,
+            LightTimeCalc::None
+        )
+        .is_err());
+}
@@ -1,11 +1,18 @@
 extern crate nyx_space as nyx;
 
-use nyx::cosmic::{Cosm, Orbit, Spacecraft};
-use nyx::dynamics::{Drag, OrbitalDynamics, SolarPressure, SpacecraftDynamics};
-use nyx::linalg::Vector6;
+use nyx::cosmic::{Bodies, Cosm, LightTimeCalc, Orbit, Spacecraft};
+use nyx::dynamics::pointing::{NadirPointing, SunPointing, VelocityPointing};
+use nyx::dynamics::{
+    AlbedoPressure, ConstantDrag, Drag, ForceModel, HorizontalWind, MacroModel, OrbitalDynamics,
+    Plate, SolarPressure, SpacecraftDynamics,
+};
+use nyx::io::space_weather::SpaceWeather;
+use nyx::linalg::{Vector3, Vector6};
 use nyx::propagators::Propagator;
 use nyx::time::{Epoch, Unit};
 use nyx::utils::rss_orbit_vec_errors;
+use nyx::State;
+use std::sync::Arc;
 
 #[test]
 fn srp_earth_full_vis() {
@@ -332,3 +339,408 @@ fn std_atm_drag_earth_low() {
 
     */
 }
+
+#[test]
+fn drag_force_units() {
+    // The density is in kg/m^3 but the velocity is in km/s, so the drag force must be scaled by 1e3 to be in kN
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let dt = Epoch::from_gregorian_tai_at_midnight(2000, 1, 1);
+    let orbit = Orbit::cartesian(7000.0, 0.0, 0.0, 0.0, 7.5, 0.0, dt, eme2k);
+    let sc = Spacecraft::from_srp_defaults(orbit, 500.0, 1.0).with_drag(10.0, 2.0);
+
+    // Computing the drag in the inertial frame, the velocity relative to the atmosphere is the orbital velocity
+    let drag = ConstantDrag {
+        rho: 1e-12,
+        drag_frame: eme2k,
+        cosm,
+    };
+
+    // a = -1/2 * rho * Cd * A / m * |v| * v = -0.5 * 1e-12 * 2.0 * 10.0 / 500.0 * (7500 m/s)^2 = -1.125e-6 m/s^2
+    let accel_km_s2 = drag.eom(&sc).unwrap() / sc.mass_kg();
+    println!("{accel_km_s2}");
+    let expected_km_s2 = Vector3::new(0.0, -1.125e-9, 0.0);
+    assert!((accel_km_s2 - expected_km_s2).norm() < 1e-12 * expected_km_s2.norm());
+}
+
+#[test]
+fn jb2008_drag_earth_leo() {
+    let cosm = Cosm::de438_gmat();
+    let eme2k = cosm.frame("EME2000");
+
+    // Synthetic space weather of moderate solar activity around the epoch
+    let mut csv = "DATE,KP1,KP2,KP3,KP4,KP5,KP6,KP7,KP8,AP1,AP2,AP3,AP4,AP5,AP6,AP7,AP8,AP_AVG,F10.7_OBS,F10.7_OBS_CENTER81,F10.7_OBS_LAST81\n".to_string();
+    let mut solfsmy =
+        "# YYYY DDD   JulianDay  F10   F81c  S10   S81c  M10   M81c  Y10   Y81c  Ssrc\n"
+            .to_string();
+    for day in 1..=10 {
+        let f10 = 140.0 + day as f64;
+        csv.push_str(&format!(
+            "2023-03-{day:02},20,20,20,20,20,20,20,20,7,7,7,7,7,7,7,7,7,{f10},145.0,145.0\n"
+        ));
+        solfsmy.push_str(&format!(
+            "2023 {} {:.1} {f10} 145.0 {f10} 140.0 {f10} 142.0 {f10} 141.0 4BH8\n",
+            59 + day,
+            2_460_004.0 + day as f64
+        ));
+    }
+    let space_weather = Arc::new(
+        SpaceWeather::from_celestrak_csv(&csv)
+            .unwrap()
+            .with_solfsmy(&solfsmy)
+            .unwrap(),
+    );
+
+    let dt = Epoch::from_gregorian_utc_at_midnight(2023, 3, 7);
+    let orbit = Orbit::keplerian(6778.0, 0.001, 51.6, 10.0, 20.0, 30.0, dt, eme2k);
+
+    let dry_mass = 300.0;
+    let sc = Spacecraft::from_srp_defaults(orbit, dry_mass, 1.0).with_drag(1.0, 2.2);
+
+    let mut decay_km = Vec::new();
+    for drag in [
+        Drag::jb2008(space_weather.clone(), cosm.clone()),
+        Drag::std_atm1976(cosm.clone()),
+    ] {
+        println!("{drag}");
+        let osc = cosm.frame_chg(&orbit, drag.drag_frame);
+        let rho = drag.density_kg_m3(&osc).unwrap();
+        println!("rho = {rho:e} kg/m^3");
+        assert!(rho > 1e-12 && rho < 1e-10);
+
+        let sc_dyn = SpacecraftDynamics::from_model(OrbitalDynamics::two_body(), drag);
+        let (final_state, _) = Propagator::default(sc_dyn)
+            .with(sc)
+            .for_duration_with_traj(Unit::Day * 1)
+            .unwrap();
+        println!("{}", final_state.orbit);
+        decay_km.push(orbit.sma_km() - final_state.orbit.sma_km());
+    }
+
+    // Both models decay the orbit by a similar amount
+    println!("SMA decay over a day: {decay_km:?} km");
+    assert!(decay_km[0] > 0.01 && decay_km[0] < 1.0);
+    assert!(decay_km[0] / decay_km[1] > 0.2 && decay_km[0] / decay_km[1] < 5.0);
+
+    // Propagating outside of the space weather data is an error
+    let late = Spacecraft::from_srp_defaults(
+        Orbit::keplerian(
+            6778.0,
+            0.001,
+            51.6,
+            10.0,
+            20.0,
+            30.0,
+            dt + Unit::Day * 10,
+            eme2k,
+        ),
+        dry_mass,
+        1.0,
+    );
+    let sc_dyn = SpacecraftDynamics::from_model(
+        OrbitalDynamics::two_body(),
+        Drag::jb2008(space_weather, cosm),
+    );
+    assert!(Propagator::default(sc_dyn)
+        .with(late)
+        .for_duration(Unit::Hour * 1)
+        .is_err());
+}
+
+#[test]
+fn drag_corotating_atmosphere() {
+    let cosm = Cosm::de438_gmat();
+    let eme2k = cosm.frame("EME2000");
+    let iau_earth = cosm.frame("IAU Earth");
+
+    let dt = Epoch::from_gregorian_utc_at_midnight(2023, 3, 7);
+    // A spacecraft at rest in the body fixed frame moves with the atmosphere
+    let at_rest = Orbit::cartesian(4000.0, 4000.0, 3000.0, 0.0, 0.0, 0.0, dt, iau_earth);
+    let orbit = cosm.frame_chg(&at_rest, eme2k);
+    let sc = Spacecraft::from_srp_defaults(orbit, 300.0, 1.0).with_drag(1.0, 2.2);
+
+    let const_drag = ConstantDrag {
+        rho: 1e-11,
+        drag_frame: iau_earth,
+        cosm: cosm.clone(),
+    };
+    let drag = Drag::std_atm1976(cosm.clone());
+    for force in [const_drag.eom(&sc).unwrap(), drag.eom(&sc).unwrap()] {
+        println!("{force}");
+        assert!(force.norm() < 1e-15, "no drag expected, got {force}");
+    }
+
+    // With an eastward wind, the drag pushes the spacecraft eastward
+    let windy = drag.with_wind(HorizontalWind::new(100.0, 0.0));
+    println!("{windy}");
+    let force = windy.eom(&sc).unwrap();
+    let east_eme2k = cosm
+        .frame_chg(
+            &Orbit::cartesian(4000.0, 4000.0, 3000.0, -1.0, 1.0, 0.0, dt, iau_earth),
+            eme2k,
+        )
+        .velocity()
+        - orbit.velocity();
+    println!("{force}");
+    assert!(force.norm() > 0.0);
+    assert!((force.normalize().dot(&east_eme2k.normalize()) - 1.0).abs() < 1e-9);
+}
+
+#[test]
+fn drag_partials() {
+    let cosm = Cosm::de438_gmat();
+    let eme2k = cosm.frame("EME2000");
+
+    let dt = Epoch::from_gregorian_utc_at_midnight(2023, 3, 7);
+    let orbit = Orbit::keplerian(6778.0, 0.001, 51.6, 10.0, 20.0, 30.0, dt, eme2k);
+    let sc = Spacecraft::from_srp_defaults(orbit, 300.0, 1.0).with_drag(1.0, 2.2);
+
+    let models: Vec<Arc<dyn ForceModel>> = vec![
+        Arc::new(ConstantDrag {
+            rho: 1e-11,
+            drag_frame: cosm.frame("IAU Earth"),
+            cosm: cosm.clone(),
+        }),
+        Drag::std_atm1976(cosm.clone()).with_wind(HorizontalWind::new(50.0, -20.0)),
+    ];
+
+    for model in models {
+        println!("{model}");
+        let (force, grad) = model.dual_eom(&sc).unwrap();
+        assert!((force - model.eom(&sc).unwrap()).norm() < 1e-20);
+
+        // Compare with central differences
+        for j in 0..6 {
+            let step = if j < 3 { 1e-3 } else { 1e-6 };
+            let mut plus = sc;
+            let mut minus = sc;
+            let mut delta = Vector6::zeros();
+            delta[j] = step;
+            plus.orbit = orbit + delta;
+            minus.orbit = orbit + -delta;
+            let fd = (model.eom(&plus).unwrap() - model.eom(&minus).unwrap()) / (2.0 * step);
+            let err = (fd - grad.column(j)).norm();
+            println!("column {j}: finite diff = {fd:e}\terr = {err:e}");
+            assert!(err <= 1e-3 * fd.norm() + 1e-20, "partials of column {j}");
+        }
+
+        // The drag is linear in Cd
+        let expected = force / sc.drag.cd;
+        assert!((grad.column(7) - expected).norm() < 1e-12 * expected.norm());
+    }
+}
+
+#[test]
+fn drag_stm_propagation() {
+    let cosm = Cosm::de438_gmat();
+    let eme2k = cosm.frame("EME2000");
+
+    let dt = Epoch::from_gregorian_utc_at_midnight(2023, 3, 7);
+    let orbit = Orbit::keplerian(6778.0, 0.001, 51.6, 10.0, 20.0, 30.0, dt, eme2k);
+    let sc = Spacecraft::from_srp_defaults(orbit, 300.0, 1.0).with_drag(1.0, 2.2);
+
+    let sc_dyn =
+        SpacecraftDynamics::from_model(OrbitalDynamics::two_body(), Drag::std_atm1976(cosm));
+
+    let nominal = Propagator::default(sc_dyn.clone())
+        .with(sc)
+        .for_duration(Unit::Hour * 1)
+        .unwrap();
+    let with_stm = Propagator::default(sc_dyn.clone())
+        .with(sc.with_stm())
+        .for_duration(Unit::Hour * 1)
+        .unwrap();
+
+    assert!((nominal.orbit.radius() - with_stm.orbit.radius()).norm() < 1e-9);
+
+    // The STM maps an initial perturbation to the final state
+    let delta = Vector6::new(0.01, -0.01, 0.005, 1e-5, 2e-5, -1e-5);
+    let mut perturbed = sc;
+    perturbed.orbit = orbit + delta;
+    let perturbed_final = Propagator::default(sc_dyn)
+        .with(perturbed)
+        .for_duration(Unit::Hour * 1)
+        .unwrap();
+
+    let stm = with_stm.stm().unwrap();
+    let predicted = stm.fixed_view::<6, 6>(0, 0) * delta;
+    let actual = perturbed_final.orbit.to_cartesian_vec() - nominal.orbit.to_cartesian_vec();
+    println!("predicted: {predicted}\nactual: {actual}");
+    assert!((predicted - actual).norm() < 1e-3 * actual.norm());
+}
+
+#[test]
+fn srp_macro_model() {
+    let cosm = Cosm::de438_gmat();
+    let eme2k = cosm.frame("EME2000");
+
+    let dt = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+    let orbit = Orbit::keplerian(29_600.0, 1e-3, 56.0, 10.0, 20.0, 30.0, dt, eme2k);
+    let sc = Spacecraft::from_srp_defaults(orbit, 700.0, 10.0);
+
+    // A single Sun facing plate is equivalent to a cannonball of the same area with Cr = 1 + specular + 2/3 diffuse
+    let (specular, diffuse) = (0.2, 0.3);
+    let plate = MacroModel::new(vec![
+        Plate::new(Vector3::z(), 10.0, specular, diffuse).unwrap()
+    ])
+    .with_pointing(SunPointing::new(cosm.clone()));
+    let srp = SolarPressure::default(eme2k, cosm.clone());
+    let srp_plate = srp.with_macro_model(Arc::new(plate));
+    println!("{srp_plate}");
+
+    let cannonball = srp
+        .eom(&sc.with_cr(1.0 + specular + 2.0 * diffuse / 3.0))
+        .unwrap();
+    let force = srp_plate.eom(&sc).unwrap();
+    println!("{cannonball}\n{force}");
+    assert!((force - cannonball).norm() < 1e-9 * cannonball.norm());
+
+    let (dual_force, grad) = srp_plate.dual_eom(&sc).unwrap();
+    assert_eq!(dual_force, force);
+    // The SRP area and Cr of the spacecraft are not used
+    assert_eq!(grad.column(6).norm(), 0.0);
+
+    // A GNSS like box-wing, nadir pointing with the solar arrays rotating about the body Y axis
+    let box_wing = MacroModel::box_wing(
+        Vector3::new(4.0, 4.0, 3.0),
+        0.2,
+        0.5,
+        20.0,
+        Vector3::y(),
+        0.05,
+        0.2,
+    )
+    .unwrap();
+    let srp_box_wing = srp.with_macro_model(Arc::new(
+        box_wing.clone().with_pointing(NadirPointing::new()),
+    ));
+
+    let sc_dyn = SpacecraftDynamics::from_model(OrbitalDynamics::two_body(), srp_box_wing);
+    let final_box_wing = Propagator::default(sc_dyn)
+        .with(sc)
+        .for_duration(Unit::Day * 1)
+        .unwrap();
+
+    let sc_dyn = SpacecraftDynamics::from_model(OrbitalDynamics::two_body(), srp.clone());
+    let final_cannonball = Propagator::default(sc_dyn)
+        .with(sc)
+        .for_duration(Unit::Day * 1)
+        .unwrap();
+
+    let delta = final_box_wing.orbit - final_cannonball.orbit;
+    println!(
+        "box-wing vs cannonball after one day: {:.3} m",
+        delta.rmag_km() * 1e3
+    );
+    assert!(delta.rmag_km() > 1e-3);
+
+    // Without an attitude nor a pointing law, the macro-model cannot be oriented
+    let no_attitude = srp.with_macro_model(Arc::new(box_wing));
+    assert!(no_attitude.eom(&sc).is_err());
+}
+
+#[test]
+fn drag_macro_model() {
+    let cosm = Cosm::de438_gmat();
+    let eme2k = cosm.frame("EME2000");
+
+    let dt = Epoch::from_gregorian_utc_at_midnight(2023, 3, 7);
+    let orbit = Orbit::keplerian(6778.0, 0.001, 51.6, 10.0, 20.0, 30.0, dt, eme2k);
+    let sc = Spacecraft::from_srp_defaults(orbit, 300.0, 1.0).with_drag(2.0, 2.2);
+
+    // A single plate facing the velocity has the same drag as a cannonball of the same area,
+    // except for the small angle between the inertial velocity and that relative to the atmosphere.
+    let plate = MacroModel::new(vec![Plate::new(Vector3::x(), 2.0, 0.0, 0.0).unwrap()])
+        .with_pointing(VelocityPointing::new());
+    let drag = Drag::std_atm1976(cosm);
+    let drag_plate = drag.with_macro_model(Arc::new(plate));
+    println!("{drag_plate}");
+
+    let cannonball = drag.eom(&sc).unwrap();
+    let force = drag_plate.eom(&sc).unwrap();
+    println!("{cannonball}\n{force}");
+    let ratio = force.norm() / cannonball.norm();
+    assert!(ratio > 0.99 && ratio <= 1.0);
+    assert!(force.normalize().dot(&cannonball.normalize()) > 1.0 - 1e-12);
+
+    // The drag remains linear in Cd
+    let (_, grad) = drag_plate.dual_eom(&sc).unwrap();
+    assert!((grad.column(7) - force / sc.drag.cd).norm() < 1e-12 * force.norm());
+
+    // Turned edge on, the plate has no drag
+    let edge_on = MacroModel::new(vec![Plate::new(Vector3::y(), 2.0, 0.0, 0.0).unwrap()])
+        .with_pointing(VelocityPointing::new());
+    let force = drag.with_macro_model(Arc::new(edge_on)).eom(&sc).unwrap();
+    assert!(force.norm() < 0.1 * cannonball.norm());
+}
+
+#[test]
+fn albedo_earth() {
+    let cosm = Cosm::de438_gmat();
+    let eme2k = cosm.frame("EME2000");
+
+    let dt = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+    let srp = SolarPressure::default(eme2k, cosm.clone());
+    let albedo = AlbedoPressure::earth(cosm.clone());
+    println!("{albedo}");
+
+    // In LEO on the day side, the Earth radiation pressure is a sizable fraction of the direct SRP
+    let sun = cosm.celestial_state(Bodies::Sun.ephem_path(), dt, eme2k, LightTimeCalc::None);
+    let sun_unit = sun.radius() / sun.rmag_km();
+    let leo = Orbit::cartesian(
+        7_000.0 * sun_unit[0],
+        7_000.0 * sun_unit[1],
+        7_000.0 * sun_unit[2],
+        0.0,
+        0.0,
+        0.0,
+        dt,
+        eme2k,
+    );
+    let sc = Spacecraft::from_srp_defaults(leo, 500.0, 2.0);
+    let direct = srp.eom(&sc).unwrap();
+    let earth = albedo.eom(&sc).unwrap();
+    let ratio = earth.norm() / direct.norm();
+    println!(
+        "LEO: albedo + IR = {:.3e} kN ({:.1}% of SRP)",
+        earth.norm(),
+        ratio * 1e2
+    );
+    assert!((0.1..0.5).contains(&ratio));
+    // And pushes the spacecraft away from the Earth
+    assert!(earth.dot(&sun_unit) > 0.99 * earth.norm());
+
+    // More rings converge to the same force
+    let fine = albedo.with_rings(8).eom(&sc).unwrap();
+    assert!((fine - earth).norm() < 0.05 * fine.norm());
+
+    // The partials with respect to Cr is the force for a unit Cr
+    let (dual_force, grad) = albedo.dual_eom(&sc).unwrap();
+    assert_eq!(dual_force, earth);
+    assert!((grad.column(6) * sc.srp.cr - earth).norm() < 1e-12 * earth.norm());
+
+    // At GNSS altitudes, the effect is much smaller
+    let gnss = Orbit::keplerian(29_600.0, 1e-3, 56.0, 10.0, 20.0, 30.0, dt, eme2k);
+    let sc_gnss = Spacecraft::from_srp_defaults(gnss, 700.0, 10.0);
+    let ratio_gnss = albedo.eom(&sc_gnss).unwrap().norm() / srp.eom(&sc_gnss).unwrap().norm();
+    println!("GNSS: albedo + IR is {:.2}% of SRP", ratio_gnss * 1e2);
+    assert!(ratio_gnss < 0.05 && ratio_gnss > 0.0);
+
+    // The Moon only emits from its sunlit side
+    let moon_j2k = cosm.frame("Moon J2000");
+    let lunar = Orbit::keplerian(1_838.0, 1e-3, 90.0, 10.0, 20.0, 30.0, dt, moon_j2k);
+    let sc_lunar = Spacecraft::from_srp_defaults(lunar, 500.0, 2.0);
+    let moon_albedo = AlbedoPressure::moon(cosm.clone());
+    println!("{moon_albedo}");
+    let moon_force = moon_albedo.eom(&sc_lunar).unwrap();
+    println!("Moon: albedo + IR = {:.3e} kN", moon_force.norm());
+    assert!(moon_force.norm() > 0.0);
+
+    let sc_dyn = SpacecraftDynamics::from_models(OrbitalDynamics::two_body(), vec![srp, albedo]);
+    let final_state = Propagator::default(sc_dyn)
+        .with(sc_gnss)
+        .for_duration(Unit::Hour * 6)
+        .unwrap();
+    println!("{final_state}");
+}
@@ -4,7 +4,7 @@ extern crate pretty_env_logger;
 use nyx::cosmic::{Bodies, Cosm, Orbit, Spacecraft};
 use nyx::dynamics::orbital::OrbitalDynamics;
 use nyx::dynamics::spacecraft::{SolarPressure, SpacecraftDynamics};
-use nyx::linalg::{Matrix2, Matrix6, Vector2, Vector6};
+use nyx::linalg::{Const, Matrix2, Matrix6, OMatrix, OVector, Vector2, Vector6};
 use nyx::md::trajectory::ExportCfg;
 use nyx::md::{Event, StateParameter};
 use nyx::od::noise::GaussMarkov;
@@ -231,3 +231,132 @@ fn od_val_sc_mb_srp_reals_duals_models() {
     assert!(delta.rmag_km() < 1e-9, "More than 1 micrometer error");
     assert!(delta.vmag_km_s() < 1e-9, "More than 1 micrometer/s error");
 }
+
+#[allow(clippy::identity_op)]
+#[test]
+fn od_val_sc_srp_estimate_cr() {
+    /*
+     * This tests that the coefficient of reflectivity can be solved for from range and Doppler measurements of a GEO spacecraft.
+     * The truth spacecraft has a Cr of 1.5 but the filter starts with the default Cr of 1.8. The partials of the SRP with respect to
+     * Cr are propagated in the STM, which is how the measurements can correct the Cr estimate.
+     **/
+    let _ = pretty_env_logger::try_init();
+
+    let cosm = Cosm::de438();
+
+    let iau_earth = cosm.frame("IAU Earth");
+    let elevation_mask = 0.0;
+    let dss65_madrid = GroundStation::dss65_madrid(
+        elevation_mask,
+        GaussMarkov::ZERO,
+        GaussMarkov::ZERO,
+        iau_earth,
+    );
+    let dss34_canberra = GroundStation::dss34_canberra(
+        elevation_mask,
+        GaussMarkov::ZERO,
+        GaussMarkov::ZERO,
+        iau_earth,
+    );
+    let dss13_goldstone = GroundStation::dss13_goldstone(
+        elevation_mask,
+        GaussMarkov::ZERO,
+        GaussMarkov::ZERO,
+        iau_earth,
+    );
+
+    let mut configs = HashMap::new();
+    for station in [&dss65_madrid, &dss34_canberra, &dss13_goldstone] {
+        configs.insert(
+            station.name.clone(),
+            TrkConfig::from_sample_rate(10.minutes()),
+        );
+    }
+
+    let all_stations = vec![dss65_madrid, dss34_canberra, dss13_goldstone];
+
+    let prop_time = 3 * Unit::Day;
+    let opts = PropOpts::with_fixed_step(1 * Unit::Minute);
+
+    let eme2k = cosm.frame("EME2000");
+    let epoch = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+    let initial_state = Orbit::keplerian(42164.0, 1e-4, 0.1, 80.0, 40.0, 0.0, epoch, eme2k);
+
+    let dry_mass_kg = 100.0;
+    let sc_area = 10.0;
+    let true_cr = 1.5;
+
+    let sc_dynamics = SpacecraftDynamics::from_model(
+        OrbitalDynamics::two_body(),
+        SolarPressure::default(eme2k, cosm.clone()),
+    );
+
+    let setup = Propagator::new::<RK4Fixed>(sc_dynamics, opts);
+
+    let sc_truth =
+        Spacecraft::from_srp_defaults(initial_state, dry_mass_kg, sc_area).with_cr(true_cr);
+    let (_, traj) = setup
+        .with(sc_truth)
+        .for_duration_with_traj(prop_time)
+        .unwrap();
+
+    let mut arc_sim = TrackingArcSim::with_seed(all_stations, traj.clone(), configs, 0).unwrap();
+    arc_sim.disallow_overlap();
+
+    let arc = arc_sim.generate_measurements(cosm.clone()).unwrap();
+
+    // The filter starts with the default Cr and solves for it
+    let sc_init_est = Spacecraft::from_srp_defaults(initial_state, dry_mass_kg, sc_area).with_stm();
+    let init_cr = sc_init_est.srp.cr;
+    let prop_est = setup.with(sc_init_est);
+
+    let covar_radius_km = 1.0e-3_f64.powi(2);
+    let covar_velocity_km_s = 1.0e-6_f64.powi(2);
+    let init_covar = OMatrix::<f64, Const<9>, Const<9>>::from_diagonal(
+        &OVector::<f64, Const<9>>::from_column_slice(&[
+            covar_radius_km,
+            covar_radius_km,
+            covar_radius_km,
+            covar_velocity_km_s,
+            covar_velocity_km_s,
+            covar_velocity_km_s,
+            0.5_f64.powi(2),
+            1e-12,
+            1e-12,
+        ]),
+    );
+
+    let initial_estimate = KfEstimate::from_covar(sc_init_est, init_covar);
+
+    let measurement_noise =
+        Matrix2::from_diagonal(&Vector2::new(15e-3_f64.powi(2), 1e-5_f64.powi(2)));
+
+    let ckf = KF::no_snc(initial_estimate, measurement_noise);
+
+    let mut odp = ODProcess::ckf(prop_est, ckf, None, cosm);
+
+    odp.process_arc::<GroundStation>(&arc).unwrap();
+
+    let est = odp.estimates.last().unwrap();
+    let est_cr = est.state().srp.cr;
+    println!("Cr: initial = {init_cr}\testimated = {est_cr}\ttrue = {true_cr}");
+    println!("Cr 1-sigma = {:e}", est.covar[(6, 6)].sqrt());
+
+    assert!(
+        (est_cr - true_cr).abs() < 0.01,
+        "Cr did not converge: {est_cr} instead of {true_cr}"
+    );
+    assert!(
+        est.covar[(6, 6)].sqrt() < 0.05,
+        "Cr covariance did not shrink"
+    );
+
+    let truth = traj.at(est.epoch()).unwrap();
+    let delta = est.state().orbit - truth.orbit;
+    println!(
+        "RMAG error = {:.2e} m\tVMAG error = {:.3e} mm/s",
+        delta.rmag_km() * 1e3,
+        delta.vmag_km_s() * 1e6
+    );
+    assert!(delta.rmag_km() < 1e-2, "More than 10 meters error");
+}
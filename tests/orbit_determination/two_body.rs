@@ -1,17 +1,20 @@
 extern crate nyx_space as nyx;
 extern crate pretty_env_logger;
 
+use arrow::array::Float64Array;
 use nyx::cosmic::{Cosm, Orbit};
 use nyx::dynamics::orbital::OrbitalDynamics;
 use nyx::dynamics::sph_harmonics::Harmonics;
 use nyx::io::ConfigRepr;
 use nyx::io::{gravity::*, ExportCfg};
-use nyx::linalg::{Matrix2, Matrix6, Vector2, Vector6};
+use nyx::linalg::{Matrix2, Matrix3, Matrix6, Vector2, Vector6};
 use nyx::od::noise::GaussMarkov;
 use nyx::od::prelude::*;
 use nyx::propagators::{PropOpts, Propagator, RK4Fixed};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
 use std::collections::HashMap;
 use std::env;
+use std::fs::File;
 use std::path::PathBuf;
 
 #[allow(clippy::identity_op)]
@@ -449,7 +452,49 @@ fn od_tb_val_ckf_fixed_step_perfect_stations() {
         .iter()
         .collect();
 
-    odp.to_parquet(path, ExportCfg::default()).unwrap();
+    let path = odp.to_parquet(path, ExportCfg::default()).unwrap();
+
+    // Check the exported RIC covariance against the position covariance rotated by hand
+    let batch = ParquetRecordBatchReaderBuilder::try_new(File::open(path).unwrap())
+        .unwrap()
+        .build()
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap();
+    let ric_hdrs = [["XX", "XY", "XZ"], ["XY", "YY", "YZ"], ["XZ", "YZ", "ZZ"]];
+    for (row, est) in odp
+        .estimates
+        .iter()
+        .enumerate()
+        .take(batch.num_rows())
+        .step_by(100)
+    {
+        let orbit = est.state();
+        let r_hat = orbit.radius() / orbit.rmag_km();
+        let c_hat = orbit.hvec() / orbit.hmag_km2_s();
+        let i_hat = c_hat.cross(&r_hat);
+        let dcm = Matrix3::from_rows(&[r_hat.transpose(), i_hat.transpose(), c_hat.transpose()]);
+        let expected = dcm * est.covar.fixed_view::<3, 3>(0, 0) * dcm.transpose();
+
+        for i in 0..3 {
+            for j in 0..3 {
+                let exported = batch
+                    .column_by_name(&format!("Covariance {} (RIC)", ric_hdrs[i][j]))
+                    .unwrap()
+                    .as_any()
+                    .downcast_ref::<Float64Array>()
+                    .unwrap()
+                    .value(row);
+                assert!(
+                    (exported - expected[(i, j)]).abs() < 1e-8 * expected.norm(),
+                    "RIC covariance [{i}, {j}] @ {}: {exported:e} != {:e}",
+                    est.epoch(),
+                    expected[(i, j)]
+                );
+            }
+        }
+    }
 
     // Check that we have as many estimates as steps taken by the propagator.
     // Note that this test cannot work when using a variable step propagator in that same setup.
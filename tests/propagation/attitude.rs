@@ -0,0 +1,142 @@
+extern crate nyx_space as nyx;
+
+use nalgebra::UnitQuaternion;
+use nyx::cosmic::{Attitude, Cosm, Orbit, Spacecraft};
+use nyx::dynamics::{
+    AttitudeDynamics, Drag, GravityGradient, OrbitalDynamics, PressureTorque, SpacecraftDynamics,
+    TorqueModel,
+};
+use nyx::linalg::{Matrix3, Vector3};
+use nyx::propagators::{PropOpts, Propagator};
+use nyx::time::{Epoch, Unit};
+use nyx::State;
+use std::f64::consts::PI;
+
+#[test]
+fn torque_free_attitude() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2023, 3, 1);
+    let orbit = Orbit::keplerian(7000.0, 1e-3, 51.6, 45.0, 30.0, 0.0, epoch, eme2k);
+
+    let att_dyn =
+        AttitudeDynamics::from_principal_moments(Vector3::new(100.0, 200.0, 300.0)).unwrap();
+    let init_att = Attitude::new(
+        UnitQuaternion::from_scaled_axis(Vector3::new(0.3, -0.2, 0.5)),
+        Vector3::new(0.05, 0.1, 0.02),
+    );
+    let init = Spacecraft::from_srp_defaults(orbit, 100.0, 1.0).with_attitude(init_att);
+
+    // Rigid body attitude dynamics alone: the orbit is not propagated
+    let final_sc = Propagator::rk89(att_dyn.clone(), PropOpts::with_fixed_step(1 * Unit::Second))
+        .with(init)
+        .for_duration(10 * Unit::Minute)
+        .unwrap();
+    assert_eq!(final_sc.orbit.radius(), orbit.radius());
+
+    let final_att = final_sc.attitude.unwrap();
+    println!("{init_att}\n{final_att}");
+    let h0 = att_dyn.angular_momentum(&init_att);
+    let hf = att_dyn.angular_momentum(&final_att);
+    assert!((hf - h0).norm() / h0.norm() < 1e-10, "{h0} != {hf}");
+    let e0 = att_dyn.rotational_energy(&init_att);
+    let ef = att_dyn.rotational_energy(&final_att);
+    assert!((ef - e0).abs() / e0 < 1e-10, "{e0} != {ef}");
+    assert!((final_att.quaternion().norm() - 1.0).abs() < 1e-12);
+
+    // Without attitude dynamics, the spacecraft rotates at constant angular velocity
+    let spin = Vector3::new(0.0, 0.0, 2.0 * PI / 600.0);
+    let spinning = Spacecraft::from_srp_defaults(orbit, 100.0, 1.0)
+        .with_attitude(Attitude::new(UnitQuaternion::identity(), spin));
+    let (final_sc, traj) =
+        Propagator::default(SpacecraftDynamics::new(OrbitalDynamics::two_body()))
+            .with(spinning)
+            .for_duration_with_traj(10 * Unit::Minute)
+            .unwrap();
+    let final_att = final_sc.attitude.unwrap();
+    assert_eq!(final_att.omega_rad_s, spin);
+    // One full revolution brings the body back to its initial orientation
+    assert!(final_att.q.angle_to(&UnitQuaternion::identity()) < 1e-6);
+
+    // The attitude is interpolated in the trajectory
+    let mid = traj
+        .at(epoch + 2.5 * Unit::Minute)
+        .unwrap()
+        .attitude
+        .unwrap();
+    let expected = UnitQuaternion::from_scaled_axis(spin * 150.0);
+    assert!(mid.q.angle_to(&expected) < 1e-6, "{mid}");
+
+    // And the orbit is not affected by the attitude
+    let final_orbit = Propagator::default(SpacecraftDynamics::new(OrbitalDynamics::two_body()))
+        .with(Spacecraft::from_srp_defaults(orbit, 100.0, 1.0))
+        .for_duration(10 * Unit::Minute)
+        .unwrap();
+    assert_eq!(final_orbit.orbit, final_sc.orbit);
+    assert_eq!(final_sc.epoch(), final_orbit.epoch());
+    assert_eq!(mid.omega_rad_s, spin);
+}
+
+#[test]
+fn attitude_torques() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2023, 3, 1);
+    // Along the X axis of the inertial frame
+    let orbit = Orbit::keplerian(7000.0, 0.0, 0.0, 0.0, 0.0, 0.0, epoch, eme2k);
+    let inertia = Matrix3::from_diagonal(&Vector3::new(100.0, 200.0, 300.0));
+
+    // Principal axes aligned with the nadir direction have no gravity gradient torque
+    let sc = Spacecraft::from_srp_defaults(orbit, 100.0, 1.0).with_attitude(Attitude::identity());
+    let gg = GravityGradient::new();
+    assert!(gg.eom(&sc, &inertia).unwrap().norm() < 1e-18);
+
+    // Otherwise it brings the axis of minimum inertia back towards the nadir
+    let tilt = 30.0_f64.to_radians();
+    let tilted = sc.with_attitude(Attitude::new(
+        UnitQuaternion::from_scaled_axis(Vector3::z() * tilt),
+        Vector3::zeros(),
+    ));
+    let torque = gg.eom(&tilted, &inertia).unwrap();
+    let expected =
+        3.0 * eme2k.gm() / 7000.0_f64.powi(3) * (200.0 - 100.0) * tilt.sin() * tilt.cos();
+    println!("gravity gradient: {torque} N m");
+    assert!((torque[2].abs() - expected).abs() < 1e-15);
+    assert!(torque[2] < 0.0);
+    assert!(torque[0].abs() < 1e-18 && torque[1].abs() < 1e-18);
+
+    // Drag applied off the center of mass
+    let drag = Drag::std_atm1976(cosm);
+    let cp = Vector3::new(0.5, 0.0, 0.0);
+    let drag_torque = PressureTorque::new(drag, cp);
+    let low = Spacecraft::from_drag_defaults(
+        Orbit::keplerian(6578.0, 0.0, 0.0, 0.0, 0.0, 0.0, epoch, eme2k),
+        100.0,
+        1.0,
+    )
+    .with_attitude(Attitude::identity());
+    let torque = drag_torque.eom(&low, &inertia).unwrap();
+    println!("{drag_torque}: {torque} N m");
+    assert!(torque.norm() > 0.0);
+    assert!(torque.dot(&cp).abs() < 1e-18);
+
+    // Torques require an attitude and the inertia must be positive definite
+    assert!(gg
+        .eom(&Spacecraft::from_srp_defaults(orbit, 100.0, 1.0), &inertia)
+        .is_err());
+    assert!(AttitudeDynamics::new(Matrix3::from_diagonal(&Vector3::new(1.0, -1.0, 1.0))).is_err());
+
+    // Gravity gradient in a full propagation: the torque oscillates the body around the nadir
+    let att_dyn = AttitudeDynamics::new(inertia).unwrap().with_model(gg);
+    let dynamics = SpacecraftDynamics::new(OrbitalDynamics::two_body())
+        .with_attitude_dynamics(att_dyn.clone());
+    println!("{dynamics}");
+    let final_sc = Propagator::default(dynamics)
+        .with(tilted)
+        .for_duration(10 * Unit::Minute)
+        .unwrap();
+    let final_att = final_sc.attitude.unwrap();
+    println!("{final_att}");
+    assert!(final_att.omega_rad_s.norm() > 0.0);
+    assert!((final_att.quaternion().norm() - 1.0).abs() < 1e-12);
+}
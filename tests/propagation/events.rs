@@ -41,11 +41,11 @@ fn event_tracker_true_anomaly() {
     }
 
     // Find all eclipses!
-    let e_loc = EclipseLocator {
-        light_source: cosm.frame("Sun J2000"),
-        shadow_bodies: vec![cosm.frame("EME2000")],
-        cosm: cosm.clone(),
-    };
+    let e_loc = EclipseLocator::new(
+        cosm.frame("Sun J2000"),
+        vec![cosm.frame("EME2000")],
+        cosm.clone(),
+    );
 
     // Adding this print to confirm that the penumbra calculation continuously increases and then decreases.
     let mut e_state = EclipseState::Umbra;
@@ -0,0 +1,131 @@
+extern crate nyx_space as nyx;
+
+use nyx::cosmic::eclipse::{line_of_sight, EclipseLocator, EclipseState, ShadowBoundary};
+use nyx::cosmic::{Cosm, Orbit};
+use nyx::dynamics::OrbitalDynamics;
+use nyx::md::trajectory::{intervals_to_parquet, ExportCfg};
+use nyx::md::EventEvaluator;
+use nyx::od::GroundStation;
+use nyx::propagators::Propagator;
+use nyx::time::{Epoch, Unit};
+use parquet::file::reader::{FileReader, SerializedFileReader};
+use std::fs::File;
+use std::path::PathBuf;
+
+#[test]
+fn traj_interval_report() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let iau_earth = cosm.frame("IAU Earth");
+
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2023, 3, 1);
+    let leo = Orbit::keplerian(7000.0, 1e-3, 70.0, 45.0, 30.0, 0.0, epoch, eme2k);
+    let (_, traj) = Propagator::default(OrbitalDynamics::two_body())
+        .with(leo)
+        .for_duration_with_traj(1 * Unit::Day)
+        .unwrap();
+
+    let step = 10 * Unit::Second;
+
+    // Station visibility
+    let station = GroundStation::from_point(
+        "Madrid".to_string(),
+        40.427_222,
+        4.250_556,
+        0.834_939,
+        iau_earth,
+    );
+    let station = GroundStation {
+        elevation_mask_deg: 5.0,
+        ..station
+    };
+    let visibility_event = station.to_visibility_event(cosm.clone());
+    let visibility = traj.find_intervals(&visibility_event, step).unwrap();
+    println!("{visibility}");
+    assert_eq!(visibility.name, "Madrid visibility");
+    assert!(visibility.len() >= 2);
+    for pass in visibility.intervals() {
+        for epoch in [pass.start, pass.end] {
+            let state = traj.at(epoch).unwrap();
+            assert!(visibility_event.eval(&state).abs() < 1e-6);
+        }
+        let mid = traj.at(pass.start + pass.duration() * 0.5).unwrap();
+        assert!(visibility_event.eval(&mid) > 0.0);
+        let before = traj.at(pass.start - 1 * Unit::Second).unwrap();
+        assert!(visibility_event.eval(&before) < 0.0);
+    }
+
+    // Eclipses
+    let e_loc = EclipseLocator::new(cosm.frame("Sun J2000"), vec![eme2k], cosm.clone());
+    let penumbra = e_loc
+        .shadow_intervals(&traj, ShadowBoundary::Penumbra, step)
+        .unwrap();
+    let umbra = e_loc
+        .shadow_intervals(&traj, ShadowBoundary::Umbra, step)
+        .unwrap();
+    assert_eq!(penumbra.len(), umbra.len());
+    // The umbra is within the penumbra
+    assert_eq!(umbra.intersection(&penumbra).intervals(), umbra.intervals());
+    let penumbra_only = penumbra.difference(&umbra);
+    assert_eq!(penumbra_only.len(), 2 * penumbra.len());
+    assert_eq!(
+        penumbra_only.total_duration() + umbra.total_duration(),
+        penumbra.total_duration()
+    );
+    for arc in penumbra_only.intervals() {
+        let mid = traj.at(arc.start + arc.duration() * 0.5).unwrap();
+        assert!(matches!(e_loc.compute(&mid), EclipseState::Penumbra(_)));
+    }
+
+    // Occultations of the Moon by the Earth, seen from the spacecraft
+    let occultation = EclipseLocator::new(cosm.frame("Moon J2000"), vec![eme2k], cosm.clone())
+        .shadow_intervals(&traj, ShadowBoundary::Umbra, step)
+        .unwrap()
+        .with_name("Moon occultation".to_string());
+    assert!(!occultation.is_empty());
+    for arc in occultation.intervals() {
+        let mid = traj.at(arc.start + arc.duration() * 0.5).unwrap();
+        let moon = cosm.celestial_state(
+            &cosm.frame("Moon J2000").ephem_path(),
+            mid.epoch,
+            eme2k,
+            nyx::cosmic::LightTimeCalc::None,
+        );
+        assert_eq!(
+            line_of_sight(&mid, &moon, eme2k, &cosm),
+            EclipseState::Umbra
+        );
+    }
+
+    // The passes in sunlight are the complement of the eclipses within the passes
+    let sunlit_passes = visibility.intersection(&penumbra.complement());
+    assert_eq!(
+        sunlit_passes.intervals(),
+        visibility.difference(&penumbra).intervals()
+    );
+    assert_eq!(
+        sunlit_passes.total_duration() + visibility.intersection(&penumbra).total_duration(),
+        visibility.total_duration()
+    );
+    assert!(sunlit_passes
+        .intervals()
+        .iter()
+        .all(|pass| !penumbra.contains(pass.start + pass.duration() * 0.5)));
+
+    // Export the report
+    let path: PathBuf = [
+        env!("CARGO_MANIFEST_DIR"),
+        "output_data",
+        "intervals.parquet",
+    ]
+    .iter()
+    .collect();
+    let sets = [&visibility, &penumbra_only, &umbra, &occultation];
+    let path = intervals_to_parquet(&sets, path, ExportCfg::default()).unwrap();
+    let reader = SerializedFileReader::new(File::open(path).unwrap()).unwrap();
+    let expected_rows: usize = sets.iter().map(|set| set.len()).sum();
+    assert_eq!(
+        reader.metadata().file_metadata().num_rows() as usize,
+        expected_rows
+    );
+}
@@ -1,4 +1,6 @@
+mod attitude;
 mod events;
+mod intervals;
 mod propagators;
 mod stm;
 mod stopcond;
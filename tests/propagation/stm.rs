@@ -1,7 +1,7 @@
 extern crate nyx_space as nyx;
 use nyx::cosmic::{Bodies, Cosm, Orbit, Spacecraft};
 use nyx::dynamics::orbital::OrbitalDynamics;
-use nyx::linalg::{Const, Matrix6, OVector};
+use nyx::linalg::{Const, Matrix6, OVector, Vector6};
 use nyx::propagators::*;
 use nyx::time::{Epoch, Unit};
 use nyx::State;
@@ -339,11 +339,11 @@ fn sc_set_unset_static() {
     let mut init_sc = Spacecraft::from_srp_defaults(init, 100.0, 1.0);
 
     // Change the full vector
-    let data = (0..90).map(|x| x as f64).collect::<Vec<f64>>();
+    let data = (0..97).map(|x| x as f64).collect::<Vec<f64>>();
     init_sc
         .set(
             init.epoch(),
-            &OVector::<f64, Const<90>>::from_column_slice(&data),
+            &OVector::<f64, Const<97>>::from_column_slice(&data),
         )
         .unwrap();
 
@@ -393,3 +393,57 @@ fn sc_and_orbit_stm_chk() {
         "Identical dynamics for Spacecraft and Orbit lead to different STM"
     );
 }
+
+#[test]
+fn stm_vs_finite_differences() {
+    let cosm = Cosm::de438_gmat();
+    let eme2k = cosm.frame("EME2000");
+    let epoch = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+
+    let init = Orbit::keplerian(8000.0, 0.2, 10.0, 5.0, 25.0, 0.0, epoch, eme2k);
+    let duration = 2 * Unit::Hour;
+    let opts = PropOpts::with_fixed_step(30 * Unit::Second);
+    let orbital_dyn = OrbitalDynamics::point_masses(&[Bodies::Luna, Bodies::Sun], cosm);
+
+    let prop = Propagator::new::<RK4Fixed>(orbital_dyn.clone(), opts);
+    let stm = prop
+        .with(init.with_stm())
+        .for_duration(duration)
+        .unwrap()
+        .stm()
+        .unwrap();
+
+    let prop_sc = Propagator::new::<RK4Fixed>(SpacecraftDynamics::new(orbital_dyn), opts);
+    let sc_stm = prop_sc
+        .with(Spacecraft::from_srp_defaults(init.with_stm(), 0.0, 0.0))
+        .for_duration(duration)
+        .unwrap()
+        .stm()
+        .unwrap();
+
+    // Central finite differences over the same two hundred and forty steps
+    let mut stm_fd = Matrix6::<f64>::zeros();
+    for i in 0..6 {
+        let pert = if i < 3 { 1e-3 } else { 1e-6 };
+        let mut delta = Vector6::zeros();
+        delta[i] = pert;
+        let plus = prop.with(init + delta).for_duration(duration).unwrap();
+        let minus = prop.with(init + -delta).for_duration(duration).unwrap();
+        stm_fd.set_column(
+            i,
+            &((plus.to_cartesian_vec() - minus.to_cartesian_vec()) / (2.0 * pert)),
+        );
+    }
+
+    println!("STM = {stm:.6e}\nFD = {stm_fd:.6e}");
+    for i in 0..6 {
+        let err = (stm.column(i) - stm_fd.column(i)).norm() / stm_fd.column(i).norm();
+        assert!(err < 1e-4, "column {i}: relative error of {err:.3e}");
+        let err = (sc_stm.fixed_view::<6, 6>(0, 0).column(i) - stm_fd.column(i)).norm()
+            / stm_fd.column(i).norm();
+        assert!(
+            err < 1e-4,
+            "spacecraft column {i}: relative error of {err:.3e}"
+        );
+    }
+}